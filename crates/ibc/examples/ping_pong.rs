@@ -0,0 +1,404 @@
+//! Writing an IBC application with ibc-rs, end to end.
+//!
+//! The `ping-pong` application answers each `ping` packet it receives with a `pong`
+//! acknowledgement. It is routed on two in-process mock chains, A and B, which are then connected
+//! by a channel opened between its ports, and exchange packets relayed by hand: a `ping` that is
+//! received and acknowledged, and one that times out.
+//!
+//! The mock clients of the chains accept any non-empty proof, so the relayer only has to keep the
+//! clients up to date for the proofs to be checked at the right heights.
+//!
+//! Run with `cargo run --example ping_pong --features mocks`.
+
+use core::time::Duration;
+
+use ibc::core::context::ExecutionContext;
+use ibc::core::ics02_client::msgs::ClientMsg;
+use ibc::core::ics03_connection::connection::{
+    ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+};
+use ibc::core::ics03_connection::version::get_compatible_versions;
+use ibc::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+use ibc::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use ibc::core::ics04_channel::error::Error;
+use ibc::core::ics04_channel::handler::send_packet::send_packet;
+use ibc::core::ics04_channel::handler::ModuleExtras;
+use ibc::core::ics04_channel::msgs::acknowledgement::{
+    Acknowledgement as GenericAcknowledgement, MsgAcknowledgement,
+};
+use ibc::core::ics04_channel::msgs::chan_open_ack::MsgChannelOpenAck;
+use ibc::core::ics04_channel::msgs::chan_open_confirm::MsgChannelOpenConfirm;
+use ibc::core::ics04_channel::msgs::chan_open_init::MsgChannelOpenInit;
+use ibc::core::ics04_channel::msgs::chan_open_try::MsgChannelOpenTry;
+use ibc::core::ics04_channel::msgs::recv_packet::MsgRecvPacket;
+use ibc::core::ics04_channel::msgs::timeout::MsgTimeout;
+use ibc::core::ics04_channel::packet::Packet;
+use ibc::core::ics04_channel::timeout::TimeoutHeight;
+use ibc::core::ics04_channel::Version;
+use ibc::core::ics23_commitment::commitment::CommitmentPrefix;
+use ibc::core::ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId};
+use ibc::core::ics26_routing::context::{
+    Acknowledgement, Module, ModuleId, ModuleOutputBuilder, OnRecvPacketAck, Router, RouterBuilder,
+};
+use ibc::events::IbcEvent;
+use ibc::mock::client_state::client_type as mock_client_type;
+use ibc::mock::context::{MockContext, MockRouterBuilder};
+use ibc::mock::host::HostType;
+use ibc::proofs::Proofs;
+use ibc::relayer::ics18_relayer::context::Ics18Context;
+use ibc::relayer::ics18_relayer::utils::build_client_update_datagram;
+use ibc::signer::Signer;
+use ibc::timestamp::Timestamp;
+use ibc::tx_msg::Msg;
+use ibc::Height;
+
+const PING: &[u8] = b"ping";
+const PONG: &[u8] = b"pong";
+
+fn module_id() -> ModuleId {
+    "pingpong".parse().unwrap()
+}
+
+fn port_id() -> PortId {
+    "pingpong".parse().unwrap()
+}
+
+fn version() -> Version {
+    Version::new("ping-pong-1".to_string())
+}
+
+/// The acknowledgement of a `ping`.
+struct Pong;
+
+impl AsRef<[u8]> for Pong {
+    fn as_ref(&self) -> &[u8] {
+        PONG
+    }
+}
+
+impl Acknowledgement for Pong {}
+
+/// The `ping-pong` application, which counts the packets it handled.
+#[derive(Debug, Default)]
+struct PingPongModule {
+    pings_received: u64,
+    pongs_received: u64,
+    pings_timed_out: u64,
+}
+
+impl Module for PingPongModule {
+    fn on_chan_open_init(
+        &mut self,
+        order: Order,
+        _connection_hops: &[ConnectionId],
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _counterparty: &Counterparty,
+        version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        if order != Order::Unordered {
+            return Err(Error::app_module("ping-pong channels are unordered".into()));
+        }
+        Ok((ModuleExtras::empty(), version.clone()))
+    }
+
+    fn on_chan_open_try(
+        &mut self,
+        order: Order,
+        _connection_hops: &[ConnectionId],
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        if order != Order::Unordered {
+            return Err(Error::app_module("ping-pong channels are unordered".into()));
+        }
+        Ok((ModuleExtras::empty(), counterparty_version.clone()))
+    }
+
+    fn on_recv_packet(
+        &self,
+        _output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        _relayer: &Signer,
+    ) -> OnRecvPacketAck {
+        if packet.data != PING {
+            return OnRecvPacketAck::Failed(Box::new(GenericAcknowledgement::from(
+                b"not a ping".to_vec(),
+            )));
+        }
+
+        // The state of the module is only written once the packet is received for sure.
+        OnRecvPacketAck::Successful(
+            Box::new(Pong),
+            Box::new(|module| {
+                let module = module
+                    .downcast_mut::<PingPongModule>()
+                    .ok_or_else(|| "not the ping-pong module".to_string())?;
+                module.pings_received += 1;
+                Ok(())
+            }),
+        )
+    }
+
+    fn on_acknowledgement_packet(
+        &mut self,
+        _output: &mut ModuleOutputBuilder,
+        _packet: &Packet,
+        acknowledgement: &GenericAcknowledgement,
+        _relayer: &Signer,
+    ) -> Result<(), Error> {
+        if acknowledgement.as_ref() != PONG {
+            return Err(Error::app_module("unexpected acknowledgement".into()));
+        }
+        self.pongs_received += 1;
+        Ok(())
+    }
+
+    fn on_timeout_packet(
+        &mut self,
+        _output: &mut ModuleOutputBuilder,
+        _packet: &Packet,
+        _relayer: &Signer,
+    ) -> Result<(), Error> {
+        self.pings_timed_out += 1;
+        Ok(())
+    }
+
+    fn supported_versions(&self, _port_id: &PortId) -> Vec<Version> {
+        vec![version()]
+    }
+}
+
+/// Sends a `ping` on the channel end `channel_id` of the `ping-pong` port, which times out on
+/// the counterparty chain at `timeout_timestamp`.
+fn send_ping<Ctx>(
+    ctx: &mut Ctx,
+    channel_id: &ChannelId,
+    timeout_timestamp: Timestamp,
+) -> Result<Packet, Error>
+where
+    Ctx: ChannelReader + ChannelKeeper,
+{
+    let channel_end = ctx.channel_end(&port_id(), channel_id)?;
+    let counterparty_channel_id = channel_end
+        .counterparty()
+        .channel_id()
+        .ok_or_else(Error::invalid_counterparty_channel_id)?;
+
+    let packet = Packet::builder()
+        .sequence(ctx.get_next_sequence_send(&port_id(), channel_id)?)
+        .source(port_id(), channel_id.clone())
+        .destination(
+            channel_end.counterparty().port_id().clone(),
+            counterparty_channel_id.clone(),
+        )
+        .data(PING.to_vec())
+        .timeout_height(TimeoutHeight::Never)
+        .timeout_timestamp(timeout_timestamp)
+        .build()?;
+
+    let output = send_packet(ctx, packet.clone())?;
+    ctx.store_packet_result(output.result)?;
+
+    Ok(packet)
+}
+
+/// Returns the `ping-pong` module routed on `ctx`.
+fn ping_pong_module(ctx: &mut MockContext) -> &PingPongModule {
+    ctx.router_mut()
+        .get_route_mut(&module_id())
+        .and_then(|module| module.as_any_mut().downcast_mut::<PingPongModule>())
+        .expect("the ping-pong module is routed")
+}
+
+/// A mock chain, on which the `ping-pong` module is bound to its port, with a client of the
+/// counterparty chain and an open connection to it.
+fn chain(name: &str, client_id: &ClientId, counterparty_client_id: &ClientId) -> MockContext {
+    let router = MockRouterBuilder::default()
+        .add_route(module_id(), PingPongModule::default())
+        .unwrap()
+        .build();
+
+    let connection_end = ConnectionEnd::new(
+        ConnectionState::Open,
+        client_id.clone(),
+        ConnectionCounterparty::new(
+            counterparty_client_id.clone(),
+            Some(ConnectionId::new(0)),
+            CommitmentPrefix::try_from(b"mock".to_vec()).unwrap(),
+        ),
+        get_compatible_versions(),
+        Duration::ZERO,
+    );
+
+    let mut ctx = MockContext::new(
+        ChainId::new(name.to_string(), 1),
+        HostType::Mock,
+        10,
+        Height::new(1, 5).unwrap(),
+    )
+    .with_router(router)
+    .with_client(client_id, Height::new(1, 1).unwrap())
+    .with_connection(ConnectionId::new(0), connection_end);
+    ctx.scope_port_to_module(port_id(), module_id());
+    ctx
+}
+
+/// Updates the client `client_id` of `src` on `dst` to the latest height of `src`, and returns
+/// the proofs of the state of `src` at that height, which messages to `dst` carry.
+fn update_client(src: &MockContext, dst: &mut MockContext, client_id: &ClientId) -> Proofs {
+    let header = src.query_latest_header().unwrap();
+    let msg = match build_client_update_datagram(dst, client_id, header.as_ref()).unwrap() {
+        ClientMsg::UpdateClient(msg) => msg,
+        msg => panic!("unexpected client message {:?}", msg),
+    };
+    dst.send(vec![msg.to_any()]).unwrap();
+
+    // The mock clients accept any non-empty proof.
+    Proofs::new(
+        vec![1].try_into().unwrap(),
+        None,
+        None,
+        None,
+        src.query_latest_height(),
+    )
+    .unwrap()
+}
+
+fn main() {
+    let client_on_a = ClientId::new(mock_client_type(), 0).unwrap();
+    let client_on_b = ClientId::new(mock_client_type(), 0).unwrap();
+    let mut ctx_a = chain("chain-a", &client_on_a, &client_on_b);
+    let mut ctx_b = chain("chain-b", &client_on_b, &client_on_a);
+    let connection_id = ConnectionId::new(0);
+
+    // Open a channel between the ports of the module, on top of the connection.
+    let events = ctx_a
+        .send(vec![MsgChannelOpenInit::new(
+            port_id(),
+            ChannelEnd::new(
+                State::Init,
+                Order::Unordered,
+                Counterparty::new(port_id(), None),
+                vec![connection_id.clone()],
+                Version::empty(),
+            ),
+            ctx_a.signer(),
+        )
+        .to_any()])
+        .unwrap();
+    let channel_on_a = match &events[..] {
+        [IbcEvent::OpenInitChannel(event)] => event.channel_id().clone(),
+        _ => panic!("unexpected events {:?}", events),
+    };
+
+    let proofs = update_client(&ctx_a, &mut ctx_b, &client_on_b);
+    let events = ctx_b
+        .send(vec![MsgChannelOpenTry::new(
+            port_id(),
+            ChannelEnd::new(
+                State::TryOpen,
+                Order::Unordered,
+                Counterparty::new(port_id(), Some(channel_on_a.clone())),
+                vec![connection_id],
+                Version::empty(),
+            ),
+            version(),
+            proofs,
+            ctx_b.signer(),
+        )
+        .to_any()])
+        .unwrap();
+    let channel_on_b = match &events[..] {
+        [IbcEvent::OpenTryChannel(event)] => event.channel_id().clone(),
+        _ => panic!("unexpected events {:?}", events),
+    };
+
+    let proofs = update_client(&ctx_b, &mut ctx_a, &client_on_a);
+    ctx_a
+        .send(vec![MsgChannelOpenAck::new(
+            port_id(),
+            channel_on_a.clone(),
+            channel_on_b.clone(),
+            version(),
+            proofs,
+            ctx_a.signer(),
+        )
+        .to_any()])
+        .unwrap();
+
+    let proofs = update_client(&ctx_a, &mut ctx_b, &client_on_b);
+    ctx_b
+        .send(vec![MsgChannelOpenConfirm::new(
+            port_id(),
+            channel_on_b.clone(),
+            proofs,
+            ctx_b.signer(),
+        )
+        .to_any()])
+        .unwrap();
+    println!("opened channel {} <-> {}", channel_on_a, channel_on_b);
+
+    // A `ping` received by B, whose `pong` is relayed back to A.
+    let timeout_timestamp = (ChannelReader::host_timestamp(&ctx_b) + Duration::from_secs(3600))
+        .expect("the timeout is representable");
+    let packet = send_ping(&mut ctx_a, &channel_on_a, timeout_timestamp).unwrap();
+    ctx_a.advance_host_chain_height();
+
+    let proofs = update_client(&ctx_a, &mut ctx_b, &client_on_b);
+    let events = ctx_b
+        .send(vec![MsgRecvPacket::new(
+            packet.clone(),
+            proofs,
+            ctx_b.signer(),
+        )
+        .to_any()])
+        .unwrap();
+    let acknowledgement = events
+        .iter()
+        .find_map(|event| match event {
+            IbcEvent::WriteAcknowledgement(event) => Some(event.acknowledgement().clone()),
+            _ => None,
+        })
+        .expect("B acknowledges the ping");
+
+    let proofs = update_client(&ctx_b, &mut ctx_a, &client_on_a);
+    ctx_a
+        .send(vec![MsgAcknowledgement::new(
+            packet,
+            acknowledgement,
+            proofs,
+            ctx_a.signer(),
+        )
+        .to_any()])
+        .unwrap();
+    println!("ping acknowledged with a pong");
+
+    // A `ping` that times out on B before it is relayed, which is then proven to A.
+    let timeout_timestamp = (ChannelReader::host_timestamp(&ctx_b) + Duration::from_secs(10))
+        .expect("the timeout is representable");
+    let packet = send_ping(&mut ctx_a, &channel_on_a, timeout_timestamp).unwrap();
+    ctx_a.advance_host_chain_height();
+    ctx_b.advance_host_chain(1, Duration::from_secs(60));
+
+    let proofs = update_client(&ctx_b, &mut ctx_a, &client_on_a);
+    let next_sequence_recv = packet.sequence;
+    ctx_a
+        .send(vec![MsgTimeout::new(
+            packet,
+            next_sequence_recv,
+            proofs,
+            ctx_a.signer(),
+        )
+        .to_any()])
+        .unwrap();
+    println!("ping timed out");
+
+    let module_a = ping_pong_module(&mut ctx_a);
+    assert_eq!(module_a.pongs_received, 1);
+    assert_eq!(module_a.pings_timed_out, 1);
+    let module_b = ping_pong_module(&mut ctx_b);
+    assert_eq!(module_b.pings_received, 1);
+}
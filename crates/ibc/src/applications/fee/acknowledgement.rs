@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use super::error::Error;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::prelude::*;
+
+/// The acknowledgement written on fee-enabled channels: the acknowledgement of the wrapped
+/// application, along with the address to pay the receive fee to on the sending chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncentivizedAcknowledgement {
+    #[serde(with = "crate::serializers::serde_base64")]
+    pub app_acknowledgement: Vec<u8>,
+    /// The counterparty payee registered by the relayer that delivered the packet, if any.
+    pub forward_relayer_address: String,
+    pub underlying_app_success: bool,
+}
+
+impl IncentivizedAcknowledgement {
+    /// Returns the acknowledgement of the wrapped application.
+    pub fn app_acknowledgement(&self) -> GenericAcknowledgement {
+        self.app_acknowledgement.clone().into()
+    }
+}
+
+impl TryFrom<&GenericAcknowledgement> for IncentivizedAcknowledgement {
+    type Error = Error;
+
+    fn try_from(ack: &GenericAcknowledgement) -> Result<Self, Self::Error> {
+        serde_json::from_slice(ack.as_ref()).map_err(|_| Error::ack_deserialization())
+    }
+}
+
+impl From<IncentivizedAcknowledgement> for GenericAcknowledgement {
+    fn from(ack: IncentivizedAcknowledgement) -> Self {
+        serde_json::to_vec(&ack)
+            .expect("IncentivizedAcknowledgement's infallible Serialize impl failed")
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ack_json() {
+        let ack = IncentivizedAcknowledgement {
+            app_acknowledgement: br#"{"result":"AQ=="}"#.to_vec(),
+            forward_relayer_address: "cosmos1payee".to_string(),
+            underlying_app_success: true,
+        };
+
+        let generic = GenericAcknowledgement::from(ack.clone());
+        assert_eq!(
+            generic.as_ref(),
+            br#"{"app_acknowledgement":"eyJyZXN1bHQiOiJBUT09In0=","forward_relayer_address":"cosmos1payee","underlying_app_success":true}"#
+        );
+        assert_eq!(
+            IncentivizedAcknowledgement::try_from(&generic).unwrap(),
+            ack
+        );
+        assert!(IncentivizedAcknowledgement::try_from(&ack.app_acknowledgement()).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Defines the required context for the ICS29 fee middleware: the registry of fee-enabled
+//! channels, the payee addresses registered by relayers and the fees escrowed for packets.
+
+use crate::applications::fee::error::Error;
+use crate::applications::fee::packet_fee::PacketFee;
+use crate::applications::transfer::PrefixedCoin;
+use crate::core::ics04_channel::packet::PacketId;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
+use crate::signer::Signer;
+
+pub trait FeeReader {
+    /// Returns `true` if the fee middleware is enabled on the given channel.
+    fn is_fee_enabled(&self, port_id: &PortId, channel_id: &ChannelId) -> bool;
+
+    /// Returns all the channels on which the fee middleware is enabled.
+    fn fee_enabled_channels(&self) -> Vec<(PortId, ChannelId)>;
+
+    /// Returns the payee address registered by `relayer` for the given channel, if any.
+    fn payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer>;
+
+    /// Returns the counterparty payee address registered by `relayer` for the given channel, if
+    /// any.
+    fn counterparty_payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer>;
+
+    /// Returns the fees escrowed for the given packet, in the order they were paid.
+    fn packet_fees(&self, packet_id: &PacketId) -> Vec<PacketFee>;
+
+    /// Returns the fees escrowed for all the packets in flight on the given channel.
+    fn channel_packet_fees(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Vec<(PacketId, Vec<PacketFee>)>;
+
+    /// Returns the address that should receive the fees earned by `relayer` on the given channel,
+    /// i.e. its registered payee or, if none was registered, the relayer itself.
+    fn fee_recipient(&self, relayer: &Signer, channel_id: &ChannelId) -> Signer {
+        self.payee(relayer, channel_id)
+            .unwrap_or_else(|| relayer.clone())
+    }
+
+    /// Returns the fee-enabled channels bound to `port_id`.
+    fn fee_enabled_channels_on_port(&self, port_id: &PortId) -> Vec<ChannelId> {
+        self.fee_enabled_channels()
+            .into_iter()
+            .filter(|(port, _)| port == port_id)
+            .map(|(_, channel_id)| channel_id)
+            .collect()
+    }
+}
+
+pub trait FeeKeeper {
+    /// Marks the given channel as fee-enabled.
+    fn set_fee_enabled(&mut self, port_id: PortId, channel_id: ChannelId);
+
+    /// Removes the given channel from the fee-enabled channels registry.
+    fn delete_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId);
+
+    /// Stores the payee address registered by `relayer` for the given channel.
+    fn store_payee(&mut self, relayer: Signer, channel_id: ChannelId, payee: Signer);
+
+    /// Stores the counterparty payee address registered by `relayer` for the given channel.
+    fn store_counterparty_payee(
+        &mut self,
+        relayer: Signer,
+        channel_id: ChannelId,
+        counterparty_payee: Signer,
+    );
+
+    /// Stores the fees escrowed for the given packet, replacing the previous ones.
+    fn store_packet_fees(&mut self, packet_id: PacketId, packet_fees: Vec<PacketFee>);
+
+    /// Removes the fees escrowed for the given packet, once they were distributed.
+    fn delete_packet_fees(&mut self, packet_id: &PacketId);
+}
+
+/// Moves the fees paid for packets in and out of the fee escrow account.
+pub trait FeeEscrowKeeper {
+    /// Moves `coins` from the account of `payer` to the fee escrow account.
+    fn escrow_fee(&mut self, payer: &Signer, coins: &[PrefixedCoin]) -> Result<(), Error>;
+
+    /// Moves `coins` from the fee escrow account to the account of `receiver`, i.e. pays out
+    /// or refunds a fee.
+    fn pay_fee(&mut self, receiver: &Signer, coins: &[PrefixedCoin]) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies which the ICS29 middleware requires to be able to process
+/// payee registrations and to escrow and distribute packet fees.
+pub trait FeeContext: FeeReader + FeeKeeper + FeeEscrowKeeper {}
@@ -0,0 +1,78 @@
+use flex_error::{define_error, TraceError};
+use ibc_proto::protobuf::Error as TendermintProtoError;
+
+use crate::applications::transfer::error::Error as Ics20Error;
+use crate::core::ics04_channel::error as channel_error;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::error::ValidationError;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
+use crate::signer::{Signer, SignerError};
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        UnknownMessageTypeUrl
+            { url: String }
+            | e | { format_args!("unrecognized ICS-29 fee message type URL {0}", e.url) },
+
+        DecodeRawMsg
+            [ TraceError<TendermintProtoError> ]
+            | _ | { "error decoding raw msg" },
+
+        InvalidPortId
+            { context: String }
+            [ ValidationError ]
+            | _ | { "invalid port identifier" },
+
+        InvalidChannelId
+            { context: String }
+            [ ValidationError ]
+            | _ | { "invalid channel identifier" },
+
+        Signer
+            [ SignerError ]
+            | _ | { "failed to parse signer" },
+
+        RelayerIsPayee
+            { relayer: Signer }
+            | e | { format_args!("relayer address {0} and payee address must not be equal", e.relayer) },
+
+        FeeNotEnabled
+            { port_id: PortId, channel_id: ChannelId }
+            | e | { format_args!("fee module is not enabled for channel {0} on port {1}", e.channel_id, e.port_id) },
+
+        Ics04Channel
+            [ channel_error::Error ]
+            | _ | { "Ics04 channel error" },
+
+        InvalidFeeCoin
+            [ Ics20Error ]
+            | _ | { "invalid fee coin" },
+
+        EmptyFee
+            | _ | { "fee must contain at least one coin" },
+
+        RelayersNotSupported
+            | _ | { "paying the fees of specific relayers is not supported, the relayers list must be empty" },
+
+        Escrow
+            { reason: String }
+            | e | { format_args!("failed to move fees from or to the fee escrow: {0}", e.reason) },
+
+        FeeVersion
+            { version: Version }
+            | e | { format_args!("invalid fee middleware version {0}", e.version) },
+
+        MetadataDeserialization
+            { version: Version }
+            | e | { format_args!("failed to deserialize fee version metadata from {0}", e.version) },
+
+        AckDeserialization
+            | _ | { "failed to deserialize incentivized acknowledgement" },
+
+        InvalidForwardRelayer
+            [ SignerError ]
+            | _ | { "invalid forward relayer address" },
+    }
+}
@@ -0,0 +1,134 @@
+//! Defines all events emitted by the ICS29 fee middleware.
+
+use crate::applications::fee::packet_fee::Fee;
+use crate::applications::fee::MODULE_ID_STR;
+use crate::applications::transfer::PrefixedCoin;
+use crate::core::ics04_channel::packet::PacketId;
+use crate::core::ics24_host::identifier::ChannelId;
+use crate::events::ModuleEvent;
+use crate::prelude::*;
+use crate::signer::Signer;
+
+const EVENT_TYPE_REGISTER_PAYEE: &str = "register_payee";
+const EVENT_TYPE_REGISTER_COUNTERPARTY_PAYEE: &str = "register_counterparty_payee";
+const EVENT_TYPE_INCENTIVIZED_PACKET: &str = "incentivized_ibc_packet";
+const EVENT_TYPE_DISTRIBUTE_FEE: &str = "distribute_fee";
+
+pub enum Event {
+    RegisterPayee(RegisterPayeeEvent),
+    RegisterCounterpartyPayee(RegisterCounterpartyPayeeEvent),
+    IncentivizedPacket(IncentivizedPacketEvent),
+    DistributeFee(DistributeFeeEvent),
+}
+
+pub struct RegisterPayeeEvent {
+    pub relayer: Signer,
+    pub payee: Signer,
+    pub channel_id: ChannelId,
+}
+
+impl From<RegisterPayeeEvent> for ModuleEvent {
+    fn from(ev: RegisterPayeeEvent) -> Self {
+        let RegisterPayeeEvent {
+            relayer,
+            payee,
+            channel_id,
+        } = ev;
+        Self {
+            kind: EVENT_TYPE_REGISTER_PAYEE.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("relayer", relayer).into(),
+                ("payee", payee).into(),
+                ("channel_id", channel_id).into(),
+            ],
+        }
+    }
+}
+
+pub struct RegisterCounterpartyPayeeEvent {
+    pub relayer: Signer,
+    pub counterparty_payee: Signer,
+    pub channel_id: ChannelId,
+}
+
+impl From<RegisterCounterpartyPayeeEvent> for ModuleEvent {
+    fn from(ev: RegisterCounterpartyPayeeEvent) -> Self {
+        let RegisterCounterpartyPayeeEvent {
+            relayer,
+            counterparty_payee,
+            channel_id,
+        } = ev;
+        Self {
+            kind: EVENT_TYPE_REGISTER_COUNTERPARTY_PAYEE.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("relayer", relayer).into(),
+                ("counterparty_payee", counterparty_payee).into(),
+                ("channel_id", channel_id).into(),
+            ],
+        }
+    }
+}
+
+/// Formats coins as a comma-separated list, e.g. `10stake,5uatom`.
+fn coins_to_string(coins: &[PrefixedCoin]) -> String {
+    coins
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub struct IncentivizedPacketEvent {
+    pub packet_id: PacketId,
+    pub fee: Fee,
+}
+
+impl From<IncentivizedPacketEvent> for ModuleEvent {
+    fn from(ev: IncentivizedPacketEvent) -> Self {
+        let IncentivizedPacketEvent { packet_id, fee } = ev;
+        Self {
+            kind: EVENT_TYPE_INCENTIVIZED_PACKET.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("port_id", packet_id.port_id).into(),
+                ("channel_id", packet_id.channel_id).into(),
+                ("packet_sequence", packet_id.sequence).into(),
+                ("recv_fee", coins_to_string(&fee.recv_fee)).into(),
+                ("ack_fee", coins_to_string(&fee.ack_fee)).into(),
+                ("timeout_fee", coins_to_string(&fee.timeout_fee)).into(),
+            ],
+        }
+    }
+}
+
+pub struct DistributeFeeEvent {
+    pub receiver: Signer,
+    pub fee: Vec<PrefixedCoin>,
+}
+
+impl From<DistributeFeeEvent> for ModuleEvent {
+    fn from(ev: DistributeFeeEvent) -> Self {
+        let DistributeFeeEvent { receiver, fee } = ev;
+        Self {
+            kind: EVENT_TYPE_DISTRIBUTE_FEE.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("receiver", receiver).into(),
+                ("fee", coins_to_string(&fee)).into(),
+            ],
+        }
+    }
+}
+
+impl From<Event> for ModuleEvent {
+    fn from(ev: Event) -> Self {
+        match ev {
+            Event::RegisterPayee(ev) => ev.into(),
+            Event::RegisterCounterpartyPayee(ev) => ev.into(),
+            Event::IncentivizedPacket(ev) => ev.into(),
+            Event::DistributeFee(ev) => ev.into(),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! This module implements the processing logic for the ICS29 messages, and the distribution of
+//! the fees escrowed for packets once they were acknowledged or timed out.
+
+use crate::applications::fee::context::FeeContext;
+use crate::applications::fee::error::Error;
+use crate::applications::fee::events::{
+    DistributeFeeEvent, IncentivizedPacketEvent, RegisterCounterpartyPayeeEvent, RegisterPayeeEvent,
+};
+use crate::applications::fee::msgs::pay_packet_fee::MsgPayPacketFee;
+use crate::applications::fee::msgs::register_counterparty_payee::MsgRegisterCounterpartyPayee;
+use crate::applications::fee::msgs::register_payee::MsgRegisterPayee;
+use crate::applications::fee::packet_fee::PacketFee;
+use crate::applications::transfer::PrefixedCoin;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::packet::PacketId;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::core::ics26_routing::context::ModuleOutputBuilder;
+use crate::events::ModuleEvent;
+use crate::handler::HandlerOutputBuilder;
+use crate::prelude::*;
+use crate::signer::Signer;
+
+/// Registers the payee of `msg.relayer` on a fee-enabled channel.
+pub fn register_payee<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutputBuilder<()>,
+    msg: MsgRegisterPayee,
+) -> Result<(), Error>
+where
+    Ctx: FeeContext,
+{
+    check_fee_enabled(ctx, &msg.port_id, &msg.channel_id)?;
+
+    ctx.store_payee(
+        msg.relayer.clone(),
+        msg.channel_id.clone(),
+        msg.payee.clone(),
+    );
+
+    output.log(format!(
+        "registered payee address {} for relayer {} on channel {}",
+        msg.payee, msg.relayer, msg.channel_id
+    ));
+    output.emit(
+        ModuleEvent::from(RegisterPayeeEvent {
+            relayer: msg.relayer,
+            payee: msg.payee,
+            channel_id: msg.channel_id,
+        })
+        .into(),
+    );
+
+    Ok(())
+}
+
+/// Registers the counterparty payee of `msg.relayer` on a fee-enabled channel.
+pub fn register_counterparty_payee<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutputBuilder<()>,
+    msg: MsgRegisterCounterpartyPayee,
+) -> Result<(), Error>
+where
+    Ctx: FeeContext,
+{
+    check_fee_enabled(ctx, &msg.port_id, &msg.channel_id)?;
+
+    ctx.store_counterparty_payee(
+        msg.relayer.clone(),
+        msg.channel_id.clone(),
+        msg.counterparty_payee.clone(),
+    );
+
+    output.log(format!(
+        "registered counterparty payee address {} for relayer {} on channel {}",
+        msg.counterparty_payee, msg.relayer, msg.channel_id
+    ));
+    output.emit(
+        ModuleEvent::from(RegisterCounterpartyPayeeEvent {
+            relayer: msg.relayer,
+            counterparty_payee: msg.counterparty_payee,
+            channel_id: msg.channel_id,
+        })
+        .into(),
+    );
+
+    Ok(())
+}
+
+/// Escrows the fee of `msg` for the next packet sent on its channel.
+pub fn pay_packet_fee<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutputBuilder<()>,
+    msg: MsgPayPacketFee,
+) -> Result<(), Error>
+where
+    Ctx: FeeContext + ChannelReader,
+{
+    check_fee_enabled(ctx, &msg.source_port_id, &msg.source_channel_id)?;
+
+    let sequence = ctx
+        .get_next_sequence_send(&msg.source_port_id, &msg.source_channel_id)
+        .map_err(Error::ics04_channel)?;
+    let packet_id = PacketId::new(
+        msg.source_port_id.clone(),
+        msg.source_channel_id.clone(),
+        sequence,
+    );
+
+    let packet_fee = msg.packet_fee();
+    ctx.escrow_fee(&packet_fee.refund_address, &packet_fee.fee.total()?)?;
+
+    let mut packet_fees = ctx.packet_fees(&packet_id);
+    packet_fees.push(packet_fee);
+    ctx.store_packet_fees(packet_id.clone(), packet_fees);
+
+    output.log(format!(
+        "escrowed fee paid by {} for packet {}",
+        msg.signer, packet_id
+    ));
+    output.emit(
+        ModuleEvent::from(IncentivizedPacketEvent {
+            packet_id,
+            fee: msg.fee,
+        })
+        .into(),
+    );
+
+    Ok(())
+}
+
+/// Distributes the fees escrowed for an acknowledged packet: the receive fee goes to the
+/// `forward_relayer` reported in the acknowledgement, the acknowledgement fee to the payee of the
+/// `relayer` that delivered the acknowledgement, and the timeout fee back to the payer. Without
+/// a forward relayer, the receive fee is refunded as well.
+pub fn distribute_packet_fees<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut ModuleOutputBuilder,
+    packet_id: &PacketId,
+    forward_relayer: Option<&Signer>,
+    relayer: &Signer,
+) -> Result<(), Error>
+where
+    Ctx: FeeContext,
+{
+    let ack_fee_recipient = ctx.fee_recipient(relayer, &packet_id.channel_id);
+
+    for PacketFee {
+        fee,
+        refund_address,
+        ..
+    } in ctx.packet_fees(packet_id)
+    {
+        let recv_fee_recipient = forward_relayer.unwrap_or(&refund_address);
+        distribute_fee(ctx, output, recv_fee_recipient, &fee.recv_fee)?;
+        distribute_fee(ctx, output, &ack_fee_recipient, &fee.ack_fee)?;
+        distribute_fee(ctx, output, &refund_address, &fee.timeout_fee)?;
+    }
+    ctx.delete_packet_fees(packet_id);
+
+    Ok(())
+}
+
+/// Distributes the fees escrowed for a timed out packet: the timeout fee goes to the payee of the
+/// `relayer` that delivered the timeout, and the receive and acknowledgement fees back to the
+/// payer.
+pub fn distribute_packet_fees_on_timeout<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut ModuleOutputBuilder,
+    packet_id: &PacketId,
+    relayer: &Signer,
+) -> Result<(), Error>
+where
+    Ctx: FeeContext,
+{
+    let timeout_fee_recipient = ctx.fee_recipient(relayer, &packet_id.channel_id);
+
+    for PacketFee {
+        fee,
+        refund_address,
+        ..
+    } in ctx.packet_fees(packet_id)
+    {
+        distribute_fee(ctx, output, &refund_address, &fee.recv_fee)?;
+        distribute_fee(ctx, output, &refund_address, &fee.ack_fee)?;
+        distribute_fee(ctx, output, &timeout_fee_recipient, &fee.timeout_fee)?;
+    }
+    ctx.delete_packet_fees(packet_id);
+
+    Ok(())
+}
+
+/// Refunds the fees escrowed for all the packets in flight on a closing channel to their payers.
+pub fn refund_fees_on_channel_closure<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut ModuleOutputBuilder,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> Result<(), Error>
+where
+    Ctx: FeeContext,
+{
+    for (packet_id, packet_fees) in ctx.channel_packet_fees(port_id, channel_id) {
+        for PacketFee {
+            fee,
+            refund_address,
+            ..
+        } in packet_fees
+        {
+            distribute_fee(ctx, output, &refund_address, &fee.total()?)?;
+        }
+        ctx.delete_packet_fees(&packet_id);
+    }
+
+    Ok(())
+}
+
+fn distribute_fee<Ctx: FeeContext>(
+    ctx: &mut Ctx,
+    output: &mut ModuleOutputBuilder,
+    receiver: &Signer,
+    fee: &[PrefixedCoin],
+) -> Result<(), Error> {
+    if fee.is_empty() {
+        return Ok(());
+    }
+
+    ctx.pay_fee(receiver, fee)?;
+    output.emit(
+        DistributeFeeEvent {
+            receiver: receiver.clone(),
+            fee: fee.to_vec(),
+        }
+        .into(),
+    );
+
+    Ok(())
+}
+
+fn check_fee_enabled<Ctx: FeeContext>(
+    ctx: &Ctx,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> Result<(), Error> {
+    if ctx.is_fee_enabled(port_id, channel_id) {
+        Ok(())
+    } else {
+        Err(Error::fee_not_enabled(port_id.clone(), channel_id.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::applications::fee::context::{FeeKeeper, FeeReader};
+    use crate::applications::fee::msgs::register_payee::test_util::get_dummy_raw_msg_register_payee;
+    use crate::events::IbcEvent;
+    use crate::handler::HandlerOutput;
+    use crate::mock::context::MockContext;
+
+    #[test]
+    fn register_payee_on_fee_enabled_channel() {
+        let msg = MsgRegisterPayee::try_from(get_dummy_raw_msg_register_payee()).unwrap();
+        let mut ctx = MockContext::default();
+
+        // The channel is not fee-enabled yet
+        let mut output = HandlerOutputBuilder::new();
+        assert!(register_payee(&mut ctx, &mut output, msg.clone()).is_err());
+        assert_eq!(
+            ctx.fee_recipient(&msg.relayer, &msg.channel_id),
+            msg.relayer
+        );
+
+        ctx.set_fee_enabled(msg.port_id.clone(), msg.channel_id.clone());
+        assert_eq!(
+            ctx.fee_enabled_channels_on_port(&msg.port_id),
+            vec![msg.channel_id.clone()]
+        );
+
+        register_payee(&mut ctx, &mut output, msg.clone()).unwrap();
+        assert_eq!(ctx.fee_recipient(&msg.relayer, &msg.channel_id), msg.payee);
+
+        let HandlerOutput { events, .. } = output.with_result(());
+        let kinds: Vec<String> = events
+            .into_iter()
+            .map(|event| match event {
+                IbcEvent::AppModule(e) => e.kind,
+                _ => panic!("unexpected event: {:?}", event),
+            })
+            .collect();
+        assert_eq!(kinds, vec!["register_payee"]);
+    }
+
+    #[test]
+    fn register_counterparty_payee_on_fee_enabled_channel() {
+        let raw = get_dummy_raw_msg_register_payee();
+        let msg = MsgRegisterCounterpartyPayee {
+            port_id: raw.port_id.parse().unwrap(),
+            channel_id: raw.channel_id.parse().unwrap(),
+            relayer: raw.relayer.parse().unwrap(),
+            counterparty_payee: "osmo1payee".parse().unwrap(),
+        };
+        let mut ctx = MockContext::default();
+        ctx.set_fee_enabled(msg.port_id.clone(), msg.channel_id.clone());
+
+        let mut output = HandlerOutputBuilder::new();
+        register_counterparty_payee(&mut ctx, &mut output, msg.clone()).unwrap();
+        assert_eq!(
+            ctx.counterparty_payee(&msg.relayer, &msg.channel_id),
+            Some(msg.counterparty_payee)
+        );
+    }
+}
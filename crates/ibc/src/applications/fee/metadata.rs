@@ -0,0 +1,78 @@
+use core::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::Error;
+use super::VERSION;
+use crate::core::ics04_channel::Version;
+use crate::prelude::*;
+
+/// The version of a fee-enabled channel: the version of the fee middleware along with the version
+/// of the wrapped application, encoded as JSON in the channel version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub fee_version: String,
+    pub app_version: String,
+}
+
+impl Metadata {
+    /// Returns the metadata of a channel on which the current fee middleware wraps an application
+    /// of version `app_version`.
+    pub fn new(app_version: &Version) -> Self {
+        Self {
+            fee_version: VERSION.to_string(),
+            app_version: app_version.to_string(),
+        }
+    }
+
+    /// Returns the version of the wrapped application.
+    pub fn app_version(&self) -> Version {
+        Version::new(self.app_version.clone())
+    }
+}
+
+/// Fails if the version is not fee metadata, or if its fee version is not supported.
+impl TryFrom<&Version> for Metadata {
+    type Error = Error;
+
+    fn try_from(version: &Version) -> Result<Self, Self::Error> {
+        let metadata: Self = serde_json::from_str(version.as_str())
+            .map_err(|_| Error::metadata_deserialization(version.clone()))?;
+        if metadata.fee_version != VERSION {
+            return Err(Error::fee_version(version.clone()));
+        }
+
+        Ok(metadata)
+    }
+}
+
+impl From<Metadata> for Version {
+    fn from(metadata: Metadata) -> Self {
+        Version::new(
+            serde_json::to_string(&metadata).expect("Metadata's infallible Serialize impl failed"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_version_round_trip() {
+        let metadata = Metadata::new(&Version::ics20());
+
+        let version = Version::from(metadata.clone());
+        assert_eq!(
+            version.as_str(),
+            r#"{"fee_version":"ics29-1","app_version":"ics20-1"}"#
+        );
+        assert_eq!(Metadata::try_from(&version).unwrap(), metadata);
+        assert_eq!(metadata.app_version(), Version::ics20());
+
+        assert!(Metadata::try_from(&Version::ics20()).is_err());
+        let unsupported =
+            Version::new(r#"{"fee_version":"ics29-2","app_version":"ics20-1"}"#.to_string());
+        assert!(Metadata::try_from(&unsupported).is_err());
+    }
+}
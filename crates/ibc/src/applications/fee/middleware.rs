@@ -0,0 +1,668 @@
+//! The ICS29 middleware, which wraps the `Module` of an application to incentivize the relaying
+//! of its packets on fee-enabled channels.
+
+use super::acknowledgement::IncentivizedAcknowledgement;
+use super::context::FeeContext;
+use super::error::{Error, ErrorDetail};
+use super::handler::{
+    distribute_packet_fees, distribute_packet_fees_on_timeout, refund_fees_on_channel_closure,
+};
+use super::metadata::Metadata;
+use crate::core::ics04_channel::channel::{Counterparty, Order};
+use crate::core::ics04_channel::error::Error as ChannelError;
+use crate::core::ics04_channel::handler::ModuleExtras;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics04_channel::{negotiate_version, verify_counterparty_version, Version};
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::{
+    Acknowledgement, Module, ModuleOutputBuilder, OnRecvPacketAck,
+};
+use crate::core::ics26_routing::middleware::forward_recv_packet_ack;
+use crate::core::ics26_routing::reentrancy::NestedDispatchGuard;
+use crate::handler::HandlerOutput;
+use crate::prelude::*;
+use crate::signer::Signer;
+
+/// Middleware wrapping the `Module` of an application, which negotiates the fee version on the
+/// channels of the application and distributes the fees escrowed for their packets.
+///
+/// A channel is fee-enabled if its version is fee [`Metadata`]; on other channels, the middleware
+/// passes all callbacks through. On fee-enabled channels, it wraps the acknowledgements of the
+/// application in an [`IncentivizedAcknowledgement`]. Asynchronous acknowledgements are written
+/// by the application and are not wrapped.
+#[derive(Debug)]
+pub struct FeeMiddleware<M, C> {
+    app: M,
+    ctx: C,
+}
+
+impl<M, C> FeeMiddleware<M, C> {
+    pub fn new(app: M, ctx: C) -> Self {
+        Self { app, ctx }
+    }
+
+    pub fn app(&self) -> &M {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut M {
+        &mut self.app
+    }
+
+    pub fn ctx(&self) -> &C {
+        &self.ctx
+    }
+
+    pub fn ctx_mut(&mut self) -> &mut C {
+        &mut self.ctx
+    }
+}
+
+impl<M, C: FeeContext> FeeMiddleware<M, C> {
+    /// Refunds the fees escrowed for the packets in flight on a closing fee-enabled channel and
+    /// forgets that the channel is fee-enabled, adding the refund events to `extras`.
+    fn close_fee_channel(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        mut extras: ModuleExtras,
+    ) -> Result<ModuleExtras, ChannelError> {
+        if !self.ctx.is_fee_enabled(port_id, channel_id) {
+            return Ok(extras);
+        }
+
+        let mut output = ModuleOutputBuilder::new();
+        refund_fees_on_channel_closure(&mut self.ctx, &mut output, port_id, channel_id)
+            .map_err(to_channel_error)?;
+        self.ctx.delete_fee_enabled(port_id, channel_id);
+
+        let HandlerOutput { events, log, .. } = output.with_result(());
+        extras.events.extend(events);
+        extras.log.extend(log);
+        Ok(extras)
+    }
+}
+
+/// Returns the fee metadata of `version`, or `None` if `version` is not fee metadata, in which
+/// case the channel is not fee-enabled.
+fn fee_metadata(version: &Version) -> Result<Option<Metadata>, ChannelError> {
+    match Metadata::try_from(version) {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(e) => match e.detail() {
+            ErrorDetail::MetadataDeserialization(_) => Ok(None),
+            _ => Err(to_channel_error(e)),
+        },
+    }
+}
+
+fn to_channel_error(e: Error) -> ChannelError {
+    ChannelError::app_module(e.to_string())
+}
+
+fn incentivize(
+    ack: Box<dyn Acknowledgement>,
+    forward_relayer_address: String,
+    underlying_app_success: bool,
+) -> Box<dyn Acknowledgement> {
+    let ack = IncentivizedAcknowledgement {
+        app_acknowledgement: ack.as_ref().as_ref().to_vec(),
+        forward_relayer_address,
+        underlying_app_success,
+    };
+    Box::new(GenericAcknowledgement::from(ack))
+}
+
+/// Incentivizes the acknowledgement of a packet received on a fee-enabled channel, paying the
+/// receive fee to `forward_relayer_address`.
+fn incentivize_recv_packet_ack(
+    ack: OnRecvPacketAck,
+    forward_relayer_address: String,
+) -> OnRecvPacketAck {
+    match ack {
+        OnRecvPacketAck::Successful(ack, write_fn) => {
+            OnRecvPacketAck::Successful(incentivize(ack, forward_relayer_address, true), write_fn)
+        }
+        OnRecvPacketAck::Failed(ack) => {
+            OnRecvPacketAck::Failed(incentivize(ack, forward_relayer_address, false))
+        }
+        ack @ OnRecvPacketAck::Nil(_) => ack,
+        OnRecvPacketAck::Deferred(ack_fn) => {
+            OnRecvPacketAck::Deferred(Box::new(move |module, output| {
+                let ack = ack_fn(module, output)?;
+                Ok(incentivize_recv_packet_ack(ack, forward_relayer_address))
+            }))
+        }
+    }
+}
+
+impl<M, C> Module for FeeMiddleware<M, C>
+where
+    M: Module,
+    C: FeeContext + Send + Sync + 'static,
+{
+    fn on_chan_open_init(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        version: &Version,
+    ) -> Result<(ModuleExtras, Version), ChannelError> {
+        // The core cannot negotiate the version of the wrapped application, as it only sees the
+        // fee metadata.
+        let supported_versions = self.app.supported_versions(port_id);
+        let metadata = match fee_metadata(version)? {
+            Some(metadata) => metadata,
+            None => {
+                return self.app.on_chan_open_init(
+                    order,
+                    connection_hops,
+                    port_id,
+                    channel_id,
+                    counterparty,
+                    &negotiate_version(port_id, &supported_versions, version)?,
+                )
+            }
+        };
+
+        let (extras, app_version) = self.app.on_chan_open_init(
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            &negotiate_version(port_id, &supported_versions, &metadata.app_version())?,
+        )?;
+        self.ctx
+            .set_fee_enabled(port_id.clone(), channel_id.clone());
+
+        Ok((extras, Metadata::new(&app_version).into()))
+    }
+
+    fn on_chan_open_try(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), ChannelError> {
+        let supported_versions = self.app.supported_versions(port_id);
+        let metadata = match fee_metadata(counterparty_version)? {
+            Some(metadata) => metadata,
+            None => {
+                verify_counterparty_version(port_id, &supported_versions, counterparty_version)?;
+                return self.app.on_chan_open_try(
+                    order,
+                    connection_hops,
+                    port_id,
+                    channel_id,
+                    counterparty,
+                    counterparty_version,
+                );
+            }
+        };
+
+        verify_counterparty_version(port_id, &supported_versions, &metadata.app_version())?;
+        let (extras, app_version) = self.app.on_chan_open_try(
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            &metadata.app_version(),
+        )?;
+        self.ctx
+            .set_fee_enabled(port_id.clone(), channel_id.clone());
+
+        Ok((extras, Metadata::new(&app_version).into()))
+    }
+
+    fn on_chan_open_ack(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty_version: &Version,
+    ) -> Result<ModuleExtras, ChannelError> {
+        let supported_versions = self.app.supported_versions(port_id);
+        if !self.ctx.is_fee_enabled(port_id, channel_id) {
+            verify_counterparty_version(port_id, &supported_versions, counterparty_version)?;
+            return self
+                .app
+                .on_chan_open_ack(port_id, channel_id, counterparty_version);
+        }
+
+        let metadata = Metadata::try_from(counterparty_version).map_err(to_channel_error)?;
+        verify_counterparty_version(port_id, &supported_versions, &metadata.app_version())?;
+        self.app
+            .on_chan_open_ack(port_id, channel_id, &metadata.app_version())
+    }
+
+    fn on_chan_open_confirm(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app.on_chan_open_confirm(port_id, channel_id)
+    }
+
+    fn on_chan_close_init(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        let extras = self.app.on_chan_close_init(port_id, channel_id)?;
+        self.close_fee_channel(port_id, channel_id, extras)
+    }
+
+    fn on_chan_close_confirm(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        let extras = self.app.on_chan_close_confirm(port_id, channel_id)?;
+        self.close_fee_channel(port_id, channel_id, extras)
+    }
+
+    fn on_recv_packet(
+        &self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> OnRecvPacketAck {
+        let ack = self.app.on_recv_packet(output, packet, relayer);
+        let fee_enabled = self
+            .ctx
+            .is_fee_enabled(&packet.destination_port, &packet.destination_channel);
+
+        // The counterparty pays the receive fee to the payee the relayer registered there.
+        let forward_relayer = || {
+            self.ctx
+                .counterparty_payee(relayer, &packet.destination_channel)
+                .map(|payee| payee.to_string())
+                .unwrap_or_default()
+        };
+
+        let ack = forward_recv_packet_ack(ack, Self::app_mut);
+        if fee_enabled {
+            incentivize_recv_packet_ack(ack, forward_relayer())
+        } else {
+            ack
+        }
+    }
+
+    fn on_acknowledgement_packet(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        acknowledgement: &GenericAcknowledgement,
+        relayer: &Signer,
+    ) -> Result<(), ChannelError> {
+        if !self
+            .ctx
+            .is_fee_enabled(&packet.source_port, &packet.source_channel)
+        {
+            return self
+                .app
+                .on_acknowledgement_packet(output, packet, acknowledgement, relayer);
+        }
+
+        let ack =
+            IncentivizedAcknowledgement::try_from(acknowledgement).map_err(to_channel_error)?;
+        // An invalid forward relayer address gets the receive fee refunded.
+        let forward_relayer = ack.forward_relayer_address.parse::<Signer>().ok();
+        distribute_packet_fees(
+            &mut self.ctx,
+            output,
+            &packet.id(),
+            forward_relayer.as_ref(),
+            relayer,
+        )
+        .map_err(to_channel_error)?;
+
+        self.app
+            .on_acknowledgement_packet(output, packet, &ack.app_acknowledgement(), relayer)
+    }
+
+    fn on_timeout_packet(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> Result<(), ChannelError> {
+        if self
+            .ctx
+            .is_fee_enabled(&packet.source_port, &packet.source_channel)
+        {
+            distribute_packet_fees_on_timeout(&mut self.ctx, output, &packet.id(), relayer)
+                .map_err(to_channel_error)?;
+        }
+
+        self.app.on_timeout_packet(output, packet, relayer)
+    }
+    fn nested_dispatch_guard(&mut self) -> Option<&mut NestedDispatchGuard> {
+        self.app.nested_dispatch_guard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::applications::fee::context::{FeeKeeper, FeeReader};
+    use crate::applications::fee::handler::pay_packet_fee;
+    use crate::applications::fee::msgs::pay_packet_fee::test_util::get_dummy_raw_msg_pay_packet_fee;
+    use crate::applications::fee::msgs::pay_packet_fee::MsgPayPacketFee;
+    use crate::applications::fee::packet_fee::test_util::get_dummy_fee;
+    use crate::core::ics26_routing::context::AsAnyMut;
+    use crate::handler::HandlerOutputBuilder;
+    use crate::mock::context::MockContext;
+
+    #[derive(Debug, Default)]
+    struct DummyApp {
+        acknowledged: Vec<GenericAcknowledgement>,
+    }
+
+    impl Module for DummyApp {
+        fn on_chan_open_init(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            version: &Version,
+        ) -> Result<(ModuleExtras, Version), ChannelError> {
+            Ok((ModuleExtras::empty(), version.clone()))
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            counterparty_version: &Version,
+        ) -> Result<(ModuleExtras, Version), ChannelError> {
+            Ok((ModuleExtras::empty(), counterparty_version.clone()))
+        }
+
+        fn supported_versions(&self, _port_id: &PortId) -> Vec<Version> {
+            vec![Version::ics20()]
+        }
+
+        fn on_recv_packet(
+            &self,
+            _output: &mut ModuleOutputBuilder,
+            _packet: &Packet,
+            _relayer: &Signer,
+        ) -> OnRecvPacketAck {
+            OnRecvPacketAck::Successful(
+                Box::new(GenericAcknowledgement::from(b"ok".to_vec())),
+                Box::new(|_| Ok(())),
+            )
+        }
+
+        fn on_acknowledgement_packet(
+            &mut self,
+            _output: &mut ModuleOutputBuilder,
+            _packet: &Packet,
+            acknowledgement: &GenericAcknowledgement,
+            _relayer: &Signer,
+        ) -> Result<(), ChannelError> {
+            self.acknowledged.push(acknowledgement.clone());
+            Ok(())
+        }
+    }
+
+    fn open_fee_channel(middleware: &mut FeeMiddleware<DummyApp, MockContext>) {
+        let version = Version::from(Metadata::new(&Version::ics20()));
+        let (_, version) = middleware
+            .on_chan_open_init(
+                Order::Unordered,
+                &[ConnectionId::default()],
+                &PortId::transfer(),
+                &ChannelId::default(),
+                &Counterparty::new(PortId::transfer(), None),
+                &version,
+            )
+            .unwrap();
+        assert_eq!(
+            Metadata::try_from(&version).unwrap().app_version(),
+            Version::ics20()
+        );
+    }
+
+    /// Returns a context sharing the store of `ctx`, as the middleware of a host would.
+    fn shared(ctx: &MockContext) -> MockContext {
+        let mut shared = ctx.clone();
+        shared.ibc_store = ctx.ibc_store_share();
+        shared
+    }
+
+    fn get_dummy_packet() -> Packet {
+        Packet::builder()
+            .sequence(1.into())
+            .source(PortId::transfer(), ChannelId::default())
+            .destination(PortId::transfer(), ChannelId::default())
+            .data(b"data".to_vec())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn wrapped_app_versions_are_negotiated() {
+        let mut middleware = FeeMiddleware::new(DummyApp::default(), MockContext::default());
+        let mut open_init = |app_version: &str| {
+            middleware.on_chan_open_init(
+                Order::Unordered,
+                &[ConnectionId::default()],
+                &PortId::transfer(),
+                &ChannelId::default(),
+                &Counterparty::new(PortId::transfer(), None),
+                &Metadata::new(&Version::new(app_version.to_string())).into(),
+            )
+        };
+
+        let (_, version) = open_init("").unwrap();
+        assert_eq!(
+            Metadata::try_from(&version).unwrap().app_version(),
+            Version::ics20()
+        );
+        assert!(open_init("ics20-2").is_err());
+
+        let open_try = middleware.on_chan_open_try(
+            Order::Unordered,
+            &[ConnectionId::default()],
+            &PortId::transfer(),
+            &ChannelId::default(),
+            &Counterparty::new(PortId::transfer(), Some(ChannelId::default())),
+            &Version::new("ics20-2".to_string()),
+        );
+        assert!(open_try.is_err());
+    }
+
+    #[test]
+    fn channels_without_fee_version_are_not_fee_enabled() {
+        let mut middleware = FeeMiddleware::new(DummyApp::default(), MockContext::default());
+
+        let (_, version) = middleware
+            .on_chan_open_try(
+                Order::Unordered,
+                &[ConnectionId::default()],
+                &PortId::transfer(),
+                &ChannelId::default(),
+                &Counterparty::new(PortId::transfer(), Some(ChannelId::default())),
+                &Version::ics20(),
+            )
+            .unwrap();
+        assert_eq!(version, Version::ics20());
+        assert!(middleware.ctx().fee_enabled_channels().is_empty());
+
+        let relayer = "relayer".parse().unwrap();
+        match middleware.on_recv_packet(
+            &mut ModuleOutputBuilder::new(),
+            &get_dummy_packet(),
+            &relayer,
+        ) {
+            OnRecvPacketAck::Successful(ack, _) => assert_eq!(ack.as_ref().as_ref(), b"ok"),
+            _ => panic!("expected a successful acknowledgement"),
+        }
+    }
+
+    #[test]
+    fn recv_packet_ack_is_incentivized() {
+        let mut middleware = FeeMiddleware::new(DummyApp::default(), MockContext::default());
+        open_fee_channel(&mut middleware);
+
+        let relayer: Signer = "relayer".parse().unwrap();
+        middleware.ctx_mut().store_counterparty_payee(
+            relayer.clone(),
+            ChannelId::default(),
+            "cosmos1payee".parse().unwrap(),
+        );
+
+        let ack = match middleware.on_recv_packet(
+            &mut ModuleOutputBuilder::new(),
+            &get_dummy_packet(),
+            &relayer,
+        ) {
+            OnRecvPacketAck::Successful(ack, write_fn) => {
+                write_fn(middleware.as_any_mut()).unwrap();
+                GenericAcknowledgement::from(ack.as_ref().as_ref().to_vec())
+            }
+            _ => panic!("expected a successful acknowledgement"),
+        };
+
+        let ack = IncentivizedAcknowledgement::try_from(&ack).unwrap();
+        assert_eq!(ack.app_acknowledgement, b"ok");
+        assert_eq!(ack.forward_relayer_address, "cosmos1payee");
+        assert!(ack.underlying_app_success);
+    }
+
+    #[test]
+    fn fees_are_distributed_on_acknowledgement() {
+        let mut ctx = MockContext::default().with_send_sequence(
+            PortId::transfer(),
+            ChannelId::default(),
+            1.into(),
+        );
+        let mut middleware = FeeMiddleware::new(DummyApp::default(), shared(&ctx));
+        open_fee_channel(&mut middleware);
+
+        let msg = MsgPayPacketFee::try_from(get_dummy_raw_msg_pay_packet_fee()).unwrap();
+        pay_packet_fee(&mut ctx, &mut HandlerOutputBuilder::new(), msg.clone()).unwrap();
+
+        let packet = get_dummy_packet();
+        assert_eq!(ctx.packet_fees(&packet.id()).len(), 1);
+
+        let relayer: Signer = "relayer".parse().unwrap();
+        ctx.store_payee(
+            relayer.clone(),
+            ChannelId::default(),
+            "cosmos1payee".parse().unwrap(),
+        );
+        let ack = IncentivizedAcknowledgement {
+            app_acknowledgement: b"ok".to_vec(),
+            forward_relayer_address: "cosmos1forward".to_string(),
+            underlying_app_success: true,
+        };
+        middleware
+            .on_acknowledgement_packet(
+                &mut ModuleOutputBuilder::new(),
+                &packet,
+                &ack.into(),
+                &relayer,
+            )
+            .unwrap();
+
+        assert_eq!(
+            middleware.app().acknowledged,
+            vec![GenericAcknowledgement::from(b"ok".to_vec())]
+        );
+        assert!(ctx.packet_fees(&packet.id()).is_empty());
+
+        let fee = get_dummy_fee();
+        let store = ctx.ibc_store.lock().unwrap();
+        assert_eq!(
+            store.fee.escrowed,
+            vec![(msg.signer.clone(), fee.total().unwrap().to_vec())]
+        );
+        assert_eq!(
+            store.fee.paid,
+            vec![
+                ("cosmos1forward".parse().unwrap(), fee.recv_fee.to_vec()),
+                ("cosmos1payee".parse().unwrap(), fee.ack_fee.to_vec()),
+                (msg.signer, fee.timeout_fee.to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fees_are_distributed_on_timeout() {
+        let mut ctx = MockContext::default().with_send_sequence(
+            PortId::transfer(),
+            ChannelId::default(),
+            1.into(),
+        );
+        let mut middleware = FeeMiddleware::new(DummyApp::default(), shared(&ctx));
+        open_fee_channel(&mut middleware);
+
+        let msg = MsgPayPacketFee::try_from(get_dummy_raw_msg_pay_packet_fee()).unwrap();
+        pay_packet_fee(&mut ctx, &mut HandlerOutputBuilder::new(), msg.clone()).unwrap();
+
+        let relayer: Signer = "relayer".parse().unwrap();
+        middleware
+            .on_timeout_packet(
+                &mut ModuleOutputBuilder::new(),
+                &get_dummy_packet(),
+                &relayer,
+            )
+            .unwrap();
+
+        let fee = get_dummy_fee();
+        let store = ctx.ibc_store.lock().unwrap();
+        assert_eq!(
+            store.fee.paid,
+            vec![
+                (msg.signer.clone(), fee.recv_fee.to_vec()),
+                (msg.signer, fee.ack_fee.to_vec()),
+                (relayer, fee.timeout_fee.to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fees_are_refunded_on_channel_closure() {
+        let mut ctx = MockContext::default().with_send_sequence(
+            PortId::transfer(),
+            ChannelId::default(),
+            1.into(),
+        );
+        let mut middleware = FeeMiddleware::new(DummyApp::default(), shared(&ctx));
+        open_fee_channel(&mut middleware);
+
+        let msg = MsgPayPacketFee::try_from(get_dummy_raw_msg_pay_packet_fee()).unwrap();
+        pay_packet_fee(&mut ctx, &mut HandlerOutputBuilder::new(), msg.clone()).unwrap();
+
+        let extras = middleware
+            .on_chan_close_init(&PortId::transfer(), &ChannelId::default())
+            .unwrap();
+        assert_eq!(extras.events.len(), 1);
+
+        let packet = get_dummy_packet();
+        assert!(ctx.packet_fees(&packet.id()).is_empty());
+        assert!(!ctx.is_fee_enabled(&PortId::transfer(), &ChannelId::default()));
+
+        let fee = get_dummy_fee();
+        let store = ctx.ibc_store.lock().unwrap();
+        assert_eq!(
+            store.fee.paid,
+            vec![(msg.signer, fee.total().unwrap().to_vec())]
+        );
+    }
+}
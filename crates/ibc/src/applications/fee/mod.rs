@@ -0,0 +1,36 @@
+//! ICS 29: Fee Payment (relayer incentivisation) middleware.
+//!
+//! The [`FeeMiddleware`](middleware::FeeMiddleware) wraps the `Module` of an application: it
+//! negotiates the fee version on the channels of the application, and distributes the fees
+//! escrowed with `MsgPayPacketFee` to the relayers of their packets, or to the payee addresses
+//! that relayer operators registered.
+//!
+//! Only the identifiers below are stable: the rest of the middleware requires the `unstable`
+//! feature.
+#[cfg(any(test, feature = "unstable"))]
+pub mod acknowledgement;
+#[cfg(any(test, feature = "unstable"))]
+pub mod context;
+#[cfg(any(test, feature = "unstable"))]
+pub mod error;
+#[cfg(any(test, feature = "unstable"))]
+pub mod events;
+#[cfg(any(test, feature = "unstable"))]
+pub mod handler;
+#[cfg(any(test, feature = "unstable"))]
+pub mod metadata;
+#[cfg(any(test, feature = "unstable"))]
+pub mod middleware;
+#[cfg(any(test, feature = "unstable"))]
+pub mod msgs;
+#[cfg(any(test, feature = "unstable"))]
+pub mod packet_fee;
+
+/// Module identifier for the ICS29 middleware.
+pub const MODULE_ID_STR: &str = "feeibc";
+
+/// The port identifier that the ICS29 middleware typically binds with.
+pub const PORT_ID_STR: &str = "feeibc";
+
+/// ICS29 middleware current version.
+pub const VERSION: &str = "ics29-1";
@@ -0,0 +1,3 @@
+pub mod pay_packet_fee;
+pub mod register_counterparty_payee;
+pub mod register_payee;
@@ -0,0 +1,194 @@
+//! Definition of the `MsgPayPacketFee` message, used to incentivize the relaying of the next packet sent on a channel.
+
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::applications::fee::v1::MsgPayPacketFee as RawMsgPayPacketFee;
+use ibc_proto::protobuf::Protobuf;
+
+use crate::applications::fee::error::Error;
+use crate::applications::fee::packet_fee::{Fee, PacketFee};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::signer::Signer;
+use crate::tx_msg::Msg;
+
+pub const TYPE_URL: &str = "/ibc.applications.fee.v1.MsgPayPacketFee";
+
+/// Escrows a fee for the next packet sent on a fee-enabled channel, typically in the same
+/// transaction as the message sending the packet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgPayPacketFee {
+    /// the fee to escrow, and pay out to the relayers of the packet
+    pub fee: Fee,
+    /// the source port of the packet
+    pub source_port_id: PortId,
+    /// the source channel of the packet
+    pub source_channel_id: ChannelId,
+    /// the account paying the fee, and receiving its refunds
+    pub signer: Signer,
+    /// reserved, must be empty
+    pub relayers: Vec<String>,
+}
+
+impl MsgPayPacketFee {
+    /// Returns the fee escrowed for the packet.
+    pub fn packet_fee(&self) -> PacketFee {
+        PacketFee {
+            fee: self.fee.clone(),
+            refund_address: self.signer.clone(),
+            relayers: self.relayers.clone(),
+        }
+    }
+}
+
+impl Msg for MsgPayPacketFee {
+    type ValidationError = Error;
+    type Raw = RawMsgPayPacketFee;
+
+    fn route(&self) -> String {
+        crate::keys::ROUTER_KEY.to_string()
+    }
+
+    fn type_url(&self) -> String {
+        TYPE_URL.to_string()
+    }
+}
+
+impl TryFrom<RawMsgPayPacketFee> for MsgPayPacketFee {
+    type Error = Error;
+
+    fn try_from(raw_msg: RawMsgPayPacketFee) -> Result<Self, Self::Error> {
+        let fee: Fee = raw_msg.fee.unwrap_or_default().try_into()?;
+        if fee.is_empty() {
+            return Err(Error::empty_fee());
+        }
+        if !raw_msg.relayers.is_empty() {
+            return Err(Error::relayers_not_supported());
+        }
+
+        Ok(MsgPayPacketFee {
+            fee,
+            source_port_id: raw_msg
+                .source_port_id
+                .parse()
+                .map_err(|e| Error::invalid_port_id(raw_msg.source_port_id.clone(), e))?,
+            source_channel_id: raw_msg
+                .source_channel_id
+                .parse()
+                .map_err(|e| Error::invalid_channel_id(raw_msg.source_channel_id.clone(), e))?,
+            signer: raw_msg.signer.parse().map_err(Error::signer)?,
+            relayers: raw_msg.relayers,
+        })
+    }
+}
+
+impl From<MsgPayPacketFee> for RawMsgPayPacketFee {
+    fn from(domain_msg: MsgPayPacketFee) -> Self {
+        RawMsgPayPacketFee {
+            fee: Some(domain_msg.fee.into()),
+            source_port_id: domain_msg.source_port_id.to_string(),
+            source_channel_id: domain_msg.source_channel_id.to_string(),
+            signer: domain_msg.signer.to_string(),
+            relayers: domain_msg.relayers,
+        }
+    }
+}
+
+impl Protobuf<RawMsgPayPacketFee> for MsgPayPacketFee {}
+
+impl TryFrom<Any> for MsgPayPacketFee {
+    type Error = Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        match raw.type_url.as_str() {
+            TYPE_URL => MsgPayPacketFee::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+            _ => Err(Error::unknown_message_type_url(raw.type_url)),
+        }
+    }
+}
+
+impl From<MsgPayPacketFee> for Any {
+    fn from(msg: MsgPayPacketFee) -> Self {
+        Self {
+            type_url: TYPE_URL.to_string(),
+            value: msg
+                .encode_vec()
+                .expect("encoding to `Any` from `MsgPayPacketFee`"),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "mocks"))]
+pub mod test_util {
+    use ibc_proto::ibc::applications::fee::v1::MsgPayPacketFee as RawMsgPayPacketFee;
+
+    use crate::applications::fee::packet_fee::test_util::get_dummy_fee;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::prelude::*;
+
+    pub fn get_dummy_raw_msg_pay_packet_fee() -> RawMsgPayPacketFee {
+        RawMsgPayPacketFee {
+            fee: Some(get_dummy_fee().into()),
+            source_port_id: PortId::transfer().to_string(),
+            source_channel_id: ChannelId::default().to_string(),
+            signer: "cosmos1payer".to_string(),
+            relayers: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::get_dummy_raw_msg_pay_packet_fee;
+    use super::*;
+
+    #[test]
+    fn msg_pay_packet_fee_round_trip() {
+        let raw = get_dummy_raw_msg_pay_packet_fee();
+        let msg = MsgPayPacketFee::try_from(raw.clone()).unwrap();
+        assert_eq!(RawMsgPayPacketFee::from(msg.clone()), raw);
+
+        let any = Any::from(msg.clone());
+        assert_eq!(MsgPayPacketFee::try_from(any).unwrap(), msg);
+    }
+
+    #[test]
+    fn msg_pay_packet_fee_validation() {
+        let default_raw = get_dummy_raw_msg_pay_packet_fee();
+
+        let tests: Vec<(&str, RawMsgPayPacketFee)> = vec![
+            (
+                "no fee",
+                RawMsgPayPacketFee {
+                    fee: None,
+                    ..default_raw.clone()
+                },
+            ),
+            (
+                "relayers",
+                RawMsgPayPacketFee {
+                    relayers: vec!["cosmos1relayer".to_string()],
+                    ..default_raw.clone()
+                },
+            ),
+            (
+                "empty signer",
+                RawMsgPayPacketFee {
+                    signer: "".to_string(),
+                    ..default_raw.clone()
+                },
+            ),
+            (
+                "bad channel id",
+                RawMsgPayPacketFee {
+                    source_channel_id: "chan".to_string(),
+                    ..default_raw.clone()
+                },
+            ),
+        ];
+
+        for (name, raw) in tests {
+            assert!(MsgPayPacketFee::try_from(raw).is_err(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Definition of the `MsgRegisterCounterpartyPayee` message, used by relayers to register the counterparty address that receives their fees.
+
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::applications::fee::v1::MsgRegisterCounterpartyPayee as RawMsgRegisterCounterpartyPayee;
+use ibc_proto::protobuf::Protobuf;
+
+use crate::applications::fee::error::Error;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::signer::Signer;
+use crate::tx_msg::Msg;
+
+pub const TYPE_URL: &str = "/ibc.applications.fee.v1.MsgRegisterCounterpartyPayee";
+
+/// Registers the counterparty payee address, which is included in the acknowledgements of packets relayed by the relayer on the counterparty chain and receives the receive fees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgRegisterCounterpartyPayee {
+    /// the port identifier of the fee-enabled channel
+    pub port_id: PortId,
+    /// the fee-enabled channel identifier
+    pub channel_id: ChannelId,
+    /// the relayer address
+    pub relayer: Signer,
+    /// the counterparty payee address
+    pub counterparty_payee: Signer,
+}
+
+impl Msg for MsgRegisterCounterpartyPayee {
+    type ValidationError = Error;
+    type Raw = RawMsgRegisterCounterpartyPayee;
+
+    fn route(&self) -> String {
+        crate::keys::ROUTER_KEY.to_string()
+    }
+
+    fn type_url(&self) -> String {
+        TYPE_URL.to_string()
+    }
+}
+
+impl TryFrom<RawMsgRegisterCounterpartyPayee> for MsgRegisterCounterpartyPayee {
+    type Error = Error;
+
+    fn try_from(raw_msg: RawMsgRegisterCounterpartyPayee) -> Result<Self, Self::Error> {
+        let relayer: Signer = raw_msg.relayer.parse().map_err(Error::signer)?;
+        let counterparty_payee: Signer =
+            raw_msg.counterparty_payee.parse().map_err(Error::signer)?;
+
+        Ok(MsgRegisterCounterpartyPayee {
+            port_id: raw_msg
+                .port_id
+                .parse()
+                .map_err(|e| Error::invalid_port_id(raw_msg.port_id.clone(), e))?,
+            channel_id: raw_msg
+                .channel_id
+                .parse()
+                .map_err(|e| Error::invalid_channel_id(raw_msg.channel_id.clone(), e))?,
+            relayer,
+            counterparty_payee,
+        })
+    }
+}
+
+impl From<MsgRegisterCounterpartyPayee> for RawMsgRegisterCounterpartyPayee {
+    fn from(domain_msg: MsgRegisterCounterpartyPayee) -> Self {
+        RawMsgRegisterCounterpartyPayee {
+            port_id: domain_msg.port_id.to_string(),
+            channel_id: domain_msg.channel_id.to_string(),
+            relayer: domain_msg.relayer.to_string(),
+            counterparty_payee: domain_msg.counterparty_payee.to_string(),
+        }
+    }
+}
+
+impl Protobuf<RawMsgRegisterCounterpartyPayee> for MsgRegisterCounterpartyPayee {}
+
+impl TryFrom<Any> for MsgRegisterCounterpartyPayee {
+    type Error = Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        match raw.type_url.as_str() {
+            TYPE_URL => {
+                MsgRegisterCounterpartyPayee::decode_vec(&raw.value).map_err(Error::decode_raw_msg)
+            }
+            _ => Err(Error::unknown_message_type_url(raw.type_url)),
+        }
+    }
+}
+
+impl From<MsgRegisterCounterpartyPayee> for Any {
+    fn from(msg: MsgRegisterCounterpartyPayee) -> Self {
+        Self {
+            type_url: TYPE_URL.to_string(),
+            value: msg
+                .encode_vec()
+                .expect("encoding to `Any` from `MsgRegisterCounterpartyPayee`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn msg_register_counterparty_payee_round_trip() {
+        let raw = RawMsgRegisterCounterpartyPayee {
+            port_id: PortId::transfer().to_string(),
+            channel_id: ChannelId::default().to_string(),
+            relayer: "cosmos1relayer".to_string(),
+            counterparty_payee: "osmo1payee".to_string(),
+        };
+        let msg = MsgRegisterCounterpartyPayee::try_from(raw.clone()).unwrap();
+        assert_eq!(RawMsgRegisterCounterpartyPayee::from(msg), raw);
+
+        let raw_empty_payee = RawMsgRegisterCounterpartyPayee {
+            counterparty_payee: "".to_string(),
+            ..raw
+        };
+        assert!(MsgRegisterCounterpartyPayee::try_from(raw_empty_payee).is_err());
+    }
+}
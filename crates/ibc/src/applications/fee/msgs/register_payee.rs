@@ -0,0 +1,174 @@
+//! Definition of the `MsgRegisterPayee` message, used by relayers to register the address that receives their fees.
+
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::applications::fee::v1::MsgRegisterPayee as RawMsgRegisterPayee;
+use ibc_proto::protobuf::Protobuf;
+
+use crate::applications::fee::error::Error;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::signer::Signer;
+use crate::tx_msg::Msg;
+
+pub const TYPE_URL: &str = "/ibc.applications.fee.v1.MsgRegisterPayee";
+
+/// Registers a payee address, to which the fees earned by a relayer on a channel are distributed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgRegisterPayee {
+    /// the port identifier of the fee-enabled channel
+    pub port_id: PortId,
+    /// the fee-enabled channel identifier
+    pub channel_id: ChannelId,
+    /// the relayer address
+    pub relayer: Signer,
+    /// the payee address, receiving the fees earned by the relayer
+    pub payee: Signer,
+}
+
+impl Msg for MsgRegisterPayee {
+    type ValidationError = Error;
+    type Raw = RawMsgRegisterPayee;
+
+    fn route(&self) -> String {
+        crate::keys::ROUTER_KEY.to_string()
+    }
+
+    fn type_url(&self) -> String {
+        TYPE_URL.to_string()
+    }
+}
+
+impl TryFrom<RawMsgRegisterPayee> for MsgRegisterPayee {
+    type Error = Error;
+
+    fn try_from(raw_msg: RawMsgRegisterPayee) -> Result<Self, Self::Error> {
+        let relayer: Signer = raw_msg.relayer.parse().map_err(Error::signer)?;
+        let payee: Signer = raw_msg.payee.parse().map_err(Error::signer)?;
+
+        if relayer == payee {
+            return Err(Error::relayer_is_payee(relayer));
+        }
+
+        Ok(MsgRegisterPayee {
+            port_id: raw_msg
+                .port_id
+                .parse()
+                .map_err(|e| Error::invalid_port_id(raw_msg.port_id.clone(), e))?,
+            channel_id: raw_msg
+                .channel_id
+                .parse()
+                .map_err(|e| Error::invalid_channel_id(raw_msg.channel_id.clone(), e))?,
+            relayer,
+            payee,
+        })
+    }
+}
+
+impl From<MsgRegisterPayee> for RawMsgRegisterPayee {
+    fn from(domain_msg: MsgRegisterPayee) -> Self {
+        RawMsgRegisterPayee {
+            port_id: domain_msg.port_id.to_string(),
+            channel_id: domain_msg.channel_id.to_string(),
+            relayer: domain_msg.relayer.to_string(),
+            payee: domain_msg.payee.to_string(),
+        }
+    }
+}
+
+impl Protobuf<RawMsgRegisterPayee> for MsgRegisterPayee {}
+
+impl TryFrom<Any> for MsgRegisterPayee {
+    type Error = Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        match raw.type_url.as_str() {
+            TYPE_URL => MsgRegisterPayee::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+            _ => Err(Error::unknown_message_type_url(raw.type_url)),
+        }
+    }
+}
+
+impl From<MsgRegisterPayee> for Any {
+    fn from(msg: MsgRegisterPayee) -> Self {
+        Self {
+            type_url: TYPE_URL.to_string(),
+            value: msg
+                .encode_vec()
+                .expect("encoding to `Any` from `MsgRegisterPayee`"),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "mocks"))]
+pub mod test_util {
+    use ibc_proto::ibc::applications::fee::v1::MsgRegisterPayee as RawMsgRegisterPayee;
+
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::prelude::*;
+
+    pub fn get_dummy_raw_msg_register_payee() -> RawMsgRegisterPayee {
+        RawMsgRegisterPayee {
+            port_id: PortId::transfer().to_string(),
+            channel_id: ChannelId::default().to_string(),
+            relayer: "cosmos1relayer".to_string(),
+            payee: "cosmos1payee".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::get_dummy_raw_msg_register_payee;
+    use super::*;
+
+    #[test]
+    fn msg_register_payee_round_trip() {
+        let raw = get_dummy_raw_msg_register_payee();
+        let msg = MsgRegisterPayee::try_from(raw.clone()).unwrap();
+        assert_eq!(RawMsgRegisterPayee::from(msg.clone()), raw);
+
+        let any = Any::from(msg.clone());
+        assert_eq!(MsgRegisterPayee::try_from(any).unwrap(), msg);
+    }
+
+    #[test]
+    fn msg_register_payee_validation() {
+        let default_raw = get_dummy_raw_msg_register_payee();
+
+        let tests: Vec<(&str, RawMsgRegisterPayee)> = vec![
+            (
+                "empty payee",
+                RawMsgRegisterPayee {
+                    payee: "".to_string(),
+                    ..default_raw.clone()
+                },
+            ),
+            (
+                "empty relayer",
+                RawMsgRegisterPayee {
+                    relayer: " ".to_string(),
+                    ..default_raw.clone()
+                },
+            ),
+            (
+                "relayer is payee",
+                RawMsgRegisterPayee {
+                    payee: default_raw.relayer.clone(),
+                    ..default_raw.clone()
+                },
+            ),
+            (
+                "bad channel id",
+                RawMsgRegisterPayee {
+                    channel_id: "chan".to_string(),
+                    ..default_raw.clone()
+                },
+            ),
+        ];
+
+        for (name, raw) in tests {
+            assert!(MsgRegisterPayee::try_from(raw).is_err(), "{}", name);
+        }
+    }
+}
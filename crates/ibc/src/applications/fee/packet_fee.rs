@@ -0,0 +1,172 @@
+//! Defines the fees that incentivize the relaying of a packet.
+
+use crate::prelude::*;
+
+use ibc_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use ibc_proto::ibc::applications::fee::v1::{Fee as RawFee, PacketFee as RawPacketFee};
+use ibc_proto::protobuf::Protobuf;
+
+use crate::applications::fee::error::Error;
+use crate::applications::transfer::PrefixedCoins;
+use crate::signer::Signer;
+
+/// The fees paid to the relayers of a packet, for each step of its lifecycle.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fee {
+    /// paid to the relayer that delivers the packet to the counterparty
+    pub recv_fee: PrefixedCoins,
+    /// paid to the relayer that delivers the acknowledgement back to the sender
+    pub ack_fee: PrefixedCoins,
+    /// paid to the relayer that delivers the timeout back to the sender
+    pub timeout_fee: PrefixedCoins,
+}
+
+impl Fee {
+    /// Returns `true` if none of the fees has a non-zero amount.
+    pub fn is_empty(&self) -> bool {
+        self.recv_fee.is_empty() && self.ack_fee.is_empty() && self.timeout_fee.is_empty()
+    }
+
+    /// Returns the coins escrowed for the packet, i.e. the sum of the receive, acknowledgement
+    /// and timeout fees.
+    pub fn total(&self) -> Result<PrefixedCoins, Error> {
+        self.recv_fee
+            .clone()
+            .checked_add(self.ack_fee.clone())
+            .and_then(|total| total.checked_add(self.timeout_fee.clone()))
+            .map_err(Error::invalid_fee_coin)
+    }
+}
+
+fn coins_from_proto(coins: Vec<ProtoCoin>) -> Result<PrefixedCoins, Error> {
+    PrefixedCoins::try_from(coins).map_err(Error::invalid_fee_coin)
+}
+
+impl TryFrom<RawFee> for Fee {
+    type Error = Error;
+
+    fn try_from(raw_fee: RawFee) -> Result<Self, Self::Error> {
+        Ok(Self {
+            recv_fee: coins_from_proto(raw_fee.recv_fee)?,
+            ack_fee: coins_from_proto(raw_fee.ack_fee)?,
+            timeout_fee: coins_from_proto(raw_fee.timeout_fee)?,
+        })
+    }
+}
+
+impl From<Fee> for RawFee {
+    fn from(fee: Fee) -> Self {
+        Self {
+            recv_fee: fee.recv_fee.into(),
+            ack_fee: fee.ack_fee.into(),
+            timeout_fee: fee.timeout_fee.into(),
+        }
+    }
+}
+
+impl Protobuf<RawFee> for Fee {}
+
+/// A fee escrowed for a packet, along with the address to refund the unpaid fees to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PacketFee {
+    pub fee: Fee,
+    pub refund_address: Signer,
+    /// Reserved: the relayers allowed to relay the packet, which must be empty.
+    pub relayers: Vec<String>,
+}
+
+impl PacketFee {
+    pub fn new(fee: Fee, refund_address: Signer) -> Self {
+        Self {
+            fee,
+            refund_address,
+            relayers: Vec::new(),
+        }
+    }
+}
+
+impl TryFrom<RawPacketFee> for PacketFee {
+    type Error = Error;
+
+    fn try_from(raw_packet_fee: RawPacketFee) -> Result<Self, Self::Error> {
+        Ok(Self {
+            fee: raw_packet_fee.fee.unwrap_or_default().try_into()?,
+            refund_address: raw_packet_fee
+                .refund_address
+                .parse()
+                .map_err(Error::signer)?,
+            relayers: raw_packet_fee.relayers,
+        })
+    }
+}
+
+impl From<PacketFee> for RawPacketFee {
+    fn from(packet_fee: PacketFee) -> Self {
+        Self {
+            fee: Some(packet_fee.fee.into()),
+            refund_address: packet_fee.refund_address.to_string(),
+            relayers: packet_fee.relayers,
+        }
+    }
+}
+
+impl Protobuf<RawPacketFee> for PacketFee {}
+
+#[cfg(any(test, feature = "mocks"))]
+pub mod test_util {
+    use super::Fee;
+    use crate::prelude::*;
+
+    /// Returns a fee of 10, 20 and 30 `stake` for the receive, acknowledgement and timeout
+    /// respectively.
+    pub fn get_dummy_fee() -> Fee {
+        let coin = |amount: u64| {
+            vec![crate::applications::transfer::PrefixedCoin {
+                denom: "stake".parse().unwrap(),
+                amount: amount.into(),
+            }]
+            .try_into()
+            .unwrap()
+        };
+
+        Fee {
+            recv_fee: coin(10),
+            ack_fee: coin(20),
+            timeout_fee: coin(30),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::get_dummy_fee;
+    use super::*;
+
+    #[test]
+    fn packet_fee_round_trip() {
+        let packet_fee = PacketFee::new(get_dummy_fee(), "cosmos1refund".parse().unwrap());
+
+        let raw = RawPacketFee::from(packet_fee.clone());
+        assert_eq!(raw.fee.as_ref().unwrap().ack_fee[0].amount, "20");
+        assert_eq!(PacketFee::try_from(raw).unwrap(), packet_fee);
+    }
+
+    #[test]
+    fn empty_fee() {
+        assert!(Fee::default().is_empty());
+        assert!(!get_dummy_fee().is_empty());
+    }
+
+    #[test]
+    fn total_fee() {
+        // The fees of the same denomination add up.
+        let total = get_dummy_fee().total().unwrap();
+        assert_eq!(total.len(), 1);
+        assert_eq!(total.amount_of(&"stake".parse().unwrap()), 60u64.into());
+
+        // Fees with unsorted or zero coins are rejected.
+        let mut raw = RawFee::from(get_dummy_fee());
+        raw.recv_fee[0].amount = "0".to_string();
+        assert!(Fee::try_from(raw).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use super::error::Error;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics26_routing::context::sanitized_ack_error;
+use crate::prelude::*;
+
+/// A string constant included in error acknowledgements.
+/// NOTE: Changing this const is state machine breaking as acknowledgements are written into state
+pub const ACK_ERR_STR: &str = "error handling packet on host chain: see events for details";
+
+/// An ICS27 acknowledgement, encoded as JSON.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Acknowledgement {
+    /// Successful Acknowledgement, with the base64 encoding of its result
+    /// e.g. `{"result":"AQ=="}`
+    #[serde(rename = "result")]
+    Success(#[serde(with = "crate::serializers::serde_base64")] Vec<u8>),
+    /// Error Acknowledgement
+    /// e.g. `{"error":"error handling packet on host chain: see events for details"}`
+    #[serde(rename = "error")]
+    Error(String),
+}
+
+impl Acknowledgement {
+    /// Returns the acknowledgement of a transaction the host executed. The result does not carry
+    /// the responses of the messages.
+    pub fn success() -> Self {
+        Self::Success(vec![1])
+    }
+
+    /// Returns the error acknowledgement of `err`, which only carries its public reason code
+    /// (see [`AckError`](crate::core::ics26_routing::context::AckError)).
+    pub fn from_error(err: Error) -> Self {
+        Self::Error(sanitized_ack_error(&err, ACK_ERR_STR))
+    }
+
+    pub fn is_successful(&self) -> bool {
+        matches!(self, Self::Success(_))
+    }
+}
+
+impl From<Acknowledgement> for GenericAcknowledgement {
+    fn from(ack: Acknowledgement) -> Self {
+        serde_json::to_vec(&ack)
+            .expect("Acknowledgement's infallible Serialize impl failed")
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ack_json() {
+        let ack = GenericAcknowledgement::from(Acknowledgement::success());
+        assert_eq!(ack.as_ref(), br#"{"result":"AQ=="}"#);
+
+        let ack: Acknowledgement = serde_json::from_slice(ack.as_ref()).unwrap();
+        assert!(ack.is_successful());
+    }
+}
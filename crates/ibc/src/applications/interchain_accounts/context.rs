@@ -0,0 +1,136 @@
+use ibc_proto::google::protobuf::Any;
+use sha2::{Digest, Sha256};
+
+use super::error::Error;
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::prelude::*;
+
+/// The name under which the Cosmos SDK derives the addresses of interchain accounts.
+const ADDRESS_MODULE_NAME: &str = "interchainaccounts";
+
+/// Read access to the interchain accounts state of the host.
+///
+/// Accounts and channels are keyed by the connection of the chain that reads them and by the
+/// controller port of the account owner, on both the controller and the host.
+pub trait InterchainAccountReader: ChannelReader {
+    /// Returns true iff this chain may open channels and send transactions as a controller.
+    fn is_controller_enabled(&self) -> bool;
+
+    /// Returns true iff this chain registers accounts and executes transactions as a host.
+    fn is_host_enabled(&self) -> bool;
+
+    /// Returns true iff the host allows interchain accounts to execute messages of type
+    /// `type_url`.
+    fn is_message_allowed(&self, type_url: &str) -> bool;
+
+    /// Returns the channel used by the account of `port_id` over `connection_id`, if any.
+    fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId>;
+
+    /// Returns the address of the interchain account of `port_id` over `connection_id`, if any.
+    fn interchain_account_address(
+        &self,
+        connection_id: &ConnectionId,
+        port_id: &PortId,
+    ) -> Option<String>;
+
+    /// Returns the addresses of the signers of `message`, which the host decodes from its
+    /// `type_url`. An interchain account may only execute the messages it is the sole signer of.
+    fn message_signers(&self, message: &Any) -> Result<Vec<String>, Error>;
+}
+
+/// Write access to the interchain accounts state of the host.
+pub trait InterchainAccountKeeper: ChannelKeeper {
+    /// Records the channel used by the account of `port_id` over `connection_id`.
+    fn store_active_channel(
+        &mut self,
+        connection_id: ConnectionId,
+        port_id: PortId,
+        channel_id: ChannelId,
+    ) -> Result<(), Error>;
+
+    /// Records the address of the interchain account of `port_id` over `connection_id`.
+    fn store_interchain_account_address(
+        &mut self,
+        connection_id: ConnectionId,
+        port_id: PortId,
+        address: String,
+    ) -> Result<(), Error>;
+
+    /// Creates a new account on the host for the owner of `port_id` over `connection_id`, and
+    /// returns its address. Cosmos SDK hosts derive it with
+    /// [`cosmos_adr028_interchain_account_address`].
+    fn create_interchain_account(
+        &mut self,
+        connection_id: &ConnectionId,
+        port_id: &PortId,
+    ) -> Result<String, Error>;
+
+    /// Executes the messages of a transaction on behalf of the interchain account at `address`.
+    /// Either all messages succeed or none of their state changes must be kept.
+    fn execute_tx(&mut self, address: &str, messages: Vec<Any>) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies which the ICS27 module requires to be able to dispatch and
+/// process IBC messages.
+pub trait InterchainAccountContext: InterchainAccountKeeper + InterchainAccountReader {}
+
+/// Returns the identifier of the connection of the counterparty chain at the other end of
+/// `connection_id`.
+pub(super) fn counterparty_connection_id(
+    ctx: &impl ChannelReader,
+    connection_id: &ConnectionId,
+) -> Result<ConnectionId, Error> {
+    ctx.connection_end(connection_id)
+        .map_err(Error::ics04_channel)?
+        .counterparty()
+        .connection_id()
+        .cloned()
+        .ok_or_else(|| Error::missing_counterparty_connection(connection_id.clone()))
+}
+
+/// Fails if the account of `port_id` over `connection_id` already has an OPEN channel, as there
+/// may be only one per account. The channel is bound to `channel_port_id` on this chain, i.e. to
+/// `port_id` on the controller and to the host port on the host.
+pub(super) fn ensure_no_open_active_channel(
+    ctx: &impl InterchainAccountReader,
+    connection_id: &ConnectionId,
+    port_id: &PortId,
+    channel_port_id: &PortId,
+) -> Result<(), Error> {
+    match ctx.active_channel(connection_id, port_id) {
+        Some(channel_id) => {
+            let channel_end = ctx
+                .channel_end(channel_port_id, &channel_id)
+                .map_err(Error::ics04_channel)?;
+            if channel_end.is_open() {
+                Err(Error::active_channel_already_set(
+                    connection_id.clone(),
+                    port_id.clone(),
+                    channel_id,
+                ))
+            } else {
+                Ok(())
+            }
+        }
+        None => Ok(()),
+    }
+}
+
+/// Returns the raw address of the interchain account of `port_id` over `connection_id`, as
+/// derived by the Cosmos SDK (ADR-028 module account address).
+pub fn cosmos_adr028_interchain_account_address(
+    connection_id: &ConnectionId,
+    port_id: &PortId,
+) -> Vec<u8> {
+    let type_hash = Sha256::digest(b"module");
+
+    let mut hasher = Sha256::new();
+    hasher.update(type_hash);
+    hasher.update(ADDRESS_MODULE_NAME.as_bytes());
+    hasher.update([0]);
+    hasher.update(connection_id.as_str().as_bytes());
+    hasher.update(port_id.as_str().as_bytes());
+
+    hasher.finalize().to_vec()
+}
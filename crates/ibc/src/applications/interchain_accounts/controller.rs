@@ -0,0 +1,299 @@
+//! The callbacks of the ICS27 controller, which opens one channel per account owner and sends
+//! the transactions of the owners over it.
+
+use core::convert::TryFrom;
+
+use super::context::{
+    counterparty_connection_id, ensure_no_open_active_channel, InterchainAccountContext,
+};
+use super::error::Error;
+use super::metadata::Metadata;
+use super::packet::PacketData;
+use super::{is_controller_port, HOST_PORT_ID_STR};
+use crate::core::ics04_channel::channel::{Counterparty, Order};
+use crate::core::ics04_channel::handler::send_packet::send_packet;
+use crate::core::ics04_channel::handler::ModuleExtras;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics04_channel::packet::{Packet, Sequence};
+use crate::core::ics04_channel::timeout::TimeoutHeight;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::ModuleOutputBuilder;
+use crate::handler::{HandlerOutput, HandlerOutputBuilder};
+use crate::prelude::*;
+use crate::signer::Signer;
+use crate::timestamp::Timestamp;
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_init(
+    ctx: &mut impl InterchainAccountContext,
+    order: Order,
+    connection_hops: &[ConnectionId],
+    port_id: &PortId,
+    _channel_id: &ChannelId,
+    counterparty: &Counterparty,
+    version: &Version,
+) -> Result<(ModuleExtras, Version), Error> {
+    if !ctx.is_controller_enabled() {
+        return Err(Error::controller_disabled());
+    }
+    if order != Order::Ordered {
+        return Err(Error::channel_not_ordered(order));
+    }
+    if !is_controller_port(port_id) {
+        return Err(Error::not_controller_port(port_id.clone()));
+    }
+    if counterparty.port_id().as_str() != HOST_PORT_ID_STR {
+        return Err(Error::invalid_host_port(counterparty.port_id().clone()));
+    }
+
+    let connection_id = &connection_hops[0];
+    let host_connection_id = counterparty_connection_id(ctx, connection_id)?;
+    let metadata = if version.is_empty() {
+        Metadata::new(connection_id.clone(), host_connection_id)
+    } else {
+        let metadata = Metadata::try_from(version)?;
+        metadata.validate(connection_id, &host_connection_id)?;
+        metadata
+    };
+    ensure_no_open_active_channel(ctx, connection_id, port_id, port_id)?;
+
+    Ok((ModuleExtras::empty(), metadata.into()))
+}
+
+pub fn on_chan_open_ack(
+    ctx: &mut impl InterchainAccountContext,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    counterparty_version: &Version,
+) -> Result<ModuleExtras, Error> {
+    let channel_end = ctx
+        .channel_end(port_id, channel_id)
+        .map_err(Error::ics04_channel)?;
+    let connection_id = channel_end.connection_hops()[0].clone();
+    let host_connection_id = counterparty_connection_id(ctx, &connection_id)?;
+
+    let metadata = Metadata::try_from(counterparty_version)?;
+    metadata.validate(&connection_id, &host_connection_id)?;
+    if metadata.address.is_empty() {
+        return Err(Error::invalid_metadata(
+            "the host did not set the account address".to_string(),
+        ));
+    }
+
+    ctx.store_active_channel(connection_id.clone(), port_id.clone(), channel_id.clone())?;
+    ctx.store_interchain_account_address(connection_id, port_id.clone(), metadata.address)?;
+
+    Ok(ModuleExtras::empty())
+}
+
+pub fn on_chan_close_init(
+    _ctx: &mut impl InterchainAccountContext,
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+) -> Result<ModuleExtras, Error> {
+    Err(Error::cant_close_channel())
+}
+
+pub fn on_chan_close_confirm(
+    _ctx: &mut impl InterchainAccountContext,
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+) -> Result<ModuleExtras, Error> {
+    Ok(ModuleExtras::empty())
+}
+
+/// The controller leaves the handling of acknowledgements to the application of the owner.
+pub fn on_acknowledgement_packet(
+    _ctx: &mut impl InterchainAccountContext,
+    _output: &mut ModuleOutputBuilder,
+    _packet: &Packet,
+    _acknowledgement: &GenericAcknowledgement,
+    _relayer: &Signer,
+) -> Result<(), Error> {
+    Ok(())
+}
+
+/// A timeout closes the ordered channel of the account, which the owner may reopen with a new
+/// `ChanOpenInit`.
+pub fn on_timeout_packet(
+    _ctx: &mut impl InterchainAccountContext,
+    _output: &mut ModuleOutputBuilder,
+    _packet: &Packet,
+    _relayer: &Signer,
+) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Sends a transaction to execute with the interchain account of `port_id` over `connection_id`,
+/// returning the sequence of the packet.
+/// If this method returns an error, the runtime is expected to rollback all state modifications to
+/// the `Ctx` caused by all messages from the transaction that this call is a part of.
+pub fn send_tx<Ctx: InterchainAccountContext>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutputBuilder<()>,
+    connection_id: &ConnectionId,
+    port_id: &PortId,
+    data: PacketData,
+    timeout_height: TimeoutHeight,
+    timeout_timestamp: Timestamp,
+) -> Result<Sequence, Error> {
+    if !ctx.is_controller_enabled() {
+        return Err(Error::controller_disabled());
+    }
+
+    let channel_id = ctx
+        .active_channel(connection_id, port_id)
+        .ok_or_else(|| Error::active_channel_not_found(connection_id.clone(), port_id.clone()))?;
+    let channel_end = ctx
+        .channel_end(port_id, &channel_id)
+        .map_err(Error::ics04_channel)?;
+    let destination_channel = channel_end
+        .counterparty()
+        .channel_id()
+        .ok_or_else(|| Error::active_channel_not_found(connection_id.clone(), port_id.clone()))?
+        .clone();
+
+    let sequence = ctx
+        .get_next_sequence_send(port_id, &channel_id)
+        .map_err(Error::ics04_channel)?;
+
+    let data = serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed");
+
+    let packet = Packet::builder()
+        .sequence(sequence)
+        .source(port_id.clone(), channel_id)
+        .destination(
+            channel_end.counterparty().port_id().clone(),
+            destination_channel,
+        )
+        .data(data)
+        .timeout_height(timeout_height)
+        .timeout_timestamp(timeout_timestamp)
+        .build()
+        .map_err(Error::ics04_channel)?;
+
+    let HandlerOutput {
+        result,
+        log,
+        events,
+    } = send_packet(ctx, packet).map_err(Error::ics04_channel)?;
+
+    ctx.store_packet_result(result)
+        .map_err(Error::ics04_channel)?;
+
+    output.merge_output(
+        HandlerOutput::builder()
+            .with_log(log)
+            .with_events(events)
+            .with_result(()),
+    );
+
+    output.log(format!(
+        "IBC interchain account transaction: {} --({})--> {}",
+        port_id, sequence, connection_id
+    ));
+
+    Ok(sequence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_proto::google::protobuf::Any;
+
+    use crate::applications::interchain_accounts::context::InterchainAccountReader;
+    use crate::applications::interchain_accounts::controller_port_id;
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics04_channel::channel::{ChannelEnd, State};
+    use crate::core::ics04_channel::context::ChannelReader;
+    use crate::core::ics24_host::identifier::ClientId;
+    use crate::mock::context::MockContext;
+    use crate::Height;
+
+    #[test]
+    fn test_controller_handshake_and_send() {
+        let controller_connection_id = ConnectionId::new(0);
+        let host_connection_id = ConnectionId::new(1);
+        let mut connection_end = ConnectionEnd::default();
+        connection_end.set_state(ConnectionState::Open);
+        connection_end.set_counterparty(ConnectionCounterparty::new(
+            ClientId::default(),
+            Some(host_connection_id.clone()),
+            Default::default(),
+        ));
+        let mut ctx = MockContext::default()
+            .with_client(&ClientId::default(), Height::new(0, 5).unwrap())
+            .with_connection(controller_connection_id.clone(), connection_end);
+
+        let port_id = controller_port_id("owner").unwrap();
+        let channel_id = ChannelId::new(0);
+        let host_port_id: PortId = HOST_PORT_ID_STR.parse().unwrap();
+        let (_, version) = on_chan_open_init(
+            &mut ctx,
+            Order::Ordered,
+            core::slice::from_ref(&controller_connection_id),
+            &port_id,
+            &channel_id,
+            &Counterparty::new(host_port_id.clone(), None),
+            &Version::empty(),
+        )
+        .unwrap();
+        let metadata = Metadata::try_from(&version).unwrap();
+        assert_eq!(
+            metadata,
+            Metadata::new(controller_connection_id.clone(), host_connection_id)
+        );
+
+        let mut ctx = ctx
+            .with_channel(
+                port_id.clone(),
+                channel_id.clone(),
+                ChannelEnd::new(
+                    State::Open,
+                    Order::Ordered,
+                    Counterparty::new(host_port_id, Some(ChannelId::new(7))),
+                    vec![controller_connection_id.clone()],
+                    version,
+                ),
+            )
+            .with_send_sequence(port_id.clone(), channel_id.clone(), 1.into());
+
+        // The host must set the address of the account.
+        assert!(
+            on_chan_open_ack(&mut ctx, &port_id, &channel_id, &metadata.clone().into()).is_err()
+        );
+        let metadata = metadata.with_address("cosmos1account".to_string());
+        on_chan_open_ack(&mut ctx, &port_id, &channel_id, &metadata.into()).unwrap();
+        assert_eq!(
+            ctx.interchain_account_address(&controller_connection_id, &port_id),
+            Some("cosmos1account".to_string())
+        );
+
+        let data = PacketData::execute_tx(
+            vec![Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                value: vec![1, 2, 3],
+            }],
+            String::new(),
+        );
+        let sequence = send_tx(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            &controller_connection_id,
+            &port_id,
+            data,
+            TimeoutHeight::At(Height::new(0, 10).unwrap()),
+            Timestamp::none(),
+        )
+        .unwrap();
+        assert_eq!(sequence, 1.into());
+        assert_eq!(
+            ctx.get_next_sequence_send(&port_id, &channel_id).unwrap(),
+            2.into()
+        );
+    }
+}
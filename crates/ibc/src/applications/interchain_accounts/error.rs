@@ -0,0 +1,118 @@
+use flex_error::{define_error, TraceError};
+use ibc_proto::protobuf::Error as TendermintProtoError;
+
+use crate::core::ics04_channel::channel::Order;
+use crate::core::ics04_channel::error as channel_error;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::error::ValidationError;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::{AckError, INTERNAL_ACK_ERROR_CODE};
+use crate::prelude::*;
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        Ics04Channel
+            [ channel_error::Error ]
+            | _ | { "Ics04 channel error" },
+
+        ControllerDisabled
+            | _ | { "interchain accounts controller is not enabled" },
+
+        HostDisabled
+            | _ | { "interchain accounts host is not enabled" },
+
+        EmptyOwner
+            | _ | { "interchain account owner is empty" },
+
+        InvalidControllerPort
+            [ ValidationError ]
+            | _ | { "invalid controller port identifier" },
+
+        NotControllerPort
+            { port_id: PortId }
+            | e | { format_args!("port {0} is not an interchain accounts controller port", e.port_id) },
+
+        InvalidHostPort
+            { port_id: PortId }
+            | e | { format_args!("invalid host port: '{0}', expected '{1}'", e.port_id, super::HOST_PORT_ID_STR) },
+
+        ChannelNotOrdered
+            { order: Order }
+            | e | { format_args!("expected '{0}' channel, got '{1}'", Order::Ordered, e.order) },
+
+        MetadataDeserialization
+            { version: Version }
+            | e | { format_args!("failed to deserialize the interchain accounts metadata from version '{0}'", e.version) },
+
+        InvalidMetadata
+            { reason: String }
+            | e | { format_args!("invalid interchain accounts metadata: {0}", e.reason) },
+
+        MissingCounterpartyConnection
+            { connection_id: ConnectionId }
+            | e | { format_args!("connection {0} has no counterparty connection identifier", e.connection_id) },
+
+        ActiveChannelAlreadySet
+            { connection_id: ConnectionId, port_id: PortId, channel_id: ChannelId }
+            | e | {
+                format_args!("channel {0} is already active for port {1} on connection {2}",
+                    e.channel_id, e.port_id, e.connection_id)
+            },
+
+        ActiveChannelNotFound
+            { connection_id: ConnectionId, port_id: PortId }
+            | e | { format_args!("no active channel for port {0} on connection {1}", e.port_id, e.connection_id) },
+
+        AccountNotFound
+            { connection_id: ConnectionId, port_id: PortId }
+            | e | { format_args!("no interchain account for port {0} on connection {1}", e.port_id, e.connection_id) },
+
+        CantCloseChannel
+            | _ | { "interchain accounts channels cannot be closed by the application" },
+
+        PacketDataDeserialization
+            | _ | { "failed to deserialize packet data" },
+
+        UnsupportedPacketType
+            | _ | { "unsupported interchain accounts packet type" },
+
+        DecodeCosmosTx
+            [ TraceError<TendermintProtoError> ]
+            | _ | { "failed to decode the transaction of the packet" },
+
+        EmptyTx
+            | _ | { "the transaction of the packet has no messages" },
+
+        MessageNotAllowed
+            { type_url: String }
+            | e | { format_args!("message type {0} is not allowed on the host", e.type_url) },
+
+        UnauthorizedSigner
+            { signer: String, address: String }
+            | e | {
+                format_args!("message signer {0} is not the interchain account {1}",
+                    e.signer, e.address)
+            },
+
+        ExecuteTx
+            { reason: String }
+            | e | { format_args!("failed to execute the transaction: {0}", e.reason) },
+    }
+}
+
+/// The codes of the errors are those of the equivalent ICS27 errors of ibc-go, if any.
+impl AckError for Error {
+    fn ack_error_code(&self) -> u32 {
+        match self.detail() {
+            ErrorDetail::PacketDataDeserialization(_) | ErrorDetail::UnsupportedPacketType(_) => 2,
+            ErrorDetail::DecodeCosmosTx(_) | ErrorDetail::EmptyTx(_) => 6,
+            ErrorDetail::AccountNotFound(_) => 8,
+            ErrorDetail::ActiveChannelNotFound(_) => 11,
+            ErrorDetail::MessageNotAllowed(_) => 14,
+            ErrorDetail::HostDisabled(_) => 19,
+            ErrorDetail::ControllerDisabled(_) => 20,
+            _ => INTERNAL_ACK_ERROR_CODE,
+        }
+    }
+}
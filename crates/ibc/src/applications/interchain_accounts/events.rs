@@ -0,0 +1,37 @@
+use crate::applications::interchain_accounts::HOST_MODULE_ID_STR;
+use crate::core::ics24_host::identifier::ChannelId;
+use crate::events::ModuleEvent;
+use crate::prelude::*;
+
+const EVENT_TYPE_PACKET: &str = "ics27_packet";
+
+/// Emitted on the host once a packet was processed, successfully or not.
+pub struct RecvEvent {
+    pub host_channel_id: ChannelId,
+    pub success: bool,
+    /// The reason the packet could not be processed, if `success` is false.
+    pub error: Option<String>,
+}
+
+impl From<RecvEvent> for ModuleEvent {
+    fn from(ev: RecvEvent) -> Self {
+        let RecvEvent {
+            host_channel_id,
+            success,
+            error,
+        } = ev;
+        let mut ev = Self {
+            kind: EVENT_TYPE_PACKET.to_string(),
+            module_name: HOST_MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("module", HOST_MODULE_ID_STR).into(),
+                ("host_channel_id", host_channel_id).into(),
+                ("success", success).into(),
+            ],
+        };
+        if let Some(error) = error {
+            ev.attributes.push(("error", error).into());
+        }
+        ev
+    }
+}
@@ -0,0 +1,324 @@
+//! The callbacks of the ICS27 host, which registers an account for each controller channel and
+//! executes the transactions received over it.
+
+use core::convert::TryFrom;
+
+use ibc_proto::google::protobuf::Any;
+
+use super::acknowledgement::Acknowledgement;
+use super::context::{
+    counterparty_connection_id, ensure_no_open_active_channel, InterchainAccountContext,
+};
+use super::error::Error;
+use super::events::RecvEvent;
+use super::metadata::Metadata;
+use super::packet::PacketData;
+use super::{is_controller_port, HOST_PORT_ID_STR};
+use crate::core::ics04_channel::channel::{Counterparty, Order};
+use crate::core::ics04_channel::handler::ModuleExtras;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::{ModuleOutputBuilder, OnRecvPacketAck};
+use crate::prelude::*;
+use crate::signer::Signer;
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_try(
+    ctx: &mut impl InterchainAccountContext,
+    order: Order,
+    connection_hops: &[ConnectionId],
+    port_id: &PortId,
+    _channel_id: &ChannelId,
+    counterparty: &Counterparty,
+    counterparty_version: &Version,
+) -> Result<(ModuleExtras, Version), Error> {
+    if !ctx.is_host_enabled() {
+        return Err(Error::host_disabled());
+    }
+    if order != Order::Ordered {
+        return Err(Error::channel_not_ordered(order));
+    }
+    if port_id.as_str() != HOST_PORT_ID_STR {
+        return Err(Error::invalid_host_port(port_id.clone()));
+    }
+    let controller_port_id = counterparty.port_id();
+    if !is_controller_port(controller_port_id) {
+        return Err(Error::not_controller_port(controller_port_id.clone()));
+    }
+
+    let connection_id = &connection_hops[0];
+    let controller_connection_id = counterparty_connection_id(ctx, connection_id)?;
+    let metadata = Metadata::try_from(counterparty_version)?;
+    metadata.validate(&controller_connection_id, connection_id)?;
+    ensure_no_open_active_channel(ctx, connection_id, controller_port_id, port_id)?;
+
+    // A controller reopening the channel of an account keeps using that account.
+    let address = match ctx.interchain_account_address(connection_id, controller_port_id) {
+        Some(address) => address,
+        None => {
+            let address = ctx.create_interchain_account(connection_id, controller_port_id)?;
+            ctx.store_interchain_account_address(
+                connection_id.clone(),
+                controller_port_id.clone(),
+                address.clone(),
+            )?;
+            address
+        }
+    };
+
+    Ok((ModuleExtras::empty(), metadata.with_address(address).into()))
+}
+
+pub fn on_chan_open_confirm(
+    ctx: &mut impl InterchainAccountContext,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> Result<ModuleExtras, Error> {
+    let channel_end = ctx
+        .channel_end(port_id, channel_id)
+        .map_err(Error::ics04_channel)?;
+    let connection_id = channel_end.connection_hops()[0].clone();
+    let controller_port_id = channel_end.counterparty().port_id().clone();
+
+    ctx.store_active_channel(connection_id, controller_port_id, channel_id.clone())?;
+
+    Ok(ModuleExtras::empty())
+}
+
+pub fn on_chan_close_init(
+    _ctx: &mut impl InterchainAccountContext,
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+) -> Result<ModuleExtras, Error> {
+    Err(Error::cant_close_channel())
+}
+
+pub fn on_chan_close_confirm(
+    _ctx: &mut impl InterchainAccountContext,
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+) -> Result<ModuleExtras, Error> {
+    Ok(ModuleExtras::empty())
+}
+
+/// Checks the transaction of the packet and executes it, acknowledging the packet with an error
+/// if the transaction may not be executed or fails.
+pub fn on_recv_packet<Ctx: 'static + InterchainAccountContext>(
+    ctx: &Ctx,
+    output: &mut ModuleOutputBuilder,
+    packet: &Packet,
+    _relayer: &Signer,
+) -> OnRecvPacketAck {
+    let host_channel_id = packet.destination_channel.clone();
+    let (address, messages) = match process_recv_packet(ctx, packet) {
+        Ok(tx) => tx,
+        Err(e) => return failed_recv_packet_ack(output, host_channel_id, e),
+    };
+
+    // The transaction is executed atomically, so that its failure leaves no state changes.
+    OnRecvPacketAck::Deferred(Box::new(move |ctx, output| {
+        let ctx = ctx
+            .downcast_mut::<Ctx>()
+            .ok_or_else(|| "interchain accounts context expected".to_string())?;
+        if let Err(e) = ctx.execute_tx(&address, messages) {
+            return Ok(failed_recv_packet_ack(output, host_channel_id, e));
+        }
+
+        output.emit(
+            RecvEvent {
+                host_channel_id,
+                success: true,
+                error: None,
+            }
+            .into(),
+        );
+        Ok(OnRecvPacketAck::Successful(
+            Box::new(GenericAcknowledgement::from(Acknowledgement::success())),
+            Box::new(|_| Ok(())),
+        ))
+    }))
+}
+
+fn failed_recv_packet_ack(
+    output: &mut ModuleOutputBuilder,
+    host_channel_id: ChannelId,
+    e: Error,
+) -> OnRecvPacketAck {
+    output.emit(
+        RecvEvent {
+            host_channel_id,
+            success: false,
+            error: Some(e.to_string()),
+        }
+        .into(),
+    );
+    OnRecvPacketAck::Failed(Box::new(GenericAcknowledgement::from(
+        Acknowledgement::from_error(e),
+    )))
+}
+
+/// Returns the address of the account the packet is sent from, and the messages to execute.
+fn process_recv_packet(
+    ctx: &impl InterchainAccountContext,
+    packet: &Packet,
+) -> Result<(String, Vec<Any>), Error> {
+    if !ctx.is_host_enabled() {
+        return Err(Error::host_disabled());
+    }
+
+    let data = serde_json::from_slice::<PacketData>(&packet.data)
+        .map_err(|_| Error::packet_data_deserialization())?;
+    let messages = data.messages()?;
+    if let Some(message) = messages
+        .iter()
+        .find(|message| !ctx.is_message_allowed(&message.type_url))
+    {
+        return Err(Error::message_not_allowed(message.type_url.clone()));
+    }
+
+    let channel_end = ctx
+        .channel_end(&packet.destination_port, &packet.destination_channel)
+        .map_err(Error::ics04_channel)?;
+    let connection_id = &channel_end.connection_hops()[0];
+    let address = ctx
+        .interchain_account_address(connection_id, &packet.source_port)
+        .ok_or_else(|| {
+            Error::account_not_found(connection_id.clone(), packet.source_port.clone())
+        })?;
+
+    for message in &messages {
+        if let Some(signer) = ctx
+            .message_signers(message)?
+            .into_iter()
+            .find(|signer| *signer != address)
+        {
+            return Err(Error::unauthorized_signer(signer, address));
+        }
+    }
+
+    Ok((address, messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::applications::interchain_accounts::context::InterchainAccountReader;
+    use crate::applications::interchain_accounts::controller_port_id;
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics04_channel::channel::{ChannelEnd, State};
+    use crate::core::ics24_host::identifier::ClientId;
+    use crate::core::ics26_routing::context::WrittenAck;
+    use crate::mock::context::MockContext;
+
+    #[test]
+    fn test_host_handshake_and_execution() {
+        let host_connection_id = ConnectionId::new(0);
+        let controller_connection_id = ConnectionId::new(1);
+        let mut connection_end = ConnectionEnd::default();
+        connection_end.set_state(ConnectionState::Open);
+        connection_end.set_counterparty(ConnectionCounterparty::new(
+            ClientId::default(),
+            Some(controller_connection_id.clone()),
+            Default::default(),
+        ));
+        let mut ctx =
+            MockContext::default().with_connection(host_connection_id.clone(), connection_end);
+
+        let host_port_id: PortId = HOST_PORT_ID_STR.parse().unwrap();
+        let controller_port_id = controller_port_id("owner").unwrap();
+        let counterparty = Counterparty::new(controller_port_id.clone(), Some(ChannelId::new(3)));
+        let metadata = Metadata::new(controller_connection_id, host_connection_id.clone());
+        let open_try = |ctx: &mut MockContext, order: Order, channel_id: ChannelId| {
+            on_chan_open_try(
+                ctx,
+                order,
+                core::slice::from_ref(&host_connection_id),
+                &host_port_id,
+                &channel_id,
+                &counterparty,
+                &metadata.clone().into(),
+            )
+        };
+
+        assert!(open_try(&mut ctx, Order::Unordered, ChannelId::new(0)).is_err());
+        let (_, version) = open_try(&mut ctx, Order::Ordered, ChannelId::new(0)).unwrap();
+        let address = Metadata::try_from(&version).unwrap().address;
+        assert!(!address.is_empty());
+
+        let mut ctx = ctx.with_channel(
+            host_port_id.clone(),
+            ChannelId::new(0),
+            ChannelEnd::new(
+                State::Open,
+                Order::Ordered,
+                counterparty.clone(),
+                vec![host_connection_id.clone()],
+                version,
+            ),
+        );
+        on_chan_open_confirm(&mut ctx, &host_port_id, &ChannelId::new(0)).unwrap();
+        assert_eq!(
+            ctx.active_channel(&host_connection_id, &controller_port_id),
+            Some(ChannelId::new(0))
+        );
+
+        // The account may not have a second channel while the first one is open.
+        assert!(open_try(&mut ctx, Order::Ordered, ChannelId::new(1)).is_err());
+
+        let recv_tx = |ctx: &mut MockContext, messages: Vec<Any>| {
+            let packet = Packet {
+                source_port: controller_port_id.clone(),
+                source_channel: ChannelId::new(3),
+                destination_port: host_port_id.clone(),
+                destination_channel: ChannelId::new(0),
+                data: serde_json::to_vec(&PacketData::execute_tx(messages, String::new())).unwrap(),
+                ..Default::default()
+            };
+            let mut output = ModuleOutputBuilder::new();
+            let ack = on_recv_packet(ctx, &mut output, &packet, &"relayer".parse().unwrap())
+                .write(ctx, &mut output)
+                .unwrap();
+            matches!(ack, WrittenAck::Successful(_))
+        };
+        let message = |type_url: &str, signer: &str| Any {
+            type_url: type_url.to_string(),
+            value: signer.as_bytes().to_vec(),
+        };
+
+        let send = message("/cosmos.bank.v1beta1.MsgSend", &address);
+        assert!(recv_tx(&mut ctx, vec![send.clone()]));
+        assert_eq!(
+            ctx.ibc_store.lock().unwrap().ica_executed_txs,
+            vec![(address.clone(), vec![send.clone()])]
+        );
+
+        // The account may only sign for itself ...
+        let foreign_send = message("/cosmos.bank.v1beta1.MsgSend", "cosmos1other");
+        assert!(!recv_tx(&mut ctx, vec![send.clone(), foreign_send]));
+
+        // ... and a transaction failing on the host is acknowledged with an error.
+        let vote = message("/cosmos.gov.v1beta1.MsgVote", &address);
+        assert!(!recv_tx(&mut ctx, vec![send, vote]));
+
+        assert_eq!(ctx.ibc_store.lock().unwrap().ica_executed_txs.len(), 1);
+
+        let packet = Packet {
+            data: b"not a packet".to_vec(),
+            destination_port: HOST_PORT_ID_STR.parse().unwrap(),
+            destination_channel: ChannelId::new(0),
+            ..Default::default()
+        };
+        assert!(!on_recv_packet(
+            &ctx,
+            &mut ModuleOutputBuilder::new(),
+            &packet,
+            &"relayer".parse().unwrap(),
+        )
+        .is_successful());
+    }
+}
@@ -0,0 +1,137 @@
+use core::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::Error;
+use super::{ENCODING_PROTOBUF, TX_TYPE_SDK_MULTI_MSG, VERSION};
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::identifier::ConnectionId;
+use crate::prelude::*;
+
+/// The version negotiated on interchain accounts channels, encoded as JSON in the channel version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub version: String,
+    pub controller_connection_id: ConnectionId,
+    pub host_connection_id: ConnectionId,
+    /// The address of the interchain account, set by the host in its `ChanOpenTry` version.
+    #[serde(default)]
+    pub address: String,
+    pub encoding: String,
+    pub tx_type: String,
+}
+
+impl Metadata {
+    /// Returns the metadata that the controller proposes for a new channel, i.e. the current
+    /// version, encoding and transaction type without an address.
+    pub fn new(controller_connection_id: ConnectionId, host_connection_id: ConnectionId) -> Self {
+        Self {
+            version: VERSION.to_string(),
+            controller_connection_id,
+            host_connection_id,
+            address: String::new(),
+            encoding: ENCODING_PROTOBUF.to_string(),
+            tx_type: TX_TYPE_SDK_MULTI_MSG.to_string(),
+        }
+    }
+
+    pub fn with_address(self, address: String) -> Self {
+        Self { address, ..self }
+    }
+
+    /// Checks that the version, encoding and transaction type are supported, and that the
+    /// connections are the ones the channel is opened on.
+    pub fn validate(
+        &self,
+        controller_connection_id: &ConnectionId,
+        host_connection_id: &ConnectionId,
+    ) -> Result<(), Error> {
+        if self.version != VERSION {
+            return Err(Error::invalid_metadata(format!(
+                "unsupported version '{}'",
+                self.version
+            )));
+        }
+        if self.encoding != ENCODING_PROTOBUF {
+            return Err(Error::invalid_metadata(format!(
+                "unsupported encoding '{}'",
+                self.encoding
+            )));
+        }
+        if self.tx_type != TX_TYPE_SDK_MULTI_MSG {
+            return Err(Error::invalid_metadata(format!(
+                "unsupported transaction type '{}'",
+                self.tx_type
+            )));
+        }
+        if &self.controller_connection_id != controller_connection_id {
+            return Err(Error::invalid_metadata(format!(
+                "expected controller connection '{}', got '{}'",
+                controller_connection_id, self.controller_connection_id
+            )));
+        }
+        if &self.host_connection_id != host_connection_id {
+            return Err(Error::invalid_metadata(format!(
+                "expected host connection '{}', got '{}'",
+                host_connection_id, self.host_connection_id
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&Version> for Metadata {
+    type Error = Error;
+
+    fn try_from(version: &Version) -> Result<Self, Self::Error> {
+        serde_json::from_str(version.as_str())
+            .map_err(|_| Error::metadata_deserialization(version.clone()))
+    }
+}
+
+impl From<Metadata> for Version {
+    fn from(metadata: Metadata) -> Self {
+        Version::new(
+            serde_json::to_string(&metadata).expect("Metadata's infallible Serialize impl failed"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_version_round_trip() {
+        let metadata = Metadata::new(ConnectionId::new(0), ConnectionId::new(1))
+            .with_address("cosmos1account".to_string());
+
+        let version = Version::from(metadata.clone());
+        assert_eq!(
+            version.as_str(),
+            r#"{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-1","address":"cosmos1account","encoding":"proto3","tx_type":"sdk_multi_msg"}"#
+        );
+        assert_eq!(Metadata::try_from(&version).unwrap(), metadata);
+        assert!(Metadata::try_from(&Version::ics20()).is_err());
+    }
+
+    #[test]
+    fn test_metadata_validation() {
+        let metadata = Metadata::new(ConnectionId::new(0), ConnectionId::new(1));
+        assert!(metadata
+            .validate(&ConnectionId::new(0), &ConnectionId::new(1))
+            .is_ok());
+        assert!(metadata
+            .validate(&ConnectionId::new(1), &ConnectionId::new(0))
+            .is_err());
+
+        let metadata = Metadata {
+            encoding: "json".to_string(),
+            ..metadata
+        };
+        assert!(metadata
+            .validate(&ConnectionId::new(0), &ConnectionId::new(1))
+            .is_err());
+    }
+}
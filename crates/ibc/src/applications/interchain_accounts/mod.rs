@@ -0,0 +1,74 @@
+//! ICS 27: Interchain Accounts allow a controller chain to register accounts on a host chain and
+//! to execute transactions with them, over one ordered channel per account.
+//!
+//! The [`controller`] callbacks open the channels and send the transactions of the account
+//! owners, while the [`host`] callbacks register the accounts and execute the transactions they
+//! receive. A chain may act as both.
+pub mod acknowledgement;
+pub mod context;
+pub mod controller;
+pub mod error;
+pub mod events;
+pub mod host;
+pub mod metadata;
+pub mod packet;
+
+use crate::core::ics24_host::identifier::PortId;
+use crate::prelude::*;
+
+use error::Error;
+
+/// Module identifier for the ICS27 controller.
+pub const CONTROLLER_MODULE_ID_STR: &str = "icacontroller";
+
+/// Prefix of the port identifiers that the ICS27 controller binds, one per account owner.
+pub const CONTROLLER_PORT_PREFIX: &str = "icacontroller-";
+
+/// Module identifier for the ICS27 host.
+pub const HOST_MODULE_ID_STR: &str = "icahost";
+
+/// The port identifier that the ICS27 host binds with.
+pub const HOST_PORT_ID_STR: &str = "icahost";
+
+/// ICS27 application current version.
+pub const VERSION: &str = "ics27-1";
+
+/// The protobuf encoding of the transactions, the only one supported.
+pub const ENCODING_PROTOBUF: &str = "proto3";
+
+/// The transaction type of the Cosmos SDK, i.e. a list of messages executed atomically.
+pub const TX_TYPE_SDK_MULTI_MSG: &str = "sdk_multi_msg";
+
+/// Returns the controller port of the given account owner, i.e. `icacontroller-{owner}`.
+pub fn controller_port_id(owner: &str) -> Result<PortId, Error> {
+    if owner.trim().is_empty() {
+        return Err(Error::empty_owner());
+    }
+    format!("{}{}", CONTROLLER_PORT_PREFIX, owner)
+        .parse()
+        .map_err(Error::invalid_controller_port)
+}
+
+/// Returns true iff `port_id` is the port of an account owner on the controller.
+pub fn is_controller_port(port_id: &PortId) -> bool {
+    port_id.as_str().len() > CONTROLLER_PORT_PREFIX.len()
+        && port_id.as_str().starts_with(CONTROLLER_PORT_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_controller_port_id() {
+        let port_id = controller_port_id("cosmos1owner").unwrap();
+        assert_eq!(port_id.as_str(), "icacontroller-cosmos1owner");
+        assert!(is_controller_port(&port_id));
+
+        assert!(controller_port_id("").is_err());
+        assert!(!is_controller_port(&PortId::transfer()));
+        assert!(!is_controller_port(
+            &CONTROLLER_PORT_PREFIX.parse().unwrap()
+        ));
+    }
+}
@@ -0,0 +1,157 @@
+use core::convert::TryFrom;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::applications::interchain_accounts::v1::{
+    CosmosTx as RawCosmosTx, InterchainAccountPacketData as RawPacketData, Type as RawType,
+};
+use ibc_proto::protobuf::Protobuf;
+use serde::{Deserialize, Serialize};
+
+use super::error::Error;
+use crate::prelude::*;
+
+/// The type of an interchain accounts packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Type {
+    #[serde(rename = "TYPE_UNSPECIFIED")]
+    Unspecified,
+    /// The packet carries a transaction to execute with the interchain account.
+    #[serde(rename = "TYPE_EXECUTE_TX")]
+    ExecuteTx,
+}
+
+/// The data of an ICS27 packet, encoded as JSON.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketData {
+    #[serde(rename = "type")]
+    pub packet_type: Type,
+    /// The protobuf encoding of a [`CosmosTx`].
+    #[serde(with = "crate::serializers::serde_base64")]
+    pub data: Vec<u8>,
+    #[serde(default)]
+    pub memo: String,
+}
+
+impl PacketData {
+    /// Returns the data of a packet executing `messages` on the host, atomically.
+    pub fn execute_tx(messages: Vec<Any>, memo: String) -> Self {
+        Self {
+            packet_type: Type::ExecuteTx,
+            data: CosmosTx { messages }
+                .encode_vec()
+                .expect("encoding a CosmosTx is infallible"),
+            memo,
+        }
+    }
+
+    /// Decodes the messages of an `ExecuteTx` packet.
+    pub fn messages(&self) -> Result<Vec<Any>, Error> {
+        if self.packet_type != Type::ExecuteTx {
+            return Err(Error::unsupported_packet_type());
+        }
+        let tx = CosmosTx::decode_vec(&self.data).map_err(Error::decode_cosmos_tx)?;
+        if tx.messages.is_empty() {
+            return Err(Error::empty_tx());
+        }
+
+        Ok(tx.messages)
+    }
+}
+
+impl From<PacketData> for RawPacketData {
+    fn from(data: PacketData) -> Self {
+        let packet_type = match data.packet_type {
+            Type::Unspecified => RawType::Unspecified,
+            Type::ExecuteTx => RawType::ExecuteTx,
+        };
+        Self {
+            r#type: packet_type as i32,
+            data: data.data,
+            memo: data.memo,
+        }
+    }
+}
+
+impl TryFrom<RawPacketData> for PacketData {
+    type Error = Error;
+
+    fn try_from(raw: RawPacketData) -> Result<Self, Self::Error> {
+        let packet_type = match RawType::from_i32(raw.r#type) {
+            Some(RawType::Unspecified) => Type::Unspecified,
+            Some(RawType::ExecuteTx) => Type::ExecuteTx,
+            None => return Err(Error::unsupported_packet_type()),
+        };
+        Ok(Self {
+            packet_type,
+            data: raw.data,
+            memo: raw.memo,
+        })
+    }
+}
+
+/// The messages of a transaction executed by an interchain account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CosmosTx {
+    pub messages: Vec<Any>,
+}
+
+impl Protobuf<RawCosmosTx> for CosmosTx {}
+
+impl From<RawCosmosTx> for CosmosTx {
+    fn from(raw: RawCosmosTx) -> Self {
+        Self {
+            messages: raw.messages,
+        }
+    }
+}
+
+impl From<CosmosTx> for RawCosmosTx {
+    fn from(tx: CosmosTx) -> Self {
+        Self {
+            messages: tx.messages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dummy_message() -> Any {
+        Any {
+            type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+            value: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_packet_data_json() {
+        let data = PacketData {
+            packet_type: Type::ExecuteTx,
+            data: vec![1],
+            memo: "memo".to_string(),
+        };
+        let json = r#"{"type":"TYPE_EXECUTE_TX","data":"AQ==","memo":"memo"}"#;
+
+        assert_eq!(serde_json::to_string(&data).unwrap(), json);
+        assert_eq!(serde_json::from_str::<PacketData>(json).unwrap(), data);
+    }
+
+    #[test]
+    fn test_packet_data_messages() {
+        let data = PacketData::execute_tx(vec![dummy_message()], String::new());
+        assert_eq!(data.messages().unwrap(), vec![dummy_message()]);
+
+        let raw = RawPacketData::from(data.clone());
+        assert_eq!(PacketData::try_from(raw).unwrap(), data);
+
+        let empty = PacketData::execute_tx(vec![], String::new());
+        assert!(empty.messages().is_err());
+
+        let unspecified = PacketData {
+            packet_type: Type::Unspecified,
+            ..data
+        };
+        assert!(unspecified.messages().is_err());
+    }
+}
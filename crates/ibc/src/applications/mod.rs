@@ -1,3 +1,10 @@
 //! Various packet encoding semantics which underpin the various types of transactions.
 
+pub mod fee;
+pub mod interchain_accounts;
+pub mod nft_transfer;
+#[cfg(any(test, feature = "unstable"))]
+pub mod packet_forward;
 pub mod transfer;
+#[cfg(any(test, feature = "unstable"))]
+pub mod transfer_hooks;
@@ -0,0 +1,221 @@
+use core::fmt::{Display, Error as FmtError, Formatter};
+use core::str::FromStr;
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle_encoding::hex;
+
+use super::error::Error;
+use crate::applications::transfer::{TracePath, TracePrefix};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
+
+/// Base class identifier, i.e. the identifier of an NFT class on the chain that created it.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Display)]
+#[serde(transparent)]
+pub struct BaseClassId(String);
+
+impl BaseClassId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for BaseClassId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            Err(Error::empty_base_class_id())
+        } else {
+            Ok(BaseClassId(s.to_owned()))
+        }
+    }
+}
+
+/// A class identifier with the trace of the channels the class went through, like the
+/// [`PrefixedDenom`](crate::applications::transfer::PrefixedDenom) of ICS20 tokens.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub struct PrefixedClassId {
+    /// A series of `{port-id}/{channel-id}`s for tracing the source of the class.
+    pub trace_path: TracePath,
+    /// Base class identifier of the relayed NFTs.
+    pub base_class_id: BaseClassId,
+}
+
+impl PrefixedClassId {
+    /// Removes the specified prefix from the trace path if there is a match, otherwise does nothing.
+    pub fn remove_trace_prefix(&mut self, prefix: &TracePrefix) {
+        self.trace_path.remove_prefix(prefix)
+    }
+
+    /// Adds the specified prefix to the trace path.
+    pub fn add_trace_prefix(&mut self, prefix: TracePrefix) {
+        self.trace_path.add_prefix(prefix)
+    }
+
+    /// Returns the hash of the full class path, i.e. the uppercase hex-encoded SHA256 of
+    /// `{trace_path}/{base_class_id}`.
+    pub fn hash(&self) -> String {
+        let hash = Sha256::digest(self.to_string().as_bytes());
+        String::from_utf8(hex::encode_upper(hash)).expect("hex encoding is valid UTF-8")
+    }
+
+    /// Returns the identifier of the class on the host, i.e. `ibc/{hash}` for voucher classes and
+    /// the base class identifier for the native ones.
+    pub fn voucher_class_id(&self) -> String {
+        if self.trace_path.is_empty() {
+            self.base_class_id.to_string()
+        } else {
+            format!("{}/{}", HASHED_CLASS_ID_PREFIX, self.hash())
+        }
+    }
+}
+
+/// The prefix of hashed voucher class identifiers, i.e. `ibc/{hash}`.
+pub const HASHED_CLASS_ID_PREFIX: &str = "ibc";
+
+/// Returns the hash of the hashed voucher class identifier `class_id`, or `None` if `class_id` is
+/// not of the form `ibc/{hash}`.
+pub fn parse_hashed_class_id(class_id: &str) -> Result<Option<&str>, Error> {
+    let hash = match class_id.split_once('/') {
+        Some((HASHED_CLASS_ID_PREFIX, hash)) => hash,
+        _ => return Ok(None),
+    };
+
+    let bytes = hex::decode_upper(hash)
+        .or_else(|_| hex::decode(hash))
+        .map_err(Error::parse_hex)?;
+    if bytes.len() != Sha256::output_size() {
+        return Err(Error::malformed_hash_class_id());
+    }
+
+    Ok(Some(hash))
+}
+
+/// Returns true if the class originally came from the sender chain, which then escrows its NFTs
+/// rather than burning them. See
+/// [`is_sender_chain_source`](crate::applications::transfer::is_sender_chain_source) for ICS20
+/// tokens.
+pub fn is_sender_chain_source(
+    source_port: PortId,
+    source_channel: ChannelId,
+    class_id: &PrefixedClassId,
+) -> bool {
+    !is_receiver_chain_source(source_port, source_channel, class_id)
+}
+
+/// Returns true if the class originally came from the receiving chain, which then unescrows its
+/// NFTs rather than minting vouchers of them.
+pub fn is_receiver_chain_source(
+    source_port: PortId,
+    source_channel: ChannelId,
+    class_id: &PrefixedClassId,
+) -> bool {
+    let prefix = TracePrefix::new(source_port, source_channel);
+    class_id.trace_path.starts_with(&prefix)
+}
+
+impl FromStr for PrefixedClassId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('/').collect();
+        let last_part = parts.pop().expect("split() returned an empty iterator");
+
+        let (base_class_id, trace_path) = {
+            if last_part == s {
+                (BaseClassId::from_str(s)?, TracePath::default())
+            } else {
+                let base_class_id = BaseClassId::from_str(last_part)?;
+                let trace_path = TracePath::try_from(parts)
+                    .map_err(|e| Error::invalid_class_trace(s.to_string(), e))?;
+                (base_class_id, trace_path)
+            }
+        };
+
+        Ok(Self {
+            trace_path,
+            base_class_id,
+        })
+    }
+}
+
+impl From<BaseClassId> for PrefixedClassId {
+    fn from(class_id: BaseClassId) -> Self {
+        Self {
+            trace_path: Default::default(),
+            base_class_id: class_id,
+        }
+    }
+}
+
+impl Display for PrefixedClassId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        if self.trace_path.is_empty() {
+            write!(f, "{}", self.base_class_id)
+        } else {
+            write!(f, "{}/{}", self.trace_path, self.base_class_id)
+        }
+    }
+}
+
+/// An NFT class, with the metadata that travels with its NFTs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NftClass {
+    pub class_id: PrefixedClassId,
+    pub uri: String,
+    pub data: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_class_id_trace() -> Result<(), Error> {
+        assert!(PrefixedClassId::from_str("").is_err(), "empty class id");
+        assert!(
+            PrefixedClassId::from_str("nft-transfer/channel-0/").is_err(),
+            "empty base class id with trace"
+        );
+        assert!(
+            PrefixedClassId::from_str("nft-transfer/kitties").is_err(),
+            "single trace with base class id"
+        );
+
+        let class_id = PrefixedClassId::from_str("nft-transfer/channel-0/kitties")?;
+        assert_eq!(
+            class_id,
+            PrefixedClassId {
+                trace_path: "nft-transfer/channel-0".parse().unwrap(),
+                base_class_id: "kitties".parse()?,
+            }
+        );
+        assert_eq!(class_id.to_string(), "nft-transfer/channel-0/kitties");
+
+        let mut native = PrefixedClassId::from_str("kitties")?;
+        assert_eq!(native.voucher_class_id(), "kitties");
+        native.add_trace_prefix(TracePrefix::new(PortId::nft_transfer(), ChannelId::new(0)));
+        assert_eq!(native, class_id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_voucher_class_id() -> Result<(), Error> {
+        let class_id = PrefixedClassId::from_str("nft-transfer/channel-0/kitties")?;
+        let voucher_class_id = class_id.voucher_class_id();
+        assert_eq!(voucher_class_id, format!("ibc/{}", class_id.hash()));
+        assert_eq!(
+            parse_hashed_class_id(&voucher_class_id)?,
+            Some(class_id.hash().as_str())
+        );
+
+        assert_eq!(parse_hashed_class_id("kitties")?, None);
+        assert!(parse_hashed_class_id("ibc/kitties").is_err(), "not a hash");
+
+        Ok(())
+    }
+}
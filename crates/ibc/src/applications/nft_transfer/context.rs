@@ -0,0 +1,306 @@
+use sha2::{Digest, Sha256};
+
+use super::error::Error;
+use crate::applications::nft_transfer::events::{AckEvent, RecvEvent, TimeoutEvent};
+use crate::applications::nft_transfer::packet::PacketData;
+use crate::applications::nft_transfer::relay::{process_recv_packet, refund_packet_nfts};
+use crate::applications::nft_transfer::{
+    parse_hashed_class_id, Nft, NftClass, PrefixedClassId, TokenId, VERSION,
+};
+use crate::applications::transfer::acknowledgement::Acknowledgement;
+use crate::core::ics04_channel::channel::{Counterparty, Order};
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics04_channel::handler::ModuleExtras;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::{ModuleOutputBuilder, OnRecvPacketAck};
+use crate::prelude::*;
+use crate::signer::Signer;
+
+/// Read access to the NFTs of the host and to the ICS721 state.
+///
+/// Classes and NFTs are identified by their [`PrefixedClassId`], i.e. by their full class path
+/// for the vouchers, which hosts may key by [`PrefixedClassId::voucher_class_id`].
+pub trait NftTransferReader: ChannelReader {
+    type AccountId: TryFrom<Signer> + PartialEq;
+
+    /// get_port returns the portID for the NFT transfer module.
+    fn get_port(&self) -> Result<PortId, Error>;
+
+    /// Returns the escrow account id for a port and channel combination. Cosmos SDK hosts derive
+    /// it with [`cosmos_adr028_escrow_address`].
+    fn get_channel_escrow_address(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Self::AccountId, Error>;
+
+    /// Parses the account id of the sender or receiver of a transfer.
+    fn parse_account(&self, address: &Signer) -> Result<Self::AccountId, Error> {
+        address
+            .clone()
+            .try_into()
+            .map_err(|_| Error::parse_account_failure())
+    }
+
+    /// Returns true iff send is enabled.
+    fn is_send_enabled(&self) -> bool;
+
+    /// Returns true iff receive is enabled.
+    fn is_receive_enabled(&self) -> bool;
+
+    /// Returns the class whose hash is `hash`, in uppercase hex, if the host minted vouchers of
+    /// it.
+    fn class_trace(&self, hash: &str) -> Option<PrefixedClassId>;
+
+    fn has_class_trace(&self, hash: &str) -> bool {
+        self.class_trace(hash).is_some()
+    }
+
+    /// Returns the class `class_id`, if it exists on the host.
+    fn get_nft_class(&self, class_id: &PrefixedClassId) -> Option<NftClass>;
+
+    /// Returns the NFT `token_id` of the class `class_id`, if it exists on the host.
+    fn get_nft(&self, class_id: &PrefixedClassId, token_id: &TokenId) -> Option<Nft>;
+
+    /// Returns the owner of the NFT `token_id` of the class `class_id`, if it exists on the host.
+    fn get_nft_owner(
+        &self,
+        class_id: &PrefixedClassId,
+        token_id: &TokenId,
+    ) -> Option<Self::AccountId>;
+
+    /// Resolves the class of the NFTs to transfer, given either as a hashed voucher class
+    /// identifier `ibc/{hash}` or as a full class path.
+    fn resolve_class_id(&self, class_id: &str) -> Result<PrefixedClassId, Error> {
+        match parse_hashed_class_id(class_id)? {
+            Some(hash) => self
+                .class_trace(&hash.to_uppercase())
+                .ok_or_else(Error::trace_not_found),
+            None => class_id.parse(),
+        }
+    }
+}
+
+/// Write access to the NFTs of the host and to the ICS721 state.
+pub trait NftTransferKeeper: NftTransferReader + ChannelKeeper {
+    /// Records the class `class_id` under its hash `hash`. Called when receiving the first
+    /// voucher of the class.
+    fn store_class_trace(&mut self, hash: String, class_id: PrefixedClassId) -> Result<(), Error>;
+
+    /// Creates the voucher class `class`, or updates its metadata if it already exists. Called
+    /// whenever vouchers of the class are received.
+    fn create_or_update_class(&mut self, class: NftClass) -> Result<(), Error>;
+
+    /// Transfers the NFT `token_id` of the class `class_id` from `from` to `to`, e.g. in and out
+    /// of the escrow account of a channel.
+    fn transfer_nft(
+        &mut self,
+        from: &Self::AccountId,
+        to: &Self::AccountId,
+        class_id: &PrefixedClassId,
+        token_id: &TokenId,
+    ) -> Result<(), Error>;
+
+    /// Mints the voucher `nft` to `owner`.
+    fn mint_nft(&mut self, owner: &Self::AccountId, nft: Nft) -> Result<(), Error>;
+
+    /// Burns the voucher `token_id` of the class `class_id`, owned by `owner`.
+    fn burn_nft(
+        &mut self,
+        owner: &Self::AccountId,
+        class_id: &PrefixedClassId,
+        token_id: &TokenId,
+    ) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies which the ICS721 module requires to be able to dispatch and
+/// process IBC messages.
+pub trait NftTransferContext: NftTransferKeeper + NftTransferReader {}
+
+// https://github.com/cosmos/cosmos-sdk/blob/master/docs/architecture/adr-028-public-key-addresses.md
+pub fn cosmos_adr028_escrow_address(port_id: &PortId, channel_id: &ChannelId) -> Vec<u8> {
+    let contents = format!("{}/{}", port_id, channel_id);
+
+    let mut hasher = Sha256::new();
+    hasher.update(VERSION.as_bytes());
+    hasher.update([0]);
+    hasher.update(contents.as_bytes());
+
+    let mut hash = hasher.finalize().to_vec();
+    hash.truncate(20);
+    hash
+}
+
+/// Opens an ICS721 channel. Hosts declare `Version::ics721()` as the version supported by their
+/// NFT transfer module (see `Module::supported_versions`), for the core to reject other versions
+/// before invoking the handshake callbacks.
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_init(
+    ctx: &mut impl NftTransferContext,
+    order: Order,
+    _connection_hops: &[ConnectionId],
+    port_id: &PortId,
+    _channel_id: &ChannelId,
+    _counterparty: &Counterparty,
+    _version: &Version,
+) -> Result<(ModuleExtras, Version), Error> {
+    if order != Order::Unordered {
+        return Err(Error::channel_not_unordered(order));
+    }
+    let bound_port = ctx.get_port()?;
+    if port_id != &bound_port {
+        return Err(Error::invalid_port(port_id.clone(), bound_port));
+    }
+
+    Ok((ModuleExtras::empty(), Version::ics721()))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_try(
+    _ctx: &mut impl NftTransferContext,
+    order: Order,
+    _connection_hops: &[ConnectionId],
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+    _counterparty: &Counterparty,
+    _counterparty_version: &Version,
+) -> Result<(ModuleExtras, Version), Error> {
+    if order != Order::Unordered {
+        return Err(Error::channel_not_unordered(order));
+    }
+    Ok((ModuleExtras::empty(), Version::ics721()))
+}
+
+pub fn on_chan_open_ack(
+    _ctx: &mut impl NftTransferContext,
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+    _counterparty_version: &Version,
+) -> Result<ModuleExtras, Error> {
+    Ok(ModuleExtras::empty())
+}
+
+pub fn on_chan_open_confirm(
+    _ctx: &mut impl NftTransferContext,
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+) -> Result<ModuleExtras, Error> {
+    Ok(ModuleExtras::empty())
+}
+
+pub fn on_chan_close_init(
+    _ctx: &mut impl NftTransferContext,
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+) -> Result<ModuleExtras, Error> {
+    Err(Error::cant_close_channel())
+}
+
+pub fn on_chan_close_confirm(
+    _ctx: &mut impl NftTransferContext,
+    _port_id: &PortId,
+    _channel_id: &ChannelId,
+) -> Result<ModuleExtras, Error> {
+    Ok(ModuleExtras::empty())
+}
+
+/// Decodes the data of an ICS721 packet.
+fn packet_data(packet: &Packet) -> Result<PacketData, Error> {
+    let data = serde_json::from_slice::<PacketData>(&packet.data)
+        .map_err(|_| Error::packet_data_deserialization())?;
+    data.validate_basic()?;
+    Ok(data)
+}
+
+pub fn on_recv_packet<Ctx: 'static + NftTransferContext>(
+    ctx: &Ctx,
+    output: &mut ModuleOutputBuilder,
+    packet: &Packet,
+    _relayer: &Signer,
+) -> OnRecvPacketAck {
+    let data = match packet_data(packet) {
+        Ok(data) => data,
+        Err(e) => return OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e))),
+    };
+
+    let (ack, error) = match process_recv_packet(ctx, output, packet, &data) {
+        Ok(write_fn) => (
+            OnRecvPacketAck::Successful(Box::new(Acknowledgement::success()), write_fn),
+            None,
+        ),
+        Err(e) => {
+            let error = e.to_string();
+            (
+                OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e))),
+                Some(error),
+            )
+        }
+    };
+
+    let recv_event = RecvEvent {
+        sender: data.sender,
+        receiver: data.receiver,
+        class_id: data.class_id,
+        token_ids: data.token_ids,
+        memo: data.memo,
+        success: ack.is_successful(),
+        error,
+    };
+    output.emit(recv_event.into());
+
+    ack
+}
+
+/// Refunds the NFTs of the packet if the counterparty failed to receive it. The acknowledgements
+/// of ICS721 packets are those of ICS20 packets.
+pub fn on_acknowledgement_packet(
+    ctx: &mut impl NftTransferContext,
+    output: &mut ModuleOutputBuilder,
+    packet: &Packet,
+    acknowledgement: &GenericAcknowledgement,
+    _relayer: &Signer,
+) -> Result<(), Error> {
+    let data = packet_data(packet)?;
+    let acknowledgement =
+        Acknowledgement::try_from(acknowledgement).map_err(|_| Error::ack_deserialization())?;
+
+    if matches!(acknowledgement, Acknowledgement::Error(_)) {
+        refund_packet_nfts(ctx, packet, &data)?;
+    }
+
+    let ack_event = AckEvent {
+        sender: data.sender,
+        receiver: data.receiver,
+        class_id: data.class_id,
+        token_ids: data.token_ids,
+        memo: data.memo,
+        acknowledgement,
+    };
+    output.emit(ack_event.into());
+
+    Ok(())
+}
+
+pub fn on_timeout_packet(
+    ctx: &mut impl NftTransferContext,
+    output: &mut ModuleOutputBuilder,
+    packet: &Packet,
+    _relayer: &Signer,
+) -> Result<(), Error> {
+    let data = packet_data(packet)?;
+
+    refund_packet_nfts(ctx, packet, &data)?;
+
+    let timeout_event = TimeoutEvent {
+        refund_receiver: data.sender,
+        refund_class_id: data.class_id,
+        refund_token_ids: data.token_ids,
+        memo: data.memo,
+    };
+    output.emit(timeout_event.into());
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use flex_error::{define_error, DisplayOnly, TraceError};
+use ibc_proto::protobuf::Error as TendermintProtoError;
+use subtle_encoding::Error as EncodingError;
+
+use super::{PrefixedClassId, TokenId};
+use crate::applications::transfer::error::Error as TransferError;
+use crate::core::ics04_channel::channel::Order;
+use crate::core::ics04_channel::error as channel_error;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::error::ValidationError;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::core::ics26_routing::context::{AckError, INTERNAL_ACK_ERROR_CODE};
+use crate::prelude::*;
+use crate::signer::SignerError;
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        Ics04Channel
+            [ channel_error::Error ]
+            | _ | { "Ics04 channel error" },
+
+        DestinationChannelNotFound
+            { port_id: PortId, channel_id: ChannelId }
+            | e | { format_args!("destination channel not found in the counterparty of port_id {0} and channel_id {1} ", e.port_id, e.channel_id) },
+
+        InvalidPortId
+            { context: String }
+            [ ValidationError ]
+            | _ | { "invalid port identifier" },
+
+        InvalidChannelId
+            { context: String }
+            [ ValidationError ]
+            | _ | { "invalid channel identifier" },
+
+        InvalidPacketTimeoutHeight
+            { context: String }
+            | _ | { "invalid packet timeout height value" },
+
+        InvalidPacketTimeoutTimestamp
+            { timestamp: u64 }
+            | _ | { "invalid packet timeout timestamp value" },
+
+        EmptyBaseClassId
+            | _ | { "base class identifier is empty" },
+
+        InvalidClassTrace
+            { class_id: String }
+            [ DisplayOnly<TransferError> ]
+            | e | { format_args!("invalid trace in class identifier {0}", e.class_id) },
+
+        MalformedHashClassId
+            | _ | { "hashed class identifier must be of the form 'ibc/{Hash}'" },
+
+        ParseHex
+            [ TraceError<EncodingError> ]
+            | _ | { "invalid hex string" },
+
+        TraceNotFound
+            | _ | { "no trace associated with specified hash" },
+
+        EmptyTokenId
+            | _ | { "token identifier is empty" },
+
+        EmptyTokenIds
+            | _ | { "no token identifiers in the packet" },
+
+        DuplicateTokenId
+            { token_id: TokenId }
+            | e | { format_args!("duplicate token identifier {0}", e.token_id) },
+
+        TokenMetadataMismatch
+            { field: String, len: usize, token_ids: usize }
+            | e | { format_args!("expected {0} to have {1} entries, one per token, got {2}", e.field, e.token_ids, e.len) },
+
+        ClassNotFound
+            { class_id: PrefixedClassId }
+            | e | { format_args!("class {0} not found", e.class_id) },
+
+        NftNotFound
+            { class_id: PrefixedClassId, token_id: TokenId }
+            | e | { format_args!("token {0} of class {1} not found", e.token_id, e.class_id) },
+
+        NftAlreadyExists
+            { class_id: PrefixedClassId, token_id: TokenId }
+            | e | { format_args!("token {0} of class {1} already exists", e.token_id, e.class_id) },
+
+        NotNftOwner
+            { class_id: PrefixedClassId, token_id: TokenId }
+            | e | { format_args!("token {0} of class {1} is not owned by the sender or escrow account", e.token_id, e.class_id) },
+
+        ChannelNotUnordered
+            { order: Order }
+            | e | { format_args!("expected '{0}' channel, got '{1}'", Order::Unordered, e.order) },
+
+        InvalidVersion
+            { version: Version }
+            | e | { format_args!("expected version '{0}', got '{1}'", Version::ics721(), e.version) },
+
+        InvalidCounterpartyVersion
+            { version: Version }
+            | e | { format_args!("expected counterparty version '{0}', got '{1}'", Version::ics721(), e.version) },
+
+        InvalidPort
+            { port_id: PortId, exp_port_id: PortId }
+            | e | { format_args!("invalid port: '{0}', expected '{1}'", e.port_id, e.exp_port_id) },
+
+        CantCloseChannel
+            | _ | { "channel cannot be closed" },
+
+        PacketDataDeserialization
+            | _ | { "failed to deserialize packet data" },
+
+        AckDeserialization
+            | _ | { "failed to deserialize acknowledgement" },
+
+        ReceiveDisabled
+            | _ | { "receive is not enabled" },
+
+        SendDisabled
+            | _ | { "send is not enabled" },
+
+        Signer
+            [ SignerError ]
+            | _ | { "failed to parse signer" },
+
+        ParseAccountFailure
+            | _ | { "failed to parse as AccountId" },
+
+        DecodeRawMsg
+            [ TraceError<TendermintProtoError> ]
+            | _ | { "error decoding raw msg" },
+
+        UnknownMsgType
+            { msg_type: String }
+            | e | { format_args!("unknown msg type: {0}", e.msg_type) },
+    }
+}
+
+/// The errors of the NFT keepers of the host are internal, unless they are one of the public
+/// reasons below.
+impl AckError for Error {
+    fn ack_error_code(&self) -> u32 {
+        match self.detail() {
+            ErrorDetail::InvalidPacketTimeoutHeight(_)
+            | ErrorDetail::InvalidPacketTimeoutTimestamp(_) => 2,
+            ErrorDetail::EmptyBaseClassId(_)
+            | ErrorDetail::InvalidClassTrace(_)
+            | ErrorDetail::MalformedHashClassId(_)
+            | ErrorDetail::ParseHex(_) => 3,
+            ErrorDetail::PacketDataDeserialization(_)
+            | ErrorDetail::EmptyTokenId(_)
+            | ErrorDetail::EmptyTokenIds(_)
+            | ErrorDetail::DuplicateTokenId(_)
+            | ErrorDetail::TokenMetadataMismatch(_) => 4,
+            ErrorDetail::InvalidVersion(_) | ErrorDetail::InvalidCounterpartyVersion(_) => 5,
+            ErrorDetail::TraceNotFound(_) => 7,
+            ErrorDetail::ClassNotFound(_) | ErrorDetail::NftNotFound(_) => 8,
+            ErrorDetail::NotNftOwner(_) => 9,
+            ErrorDetail::NftAlreadyExists(_) => 13,
+            ErrorDetail::SendDisabled(_) => 10,
+            ErrorDetail::ReceiveDisabled(_) => 11,
+            ErrorDetail::Signer(_) | ErrorDetail::ParseAccountFailure(_) => 12,
+            _ => INTERNAL_ACK_ERROR_CODE,
+        }
+    }
+}
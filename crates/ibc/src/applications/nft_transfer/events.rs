@@ -0,0 +1,185 @@
+use crate::applications::nft_transfer::{PrefixedClassId, TokenId, MODULE_ID_STR};
+use crate::applications::transfer::acknowledgement::Acknowledgement;
+use crate::events::ModuleEvent;
+use crate::prelude::*;
+use crate::signer::Signer;
+
+const EVENT_TYPE_PACKET: &str = "non_fungible_token_packet";
+const EVENT_TYPE_TIMEOUT: &str = "timeout";
+const EVENT_TYPE_CLASS_TRACE: &str = "class_trace";
+const EVENT_TYPE_TRANSFER: &str = "ibc_nft_transfer";
+
+/// The token identifiers of a packet, as a comma separated list.
+fn token_ids_attribute(token_ids: &[TokenId]) -> String {
+    token_ids
+        .iter()
+        .map(TokenId::as_str)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Emitted on the receiving chain once a packet was processed, successfully or not.
+pub struct RecvEvent {
+    pub sender: Signer,
+    pub receiver: Signer,
+    pub class_id: PrefixedClassId,
+    pub token_ids: Vec<TokenId>,
+    pub memo: String,
+    pub success: bool,
+    /// The reason the packet could not be processed, if `success` is false.
+    pub error: Option<String>,
+}
+
+impl From<RecvEvent> for ModuleEvent {
+    fn from(ev: RecvEvent) -> Self {
+        let RecvEvent {
+            sender,
+            receiver,
+            class_id,
+            token_ids,
+            memo,
+            success,
+            error,
+        } = ev;
+        let mut ev = Self {
+            kind: EVENT_TYPE_PACKET.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("module", MODULE_ID_STR).into(),
+                ("sender", sender).into(),
+                ("receiver", receiver).into(),
+                ("class_id", class_id).into(),
+                ("token_ids", token_ids_attribute(&token_ids)).into(),
+                ("memo", memo).into(),
+                ("success", success).into(),
+            ],
+        };
+        if let Some(error) = error {
+            ev.attributes.push(("error", error).into());
+        }
+        ev
+    }
+}
+
+/// Emitted on the sending chain once an acknowledgement was processed.
+pub struct AckEvent {
+    pub sender: Signer,
+    pub receiver: Signer,
+    pub class_id: PrefixedClassId,
+    pub token_ids: Vec<TokenId>,
+    pub memo: String,
+    pub acknowledgement: Acknowledgement,
+}
+
+impl From<AckEvent> for ModuleEvent {
+    fn from(ev: AckEvent) -> Self {
+        let AckEvent {
+            sender,
+            receiver,
+            class_id,
+            token_ids,
+            memo,
+            acknowledgement,
+        } = ev;
+        let success = matches!(acknowledgement, Acknowledgement::Success(_));
+        Self {
+            kind: EVENT_TYPE_PACKET.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("module", MODULE_ID_STR).into(),
+                ("sender", sender).into(),
+                ("receiver", receiver).into(),
+                ("class_id", class_id).into(),
+                ("token_ids", token_ids_attribute(&token_ids)).into(),
+                ("memo", memo).into(),
+                ("acknowledgement", acknowledgement).into(),
+                ("success", success).into(),
+            ],
+        }
+    }
+}
+
+/// Emitted on the sending chain once the NFTs of a timed out packet were refunded.
+pub struct TimeoutEvent {
+    pub refund_receiver: Signer,
+    pub refund_class_id: PrefixedClassId,
+    pub refund_token_ids: Vec<TokenId>,
+    pub memo: String,
+}
+
+impl From<TimeoutEvent> for ModuleEvent {
+    fn from(ev: TimeoutEvent) -> Self {
+        let TimeoutEvent {
+            refund_receiver,
+            refund_class_id,
+            refund_token_ids,
+            memo,
+        } = ev;
+        Self {
+            kind: EVENT_TYPE_TIMEOUT.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("module", MODULE_ID_STR).into(),
+                ("refund_receiver", refund_receiver).into(),
+                ("refund_class_id", refund_class_id).into(),
+                ("refund_token_ids", token_ids_attribute(&refund_token_ids)).into(),
+                ("memo", memo).into(),
+            ],
+        }
+    }
+}
+
+/// Emitted on the receiving chain when the first voucher of a class is minted.
+pub struct ClassTraceEvent {
+    pub trace_hash: String,
+    pub class_id: PrefixedClassId,
+}
+
+impl From<ClassTraceEvent> for ModuleEvent {
+    fn from(ev: ClassTraceEvent) -> Self {
+        let ClassTraceEvent {
+            trace_hash,
+            class_id,
+        } = ev;
+        Self {
+            kind: EVENT_TYPE_CLASS_TRACE.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("trace_hash", trace_hash).into(),
+                ("class_id", class_id).into(),
+            ],
+        }
+    }
+}
+
+/// Emitted on the sending chain for every `MsgTransfer`.
+pub struct TransferEvent {
+    pub sender: Signer,
+    pub receiver: Signer,
+    pub class_id: PrefixedClassId,
+    pub token_ids: Vec<TokenId>,
+    pub memo: String,
+}
+
+impl From<TransferEvent> for ModuleEvent {
+    fn from(ev: TransferEvent) -> Self {
+        let TransferEvent {
+            sender,
+            receiver,
+            class_id,
+            token_ids,
+            memo,
+        } = ev;
+        Self {
+            kind: EVENT_TYPE_TRANSFER.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("sender", sender).into(),
+                ("receiver", receiver).into(),
+                ("class_id", class_id).into(),
+                ("token_ids", token_ids_attribute(&token_ids)).into(),
+                ("memo", memo).into(),
+            ],
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! ICS 721: Non-Fungible Token Transfer allows NFTs to be transferred between chains, with the
+//! classes and tokens escrowed on the chain they come from and minted as vouchers on the others,
+//! like ICS20 tokens.
+pub mod class;
+pub mod context;
+pub mod error;
+pub mod events;
+pub mod msgs;
+pub mod packet;
+pub mod relay;
+pub mod token;
+
+pub use class::*;
+pub use token::*;
+
+/// Module identifier for the ICS721 application.
+pub const MODULE_ID_STR: &str = "nfttransfer";
+
+/// The port identifier that the ICS721 applications typically bind with.
+pub const PORT_ID_STR: &str = "nft-transfer";
+
+/// ICS721 application current version.
+pub const VERSION: &str = "ics721-1";
@@ -0,0 +1,235 @@
+//! This is the definition of the NFT transfer message that an application submits to a chain.
+
+use crate::prelude::*;
+
+use alloc::collections::BTreeSet;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::core::client::v1::Height as RawHeight;
+use ibc_proto::protobuf::Protobuf;
+
+use crate::applications::nft_transfer::error::Error;
+use crate::applications::nft_transfer::{parse_hashed_class_id, PrefixedClassId, TokenId};
+use crate::core::ics04_channel::timeout::TimeoutHeight;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::signer::Signer;
+use crate::timestamp::Timestamp;
+use crate::tx_msg::Msg;
+
+pub const TYPE_URL: &str = "/ibc.applications.nft_transfer.v1.MsgTransfer";
+
+/// The protobuf encoding of [`MsgTransfer`], which `ibc-proto` does not provide.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RawMsgTransfer {
+    #[prost(string, tag = "1")]
+    pub source_port: String,
+    #[prost(string, tag = "2")]
+    pub source_channel: String,
+    #[prost(string, tag = "3")]
+    pub class_id: String,
+    #[prost(string, repeated, tag = "4")]
+    pub token_ids: Vec<String>,
+    #[prost(string, tag = "5")]
+    pub sender: String,
+    #[prost(string, tag = "6")]
+    pub receiver: String,
+    #[prost(message, optional, tag = "7")]
+    pub timeout_height: Option<RawHeight>,
+    #[prost(uint64, tag = "8")]
+    pub timeout_timestamp: u64,
+    #[prost(string, tag = "9")]
+    pub memo: String,
+}
+
+/// Message used to build an ICS721 NFT transfer packet, like the ICS20
+/// [`MsgTransfer`](crate::applications::transfer::msgs::transfer::MsgTransfer).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgTransfer {
+    /// the port on which the packet will be sent
+    pub source_port: PortId,
+    /// the channel by which the packet will be sent
+    pub source_channel: ChannelId,
+    /// the class of the NFTs, as either a hashed voucher class identifier `ibc/{hash}` or a full
+    /// class path
+    pub class_id: String,
+    /// the NFTs of the class to be transferred
+    pub token_ids: Vec<TokenId>,
+    /// the sender address
+    pub sender: Signer,
+    /// the recipient address on the destination chain
+    pub receiver: Signer,
+    /// Timeout height relative to the current block height.
+    /// The timeout is disabled when set to None.
+    pub timeout_height: TimeoutHeight,
+    /// Timeout timestamp relative to the current block timestamp.
+    /// The timeout is disabled when set to 0.
+    pub timeout_timestamp: Timestamp,
+    /// optional memo, carried in the packet for the destination chain
+    pub memo: String,
+}
+
+impl Msg for MsgTransfer {
+    type ValidationError = Error;
+    type Raw = RawMsgTransfer;
+
+    fn route(&self) -> String {
+        crate::keys::ROUTER_KEY.to_string()
+    }
+
+    fn type_url(&self) -> String {
+        TYPE_URL.to_string()
+    }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        // the class may be identified by a hashed voucher class identifier, resolved by the host
+        if parse_hashed_class_id(&self.class_id)?.is_none() {
+            self.class_id.parse::<PrefixedClassId>()?;
+        }
+        if self.token_ids.is_empty() {
+            return Err(Error::empty_token_ids());
+        }
+        let mut token_ids = BTreeSet::new();
+        for token_id in &self.token_ids {
+            if !token_ids.insert(token_id) {
+                return Err(Error::duplicate_token_id(token_id.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<RawMsgTransfer> for MsgTransfer {
+    type Error = Error;
+
+    fn try_from(raw_msg: RawMsgTransfer) -> Result<Self, Self::Error> {
+        let timeout_timestamp = Timestamp::from_nanoseconds(raw_msg.timeout_timestamp)
+            .map_err(|_| Error::invalid_packet_timeout_timestamp(raw_msg.timeout_timestamp))?;
+
+        let timeout_height: TimeoutHeight = raw_msg.timeout_height.try_into().map_err(|e| {
+            Error::invalid_packet_timeout_height(format!("invalid timeout height {}", e))
+        })?;
+
+        let msg = MsgTransfer {
+            source_port: raw_msg
+                .source_port
+                .parse()
+                .map_err(|e| Error::invalid_port_id(raw_msg.source_port.clone(), e))?,
+            source_channel: raw_msg
+                .source_channel
+                .parse()
+                .map_err(|e| Error::invalid_channel_id(raw_msg.source_channel.clone(), e))?,
+            class_id: raw_msg.class_id,
+            token_ids: raw_msg
+                .token_ids
+                .iter()
+                .map(|token_id| token_id.parse())
+                .collect::<Result<_, _>>()?,
+            sender: raw_msg.sender.parse().map_err(Error::signer)?,
+            receiver: raw_msg.receiver.parse().map_err(Error::signer)?,
+            timeout_height,
+            timeout_timestamp,
+            memo: raw_msg.memo,
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
+    }
+}
+
+impl From<MsgTransfer> for RawMsgTransfer {
+    fn from(domain_msg: MsgTransfer) -> Self {
+        RawMsgTransfer {
+            source_port: domain_msg.source_port.to_string(),
+            source_channel: domain_msg.source_channel.to_string(),
+            class_id: domain_msg.class_id,
+            token_ids: domain_msg
+                .token_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            sender: domain_msg.sender.to_string(),
+            receiver: domain_msg.receiver.to_string(),
+            timeout_height: domain_msg.timeout_height.into(),
+            timeout_timestamp: domain_msg.timeout_timestamp.nanoseconds(),
+            memo: domain_msg.memo,
+        }
+    }
+}
+
+impl Protobuf<RawMsgTransfer> for MsgTransfer {}
+
+impl TryFrom<Any> for MsgTransfer {
+    type Error = Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        match raw.type_url.as_str() {
+            TYPE_URL => MsgTransfer::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+            _ => Err(Error::unknown_msg_type(raw.type_url)),
+        }
+    }
+}
+
+impl From<MsgTransfer> for Any {
+    fn from(msg: MsgTransfer) -> Self {
+        Self {
+            type_url: TYPE_URL.to_string(),
+            value: msg
+                .encode_vec()
+                .expect("encoding to `Any` from `MsgTranfer`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_msg_transfer() -> RawMsgTransfer {
+        RawMsgTransfer {
+            source_port: "nft-transfer".to_string(),
+            source_channel: "channel-0".to_string(),
+            class_id: "kitties".to_string(),
+            token_ids: vec!["kitty-1".to_string(), "kitty-2".to_string()],
+            sender: "sender".to_string(),
+            receiver: "receiver".to_string(),
+            timeout_height: None,
+            timeout_timestamp: 0,
+            memo: String::new(),
+        }
+    }
+
+    #[test]
+    fn msg_transfer_validate_basic() {
+        let raw = raw_msg_transfer();
+        let msg = MsgTransfer::try_from(raw.clone()).unwrap();
+        assert_eq!(
+            MsgTransfer::try_from(RawMsgTransfer::from(msg.clone())).unwrap(),
+            msg
+        );
+
+        let hashed_class_id = RawMsgTransfer {
+            class_id: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+                .to_string(),
+            ..raw.clone()
+        };
+        assert!(MsgTransfer::try_from(hashed_class_id).is_ok());
+
+        let bad_class_id = RawMsgTransfer {
+            class_id: "nft-transfer/kitties".to_string(),
+            ..raw.clone()
+        };
+        assert!(MsgTransfer::try_from(bad_class_id).is_err());
+
+        let no_tokens = RawMsgTransfer {
+            token_ids: vec![],
+            ..raw.clone()
+        };
+        assert!(MsgTransfer::try_from(no_tokens).is_err());
+
+        let duplicate_tokens = RawMsgTransfer {
+            token_ids: vec!["kitty-1".to_string(), "kitty-1".to_string()],
+            ..raw
+        };
+        assert!(MsgTransfer::try_from(duplicate_tokens).is_err());
+    }
+}
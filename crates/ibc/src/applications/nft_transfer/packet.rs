@@ -0,0 +1,188 @@
+use alloc::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::Error;
+use super::{Nft, NftClass, PrefixedClassId, TokenId};
+use crate::prelude::*;
+use crate::serializers::serde_string;
+use crate::signer::Signer;
+
+/// The data of an ICS721 packet, encoded as JSON.
+///
+/// The URIs and data of the tokens are either empty or given for every token, in the order of
+/// their identifiers. Empty optional fields are not encoded, as by the ICS721 modules of the
+/// Cosmos SDK chains.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PacketData {
+    #[serde(with = "serde_string")]
+    pub class_id: PrefixedClassId,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub class_uri: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub class_data: String,
+    pub token_ids: Vec<TokenId>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_uris: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_data: Vec<String>,
+    pub sender: Signer,
+    pub receiver: Signer,
+    /// Arbitrary data for the receiving chain, e.g. instructions for middlewares.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub memo: String,
+}
+
+impl PacketData {
+    /// Returns the data of a packet transferring `nfts` of `class`.
+    pub fn new(class: NftClass, nfts: Vec<Nft>, sender: Signer, receiver: Signer) -> Self {
+        let token_uris = if nfts.iter().all(|nft| nft.uri.is_empty()) {
+            vec![]
+        } else {
+            nfts.iter().map(|nft| nft.uri.clone()).collect()
+        };
+        let token_data = if nfts.iter().all(|nft| nft.data.is_empty()) {
+            vec![]
+        } else {
+            nfts.iter().map(|nft| nft.data.clone()).collect()
+        };
+
+        Self {
+            class_id: class.class_id,
+            class_uri: class.uri,
+            class_data: class.data,
+            token_ids: nfts.into_iter().map(|nft| nft.token_id).collect(),
+            token_uris,
+            token_data,
+            sender,
+            receiver,
+            memo: String::new(),
+        }
+    }
+
+    pub fn with_memo(self, memo: String) -> Self {
+        Self { memo, ..self }
+    }
+
+    /// Checks that the packet transfers at least one token, without duplicates, and that the
+    /// URIs and data of the tokens, if any, are given for every token.
+    pub fn validate_basic(&self) -> Result<(), Error> {
+        if self.token_ids.is_empty() {
+            return Err(Error::empty_token_ids());
+        }
+        let mut token_ids = BTreeSet::new();
+        for token_id in &self.token_ids {
+            if token_id.as_str().trim().is_empty() {
+                return Err(Error::empty_token_id());
+            }
+            if !token_ids.insert(token_id) {
+                return Err(Error::duplicate_token_id(token_id.clone()));
+            }
+        }
+
+        let metadata = [
+            ("tokenUris", self.token_uris.len()),
+            ("tokenData", self.token_data.len()),
+        ];
+        for (field, len) in metadata {
+            if len != 0 && len != self.token_ids.len() {
+                return Err(Error::token_metadata_mismatch(
+                    field.to_string(),
+                    len,
+                    self.token_ids.len(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the class of the packet, under the identifier `class_id`.
+    pub fn class(&self, class_id: PrefixedClassId) -> NftClass {
+        NftClass {
+            class_id,
+            uri: self.class_uri.clone(),
+            data: self.class_data.clone(),
+        }
+    }
+
+    /// Returns the tokens of the packet, of the class `class_id`.
+    pub fn nfts(&self, class_id: &PrefixedClassId) -> Vec<Nft> {
+        self.token_ids
+            .iter()
+            .enumerate()
+            .map(|(i, token_id)| Nft {
+                class_id: class_id.clone(),
+                token_id: token_id.clone(),
+                uri: self.token_uris.get(i).cloned().unwrap_or_default(),
+                data: self.token_data.get(i).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet_data(token_ids: &[&str]) -> PacketData {
+        PacketData {
+            class_id: "nft-transfer/channel-0/kitties".parse().unwrap(),
+            class_uri: String::new(),
+            class_data: String::new(),
+            token_ids: token_ids.iter().map(|id| id.parse().unwrap()).collect(),
+            token_uris: vec![],
+            token_data: vec![],
+            sender: "sender".parse().unwrap(),
+            receiver: "receiver".parse().unwrap(),
+            memo: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_packet_data_json() {
+        let data = PacketData {
+            class_uri: "https://kitties.io".to_string(),
+            token_uris: vec!["https://kitties.io/1".to_string()],
+            ..packet_data(&["kitty-1"])
+        };
+        let json = serde_json::to_string(&data).unwrap();
+        assert_eq!(
+            json,
+            r#"{"classId":"nft-transfer/channel-0/kitties","classUri":"https://kitties.io","tokenIds":["kitty-1"],"tokenUris":["https://kitties.io/1"],"sender":"sender","receiver":"receiver"}"#
+        );
+        assert_eq!(serde_json::from_str::<PacketData>(&json).unwrap(), data);
+
+        let nfts = data.nfts(&data.class_id);
+        assert_eq!(nfts[0].uri, "https://kitties.io/1");
+        assert_eq!(nfts[0].data, "");
+    }
+
+    #[test]
+    fn test_packet_data_validation() {
+        assert!(packet_data(&["kitty-1", "kitty-2"])
+            .validate_basic()
+            .is_ok());
+        assert!(packet_data(&[]).validate_basic().is_err(), "no tokens");
+        assert!(
+            packet_data(&["kitty-1", "kitty-1"])
+                .validate_basic()
+                .is_err(),
+            "duplicate tokens"
+        );
+        assert!(
+            serde_json::from_str::<PacketData>(
+                r#"{"classId":"","tokenIds":["kitty-1"],"sender":"sender","receiver":"receiver"}"#
+            )
+            .is_err(),
+            "empty class"
+        );
+
+        let data = PacketData {
+            token_data: vec!["{}".to_string()],
+            ..packet_data(&["kitty-1", "kitty-2"])
+        };
+        assert!(data.validate_basic().is_err(), "missing token data");
+    }
+}
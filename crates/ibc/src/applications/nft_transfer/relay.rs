@@ -0,0 +1,442 @@
+//! This module implements the processing logic for ICS721 (NFT transfer) messages and packets.
+use crate::applications::nft_transfer::context::NftTransferContext;
+use crate::applications::nft_transfer::error::Error;
+use crate::applications::nft_transfer::events::{ClassTraceEvent, TransferEvent};
+use crate::applications::nft_transfer::msgs::transfer::MsgTransfer;
+use crate::applications::nft_transfer::packet::PacketData;
+use crate::applications::nft_transfer::{is_receiver_chain_source, is_sender_chain_source};
+use crate::applications::transfer::TracePrefix;
+use crate::core::ics04_channel::handler::send_packet::send_packet;
+use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics26_routing::context::{ModuleOutputBuilder, WriteFn};
+use crate::events::ModuleEvent;
+use crate::handler::{HandlerOutput, HandlerOutputBuilder};
+use crate::prelude::*;
+
+/// This function handles the NFT transfer sending logic. The class of the NFTs is resolved with
+/// [`NftTransferReader::resolve_class_id`](crate::applications::nft_transfer::context::NftTransferReader::resolve_class_id).
+/// The NFTs are escrowed if the class comes from this chain, and burnt otherwise.
+/// If this method returns an error, the runtime is expected to rollback all state modifications to
+/// the `Ctx` caused by all messages from the transaction that this `msg` is a part of.
+pub fn send_nft_transfer<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutputBuilder<()>,
+    msg: MsgTransfer,
+) -> Result<(), Error>
+where
+    Ctx: NftTransferContext,
+{
+    if !ctx.is_send_enabled() {
+        return Err(Error::send_disabled());
+    }
+
+    let source_channel_end = ctx
+        .channel_end(&msg.source_port, &msg.source_channel)
+        .map_err(Error::ics04_channel)?;
+
+    let destination_port = source_channel_end.counterparty().port_id().clone();
+    let destination_channel = source_channel_end
+        .counterparty()
+        .channel_id()
+        .ok_or_else(|| {
+            Error::destination_channel_not_found(
+                msg.source_port.clone(),
+                msg.source_channel.clone(),
+            )
+        })?
+        .clone();
+
+    // get the next sequence
+    let sequence = ctx
+        .get_next_sequence_send(&msg.source_port, &msg.source_channel)
+        .map_err(Error::ics04_channel)?;
+
+    let class_id = ctx.resolve_class_id(&msg.class_id)?;
+    let class = ctx
+        .get_nft_class(&class_id)
+        .ok_or_else(|| Error::class_not_found(class_id.clone()))?;
+    let sender = ctx.parse_account(&msg.sender)?;
+
+    let mut nfts = Vec::with_capacity(msg.token_ids.len());
+    for token_id in &msg.token_ids {
+        let nft = ctx
+            .get_nft(&class_id, token_id)
+            .ok_or_else(|| Error::nft_not_found(class_id.clone(), token_id.clone()))?;
+        if ctx.get_nft_owner(&class_id, token_id).as_ref() != Some(&sender) {
+            return Err(Error::not_nft_owner(class_id, token_id.clone()));
+        }
+        nfts.push(nft);
+    }
+
+    if is_sender_chain_source(
+        msg.source_port.clone(),
+        msg.source_channel.clone(),
+        &class_id,
+    ) {
+        let escrow_address =
+            ctx.get_channel_escrow_address(&msg.source_port, &msg.source_channel)?;
+        for token_id in &msg.token_ids {
+            ctx.transfer_nft(&sender, &escrow_address, &class_id, token_id)?;
+        }
+    } else {
+        for token_id in &msg.token_ids {
+            ctx.burn_nft(&sender, &class_id, token_id)?;
+        }
+    }
+
+    let data = {
+        let data = PacketData::new(class, nfts, msg.sender.clone(), msg.receiver.clone())
+            .with_memo(msg.memo.clone());
+        serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
+    };
+
+    let packet = Packet::builder()
+        .sequence(sequence)
+        .source(msg.source_port, msg.source_channel)
+        .destination(destination_port, destination_channel)
+        .data(data)
+        .timeout_height(msg.timeout_height)
+        .timeout_timestamp(msg.timeout_timestamp)
+        .build()
+        .map_err(Error::ics04_channel)?;
+
+    let HandlerOutput {
+        result,
+        log,
+        events,
+    } = send_packet(ctx, packet).map_err(Error::ics04_channel)?;
+
+    ctx.store_packet_result(result)
+        .map_err(Error::ics04_channel)?;
+
+    output.merge_output(
+        HandlerOutput::builder()
+            .with_log(log)
+            .with_events(events)
+            .with_result(()),
+    );
+
+    output.log(format!(
+        "IBC NFT transfer: {} --({}: {} tokens)--> {}",
+        msg.sender,
+        class_id,
+        msg.token_ids.len(),
+        msg.receiver
+    ));
+
+    let transfer_event = TransferEvent {
+        sender: msg.sender,
+        receiver: msg.receiver,
+        class_id,
+        token_ids: msg.token_ids,
+        memo: msg.memo,
+    };
+    output.emit(ModuleEvent::from(transfer_event).into());
+
+    Ok(())
+}
+
+/// Checks that the NFTs of a packet may be received, and returns the function that unescrows
+/// them, if the class comes from this chain, or mints vouchers of them otherwise.
+pub fn process_recv_packet<Ctx: 'static + NftTransferContext>(
+    ctx: &Ctx,
+    output: &mut ModuleOutputBuilder,
+    packet: &Packet,
+    data: &PacketData,
+) -> Result<Box<WriteFn>, Error> {
+    if !ctx.is_receive_enabled() {
+        return Err(Error::receive_disabled());
+    }
+
+    let receiver_account = ctx.parse_account(&data.receiver)?;
+
+    if is_receiver_chain_source(
+        packet.source_port.clone(),
+        packet.source_channel.clone(),
+        &data.class_id,
+    ) {
+        // sender chain is not the source, unescrow the NFTs
+        let prefix = TracePrefix::new(packet.source_port.clone(), packet.source_channel.clone());
+        let mut class_id = data.class_id.clone();
+        class_id.remove_trace_prefix(&prefix);
+
+        let escrow_address =
+            ctx.get_channel_escrow_address(&packet.destination_port, &packet.destination_channel)?;
+        for token_id in &data.token_ids {
+            if ctx.get_nft_owner(&class_id, token_id).as_ref() != Some(&escrow_address) {
+                return Err(Error::not_nft_owner(class_id, token_id.clone()));
+            }
+        }
+
+        let token_ids = data.token_ids.clone();
+        Ok(Box::new(move |ctx| {
+            let ctx = ctx.downcast_mut::<Ctx>().unwrap();
+            for token_id in &token_ids {
+                ctx.transfer_nft(&escrow_address, &receiver_account, &class_id, token_id)
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }))
+    } else {
+        // sender chain is the source, mint vouchers
+        let prefix = TracePrefix::new(
+            packet.destination_port.clone(),
+            packet.destination_channel.clone(),
+        );
+        let mut class_id = data.class_id.clone();
+        class_id.add_trace_prefix(prefix);
+
+        for token_id in &data.token_ids {
+            if ctx.get_nft(&class_id, token_id).is_some() {
+                return Err(Error::nft_already_exists(class_id, token_id.clone()));
+            }
+        }
+
+        let hash = class_id.hash();
+        if !ctx.has_class_trace(&hash) {
+            let class_trace_event = ClassTraceEvent {
+                trace_hash: hash.clone(),
+                class_id: class_id.clone(),
+            };
+            output.emit(class_trace_event.into());
+        }
+
+        let nfts = data.nfts(&class_id);
+        let class = data.class(class_id);
+        Ok(Box::new(move |ctx| {
+            let ctx = ctx.downcast_mut::<Ctx>().unwrap();
+            if !ctx.has_class_trace(&hash) {
+                ctx.store_class_trace(hash, class.class_id.clone())
+                    .map_err(|e| e.to_string())?;
+            }
+            ctx.create_or_update_class(class)
+                .map_err(|e| e.to_string())?;
+            for nft in nfts {
+                ctx.mint_nft(&receiver_account, nft)
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }))
+    }
+}
+
+/// Gives the NFTs of a packet that was not received back to their sender, by unescrowing them if
+/// the class comes from this chain, or by minting the vouchers burnt on send otherwise.
+pub fn refund_packet_nfts(
+    ctx: &mut impl NftTransferContext,
+    packet: &Packet,
+    data: &PacketData,
+) -> Result<(), Error> {
+    let sender = ctx.parse_account(&data.sender)?;
+
+    if is_sender_chain_source(
+        packet.source_port.clone(),
+        packet.source_channel.clone(),
+        &data.class_id,
+    ) {
+        let escrow_address =
+            ctx.get_channel_escrow_address(&packet.source_port, &packet.source_channel)?;
+        for token_id in &data.token_ids {
+            ctx.transfer_nft(&escrow_address, &sender, &data.class_id, token_id)?;
+        }
+    } else {
+        for nft in data.nfts(&data.class_id) {
+            ctx.mint_nft(&sender, nft)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::applications::nft_transfer::context::{
+        on_acknowledgement_packet, on_recv_packet, on_timeout_packet, NftTransferReader,
+    };
+    use crate::applications::nft_transfer::{Nft, NftClass, PrefixedClassId, TokenId};
+    use crate::applications::transfer::acknowledgement::Acknowledgement;
+    use crate::core::ics03_connection::connection::{ConnectionEnd, State as ConnectionState};
+    use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+    use crate::core::ics26_routing::context::OnRecvPacketAck;
+    use crate::events::IbcEvent;
+    use crate::mock::context::MockContext;
+    use crate::signer::Signer;
+    use crate::timestamp::Timestamp;
+    use crate::Height;
+
+    /// A chain with an open NFT transfer channel `channel_id`, to `counterparty_channel_id`.
+    fn chain_ctx(channel_id: u64, counterparty_channel_id: u64) -> MockContext {
+        let mut connection_end = ConnectionEnd::default();
+        connection_end.set_state(ConnectionState::Open);
+        MockContext::default()
+            .with_client(&ClientId::default(), Height::new(0, 5).unwrap())
+            .with_connection(ConnectionId::new(0), connection_end)
+            .with_channel(
+                PortId::nft_transfer(),
+                ChannelId::new(channel_id),
+                ChannelEnd::new(
+                    State::Open,
+                    Order::Unordered,
+                    Counterparty::new(
+                        PortId::nft_transfer(),
+                        Some(ChannelId::new(counterparty_channel_id)),
+                    ),
+                    vec![ConnectionId::new(0)],
+                    Version::ics721(),
+                ),
+            )
+            .with_send_sequence(PortId::nft_transfer(), ChannelId::new(channel_id), 1.into())
+    }
+
+    /// Sends the token `kitty-1` of `class_id` from `sender` to `receiver` over `channel_id`, and
+    /// returns the packet sent.
+    fn send(
+        ctx: &mut MockContext,
+        channel_id: u64,
+        class_id: String,
+        sender: &Signer,
+        receiver: &Signer,
+    ) -> Result<Packet, Error> {
+        let msg = MsgTransfer {
+            source_port: PortId::nft_transfer(),
+            source_channel: ChannelId::new(channel_id),
+            class_id,
+            token_ids: vec!["kitty-1".parse().unwrap()],
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            timeout_height: Height::new(0, 10).unwrap().into(),
+            timeout_timestamp: Timestamp::none(),
+            memo: String::new(),
+        };
+        let mut output = HandlerOutputBuilder::new();
+        send_nft_transfer(ctx, &mut output, msg)?;
+        let packet = output
+            .with_result(())
+            .events
+            .into_iter()
+            .find_map(|event| match event {
+                IbcEvent::SendPacket(e) => Some(e.packet()),
+                _ => None,
+            })
+            .expect("no packet was sent");
+        Ok(packet)
+    }
+
+    fn recv(ctx: &mut MockContext, packet: &Packet) -> bool {
+        let relayer = "relayer".parse().unwrap();
+        match on_recv_packet(ctx, &mut ModuleOutputBuilder::new(), packet, &relayer) {
+            OnRecvPacketAck::Successful(_, write_fn) => {
+                write_fn(ctx).unwrap();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn owner(ctx: &MockContext, class_id: &PrefixedClassId) -> Option<Signer> {
+        ctx.get_nft_owner(class_id, &"kitty-1".parse().unwrap())
+    }
+
+    #[test]
+    fn test_nft_round_trip() {
+        let (alice, bob): (Signer, Signer) = ("alice".parse().unwrap(), "bob".parse().unwrap());
+        let class_id: PrefixedClassId = "kitties".parse().unwrap();
+        let token_id: TokenId = "kitty-1".parse().unwrap();
+        let mut ctx_a = chain_ctx(0, 1);
+        let mut ctx_b = chain_ctx(1, 0);
+        {
+            let mut store = ctx_a.ibc_store.lock().unwrap();
+            store.nft.classes.insert(
+                class_id.clone(),
+                NftClass {
+                    class_id: class_id.clone(),
+                    uri: "https://kitties.io".to_string(),
+                    data: String::new(),
+                },
+            );
+            let nft = Nft {
+                class_id: class_id.clone(),
+                token_id: token_id.clone(),
+                uri: "https://kitties.io/1".to_string(),
+                data: String::new(),
+            };
+            store
+                .nft
+                .nfts
+                .insert((class_id.clone(), token_id), (alice.clone(), nft));
+        }
+
+        // Only the owner may send the NFT, which is escrowed on its source chain ...
+        assert!(send(&mut ctx_a, 0, "kitties".to_string(), &bob, &alice).is_err());
+        let packet = send(&mut ctx_a, 0, "kitties".to_string(), &alice, &bob).unwrap();
+        let escrow_a = ctx_a
+            .get_channel_escrow_address(&PortId::nft_transfer(), &ChannelId::new(0))
+            .unwrap();
+        assert_eq!(owner(&ctx_a, &class_id), Some(escrow_a.clone()));
+
+        // ... and received as a voucher, along with its class.
+        assert!(recv(&mut ctx_b, &packet));
+        let voucher_class_id: PrefixedClassId = "nft-transfer/channel-1/kitties".parse().unwrap();
+        assert_eq!(owner(&ctx_b, &voucher_class_id), Some(bob.clone()));
+        assert_eq!(
+            ctx_b.get_nft_class(&voucher_class_id).unwrap().uri,
+            "https://kitties.io"
+        );
+        assert_eq!(
+            ctx_b.class_trace(&voucher_class_id.hash()),
+            Some(voucher_class_id.clone())
+        );
+        // The same NFT cannot be received twice.
+        assert!(!recv(&mut ctx_b, &packet));
+
+        // The voucher is burnt when sent back by its hashed class identifier, and unescrowed on
+        // its source chain.
+        let packet = send(
+            &mut ctx_b,
+            1,
+            voucher_class_id.voucher_class_id(),
+            &bob,
+            &alice,
+        )
+        .unwrap();
+        assert_eq!(owner(&ctx_b, &voucher_class_id), None);
+        assert!(recv(&mut ctx_a, &packet));
+        assert_eq!(owner(&ctx_a, &class_id), Some(alice.clone()));
+
+        // NFTs that were not received are refunded.
+        let packet = send(&mut ctx_a, 0, "kitties".to_string(), &alice, &bob).unwrap();
+        let error_ack = Acknowledgement::Error("error".to_string());
+        on_acknowledgement_packet(
+            &mut ctx_a,
+            &mut ModuleOutputBuilder::new(),
+            &packet,
+            &serde_json::to_vec(&error_ack).unwrap().into(),
+            &"relayer".parse().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(owner(&ctx_a, &class_id), Some(alice.clone()));
+
+        let packet = send(&mut ctx_a, 0, "kitties".to_string(), &alice, &bob).unwrap();
+        assert!(recv(&mut ctx_b, &packet));
+        let packet = send(
+            &mut ctx_b,
+            1,
+            voucher_class_id.voucher_class_id(),
+            &bob,
+            &alice,
+        )
+        .unwrap();
+        on_timeout_packet(
+            &mut ctx_b,
+            &mut ModuleOutputBuilder::new(),
+            &packet,
+            &"relayer".parse().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(owner(&ctx_b, &voucher_class_id), Some(bob));
+    }
+}
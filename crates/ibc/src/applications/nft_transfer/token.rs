@@ -0,0 +1,40 @@
+use core::str::FromStr;
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use super::class::PrefixedClassId;
+use super::error::Error;
+use crate::prelude::*;
+
+/// The identifier of an NFT within its class.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Display)]
+#[serde(transparent)]
+pub struct TokenId(String);
+
+impl TokenId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for TokenId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            Err(Error::empty_token_id())
+        } else {
+            Ok(TokenId(s.to_owned()))
+        }
+    }
+}
+
+/// An NFT, with the metadata that travels with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Nft {
+    pub class_id: PrefixedClassId,
+    pub token_id: TokenId,
+    pub uri: String,
+    pub data: String,
+}
@@ -0,0 +1,81 @@
+//! Defines the required context for the packet-forward middleware: the intermediate accounts
+//! holding the funds being forwarded, the packets in flight, and the sending of the forwarded
+//! transfers.
+
+use core::time::Duration;
+
+use super::error::Error;
+use crate::applications::transfer::acknowledgement::Acknowledgement;
+use crate::applications::transfer::msgs::transfer::MsgTransfer;
+use crate::applications::transfer::PrefixedCoin;
+use crate::core::ics04_channel::packet::{Packet, PacketId, Sequence};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
+use crate::signer::Signer;
+use crate::timestamp::Timestamp;
+
+/// A received packet that was forwarded, and whose acknowledgement is written once the forwarded
+/// packet is acknowledged or ultimately times out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InFlightPacket {
+    /// The received packet.
+    pub packet: Packet,
+    /// The transfer that forwards the funds of the received packet, sent by the intermediate
+    /// receiver, without its timeout.
+    pub transfer: MsgTransfer<PrefixedCoin>,
+    /// The timeout of the forwarded packet, relative to the time it is sent.
+    pub timeout: Duration,
+    /// The number of times the forwarded packet is still sent again if it times out.
+    pub retries: u8,
+}
+
+pub trait PacketForwardReader {
+    /// Returns the account that receives the funds of a packet received on `channel_id` and sent
+    /// by `original_sender`, and forwards them.
+    fn intermediate_receiver(
+        &self,
+        channel_id: &ChannelId,
+        original_sender: &Signer,
+    ) -> Result<Signer, Error>;
+
+    /// Returns the packet in flight that was forwarded as the packet `packet_id`, if any.
+    fn in_flight_packet(&self, packet_id: &PacketId) -> Option<InFlightPacket>;
+
+    /// Returns the current timestamp of the host, from which the timeouts of forwarded packets
+    /// are computed.
+    fn host_timestamp(&self) -> Timestamp;
+}
+
+pub trait PacketForwardKeeper {
+    /// Stores the packet in flight that was forwarded as the packet `packet_id`.
+    fn store_in_flight_packet(&mut self, packet_id: PacketId, in_flight_packet: InFlightPacket);
+
+    /// Removes the packet in flight that was forwarded as the packet `packet_id`.
+    fn delete_in_flight_packet(&mut self, packet_id: &PacketId);
+
+    /// Sends the ICS20 `transfer` and returns the sequence of its packet.
+    fn send_transfer(&mut self, transfer: MsgTransfer<PrefixedCoin>) -> Result<Sequence, Error>;
+
+    /// Writes the acknowledgement of the received `packet`, in its JSON encoding.
+    fn write_acknowledgement(
+        &mut self,
+        packet: &Packet,
+        acknowledgement: Acknowledgement,
+    ) -> Result<(), Error>;
+
+    /// Burns `coin`, i.e. vouchers minted on this chain, from the account of `holder`.
+    fn burn_coins(&mut self, holder: &Signer, coin: &PrefixedCoin) -> Result<(), Error>;
+
+    /// Moves `coin` from the account of `holder` to the escrow account of the given channel.
+    fn escrow_coins(
+        &mut self,
+        holder: &Signer,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        coin: &PrefixedCoin,
+    ) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies which the packet-forward middleware requires to forward packets
+/// and to propagate their acknowledgements back.
+pub trait PacketForwardContext: PacketForwardReader + PacketForwardKeeper {}
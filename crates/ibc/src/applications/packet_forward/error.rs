@@ -0,0 +1,82 @@
+use flex_error::{define_error, TraceError};
+
+use crate::applications::transfer::error::Error as Ics20Error;
+use crate::core::ics24_host::error::ValidationError;
+use crate::core::ics26_routing::context::{AckError, INTERNAL_ACK_ERROR_CODE};
+use crate::prelude::*;
+use crate::signer::SignerError;
+use crate::timestamp::TimestampOverflowError;
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        Ics20
+            [ Ics20Error ]
+            | _ | { "ICS20 fungible token transfer error" },
+
+        MemoDeserialization
+            [ TraceError<serde_json::Error> ]
+            | _ | { "failed to deserialize the forward metadata in the packet memo" },
+
+        InvalidForwardReceiver
+            [ SignerError ]
+            | _ | { "invalid forward receiver address" },
+
+        InvalidForwardPort
+            [ ValidationError ]
+            | _ | { "invalid forward port identifier" },
+
+        InvalidForwardChannel
+            [ ValidationError ]
+            | _ | { "invalid forward channel identifier" },
+
+        InvalidForwardTimeout
+            { timeout: String }
+            | e | { format_args!("invalid forward timeout: {0}", e.timeout) },
+
+        InvalidForwardNext
+            | _ | { "the `next` forward metadata must be a JSON object or string" },
+
+        TimestampOverflow
+            [ TimestampOverflowError ]
+            | _ | { "the timeout of the forwarded packet overflows" },
+
+        IntermediateReceiver
+            { reason: String }
+            | e | { format_args!("failed to derive the intermediate receiver account: {0}", e.reason) },
+
+        Forward
+            { reason: String }
+            | e | { format_args!("failed to forward the packet: {0}", e.reason) },
+
+        ForwardTimeout
+            | _ | { "the forwarded packet timed out" },
+
+        Refund
+            { reason: String }
+            | e | { format_args!("failed to refund the forwarded packet: {0}", e.reason) },
+
+        WriteAcknowledgement
+            { reason: String }
+            | e | { format_args!("failed to write the acknowledgement of the forwarded packet: {0}", e.reason) },
+    }
+}
+
+/// Invalid forward metadata is reported with the code of the invalid memo ICS20 error of ibc-go,
+/// and forwarded packets that timed out with the one of the timed out forwarded packets.
+impl AckError for Error {
+    fn ack_error_code(&self) -> u32 {
+        match self.detail() {
+            ErrorDetail::Ics20(e) => e.source.ack_error_code(),
+            ErrorDetail::MemoDeserialization(_)
+            | ErrorDetail::InvalidForwardReceiver(_)
+            | ErrorDetail::InvalidForwardPort(_)
+            | ErrorDetail::InvalidForwardChannel(_)
+            | ErrorDetail::InvalidForwardTimeout(_)
+            | ErrorDetail::InvalidForwardNext(_) => 11,
+            ErrorDetail::TimestampOverflow(_) => 2,
+            ErrorDetail::ForwardTimeout(_) => 12,
+            _ => INTERNAL_ACK_ERROR_CODE,
+        }
+    }
+}
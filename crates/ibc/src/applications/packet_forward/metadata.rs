@@ -0,0 +1,206 @@
+use core::str::FromStr;
+use core::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::error::Error;
+use super::{DEFAULT_FORWARD_RETRIES, DEFAULT_FORWARD_TIMEOUT, FORWARD_MEMO_KEY};
+use crate::applications::transfer::packet::PacketData;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
+use crate::signer::Signer;
+
+/// A forwarding request carried in the memo of a transfer packet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForwardMetadata {
+    /// The receiver of the forwarded packet, on the chain at the other end of `channel`.
+    pub receiver: Signer,
+    pub port: PortId,
+    pub channel: ChannelId,
+    /// The timeout of the forwarded packet, relative to the time it is sent.
+    pub timeout: Duration,
+    /// The number of times the forwarded packet is sent again if it times out.
+    pub retries: u8,
+    /// The memo of the forwarded packet, e.g. the forward metadata of the next hop.
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawForwardMetadata {
+    receiver: String,
+    port: String,
+    channel: String,
+    #[serde(default)]
+    timeout: Option<Value>,
+    #[serde(default)]
+    retries: Option<u8>,
+    #[serde(default)]
+    next: Option<Value>,
+}
+
+/// Extracts the forward metadata from the memo of the transfer packet `data`.
+///
+/// Returns `Ok(None)` if the packet is not meant to be forwarded, i.e. if it has no memo, the
+/// memo is not a JSON object or the object has no `forward` entry. A `forward` entry that cannot
+/// be parsed into [`ForwardMetadata`] is an error.
+pub fn parse_forward_metadata(data: &PacketData) -> Result<Option<ForwardMetadata>, Error> {
+    let forward = match serde_json::from_str::<Value>(&data.memo) {
+        Ok(Value::Object(mut memo)) => match memo.remove(FORWARD_MEMO_KEY) {
+            Some(forward) => forward,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let raw: RawForwardMetadata =
+        serde_json::from_value(forward).map_err(Error::memo_deserialization)?;
+
+    let timeout = match raw.timeout {
+        None => DEFAULT_FORWARD_TIMEOUT,
+        Some(Value::Number(nanos)) => nanos
+            .as_u64()
+            .map(Duration::from_nanos)
+            .ok_or_else(|| Error::invalid_forward_timeout(nanos.to_string()))?,
+        Some(Value::String(timeout)) => {
+            parse_duration(&timeout).ok_or_else(|| Error::invalid_forward_timeout(timeout))?
+        }
+        Some(timeout) => return Err(Error::invalid_forward_timeout(timeout.to_string())),
+    };
+
+    let next = match raw.next {
+        None | Some(Value::Null) => None,
+        Some(Value::String(next)) if next.is_empty() => None,
+        Some(Value::String(next)) => Some(next),
+        Some(next @ Value::Object(_)) => Some(next.to_string()),
+        Some(_) => return Err(Error::invalid_forward_next()),
+    };
+
+    Ok(Some(ForwardMetadata {
+        receiver: Signer::from_str(&raw.receiver).map_err(Error::invalid_forward_receiver)?,
+        port: PortId::from_str(&raw.port).map_err(Error::invalid_forward_port)?,
+        channel: ChannelId::from_str(&raw.channel).map_err(Error::invalid_forward_channel)?,
+        timeout,
+        retries: raw.retries.unwrap_or(DEFAULT_FORWARD_RETRIES),
+        next,
+    }))
+}
+
+/// Parses a duration in the format of Go's `time.Duration`, e.g. `1h30m`, restricted to integer
+/// amounts of each unit.
+fn parse_duration(s: &str) -> Option<Duration> {
+    const UNITS: [(&str, u64); 7] = [
+        ("ns", 1),
+        ("us", 1_000),
+        ("µs", 1_000),
+        ("ms", 1_000_000),
+        ("s", 1_000_000_000),
+        ("m", 60_000_000_000),
+        ("h", 3_600_000_000_000),
+    ];
+
+    let mut rest = s;
+    let mut nanos = 0u64;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount = rest[..digits].parse::<u64>().ok()?;
+        rest = &rest[digits..];
+
+        // Longest unit first, so that `ms` is not read as `m`.
+        let (unit, factor) = UNITS
+            .iter()
+            .filter(|(unit, _)| rest.starts_with(unit))
+            .max_by_key(|(unit, _)| unit.len())?;
+        rest = &rest[unit.len()..];
+
+        nanos = nanos.checked_add(amount.checked_mul(*factor)?)?;
+    }
+
+    (!s.is_empty()).then(|| Duration::from_nanos(nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet_data(memo: &str) -> PacketData {
+        serde_json::from_value(serde_json::json!({
+            "denom": "uatom",
+            "amount": "100",
+            "sender": "sender",
+            "receiver": "intermediate",
+            "memo": memo,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_forward_metadata() {
+        let metadata = parse_forward_metadata(&packet_data(
+            r#"{"forward":{"receiver":"receiver","port":"transfer","channel":"channel-1"}}"#,
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(metadata.receiver.as_ref(), "receiver");
+        assert_eq!(metadata.channel, ChannelId::new(1));
+        assert_eq!(metadata.timeout, DEFAULT_FORWARD_TIMEOUT);
+        assert_eq!(metadata.retries, DEFAULT_FORWARD_RETRIES);
+        assert_eq!(metadata.next, None);
+
+        let metadata = parse_forward_metadata(&packet_data(
+            r#"{"forward":{"receiver":"a","port":"transfer","channel":"channel-1","timeout":"1h30m","retries":2,"next":{"forward":{"receiver":"b","port":"transfer","channel":"channel-2"}}}}"#,
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(metadata.timeout, Duration::from_secs(90 * 60));
+        assert_eq!(metadata.retries, 2);
+        let next = packet_data(&metadata.next.unwrap());
+        assert_eq!(
+            parse_forward_metadata(&next).unwrap().unwrap().channel,
+            ChannelId::new(2)
+        );
+    }
+
+    #[test]
+    fn test_parse_no_forward_metadata() {
+        assert_eq!(parse_forward_metadata(&packet_data("")).unwrap(), None);
+        assert_eq!(
+            parse_forward_metadata(&packet_data("some memo")).unwrap(),
+            None
+        );
+        assert_eq!(
+            parse_forward_metadata(&packet_data(r#"{"wasm":{}}"#)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_forward_metadata() {
+        for memo in [
+            r#"{"forward":{"receiver":"a","port":"transfer"}}"#,
+            r#"{"forward":{"receiver":"a","port":"transfer","channel":"channel-1","timeout":"1d"}}"#,
+            r#"{"forward":{"receiver":"","port":"transfer","channel":"channel-1"}}"#,
+            r#"{"forward":{"receiver":"a","port":"transfer","channel":"channel-1","next":1}}"#,
+        ] {
+            assert!(
+                parse_forward_metadata(&packet_data(memo)).is_err(),
+                "{}",
+                memo
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("10m"), Some(Duration::from_secs(600)));
+        assert_eq!(
+            parse_duration("1h1ms"),
+            Some(Duration::from_millis(3_600_001))
+        );
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("1.5h"), None);
+    }
+}
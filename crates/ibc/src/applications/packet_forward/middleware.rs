@@ -0,0 +1,730 @@
+//! The packet-forward middleware, which wraps the `Module` of an ICS20 application to forward
+//! the funds of incoming transfers to another chain.
+
+use core::any::Any;
+
+use super::context::{InFlightPacket, PacketForwardContext};
+use super::error::Error;
+use super::metadata::{parse_forward_metadata, ForwardMetadata};
+use crate::applications::transfer::acknowledgement::Acknowledgement;
+use crate::applications::transfer::error::Error as Ics20Error;
+use crate::applications::transfer::is_receiver_chain_source;
+use crate::applications::transfer::msgs::transfer::MsgTransfer;
+use crate::applications::transfer::packet::PacketData;
+use crate::applications::transfer::relay::on_recv_packet::received_coin;
+use crate::core::ics04_channel::channel::{Counterparty, Order};
+use crate::core::ics04_channel::error::Error as ChannelError;
+use crate::core::ics04_channel::handler::ModuleExtras;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics04_channel::packet::{Packet, PacketId};
+use crate::core::ics04_channel::timeout::TimeoutHeight;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::{Module, ModuleOutputBuilder, OnRecvPacketAck};
+use crate::core::ics26_routing::middleware::forward_recv_packet_ack;
+use crate::core::ics26_routing::reentrancy::NestedDispatchGuard;
+use crate::prelude::*;
+use crate::signer::Signer;
+use crate::timestamp::Timestamp;
+
+/// Middleware wrapping an ICS20 application `Module` that forwards the funds of incoming transfer
+/// packets carrying forward metadata in their memo.
+///
+/// The acknowledgement of a forwarded packet is not written on receipt, but once the packet it
+/// was forwarded as is acknowledged or ultimately times out, through the
+/// [`PacketForwardKeeper`](super::context::PacketForwardKeeper) of the host.
+#[derive(Debug)]
+pub struct PacketForwardMiddleware<M, C> {
+    app: M,
+    ctx: C,
+}
+
+impl<M, C> PacketForwardMiddleware<M, C> {
+    pub fn new(app: M, ctx: C) -> Self {
+        Self { app, ctx }
+    }
+
+    pub fn app(&self) -> &M {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut M {
+        &mut self.app
+    }
+
+    pub fn ctx(&self) -> &C {
+        &self.ctx
+    }
+
+    pub fn ctx_mut(&mut self) -> &mut C {
+        &mut self.ctx
+    }
+}
+
+impl<M, C> PacketForwardMiddleware<M, C>
+where
+    M: Module,
+    C: PacketForwardContext + Send + Sync + 'static,
+{
+    fn on_recv_forwarded_packet(
+        &self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        data: PacketData,
+        metadata: ForwardMetadata,
+        relayer: &Signer,
+    ) -> Result<OnRecvPacketAck, Error> {
+        // Credit the funds to the intermediate receiver, which forwards them. The memo is
+        // consumed by the middleware.
+        let receiver = self
+            .ctx
+            .intermediate_receiver(&packet.destination_channel, &data.sender)?;
+        let received_packet = {
+            let mut received_data = data.clone();
+            received_data.receiver = receiver.clone();
+            received_data.memo.clear();
+
+            let mut p = packet.clone();
+            p.data = serde_json::to_vec(&received_data).map_err(Error::memo_deserialization)?;
+            p
+        };
+
+        let in_flight_packet = InFlightPacket {
+            packet: packet.clone(),
+            transfer: MsgTransfer {
+                source_port: metadata.port,
+                source_channel: metadata.channel,
+                token: received_coin(packet, data.token),
+                sender: receiver,
+                receiver: metadata.receiver,
+                timeout_height: TimeoutHeight::no_timeout(),
+                timeout_timestamp: Timestamp::none(),
+                memo: metadata.next.unwrap_or_default(),
+            },
+            timeout: metadata.timeout,
+            retries: metadata.retries,
+        };
+
+        let ack = self.app.on_recv_packet(output, &received_packet, relayer);
+        Ok(forward_on_success::<M, C>(
+            forward_recv_packet_ack(ack, Self::app_mut),
+            in_flight_packet,
+        ))
+    }
+}
+
+/// Replaces the successful acknowledgement of the received packet with the forwarding of the
+/// packet, which is acknowledged once the forwarded packet is acknowledged.
+fn forward_on_success<M, C>(
+    ack: OnRecvPacketAck,
+    in_flight_packet: InFlightPacket,
+) -> OnRecvPacketAck
+where
+    M: Module,
+    C: PacketForwardContext + Send + Sync + 'static,
+{
+    match ack {
+        OnRecvPacketAck::Successful(_, write_fn) => {
+            OnRecvPacketAck::Nil(Box::new(move |module: &mut dyn Any| {
+                write_fn(module)?;
+                let middleware = downcast_middleware::<M, C>(module)?;
+                forward(&mut middleware.ctx, in_flight_packet).map_err(|e| e.to_string())
+            }))
+        }
+        OnRecvPacketAck::Deferred(ack_fn) => {
+            OnRecvPacketAck::Deferred(Box::new(move |module: &mut dyn Any, output| {
+                let ack = ack_fn(module, output)?;
+                Ok(forward_on_success::<M, C>(ack, in_flight_packet))
+            }))
+        }
+        ack => ack,
+    }
+}
+
+/// Sends the transfer of `in_flight_packet`, timing out `timeout` from now, and records the
+/// packet in flight until the forwarded packet is acknowledged.
+fn forward<C: PacketForwardContext>(
+    ctx: &mut C,
+    in_flight_packet: InFlightPacket,
+) -> Result<(), Error> {
+    let mut transfer = in_flight_packet.transfer.clone();
+    transfer.timeout_timestamp =
+        (ctx.host_timestamp() + in_flight_packet.timeout).map_err(Error::timestamp_overflow)?;
+
+    let port_id = transfer.source_port.clone();
+    let channel_id = transfer.source_channel.clone();
+    let sequence = ctx.send_transfer(transfer)?;
+    ctx.store_in_flight_packet(
+        PacketId {
+            port_id,
+            channel_id,
+            sequence,
+        },
+        in_flight_packet,
+    );
+
+    Ok(())
+}
+
+/// Undoes the receipt of the funds of the received packet by the intermediate receiver, once the
+/// forwarded packet failed and its funds were refunded to it, so that the error acknowledgement
+/// refunds the original sender instead.
+fn refund<C: PacketForwardContext>(
+    ctx: &mut C,
+    in_flight_packet: &InFlightPacket,
+) -> Result<(), Error> {
+    let packet = &in_flight_packet.packet;
+    let data = serde_json::from_slice::<PacketData>(&packet.data)
+        .map_err(|_| Error::ics20(Ics20Error::packet_data_deserialization()))?;
+    let holder = &in_flight_packet.transfer.sender;
+    let coin = &in_flight_packet.transfer.token;
+
+    if is_receiver_chain_source(
+        packet.source_port.clone(),
+        packet.source_channel.clone(),
+        &data.token.denom,
+    ) {
+        // The funds were unescrowed on receipt.
+        ctx.escrow_coins(
+            holder,
+            &packet.destination_port,
+            &packet.destination_channel,
+            coin,
+        )
+    } else {
+        // Vouchers were minted on receipt.
+        ctx.burn_coins(holder, coin)
+    }
+}
+
+fn to_channel_error(e: Error) -> ChannelError {
+    ChannelError::app_module(e.to_string())
+}
+
+fn downcast_middleware<M, C>(
+    module: &mut dyn Any,
+) -> Result<&mut PacketForwardMiddleware<M, C>, String>
+where
+    M: Module,
+    C: PacketForwardContext + Send + Sync + 'static,
+{
+    module
+        .downcast_mut::<PacketForwardMiddleware<M, C>>()
+        .ok_or_else(|| "packet-forward middleware expected".to_string())
+}
+
+impl<M, C> Module for PacketForwardMiddleware<M, C>
+where
+    M: Module,
+    C: PacketForwardContext + Send + Sync + 'static,
+{
+    fn on_chan_open_init(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        version: &Version,
+    ) -> Result<(ModuleExtras, Version), ChannelError> {
+        self.app.on_chan_open_init(
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            version,
+        )
+    }
+
+    fn on_chan_open_try(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), ChannelError> {
+        self.app.on_chan_open_try(
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            counterparty_version,
+        )
+    }
+
+    fn supported_versions(&self, port_id: &PortId) -> Vec<Version> {
+        self.app.supported_versions(port_id)
+    }
+
+    fn on_chan_open_ack(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty_version: &Version,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app
+            .on_chan_open_ack(port_id, channel_id, counterparty_version)
+    }
+
+    fn on_chan_open_confirm(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app.on_chan_open_confirm(port_id, channel_id)
+    }
+
+    fn on_chan_close_init(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app.on_chan_close_init(port_id, channel_id)
+    }
+
+    fn on_chan_close_confirm(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app.on_chan_close_confirm(port_id, channel_id)
+    }
+
+    fn on_recv_packet(
+        &self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> OnRecvPacketAck {
+        let forward = serde_json::from_slice::<PacketData>(&packet.data)
+            .ok()
+            .map(|data| parse_forward_metadata(&data).map(|metadata| (data, metadata)));
+
+        match forward {
+            Some(Ok((data, Some(metadata)))) => self
+                .on_recv_forwarded_packet(output, packet, data, metadata, relayer)
+                .unwrap_or_else(|e| {
+                    OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e)))
+                }),
+            Some(Err(e)) => OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e))),
+            // Not a forwarded packet, or not a valid transfer packet to begin with, in which case
+            // the application is responsible for rejecting it.
+            _ => forward_recv_packet_ack(
+                self.app.on_recv_packet(output, packet, relayer),
+                Self::app_mut,
+            ),
+        }
+    }
+
+    fn on_acknowledgement_packet(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        acknowledgement: &GenericAcknowledgement,
+        relayer: &Signer,
+    ) -> Result<(), ChannelError> {
+        // The application refunds the intermediate receiver first if the forward failed.
+        self.app
+            .on_acknowledgement_packet(output, packet, acknowledgement, relayer)?;
+
+        let packet_id = packet.id();
+        let in_flight_packet = match self.ctx.in_flight_packet(&packet_id) {
+            Some(in_flight_packet) => in_flight_packet,
+            None => return Ok(()),
+        };
+        self.ctx.delete_in_flight_packet(&packet_id);
+
+        // The error of the forwarded packet is propagated back to the original sender.
+        let ack = Acknowledgement::try_from(acknowledgement)
+            .unwrap_or_else(|_| Acknowledgement::from_error(Ics20Error::ack_deserialization()));
+        if let Acknowledgement::Error(_) = ack {
+            refund(&mut self.ctx, &in_flight_packet).map_err(to_channel_error)?;
+        }
+
+        self.ctx
+            .write_acknowledgement(&in_flight_packet.packet, ack)
+            .map_err(to_channel_error)
+    }
+
+    fn on_timeout_packet(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> Result<(), ChannelError> {
+        // The application refunds the intermediate receiver first.
+        self.app.on_timeout_packet(output, packet, relayer)?;
+
+        let packet_id = packet.id();
+        let mut in_flight_packet = match self.ctx.in_flight_packet(&packet_id) {
+            Some(in_flight_packet) => in_flight_packet,
+            None => return Ok(()),
+        };
+        self.ctx.delete_in_flight_packet(&packet_id);
+
+        if in_flight_packet.retries > 0 {
+            in_flight_packet.retries -= 1;
+            return forward(&mut self.ctx, in_flight_packet).map_err(to_channel_error);
+        }
+
+        refund(&mut self.ctx, &in_flight_packet).map_err(to_channel_error)?;
+        self.ctx
+            .write_acknowledgement(
+                &in_flight_packet.packet,
+                Acknowledgement::from_error(Error::forward_timeout()),
+            )
+            .map_err(to_channel_error)
+    }
+    fn nested_dispatch_guard(&mut self) -> Option<&mut NestedDispatchGuard> {
+        self.app.nested_dispatch_guard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::collections::BTreeMap;
+
+    use crate::applications::packet_forward::context::{PacketForwardKeeper, PacketForwardReader};
+    use crate::applications::transfer::acknowledgement::ACK_ERR_STR;
+    use crate::applications::transfer::PrefixedCoin;
+    use crate::core::ics04_channel::packet::Sequence;
+    use crate::core::ics26_routing::context::{AsAnyMut, WrittenAck};
+
+    #[derive(Debug, Default)]
+    struct DummyApp {
+        received: Vec<PacketData>,
+        acknowledged: Vec<Packet>,
+        timed_out: Vec<Packet>,
+    }
+
+    impl Module for DummyApp {
+        fn on_chan_open_init(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            version: &Version,
+        ) -> Result<(ModuleExtras, Version), ChannelError> {
+            Ok((ModuleExtras::empty(), version.clone()))
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            counterparty_version: &Version,
+        ) -> Result<(ModuleExtras, Version), ChannelError> {
+            Ok((ModuleExtras::empty(), counterparty_version.clone()))
+        }
+
+        fn on_recv_packet(
+            &self,
+            _output: &mut ModuleOutputBuilder,
+            packet: &Packet,
+            _relayer: &Signer,
+        ) -> OnRecvPacketAck {
+            let data = serde_json::from_slice::<PacketData>(&packet.data).unwrap();
+            OnRecvPacketAck::Successful(
+                Box::new(Acknowledgement::success()),
+                Box::new(move |module| {
+                    let app = module.downcast_mut::<DummyApp>().unwrap();
+                    app.received.push(data);
+                    Ok(())
+                }),
+            )
+        }
+
+        fn on_acknowledgement_packet(
+            &mut self,
+            _output: &mut ModuleOutputBuilder,
+            packet: &Packet,
+            _acknowledgement: &GenericAcknowledgement,
+            _relayer: &Signer,
+        ) -> Result<(), ChannelError> {
+            self.acknowledged.push(packet.clone());
+            Ok(())
+        }
+
+        fn on_timeout_packet(
+            &mut self,
+            _output: &mut ModuleOutputBuilder,
+            packet: &Packet,
+            _relayer: &Signer,
+        ) -> Result<(), ChannelError> {
+            self.timed_out.push(packet.clone());
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct DummyForwardContext {
+        in_flight_packets: BTreeMap<PacketId, InFlightPacket>,
+        sent: Vec<MsgTransfer<PrefixedCoin>>,
+        acknowledgements: Vec<(Packet, Acknowledgement)>,
+        burned: Vec<(Signer, PrefixedCoin)>,
+        escrowed: Vec<(Signer, ChannelId, PrefixedCoin)>,
+    }
+
+    impl PacketForwardReader for DummyForwardContext {
+        fn intermediate_receiver(
+            &self,
+            channel_id: &ChannelId,
+            original_sender: &Signer,
+        ) -> Result<Signer, Error> {
+            Ok(format!("{}/{}", channel_id, original_sender)
+                .parse()
+                .unwrap())
+        }
+
+        fn in_flight_packet(&self, packet_id: &PacketId) -> Option<InFlightPacket> {
+            self.in_flight_packets.get(packet_id).cloned()
+        }
+
+        fn host_timestamp(&self) -> Timestamp {
+            Timestamp::from_nanoseconds(1_000).unwrap()
+        }
+    }
+
+    impl PacketForwardKeeper for DummyForwardContext {
+        fn store_in_flight_packet(
+            &mut self,
+            packet_id: PacketId,
+            in_flight_packet: InFlightPacket,
+        ) {
+            self.in_flight_packets.insert(packet_id, in_flight_packet);
+        }
+
+        fn delete_in_flight_packet(&mut self, packet_id: &PacketId) {
+            self.in_flight_packets.remove(packet_id);
+        }
+
+        fn send_transfer(
+            &mut self,
+            transfer: MsgTransfer<PrefixedCoin>,
+        ) -> Result<Sequence, Error> {
+            self.sent.push(transfer);
+            Ok((self.sent.len() as u64).into())
+        }
+
+        fn write_acknowledgement(
+            &mut self,
+            packet: &Packet,
+            acknowledgement: Acknowledgement,
+        ) -> Result<(), Error> {
+            self.acknowledgements
+                .push((packet.clone(), acknowledgement));
+            Ok(())
+        }
+
+        fn burn_coins(&mut self, holder: &Signer, coin: &PrefixedCoin) -> Result<(), Error> {
+            self.burned.push((holder.clone(), coin.clone()));
+            Ok(())
+        }
+
+        fn escrow_coins(
+            &mut self,
+            holder: &Signer,
+            _port_id: &PortId,
+            channel_id: &ChannelId,
+            coin: &PrefixedCoin,
+        ) -> Result<(), Error> {
+            self.escrowed
+                .push((holder.clone(), channel_id.clone(), coin.clone()));
+            Ok(())
+        }
+    }
+
+    impl PacketForwardContext for DummyForwardContext {}
+
+    type DummyMiddleware = PacketForwardMiddleware<DummyApp, DummyForwardContext>;
+
+    fn get_dummy_middleware() -> DummyMiddleware {
+        PacketForwardMiddleware::new(DummyApp::default(), DummyForwardContext::default())
+    }
+
+    fn get_dummy_packet(denom: &str, memo: &str) -> Packet {
+        let data = serde_json::json!({
+            "denom": denom,
+            "amount": "100",
+            "sender": "sender",
+            "receiver": "receiver",
+            "memo": memo,
+        });
+
+        Packet::builder()
+            .sequence(1.into())
+            .source(PortId::transfer(), ChannelId::new(0))
+            .destination(PortId::transfer(), ChannelId::new(1))
+            .data(serde_json::to_vec(&data).unwrap())
+            .build()
+            .unwrap()
+    }
+
+    /// Returns the packet the received packet was forwarded as, once sent as the `n`-th transfer.
+    fn forwarded_packet(middleware: &DummyMiddleware, n: usize) -> Packet {
+        let transfer = &middleware.ctx().sent[n - 1];
+        Packet::builder()
+            .sequence((n as u64).into())
+            .source(
+                transfer.source_port.clone(),
+                transfer.source_channel.clone(),
+            )
+            .destination(PortId::transfer(), ChannelId::new(5))
+            .data(vec![1])
+            .build()
+            .unwrap()
+    }
+
+    fn recv_packet(middleware: &mut DummyMiddleware, packet: &Packet) -> Option<Vec<u8>> {
+        let relayer = "relayer".parse().unwrap();
+        let ack = middleware.on_recv_packet(&mut ModuleOutputBuilder::new(), packet, &relayer);
+        match ack
+            .write(middleware.as_any_mut(), &mut ModuleOutputBuilder::new())
+            .unwrap()
+        {
+            WrittenAck::Successful(ack) | WrittenAck::Failed(ack) => {
+                Some(ack.as_ref().as_ref().to_vec())
+            }
+            WrittenAck::Nil => None,
+        }
+    }
+
+    fn ack_packet(middleware: &mut DummyMiddleware, packet: &Packet, ack: Acknowledgement) {
+        let relayer = "relayer".parse().unwrap();
+        let ack = serde_json::to_vec(&ack).unwrap().into();
+        middleware
+            .on_acknowledgement_packet(&mut ModuleOutputBuilder::new(), packet, &ack, &relayer)
+            .unwrap();
+    }
+
+    fn timeout_packet(middleware: &mut DummyMiddleware, packet: &Packet) {
+        let relayer = "relayer".parse().unwrap();
+        middleware
+            .on_timeout_packet(&mut ModuleOutputBuilder::new(), packet, &relayer)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_recv_packet_without_forward() {
+        let mut middleware = get_dummy_middleware();
+
+        let ack = recv_packet(&mut middleware, &get_dummy_packet("uatom", ""));
+
+        assert_eq!(ack, Some(Acknowledgement::success().as_ref().to_vec()));
+        assert_eq!(middleware.app().received[0].receiver.as_ref(), "receiver");
+        assert!(middleware.ctx().sent.is_empty());
+    }
+
+    #[test]
+    fn test_forward_and_acknowledge() {
+        let mut middleware = get_dummy_middleware();
+        let memo = r#"{"forward":{"receiver":"final","port":"transfer","channel":"channel-2","timeout":"1s","next":{"wasm":{}}}}"#;
+        let packet = get_dummy_packet("uatom", memo);
+
+        // the funds are credited to the intermediate receiver and forwarded, without an ack
+        assert_eq!(recv_packet(&mut middleware, &packet), None);
+        let received = &middleware.app().received[0];
+        assert_eq!(received.receiver.as_ref(), "channel-1/sender");
+        assert!(received.memo.is_empty());
+
+        let transfer = &middleware.ctx().sent[0];
+        assert_eq!(transfer.sender.as_ref(), "channel-1/sender");
+        assert_eq!(transfer.receiver.as_ref(), "final");
+        assert_eq!(transfer.source_channel, ChannelId::new(2));
+        assert_eq!(transfer.token.denom.to_string(), "transfer/channel-1/uatom");
+        assert_eq!(
+            transfer.timeout_timestamp,
+            Timestamp::from_nanoseconds(1_000_001_000).unwrap()
+        );
+        assert_eq!(transfer.memo, r#"{"wasm":{}}"#);
+        assert!(middleware.ctx().acknowledgements.is_empty());
+
+        // the ack of the forwarded packet is written as the ack of the received packet
+        let forwarded = forwarded_packet(&middleware, 1);
+        ack_packet(&mut middleware, &forwarded, Acknowledgement::success());
+
+        assert_eq!(middleware.app().acknowledged, vec![forwarded]);
+        assert_eq!(
+            middleware.ctx().acknowledgements,
+            vec![(packet, Acknowledgement::success())]
+        );
+        assert!(middleware.ctx().in_flight_packets.is_empty());
+        assert!(middleware.ctx().burned.is_empty());
+    }
+
+    #[test]
+    fn test_forward_error_acknowledgement_refunds() {
+        let mut middleware = get_dummy_middleware();
+        let memo = r#"{"forward":{"receiver":"final","port":"transfer","channel":"channel-2"}}"#;
+        // the denom originates from this chain, so the received funds were unescrowed
+        let packet = get_dummy_packet("transfer/channel-0/uatom", memo);
+
+        recv_packet(&mut middleware, &packet);
+        let error_ack = Acknowledgement::Error("failed".to_string());
+        let forwarded = forwarded_packet(&middleware, 1);
+        ack_packet(&mut middleware, &forwarded, error_ack.clone());
+
+        let (holder, channel_id, coin) = &middleware.ctx().escrowed[0];
+        assert_eq!(holder.as_ref(), "channel-1/sender");
+        assert_eq!(channel_id, &ChannelId::new(1));
+        assert_eq!(coin.denom.to_string(), "uatom");
+        assert_eq!(middleware.ctx().acknowledgements, vec![(packet, error_ack)]);
+    }
+
+    #[test]
+    fn test_forward_timeout_retries_then_refunds() {
+        let mut middleware = get_dummy_middleware();
+        let memo = r#"{"forward":{"receiver":"final","port":"transfer","channel":"channel-2","retries":1}}"#;
+        let packet = get_dummy_packet("uatom", memo);
+
+        recv_packet(&mut middleware, &packet);
+
+        // the first timeout sends the forwarded packet again ...
+        let forwarded = forwarded_packet(&middleware, 1);
+        timeout_packet(&mut middleware, &forwarded);
+        assert_eq!(middleware.ctx().sent.len(), 2);
+        assert!(middleware.ctx().acknowledgements.is_empty());
+
+        // ... and the last one refunds the original sender
+        let forwarded = forwarded_packet(&middleware, 2);
+        timeout_packet(&mut middleware, &forwarded);
+        assert_eq!(middleware.ctx().sent.len(), 2);
+        assert!(middleware.ctx().in_flight_packets.is_empty());
+
+        let (holder, coin) = &middleware.ctx().burned[0];
+        assert_eq!(holder.as_ref(), "channel-1/sender");
+        assert_eq!(coin.denom.to_string(), "transfer/channel-1/uatom");
+
+        let (acked, ack) = &middleware.ctx().acknowledgements[0];
+        assert_eq!(acked, &packet);
+        assert!(matches!(ack, Acknowledgement::Error(_)));
+        assert_eq!(middleware.app().timed_out.len(), 2);
+    }
+
+    #[test]
+    fn test_recv_packet_with_invalid_forward() {
+        let mut middleware = get_dummy_middleware();
+        let memo = r#"{"forward":{"receiver":"final","port":"transfer"}}"#;
+
+        let ack = recv_packet(&mut middleware, &get_dummy_packet("uatom", memo)).unwrap();
+
+        // Only the code of the invalid memo error is acknowledged, not the error itself.
+        assert_eq!(
+            String::from_utf8(ack).unwrap(),
+            format!("ABCI code: 11: {}", ACK_ERR_STR)
+        );
+        assert!(middleware.app().received.is_empty());
+        assert!(middleware.ctx().sent.is_empty());
+    }
+}
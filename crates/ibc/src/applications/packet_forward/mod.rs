@@ -0,0 +1,29 @@
+//! Packet-forward middleware, replicating the behaviour of the `packet-forward-middleware` of
+//! Strangelove.
+//!
+//! The middleware wraps an ICS20 application module. When an incoming transfer packet carries a
+//! memo of the form `{"forward": {"receiver": "<addr>", "port": "<port>", "channel": "<chan>"}}`,
+//! the funds are credited to an intermediate account of this chain, then transferred on to
+//! `receiver` over the given channel, with the optional `next` entry of the memo as the memo of
+//! the forwarded packet. The acknowledgement of the incoming packet is written once the forwarded
+//! packet is acknowledged, so that failures propagate back to the original sender: forwarded
+//! packets that time out are sent again up to `retries` times, and the funds of packets that
+//! ultimately fail are refunded along the path. All other packets are passed through to the
+//! wrapped application untouched.
+pub mod context;
+pub mod error;
+pub mod metadata;
+pub mod middleware;
+
+pub use middleware::PacketForwardMiddleware;
+
+/// The key under which the forward metadata is encoded in the memo of a transfer packet.
+pub const FORWARD_MEMO_KEY: &str = "forward";
+
+/// The timeout of forwarded packets, relative to the time they are sent, when the memo does not
+/// set one.
+pub const DEFAULT_FORWARD_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(10 * 60);
+
+/// The number of times a forwarded packet that timed out is sent again, when the memo does not
+/// set one.
+pub const DEFAULT_FORWARD_RETRIES: u8 = 0;
@@ -1,9 +1,16 @@
 use core::fmt::{Display, Error as FmtError, Formatter};
 
+use ibc_proto::ibc::core::channel::v1::{
+    acknowledgement::Response as RawResponse, Acknowledgement as RawAcknowledgement,
+};
+use prost::Message;
 use serde::{Deserialize, Serialize};
 
 use super::error::Error;
-use crate::core::ics26_routing::context::Acknowledgement as AckTrait;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics26_routing::context::{
+    sanitized_ack_error, AckError, Acknowledgement as AckTrait,
+};
 use crate::prelude::*;
 
 /// A string constant included in error acknowledgements.
@@ -13,6 +20,9 @@ pub const ACK_ERR_STR: &str = "error handling packet on destination chain: see e
 /// A successful acknowledgement, equivalent to `base64::encode(0x01)`.
 pub const ACK_SUCCESS_B64: &str = "AQ==";
 
+/// The result of a successful acknowledgement, before its base64 encoding.
+const ACK_SUCCESS_RESULT: [u8; 1] = [0x01];
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConstAckSuccess {
     #[serde(rename = "AQ==")]
@@ -36,8 +46,10 @@ impl Acknowledgement {
         Self::Success(ConstAckSuccess::Success)
     }
 
-    pub fn from_error(err: Error) -> Self {
-        Self::Error(format!("{}: {}", ACK_ERR_STR, err))
+    /// Returns the error acknowledgement of `err`, which only carries its public reason code
+    /// (see [`AckError`]).
+    pub fn from_error(err: impl AckError) -> Self {
+        Self::Error(sanitized_ack_error(&err, ACK_ERR_STR))
     }
 }
 
@@ -61,6 +73,46 @@ impl Display for Acknowledgement {
 
 impl AckTrait for Acknowledgement {}
 
+impl TryFrom<RawAcknowledgement> for Acknowledgement {
+    type Error = Error;
+
+    fn try_from(raw_ack: RawAcknowledgement) -> Result<Self, Error> {
+        match raw_ack.response {
+            Some(RawResponse::Result(result)) if result == ACK_SUCCESS_RESULT => {
+                Ok(Self::success())
+            }
+            Some(RawResponse::Error(err)) if !err.is_empty() => Ok(Self::Error(err)),
+            _ => Err(Error::ack_deserialization()),
+        }
+    }
+}
+
+impl From<Acknowledgement> for RawAcknowledgement {
+    fn from(ack: Acknowledgement) -> Self {
+        let response = match ack {
+            Acknowledgement::Success(_) => RawResponse::Result(ACK_SUCCESS_RESULT.to_vec()),
+            Acknowledgement::Error(err) => RawResponse::Error(err),
+        };
+        Self {
+            response: Some(response),
+        }
+    }
+}
+
+/// Decodes the acknowledgement written by the counterparty, which is JSON encoded by the
+/// SDK chains but may be protobuf encoded by others, such as wasm chains.
+impl TryFrom<&GenericAcknowledgement> for Acknowledgement {
+    type Error = Error;
+
+    fn try_from(ack: &GenericAcknowledgement) -> Result<Self, Error> {
+        serde_json::from_slice(ack.as_ref()).or_else(|_| {
+            RawAcknowledgement::decode(ack.as_ref())
+                .map_err(|_| Error::ack_deserialization())?
+                .try_into()
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -95,4 +147,49 @@ mod test {
         assert!(serde_json::from_str::<Acknowledgement>(r#"{"result":"AQ="}"#).is_err());
         assert!(serde_json::from_str::<Acknowledgement>(r#"{"success":"AQ=="}"#).is_err());
     }
+
+    #[test]
+    fn test_ack_from_error() {
+        assert_eq!(
+            Acknowledgement::from_error(Error::receive_disabled()),
+            Acknowledgement::Error(format!("ABCI code: 8: {}", ACK_ERR_STR))
+        );
+
+        // The errors of the keepers of the host are internal, and their details never acknowledged.
+        let escrow_error =
+            Error::insufficient_total_escrow("uatom".parse().unwrap(), 20.into(), 10.into());
+        assert_eq!(
+            Acknowledgement::from_error(escrow_error),
+            Acknowledgement::Error(format!("ABCI code: 1: {}", ACK_ERR_STR))
+        );
+    }
+
+    #[test]
+    fn test_ack_decode_generic() {
+        fn decode(bytes: Vec<u8>) -> Result<Acknowledgement, Error> {
+            Acknowledgement::try_from(&GenericAcknowledgement::from(bytes))
+        }
+        let err = "cannot unmarshal ICS-20 transfer packet data".to_owned();
+
+        assert_eq!(
+            decode(br#"{"result":"AQ=="}"#.to_vec()).unwrap(),
+            Acknowledgement::success()
+        );
+        assert_eq!(
+            decode(RawAcknowledgement::from(Acknowledgement::success()).encode_to_vec()).unwrap(),
+            Acknowledgement::success()
+        );
+        assert_eq!(
+            decode(RawAcknowledgement::from(Acknowledgement::Error(err.clone())).encode_to_vec())
+                .unwrap(),
+            Acknowledgement::Error(err)
+        );
+
+        let unexpected_result = RawAcknowledgement {
+            response: Some(RawResponse::Result(vec![0x02])),
+        };
+        assert!(decode(unexpected_result.encode_to_vec()).is_err());
+        assert!(decode(RawAcknowledgement { response: None }.encode_to_vec()).is_err());
+        assert!(decode(b"AQ==".to_vec()).is_err());
+    }
 }
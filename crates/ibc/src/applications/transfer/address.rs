@@ -0,0 +1,174 @@
+//! The encodings of the account addresses of the hosts, in which the senders and receivers of
+//! transfers are given.
+
+use subtle_encoding::{bech32, hex};
+
+use crate::applications::transfer::error::Error;
+use crate::prelude::*;
+
+/// The alphabet of base58 addresses, as used by Bitcoin and Solana.
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The encoding of the account addresses of a host, between their raw bytes and the string form
+/// in which they are carried by transfer messages and packets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddressCodec {
+    /// Bech32 addresses with the given human-readable part, as on Cosmos SDK chains.
+    Bech32 { hrp: String },
+    /// `0x`-prefixed hex addresses, as on EVM-compatible chains.
+    Hex,
+    /// Base58 addresses, with the Bitcoin alphabet.
+    Base58,
+}
+
+impl AddressCodec {
+    /// Returns a bech32 codec with the given human-readable part.
+    pub fn bech32(hrp: impl Into<String>) -> Self {
+        Self::Bech32 { hrp: hrp.into() }
+    }
+
+    /// Decodes an address into its raw bytes, failing if it is not a valid address of the host.
+    pub fn decode(&self, address: &str) -> Result<Vec<u8>, Error> {
+        let invalid = |reason: String| Error::invalid_address(address.to_string(), reason);
+
+        let bytes = match self {
+            Self::Bech32 { hrp } => {
+                let (address_hrp, bytes) =
+                    bech32::decode(address).map_err(|e| invalid(e.to_string()))?;
+                if &address_hrp != hrp {
+                    return Err(invalid(format!("expected the bech32 prefix {}", hrp)));
+                }
+                bytes
+            }
+            Self::Hex => {
+                let digits = address
+                    .strip_prefix("0x")
+                    .ok_or_else(|| invalid("missing 0x prefix".to_string()))?;
+                hex::decode(digits.to_ascii_lowercase()).map_err(|e| invalid(e.to_string()))?
+            }
+            Self::Base58 => base58_decode(address).map_err(invalid)?,
+        };
+
+        if bytes.is_empty() {
+            return Err(invalid("empty address".to_string()));
+        }
+        Ok(bytes)
+    }
+
+    /// Encodes the raw bytes of an address of the host.
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Bech32 { hrp } => bech32::encode(hrp, bytes),
+            Self::Hex => format!(
+                "0x{}",
+                String::from_utf8(hex::encode(bytes)).expect("hex is valid UTF-8")
+            ),
+            Self::Base58 => base58_encode(bytes),
+        }
+    }
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+
+    // The base58 digits of the number, least significant first.
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in &bytes[zeros..] {
+        let mut carry = u32::from(byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    core::iter::repeat(BASE58_ALPHABET[0])
+        .take(zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&d| BASE58_ALPHABET[usize::from(d)]),
+        )
+        .map(char::from)
+        .collect()
+}
+
+fn base58_decode(address: &str) -> Result<Vec<u8>, String> {
+    let zeros = address
+        .bytes()
+        .take_while(|&c| c == BASE58_ALPHABET[0])
+        .count();
+
+    // The bytes of the number, least significant first.
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in address.bytes().skip(zeros) {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or_else(|| format!("invalid base58 character {:?}", char::from(c)))?
+            as u32;
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    Ok(core::iter::repeat(0)
+        .take(zeros)
+        .chain(bytes.into_iter().rev())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_codec_roundtrip() {
+        let bytes = [0, 0, 17, 42, 255, 3];
+        for codec in [
+            AddressCodec::bech32("cosmos"),
+            AddressCodec::Hex,
+            AddressCodec::Base58,
+        ] {
+            let address = codec.encode(&bytes);
+            assert_eq!(codec.decode(&address).unwrap(), bytes, "{}", address);
+        }
+    }
+
+    #[test]
+    fn address_codec_known_addresses() {
+        let evm = "0x52908400098527886E0F7030069857D2E4169EE7";
+        assert_eq!(AddressCodec::Hex.decode(evm).unwrap().len(), 20);
+
+        // The Bitcoin address of the hash `00010966776006953d5567439e5e39f86a0d273bee`.
+        let bitcoin = "16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvM";
+        let decoded = AddressCodec::Base58.decode(bitcoin).unwrap();
+        assert_eq!(
+            hex::encode(&decoded[..21]),
+            b"00010966776006953d5567439e5e39f86a0d273bee"
+        );
+        assert_eq!(AddressCodec::Base58.encode(&decoded), bitcoin);
+    }
+
+    #[test]
+    fn address_codec_rejects_foreign_addresses() {
+        let cosmos = AddressCodec::bech32("cosmos").encode(&[1; 20]);
+
+        assert!(AddressCodec::bech32("osmo").decode(&cosmos).is_err());
+        assert!(AddressCodec::Hex.decode(&cosmos).is_err());
+        assert!(AddressCodec::Hex.decode("52908400098527886e0f").is_err());
+        assert!(AddressCodec::Base58.decode("0OIl").is_err());
+        assert!(AddressCodec::Base58.decode("").is_err());
+    }
+}
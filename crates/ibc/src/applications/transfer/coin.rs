@@ -1,4 +1,6 @@
+use core::cmp::Ordering;
 use core::fmt::{Display, Error as FmtError, Formatter};
+use core::ops::Deref;
 use core::str::{from_utf8, FromStr};
 use ibc_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
 use safe_regex::regex;
@@ -18,6 +20,9 @@ pub type BaseCoin = Coin<BaseDenom>;
 
 pub type RawCoin = Coin<String>;
 
+/// A `Coins` collection with fully qualified `PrefixedDenom`s.
+pub type PrefixedCoins = Coins<PrefixedDenom>;
+
 /// Coin defines a token with a denomination and an amount.
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Coin<D> {
@@ -28,6 +33,39 @@ pub struct Coin<D> {
     pub amount: Amount,
 }
 
+impl<D: PartialEq + Display> Coin<D> {
+    /// Adds the amount of `other`, a coin of the same denomination.
+    pub fn checked_add(self, other: Self) -> Result<Self, Error> {
+        self.check_denom(&other)?;
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or_else(|| Error::coin_overflow(self.denom.to_string()))?;
+        Ok(Self { amount, ..self })
+    }
+
+    /// Subtracts the amount of `other`, a coin of the same denomination and of at most the same
+    /// amount.
+    pub fn checked_sub(self, other: Self) -> Result<Self, Error> {
+        self.check_denom(&other)?;
+        let amount = self
+            .amount
+            .checked_sub(other.amount)
+            .ok_or_else(|| Error::insufficient_coins(other.to_string(), self.to_string()))?;
+        Ok(Self { amount, ..self })
+    }
+
+    fn check_denom(&self, other: &Self) -> Result<(), Error> {
+        if self.denom != other.denom {
+            return Err(Error::denom_mismatch(
+                self.denom.to_string(),
+                other.denom.to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl<D: FromStr> Coin<D>
 where
     D::Err: Into<Error>,
@@ -97,15 +135,194 @@ impl From<BaseCoin> for PrefixedCoin {
     }
 }
 
+impl From<PrefixedCoin> for RawCoin {
+    fn from(coin: PrefixedCoin) -> RawCoin {
+        RawCoin {
+            denom: coin.denom.to_string(),
+            amount: coin.amount,
+        }
+    }
+}
+
 impl<D: Display> Display for Coin<D> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         write!(f, "{}{}", self.amount, self.denom)
     }
 }
 
+/// A collection of coins of distinct denominations, none of which has a zero amount, sorted by
+/// denomination as the `sdk.Coins` of the Cosmos SDK are, i.e. by the string representation of
+/// the denominations.
+///
+/// Holds the amounts of several tokens at once, e.g. the fees paid for the relaying of a packet.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(
+    try_from = "Vec<Coin<D>>",
+    into = "Vec<Coin<D>>",
+    bound(
+        serialize = "D: Clone + Serialize",
+        deserialize = "D: PartialEq + Display + Deserialize<'de>"
+    )
+)]
+pub struct Coins<D>(Vec<Coin<D>>);
+
+impl<D> Default for Coins<D> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+fn cmp_denoms<D: Display>(denom: &D, other: &D) -> Ordering {
+    denom.to_string().cmp(&other.to_string())
+}
+
+impl<D: PartialEq + Display> Coins<D> {
+    /// Returns the amount of the coins of `denom`, zero if there are none.
+    pub fn amount_of(&self, denom: &D) -> Amount {
+        self.position(denom)
+            .map_or_else(|_| 0u64.into(), |i| self.0[i].amount)
+    }
+
+    /// Adds `coin`, merging it with the coins of the same denomination, if any.
+    pub fn checked_add_coin(mut self, coin: Coin<D>) -> Result<Self, Error> {
+        match self.position(&coin.denom) {
+            Ok(i) => {
+                let sum = self.0.remove(i).checked_add(coin)?;
+                self.0.insert(i, sum);
+            }
+            Err(i) if coin.amount != 0u64.into() => self.0.insert(i, coin),
+            Err(_) => {}
+        }
+        Ok(self)
+    }
+
+    /// Subtracts `coin` from the coins of the same denomination, which must be at least as many.
+    pub fn checked_sub_coin(mut self, coin: Coin<D>) -> Result<Self, Error> {
+        match self.position(&coin.denom) {
+            Ok(i) => {
+                let difference = self.0.remove(i).checked_sub(coin)?;
+                if difference.amount != 0u64.into() {
+                    self.0.insert(i, difference);
+                }
+            }
+            Err(_) if coin.amount != 0u64.into() => {
+                return Err(Error::insufficient_coins(
+                    coin.to_string(),
+                    format!("0{}", coin.denom),
+                ))
+            }
+            Err(_) => {}
+        }
+        Ok(self)
+    }
+
+    /// Adds all the coins of `other`.
+    pub fn checked_add(self, other: Self) -> Result<Self, Error> {
+        other.0.into_iter().try_fold(self, Self::checked_add_coin)
+    }
+
+    /// Subtracts all the coins of `other`, each of which must be covered by `self`.
+    pub fn checked_sub(self, other: Self) -> Result<Self, Error> {
+        other.0.into_iter().try_fold(self, Self::checked_sub_coin)
+    }
+
+    fn position(&self, denom: &D) -> Result<usize, usize> {
+        self.0
+            .binary_search_by(|coin| cmp_denoms(&coin.denom, denom))
+    }
+}
+
+impl<D> Deref for Coins<D> {
+    type Target = [Coin<D>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<D> IntoIterator for Coins<D> {
+    type Item = Coin<D>;
+    type IntoIter = vec::IntoIter<Coin<D>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, D> IntoIterator for &'a Coins<D> {
+    type Item = &'a Coin<D>;
+    type IntoIter = core::slice::Iter<'a, Coin<D>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Checks the invariants of `Coins`, rejecting rather than normalizing coins that break them.
+impl<D: PartialEq + Display> TryFrom<Vec<Coin<D>>> for Coins<D> {
+    type Error = Error;
+
+    fn try_from(coins: Vec<Coin<D>>) -> Result<Self, Self::Error> {
+        let invalid = |reason: &str| {
+            let coins: Vec<_> = coins.iter().map(ToString::to_string).collect();
+            Error::invalid_coins(coins.join(","), reason.to_string())
+        };
+        if coins.iter().any(|coin| coin.amount == 0u64.into()) {
+            return Err(invalid("zero amount"));
+        }
+        if coins
+            .windows(2)
+            .any(|pair| cmp_denoms(&pair[0].denom, &pair[1].denom) != Ordering::Less)
+        {
+            return Err(invalid("denominations are unsorted or duplicate"));
+        }
+        Ok(Self(coins))
+    }
+}
+
+impl<D> From<Coins<D>> for Vec<Coin<D>> {
+    fn from(coins: Coins<D>) -> Self {
+        coins.0
+    }
+}
+
+impl<D: FromStr + PartialEq + Display> TryFrom<Vec<ProtoCoin>> for Coins<D>
+where
+    D::Err: Into<Error>,
+{
+    type Error = Error;
+
+    fn try_from(proto: Vec<ProtoCoin>) -> Result<Self, Self::Error> {
+        proto
+            .into_iter()
+            .map(Coin::try_from)
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+    }
+}
+
+impl<D: ToString> From<Coins<D>> for Vec<ProtoCoin> {
+    fn from(coins: Coins<D>) -> Self {
+        coins.0.into_iter().map(Into::into).collect()
+    }
+}
+
+impl<D: Display> Display for Coins<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        for (i, coin) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", coin)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bigint::U256;
 
     #[test]
     fn test_parse_raw_coin() -> Result<(), Error> {
@@ -154,4 +371,85 @@ mod tests {
 
         Ok(())
     }
+
+    fn coin(coin: &str) -> RawCoin {
+        coin.parse().unwrap()
+    }
+
+    fn coins(coins: &str) -> Coins<String> {
+        RawCoin::from_string_list(coins)
+            .unwrap()
+            .into_iter()
+            .try_fold(Coins::default(), Coins::checked_add_coin)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_coin_arithmetic() {
+        assert_eq!(
+            coin("1stake").checked_add(coin("2stake")).unwrap(),
+            coin("3stake")
+        );
+        assert_eq!(
+            coin("3stake").checked_sub(coin("3stake")).unwrap(),
+            coin("0stake")
+        );
+        assert!(coin("1stake").checked_sub(coin("2stake")).is_err());
+        assert!(coin("1stake").checked_add(coin("1atom")).is_err());
+
+        let max = RawCoin {
+            denom: "stake".to_string(),
+            amount: U256::MAX.into(),
+        };
+        assert!(max.checked_add(coin("1stake")).is_err());
+    }
+
+    #[test]
+    fn test_coins_are_sorted_and_merged() {
+        let sum = coins("3stake,1uatom,2atom,0zero,4stake");
+        assert_eq!(sum.to_string(), "2atom,7stake,1uatom");
+        assert_eq!(sum.amount_of(&"stake".to_string()), 7u64.into());
+        assert_eq!(sum.amount_of(&"zero".to_string()), 0u64.into());
+
+        let total = sum.clone().checked_add(coins("1atom,1btc")).unwrap();
+        assert_eq!(total.to_string(), "3atom,1btc,7stake,1uatom");
+        assert_eq!(
+            total.checked_sub(coins("1btc,7stake")).unwrap().to_string(),
+            "3atom,1uatom"
+        );
+
+        // There is not enough, or nothing at all, to subtract from.
+        assert!(sum.clone().checked_sub(coins("8stake")).is_err());
+        assert!(sum.checked_sub(coins("1btc")).is_err());
+    }
+
+    #[test]
+    fn test_coins_conversions() {
+        let coins = coins("1atom,2stake");
+        let proto = Vec::<ProtoCoin>::from(coins.clone());
+        assert_eq!(proto[1].denom, "stake");
+        assert_eq!(Coins::try_from(proto.clone()).unwrap(), coins);
+
+        let json = serde_json::to_string(&coins).unwrap();
+        assert_eq!(serde_json::from_str::<Coins<String>>(&json).unwrap(), coins);
+
+        // Unsorted, duplicate and zero coins are rejected rather than normalized.
+        for invalid in [
+            vec![proto[1].clone(), proto[0].clone()],
+            vec![proto[0].clone(), proto[0].clone()],
+            vec![ProtoCoin {
+                denom: "stake".to_string(),
+                amount: "0".to_string(),
+            }],
+        ] {
+            assert!(matches!(
+                Coins::<String>::try_from(invalid).unwrap_err().detail(),
+                crate::applications::transfer::error::ErrorDetail::InvalidCoins(_)
+            ));
+        }
+        assert!(serde_json::from_str::<Coins<String>>(
+            r#"[{"denom":"b","amount":"1"},{"denom":"a","amount":"1"}]"#
+        )
+        .is_err());
+    }
 }
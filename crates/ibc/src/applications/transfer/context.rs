@@ -7,7 +7,10 @@ use crate::applications::transfer::packet::PacketData;
 use crate::applications::transfer::relay::on_ack_packet::process_ack_packet;
 use crate::applications::transfer::relay::on_recv_packet::process_recv_packet;
 use crate::applications::transfer::relay::on_timeout_packet::process_timeout_packet;
-use crate::applications::transfer::{PrefixedCoin, PrefixedDenom, VERSION};
+use crate::applications::transfer::{
+    parse_hashed_denom, AddressCodec, Amount, PrefixedCoin, PrefixedDenom, VoucherDenomFormat,
+    DEFAULT_MAX_DENOM_LENGTH, DEFAULT_MAX_TRACE_HOPS, VERSION,
+};
 use crate::core::ics04_channel::channel::{Counterparty, Order};
 use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
 use crate::core::ics04_channel::handler::ModuleExtras;
@@ -20,37 +23,154 @@ use crate::prelude::*;
 use crate::signer::Signer;
 
 pub trait Ics20Keeper:
-    ChannelKeeper + BankKeeper<AccountId = <Self as Ics20Keeper>::AccountId>
+    ChannelKeeper
+    + DenomTraceKeeper
+    + EscrowKeeper
+    + BankKeeper<AccountId = <Self as Ics20Keeper>::AccountId>
 {
     type AccountId;
 }
 
-pub trait Ics20Reader: ChannelReader {
+pub trait Ics20Reader: ChannelReader + DenomTraceReader + EscrowReader {
     type AccountId: TryFrom<Signer>;
 
     /// get_port returns the portID for the transfer module.
     fn get_port(&self) -> Result<PortId, Ics20Error>;
 
-    /// Returns the escrow account id for a port and channel combination
+    /// Returns the raw escrow address for a port and channel combination.
+    ///
+    /// Defaults to the Cosmos SDK derivation (see [`cosmos_adr028_escrow_address`]). Hosts with a
+    /// different address format, e.g. EVM-compatible chains or hosts escrowing funds in a
+    /// contract, override it to plug in their own derivation.
+    fn escrow_address_bytes(&self, port_id: &PortId, channel_id: &ChannelId) -> Vec<u8> {
+        cosmos_adr028_escrow_address(port_id, channel_id)
+    }
+
+    /// Returns the escrow account id for a port and channel combination, typically by encoding
+    /// the address returned by `escrow_address_bytes` with the
+    /// [`address_codec`](Self::address_codec) of the host.
     fn get_channel_escrow_address(
         &self,
         port_id: &PortId,
         channel_id: &ChannelId,
     ) -> Result<<Self as Ics20Reader>::AccountId, Ics20Error>;
 
+    /// Returns the encoding of the account addresses of the host, against which the senders and
+    /// receivers of transfers are checked before being parsed into account ids.
+    ///
+    /// Defaults to `None`, leaving their validation to the `TryFrom<Signer>` implementation of
+    /// the account ids.
+    fn address_codec(&self) -> Option<AddressCodec> {
+        None
+    }
+
+    /// Parses the account id of the sender or receiver of a transfer, rejecting the addresses
+    /// which the [`address_codec`](Self::address_codec) of the host fails to decode.
+    fn parse_account(
+        &self,
+        address: &Signer,
+    ) -> Result<<Self as Ics20Reader>::AccountId, Ics20Error> {
+        if let Some(codec) = self.address_codec() {
+            codec.decode(address.as_str())?;
+        }
+        address
+            .clone()
+            .try_into()
+            .map_err(|_| Ics20Error::parse_account_failure())
+    }
+
     /// Returns true iff send is enabled.
     fn is_send_enabled(&self) -> bool;
 
     /// Returns true iff receive is enabled.
     fn is_receive_enabled(&self) -> bool;
 
-    /// Returns a hash of the prefixed denom.
-    /// Implement only if the host chain supports hashed denominations.
-    fn denom_hash_string(&self, _denom: &PrefixedDenom) -> Option<String> {
-        None
+    /// Returns the maximum number of hops in the trace of a voucher minted on receive. Packets
+    /// that would mint a voucher with a longer trace are rejected with an error acknowledgement.
+    fn max_trace_hops(&self) -> usize {
+        DEFAULT_MAX_TRACE_HOPS
+    }
+
+    /// Returns the maximum length of the denomination, trace included, of a voucher minted on
+    /// receive. Packets that would mint a voucher with a longer denomination are rejected with an
+    /// error acknowledgement.
+    fn max_denom_length(&self) -> usize {
+        DEFAULT_MAX_DENOM_LENGTH
+    }
+
+    /// Returns the format of the denomination of the vouchers minted by the host, in which
+    /// [`voucher_denom`](Self::voucher_denom) renders them.
+    fn voucher_denom_format(&self) -> VoucherDenomFormat {
+        VoucherDenomFormat::default()
+    }
+
+    /// Returns the denomination under which the host bank holds `denom`, in particular the
+    /// vouchers it mints and the funds it escrows. `BankKeeper` implementations key the coins they
+    /// are given by this denomination.
+    fn voucher_denom(&self, denom: &PrefixedDenom) -> String {
+        self.voucher_denom_format().render(denom)
+    }
+
+    /// Returns a hash of the prefixed denom, if the host hashes voucher denominations.
+    fn denom_hash_string(&self, denom: &PrefixedDenom) -> Option<String> {
+        match self.voucher_denom_format() {
+            VoucherDenomFormat::Hashed => Some(denom.hash()),
+            VoucherDenomFormat::FullTrace => None,
+        }
+    }
+
+    /// Resolves the denomination of a token to transfer, given either as a hashed voucher
+    /// denomination `ibc/{hash}` or as a full denomination path, whatever the format of the host.
+    fn resolve_denom(&self, denom: &str) -> Result<PrefixedDenom, Ics20Error> {
+        match parse_hashed_denom(denom)? {
+            Some(hash) => self
+                .denom_trace(&hash.to_uppercase())
+                .ok_or_else(Ics20Error::trace_not_found),
+            None => denom.parse(),
+        }
+    }
+}
+
+/// The registry of the denominations of the vouchers minted by the host, by their
+/// [hash](PrefixedDenom::hash), as kept by ibc-go's transfer keeper, for the hashed denominations
+/// `ibc/{hash}` of vouchers to be resolved.
+pub trait DenomTraceReader {
+    /// Returns the denomination whose hash is `hash`, in uppercase hex, if the host minted vouchers
+    /// of it.
+    fn denom_trace(&self, hash: &str) -> Option<PrefixedDenom>;
+
+    /// Returns all the denominations of the vouchers minted by the host.
+    fn all_denom_traces(&self) -> Vec<PrefixedDenom>;
+
+    fn has_denom_trace(&self, hash: &str) -> bool {
+        self.denom_trace(hash).is_some()
     }
 }
 
+pub trait DenomTraceKeeper: DenomTraceReader {
+    /// Records the denomination `denom` under its hash `hash`. Called when receiving the first
+    /// voucher of the denomination.
+    fn store_denom_trace(&mut self, hash: String, denom: PrefixedDenom) -> Result<(), Ics20Error>;
+}
+
+/// The total amounts of the tokens escrowed by the host in all its channels, per denomination,
+/// as kept by ibc-go's transfer keeper since v7.1, e.g. for the host to check that the tokens
+/// locked in the escrow accounts match those circulating as vouchers on other chains.
+pub trait EscrowReader {
+    /// Returns the total amount of `denom` escrowed by the host, zero if none.
+    fn get_total_escrow(&self, denom: &PrefixedDenom) -> Amount;
+}
+
+pub trait EscrowKeeper: EscrowReader {
+    /// Records the total amount of `denom` escrowed. Called whenever tokens of the denomination
+    /// are escrowed on send, or unescrowed on receive or refund.
+    fn store_total_escrow(
+        &mut self,
+        denom: PrefixedDenom,
+        amount: Amount,
+    ) -> Result<(), Ics20Error>;
+}
+
 // https://github.com/cosmos/cosmos-sdk/blob/master/docs/architecture/adr-028-public-key-addresses.md
 pub fn cosmos_adr028_escrow_address(port_id: &PortId, channel_id: &ChannelId) -> Vec<u8> {
     let contents = format!("{}/{}", port_id, channel_id);
@@ -100,6 +220,9 @@ pub trait Ics20Context:
     type AccountId: TryFrom<Signer>;
 }
 
+/// Opens an ICS20 channel. Hosts declare `Version::ics20()` as the version supported by their
+/// transfer module (see `Module::supported_versions`), for the core to reject other versions
+/// before invoking the handshake callbacks.
 #[allow(clippy::too_many_arguments)]
 pub fn on_chan_open_init(
     ctx: &mut impl Ics20Context,
@@ -108,7 +231,7 @@ pub fn on_chan_open_init(
     port_id: &PortId,
     _channel_id: &ChannelId,
     _counterparty: &Counterparty,
-    version: &Version,
+    _version: &Version,
 ) -> Result<(ModuleExtras, Version), Ics20Error> {
     if order != Order::Unordered {
         return Err(Ics20Error::channel_not_unordered(order));
@@ -118,10 +241,6 @@ pub fn on_chan_open_init(
         return Err(Ics20Error::invalid_port(port_id.clone(), bound_port));
     }
 
-    if !version.is_empty() && version != &Version::ics20() {
-        return Err(Ics20Error::invalid_version(version.clone()));
-    }
-
     Ok((ModuleExtras::empty(), Version::ics20()))
 }
 
@@ -133,17 +252,11 @@ pub fn on_chan_open_try(
     _port_id: &PortId,
     _channel_id: &ChannelId,
     _counterparty: &Counterparty,
-    counterparty_version: &Version,
+    _counterparty_version: &Version,
 ) -> Result<(ModuleExtras, Version), Ics20Error> {
     if order != Order::Unordered {
         return Err(Ics20Error::channel_not_unordered(order));
     }
-    if counterparty_version != &Version::ics20() {
-        return Err(Ics20Error::invalid_counterparty_version(
-            counterparty_version.clone(),
-        ));
-    }
-
     Ok((ModuleExtras::empty(), Version::ics20()))
 }
 
@@ -151,14 +264,8 @@ pub fn on_chan_open_ack(
     _ctx: &mut impl Ics20Context,
     _port_id: &PortId,
     _channel_id: &ChannelId,
-    counterparty_version: &Version,
+    _counterparty_version: &Version,
 ) -> Result<ModuleExtras, Ics20Error> {
-    if counterparty_version != &Version::ics20() {
-        return Err(Ics20Error::invalid_counterparty_version(
-            counterparty_version.clone(),
-        ));
-    }
-
     Ok(ModuleExtras::empty())
 }
 
@@ -195,22 +302,34 @@ pub fn on_recv_packet<Ctx: 'static + Ics20Context>(
     let data = match serde_json::from_slice::<PacketData>(&packet.data) {
         Ok(data) => data,
         Err(_) => {
-            return OnRecvPacketAck::Failed(Box::new(Acknowledgement::Error(
-                Ics20Error::packet_data_deserialization().to_string(),
+            return OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(
+                Ics20Error::packet_data_deserialization(),
             )))
         }
     };
 
-    let ack = match process_recv_packet(ctx, output, packet, data.clone()) {
-        Ok(write_fn) => OnRecvPacketAck::Successful(Box::new(Acknowledgement::success()), write_fn),
-        Err(e) => OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e))),
+    let (ack, error) = match process_recv_packet(ctx, output, packet, data.clone()) {
+        Ok(write_fn) => (
+            OnRecvPacketAck::Successful(Box::new(Acknowledgement::success()), write_fn),
+            None,
+        ),
+        Err(e) => {
+            let error = e.to_string();
+            (
+                OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e))),
+                Some(error),
+            )
+        }
     };
 
     let recv_event = RecvEvent {
+        sender: data.sender,
         receiver: data.receiver,
         denom: data.token.denom,
         amount: data.token.amount,
+        memo: data.memo,
         success: ack.is_successful(),
+        error,
     };
     output.emit(recv_event.into());
 
@@ -227,15 +346,16 @@ pub fn on_acknowledgement_packet(
     let data = serde_json::from_slice::<PacketData>(&packet.data)
         .map_err(|_| Ics20Error::packet_data_deserialization())?;
 
-    let acknowledgement = serde_json::from_slice::<Acknowledgement>(acknowledgement.as_ref())
-        .map_err(|_| Ics20Error::ack_deserialization())?;
+    let acknowledgement = Acknowledgement::try_from(acknowledgement)?;
 
     process_ack_packet(ctx, packet, &data, &acknowledgement)?;
 
     let ack_event = AckEvent {
+        sender: data.sender,
         receiver: data.receiver,
         denom: data.token.denom,
         amount: data.token.amount,
+        memo: data.memo,
         acknowledgement: acknowledgement.clone(),
     };
     output.emit(ack_event.into());
@@ -259,6 +379,7 @@ pub fn on_timeout_packet(
         refund_receiver: data.sender,
         refund_denom: data.token.denom,
         refund_amount: data.token.amount,
+        memo: data.memo,
     };
     output.emit(timeout_event.into());
 
@@ -269,7 +390,9 @@ pub fn on_timeout_packet(
 pub(crate) mod test {
     use subtle_encoding::bech32;
 
-    use crate::applications::transfer::context::{cosmos_adr028_escrow_address, on_chan_open_try};
+    use crate::applications::transfer::context::{
+        cosmos_adr028_escrow_address, on_chan_open_try, Ics20Reader,
+    };
     use crate::applications::transfer::error::Error as Ics20Error;
     use crate::applications::transfer::msgs::transfer::MsgTransfer;
     use crate::applications::transfer::relay::send_transfer::send_transfer;
@@ -345,6 +468,16 @@ pub(crate) mod test {
             "channel-187",
             "cosmos177x69sver58mcfs74x6dg0tv6ls4s3xmmcaw53",
         );
+
+        // Hosts that do not override the derivation get the ADR-028 escrow addresses.
+        let ctx = get_dummy_transfer_module();
+        let escrow_address = ctx
+            .get_channel_escrow_address(&PortId::transfer(), &"channel-141".parse().unwrap())
+            .unwrap();
+        assert_eq!(
+            escrow_address.to_string(),
+            "cosmos1x54ltnyg88k0ejmk8ytwrhd3ltm84xehrnlslf"
+        );
     }
 
     /// If the relayer passed "", indicating that it wants us to return the versions we support.
@@ -389,25 +522,6 @@ pub(crate) mod test {
         assert_eq!(out_version, Version::ics20());
     }
 
-    /// If the relayer passed in an unsupported version, then fail
-    #[test]
-    fn test_on_chan_open_init_incorrect_version() {
-        let (mut ctx, order, connection_hops, port_id, channel_id, counterparty) = get_defaults();
-
-        let in_version = Version::new("some-unsupported-version".to_string());
-        let res = on_chan_open_init(
-            &mut ctx,
-            order,
-            &connection_hops,
-            &port_id,
-            &channel_id,
-            &counterparty,
-            &in_version,
-        );
-
-        assert!(res.is_err());
-    }
-
     /// If the counterparty supports ics20, then return ics20
     #[test]
     fn test_on_chan_open_try_counterparty_correct_version() {
@@ -429,23 +543,410 @@ pub(crate) mod test {
         assert_eq!(out_version, Version::ics20());
     }
 
-    /// If the counterparty doesn't support ics20, then fail
     #[test]
-    fn test_on_chan_open_try_counterparty_incorrect_version() {
-        let (mut ctx, order, connection_hops, port_id, channel_id, counterparty) = get_defaults();
+    fn test_recv_packet_denom_limits() {
+        use crate::applications::transfer::error::ErrorDetail;
+        use crate::applications::transfer::packet::PacketData;
+        use crate::applications::transfer::relay::on_recv_packet::process_recv_packet;
+        use crate::applications::transfer::DEFAULT_MAX_TRACE_HOPS;
+        use crate::core::ics04_channel::packet::Packet;
+        use crate::core::ics26_routing::context::ModuleOutputBuilder;
+
+        let ctx = get_dummy_transfer_module();
+        let packet = Packet {
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(0),
+            destination_port: PortId::transfer(),
+            destination_channel: ChannelId::new(1),
+            ..Default::default()
+        };
+        let recv = |denom: String| {
+            let data = PacketData::new(
+                PrefixedCoin {
+                    denom: denom.parse().unwrap(),
+                    amount: 100.into(),
+                },
+                "sender".parse().unwrap(),
+                "receiver".parse().unwrap(),
+            );
+            process_recv_packet(&ctx, &mut ModuleOutputBuilder::new(), &packet, data).map(|_| ())
+        };
+
+        // The voucher gets one more hop on receive.
+        let traced = |hops: usize| "transfer/channel-5/".repeat(hops) + "uatom";
+        assert!(recv(traced(DEFAULT_MAX_TRACE_HOPS - 1)).is_ok());
+        assert!(matches!(
+            recv(traced(DEFAULT_MAX_TRACE_HOPS)).unwrap_err().detail(),
+            ErrorDetail::TooManyTraceHops(e) if e.hops == DEFAULT_MAX_TRACE_HOPS + 1
+        ));
 
-        let counterparty_version = Version::new("some-unsupported-version".to_string());
+        assert!(recv("a".repeat(2000)).is_ok());
+        assert!(matches!(
+            recv("a".repeat(2100)).unwrap_err().detail(),
+            ErrorDetail::DenomTooLong(_)
+        ));
+    }
 
-        let res = on_chan_open_try(
-            &mut ctx,
-            order,
-            &connection_hops,
-            &port_id,
-            &channel_id,
-            &counterparty,
-            &counterparty_version,
+    #[test]
+    fn test_address_codec() {
+        use crate::applications::transfer::context::EscrowKeeper;
+        use crate::applications::transfer::error::ErrorDetail;
+        use crate::applications::transfer::packet::PacketData;
+        use crate::applications::transfer::relay::on_recv_packet::process_recv_packet;
+        use crate::applications::transfer::relay::on_timeout_packet::process_timeout_packet;
+        use crate::applications::transfer::AddressCodec;
+        use crate::core::ics04_channel::packet::Packet;
+        use crate::core::ics26_routing::context::ModuleOutputBuilder;
+        use crate::signer::Signer;
+        use crate::test_utils::get_dummy_bech32_account;
+
+        // An EVM-compatible host.
+        let mut ctx = get_dummy_transfer_module().with_address_codec(AddressCodec::Hex);
+        let packet = Packet {
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(0),
+            destination_port: PortId::transfer(),
+            destination_channel: ChannelId::new(1),
+            ..Default::default()
+        };
+        let evm_account: Signer = AddressCodec::Hex.encode(&[7; 20]).parse().unwrap();
+        let cosmos_account: Signer = get_dummy_bech32_account().parse().unwrap();
+        let data = |sender: &Signer, receiver: &Signer| {
+            PacketData::new(
+                PrefixedCoin {
+                    denom: "uatom".parse().unwrap(),
+                    amount: 100.into(),
+                },
+                sender.clone(),
+                receiver.clone(),
+            )
+        };
+
+        let recv = |ctx: &DummyTransferModule, receiver: &Signer| {
+            process_recv_packet(
+                ctx,
+                &mut ModuleOutputBuilder::new(),
+                &packet,
+                data(&cosmos_account, receiver),
+            )
+            .map(|_| ())
+        };
+        assert!(recv(&ctx, &evm_account).is_ok());
+        assert!(matches!(
+            recv(&ctx, &cosmos_account).unwrap_err().detail(),
+            ErrorDetail::InvalidAddress(e) if e.address == cosmos_account.as_str()
+        ));
+
+        // The tokens of a transfer which timed out are unescrowed back to its sender.
+        ctx.store_total_escrow("uatom".parse().unwrap(), 100.into())
+            .unwrap();
+        process_timeout_packet(&mut ctx, &packet, &data(&evm_account, &cosmos_account)).unwrap();
+        assert_eq!(ctx.balance(&evm_account, "uatom"), 100);
+        assert!(
+            process_timeout_packet(&mut ctx, &packet, &data(&cosmos_account, &evm_account))
+                .is_err()
         );
 
-        assert!(res.is_err());
+        let escrow = ctx
+            .get_channel_escrow_address(&PortId::transfer(), &ChannelId::new(0))
+            .unwrap();
+        assert!(escrow.as_str().starts_with("0x"));
+    }
+
+    #[test]
+    fn test_recv_packet_records_denom_traces() {
+        use crate::applications::transfer::context::{DenomTraceReader, EscrowKeeper};
+        use crate::applications::transfer::packet::PacketData;
+        use crate::applications::transfer::relay::on_recv_packet::process_recv_packet;
+        use crate::applications::transfer::PrefixedDenom;
+        use crate::core::ics04_channel::packet::Packet;
+        use crate::core::ics26_routing::context::ModuleOutputBuilder;
+
+        let mut ctx = get_dummy_transfer_module();
+        ctx.store_total_escrow("uosmo".parse().unwrap(), 100.into())
+            .unwrap();
+        let packet = Packet {
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(0),
+            destination_port: PortId::transfer(),
+            destination_channel: ChannelId::new(1),
+            ..Default::default()
+        };
+        let mut recv = |denom: &str| {
+            let data = PacketData::new(
+                PrefixedCoin {
+                    denom: denom.parse().unwrap(),
+                    amount: 100.into(),
+                },
+                "sender".parse().unwrap(),
+                "receiver".parse().unwrap(),
+            );
+            let write_fn =
+                process_recv_packet(&ctx, &mut ModuleOutputBuilder::new(), &packet, data).unwrap();
+            write_fn(&mut ctx).unwrap();
+        };
+
+        // The vouchers of a denomination are recorded once, when first received ...
+        recv("uatom");
+        recv("uatom");
+        // ... unlike the tokens that return to the host, which are unescrowed.
+        recv("transfer/channel-0/uosmo");
+
+        let voucher: PrefixedDenom = "transfer/channel-1/uatom".parse().unwrap();
+        assert_eq!(ctx.all_denom_traces(), vec![voucher.clone()]);
+        assert_eq!(ctx.denom_trace(&voucher.hash()), Some(voucher.clone()));
+        assert_eq!(
+            ctx.resolve_denom(&format!("ibc/{}", voucher.hash().to_lowercase()))
+                .unwrap(),
+            voucher
+        );
+        assert!(!ctx.has_denom_trace(&"uosmo".parse::<PrefixedDenom>().unwrap().hash()));
+    }
+
+    #[test]
+    fn test_total_escrow() {
+        use crate::applications::transfer::context::{EscrowKeeper, EscrowReader};
+        use crate::applications::transfer::error::ErrorDetail;
+        use crate::applications::transfer::packet::PacketData;
+        use crate::applications::transfer::relay::on_recv_packet::process_recv_packet;
+        use crate::applications::transfer::relay::on_timeout_packet::process_timeout_packet;
+        use crate::applications::transfer::PrefixedDenom;
+        use crate::core::ics04_channel::packet::Packet;
+        use crate::core::ics26_routing::context::ModuleOutputBuilder;
+
+        let mut ctx = get_dummy_transfer_module();
+        let uatom: PrefixedDenom = "uatom".parse().unwrap();
+        ctx.store_total_escrow(uatom.clone(), 100.into()).unwrap();
+
+        let packet = Packet {
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(0),
+            destination_port: PortId::transfer(),
+            destination_channel: ChannelId::new(1),
+            ..Default::default()
+        };
+        let data = |denom: &str, amount: u64| {
+            PacketData::new(
+                PrefixedCoin {
+                    denom: denom.parse().unwrap(),
+                    amount: amount.into(),
+                },
+                "sender".parse().unwrap(),
+                "receiver".parse().unwrap(),
+            )
+        };
+
+        // tokens returning to the host are unescrowed ...
+        let write_fn = process_recv_packet(
+            &ctx,
+            &mut ModuleOutputBuilder::new(),
+            &packet,
+            data("transfer/channel-0/uatom", 30),
+        )
+        .unwrap();
+        write_fn(&mut ctx).unwrap();
+        assert_eq!(ctx.get_total_escrow(&uatom), 70.into());
+
+        // ... as are those refunded on timeout ...
+        let mut sent = packet.clone();
+        sent.source_channel = ChannelId::new(1);
+        process_timeout_packet(&mut ctx, &sent, &data("uatom", 20)).unwrap();
+        assert_eq!(ctx.get_total_escrow(&uatom), 50.into());
+
+        // ... but never more than escrowed
+        let err = process_recv_packet(
+            &ctx,
+            &mut ModuleOutputBuilder::new(),
+            &packet,
+            data("transfer/channel-0/uatom", 60),
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::InsufficientTotalEscrow(e) if e.total_escrow == 50.into()
+        ));
+        assert_eq!(ctx.get_total_escrow(&uatom), 50.into());
+    }
+
+    #[test]
+    fn test_voucher_denom_formats() {
+        use core::time::Duration;
+
+        use crate::applications::transfer::context::EscrowReader;
+        use crate::applications::transfer::packet::PacketData;
+        use crate::applications::transfer::relay::on_recv_packet::process_recv_packet;
+        use crate::applications::transfer::{PrefixedDenom, RawCoin, VoucherDenomFormat};
+        use crate::core::ics03_connection::connection::{
+            ConnectionEnd, Counterparty as ConnCounterparty, State as ConnState,
+        };
+        use crate::core::ics03_connection::version::Version as ConnVersion;
+        use crate::core::ics04_channel::channel::{ChannelEnd, State};
+        use crate::core::ics04_channel::context::ChannelReader;
+        use crate::core::ics04_channel::packet::Packet;
+        use crate::core::ics04_channel::timeout::TimeoutHeight;
+        use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+        use crate::core::ics24_host::identifier::ClientId;
+        use crate::core::ics26_routing::context::ModuleOutputBuilder;
+        use crate::mock::context::MockContext;
+        use crate::signer::Signer;
+        use crate::test_utils::get_dummy_bech32_account;
+        use crate::timestamp::Timestamp;
+        use crate::Height;
+
+        let account: Signer = get_dummy_bech32_account().parse().unwrap();
+        let voucher: PrefixedDenom = "transfer/channel-0/uatom".parse().unwrap();
+
+        // channels `channel-0` and `channel-2`, to `channel-1` and `channel-3` on other chains
+        let get_ctx = || {
+            let channel = |counterparty: u64| {
+                ChannelEnd::new(
+                    State::Open,
+                    Order::Unordered,
+                    Counterparty::new(PortId::transfer(), Some(ChannelId::new(counterparty))),
+                    vec![ConnectionId::new(0)],
+                    Version::ics20(),
+                )
+            };
+            MockContext::default()
+                .with_client(&ClientId::default(), Height::new(0, 1).unwrap())
+                .with_connection(
+                    ConnectionId::new(0),
+                    ConnectionEnd::new(
+                        ConnState::Open,
+                        ClientId::default(),
+                        ConnCounterparty::new(
+                            ClientId::default(),
+                            Some(ConnectionId::new(0)),
+                            CommitmentPrefix::default(),
+                        ),
+                        vec![ConnVersion::default()],
+                        Duration::MAX,
+                    ),
+                )
+                .with_channel(PortId::transfer(), ChannelId::new(0), channel(1))
+                .with_channel(PortId::transfer(), ChannelId::new(2), channel(3))
+                .with_send_sequence(PortId::transfer(), ChannelId::new(0), 1.into())
+                .with_send_sequence(PortId::transfer(), ChannelId::new(2), 1.into())
+        };
+        let recv =
+            |module: &mut DummyTransferModule, source: u64, destination: u64, denom: &str| {
+                let data = PacketData::new(
+                    PrefixedCoin {
+                        denom: denom.parse().unwrap(),
+                        amount: 10.into(),
+                    },
+                    "sender".parse().unwrap(),
+                    account.clone(),
+                );
+                let packet = Packet::builder()
+                    .sequence(1.into())
+                    .source(PortId::transfer(), ChannelId::new(source))
+                    .destination(PortId::transfer(), ChannelId::new(destination))
+                    .data(serde_json::to_vec(&data).unwrap())
+                    .build()
+                    .unwrap();
+                let write_fn =
+                    process_recv_packet(module, &mut ModuleOutputBuilder::new(), &packet, data)
+                        .unwrap();
+                write_fn(module).unwrap();
+            };
+        let send = |module: &mut DummyTransferModule, channel: u64, denom: String| {
+            let msg = MsgTransfer {
+                source_port: PortId::transfer(),
+                source_channel: ChannelId::new(channel),
+                token: RawCoin {
+                    denom,
+                    amount: 10.into(),
+                },
+                sender: account.clone(),
+                receiver: "receiver".parse().unwrap(),
+                timeout_height: TimeoutHeight::At(Height::new(0, 100).unwrap()),
+                timeout_timestamp: Timestamp::none(),
+                memo: String::new(),
+            };
+            send_transfer(module, &mut HandlerOutputBuilder::new(), msg).unwrap();
+        };
+
+        let commitments =
+            [VoucherDenomFormat::Hashed, VoucherDenomFormat::FullTrace].map(|format| {
+                let ctx = get_ctx();
+                let mut module = DummyTransferModule::new(ctx.ibc_store_share())
+                    .with_voucher_denom_format(format);
+                let voucher_denom = module.voucher_denom(&voucher);
+                let escrow_address = module
+                    .get_channel_escrow_address(&PortId::transfer(), &ChannelId::new(2))
+                    .unwrap();
+
+                recv(&mut module, 1, 0, "uatom");
+                assert_eq!(module.balance(&account, &voucher_denom), 10);
+
+                // sent on to another chain by its full path, the voucher is escrowed under its
+                // voucher denomination ...
+                send(&mut module, 2, voucher.to_string());
+                assert_eq!(module.balance(&escrow_address, &voucher_denom), 10);
+                assert_eq!(module.get_total_escrow(&voucher), 10.into());
+
+                // ... and unescrowed when received back
+                recv(
+                    &mut module,
+                    3,
+                    2,
+                    "transfer/channel-3/transfer/channel-0/uatom",
+                );
+                assert_eq!(module.balance(&escrow_address, &voucher_denom), 0);
+                assert_eq!(module.balance(&account, &voucher_denom), 10);
+                assert_eq!(module.get_total_escrow(&voucher), 0.into());
+
+                // returned by its voucher denomination, the voucher is burned
+                send(&mut module, 0, voucher_denom.clone());
+                assert_eq!(module.balance(&account, &voucher_denom), 0);
+
+                ctx.get_packet_commitment(&PortId::transfer(), &ChannelId::new(0), 1.into())
+                    .unwrap()
+            });
+
+        // packets carry the full denomination path, whatever the format of the host
+        assert_eq!(commitments[0], commitments[1]);
+    }
+
+    #[test]
+    fn test_recv_packet_memo() {
+        use crate::applications::transfer::packet::PacketData;
+        use crate::core::ics04_channel::packet::Packet;
+        use crate::core::ics26_routing::context::ModuleOutputBuilder;
+        use crate::events::ModuleEventAttribute;
+
+        use super::on_recv_packet;
+
+        let ctx = get_dummy_transfer_module();
+        let memo = r#"{"wasm":{}}"#;
+        let data = PacketData::new(
+            PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 100.into(),
+            },
+            "sender".parse().unwrap(),
+            "receiver".parse().unwrap(),
+        )
+        .with_memo(memo.to_string());
+        let packet = Packet::builder()
+            .sequence(1.into())
+            .source(PortId::transfer(), ChannelId::new(0))
+            .destination(PortId::transfer(), ChannelId::new(1))
+            .data(serde_json::to_vec(&data).unwrap())
+            .build()
+            .unwrap();
+
+        let mut output = ModuleOutputBuilder::new();
+        let ack = on_recv_packet(&ctx, &mut output, &packet, &"relayer".parse().unwrap());
+        assert!(ack.is_successful());
+
+        let memo_attribute = ModuleEventAttribute::from(("memo", memo));
+        assert!(output
+            .with_result(())
+            .events
+            .iter()
+            .any(|event| event.attributes.contains(&memo_attribute)));
     }
 }
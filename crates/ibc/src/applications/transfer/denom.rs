@@ -4,6 +4,8 @@ use core::str::FromStr;
 use derive_more::{Display, From};
 use ibc_proto::ibc::applications::transfer::v1::DenomTrace as RawDenomTrace;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle_encoding::hex;
 
 use super::error::Error;
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
@@ -83,6 +85,11 @@ impl TracePath {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Returns the number of hops in the path.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
 }
 
 impl<'a> TryFrom<Vec<&'a str>> for TracePath {
@@ -159,6 +166,65 @@ impl PrefixedDenom {
     pub fn add_trace_prefix(&mut self, prefix: TracePrefix) {
         self.trace_path.add_prefix(prefix)
     }
+
+    /// Returns the hash of the full denomination path, i.e. the uppercase hex-encoded SHA256 of
+    /// `{trace_path}/{base_denom}`, as computed by ibc-go.
+    pub fn hash(&self) -> String {
+        let hash = Sha256::digest(self.to_string().as_bytes());
+        String::from_utf8(hex::encode_upper(hash)).expect("hex encoding is valid UTF-8")
+    }
+}
+
+/// The prefix of hashed voucher denominations, i.e. `ibc/{hash}`.
+pub const HASHED_DENOM_PREFIX: &str = "ibc";
+
+/// The denomination of the vouchers minted by a host, under which they are held in its bank and
+/// referred to by its users. Denominations without a trace are native to the host, and denominated
+/// by their base denomination in either format.
+///
+/// Packets always carry the full trace path, so hosts using different formats interoperate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoucherDenomFormat {
+    /// `ibc/{hash}`, where `hash` is the [hash](PrefixedDenom::hash) of the full denomination
+    /// path, as in ibc-go.
+    Hashed,
+    /// The full denomination path, e.g. `transfer/channel-0/uatom`.
+    FullTrace,
+}
+
+impl VoucherDenomFormat {
+    /// Renders `denom` in this format.
+    pub fn render(&self, denom: &PrefixedDenom) -> String {
+        match self {
+            _ if denom.trace_path.is_empty() => denom.base_denom.to_string(),
+            Self::Hashed => format!("{}/{}", HASHED_DENOM_PREFIX, denom.hash()),
+            Self::FullTrace => denom.to_string(),
+        }
+    }
+}
+
+impl Default for VoucherDenomFormat {
+    fn default() -> Self {
+        Self::Hashed
+    }
+}
+
+/// Returns the hash of the hashed voucher denomination `denom`, or `None` if `denom` is not of the
+/// form `ibc/{hash}`.
+pub fn parse_hashed_denom(denom: &str) -> Result<Option<&str>, Error> {
+    let hash = match denom.split_once('/') {
+        Some((HASHED_DENOM_PREFIX, hash)) => hash,
+        _ => return Ok(None),
+    };
+
+    let bytes = hex::decode_upper(hash)
+        .or_else(|_| hex::decode(hash))
+        .map_err(Error::parse_hex)?;
+    if bytes.len() != Sha256::output_size() {
+        return Err(Error::malformed_hash_denom());
+    }
+
+    Ok(Some(hash))
 }
 
 /// Returns true if the denomination originally came from the sender chain and
@@ -392,4 +458,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_voucher_denom_format() -> Result<(), Error> {
+        let denom = PrefixedDenom::from_str("transfer/channel-0/uatom")?;
+        let hash = "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        assert_eq!(denom.hash(), hash);
+
+        let hashed = VoucherDenomFormat::Hashed.render(&denom);
+        assert_eq!(hashed, format!("ibc/{}", hash));
+        assert_eq!(parse_hashed_denom(&hashed)?, Some(hash));
+        assert_eq!(
+            VoucherDenomFormat::FullTrace.render(&denom),
+            "transfer/channel-0/uatom"
+        );
+
+        // native denominations are never hashed
+        let native = PrefixedDenom::from_str("uatom")?;
+        assert_eq!(VoucherDenomFormat::Hashed.render(&native), "uatom");
+        assert_eq!(VoucherDenomFormat::FullTrace.render(&native), "uatom");
+
+        assert_eq!(parse_hashed_denom("transfer/channel-0/uatom")?, None);
+        assert_eq!(parse_hashed_denom("uatom")?, None);
+        assert!(parse_hashed_denom(&hashed.to_lowercase())?.is_some());
+        assert!(parse_hashed_denom("ibc/27394F").is_err(), "truncated hash");
+        assert!(parse_hashed_denom("ibc/uatom").is_err(), "not a hash");
+
+        Ok(())
+    }
 }
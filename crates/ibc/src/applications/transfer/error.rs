@@ -7,11 +7,13 @@ use ibc_proto::protobuf::Error as TendermintProtoError;
 use subtle_encoding::Error as EncodingError;
 use uint::FromDecStrErr;
 
+use crate::applications::transfer::{Amount, PrefixedDenom};
 use crate::core::ics04_channel::channel::Order;
 use crate::core::ics04_channel::error as channel_error;
 use crate::core::ics04_channel::Version;
 use crate::core::ics24_host::error::ValidationError;
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::core::ics26_routing::context::{AckError, INTERNAL_ACK_ERROR_CODE};
 use crate::prelude::*;
 use crate::signer::SignerError;
 
@@ -69,6 +71,14 @@ define_error! {
             { len: usize }
             | e | { format_args!("trace length must be even but got: {0}", e.len) },
 
+        TooManyTraceHops
+            { hops: usize, max_hops: usize }
+            | e | { format_args!("denom trace has {0} hops, more than the maximum of {1}", e.hops, e.max_hops) },
+
+        DenomTooLong
+            { length: usize, max_length: usize }
+            | e | { format_args!("denom is {0} characters long, more than the maximum of {1}", e.length, e.max_length) },
+
         InvalidAmount
             [ TraceError<FromDecStrErr> ]
             | _ | { "invalid amount" },
@@ -76,6 +86,17 @@ define_error! {
         InvalidToken
             | _ | { "invalid token" },
 
+        TotalEscrowOverflow
+            { denom: PrefixedDenom }
+            | e | { format_args!("total escrow of {0} overflows", e.denom) },
+
+        InsufficientTotalEscrow
+            { denom: PrefixedDenom, amount: Amount, total_escrow: Amount }
+            | e | {
+                format_args!("cannot unescrow {0}{1}, more than the total escrow of {2}{1}",
+                    e.amount, e.denom, e.total_escrow)
+            },
+
         Signer
             [ SignerError ]
             | _ | { "failed to parse signer" },
@@ -120,6 +141,10 @@ define_error! {
         ParseAccountFailure
             | _ | { "failed to parse as AccountId" },
 
+        InvalidAddress
+            { address: String, reason: String }
+            | e | { format_args!("invalid address {0}: {1}", e.address, e.reason) },
+
         InvalidPort
             { port_id: PortId, exp_port_id: PortId }
             | e | { format_args!("invalid port: '{0}', expected '{1}'", e.port_id, e.exp_port_id) },
@@ -139,12 +164,70 @@ define_error! {
             { coin: String }
             | e | { format_args!("invalid coin string: {}", e.coin) },
 
+        InvalidCoins
+            { coins: String, reason: String }
+            | e | { format_args!("invalid coins {0}: {1}", e.coins, e.reason) },
+
+        DenomMismatch
+            { denom: String, other_denom: String }
+            | e | { format_args!("cannot combine coins of {0} with coins of {1}", e.denom, e.other_denom) },
+
+        CoinOverflow
+            { denom: String }
+            | e | { format_args!("amount of {0} overflows", e.denom) },
+
+        InsufficientCoins
+            { coin: String, available: String }
+            | e | { format_args!("cannot subtract {0} from {1}", e.coin, e.available) },
+
         Utf8Decode
             [ TraceError<Utf8Error> ]
             | _ | { "error decoding raw bytes as UTF8 string" },
     }
 }
 
+/// The codes of the errors are those of the equivalent ICS20 errors of ibc-go, if any. The errors
+/// of the bank and of the other keepers of the host are internal, unless they are one of the
+/// public reasons below.
+impl AckError for Error {
+    fn ack_error_code(&self) -> u32 {
+        self.detail().ack_error_code()
+    }
+}
+
+impl ErrorDetail {
+    pub(crate) fn ack_error_code(&self) -> u32 {
+        match self {
+            ErrorDetail::InvalidPacketTimeoutHeight(_)
+            | ErrorDetail::InvalidPacketTimeoutTimestamp(_) => 2,
+            ErrorDetail::EmptyBaseDenom(_)
+            | ErrorDetail::InvalidTracePortId(_)
+            | ErrorDetail::InvalidTraceChannelId(_)
+            | ErrorDetail::InvalidTraceLength(_)
+            | ErrorDetail::TooManyTraceHops(_)
+            | ErrorDetail::DenomTooLong(_)
+            | ErrorDetail::MissingDenomIbcPrefix(_)
+            | ErrorDetail::MalformedHashDenom(_)
+            | ErrorDetail::ParseHex(_) => 3,
+            ErrorDetail::InvalidVersion(_) | ErrorDetail::InvalidCounterpartyVersion(_) => 4,
+            ErrorDetail::InvalidAmount(_)
+            | ErrorDetail::InvalidToken(_)
+            | ErrorDetail::InvalidCoin(_)
+            | ErrorDetail::InvalidCoins(_) => 5,
+            ErrorDetail::TraceNotFound(_) => 6,
+            ErrorDetail::SendDisabled(_) => 7,
+            ErrorDetail::ReceiveDisabled(_) => 8,
+            ErrorDetail::PacketDataDeserialization(_)
+            | ErrorDetail::Utf8(_)
+            | ErrorDetail::Utf8Decode(_) => 101,
+            ErrorDetail::Signer(_)
+            | ErrorDetail::ParseAccountFailure(_)
+            | ErrorDetail::InvalidAddress(_) => 102,
+            _ => INTERNAL_ACK_ERROR_CODE,
+        }
+    }
+}
+
 impl From<Infallible> for Error {
     fn from(e: Infallible) -> Self {
         match e {}
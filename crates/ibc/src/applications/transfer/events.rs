@@ -18,56 +18,79 @@ pub enum Event {
     Transfer(TransferEvent),
 }
 
+/// Emitted on the receiving chain once a packet was processed, successfully or not.
 pub struct RecvEvent {
+    pub sender: Signer,
     pub receiver: Signer,
     pub denom: PrefixedDenom,
     pub amount: Amount,
+    pub memo: String,
     pub success: bool,
+    /// The reason the packet could not be processed, if `success` is false.
+    pub error: Option<String>,
 }
 
 impl From<RecvEvent> for ModuleEvent {
     fn from(ev: RecvEvent) -> Self {
         let RecvEvent {
+            sender,
             receiver,
             denom,
             amount,
+            memo,
             success,
+            error,
         } = ev;
-        Self {
+        let mut ev = Self {
             kind: EVENT_TYPE_PACKET.to_string(),
             module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
             attributes: vec![
+                ("module", MODULE_ID_STR).into(),
+                ("sender", sender).into(),
                 ("receiver", receiver).into(),
                 ("denom", denom).into(),
                 ("amount", amount).into(),
+                ("memo", memo).into(),
                 ("success", success).into(),
             ],
+        };
+        if let Some(error) = error {
+            ev.attributes.push(("error", error).into());
         }
+        ev
     }
 }
 
+/// Emitted on the sending chain once an acknowledgement was processed.
 pub struct AckEvent {
+    pub sender: Signer,
     pub receiver: Signer,
     pub denom: PrefixedDenom,
     pub amount: Amount,
+    pub memo: String,
     pub acknowledgement: Acknowledgement,
 }
 
 impl From<AckEvent> for ModuleEvent {
     fn from(ev: AckEvent) -> Self {
         let AckEvent {
+            sender,
             receiver,
             denom,
             amount,
+            memo,
             acknowledgement,
         } = ev;
         Self {
             kind: EVENT_TYPE_PACKET.to_string(),
             module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
             attributes: vec![
+                ("module", MODULE_ID_STR).into(),
+                ("sender", sender).into(),
                 ("receiver", receiver).into(),
                 ("denom", denom).into(),
                 ("amount", amount).into(),
+                ("memo", memo).into(),
                 ("acknowledgement", acknowledgement).into(),
             ],
         }
@@ -97,10 +120,12 @@ impl From<AckStatusEvent> for ModuleEvent {
     }
 }
 
+/// Emitted on the sending chain once the tokens of a timed out packet were refunded.
 pub struct TimeoutEvent {
     pub refund_receiver: Signer,
     pub refund_denom: PrefixedDenom,
     pub refund_amount: Amount,
+    pub memo: String,
 }
 
 impl From<TimeoutEvent> for ModuleEvent {
@@ -109,19 +134,25 @@ impl From<TimeoutEvent> for ModuleEvent {
             refund_receiver,
             refund_denom,
             refund_amount,
+            memo,
         } = ev;
         Self {
             kind: EVENT_TYPE_TIMEOUT.to_string(),
             module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
             attributes: vec![
+                ("module", MODULE_ID_STR).into(),
                 ("refund_receiver", refund_receiver).into(),
                 ("refund_denom", refund_denom).into(),
                 ("refund_amount", refund_amount).into(),
+                ("memo", memo).into(),
             ],
         }
     }
 }
 
+/// Emitted on the receiving chain when a voucher is minted. The `denom` attribute is the
+/// `ibc/{trace_hash}` voucher denomination if the host hashes denomination traces, and the
+/// full trace path otherwise.
 pub struct DenomTraceEvent {
     pub trace_hash: Option<String>,
     pub denom: PrefixedDenom,
@@ -130,30 +161,49 @@ pub struct DenomTraceEvent {
 impl From<DenomTraceEvent> for ModuleEvent {
     fn from(ev: DenomTraceEvent) -> Self {
         let DenomTraceEvent { trace_hash, denom } = ev;
-        let mut ev = Self {
+        let attributes = match trace_hash {
+            Some(hash) => vec![
+                ("trace_hash", &hash).into(),
+                ("denom", format!("ibc/{}", hash)).into(),
+            ],
+            None => vec![("denom", denom).into()],
+        };
+        Self {
             kind: EVENT_TYPE_DENOM_TRACE.to_string(),
             module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
-            attributes: vec![("denom", denom).into()],
-        };
-        if let Some(hash) = trace_hash {
-            ev.attributes.push(("trace_hash", hash).into());
+            attributes,
         }
-        ev
     }
 }
 
+/// Emitted on the sending chain for every `MsgTransfer`.
 pub struct TransferEvent {
     pub sender: Signer,
     pub receiver: Signer,
+    pub amount: Amount,
+    pub denom: PrefixedDenom,
+    pub memo: String,
 }
 
 impl From<TransferEvent> for ModuleEvent {
     fn from(ev: TransferEvent) -> Self {
-        let TransferEvent { sender, receiver } = ev;
+        let TransferEvent {
+            sender,
+            receiver,
+            amount,
+            denom,
+            memo,
+        } = ev;
         Self {
             kind: EVENT_TYPE_TRANSFER.to_string(),
             module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
-            attributes: vec![("sender", sender).into(), ("receiver", receiver).into()],
+            attributes: vec![
+                ("sender", sender).into(),
+                ("receiver", receiver).into(),
+                ("amount", amount).into(),
+                ("denom", denom).into(),
+                ("memo", memo).into(),
+            ],
         }
     }
 }
@@ -170,3 +220,73 @@ impl From<Event> for ModuleEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tendermint::abci::Event as AbciEvent;
+
+    use crate::test_utils::get_dummy_account_id;
+
+    fn abci_attributes(ev: impl Into<ModuleEvent>) -> (String, Vec<(String, String)>) {
+        let abci_event = AbciEvent::try_from(ev.into()).unwrap();
+        let attributes = abci_event
+            .attributes
+            .into_iter()
+            .map(|tag| (tag.key.to_string(), tag.value.to_string()))
+            .collect();
+        (abci_event.type_str, attributes)
+    }
+
+    fn pairs(attributes: &[(&str, &str)]) -> Vec<(String, String)> {
+        attributes
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn recv_event_matches_ibc_go() {
+        let account = get_dummy_account_id();
+        let ev = RecvEvent {
+            sender: account.clone(),
+            receiver: account.clone(),
+            denom: "transfer/channel-0/uatom".parse().unwrap(),
+            amount: 10u64.into(),
+            memo: String::new(),
+            success: false,
+            error: Some("insufficient funds".to_string()),
+        };
+
+        let (kind, attributes) = abci_attributes(ev);
+        assert_eq!(kind, "fungible_token_packet");
+        assert_eq!(
+            attributes,
+            pairs(&[
+                ("module", "transfer"),
+                ("sender", account.as_ref()),
+                ("receiver", account.as_ref()),
+                ("denom", "transfer/channel-0/uatom"),
+                ("amount", "10"),
+                ("memo", ""),
+                ("success", "false"),
+                ("error", "insufficient funds"),
+            ])
+        );
+    }
+
+    #[test]
+    fn denom_trace_event_reports_voucher_denom() {
+        let ev = DenomTraceEvent {
+            trace_hash: Some("27A6394C".to_string()),
+            denom: "transfer/channel-0/uatom".parse().unwrap(),
+        };
+
+        let (kind, attributes) = abci_attributes(ev);
+        assert_eq!(kind, "denomination_trace");
+        assert_eq!(
+            attributes,
+            pairs(&[("trace_hash", "27A6394C"), ("denom", "ibc/27A6394C")])
+        );
+    }
+}
@@ -2,6 +2,7 @@
 //! constitutes a "fungible token transfer bridge module" between the IBC routing module and an
 //! asset tracking module.
 pub mod acknowledgement;
+pub mod address;
 pub mod amount;
 pub mod coin;
 pub mod context;
@@ -12,6 +13,7 @@ pub mod msgs;
 pub mod packet;
 pub mod relay;
 
+pub use address::AddressCodec;
 pub use amount::*;
 pub use coin::*;
 pub use denom::*;
@@ -25,3 +27,9 @@ pub const PORT_ID_STR: &str = "transfer";
 
 /// ICS20 application current version.
 pub const VERSION: &str = "ics20-1";
+
+/// Default maximum number of hops in the trace of a received denomination.
+pub const DEFAULT_MAX_TRACE_HOPS: usize = 32;
+
+/// Default maximum length of a received denomination, including its trace.
+pub const DEFAULT_MAX_DENOM_LENGTH: usize = 2048;
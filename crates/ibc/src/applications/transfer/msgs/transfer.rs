@@ -4,10 +4,11 @@ use crate::prelude::*;
 
 use ibc_proto::cosmos::base::v1beta1::Coin;
 use ibc_proto::google::protobuf::Any;
-use ibc_proto::ibc::applications::transfer::v1::MsgTransfer as RawMsgTransfer;
+use ibc_proto::ibc::core::client::v1::Height as RawHeight;
 use ibc_proto::protobuf::Protobuf;
 
 use crate::applications::transfer::error::Error;
+use crate::applications::transfer::{parse_hashed_denom, Amount, PrefixedDenom, RawCoin};
 use crate::core::ics04_channel::timeout::TimeoutHeight;
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::signer::Signer;
@@ -16,6 +17,28 @@ use crate::tx_msg::Msg;
 
 pub const TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
 
+/// The protobuf encoding of [`MsgTransfer`] as of ibc-go v6, with a memo, which `ibc-proto` does
+/// not provide yet.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RawMsgTransfer {
+    #[prost(string, tag = "1")]
+    pub source_port: String,
+    #[prost(string, tag = "2")]
+    pub source_channel: String,
+    #[prost(message, optional, tag = "3")]
+    pub token: Option<Coin>,
+    #[prost(string, tag = "4")]
+    pub sender: String,
+    #[prost(string, tag = "5")]
+    pub receiver: String,
+    #[prost(message, optional, tag = "6")]
+    pub timeout_height: Option<RawHeight>,
+    #[prost(uint64, tag = "7")]
+    pub timeout_timestamp: u64,
+    #[prost(string, tag = "8")]
+    pub memo: String,
+}
+
 /// Message used to build an ICS20 token transfer packet.
 ///
 /// Note that this message is not a packet yet, as it lacks the proper sequence
@@ -41,6 +64,8 @@ pub struct MsgTransfer<C = Coin> {
     /// Timeout timestamp relative to the current block timestamp.
     /// The timeout is disabled when set to 0.
     pub timeout_timestamp: Timestamp,
+    /// optional memo, carried in the packet for the destination chain
+    pub memo: String,
 }
 
 impl Msg for MsgTransfer {
@@ -54,6 +79,18 @@ impl Msg for MsgTransfer {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        // the token may be denominated by a hashed voucher denomination, resolved by the host
+        let token = RawCoin::try_from(self.token.clone())?;
+        if parse_hashed_denom(&token.denom)?.is_none() {
+            token.denom.parse::<PrefixedDenom>()?;
+        }
+        if token.amount == Amount::from(0) {
+            return Err(Error::invalid_token());
+        }
+        Ok(())
+    }
 }
 
 impl TryFrom<RawMsgTransfer> for MsgTransfer {
@@ -67,7 +104,7 @@ impl TryFrom<RawMsgTransfer> for MsgTransfer {
             Error::invalid_packet_timeout_height(format!("invalid timeout height {}", e))
         })?;
 
-        Ok(MsgTransfer {
+        let msg = MsgTransfer {
             source_port: raw_msg
                 .source_port
                 .parse()
@@ -81,7 +118,11 @@ impl TryFrom<RawMsgTransfer> for MsgTransfer {
             receiver: raw_msg.receiver.parse().map_err(Error::signer)?,
             timeout_height,
             timeout_timestamp,
-        })
+            memo: raw_msg.memo,
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
@@ -95,6 +136,7 @@ impl From<MsgTransfer> for RawMsgTransfer {
             receiver: domain_msg.receiver.to_string(),
             timeout_height: domain_msg.timeout_height.into(),
             timeout_timestamp: domain_msg.timeout_timestamp.nanoseconds(),
+            memo: domain_msg.memo,
         }
     }
 }
@@ -123,8 +165,10 @@ impl From<MsgTransfer> for Any {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
+    use crate::prelude::*;
+
     use core::ops::Add;
     use core::time::Duration;
 
@@ -162,6 +206,7 @@ pub mod test_util {
             timeout_timestamp: timeout_timestamp
                 .unwrap_or_else(|| Timestamp::now().add(Duration::from_secs(10)).unwrap()),
             timeout_height,
+            memo: String::new(),
         }
     }
 
@@ -172,23 +217,91 @@ pub mod test_util {
         };
 
         let data = {
-            let data = PacketData {
-                token: coin,
-                sender: msg.sender.clone(),
-                receiver: msg.receiver.clone(),
-            };
+            let data =
+                PacketData::new(coin, msg.sender.clone(), msg.receiver.clone()).with_memo(msg.memo);
             serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
         };
 
-        Packet {
-            sequence,
+        Packet::builder()
+            .sequence(sequence)
+            .source(msg.source_port, msg.source_channel)
+            .destination(PortId::default(), ChannelId::default())
+            .data(data)
+            .timeout_height(msg.timeout_height)
+            .timeout_timestamp(msg.timeout_timestamp)
+            .build()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::test_util::get_dummy_msg_transfer;
+    use super::{MsgTransfer, RawMsgTransfer};
+    use crate::core::ics04_channel::timeout::TimeoutHeight;
+    use crate::tx_msg::Msg;
+    use ibc_proto::ibc::applications::transfer::v1::MsgTransfer as RawMsgTransferV1;
+    use prost::Message;
+
+    #[test]
+    fn msg_transfer_validate_basic() {
+        let msg = get_dummy_msg_transfer(TimeoutHeight::Never, None);
+        let raw = RawMsgTransfer::from(MsgTransfer {
             source_port: msg.source_port,
             source_channel: msg.source_channel,
-            destination_port: PortId::default(),
-            destination_channel: ChannelId::default(),
-            data,
+            token: msg.token.into(),
+            sender: msg.sender,
+            receiver: msg.receiver,
             timeout_height: msg.timeout_height,
             timeout_timestamp: msg.timeout_timestamp,
-        }
+            memo: msg.memo,
+        });
+        let msg = MsgTransfer::try_from(raw.clone()).unwrap();
+        assert!(msg.validate_basic().is_ok());
+
+        let mut zero_amount = raw.clone();
+        zero_amount.token.as_mut().unwrap().amount = "0".to_string();
+        assert!(MsgTransfer::try_from(zero_amount).is_err());
+
+        let mut bad_denom = raw.clone();
+        bad_denom.token.as_mut().unwrap().denom = "".to_string();
+        assert!(MsgTransfer::try_from(bad_denom).is_err());
+
+        let mut hashed_denom = raw.clone();
+        hashed_denom.token.as_mut().unwrap().denom =
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string();
+        assert!(MsgTransfer::try_from(hashed_denom).is_ok());
+
+        let mut bad_hashed_denom = raw;
+        bad_hashed_denom.token.as_mut().unwrap().denom = "ibc/uatom".to_string();
+        assert!(MsgTransfer::try_from(bad_hashed_denom).is_err());
+    }
+
+    #[test]
+    fn msg_transfer_memo_encoding() {
+        let msg = get_dummy_msg_transfer(TimeoutHeight::Never, None);
+        let msg = MsgTransfer {
+            source_port: msg.source_port,
+            source_channel: msg.source_channel,
+            token: msg.token.into(),
+            sender: msg.sender,
+            receiver: msg.receiver,
+            timeout_height: msg.timeout_height,
+            timeout_timestamp: msg.timeout_timestamp,
+            memo: r#"{"wasm":{}}"#.to_string(),
+        };
+        let raw = RawMsgTransfer::from(msg.clone());
+        assert_eq!(MsgTransfer::try_from(raw.clone()).unwrap(), msg);
+
+        // the memo is ignored by decoders of the message without one ...
+        let bytes = raw.encode_to_vec();
+        let raw_v1 = RawMsgTransferV1::decode(bytes.as_slice()).unwrap();
+        assert_eq!(raw_v1.token, raw.token);
+
+        // ... and messages without one are decoded with an empty memo
+        let raw = RawMsgTransfer::decode(raw_v1.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(MsgTransfer::try_from(raw).unwrap().memo, "");
     }
 }
@@ -1,20 +1,48 @@
-use alloc::string::ToString;
+use crate::prelude::*;
+
+use alloc::collections::BTreeMap;
 use core::convert::TryFrom;
 use core::str::FromStr;
 
 use ibc_proto::ibc::applications::transfer::v2::FungibleTokenPacketData as RawPacketData;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use super::error::Error;
 use super::{Amount, PrefixedCoin, PrefixedDenom};
 use crate::signer::Signer;
 
+/// The data of an ICS20 packet.
+///
+/// The memo, introduced by ibc-go v6, is only encoded when non-empty, for packets without a memo
+/// to be encoded as by older counterparties. Other fields of the JSON encoding that this version of the module does not know about, e.g. the
+/// ones introduced by newer counterparties, do not fail the decoding: they are kept in
+/// [`PacketData::unknown_fields`] for middlewares to inspect, and encoded back as they were.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(try_from = "RawPacketData", into = "RawPacketData")]
+#[serde(try_from = "JsonPacketData", into = "JsonPacketData")]
 pub struct PacketData {
     pub token: PrefixedCoin,
     pub sender: Signer,
     pub receiver: Signer,
+    /// Arbitrary data for the receiving chain, e.g. instructions for middlewares.
+    pub memo: String,
+    pub unknown_fields: BTreeMap<String, Value>,
+}
+
+impl PacketData {
+    pub fn new(token: PrefixedCoin, sender: Signer, receiver: Signer) -> Self {
+        Self {
+            token,
+            sender,
+            receiver,
+            memo: String::new(),
+            unknown_fields: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_memo(self, memo: String) -> Self {
+        Self { memo, ..self }
+    }
 }
 
 impl TryFrom<RawPacketData> for PacketData {
@@ -24,14 +52,16 @@ impl TryFrom<RawPacketData> for PacketData {
         // This denom may be prefixed or unprefixed.
         let denom = PrefixedDenom::from_str(&raw_pkt_data.denom)?;
         let amount = Amount::from_str(&raw_pkt_data.amount)?;
-        Ok(Self {
-            token: PrefixedCoin { denom, amount },
-            sender: raw_pkt_data.sender.parse().map_err(Error::signer)?,
-            receiver: raw_pkt_data.receiver.parse().map_err(Error::signer)?,
-        })
+        Ok(Self::new(
+            PrefixedCoin { denom, amount },
+            raw_pkt_data.sender.parse().map_err(Error::signer)?,
+            raw_pkt_data.receiver.parse().map_err(Error::signer)?,
+        ))
     }
 }
 
+/// Drops the memo and the unknown fields, which the protobuf encoding of `ibc-proto` does not
+/// have. Packets are JSON encoded on the wire.
 impl From<PacketData> for RawPacketData {
     fn from(pkt_data: PacketData) -> Self {
         Self {
@@ -42,3 +72,99 @@ impl From<PacketData> for RawPacketData {
         }
     }
 }
+
+/// The JSON encoding of [`PacketData`], i.e. the one of [`RawPacketData`] with the memo and any
+/// other fields.
+#[derive(Serialize, Deserialize)]
+struct JsonPacketData {
+    #[serde(flatten)]
+    raw: RawPacketData,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    memo: String,
+    #[serde(flatten)]
+    unknown_fields: BTreeMap<String, Value>,
+}
+
+impl TryFrom<JsonPacketData> for PacketData {
+    type Error = Error;
+
+    fn try_from(json_pkt_data: JsonPacketData) -> Result<Self, Self::Error> {
+        Ok(Self {
+            memo: json_pkt_data.memo,
+            unknown_fields: json_pkt_data.unknown_fields,
+            ..PacketData::try_from(json_pkt_data.raw)?
+        })
+    }
+}
+
+impl From<PacketData> for JsonPacketData {
+    fn from(pkt_data: PacketData) -> Self {
+        Self {
+            memo: pkt_data.memo.clone(),
+            unknown_fields: pkt_data.unknown_fields.clone(),
+            raw: pkt_data.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_fields_are_kept() {
+        let json = r#"{"denom":"transfer/channel-0/uatom","amount":"100","sender":"sender","receiver":"receiver","memo":"hello","forwarding":{"hops":[]}}"#;
+
+        let data: PacketData = serde_json::from_str(json).unwrap();
+        assert_eq!(data.token.amount, Amount::from(100));
+        assert_eq!(data.memo, "hello");
+        assert_eq!(data.unknown_fields.len(), 1);
+        assert_eq!(
+            data.unknown_fields["forwarding"],
+            serde_json::json!({ "hops": [] })
+        );
+
+        assert_eq!(serde_json::to_string(&data).unwrap(), json);
+    }
+
+    #[test]
+    fn memo_is_only_encoded_when_not_empty() {
+        let data = PacketData::new(
+            PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 100.into(),
+            },
+            "sender".parse().unwrap(),
+            "receiver".parse().unwrap(),
+        );
+        assert_eq!(
+            serde_json::to_string(&data).unwrap(),
+            r#"{"denom":"uatom","amount":"100","sender":"sender","receiver":"receiver"}"#
+        );
+
+        let data = data.with_memo(r#"{"wasm":{}}"#.to_string());
+        let json = serde_json::to_string(&data).unwrap();
+        assert_eq!(
+            json,
+            r#"{"denom":"uatom","amount":"100","sender":"sender","receiver":"receiver","memo":"{\"wasm\":{}}"}"#
+        );
+        assert_eq!(serde_json::from_str::<PacketData>(&json).unwrap(), data);
+    }
+
+    #[test]
+    fn encoding_without_unknown_fields_is_unchanged() {
+        let data = PacketData::new(
+            PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 100.into(),
+            },
+            "sender".parse().unwrap(),
+            "receiver".parse().unwrap(),
+        );
+
+        assert_eq!(
+            serde_json::to_vec(&data).unwrap(),
+            serde_json::to_vec(&RawPacketData::from(data)).unwrap()
+        );
+    }
+}
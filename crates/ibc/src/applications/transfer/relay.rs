@@ -1,8 +1,8 @@
 //! This module implements the processing logic for ICS20 (token transfer) message.
-use crate::applications::transfer::context::Ics20Context;
+use crate::applications::transfer::context::{EscrowReader, Ics20Context};
 use crate::applications::transfer::error::Error as Ics20Error;
-use crate::applications::transfer::is_sender_chain_source;
 use crate::applications::transfer::packet::PacketData;
+use crate::applications::transfer::{is_sender_chain_source, Amount, PrefixedCoin};
 use crate::core::ics04_channel::packet::Packet;
 use crate::prelude::*;
 
@@ -16,11 +16,7 @@ fn refund_packet_token(
     packet: &Packet,
     data: &PacketData,
 ) -> Result<(), Ics20Error> {
-    let sender = data
-        .sender
-        .clone()
-        .try_into()
-        .map_err(|_| Ics20Error::parse_account_failure())?;
+    let sender = ctx.parse_account(&data.sender)?;
 
     if is_sender_chain_source(
         packet.source_port.clone(),
@@ -31,10 +27,33 @@ fn refund_packet_token(
         let escrow_address =
             ctx.get_channel_escrow_address(&packet.source_port, &packet.source_channel)?;
 
-        ctx.send_coins(&escrow_address, &sender, &data.token)
+        let total_escrow = unescrowed_total_escrow(ctx, &data.token)?;
+        ctx.send_coins(&escrow_address, &sender, &data.token)?;
+        ctx.store_total_escrow(data.token.denom.clone(), total_escrow)
     }
     // mint vouchers back to sender
     else {
         ctx.mint_coins(&sender, &data.token)
     }
 }
+
+/// Returns the total escrow of the denomination of `coin` once `coin` is escrowed.
+fn escrowed_total_escrow(
+    ctx: &impl EscrowReader,
+    coin: &PrefixedCoin,
+) -> Result<Amount, Ics20Error> {
+    ctx.get_total_escrow(&coin.denom)
+        .checked_add(coin.amount)
+        .ok_or_else(|| Ics20Error::total_escrow_overflow(coin.denom.clone()))
+}
+
+/// Returns the total escrow of the denomination of `coin` once `coin` is unescrowed.
+fn unescrowed_total_escrow(
+    ctx: &impl EscrowReader,
+    coin: &PrefixedCoin,
+) -> Result<Amount, Ics20Error> {
+    let total_escrow = ctx.get_total_escrow(&coin.denom);
+    total_escrow.checked_sub(coin.amount).ok_or_else(|| {
+        Ics20Error::insufficient_total_escrow(coin.denom.clone(), coin.amount, total_escrow)
+    })
+}
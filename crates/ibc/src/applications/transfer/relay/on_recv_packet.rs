@@ -2,7 +2,8 @@ use crate::applications::transfer::context::Ics20Context;
 use crate::applications::transfer::error::Error as Ics20Error;
 use crate::applications::transfer::events::DenomTraceEvent;
 use crate::applications::transfer::packet::PacketData;
-use crate::applications::transfer::{is_receiver_chain_source, TracePrefix};
+use crate::applications::transfer::relay::unescrowed_total_escrow;
+use crate::applications::transfer::{is_receiver_chain_source, PrefixedCoin, TracePrefix};
 use crate::core::ics04_channel::packet::Packet;
 use crate::core::ics26_routing::context::{ModuleOutputBuilder, WriteFn};
 use crate::prelude::*;
@@ -17,11 +18,7 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
         return Err(Ics20Error::receive_disabled());
     }
 
-    let receiver_account = data
-        .receiver
-        .clone()
-        .try_into()
-        .map_err(|_| Ics20Error::parse_account_failure())?;
+    let receiver_account = ctx.parse_account(&data.receiver)?;
 
     if is_receiver_chain_source(
         packet.source_port.clone(),
@@ -38,10 +35,13 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
 
         let escrow_address =
             ctx.get_channel_escrow_address(&packet.destination_port, &packet.destination_channel)?;
+        let total_escrow = unescrowed_total_escrow(ctx, &coin)?;
 
         Ok(Box::new(move |ctx| {
             let ctx = ctx.downcast_mut::<Ctx>().unwrap();
             ctx.send_coins(&escrow_address, &receiver_account, &coin)
+                .map_err(|e| e.to_string())?;
+            ctx.store_total_escrow(coin.denom, total_escrow)
                 .map_err(|e| e.to_string())
         }))
     } else {
@@ -56,6 +56,18 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
             c
         };
 
+        let hops = coin.denom.trace_path.len();
+        if hops > ctx.max_trace_hops() {
+            return Err(Ics20Error::too_many_trace_hops(hops, ctx.max_trace_hops()));
+        }
+        let denom_length = coin.denom.to_string().len();
+        if denom_length > ctx.max_denom_length() {
+            return Err(Ics20Error::denom_too_long(
+                denom_length,
+                ctx.max_denom_length(),
+            ));
+        }
+
         let denom_trace_event = DenomTraceEvent {
             trace_hash: ctx.denom_hash_string(&coin.denom),
             denom: coin.denom.clone(),
@@ -64,8 +76,34 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
 
         Ok(Box::new(move |ctx| {
             let ctx = ctx.downcast_mut::<Ctx>().unwrap();
+            let hash = coin.denom.hash();
+            if !ctx.has_denom_trace(&hash) {
+                ctx.store_denom_trace(hash, coin.denom.clone())
+                    .map_err(|e| e.to_string())?;
+            }
             ctx.mint_coins(&receiver_account, &coin)
                 .map_err(|e| e.to_string())
         }))
     }
 }
+
+/// Returns `token`, sent in `packet`, as it is denominated on the receiving chain once the
+/// packet is received.
+pub fn received_coin(packet: &Packet, token: PrefixedCoin) -> PrefixedCoin {
+    let mut coin = token;
+    if is_receiver_chain_source(
+        packet.source_port.clone(),
+        packet.source_channel.clone(),
+        &coin.denom,
+    ) {
+        let prefix = TracePrefix::new(packet.source_port.clone(), packet.source_channel.clone());
+        coin.denom.remove_trace_prefix(&prefix);
+    } else {
+        let prefix = TracePrefix::new(
+            packet.destination_port.clone(),
+            packet.destination_channel.clone(),
+        );
+        coin.denom.add_trace_prefix(prefix);
+    }
+    coin
+}
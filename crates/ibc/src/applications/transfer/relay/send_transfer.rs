@@ -3,14 +3,17 @@ use crate::applications::transfer::error::Error;
 use crate::applications::transfer::events::TransferEvent;
 use crate::applications::transfer::msgs::transfer::MsgTransfer;
 use crate::applications::transfer::packet::PacketData;
-use crate::applications::transfer::{is_sender_chain_source, Coin, PrefixedCoin};
+use crate::applications::transfer::relay::escrowed_total_escrow;
+use crate::applications::transfer::{is_sender_chain_source, PrefixedCoin, RawCoin};
 use crate::core::ics04_channel::handler::send_packet::send_packet;
 use crate::core::ics04_channel::packet::Packet;
 use crate::events::ModuleEvent;
 use crate::handler::{HandlerOutput, HandlerOutputBuilder};
 use crate::prelude::*;
 
-/// This function handles the transfer sending logic.
+/// This function handles the transfer sending logic. The denomination of the token is resolved with
+/// [`Ics20Reader::resolve_denom`](crate::applications::transfer::context::Ics20Reader::resolve_denom),
+/// and may be either a hashed voucher denomination or a full denomination path.
 /// If this method returns an error, the runtime is expected to rollback all state modifications to
 /// the `Ctx` caused by all messages from the transaction that this `msg` is a part of.
 pub fn send_transfer<Ctx, C>(
@@ -20,7 +23,7 @@ pub fn send_transfer<Ctx, C>(
 ) -> Result<(), Error>
 where
     Ctx: Ics20Context,
-    C: TryInto<PrefixedCoin>,
+    C: TryInto<RawCoin>,
 {
     if !ctx.is_send_enabled() {
         return Err(Error::send_disabled());
@@ -47,46 +50,40 @@ where
         .get_next_sequence_send(&msg.source_port, &msg.source_channel)
         .map_err(Error::ics04_channel)?;
 
-    let token = msg.token.try_into().map_err(|_| Error::invalid_token())?;
-    let denom = token.denom.clone();
-    let coin = Coin {
+    let token: RawCoin = msg.token.try_into().map_err(|_| Error::invalid_token())?;
+    let denom = ctx.resolve_denom(&token.denom)?;
+    let coin = PrefixedCoin {
         denom: denom.clone(),
         amount: token.amount,
     };
 
-    let sender = msg
-        .sender
-        .clone()
-        .try_into()
-        .map_err(|_| Error::parse_account_failure())?;
+    let sender = ctx.parse_account(&msg.sender)?;
 
     if is_sender_chain_source(msg.source_port.clone(), msg.source_channel.clone(), &denom) {
         let escrow_address =
             ctx.get_channel_escrow_address(&msg.source_port, &msg.source_channel)?;
+        let total_escrow = escrowed_total_escrow(ctx, &coin)?;
         ctx.send_coins(&sender, &escrow_address, &coin)?;
+        ctx.store_total_escrow(coin.denom.clone(), total_escrow)?;
     } else {
         ctx.burn_coins(&sender, &coin)?;
     }
 
     let data = {
-        let data = PacketData {
-            token: coin,
-            sender: msg.sender.clone(),
-            receiver: msg.receiver.clone(),
-        };
+        let data = PacketData::new(coin.clone(), msg.sender.clone(), msg.receiver.clone())
+            .with_memo(msg.memo.clone());
         serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
     };
 
-    let packet = Packet {
-        sequence,
-        source_port: msg.source_port,
-        source_channel: msg.source_channel,
-        destination_port,
-        destination_channel,
-        data,
-        timeout_height: msg.timeout_height,
-        timeout_timestamp: msg.timeout_timestamp,
-    };
+    let packet = Packet::builder()
+        .sequence(sequence)
+        .source(msg.source_port, msg.source_channel)
+        .destination(destination_port, destination_channel)
+        .data(data)
+        .timeout_height(msg.timeout_height)
+        .timeout_timestamp(msg.timeout_timestamp)
+        .build()
+        .map_err(Error::ics04_channel)?;
 
     let HandlerOutput {
         result,
@@ -106,12 +103,15 @@ where
 
     output.log(format!(
         "IBC fungible token transfer: {} --({})--> {}",
-        msg.sender, token, msg.receiver
+        msg.sender, coin, msg.receiver
     ));
 
     let transfer_event = TransferEvent {
         sender: msg.sender,
         receiver: msg.receiver,
+        amount: coin.amount,
+        denom: coin.denom,
+        memo: msg.memo,
     };
     output.emit(ModuleEvent::from(transfer_event).into());
 
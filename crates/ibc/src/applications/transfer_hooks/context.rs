@@ -0,0 +1,59 @@
+use sha2::{Digest, Sha256};
+
+use super::error::Error;
+use super::SENDER_PREFIX;
+use crate::applications::transfer::PrefixedCoin;
+use crate::core::ics24_host::identifier::ChannelId;
+use crate::prelude::*;
+use crate::signer::Signer;
+
+/// Captures the host functionality that the transfer hooks middleware requires to execute
+/// contracts on receipt of a hooked transfer packet.
+pub trait ContractExecutor {
+    /// Returns the account that receives the transferred funds and on whose behalf the contract
+    /// is executed, for a packet received on `channel_id` and sent by `original_sender`.
+    ///
+    /// Hosts following the `ibc-hooks` convention should encode the output of
+    /// [`cosmos_adr028_intermediate_sender_address`] using their own address format.
+    fn intermediate_sender(
+        &self,
+        channel_id: &ChannelId,
+        original_sender: &Signer,
+    ) -> Result<Signer, Error>;
+
+    /// Executes `contract` with the JSON encoded `msg` on behalf of `sender`, attaching `funds`
+    /// that were credited to `sender` by the transfer application.
+    fn execute_contract(
+        &mut self,
+        sender: &Signer,
+        contract: &Signer,
+        msg: &[u8],
+        funds: &PrefixedCoin,
+    ) -> Result<(), Error>;
+
+    /// Runs `f`, which credits the funds to the intermediate sender and executes the contract,
+    /// as a single transaction: the host must discard all the state changes made by `f` if it
+    /// fails, e.g. by running it on a branch of its store that is only written on success.
+    fn atomically<T, F>(&mut self, f: F) -> Result<T, Error>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Result<T, Error>;
+}
+
+// https://github.com/cosmos/cosmos-sdk/blob/master/docs/architecture/adr-028-public-key-addresses.md
+pub fn cosmos_adr028_intermediate_sender_address(
+    channel_id: &ChannelId,
+    original_sender: &Signer,
+) -> Vec<u8> {
+    let contents = format!("{}/{}", channel_id, original_sender);
+
+    let type_hash = Sha256::digest(b"module");
+
+    let mut hasher = Sha256::new();
+    hasher.update(type_hash);
+    hasher.update(SENDER_PREFIX.as_bytes());
+    hasher.update([0]);
+    hasher.update(contents.as_bytes());
+
+    hasher.finalize().to_vec()
+}
@@ -0,0 +1,59 @@
+use flex_error::{define_error, TraceError};
+
+use crate::applications::transfer::error::Error as Ics20Error;
+use crate::core::ics26_routing::context::{AckError, INTERNAL_ACK_ERROR_CODE};
+use crate::prelude::*;
+use crate::signer::{Signer, SignerError};
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        Ics20
+            [ Ics20Error ]
+            | _ | { "ICS20 fungible token transfer error" },
+
+        MemoDeserialization
+            [ TraceError<serde_json::Error> ]
+            | _ | { "failed to deserialize the wasm hook in the packet memo" },
+
+        InvalidHookMsg
+            | _ | { "the wasm hook `msg` field must be a JSON object" },
+
+        InvalidHookContract
+            [ SignerError ]
+            | _ | { "invalid wasm hook contract address" },
+
+        ReceiverMismatch
+            { receiver: Signer, contract: Signer }
+            | e | {
+                format_args!("packet receiver {0} does not match the wasm hook contract {1}",
+                    e.receiver, e.contract)
+            },
+
+        IntermediateSender
+            { reason: String }
+            | e | { format_args!("failed to derive the intermediate sender account: {0}", e.reason) },
+
+        AppWrite
+            { reason: String }
+            | e | { format_args!("failed to credit the received funds: {0}", e.reason) },
+
+        ContractExecution
+            { reason: String }
+            | e | { format_args!("wasm hook contract execution failed: {0}", e.reason) },
+    }
+}
+
+/// Invalid wasm hooks are reported with the code of the invalid memo ICS20 error of ibc-go.
+impl AckError for Error {
+    fn ack_error_code(&self) -> u32 {
+        match self.detail() {
+            ErrorDetail::Ics20(e) => e.source.ack_error_code(),
+            ErrorDetail::MemoDeserialization(_)
+            | ErrorDetail::InvalidHookMsg(_)
+            | ErrorDetail::InvalidHookContract(_)
+            | ErrorDetail::ReceiverMismatch(_) => 11,
+            _ => INTERNAL_ACK_ERROR_CODE,
+        }
+    }
+}
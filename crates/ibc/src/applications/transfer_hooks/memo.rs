@@ -0,0 +1,110 @@
+use core::str::FromStr;
+
+use serde::Deserialize;
+
+use super::error::Error;
+use super::WASM_HOOK_MEMO_KEY;
+use crate::prelude::*;
+use crate::signer::Signer;
+
+/// A contract execution request carried in the memo of a transfer packet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WasmHook {
+    /// The contract to execute. Must match the receiver of the transfer packet.
+    pub contract: Signer,
+    /// The JSON encoded execute message.
+    pub msg: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct RawPacketMemo {
+    #[serde(default)]
+    memo: String,
+}
+
+#[derive(Deserialize)]
+struct RawWasmHook {
+    contract: String,
+    msg: serde_json::Value,
+}
+
+/// Extracts the wasm hook from the JSON encoded transfer packet `data`.
+///
+/// Returns `Ok(None)` if the packet is not meant to trigger a hook, i.e. if it has no memo, the
+/// memo is not a JSON object or the object has no `wasm` entry. A `wasm` entry that cannot be
+/// parsed into a [`WasmHook`] is an error.
+pub fn parse_wasm_hook(data: &[u8]) -> Result<Option<WasmHook>, Error> {
+    let memo = match serde_json::from_slice::<RawPacketMemo>(data) {
+        Ok(packet) => packet.memo,
+        Err(_) => return Ok(None),
+    };
+
+    let hook = match serde_json::from_str::<serde_json::Value>(&memo) {
+        Ok(serde_json::Value::Object(mut memo)) => match memo.remove(WASM_HOOK_MEMO_KEY) {
+            Some(hook) => hook,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let RawWasmHook { contract, msg } =
+        serde_json::from_value(hook).map_err(Error::memo_deserialization)?;
+    if !msg.is_object() {
+        return Err(Error::invalid_hook_msg());
+    }
+
+    Ok(Some(WasmHook {
+        contract: Signer::from_str(&contract).map_err(Error::invalid_hook_contract)?,
+        msg: serde_json::to_vec(&msg).map_err(Error::memo_deserialization)?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet_data(memo: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "denom": "uatom",
+            "amount": "100",
+            "sender": "sender",
+            "receiver": "contract",
+            "memo": memo,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_wasm_hook() {
+        let hook = parse_wasm_hook(&packet_data(
+            r#"{"wasm":{"contract":"contract","msg":{"echo":{}}}}"#,
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(hook.contract.as_ref(), "contract");
+        assert_eq!(hook.msg, br#"{"echo":{}}"#.to_vec());
+    }
+
+    #[test]
+    fn test_parse_no_wasm_hook() {
+        assert_eq!(parse_wasm_hook(&packet_data("")).unwrap(), None);
+        assert_eq!(parse_wasm_hook(&packet_data("some memo")).unwrap(), None);
+        assert_eq!(
+            parse_wasm_hook(&packet_data(r#"{"forward":{}}"#)).unwrap(),
+            None
+        );
+
+        let no_memo = br#"{"denom":"uatom","amount":"100","sender":"a","receiver":"b"}"#;
+        assert_eq!(parse_wasm_hook(no_memo).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_invalid_wasm_hook() {
+        assert!(parse_wasm_hook(&packet_data(r#"{"wasm":{"contract":"contract"}}"#)).is_err());
+        assert!(parse_wasm_hook(&packet_data(
+            r#"{"wasm":{"contract":"contract","msg":"not an object"}}"#
+        ))
+        .is_err());
+        assert!(parse_wasm_hook(&packet_data(r#"{"wasm":{"contract":"","msg":{}}}"#)).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! Transfer hooks middleware, replicating the behaviour of the Osmosis `ibc-hooks` module.
+//!
+//! The middleware wraps an ICS20 application module. When an incoming transfer packet carries a
+//! memo of the form `{"wasm": {"contract": "<addr>", "msg": {...}}}` and the packet receiver is
+//! that same contract address, the funds are first credited to an intermediate account derived
+//! from the destination channel and the original sender, and the contract is then executed on
+//! behalf of that account with the received funds attached. All other packets are passed through
+//! to the wrapped application untouched.
+pub mod context;
+pub mod error;
+pub mod memo;
+pub mod module;
+
+pub use module::TransferHooks;
+
+/// The key under which the hook is encoded in the memo of a transfer packet.
+pub const WASM_HOOK_MEMO_KEY: &str = "wasm";
+
+/// Module name used for deriving the intermediate sender account.
+pub const SENDER_PREFIX: &str = "ibc-wasm-hook-intermediary";
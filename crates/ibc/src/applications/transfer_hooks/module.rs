@@ -0,0 +1,480 @@
+use core::any::Any;
+
+use super::context::ContractExecutor;
+use super::error::Error;
+use super::memo::{parse_wasm_hook, WasmHook};
+use crate::applications::transfer::acknowledgement::Acknowledgement;
+use crate::applications::transfer::packet::PacketData;
+use crate::applications::transfer::relay::on_recv_packet::received_coin;
+use crate::core::ics04_channel::channel::{Counterparty, Order};
+use crate::core::ics04_channel::error::Error as ChannelError;
+use crate::core::ics04_channel::handler::ModuleExtras;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::{
+    Module, ModuleOutputBuilder, OnRecvPacketAck, WrittenAck,
+};
+use crate::core::ics26_routing::middleware::forward_recv_packet_ack;
+use crate::core::ics26_routing::reentrancy::NestedDispatchGuard;
+use crate::prelude::*;
+use crate::signer::Signer;
+
+/// Middleware wrapping an ICS20 application `Module` that executes contracts on receipt of
+/// transfer packets carrying a wasm hook in their memo.
+#[derive(Debug)]
+pub struct TransferHooks<M, E> {
+    app: M,
+    executor: E,
+}
+
+impl<M, E> TransferHooks<M, E> {
+    pub fn new(app: M, executor: E) -> Self {
+        Self { app, executor }
+    }
+
+    pub fn app(&self) -> &M {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut M {
+        &mut self.app
+    }
+
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
+
+    pub fn executor_mut(&mut self) -> &mut E {
+        &mut self.executor
+    }
+}
+
+impl<M, E> TransferHooks<M, E>
+where
+    M: Module,
+    E: ContractExecutor + Send + Sync + 'static,
+{
+    fn on_recv_hooked_packet(
+        &self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        data: PacketData,
+        hook: WasmHook,
+        relayer: &Signer,
+    ) -> Result<OnRecvPacketAck, Error> {
+        if data.receiver != hook.contract {
+            return Err(Error::receiver_mismatch(data.receiver, hook.contract));
+        }
+
+        // Credit the funds to the intermediate sender instead of the contract.
+        let sender = self
+            .executor
+            .intermediate_sender(&packet.destination_channel, &data.sender)?;
+        let hooked_packet = {
+            // Only the receiver is replaced: the memo, and the fields this version does not know
+            // about, are passed on as they were sent.
+            let mut hooked_data = serde_json::from_slice::<serde_json::Value>(&packet.data)
+                .map_err(Error::memo_deserialization)?;
+            hooked_data["receiver"] = sender.to_string().into();
+
+            let mut p = packet.clone();
+            p.data = serde_json::to_vec(&hooked_data).map_err(Error::memo_deserialization)?;
+            p
+        };
+
+        let funds = received_coin(packet, data.token);
+        let ack = match self.app.on_recv_packet(output, &hooked_packet, relayer) {
+            ack @ OnRecvPacketAck::Failed(_) => ack,
+            // The contract is executed along with the state changes of the application, and
+            // its failure discards them and fails the packet.
+            ack => OnRecvPacketAck::Deferred(Box::new(move |module: &mut dyn Any, output| {
+                let TransferHooks { app, executor } = downcast_hooks::<M, E>(module)?;
+                let written = executor.atomically(|executor| {
+                    let written = ack
+                        .write(app.as_any_mut(), output)
+                        .map_err(Error::app_write)?;
+                    if let WrittenAck::Successful(_) = written {
+                        executor.execute_contract(&sender, &hook.contract, &hook.msg, &funds)?;
+                    }
+                    Ok(written)
+                });
+
+                Ok(match written {
+                    Ok(WrittenAck::Successful(ack)) => {
+                        OnRecvPacketAck::Successful(ack, Box::new(|_| Ok(())))
+                    }
+                    Ok(WrittenAck::Failed(ack)) => OnRecvPacketAck::Failed(ack),
+                    Ok(WrittenAck::Nil) => OnRecvPacketAck::Nil(Box::new(|_| Ok(()))),
+                    Err(e) => OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e))),
+                })
+            })),
+        };
+
+        Ok(ack)
+    }
+}
+
+fn downcast_hooks<M, E>(module: &mut dyn Any) -> Result<&mut TransferHooks<M, E>, String>
+where
+    M: Module,
+    E: ContractExecutor + Send + Sync + 'static,
+{
+    module
+        .downcast_mut::<TransferHooks<M, E>>()
+        .ok_or_else(|| "transfer hooks middleware expected".to_string())
+}
+
+impl<M, E> Module for TransferHooks<M, E>
+where
+    M: Module,
+    E: ContractExecutor + Send + Sync + 'static,
+{
+    fn on_chan_open_init(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        version: &Version,
+    ) -> Result<(ModuleExtras, Version), ChannelError> {
+        self.app.on_chan_open_init(
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            version,
+        )
+    }
+
+    fn on_chan_open_try(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), ChannelError> {
+        self.app.on_chan_open_try(
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            counterparty_version,
+        )
+    }
+
+    fn supported_versions(&self, port_id: &PortId) -> Vec<Version> {
+        self.app.supported_versions(port_id)
+    }
+
+    fn on_chan_open_ack(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty_version: &Version,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app
+            .on_chan_open_ack(port_id, channel_id, counterparty_version)
+    }
+
+    fn on_chan_open_confirm(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app.on_chan_open_confirm(port_id, channel_id)
+    }
+
+    fn on_chan_close_init(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app.on_chan_close_init(port_id, channel_id)
+    }
+
+    fn on_chan_close_confirm(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, ChannelError> {
+        self.app.on_chan_close_confirm(port_id, channel_id)
+    }
+
+    fn on_recv_packet(
+        &self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> OnRecvPacketAck {
+        let hook = parse_wasm_hook(&packet.data);
+        let data = serde_json::from_slice::<PacketData>(&packet.data);
+
+        match (hook, data) {
+            (Ok(Some(hook)), Ok(data)) => self
+                .on_recv_hooked_packet(output, packet, data, hook, relayer)
+                .unwrap_or_else(|e| {
+                    OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e)))
+                }),
+            (Err(e), _) => OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e))),
+            // Not a hooked packet, or not a valid transfer packet to begin with, in which case
+            // the application is responsible for rejecting it.
+            _ => forward_recv_packet_ack(
+                self.app.on_recv_packet(output, packet, relayer),
+                Self::app_mut,
+            ),
+        }
+    }
+
+    fn on_acknowledgement_packet(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        acknowledgement: &GenericAcknowledgement,
+        relayer: &Signer,
+    ) -> Result<(), ChannelError> {
+        self.app
+            .on_acknowledgement_packet(output, packet, acknowledgement, relayer)
+    }
+
+    fn on_timeout_packet(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> Result<(), ChannelError> {
+        self.app.on_timeout_packet(output, packet, relayer)
+    }
+    fn nested_dispatch_guard(&mut self) -> Option<&mut NestedDispatchGuard> {
+        self.app.nested_dispatch_guard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::applications::transfer::acknowledgement::Acknowledgement as Ics20Acknowledgement;
+    use crate::applications::transfer::PrefixedCoin;
+    use crate::core::ics26_routing::context::AsAnyMut;
+
+    #[derive(Debug, Default)]
+    struct DummyApp {
+        received: Vec<PacketData>,
+    }
+
+    impl Module for DummyApp {
+        fn on_chan_open_init(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            version: &Version,
+        ) -> Result<(ModuleExtras, Version), ChannelError> {
+            Ok((ModuleExtras::empty(), version.clone()))
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            counterparty_version: &Version,
+        ) -> Result<(ModuleExtras, Version), ChannelError> {
+            Ok((ModuleExtras::empty(), counterparty_version.clone()))
+        }
+
+        fn on_recv_packet(
+            &self,
+            _output: &mut ModuleOutputBuilder,
+            packet: &Packet,
+            _relayer: &Signer,
+        ) -> OnRecvPacketAck {
+            let data = serde_json::from_slice::<PacketData>(&packet.data).unwrap();
+            OnRecvPacketAck::Successful(
+                Box::new(Ics20Acknowledgement::success()),
+                Box::new(move |module| {
+                    let app = module.downcast_mut::<DummyApp>().unwrap();
+                    app.received.push(data);
+                    Ok(())
+                }),
+            )
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct DummyExecutor {
+        executed: Vec<(Signer, Signer, Vec<u8>, PrefixedCoin)>,
+        fail: bool,
+    }
+
+    impl ContractExecutor for DummyExecutor {
+        fn intermediate_sender(
+            &self,
+            channel_id: &ChannelId,
+            original_sender: &Signer,
+        ) -> Result<Signer, Error> {
+            Ok(format!("{}/{}", channel_id, original_sender)
+                .parse()
+                .unwrap())
+        }
+
+        fn execute_contract(
+            &mut self,
+            sender: &Signer,
+            contract: &Signer,
+            msg: &[u8],
+            funds: &PrefixedCoin,
+        ) -> Result<(), Error> {
+            if self.fail {
+                return Err(Error::contract_execution("out of gas".to_string()));
+            }
+            self.executed.push((
+                sender.clone(),
+                contract.clone(),
+                msg.to_vec(),
+                funds.clone(),
+            ));
+            Ok(())
+        }
+
+        fn atomically<T, F>(&mut self, f: F) -> Result<T, Error>
+        where
+            F: FnOnce(&mut Self) -> Result<T, Error>,
+        {
+            f(self)
+        }
+    }
+
+    fn get_dummy_packet(receiver: &str, memo: &str) -> Packet {
+        let data = serde_json::json!({
+            "denom": "uatom",
+            "amount": "100",
+            "sender": "sender",
+            "receiver": receiver,
+            "memo": memo,
+        });
+
+        Packet::builder()
+            .sequence(1.into())
+            .source(PortId::transfer(), ChannelId::new(0))
+            .destination(PortId::transfer(), ChannelId::new(1))
+            .data(serde_json::to_vec(&data).unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn recv_packet(
+        hooks: &mut TransferHooks<DummyApp, DummyExecutor>,
+        packet: &Packet,
+    ) -> Result<(), Vec<u8>> {
+        let relayer = "relayer".parse().unwrap();
+        let ack = hooks.on_recv_packet(&mut ModuleOutputBuilder::new(), packet, &relayer);
+        match ack
+            .write(hooks.as_any_mut(), &mut ModuleOutputBuilder::new())
+            .unwrap()
+        {
+            WrittenAck::Failed(ack) => Err(ack.as_ref().as_ref().to_vec()),
+            _ => Ok(()),
+        }
+    }
+
+    #[test]
+    fn test_recv_packet_without_hook() {
+        let mut hooks = TransferHooks::new(DummyApp::default(), DummyExecutor::default());
+
+        recv_packet(&mut hooks, &get_dummy_packet("receiver", "")).unwrap();
+
+        assert_eq!(hooks.app().received.len(), 1);
+        assert_eq!(hooks.app().received[0].receiver.as_ref(), "receiver");
+        assert!(hooks.executor().executed.is_empty());
+    }
+
+    #[test]
+    fn test_recv_packet_with_hook() {
+        let mut hooks = TransferHooks::new(DummyApp::default(), DummyExecutor::default());
+        let memo = r#"{"wasm":{"contract":"contract","msg":{"echo":{}}}}"#;
+
+        recv_packet(&mut hooks, &get_dummy_packet("contract", memo)).unwrap();
+
+        // the funds are credited to the intermediate sender ...
+        assert_eq!(hooks.app().received.len(), 1);
+        assert_eq!(
+            hooks.app().received[0].receiver.as_ref(),
+            "channel-1/sender"
+        );
+
+        // ... which executes the contract with the vouchers received on this chain
+        let (sender, contract, msg, funds) = &hooks.executor().executed[0];
+        assert_eq!(sender.as_ref(), "channel-1/sender");
+        assert_eq!(contract.as_ref(), "contract");
+        assert_eq!(msg, br#"{"echo":{}}"#);
+        assert_eq!(funds.denom.to_string(), "transfer/channel-1/uatom");
+        assert_eq!(funds.amount, "100".parse().unwrap());
+    }
+
+    #[test]
+    fn test_recv_packet_keeps_the_memo_and_unknown_fields() {
+        let mut hooks = TransferHooks::new(DummyApp::default(), DummyExecutor::default());
+        let memo = r#"{"wasm":{"contract":"contract","msg":{"echo":{}}}}"#;
+        let mut packet = get_dummy_packet("contract", memo);
+        let mut data = serde_json::from_slice::<serde_json::Value>(&packet.data).unwrap();
+        data["forwarding"] = serde_json::json!({ "hops": [] });
+        packet.data = serde_json::to_vec(&data).unwrap();
+
+        recv_packet(&mut hooks, &packet).unwrap();
+
+        let received = &hooks.app().received[0];
+        assert_eq!(received.memo, memo);
+        assert_eq!(
+            received.unknown_fields["forwarding"],
+            serde_json::json!({ "hops": [] })
+        );
+    }
+
+    #[test]
+    fn test_recv_packet_with_failing_contract() {
+        let mut hooks = TransferHooks::new(
+            DummyApp::default(),
+            DummyExecutor {
+                fail: true,
+                ..Default::default()
+            },
+        );
+        let memo = r#"{"wasm":{"contract":"contract","msg":{"echo":{}}}}"#;
+
+        let ack = recv_packet(&mut hooks, &get_dummy_packet("contract", memo)).unwrap_err();
+        assert!(String::from_utf8(ack).unwrap().starts_with("ABCI code: 1:"));
+        assert!(hooks.executor().executed.is_empty());
+    }
+
+    #[test]
+    fn test_recv_packet_with_invalid_hook() {
+        let mut hooks = TransferHooks::new(DummyApp::default(), DummyExecutor::default());
+
+        let mismatched_receiver = get_dummy_packet(
+            "receiver",
+            r#"{"wasm":{"contract":"contract","msg":{"echo":{}}}}"#,
+        );
+        assert!(recv_packet(&mut hooks, &mismatched_receiver).is_err());
+
+        let malformed_msg = get_dummy_packet(
+            "contract",
+            r#"{"wasm":{"contract":"contract","msg":"echo"}}"#,
+        );
+        assert!(recv_packet(&mut hooks, &malformed_msg).is_err());
+
+        assert!(hooks.app().received.is_empty());
+        assert!(hooks.executor().executed.is_empty());
+    }
+}
@@ -0,0 +1,858 @@
+use crate::prelude::*;
+
+use core::time::Duration;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+use ibc_proto::protobuf::Protobuf;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::clients::ics06_solomachine::consensus_state::ConsensusState as SoloMachineConsensusState;
+use crate::clients::ics06_solomachine::error::Error;
+use crate::clients::ics06_solomachine::header::Header as SoloMachineHeader;
+use crate::clients::ics06_solomachine::misbehaviour::Misbehaviour;
+use crate::clients::ics06_solomachine::raw::{
+    ClientState as RawClientState, SignBytes, TimestampedSignatureData,
+};
+use crate::clients::ics06_solomachine::signature::{merkle_path_bytes, verify_signature};
+use crate::core::ics02_client::client_state::{
+    ClientState as Ics2ClientState, UpdatedState, UpgradeOptions,
+};
+use crate::core::ics02_client::client_type::ClientType;
+use crate::core::ics02_client::consensus_state::ConsensusState;
+use crate::core::ics02_client::context::ClientReader;
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::core::ics02_client::header::Header;
+use crate::core::ics03_connection::connection::ConnectionEnd;
+use crate::core::ics04_channel::channel::ChannelEnd;
+use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCommitment};
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::packet::{Receipt, Sequence};
+use crate::core::ics23_commitment::commitment::{
+    CommitmentPrefix, CommitmentProofBytes, CommitmentRoot,
+};
+use crate::core::ics23_commitment::merkle::apply_prefix;
+use crate::core::ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId};
+use crate::core::ics24_host::path::{
+    AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath, CommitmentsPath,
+    ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+};
+use crate::core::ics24_host::Path;
+use crate::Height;
+
+use super::client_type as solomachine_client_type;
+
+pub const SOLOMACHINE_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.solomachine.v3.ClientState";
+
+/// The state of a solo machine client. Its height is the sequence of the solo machine, which
+/// each header advances by one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientState {
+    pub sequence: u64,
+    pub is_frozen: bool,
+    pub consensus_state: SoloMachineConsensusState,
+}
+
+impl ClientState {
+    pub fn new(sequence: u64, consensus_state: SoloMachineConsensusState) -> Result<Self, Error> {
+        if sequence == 0 {
+            return Err(Error::invalid_raw_client_state("zero sequence".into()));
+        }
+
+        Ok(Self {
+            sequence,
+            is_frozen: false,
+            consensus_state,
+        })
+    }
+
+    /// Verifies that `misbehaviour` holds two different signatures of the solo machine at the
+    /// same sequence, and returns the client frozen if so.
+    pub fn check_misbehaviour_and_update_state(
+        &self,
+        misbehaviour: Misbehaviour,
+    ) -> Result<Self, Error> {
+        if self.is_frozen {
+            return Err(Error::client_frozen());
+        }
+        if misbehaviour.signature_one.signature == misbehaviour.signature_two.signature {
+            return Err(Error::equal_signatures());
+        }
+
+        for signature in [&misbehaviour.signature_one, &misbehaviour.signature_two] {
+            let sign_bytes =
+                signature.sign_bytes(misbehaviour.sequence, &self.consensus_state.diversifier);
+            verify_signature(
+                &self.consensus_state.public_key,
+                &sign_bytes,
+                &signature.signature,
+            )?;
+        }
+
+        Ok(Self {
+            is_frozen: true,
+            ..self.clone()
+        })
+    }
+
+    /// Verifies that the proof is a signature, at the current sequence, of `value` (or of empty
+    /// data for absence) under the prefixed `path`. The height of the proof must be the latest
+    /// height of the client.
+    fn verify_signature(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        path: impl Into<Path>,
+        value: Vec<u8>,
+    ) -> Result<(), Ics02Error> {
+        if self.is_frozen {
+            return Err(Error::client_frozen().into());
+        }
+        if height != self.latest_height() {
+            return Err(Error::invalid_proof_height(height, self.sequence).into());
+        }
+
+        let proof = TimestampedSignatureData::decode(proof.as_bytes()).map_err(Error::decode)?;
+        if proof.timestamp < self.consensus_state.timestamp.nanoseconds() {
+            return Err(Error::timestamp_too_old(
+                proof.timestamp,
+                self.consensus_state.timestamp.nanoseconds(),
+            )
+            .into());
+        }
+
+        let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
+        let sign_bytes = SignBytes {
+            sequence: self.sequence,
+            timestamp: proof.timestamp,
+            diversifier: self.consensus_state.diversifier.clone(),
+            path: merkle_path_bytes(&merkle_path),
+            data: value,
+        };
+
+        verify_signature(
+            &self.consensus_state.public_key,
+            &sign_bytes,
+            &proof.signature_data,
+        )
+        .map_err(Into::into)
+    }
+
+    fn decode_header(&self, header: Any) -> Result<SoloMachineHeader, Ics02Error> {
+        Ok(SoloMachineHeader {
+            sequence: self.sequence,
+            ..SoloMachineHeader::try_from(header)?
+        })
+    }
+}
+
+impl Ics2ClientState for ClientState {
+    /// Solo machines have no chain id, the diversifier stands in for it.
+    fn chain_id(&self) -> ChainId {
+        ChainId::from_string(&self.consensus_state.diversifier)
+    }
+
+    fn client_type(&self) -> ClientType {
+        solomachine_client_type()
+    }
+
+    fn latest_height(&self) -> Height {
+        Height::new(0, self.sequence).expect("the sequence is never zero")
+    }
+
+    fn frozen_height(&self) -> Option<Height> {
+        self.is_frozen
+            .then(|| Height::new(0, 1).expect("non-zero height"))
+    }
+
+    /// Solo machines cannot be upgraded.
+    fn upgrade(
+        &mut self,
+        _upgrade_height: Height,
+        _upgrade_options: &dyn UpgradeOptions,
+        _chain_id: ChainId,
+    ) {
+    }
+
+    fn expired(&self, _elapsed: Duration) -> bool {
+        false
+    }
+
+    fn initialise(&self, consensus_state: Any) -> Result<Box<dyn ConsensusState>, Ics02Error> {
+        SoloMachineConsensusState::try_from(consensus_state)
+            .map(SoloMachineConsensusState::into_box)
+    }
+
+    fn decode_header(&self, header: Any) -> Result<Box<dyn Header>, Ics02Error> {
+        ClientState::decode_header(self, header).map(SoloMachineHeader::into_box)
+    }
+
+    fn check_header_and_update_state(
+        &self,
+        _ctx: &dyn ClientReader,
+        _client_id: ClientId,
+        header: Any,
+    ) -> Result<UpdatedState, Ics02Error> {
+        if self.is_frozen {
+            return Err(Error::client_frozen().into());
+        }
+
+        let header = ClientState::decode_header(self, header)?;
+        if header.timestamp.nanoseconds() < self.consensus_state.timestamp.nanoseconds() {
+            return Err(Error::timestamp_too_old(
+                header.timestamp.nanoseconds(),
+                self.consensus_state.timestamp.nanoseconds(),
+            )
+            .into());
+        }
+
+        let sign_bytes = header.sign_bytes(&self.consensus_state.diversifier);
+        verify_signature(
+            &self.consensus_state.public_key,
+            &sign_bytes,
+            &header.signature,
+        )?;
+
+        let consensus_state = SoloMachineConsensusState::new(
+            header.new_public_key,
+            header.new_diversifier,
+            header.timestamp,
+        );
+        let client_state = Self {
+            sequence: self.sequence + 1,
+            is_frozen: false,
+            consensus_state: consensus_state.clone(),
+        };
+
+        Ok(UpdatedState {
+            client_state: client_state.into_box(),
+            consensus_state: consensus_state.into_box(),
+        })
+    }
+
+    fn check_misbehaviour_and_update_state(
+        &self,
+        _ctx: &dyn ClientReader,
+        _client_id: ClientId,
+        misbehaviour: Any,
+    ) -> Result<Box<dyn Ics2ClientState>, Ics02Error> {
+        let misbehaviour = Misbehaviour::try_from(misbehaviour)?;
+        ClientState::check_misbehaviour_and_update_state(self, misbehaviour)
+            .map(ClientState::into_box)
+            .map_err(Into::into)
+    }
+    fn verify_upgrade_and_update_state(
+        &self,
+        _consensus_state: Any,
+        _proof_upgrade_client: RawMerkleProof,
+        _proof_upgrade_consensus_state: RawMerkleProof,
+    ) -> Result<UpdatedState, Ics02Error> {
+        Err(Error::upgrade_not_supported().into())
+    }
+
+    /// Each message proven by the solo machine consumes its sequence, so that its signatures
+    /// cannot be replayed.
+    fn consume_proofs(&self) -> Option<UpdatedState> {
+        let client_state = Self {
+            sequence: self.sequence + 1,
+            ..self.clone()
+        };
+        Some(UpdatedState {
+            consensus_state: client_state.consensus_state.clone().into_box(),
+            client_state: client_state.into_box(),
+        })
+    }
+
+    fn verify_client_consensus_state(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        client_id: &ClientId,
+        consensus_height: Height,
+        expected_consensus_state: &dyn ConsensusState,
+    ) -> Result<(), Ics02Error> {
+        let path = ClientConsensusStatePath::new(client_id.clone(), consensus_height);
+        let value = expected_consensus_state
+            .encode_vec()
+            .map_err(Ics02Error::invalid_any_consensus_state)?;
+
+        self.verify_signature(height, prefix, proof, path, value)
+    }
+
+    fn verify_connection_state(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        connection_id: &ConnectionId,
+        expected_connection_end: &ConnectionEnd,
+    ) -> Result<(), Ics02Error> {
+        let path = ConnectionsPath(connection_id.clone());
+        let value = expected_connection_end
+            .encode_vec()
+            .map_err(Ics02Error::invalid_connection_end)?;
+
+        self.verify_signature(height, prefix, proof, path, value)
+    }
+
+    fn verify_channel_state(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        expected_channel_end: &ChannelEnd,
+    ) -> Result<(), Ics02Error> {
+        let path = ChannelEndsPath(port_id.clone(), channel_id.clone());
+        let value = expected_channel_end
+            .encode_vec()
+            .map_err(Ics02Error::invalid_channel_end)?;
+
+        self.verify_signature(height, prefix, proof, path, value)
+    }
+
+    fn verify_client_full_state(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        client_id: &ClientId,
+        expected_client_state: Any,
+    ) -> Result<(), Ics02Error> {
+        let path = ClientStatePath(client_id.clone());
+        let value = expected_client_state.encode_to_vec();
+
+        self.verify_signature(height, prefix, proof, path, value)
+    }
+
+    fn verify_packet_data(
+        &self,
+        _ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        commitment: PacketCommitment,
+    ) -> Result<(), Ics02Error> {
+        let commitment_path = CommitmentsPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.verify_signature(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            commitment_path,
+            commitment.into_vec(),
+        )
+    }
+
+    fn verify_packet_acknowledgement(
+        &self,
+        _ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        ack_commitment: AcknowledgementCommitment,
+    ) -> Result<(), Ics02Error> {
+        let ack_path = AcksPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.verify_signature(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            ack_path,
+            ack_commitment.into_vec(),
+        )
+    }
+
+    fn verify_next_sequence_recv(
+        &self,
+        _ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) -> Result<(), Ics02Error> {
+        let mut seq_bytes = Vec::new();
+        u64::from(sequence)
+            .encode(&mut seq_bytes)
+            .expect("buffer size too small");
+
+        let seq_path = SeqRecvsPath(port_id.clone(), channel_id.clone());
+        self.verify_signature(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            seq_path,
+            seq_bytes,
+        )
+    }
+
+    fn verify_packet_receipt_absence(
+        &self,
+        _ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) -> Result<(), Ics02Error> {
+        let receipt_path = ReceiptsPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.verify_signature(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            receipt_path,
+            Vec::new(),
+        )
+    }
+
+    fn verify_packet_receipt(
+        &self,
+        _ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        receipt: &Receipt,
+    ) -> Result<(), Ics02Error> {
+        let receipt_path = ReceiptsPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.verify_signature(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            receipt_path,
+            receipt.as_bytes().to_vec(),
+        )
+    }
+}
+
+impl Protobuf<RawClientState> for ClientState {}
+
+impl TryFrom<RawClientState> for ClientState {
+    type Error = Error;
+
+    fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
+        let consensus_state = raw
+            .consensus_state
+            .ok_or_else(|| Error::invalid_raw_client_state("missing consensus state".into()))?
+            .try_into()?;
+
+        Ok(Self {
+            is_frozen: raw.is_frozen,
+            ..Self::new(raw.sequence, consensus_state)?
+        })
+    }
+}
+
+impl From<ClientState> for RawClientState {
+    fn from(value: ClientState) -> Self {
+        Self {
+            sequence: value.sequence,
+            is_frozen: value.is_frozen,
+            consensus_state: Some(value.consensus_state.into()),
+        }
+    }
+}
+
+impl Protobuf<Any> for ClientState {}
+
+impl TryFrom<Any> for ClientState {
+    type Error = Ics02Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        use core::ops::Deref;
+
+        match raw.type_url.as_str() {
+            SOLOMACHINE_CLIENT_STATE_TYPE_URL => RawClientState::decode(raw.value.deref())
+                .map_err(Error::decode)?
+                .try_into()
+                .map_err(Into::into),
+            _ => Err(Ics02Error::unknown_client_state_type(raw.type_url)),
+        }
+    }
+}
+
+impl From<ClientState> for Any {
+    fn from(client_state: ClientState) -> Self {
+        Any {
+            type_url: SOLOMACHINE_CLIENT_STATE_TYPE_URL.to_string(),
+            value: Protobuf::<RawClientState>::encode_vec(&client_state)
+                .expect("encoding to `Any` from `SoloMachineClientState`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_proto::ibc::core::commitment::v1::MerklePath;
+    use tendermint::signature::Signer;
+    use tendermint_testgen::Validator;
+
+    use crate::clients::ics06_solomachine::misbehaviour::SignatureAndData;
+    use crate::clients::ics06_solomachine::public_key::PublicKey;
+    use crate::clients::ics06_solomachine::signature::encode_single_signature;
+    use crate::core::ics02_client::handler::{dispatch, ClientResult};
+    use crate::core::ics02_client::msgs::update_client::MsgUpdateClient;
+    use crate::core::ics02_client::msgs::ClientMsg;
+    use crate::core::ics03_connection::connection::{
+        Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics03_connection::context::{ConnectionKeeper, ConnectionReader};
+    use crate::core::ics03_connection::msgs::conn_open_confirm::MsgConnectionOpenConfirm;
+    use crate::core::ics03_connection::msgs::ConnectionMsg;
+    use crate::core::ics26_routing::handler::dispatch as ics26_dispatch;
+    use crate::core::ics26_routing::msgs::Ics26Envelope::Ics3Msg;
+    use crate::mock::client_state::MockClientRecord;
+    use crate::mock::context::MockContext;
+    use crate::test_utils::get_dummy_account_id;
+    use crate::timestamp::Timestamp;
+
+    struct SoloMachine {
+        key: tendermint::private_key::Ed25519,
+    }
+
+    impl SoloMachine {
+        fn new(id: &str) -> Self {
+            Self {
+                key: Validator::new(id).get_private_key().unwrap(),
+            }
+        }
+
+        fn public_key(&self) -> PublicKey {
+            PublicKey(self.key.public.into())
+        }
+
+        fn sign(&self, sign_bytes: &SignBytes) -> Vec<u8> {
+            let signature: tendermint::signature::Ed25519Signature =
+                self.key.sign(&sign_bytes.encode_to_vec());
+            encode_single_signature(signature.to_bytes().to_vec())
+        }
+    }
+
+    fn timestamp(nanoseconds: u64) -> Timestamp {
+        Timestamp::from_nanoseconds(nanoseconds).unwrap()
+    }
+
+    fn client_state(machine: &SoloMachine) -> ClientState {
+        let consensus_state =
+            SoloMachineConsensusState::new(machine.public_key(), "solo".to_string(), timestamp(10));
+        ClientState::new(1, consensus_state).unwrap()
+    }
+
+    fn header(machine: &SoloMachine, next: &SoloMachine, ts: u64) -> SoloMachineHeader {
+        let mut header = SoloMachineHeader {
+            sequence: 1,
+            timestamp: timestamp(ts),
+            signature: vec![],
+            new_public_key: next.public_key(),
+            new_diversifier: "solo-2".to_string(),
+        };
+        header.signature = machine.sign(&header.sign_bytes("solo"));
+        header
+    }
+
+    fn solo_machine_ctx(client_id: &ClientId, client_state: &ClientState) -> MockContext {
+        let ctx = MockContext::default();
+        ctx.ibc_store.lock().unwrap().clients.insert(
+            client_id.clone(),
+            MockClientRecord {
+                client_type: solomachine_client_type(),
+                client_state: Some(client_state.clone().into_box()),
+                encoded_client_state: None,
+                consensus_states: [(
+                    client_state.latest_height(),
+                    client_state.consensus_state.clone().into_box(),
+                )]
+                .into_iter()
+                .collect(),
+            },
+        );
+        ctx
+    }
+
+    #[test]
+    fn client_state_any_round_trip() {
+        let client_state = client_state(&SoloMachine::new("a"));
+
+        let any = Any::from(client_state.clone());
+        assert_eq!(any.type_url, SOLOMACHINE_CLIENT_STATE_TYPE_URL);
+        assert_eq!(ClientState::try_from(any).unwrap(), client_state);
+        assert_eq!(client_state.chain_id().as_str(), "solo");
+
+        assert!(ClientState::new(0, client_state.consensus_state).is_err());
+    }
+
+    #[test]
+    fn path_is_escaped_as_in_ibc_go() {
+        let merkle_path = MerklePath {
+            key_path: vec!["ibc".to_string(), "connections/connection-0".to_string()],
+        };
+        assert_eq!(
+            merkle_path_bytes(&merkle_path),
+            b"/ibc/connections%2Fconnection-0".to_vec()
+        );
+    }
+
+    #[test]
+    fn update_rotates_the_key() {
+        let machine = SoloMachine::new("a");
+        let next = SoloMachine::new("b");
+
+        let client_id = ClientId::new(solomachine_client_type(), 0).unwrap();
+        let client_state = client_state(&machine);
+        let ctx = solo_machine_ctx(&client_id, &client_state);
+
+        let msg = MsgUpdateClient {
+            client_id,
+            header: header(&machine, &next, 20).into(),
+            signer: get_dummy_account_id(),
+        };
+        let res = match dispatch(&ctx, ClientMsg::UpdateClient(msg)).unwrap().result {
+            ClientResult::Update(res) => res,
+            _ => panic!("update handler result has incorrect type"),
+        };
+        assert_eq!(res.consensus_heights, vec![Height::new(0, 2).unwrap()]);
+        let updated = res
+            .client_state
+            .as_any()
+            .downcast_ref::<ClientState>()
+            .unwrap();
+        assert_eq!(updated.sequence, 2);
+        assert_eq!(updated.consensus_state.public_key, next.public_key());
+        assert_eq!(updated.consensus_state.diversifier, "solo-2");
+
+        let update = |header: SoloMachineHeader| {
+            client_state.check_header_and_update_state(&ctx, ClientId::default(), header.into())
+        };
+        // Signed by the wrong key.
+        assert!(update(header(&next, &next, 20)).is_err());
+        // Older than the consensus state.
+        assert!(update(header(&machine, &next, 5)).is_err());
+    }
+
+    #[test]
+    fn membership_is_verified_against_the_signature() {
+        let machine = SoloMachine::new("a");
+        let client_state = client_state(&machine);
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+        let root = client_state.consensus_state.root().clone();
+        let connection_id = ConnectionId::default();
+        let connection_end = ConnectionEnd::default();
+
+        let sign = |data: Vec<u8>| {
+            let merkle_path = apply_prefix(
+                &prefix,
+                vec![Path::from(ConnectionsPath(connection_id.clone())).to_string()],
+            );
+            let sign_bytes = SignBytes {
+                sequence: 1,
+                timestamp: 15,
+                diversifier: "solo".to_string(),
+                path: merkle_path_bytes(&merkle_path),
+                data,
+            };
+            TimestampedSignatureData {
+                signature_data: machine.sign(&sign_bytes),
+                timestamp: 15,
+            }
+            .encode_to_vec()
+        };
+        let verify = |height: Height, proof: Vec<u8>| {
+            client_state.verify_connection_state(
+                height,
+                &prefix,
+                &proof.try_into().unwrap(),
+                &root,
+                &connection_id,
+                &connection_end,
+            )
+        };
+
+        let proof = sign(connection_end.encode_vec().unwrap());
+        assert!(verify(Height::new(0, 1).unwrap(), proof.clone()).is_ok());
+        assert!(verify(Height::new(0, 2).unwrap(), proof).is_err());
+        assert!(verify(Height::new(0, 1).unwrap(), sign(vec![1])).is_err());
+    }
+
+    #[test]
+    fn proofs_cannot_be_replayed() {
+        let machine = SoloMachine::new("a");
+        let client_state = client_state(&machine);
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+        let root = client_state.consensus_state.root().clone();
+        let connection_id = ConnectionId::default();
+        let connection_end = ConnectionEnd::default();
+
+        let merkle_path = apply_prefix(
+            &prefix,
+            vec![Path::from(ConnectionsPath(connection_id.clone())).to_string()],
+        );
+        let sign_bytes = SignBytes {
+            sequence: 1,
+            timestamp: 15,
+            diversifier: "solo".to_string(),
+            path: merkle_path_bytes(&merkle_path),
+            data: connection_end.encode_vec().unwrap(),
+        };
+        let proof: CommitmentProofBytes = TimestampedSignatureData {
+            signature_data: machine.sign(&sign_bytes),
+            timestamp: 15,
+        }
+        .encode_to_vec()
+        .try_into()
+        .unwrap();
+        let verify = |client_state: &dyn Ics2ClientState, height: Height| {
+            client_state.verify_connection_state(
+                height,
+                &prefix,
+                &proof,
+                &root,
+                &connection_id,
+                &connection_end,
+            )
+        };
+
+        assert!(verify(&client_state, client_state.latest_height()).is_ok());
+
+        let consumed = client_state.consume_proofs().unwrap().client_state;
+        assert_eq!(consumed.latest_height(), Height::new(0, 2).unwrap());
+        assert!(verify(consumed.as_ref(), client_state.latest_height()).is_err());
+        assert!(verify(consumed.as_ref(), consumed.latest_height()).is_err());
+    }
+
+    #[test]
+    fn messages_consume_the_sequence() {
+        let machine = SoloMachine::new("a");
+        let client_id = ClientId::new(solomachine_client_type(), 0).unwrap();
+        let mut ctx = solo_machine_ctx(&client_id, &client_state(&machine));
+
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+        let conn_id_on_a = ConnectionId::new(1);
+        let conn_id_on_b = ConnectionId::new(0);
+        let conn_end_on_b = ConnectionEnd::new(
+            ConnectionState::TryOpen,
+            client_id.clone(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(conn_id_on_a.clone()),
+                prefix.clone(),
+            ),
+            ConnectionEnd::default().versions().to_vec(),
+            Duration::ZERO,
+        );
+        let conn_end_on_a = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                client_id.clone(),
+                Some(conn_id_on_b.clone()),
+                ctx.commitment_prefix(),
+            ),
+            conn_end_on_b.versions().to_vec(),
+            Duration::ZERO,
+        );
+
+        let merkle_path = apply_prefix(
+            &prefix,
+            vec![Path::from(ConnectionsPath(conn_id_on_a)).to_string()],
+        );
+        let sign_bytes = SignBytes {
+            sequence: 1,
+            timestamp: 15,
+            diversifier: "solo".to_string(),
+            path: merkle_path_bytes(&merkle_path),
+            data: conn_end_on_a.encode_vec().unwrap(),
+        };
+        let msg = MsgConnectionOpenConfirm {
+            conn_id_on_b: conn_id_on_b.clone(),
+            proof_conn_end_on_a: TimestampedSignatureData {
+                signature_data: machine.sign(&sign_bytes),
+                timestamp: 15,
+            }
+            .encode_to_vec()
+            .try_into()
+            .unwrap(),
+            proof_height_on_a: Height::new(0, 1).unwrap(),
+            signer: get_dummy_account_id(),
+        };
+        let confirm = |ctx: &mut MockContext, msg: MsgConnectionOpenConfirm| {
+            ctx.store_connection(conn_id_on_b.clone(), &conn_end_on_b)
+                .unwrap();
+            ics26_dispatch(ctx, Ics3Msg(ConnectionMsg::ConnectionOpenConfirm(msg)))
+        };
+
+        confirm(&mut ctx, msg.clone()).unwrap();
+        let client_state = ClientReader::client_state(&ctx, &client_id).unwrap();
+        assert_eq!(client_state.latest_height(), Height::new(0, 2).unwrap());
+
+        // The signature was bound to the consumed sequence.
+        assert!(confirm(&mut ctx, msg.clone()).is_err());
+        let msg = MsgConnectionOpenConfirm {
+            proof_height_on_a: Height::new(0, 2).unwrap(),
+            ..msg
+        };
+        assert!(confirm(&mut ctx, msg).is_err());
+    }
+
+    #[test]
+    fn misbehaviour_freezes_the_client() {
+        let machine = SoloMachine::new("a");
+        let client_state = client_state(&machine);
+
+        let signature_and_data = |data: Vec<u8>| {
+            let mut signature = SignatureAndData {
+                signature: vec![],
+                path: b"/ibc/connections%2Fconnection-0".to_vec(),
+                data,
+                timestamp: timestamp(15),
+            };
+            signature.signature = machine.sign(&signature.sign_bytes(1, "solo"));
+            signature
+        };
+
+        let misbehaviour = Misbehaviour {
+            sequence: 1,
+            signature_one: signature_and_data(vec![1]),
+            signature_two: signature_and_data(vec![2]),
+        };
+        let frozen = client_state
+            .check_misbehaviour_and_update_state(misbehaviour.clone())
+            .unwrap();
+        assert!(frozen.is_frozen());
+        assert!(frozen
+            .check_misbehaviour_and_update_state(misbehaviour)
+            .is_err());
+
+        let equal = Misbehaviour {
+            sequence: 1,
+            signature_one: signature_and_data(vec![1]),
+            signature_two: signature_and_data(vec![1]),
+        };
+        assert!(client_state
+            .check_misbehaviour_and_update_state(equal)
+            .is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::prelude::*;
+
+use flex_error::{define_error, TraceError};
+
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::Height;
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        InvalidRawClientState
+            { reason: String }
+            |e| { format_args!("invalid raw client state: {}", e.reason) },
+
+        InvalidRawConsensusState
+            { reason: String }
+            |e| { format_args!("invalid raw consensus state: {}", e.reason) },
+
+        InvalidRawHeader
+            { reason: String }
+            |e| { format_args!("invalid raw header: {}", e.reason) },
+
+        InvalidRawMisbehaviour
+            { reason: String }
+            |e| { format_args!("invalid raw misbehaviour: {}", e.reason) },
+
+        Decode
+            [ TraceError<prost::DecodeError> ]
+            |_| { "decode error" },
+
+        UnsupportedPublicKey
+            { type_url: String }
+            |e| { format_args!("unsupported public key type: {}", e.type_url) },
+
+        InvalidPublicKey
+            |_| { "invalid public key" },
+
+        EmptySignature
+            |_| { "empty signature" },
+
+        UnsupportedMultiSignature
+            |_| { "multi-signatures are not supported" },
+
+        SignatureVerification
+            { reason: String }
+            |e| { format_args!("signature verification failed: {}", e.reason) },
+
+        ClientFrozen
+            |_| { "the client is frozen" },
+
+        InvalidProofHeight
+            {
+                height: Height,
+                sequence: u64,
+            }
+            |e| {
+                format_args!("proof height {0} does not match the sequence {1} of the client",
+                    e.height, e.sequence)
+            },
+
+        TimestampTooOld
+            {
+                timestamp: u64,
+                consensus_timestamp: u64,
+            }
+            |e| {
+                format_args!("timestamp {0} is older than the timestamp {1} of the consensus state",
+                    e.timestamp, e.consensus_timestamp)
+            },
+
+        EqualSignatures
+            |_| { "the signatures of the misbehaviour are equal" },
+
+        UpgradeNotSupported
+            |_| { "solo machine clients cannot be upgraded" },
+    }
+}
+
+impl From<Error> for Ics02Error {
+    fn from(e: Error) -> Self {
+        Self::client_specific(e.to_string())
+    }
+}
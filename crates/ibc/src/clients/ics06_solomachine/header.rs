@@ -0,0 +1,131 @@
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::protobuf::Protobuf;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::clients::ics06_solomachine::error::Error;
+use crate::clients::ics06_solomachine::public_key::PublicKey;
+use crate::clients::ics06_solomachine::raw::{
+    Header as RawHeader, HeaderData as RawHeaderData, SignBytes,
+};
+use crate::clients::ics06_solomachine::signature::HEADER_SIGN_BYTES_PATH;
+use crate::core::ics02_client::client_type::ClientType;
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::timestamp::Timestamp;
+use crate::Height;
+
+use super::client_type as solomachine_client_type;
+
+pub const SOLOMACHINE_HEADER_TYPE_URL: &str = "/ibc.lightclients.solomachine.v3.Header";
+
+/// A header rotating the key and diversifier of the solo machine, signed by its current key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    /// The sequence that the header is signed at. It is not part of the encoding, but set from
+    /// the client state when the header is decoded by the client.
+    pub sequence: u64,
+    pub timestamp: Timestamp,
+    /// The encoded `SignatureDescriptor.Data` of the signature.
+    pub signature: Vec<u8>,
+    pub new_public_key: PublicKey,
+    pub new_diversifier: String,
+}
+
+impl Header {
+    /// Returns the bytes that the solo machine signs for the header, under the `diversifier` of
+    /// the current consensus state.
+    pub fn sign_bytes(&self, diversifier: &str) -> SignBytes {
+        let data = RawHeaderData {
+            new_pub_key: Some(self.new_public_key.into()),
+            new_diversifier: self.new_diversifier.clone(),
+        };
+
+        SignBytes {
+            sequence: self.sequence,
+            timestamp: self.timestamp.nanoseconds(),
+            diversifier: diversifier.to_string(),
+            path: HEADER_SIGN_BYTES_PATH.to_vec(),
+            data: data.encode_to_vec(),
+        }
+    }
+}
+
+impl crate::core::ics02_client::header::Header for Header {
+    fn client_type(&self) -> ClientType {
+        solomachine_client_type()
+    }
+
+    /// The height of the consensus state that the header produces.
+    fn height(&self) -> Height {
+        Height::new(0, self.sequence + 1).expect("sequence + 1 is never zero")
+    }
+
+    fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+}
+
+impl Protobuf<RawHeader> for Header {}
+
+impl TryFrom<RawHeader> for Header {
+    type Error = Error;
+
+    fn try_from(raw: RawHeader) -> Result<Self, Self::Error> {
+        if raw.signature.is_empty() {
+            return Err(Error::empty_signature());
+        }
+
+        Ok(Self {
+            sequence: 0,
+            timestamp: Timestamp::from_nanoseconds(raw.timestamp)
+                .map_err(|e| Error::invalid_raw_header(e.to_string()))?,
+            signature: raw.signature,
+            new_public_key: raw
+                .new_public_key
+                .ok_or_else(|| Error::invalid_raw_header("missing new public key".into()))?
+                .try_into()?,
+            new_diversifier: raw.new_diversifier,
+        })
+    }
+}
+
+impl From<Header> for RawHeader {
+    fn from(value: Header) -> Self {
+        Self {
+            timestamp: value.timestamp.nanoseconds(),
+            signature: value.signature,
+            new_public_key: Some(value.new_public_key.into()),
+            new_diversifier: value.new_diversifier,
+        }
+    }
+}
+
+impl Protobuf<Any> for Header {}
+
+impl TryFrom<Any> for Header {
+    type Error = Ics02Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        use core::ops::Deref;
+
+        match raw.type_url.as_str() {
+            SOLOMACHINE_HEADER_TYPE_URL => RawHeader::decode(raw.value.deref())
+                .map_err(Error::decode)?
+                .try_into()
+                .map_err(Into::into),
+            _ => Err(Ics02Error::unknown_header_type(raw.type_url)),
+        }
+    }
+}
+
+impl From<Header> for Any {
+    fn from(header: Header) -> Self {
+        Any {
+            type_url: SOLOMACHINE_HEADER_TYPE_URL.to_string(),
+            value: Protobuf::<RawHeader>::encode_vec(&header)
+                .expect("encoding to `Any` from `SoloMachineHeader`"),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::protobuf::Protobuf;
+use serde::{Deserialize, Serialize};
+
+use crate::clients::ics06_solomachine::error::Error;
+use crate::clients::ics06_solomachine::raw::{
+    Misbehaviour as RawMisbehaviour, SignBytes, SignatureAndData as RawSignatureAndData,
+};
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::timestamp::Timestamp;
+
+pub const SOLOMACHINE_MISBEHAVIOUR_TYPE_URL: &str = "/ibc.lightclients.solomachine.v3.Misbehaviour";
+
+/// Two different signatures by the solo machine at the same sequence.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Misbehaviour {
+    pub sequence: u64,
+    pub signature_one: SignatureAndData,
+    pub signature_two: SignatureAndData,
+}
+
+/// A signature, along with the path and data that it signs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureAndData {
+    /// The encoded `SignatureDescriptor.Data` of the signature.
+    pub signature: Vec<u8>,
+    pub path: Vec<u8>,
+    pub data: Vec<u8>,
+    pub timestamp: Timestamp,
+}
+
+impl SignatureAndData {
+    /// Returns the bytes signed at `sequence` under `diversifier`.
+    pub fn sign_bytes(&self, sequence: u64, diversifier: &str) -> SignBytes {
+        SignBytes {
+            sequence,
+            timestamp: self.timestamp.nanoseconds(),
+            diversifier: diversifier.to_string(),
+            path: self.path.clone(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl TryFrom<RawSignatureAndData> for SignatureAndData {
+    type Error = Error;
+
+    fn try_from(raw: RawSignatureAndData) -> Result<Self, Self::Error> {
+        if raw.signature.is_empty() {
+            return Err(Error::empty_signature());
+        }
+
+        Ok(Self {
+            signature: raw.signature,
+            path: raw.path,
+            data: raw.data,
+            timestamp: Timestamp::from_nanoseconds(raw.timestamp)
+                .map_err(|e| Error::invalid_raw_misbehaviour(e.to_string()))?,
+        })
+    }
+}
+
+impl From<SignatureAndData> for RawSignatureAndData {
+    fn from(value: SignatureAndData) -> Self {
+        Self {
+            signature: value.signature,
+            path: value.path,
+            data: value.data,
+            timestamp: value.timestamp.nanoseconds(),
+        }
+    }
+}
+
+impl Protobuf<RawMisbehaviour> for Misbehaviour {}
+
+impl TryFrom<RawMisbehaviour> for Misbehaviour {
+    type Error = Error;
+
+    fn try_from(raw: RawMisbehaviour) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sequence: raw.sequence,
+            signature_one: raw
+                .signature_one
+                .ok_or_else(|| Error::invalid_raw_misbehaviour("missing signature one".into()))?
+                .try_into()?,
+            signature_two: raw
+                .signature_two
+                .ok_or_else(|| Error::invalid_raw_misbehaviour("missing signature two".into()))?
+                .try_into()?,
+        })
+    }
+}
+
+impl From<Misbehaviour> for RawMisbehaviour {
+    fn from(value: Misbehaviour) -> Self {
+        Self {
+            sequence: value.sequence,
+            signature_one: Some(value.signature_one.into()),
+            signature_two: Some(value.signature_two.into()),
+        }
+    }
+}
+
+impl Protobuf<Any> for Misbehaviour {}
+
+impl TryFrom<Any> for Misbehaviour {
+    type Error = Ics02Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        use core::ops::Deref;
+        use prost::Message;
+
+        match raw.type_url.as_str() {
+            SOLOMACHINE_MISBEHAVIOUR_TYPE_URL => RawMisbehaviour::decode(raw.value.deref())
+                .map_err(Error::decode)?
+                .try_into()
+                .map_err(Into::into),
+            _ => Err(Ics02Error::unknown_misbehaviour_type(raw.type_url)),
+        }
+    }
+}
+
+impl From<Misbehaviour> for Any {
+    fn from(misbehaviour: Misbehaviour) -> Self {
+        Any {
+            type_url: SOLOMACHINE_MISBEHAVIOUR_TYPE_URL.to_string(),
+            value: Protobuf::<RawMisbehaviour>::encode_vec(&misbehaviour)
+                .expect("encoding to `Any` from `SoloMachineMisbehaviour`"),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! ICS 06: Solo Machine Client implements a client verification algorithm for standalone
+//! machines (e.g. devices or custodial services) that sign their state with a single key, as in
+//! the v3 solo machine client of ibc-go.
+//!
+//! The machine signs headers that rotate its key, and proofs made of a signature over the path
+//! and value being proven, bound to its current sequence and diversifier. Unlike in ibc-go, proof
+//! verification cannot advance the sequence of the client, so that all proofs are signed at the
+//! sequence of the latest header.
+
+use alloc::string::ToString;
+
+use crate::core::ics02_client::client_type::ClientType;
+
+pub mod client_state;
+pub mod consensus_state;
+pub mod error;
+pub mod header;
+pub mod misbehaviour;
+pub mod public_key;
+pub mod raw;
+pub mod signature;
+
+pub(crate) const SOLOMACHINE_CLIENT_TYPE: &str = "06-solomachine";
+
+pub fn client_type() -> ClientType {
+    ClientType::new(SOLOMACHINE_CLIENT_TYPE.to_string())
+}
@@ -0,0 +1,55 @@
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::clients::ics06_solomachine::error::Error;
+use crate::clients::ics06_solomachine::raw::Ed25519PubKey as RawEd25519PubKey;
+
+pub const ED25519_PUBLIC_KEY_TYPE_URL: &str = "/cosmos.crypto.ed25519.PubKey";
+
+/// The key that a solo machine signs with. Only ed25519 keys are supported.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKey(pub tendermint::PublicKey);
+
+impl PublicKey {
+    /// Verifies the raw `signature` of `msg`.
+    pub fn verify(&self, msg: &[u8], signature: &[u8]) -> Result<(), Error> {
+        let signature = tendermint::Signature::new(signature)
+            .map_err(|e| Error::signature_verification(e.to_string()))?
+            .ok_or_else(Error::empty_signature)?;
+
+        self.0
+            .verify(msg, &signature)
+            .map_err(|e| Error::signature_verification(e.to_string()))
+    }
+}
+
+impl TryFrom<Any> for PublicKey {
+    type Error = Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        match raw.type_url.as_str() {
+            ED25519_PUBLIC_KEY_TYPE_URL => {
+                let key = RawEd25519PubKey::decode(raw.value.as_slice()).map_err(Error::decode)?;
+                tendermint::PublicKey::from_raw_ed25519(&key.key)
+                    .map(Self)
+                    .ok_or_else(Error::invalid_public_key)
+            }
+            _ => Err(Error::unsupported_public_key(raw.type_url)),
+        }
+    }
+}
+
+impl From<PublicKey> for Any {
+    fn from(public_key: PublicKey) -> Self {
+        Any {
+            type_url: ED25519_PUBLIC_KEY_TYPE_URL.to_string(),
+            value: RawEd25519PubKey {
+                key: public_key.0.to_bytes(),
+            }
+            .encode_to_vec(),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Protobuf definitions of the v3 solo machine client, which `ibc-proto` does not provide yet.
+
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientState {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    #[prost(bool, tag = "2")]
+    pub is_frozen: bool,
+    #[prost(message, optional, tag = "3")]
+    pub consensus_state: Option<ConsensusState>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConsensusState {
+    #[prost(message, optional, tag = "1")]
+    pub public_key: Option<Any>,
+    #[prost(string, tag = "2")]
+    pub diversifier: String,
+    #[prost(uint64, tag = "3")]
+    pub timestamp: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Header {
+    #[prost(uint64, tag = "1")]
+    pub timestamp: u64,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub new_public_key: Option<Any>,
+    #[prost(string, tag = "4")]
+    pub new_diversifier: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Misbehaviour {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    #[prost(message, optional, tag = "2")]
+    pub signature_one: Option<SignatureAndData>,
+    #[prost(message, optional, tag = "3")]
+    pub signature_two: Option<SignatureAndData>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignatureAndData {
+    #[prost(bytes = "vec", tag = "1")]
+    pub signature: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub path: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: Vec<u8>,
+    #[prost(uint64, tag = "4")]
+    pub timestamp: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimestampedSignatureData {
+    #[prost(bytes = "vec", tag = "1")]
+    pub signature_data: Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub timestamp: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignBytes {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    #[prost(uint64, tag = "2")]
+    pub timestamp: u64,
+    #[prost(string, tag = "3")]
+    pub diversifier: String,
+    #[prost(bytes = "vec", tag = "4")]
+    pub path: Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HeaderData {
+    #[prost(message, optional, tag = "1")]
+    pub new_pub_key: Option<Any>,
+    #[prost(string, tag = "2")]
+    pub new_diversifier: String,
+}
+
+/// The ed25519 public key of the Cosmos SDK (`cosmos.crypto.ed25519.PubKey`).
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Ed25519PubKey {
+    #[prost(bytes = "vec", tag = "1")]
+    pub key: Vec<u8>,
+}
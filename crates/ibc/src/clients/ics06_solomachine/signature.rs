@@ -0,0 +1,67 @@
+//! Construction and verification of the bytes that a solo machine signs.
+
+use crate::prelude::*;
+
+use ibc_proto::cosmos::tx::signing::v1beta1::signature_descriptor::data::{Single, Sum};
+use ibc_proto::cosmos::tx::signing::v1beta1::signature_descriptor::Data as RawSignatureData;
+use ibc_proto::cosmos::tx::signing::v1beta1::SignMode;
+use ibc_proto::ibc::core::commitment::v1::MerklePath;
+use prost::Message;
+
+use crate::clients::ics06_solomachine::error::Error;
+use crate::clients::ics06_solomachine::public_key::PublicKey;
+use crate::clients::ics06_solomachine::raw::SignBytes;
+
+/// The path of the sign bytes of headers.
+pub const HEADER_SIGN_BYTES_PATH: &[u8] = b"solomachine:header";
+
+/// Returns the path of `merkle_path` that proofs sign, i.e. its keys URL-escaped and each
+/// preceded by a `/`, as ibc-go formats it.
+pub fn merkle_path_bytes(merkle_path: &MerklePath) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for key in &merkle_path.key_path {
+        bytes.push(b'/');
+        for byte in key.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-_.~$&+:=@".contains(&byte) {
+                bytes.push(byte);
+            } else {
+                bytes.extend_from_slice(format!("%{:02X}", byte).as_bytes());
+            }
+        }
+    }
+    bytes
+}
+
+/// Encodes a single signature as the `SignatureDescriptor.Data` that solo machines submit.
+pub fn encode_single_signature(signature: Vec<u8>) -> Vec<u8> {
+    RawSignatureData {
+        sum: Some(Sum::Single(Single {
+            mode: SignMode::Direct as i32,
+            signature,
+        })),
+    }
+    .encode_to_vec()
+}
+
+/// Verifies that `signature_data`, an encoded `SignatureDescriptor.Data`, holds the signature of
+/// `sign_bytes` by `public_key`. Multi-signatures are not supported.
+pub fn verify_signature(
+    public_key: &PublicKey,
+    sign_bytes: &SignBytes,
+    signature_data: &[u8],
+) -> Result<(), Error> {
+    if signature_data.is_empty() {
+        return Err(Error::empty_signature());
+    }
+
+    let signature = match RawSignatureData::decode(signature_data)
+        .map_err(Error::decode)?
+        .sum
+    {
+        Some(Sum::Single(single)) => single.signature,
+        Some(Sum::Multi(_)) => return Err(Error::unsupported_multi_signature()),
+        None => return Err(Error::empty_signature()),
+    };
+
+    public_key.verify(&sign_bytes.encode_to_vec(), &signature)
+}
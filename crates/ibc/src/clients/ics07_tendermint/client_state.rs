@@ -1,11 +1,13 @@
 use crate::core::ics02_client::context::ClientReader;
 use crate::core::ics03_connection::connection::ConnectionEnd;
 use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCommitment};
-use crate::core::ics04_channel::packet::Sequence;
+use crate::core::ics04_channel::packet::{Receipt, Sequence};
 use crate::core::ics23_commitment::commitment::{
     CommitmentPrefix, CommitmentProofBytes, CommitmentRoot,
 };
-use crate::core::ics23_commitment::merkle::{apply_prefix, MerkleProof};
+use crate::core::ics23_commitment::merkle::apply_prefix;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics24_host::path::ChannelUpgradePath;
 use crate::core::ics24_host::path::{
     AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath, CommitmentsPath,
     ConnectionsPath, ReceiptsPath, SeqRecvsPath,
@@ -25,6 +27,7 @@ use prost::Message;
 use serde::{Deserialize, Serialize};
 use tendermint::chain::id::MAX_LENGTH as MaxChainIdLen;
 use tendermint::trust_threshold::TrustThresholdFraction as TendermintTrustThresholdFraction;
+use tendermint::validator::Set as ValidatorSet;
 use tendermint_light_client_verifier::options::Options;
 use tendermint_light_client_verifier::types::{TrustedBlockState, UntrustedBlockState};
 use tendermint_light_client_verifier::{ProdVerifier, Verdict, Verifier};
@@ -32,12 +35,14 @@ use tendermint_light_client_verifier::{ProdVerifier, Verdict, Verifier};
 use crate::clients::ics07_tendermint::consensus_state::ConsensusState as TmConsensusState;
 use crate::clients::ics07_tendermint::error::Error;
 use crate::clients::ics07_tendermint::header::Header as TmHeader;
+use crate::clients::ics07_tendermint::misbehaviour::Misbehaviour as TmMisbehaviour;
 use crate::core::ics02_client::client_state::{
     ClientState as Ics2ClientState, UpdatedState, UpgradeOptions as CoreUpgradeOptions,
 };
 use crate::core::ics02_client::client_type::ClientType;
 use crate::core::ics02_client::consensus_state::ConsensusState;
 use crate::core::ics02_client::error::{Error as Ics02Error, ErrorDetail as Ics02ErrorDetail};
+use crate::core::ics02_client::header::Header;
 use crate::core::ics02_client::trust_threshold::TrustThreshold;
 use crate::core::ics04_channel::context::ChannelReader;
 use crate::core::ics23_commitment::specs::ProofSpecs;
@@ -148,6 +153,11 @@ impl ClientState {
             ));
         }
 
+        // The proof-specs are up to the counterparty, but must be well-formed
+        proof_specs.validate().map_err(|e| {
+            Error::validation(format!("ClientState proof-specs are invalid: {}", e))
+        })?;
+
         // `upgrade_path` itself may be empty, but if not then each key must be non-empty
         for (idx, key) in upgrade_path.iter().enumerate() {
             if key.trim().is_empty() {
@@ -177,13 +187,17 @@ impl ClientState {
         self.latest_height
     }
 
+    /// Returns the client state updated with the header `h`. The latest height never decreases:
+    /// a header below it fills a gap in the consensus states of the client.
     pub fn with_header(self, h: TmHeader) -> Result<Self, Error> {
+        let header_height = Height::new(
+            self.latest_height.revision_number(),
+            h.signed_header.header.height.into(),
+        )
+        .map_err(|_| Error::invalid_header_height(h.signed_header.header.height.value()))?;
+
         Ok(ClientState {
-            latest_height: Height::new(
-                self.latest_height.revision_number(),
-                h.signed_header.header.height.into(),
-            )
-            .map_err(|_| Error::invalid_header_height(h.signed_header.header.height.value()))?,
+            latest_height: core::cmp::max(self.latest_height, header_height),
             ..self
         })
     }
@@ -252,6 +266,89 @@ impl ClientState {
             _ => Ok(()),
         }
     }
+
+    /// Checks that `header` is at the revision of the chain tracked by the client.
+    fn check_header_revision(&self, header: &TmHeader) -> Result<(), Ics02Error> {
+        if header.height().revision_number() != self.chain_id().version() {
+            return Err(Ics02Error::client_specific(
+                Error::mismatched_revisions(
+                    self.chain_id().version(),
+                    header.height().revision_number(),
+                )
+                .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Verifies `header` against the consensus state of the client at its trusted height.
+    fn verify_header(
+        &self,
+        ctx: &dyn ClientReader,
+        client_id: &ClientId,
+        header: &TmHeader,
+    ) -> Result<(), Ics02Error> {
+        let trusted_consensus_state = downcast_tm_consensus_state(
+            ctx.consensus_state(client_id, header.trusted_height)?
+                .as_ref(),
+        )?;
+
+        let trusted_validators_hash = header.trusted_validator_set.hash();
+        if trusted_validators_hash != trusted_consensus_state.next_validators_hash {
+            return Err(Error::mismatched_trusted_validators_hash(
+                header.trusted_height,
+                trusted_consensus_state.next_validators_hash,
+                trusted_validators_hash,
+            )
+            .into());
+        }
+
+        let trusted_state = TrustedBlockState {
+            header_time: trusted_consensus_state.timestamp,
+            height: header
+                .trusted_height
+                .revision_height()
+                .try_into()
+                .map_err(|_| {
+                    Ics02Error::client_specific(
+                        Error::invalid_header_height(header.trusted_height.revision_height())
+                            .to_string(),
+                    )
+                })?,
+            next_validators: &header.trusted_validator_set,
+            next_validators_hash: trusted_consensus_state.next_validators_hash,
+        };
+
+        let untrusted_state = UntrustedBlockState {
+            signed_header: &header.signed_header,
+            validators: &header.validator_set,
+            // NB: This will skip the
+            // VerificationPredicates::next_validators_match check for the
+            // untrusted state.
+            next_validators: None,
+        };
+
+        let options = self.as_light_client_options()?;
+
+        let verdict = self.verifier.verify(
+            untrusted_state,
+            trusted_state,
+            &options,
+            ctx.host_timestamp().into_tm_time().unwrap(),
+        );
+
+        match verdict {
+            Verdict::Success => Ok(()),
+            Verdict::NotEnoughTrust(voting_power_tally) => {
+                Err(Error::not_enough_trusted_vals_signed(format!(
+                    "voting power tally: {}",
+                    voting_power_tally
+                ))
+                .into())
+            }
+            Verdict::Invalid(detail) => Err(Error::verification_error(detail).into()),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -307,10 +404,18 @@ impl Ics2ClientState for ClientState {
         elapsed > self.trusting_period
     }
 
+    fn trusting_period(&self) -> Option<Duration> {
+        Some(self.trusting_period)
+    }
+
     fn initialise(&self, consensus_state: Any) -> Result<Box<dyn ConsensusState>, Ics02Error> {
         TmConsensusState::try_from(consensus_state).map(TmConsensusState::into_box)
     }
 
+    fn decode_header(&self, header: Any) -> Result<Box<dyn Header>, Ics02Error> {
+        TmHeader::try_from(header).map(TmHeader::into_box)
+    }
+
     fn check_header_and_update_state(
         &self,
         ctx: &dyn ClientReader,
@@ -334,15 +439,7 @@ impl Ics2ClientState for ClientState {
         let client_state = downcast_tm_client_state(self)?.clone();
         let header = TmHeader::try_from(header)?;
 
-        if header.height().revision_number() != client_state.chain_id().version() {
-            return Err(Ics02Error::client_specific(
-                Error::mismatched_revisions(
-                    client_state.chain_id().version(),
-                    header.height().revision_number(),
-                )
-                .to_string(),
-            ));
-        }
+        client_state.check_header_revision(&header)?;
 
         // Check if a consensus state is already installed; if so it should
         // match the untrusted header.
@@ -366,56 +463,7 @@ impl Ics2ClientState for ClientState {
                 None => None,
             };
 
-        let trusted_consensus_state = downcast_tm_consensus_state(
-            ctx.consensus_state(&client_id, header.trusted_height)?
-                .as_ref(),
-        )?;
-
-        let trusted_state = TrustedBlockState {
-            header_time: trusted_consensus_state.timestamp,
-            height: header
-                .trusted_height
-                .revision_height()
-                .try_into()
-                .map_err(|_| {
-                    Ics02Error::client_specific(
-                        Error::invalid_header_height(header.trusted_height.revision_height())
-                            .to_string(),
-                    )
-                })?,
-            next_validators: &header.trusted_validator_set,
-            next_validators_hash: trusted_consensus_state.next_validators_hash,
-        };
-
-        let untrusted_state = UntrustedBlockState {
-            signed_header: &header.signed_header,
-            validators: &header.validator_set,
-            // NB: This will skip the
-            // VerificationPredicates::next_validators_match check for the
-            // untrusted state.
-            next_validators: None,
-        };
-
-        let options = client_state.as_light_client_options()?;
-
-        let verdict = self.verifier.verify(
-            untrusted_state,
-            trusted_state,
-            &options,
-            ctx.host_timestamp().into_tm_time().unwrap(),
-        );
-
-        match verdict {
-            Verdict::Success => {}
-            Verdict::NotEnoughTrust(voting_power_tally) => {
-                return Err(Error::not_enough_trusted_vals_signed(format!(
-                    "voting power tally: {}",
-                    voting_power_tally
-                ))
-                .into());
-            }
-            Verdict::Invalid(detail) => return Err(Error::verification_error(detail).into()),
-        }
+        client_state.verify_header(ctx, &client_id, &header)?;
 
         // If the header has verified, but its corresponding consensus state
         // differs from the existing consensus state for that height, freeze the
@@ -482,6 +530,45 @@ impl Ics2ClientState for ClientState {
         })
     }
 
+    fn check_misbehaviour_and_update_state(
+        &self,
+        ctx: &dyn ClientReader,
+        client_id: ClientId,
+        misbehaviour: Any,
+    ) -> Result<Box<dyn Ics2ClientState>, Ics02Error> {
+        let client_state = downcast_tm_client_state(self)?.clone();
+        let misbehaviour = TmMisbehaviour::try_from(misbehaviour)?;
+        let (header1, header2) = (&misbehaviour.header1, &misbehaviour.header2);
+
+        if header1.height() < header2.height() {
+            return Err(Error::invalid_misbehaviour_header_heights(
+                header1.height(),
+                header2.height(),
+            )
+            .into());
+        }
+
+        // Headers at the same height must commit to different blocks, and a header above another
+        // must not have a later time, which would break the monotonicity of BFT time.
+        let conflicting = if header1.height() == header2.height() {
+            header1.signed_header.commit.block_id.hash != header2.signed_header.commit.block_id.hash
+        } else {
+            header1.signed_header.header().time <= header2.signed_header.header().time
+        };
+        if !conflicting {
+            return Err(Error::misbehaviour_headers_not_conflicting().into());
+        }
+
+        for header in [header1, header2] {
+            client_state.check_header_revision(header)?;
+            client_state.verify_header(ctx, &client_id, header)?;
+        }
+
+        Ok(client_state
+            .with_frozen_height(header1.height())?
+            .into_box())
+    }
+
     fn verify_upgrade_and_update_state(
         &self,
         _consensus_state: Any,
@@ -504,11 +591,7 @@ impl Ics2ClientState for ClientState {
         let client_state = downcast_tm_client_state(self)?;
         client_state.verify_height(height)?;
 
-        let path = ClientConsensusStatePath {
-            client_id: client_id.clone(),
-            epoch: consensus_height.revision_number(),
-            height: consensus_height.revision_height(),
-        };
+        let path = ClientConsensusStatePath::new(client_id.clone(), consensus_height);
         let value = expected_consensus_state
             .encode_vec()
             .map_err(Ics02Error::invalid_any_consensus_state)?;
@@ -555,6 +638,48 @@ impl Ics2ClientState for ClientState {
         verify_membership(client_state, prefix, proof, root, path, value)
     }
 
+    #[cfg(any(test, feature = "unstable"))]
+    fn verify_channel_upgrade(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        expected_upgrade: &crate::core::ics04_channel::upgrade::Upgrade,
+    ) -> Result<(), Ics02Error> {
+        let client_state = downcast_tm_client_state(self)?;
+        client_state.verify_height(height)?;
+
+        let path = ChannelUpgradePath::Upgrade(port_id.clone(), channel_id.clone());
+        let value = expected_upgrade
+            .encode_vec()
+            .map_err(Ics02Error::invalid_channel_upgrade)?;
+        verify_membership(client_state, prefix, proof, root, path, value)
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn verify_channel_upgrade_error(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        expected_error_receipt: &crate::core::ics04_channel::upgrade::ErrorReceipt,
+    ) -> Result<(), Ics02Error> {
+        let client_state = downcast_tm_client_state(self)?;
+        client_state.verify_height(height)?;
+
+        let path = ChannelUpgradePath::UpgradeError(port_id.clone(), channel_id.clone());
+        let value = expected_error_receipt
+            .encode_vec()
+            .map_err(Ics02Error::invalid_channel_upgrade)?;
+        verify_membership(client_state, prefix, proof, root, path, value)
+    }
+
     fn verify_client_full_state(
         &self,
         height: Height,
@@ -588,11 +713,7 @@ impl Ics2ClientState for ClientState {
         client_state.verify_height(height)?;
         verify_delay_passed(ctx, height, connection_end)?;
 
-        let commitment_path = CommitmentsPath {
-            port_id: port_id.clone(),
-            channel_id: channel_id.clone(),
-            sequence,
-        };
+        let commitment_path = CommitmentsPath::new(port_id.clone(), channel_id.clone(), sequence);
 
         verify_membership(
             client_state,
@@ -620,11 +741,7 @@ impl Ics2ClientState for ClientState {
         client_state.verify_height(height)?;
         verify_delay_passed(ctx, height, connection_end)?;
 
-        let ack_path = AcksPath {
-            port_id: port_id.clone(),
-            channel_id: channel_id.clone(),
-            sequence,
-        };
+        let ack_path = AcksPath::new(port_id.clone(), channel_id.clone(), sequence);
         verify_membership(
             client_state,
             connection_end.counterparty().prefix(),
@@ -682,11 +799,7 @@ impl Ics2ClientState for ClientState {
         client_state.verify_height(height)?;
         verify_delay_passed(ctx, height, connection_end)?;
 
-        let receipt_path = ReceiptsPath {
-            port_id: port_id.clone(),
-            channel_id: channel_id.clone(),
-            sequence,
-        };
+        let receipt_path = ReceiptsPath::new(port_id.clone(), channel_id.clone(), sequence);
         verify_non_membership(
             client_state,
             connection_end.counterparty().prefix(),
@@ -695,6 +808,33 @@ impl Ics2ClientState for ClientState {
             receipt_path,
         )
     }
+
+    fn verify_packet_receipt(
+        &self,
+        ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        receipt: &Receipt,
+    ) -> Result<(), Ics02Error> {
+        let client_state = downcast_tm_client_state(self)?;
+        client_state.verify_height(height)?;
+        verify_delay_passed(ctx, height, connection_end)?;
+
+        let receipt_path = ReceiptsPath::new(port_id.clone(), channel_id.clone(), sequence);
+        verify_membership(
+            client_state,
+            connection_end.counterparty().prefix(),
+            proof,
+            root,
+            receipt_path,
+            receipt.as_bytes().to_vec(),
+        )
+    }
 }
 
 fn verify_membership(
@@ -706,11 +846,9 @@ fn verify_membership(
     value: Vec<u8>,
 ) -> Result<(), Ics02Error> {
     let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
-    let merkle_proof: MerkleProof = RawMerkleProof::try_from(proof.clone())
+    proof
+        .merkle_proof()
         .map_err(Ics02Error::invalid_commitment_proof)?
-        .into();
-
-    merkle_proof
         .verify_membership(
             &client_state.proof_specs,
             root.clone().into(),
@@ -729,11 +867,9 @@ fn verify_non_membership(
     path: impl Into<Path>,
 ) -> Result<(), Ics02Error> {
     let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
-    let merkle_proof: MerkleProof = RawMerkleProof::try_from(proof.clone())
+    proof
+        .merkle_proof()
         .map_err(Ics02Error::invalid_commitment_proof)?
-        .into();
-
-    merkle_proof
         .verify_non_membership(&client_state.proof_specs, root.clone().into(), merkle_path)
         .map_err(Ics02Error::ics23_verification)
 }
@@ -743,6 +879,12 @@ fn verify_delay_passed(
     height: Height,
     connection_end: &ConnectionEnd,
 ) -> Result<(), Ics02Error> {
+    // Without a delay period, the proof is usable as soon as its consensus state was processed,
+    // so hosts need not record when that was.
+    if connection_end.delay_period().is_zero() {
+        return Ok(());
+    }
+
     let current_timestamp = ctx.host_timestamp();
     let current_height = ctx.host_height();
 
@@ -774,11 +916,51 @@ fn downcast_tm_client_state(cs: &dyn Ics2ClientState) -> Result<&ClientState, Ic
         .ok_or_else(|| Ics02Error::client_args_type_mismatch(tm_client_type()))
 }
 
+/// Suggests a trusted height for a header whose trusted validator set is `trusted_validator_set`,
+/// e.g. after an update failed with `MismatchedTrustedValidatorsHash`.
+///
+/// Returns the stored consensus height of the same revision that is closest to `trusted_height`,
+/// lower than `header_height`, and whose next validators hash matches the given validator set.
+/// Returns `None` if there is no such height, or if the host cannot enumerate consensus heights.
+pub fn suggest_trusted_height(
+    ctx: &dyn ClientReader,
+    client_id: &ClientId,
+    trusted_validator_set: &ValidatorSet,
+    trusted_height: Height,
+    header_height: Height,
+) -> Result<Option<Height>, Ics02Error> {
+    let validators_hash = trusted_validator_set.hash();
+    let mut suggestion: Option<Height> = None;
+
+    for height in ctx.consensus_state_heights(client_id)? {
+        if height >= header_height || height.revision_number() != trusted_height.revision_number() {
+            continue;
+        }
+
+        let consensus_state =
+            downcast_tm_consensus_state(ctx.consensus_state(client_id, height)?.as_ref())?;
+        if consensus_state.next_validators_hash != validators_hash {
+            continue;
+        }
+
+        let distance = |h: Height| {
+            h.revision_height()
+                .abs_diff(trusted_height.revision_height())
+        };
+        suggestion = match suggestion {
+            Some(best) if distance(best) < distance(height) => Some(best),
+            _ => Some(height),
+        };
+    }
+
+    Ok(suggestion)
+}
+
 fn downcast_tm_consensus_state(cs: &dyn ConsensusState) -> Result<TmConsensusState, Ics02Error> {
     cs.as_any()
         .downcast_ref::<TmConsensusState>()
         .ok_or_else(|| Ics02Error::client_args_type_mismatch(tm_client_type()))
-        .map(Clone::clone)
+        .cloned()
 }
 
 impl Protobuf<RawTmClientState> for ClientState {}
@@ -920,7 +1102,9 @@ mod tests {
     use ibc_proto::ics23::ProofSpec as Ics23ProofSpec;
     use tendermint_rpc::endpoint::abci_query::AbciQuery;
 
+    use crate::clients::ics07_tendermint::client_state::test_util::get_dummy_tendermint_client_state;
     use crate::clients::ics07_tendermint::client_state::{AllowUpdate, ClientState};
+    use crate::clients::ics07_tendermint::header::test_util::get_dummy_ics07_header;
     use crate::core::ics02_client::trust_threshold::TrustThreshold;
     use crate::core::ics23_commitment::specs::ProofSpecs;
     use crate::core::ics24_host::identifier::ChainId;
@@ -1199,6 +1383,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn client_state_with_lower_header_keeps_latest_height() {
+        let header = get_dummy_ics07_header();
+        let client_state = get_dummy_tendermint_client_state(header.signed_header.header.clone());
+        let latest_height = client_state.latest_height();
+
+        let mut lower_header = header.clone();
+        lower_header.signed_header.header.height = 10u32.into();
+        assert_eq!(
+            client_state
+                .clone()
+                .with_header(lower_header)
+                .unwrap()
+                .latest_height(),
+            latest_height
+        );
+
+        let mut higher_header = header;
+        higher_header.signed_header.header.height = 30u32.into();
+        assert_eq!(
+            client_state
+                .with_header(higher_header)
+                .unwrap()
+                .latest_height(),
+            latest_height.add(10)
+        );
+    }
+
     #[test]
     fn client_state_verify_height() {
         // Define a "default" set of parameters to reuse throughout these tests.
@@ -235,6 +235,17 @@ define_error! {
                 format_args!("invalid validator set: header_validators_hash={} and validators_hash={}", e.hash1, e.hash2)
             },
 
+        MismatchedTrustedValidatorsHash
+            {
+                trusted_height: Height,
+                stored_next_validators_hash: Hash,
+                header_trusted_validators_hash: Hash,
+            }
+            | e | {
+                format_args!("the header's trusted validators hash {0} does not match the next validators hash {1} of the consensus state at trusted height {2}",
+                    e.header_trusted_validators_hash, e.stored_next_validators_hash, e.trusted_height)
+            },
+
         NotEnoughTrustedValsSigned
             { reason: String }
             | e | {
@@ -247,6 +258,20 @@ define_error! {
                 format_args!("verification failed: {}", e.detail)
             },
 
+        InvalidMisbehaviourHeaderHeights
+            {
+                height1: Height,
+                height2: Height,
+            }
+            | e | {
+                format_args!(
+                    "the first header of misbehaviour must not be below the second: {0} < {1}",
+                    e.height1, e.height2)
+            },
+
+        MisbehaviourHeadersNotConflicting
+            | _ | { "the headers of misbehaviour are not conflicting" },
+
         ProcessedTimeNotFound
             {
                 client_id: ClientId,
@@ -24,6 +24,12 @@ use super::client_type as tm_client_type;
 pub const TENDERMINT_HEADER_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.Header";
 
 /// Tendermint consensus header
+///
+/// Headers of chains running ABCI++ with vote extensions are verified like any other: extensions
+/// are signed by validators separately from the canonical vote and are not part of the commit of a
+/// light block, so neither the header hash nor the commit signatures depend on them. Extension
+/// fields that a relayer forwards along with the commit signatures (as in a CometBFT
+/// `ExtendedCommit`) are dropped when decoding.
 #[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Header {
     pub signed_header: SignedHeader, // contains the commitment root
@@ -241,3 +247,117 @@ pub mod test_util {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_proto::ibc::core::client::v1::Height as RawHeight;
+    use tendermint_light_client_verifier::operations::{
+        ProdVotingPowerCalculator, VotingPowerCalculator,
+    };
+    use tendermint_proto::google::protobuf::Timestamp as RawTimestamp;
+    use tendermint_proto::types::{
+        BlockId as RawBlockId, Header as RawTmHeader, ValidatorSet as RawValidatorSet,
+    };
+
+    use crate::mock::host::HostBlock;
+    use crate::prelude::*;
+
+    /// A commit signature carrying a vote extension, as in CometBFT 0.38.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct RawExtendedCommitSig {
+        #[prost(int32, tag = "1")]
+        block_id_flag: i32,
+        #[prost(bytes = "vec", tag = "2")]
+        validator_address: Vec<u8>,
+        #[prost(message, optional, tag = "3")]
+        timestamp: Option<RawTimestamp>,
+        #[prost(bytes = "vec", tag = "4")]
+        signature: Vec<u8>,
+        #[prost(bytes = "vec", tag = "5")]
+        extension: Vec<u8>,
+        #[prost(bytes = "vec", tag = "6")]
+        extension_signature: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct RawExtendedCommit {
+        #[prost(int64, tag = "1")]
+        height: i64,
+        #[prost(int32, tag = "2")]
+        round: i32,
+        #[prost(message, optional, tag = "3")]
+        block_id: Option<RawBlockId>,
+        #[prost(message, repeated, tag = "4")]
+        extended_signatures: Vec<RawExtendedCommitSig>,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct RawExtendedSignedHeader {
+        #[prost(message, optional, tag = "1")]
+        header: Option<RawTmHeader>,
+        #[prost(message, optional, tag = "2")]
+        commit: Option<RawExtendedCommit>,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct RawExtendedHeader {
+        #[prost(message, optional, tag = "1")]
+        signed_header: Option<RawExtendedSignedHeader>,
+        #[prost(message, optional, tag = "2")]
+        validator_set: Option<RawValidatorSet>,
+        #[prost(message, optional, tag = "3")]
+        trusted_height: Option<RawHeight>,
+        #[prost(message, optional, tag = "4")]
+        trusted_validators: Option<RawValidatorSet>,
+    }
+
+    #[test]
+    fn header_with_vote_extensions_verifies() {
+        let block =
+            HostBlock::generate_tm_block(ChainId::new("test".to_string(), 1), 5, Timestamp::now());
+        let raw = RawHeader::decode(
+            Any::from(HostBlock::SyntheticTendermint(block))
+                .value
+                .as_slice(),
+        )
+        .unwrap();
+        let header = Header::try_from(raw.clone()).unwrap();
+
+        let signed_header = raw.signed_header.unwrap();
+        let commit = signed_header.commit.unwrap();
+        let extended = RawExtendedHeader {
+            signed_header: Some(RawExtendedSignedHeader {
+                header: signed_header.header,
+                commit: Some(RawExtendedCommit {
+                    height: commit.height,
+                    round: commit.round,
+                    block_id: commit.block_id,
+                    extended_signatures: commit
+                        .signatures
+                        .into_iter()
+                        .map(|sig| RawExtendedCommitSig {
+                            block_id_flag: sig.block_id_flag,
+                            validator_address: sig.validator_address,
+                            timestamp: sig.timestamp,
+                            signature: sig.signature,
+                            extension: b"vote extension".to_vec(),
+                            extension_signature: vec![0xab; 64],
+                        })
+                        .collect(),
+                }),
+            }),
+            validator_set: raw.validator_set,
+            trusted_height: raw.trusted_height,
+            trusted_validators: raw.trusted_validators,
+        };
+
+        let decoded =
+            <Header as Protobuf<RawHeader>>::decode_vec(&extended.encode_to_vec()).unwrap();
+        assert!(decoded == header);
+        ProdVotingPowerCalculator
+            .check_signers_overlap(&decoded.signed_header, &decoded.validator_set)
+            .unwrap();
+    }
+}
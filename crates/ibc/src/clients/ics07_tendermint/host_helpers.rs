@@ -2,6 +2,7 @@ use core::time::Duration;
 
 use alloc::format;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use ibc_proto::google::protobuf::Any;
 
 use crate::clients::ics07_tendermint::client_state::ClientState as TmClientState;
@@ -13,89 +14,114 @@ use crate::Height;
 
 use tendermint::trust_threshold::TrustThresholdFraction as TendermintTrustThresholdFraction;
 
-/// Provides an implementation of `ConnectionReader::validate_self_client` for
-/// Tendermint-based hosts.
-pub trait ValidateSelfClientContext {
-    fn validate_self_client(&self, counterparty_client_state: Any) -> Result<(), Error> {
-        let counterparty_client_state = TmClientState::try_from(counterparty_client_state)
-            .map_err(|_| {
-                Error::invalid_client_state("client must be a tendermint client".to_string())
-            })?;
+/// The parameters of a Tendermint host that the clients of it, running on counterparty chains,
+/// must match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelfClientParams {
+    pub chain_id: ChainId,
+    pub proof_specs: ProofSpecs,
+    pub unbonding_period: Duration,
+    /// May be empty.
+    pub upgrade_path: Vec<String>,
+}
 
-        if counterparty_client_state.is_frozen() {
-            return Err(Error::invalid_client_state("client is frozen".to_string()));
-        }
+/// Validates `counterparty_client_state`, the state of a client of a Tendermint host running on a
+/// counterparty chain, against the `params` of the host at its current height `host_height`.
+pub fn validate_self_tendermint_client(
+    counterparty_client_state: Any,
+    host_height: Height,
+    params: &SelfClientParams,
+) -> Result<(), Error> {
+    let counterparty_client_state =
+        TmClientState::try_from(counterparty_client_state).map_err(|_| {
+            Error::invalid_client_state("client must be a tendermint client".to_string())
+        })?;
 
-        let self_chain_id = self.chain_id();
-        if self_chain_id != &counterparty_client_state.chain_id {
-            return Err(Error::invalid_client_state(format!(
-                "invalid chain-id. expected: {}, got: {}",
-                self_chain_id, counterparty_client_state.chain_id
-            )));
-        }
+    if counterparty_client_state.is_frozen() {
+        return Err(Error::invalid_client_state("client is frozen".to_string()));
+    }
 
-        let self_revision_number = self_chain_id.version();
-        if self_revision_number != counterparty_client_state.latest_height().revision_number() {
-            return Err(Error::invalid_client_state(format!(
-                "client is not in the same revision as the chain. expected: {}, got: {}",
-                self_revision_number,
-                counterparty_client_state.latest_height().revision_number()
-            )));
-        }
+    let self_chain_id = &params.chain_id;
+    if self_chain_id != &counterparty_client_state.chain_id {
+        return Err(Error::invalid_client_state(format!(
+            "invalid chain-id. expected: {}, got: {}",
+            self_chain_id, counterparty_client_state.chain_id
+        )));
+    }
 
-        if counterparty_client_state.latest_height() >= self.host_current_height() {
-            return Err(Error::invalid_client_state(format!(
-                "client has latest height {} greater than or equal to chain height {}",
-                counterparty_client_state.latest_height(),
-                self.host_current_height()
-            )));
-        }
+    let self_revision_number = self_chain_id.version();
+    if self_revision_number != counterparty_client_state.latest_height().revision_number() {
+        return Err(Error::invalid_client_state(format!(
+            "client is not in the same revision as the chain. expected: {}, got: {}",
+            self_revision_number,
+            counterparty_client_state.latest_height().revision_number()
+        )));
+    }
 
-        if self.proof_specs() != &counterparty_client_state.proof_specs {
-            return Err(Error::invalid_client_state(format!(
-                "client has invalid proof specs. expected: {:?}, got: {:?}",
-                self.proof_specs(),
-                counterparty_client_state.proof_specs
-            )));
-        }
+    if counterparty_client_state.latest_height() >= host_height {
+        return Err(Error::invalid_client_state(format!(
+            "client has latest height {} greater than or equal to chain height {}",
+            counterparty_client_state.latest_height(),
+            host_height
+        )));
+    }
+
+    if params.proof_specs != counterparty_client_state.proof_specs {
+        return Err(Error::invalid_client_state(format!(
+            "client has invalid proof specs. expected: {:?}, got: {:?}",
+            params.proof_specs, counterparty_client_state.proof_specs
+        )));
+    }
 
-        let _ = {
-            let trust_level = counterparty_client_state.trust_level;
+    let _ = {
+        let trust_level = counterparty_client_state.trust_level;
 
-            TendermintTrustThresholdFraction::new(
-                trust_level.numerator(),
-                trust_level.denominator(),
-            )
+        TendermintTrustThresholdFraction::new(trust_level.numerator(), trust_level.denominator())
             .map_err(|_| Error::invalid_client_state("invalid trust level".to_string()))?
-        };
+    };
 
-        if self.unbonding_period() != counterparty_client_state.unbonding_period {
-            return Err(Error::invalid_client_state(format!(
-                "invalid unbonding period. expected: {:?}, got: {:?}",
-                self.unbonding_period(),
-                counterparty_client_state.unbonding_period,
-            )));
-        }
+    if params.unbonding_period != counterparty_client_state.unbonding_period {
+        return Err(Error::invalid_client_state(format!(
+            "invalid unbonding period. expected: {:?}, got: {:?}",
+            params.unbonding_period, counterparty_client_state.unbonding_period,
+        )));
+    }
 
-        if counterparty_client_state.unbonding_period < counterparty_client_state.trusting_period {
-            return Err(Error::invalid_client_state(format!(
-                "unbonding period must be greater than trusting period. unbonding period ({:?}) < trusting period ({:?})",
-                counterparty_client_state.unbonding_period,
-                counterparty_client_state.trusting_period
-            )));
-        }
+    if counterparty_client_state.unbonding_period < counterparty_client_state.trusting_period {
+        return Err(Error::invalid_client_state(format!(
+            "unbonding period must be greater than trusting period. unbonding period ({:?}) < trusting period ({:?})",
+            counterparty_client_state.unbonding_period,
+            counterparty_client_state.trusting_period
+        )));
+    }
 
-        if !counterparty_client_state.upgrade_path.is_empty()
-            && self.upgrade_path() != counterparty_client_state.upgrade_path
-        {
-            return Err(Error::invalid_client_state(format!(
-                "invalid upgrade path. expected: {:?}, got: {:?}",
-                self.upgrade_path(),
-                counterparty_client_state.upgrade_path
-            )));
-        }
+    if !counterparty_client_state.upgrade_path.is_empty()
+        && params.upgrade_path != counterparty_client_state.upgrade_path
+    {
+        return Err(Error::invalid_client_state(format!(
+            "invalid upgrade path. expected: {:?}, got: {:?}",
+            params.upgrade_path, counterparty_client_state.upgrade_path
+        )));
+    }
 
-        Ok(())
+    Ok(())
+}
+
+/// Provides an implementation of `ConnectionReader::validate_self_client` for
+/// Tendermint-based hosts.
+pub trait ValidateSelfClientContext {
+    fn validate_self_client(&self, counterparty_client_state: Any) -> Result<(), Error> {
+        let params = SelfClientParams {
+            chain_id: self.chain_id().clone(),
+            proof_specs: self.proof_specs().clone(),
+            unbonding_period: self.unbonding_period(),
+            upgrade_path: self.upgrade_path().to_vec(),
+        };
+        validate_self_tendermint_client(
+            counterparty_client_state,
+            self.host_current_height(),
+            &params,
+        )
     }
 
     /// Returns the host chain id
@@ -113,3 +139,136 @@ pub trait ValidateSelfClientContext {
     /// Returns the host uprade path. May be empty.
     fn upgrade_path(&self) -> &[String];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    use crate::clients::ics07_tendermint::client_state::AllowUpdate;
+    use crate::core::ics02_client::trust_threshold::TrustThreshold;
+
+    fn params() -> SelfClientParams {
+        SelfClientParams {
+            chain_id: ChainId::new("gaia".to_string(), 1),
+            proof_specs: ProofSpecs::cosmos(),
+            unbonding_period: Duration::from_secs(128000),
+            upgrade_path: vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+        }
+    }
+
+    fn client_state(
+        chain_id: ChainId,
+        latest_height: Height,
+        proof_specs: ProofSpecs,
+        unbonding_period: Duration,
+    ) -> TmClientState {
+        TmClientState::new(
+            chain_id,
+            TrustThreshold::ONE_THIRD,
+            Duration::from_secs(64000),
+            unbonding_period,
+            Duration::from_millis(3000),
+            latest_height,
+            proof_specs,
+            Vec::new(),
+            AllowUpdate {
+                after_expiry: false,
+                after_misbehaviour: false,
+            },
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_self_tendermint_client() {
+        let params = params();
+        let host_height = Height::new(1, 20).unwrap();
+        let height = Height::new(1, 10).unwrap();
+        let unbonding_period = params.unbonding_period;
+
+        struct Test {
+            name: &'static str,
+            client_state: TmClientState,
+            want_pass: bool,
+        }
+
+        let tests = vec![
+            Test {
+                name: "Client of the host",
+                client_state: client_state(
+                    params.chain_id.clone(),
+                    height,
+                    ProofSpecs::cosmos(),
+                    unbonding_period,
+                ),
+                want_pass: true,
+            },
+            Test {
+                name: "Client of another chain",
+                client_state: client_state(
+                    ChainId::new("osmosis".to_string(), 1),
+                    height,
+                    ProofSpecs::cosmos(),
+                    unbonding_period,
+                ),
+                want_pass: false,
+            },
+            Test {
+                name: "Frozen client of the host",
+                client_state: client_state(
+                    params.chain_id.clone(),
+                    height,
+                    ProofSpecs::cosmos(),
+                    unbonding_period,
+                )
+                .with_frozen_height(height)
+                .unwrap(),
+                want_pass: false,
+            },
+            Test {
+                name: "Client at the height of the host",
+                client_state: client_state(
+                    params.chain_id.clone(),
+                    host_height,
+                    ProofSpecs::cosmos(),
+                    unbonding_period,
+                ),
+                want_pass: false,
+            },
+            Test {
+                name: "Client with other proof specs",
+                client_state: client_state(
+                    params.chain_id.clone(),
+                    height,
+                    vec![ics23::tendermint_spec()].into(),
+                    unbonding_period,
+                ),
+                want_pass: false,
+            },
+            Test {
+                name: "Client with another unbonding period",
+                client_state: client_state(
+                    params.chain_id.clone(),
+                    height,
+                    ProofSpecs::cosmos(),
+                    Duration::from_secs(100000),
+                ),
+                want_pass: false,
+            },
+        ];
+
+        for test in tests {
+            let res =
+                validate_self_tendermint_client(test.client_state.into(), host_height, &params);
+            assert_eq!(
+                res.is_ok(),
+                test.want_pass,
+                "validate_self_tendermint_client: test {} failed: {:?}",
+                test.name,
+                res.err()
+            );
+        }
+    }
+}
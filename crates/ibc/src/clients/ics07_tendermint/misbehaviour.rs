@@ -1,11 +1,13 @@
 use crate::prelude::*;
 
+use ibc_proto::google::protobuf::Any;
 use ibc_proto::ibc::lightclients::tendermint::v1::Misbehaviour as RawMisbehaviour;
 use ibc_proto::protobuf::Protobuf;
 use serde::{Deserialize, Serialize};
 
 use crate::clients::ics07_tendermint::error::Error;
 use crate::clients::ics07_tendermint::header::Header;
+use crate::core::ics02_client::error::Error as Ics02Error;
 use crate::core::ics24_host::identifier::ClientId;
 use crate::Height;
 
@@ -58,6 +60,35 @@ impl From<Misbehaviour> for RawMisbehaviour {
     }
 }
 
+impl Protobuf<Any> for Misbehaviour {}
+
+impl TryFrom<Any> for Misbehaviour {
+    type Error = Ics02Error;
+
+    fn try_from(raw: Any) -> Result<Self, Ics02Error> {
+        use core::ops::Deref;
+        use prost::Message;
+
+        match raw.type_url.as_str() {
+            TENDERMINT_MISBEHAVIOR_TYPE_URL => RawMisbehaviour::decode(raw.value.deref())
+                .map_err(Error::decode)?
+                .try_into()
+                .map_err(Into::into),
+            _ => Err(Ics02Error::unknown_misbehaviour_type(raw.type_url)),
+        }
+    }
+}
+
+impl From<Misbehaviour> for Any {
+    fn from(misbehaviour: Misbehaviour) -> Self {
+        Any {
+            type_url: TENDERMINT_MISBEHAVIOR_TYPE_URL.to_string(),
+            value: Protobuf::<RawMisbehaviour>::encode_vec(&misbehaviour)
+                .expect("encoding to `Any` from `TmMisbehaviour`"),
+        }
+    }
+}
+
 impl core::fmt::Display for Misbehaviour {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(
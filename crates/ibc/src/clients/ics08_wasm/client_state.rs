@@ -0,0 +1,808 @@
+use crate::prelude::*;
+
+use core::time::Duration;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+use ibc_proto::protobuf::Protobuf;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::clients::ics08_wasm::consensus_state::ConsensusState as WasmConsensusState;
+use crate::clients::ics08_wasm::error::Error;
+use crate::clients::ics08_wasm::header::Header as WasmHeader;
+use crate::clients::ics08_wasm::misbehaviour::Misbehaviour as WasmMisbehaviour;
+use crate::clients::ics08_wasm::raw::ClientState as RawClientState;
+use crate::clients::ics08_wasm::vm::WasmVmHost;
+use crate::core::ics02_client::client_state::{
+    ClientState as Ics2ClientState, UpdatedState, UpgradeOptions,
+};
+use crate::core::ics02_client::client_type::ClientType;
+use crate::core::ics02_client::consensus_state::{downcast_consensus_state, ConsensusState};
+use crate::core::ics02_client::context::ClientReader;
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::core::ics02_client::header::Header;
+use crate::core::ics03_connection::connection::ConnectionEnd;
+use crate::core::ics04_channel::channel::ChannelEnd;
+use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCommitment};
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::packet::{Receipt, Sequence};
+use crate::core::ics23_commitment::commitment::{
+    CommitmentPrefix, CommitmentProofBytes, CommitmentRoot,
+};
+use crate::core::ics23_commitment::merkle::apply_prefix;
+use crate::core::ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId};
+use crate::core::ics24_host::path::{
+    AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath, CommitmentsPath,
+    ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+};
+use crate::core::ics24_host::Path;
+use crate::Height;
+
+use super::client_type as wasm_client_type;
+
+pub const WASM_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ClientState";
+
+/// The state of a wasm client, whose verification is delegated to the contract of hash
+/// `code_hash`, executed by `vm`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClientState<H> {
+    /// The client state of the wrapped client, serialized by its contract.
+    pub data: Vec<u8>,
+    pub code_hash: Vec<u8>,
+    pub latest_height: Height,
+    pub frozen_height: Option<Height>,
+    #[serde(skip)]
+    pub vm: H,
+}
+
+impl<H: WasmVmHost> ClientState<H> {
+    pub fn new(
+        data: Vec<u8>,
+        code_hash: Vec<u8>,
+        latest_height: Height,
+        vm: H,
+    ) -> Result<Self, Error> {
+        if code_hash.is_empty() {
+            return Err(Error::empty_code_hash());
+        }
+
+        Ok(Self {
+            data,
+            code_hash,
+            latest_height,
+            frozen_height: None,
+            vm,
+        })
+    }
+
+    pub fn with_frozen_height(self, h: Height) -> Self {
+        Self {
+            frozen_height: Some(h),
+            ..self
+        }
+    }
+
+    /// Verify that the client is at a sufficient height and unfrozen at the given height
+    pub fn verify_height(&self, height: Height) -> Result<(), Error> {
+        if self.latest_height < height {
+            return Err(Error::insufficient_height(self.latest_height, height));
+        }
+
+        match self.frozen_height {
+            Some(frozen_height) if frozen_height <= height => {
+                Err(Error::client_frozen(frozen_height, height))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the consensus state of the client at its latest height, which the contract
+    /// verifies headers and misbehaviour against.
+    fn latest_consensus_state(
+        &self,
+        ctx: &dyn ClientReader,
+        client_id: &ClientId,
+    ) -> Result<WasmConsensusState, Ics02Error> {
+        let consensus_state = ctx.consensus_state(client_id, self.latest_height)?;
+        downcast_consensus_state::<WasmConsensusState>(consensus_state.as_ref())
+            .cloned()
+            .ok_or_else(|| Ics02Error::client_args_type_mismatch(wasm_client_type()))
+    }
+
+    fn verify_membership(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        path: impl Into<Path>,
+        value: Vec<u8>,
+    ) -> Result<(), Ics02Error> {
+        self.verify_height(height)?;
+
+        let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
+        self.vm
+            .verify_membership(
+                &self.code_hash,
+                &self.data,
+                root.as_bytes(),
+                height,
+                proof.as_bytes(),
+                &merkle_path,
+                &value,
+            )
+            .map_err(Into::into)
+    }
+
+    fn verify_non_membership(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        path: impl Into<Path>,
+    ) -> Result<(), Ics02Error> {
+        self.verify_height(height)?;
+
+        let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
+        self.vm
+            .verify_non_membership(
+                &self.code_hash,
+                &self.data,
+                root.as_bytes(),
+                height,
+                proof.as_bytes(),
+                &merkle_path,
+            )
+            .map_err(Into::into)
+    }
+}
+
+/// The VM handle is not part of the state.
+impl<H> PartialEq for ClientState<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+            && self.code_hash == other.code_hash
+            && self.latest_height == other.latest_height
+            && self.frozen_height == other.frozen_height
+    }
+}
+
+impl<H> Eq for ClientState<H> {}
+
+impl<H: WasmVmHost> Ics2ClientState for ClientState<H> {
+    fn chain_id(&self) -> ChainId {
+        self.vm.chain_id(&self.code_hash, &self.data)
+    }
+
+    fn client_type(&self) -> ClientType {
+        wasm_client_type()
+    }
+
+    fn latest_height(&self) -> Height {
+        self.latest_height
+    }
+
+    fn frozen_height(&self) -> Option<Height> {
+        self.frozen_height
+    }
+
+    /// Only the latest and frozen heights are upgraded, as the rest of the state is opaque to
+    /// the core.
+    fn upgrade(
+        &mut self,
+        upgrade_height: Height,
+        _upgrade_options: &dyn UpgradeOptions,
+        _chain_id: ChainId,
+    ) {
+        self.latest_height = upgrade_height;
+        self.frozen_height = None;
+    }
+
+    fn expired(&self, _elapsed: Duration) -> bool {
+        false
+    }
+
+    fn initialise(&self, consensus_state: Any) -> Result<Box<dyn ConsensusState>, Ics02Error> {
+        WasmConsensusState::try_from(consensus_state).map(WasmConsensusState::into_box)
+    }
+
+    fn decode_header(&self, header: Any) -> Result<Box<dyn Header>, Ics02Error> {
+        WasmHeader::try_from(header).map(WasmHeader::into_box)
+    }
+
+    fn check_header_and_update_state(
+        &self,
+        ctx: &dyn ClientReader,
+        client_id: ClientId,
+        header: Any,
+    ) -> Result<UpdatedState, Ics02Error> {
+        let header = WasmHeader::try_from(header)?;
+        let consensus_state = self.latest_consensus_state(ctx, &client_id)?;
+
+        let updated = self.vm.update_state(
+            &self.code_hash,
+            &self.data,
+            consensus_state.data.as_bytes(),
+            &header.data,
+        )?;
+
+        // The height and timestamp of the header are only trusted once the contract vouched
+        // for them, as the consensus state is stored at the former.
+        if header.height != updated.height {
+            return Err(Error::mismatched_header_height(header.height, updated.height).into());
+        }
+        if header.timestamp != updated.timestamp {
+            return Err(
+                Error::mismatched_header_timestamp(header.timestamp, updated.timestamp).into(),
+            );
+        }
+
+        let client_state = Self {
+            data: updated.client_state,
+            latest_height: self.latest_height.max(updated.height),
+            ..self.clone()
+        };
+        let consensus_state = WasmConsensusState::new(updated.consensus_state, updated.timestamp);
+
+        Ok(UpdatedState {
+            client_state: client_state.into_box(),
+            consensus_state: consensus_state.into_box(),
+        })
+    }
+
+    fn check_misbehaviour_and_update_state(
+        &self,
+        ctx: &dyn ClientReader,
+        client_id: ClientId,
+        misbehaviour: Any,
+    ) -> Result<Box<dyn Ics2ClientState>, Ics02Error> {
+        let misbehaviour = WasmMisbehaviour::try_from(misbehaviour)?;
+        let consensus_state = self.latest_consensus_state(ctx, &client_id)?;
+
+        let frozen = self.vm.check_misbehaviour(
+            &self.code_hash,
+            &self.data,
+            consensus_state.data.as_bytes(),
+            &misbehaviour.data,
+        )?;
+
+        let client_state = Self {
+            data: frozen.client_state,
+            ..self.clone()
+        }
+        .with_frozen_height(frozen.frozen_height);
+
+        Ok(client_state.into_box())
+    }
+
+    fn verify_upgrade_and_update_state(
+        &self,
+        _consensus_state: Any,
+        _proof_upgrade_client: RawMerkleProof,
+        _proof_upgrade_consensus_state: RawMerkleProof,
+    ) -> Result<UpdatedState, Ics02Error> {
+        Err(Error::upgrade_not_supported().into())
+    }
+
+    fn verify_client_consensus_state(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        client_id: &ClientId,
+        consensus_height: Height,
+        expected_consensus_state: &dyn ConsensusState,
+    ) -> Result<(), Ics02Error> {
+        let path = ClientConsensusStatePath::new(client_id.clone(), consensus_height);
+        let value = expected_consensus_state
+            .encode_vec()
+            .map_err(Ics02Error::invalid_any_consensus_state)?;
+
+        self.verify_membership(height, prefix, proof, root, path, value)
+    }
+
+    fn verify_connection_state(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        connection_id: &ConnectionId,
+        expected_connection_end: &ConnectionEnd,
+    ) -> Result<(), Ics02Error> {
+        let path = ConnectionsPath(connection_id.clone());
+        let value = expected_connection_end
+            .encode_vec()
+            .map_err(Ics02Error::invalid_connection_end)?;
+
+        self.verify_membership(height, prefix, proof, root, path, value)
+    }
+
+    fn verify_channel_state(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        expected_channel_end: &ChannelEnd,
+    ) -> Result<(), Ics02Error> {
+        let path = ChannelEndsPath(port_id.clone(), channel_id.clone());
+        let value = expected_channel_end
+            .encode_vec()
+            .map_err(Ics02Error::invalid_channel_end)?;
+
+        self.verify_membership(height, prefix, proof, root, path, value)
+    }
+
+    fn verify_client_full_state(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        client_id: &ClientId,
+        expected_client_state: Any,
+    ) -> Result<(), Ics02Error> {
+        let path = ClientStatePath(client_id.clone());
+        let value = expected_client_state.encode_to_vec();
+
+        self.verify_membership(height, prefix, proof, root, path, value)
+    }
+
+    fn verify_packet_data(
+        &self,
+        ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        commitment: PacketCommitment,
+    ) -> Result<(), Ics02Error> {
+        verify_delay_passed(ctx, height, connection_end)?;
+
+        let commitment_path = CommitmentsPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.verify_membership(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            root,
+            commitment_path,
+            commitment.into_vec(),
+        )
+    }
+
+    fn verify_packet_acknowledgement(
+        &self,
+        ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        ack_commitment: AcknowledgementCommitment,
+    ) -> Result<(), Ics02Error> {
+        verify_delay_passed(ctx, height, connection_end)?;
+
+        let ack_path = AcksPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.verify_membership(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            root,
+            ack_path,
+            ack_commitment.into_vec(),
+        )
+    }
+
+    fn verify_next_sequence_recv(
+        &self,
+        ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) -> Result<(), Ics02Error> {
+        verify_delay_passed(ctx, height, connection_end)?;
+
+        let mut seq_bytes = Vec::new();
+        u64::from(sequence)
+            .encode(&mut seq_bytes)
+            .expect("buffer size too small");
+
+        let seq_path = SeqRecvsPath(port_id.clone(), channel_id.clone());
+        self.verify_membership(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            root,
+            seq_path,
+            seq_bytes,
+        )
+    }
+
+    fn verify_packet_receipt_absence(
+        &self,
+        ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) -> Result<(), Ics02Error> {
+        verify_delay_passed(ctx, height, connection_end)?;
+
+        let receipt_path = ReceiptsPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.verify_non_membership(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            root,
+            receipt_path,
+        )
+    }
+
+    fn verify_packet_receipt(
+        &self,
+        ctx: &dyn ChannelReader,
+        height: Height,
+        connection_end: &ConnectionEnd,
+        proof: &CommitmentProofBytes,
+        root: &CommitmentRoot,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        receipt: &Receipt,
+    ) -> Result<(), Ics02Error> {
+        verify_delay_passed(ctx, height, connection_end)?;
+
+        let receipt_path = ReceiptsPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.verify_membership(
+            height,
+            connection_end.counterparty().prefix(),
+            proof,
+            root,
+            receipt_path,
+            receipt.as_bytes().to_vec(),
+        )
+    }
+}
+
+fn verify_delay_passed(
+    ctx: &dyn ChannelReader,
+    height: Height,
+    connection_end: &ConnectionEnd,
+) -> Result<(), Error> {
+    // Without a delay period, the proof is usable as soon as its consensus state was processed,
+    // so hosts need not record when that was.
+    if connection_end.delay_period().is_zero() {
+        return Ok(());
+    }
+
+    let current_timestamp = ctx.host_timestamp();
+    let current_height = ctx.host_height();
+
+    let client_id = connection_end.client_id();
+    let processed_time = ctx
+        .client_update_time(client_id, height)
+        .map_err(|_| Error::processed_time_not_found(client_id.clone(), height))?;
+    let processed_height = ctx
+        .client_update_height(client_id, height)
+        .map_err(|_| Error::processed_height_not_found(client_id.clone(), height))?;
+
+    let delay_period_time = connection_end.delay_period();
+    let earliest_time = (processed_time + delay_period_time).map_err(Error::timestamp_overflow)?;
+    if !(current_timestamp == earliest_time || current_timestamp.after(&earliest_time)) {
+        return Err(Error::not_enough_time_elapsed(
+            current_timestamp,
+            earliest_time,
+        ));
+    }
+
+    let earliest_height = processed_height.add(ctx.block_delay(delay_period_time));
+    if current_height < earliest_height {
+        return Err(Error::not_enough_blocks_elapsed(
+            current_height,
+            earliest_height,
+        ));
+    }
+
+    Ok(())
+}
+
+impl<H: WasmVmHost> Protobuf<RawClientState> for ClientState<H> {}
+
+impl<H: WasmVmHost> TryFrom<RawClientState> for ClientState<H> {
+    type Error = Error;
+
+    fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
+        let latest_height = raw
+            .latest_height
+            .ok_or_else(|| Error::invalid_raw_client_state("missing latest height".into()))?
+            .try_into()
+            .map_err(|e: Ics02Error| Error::invalid_raw_client_state(e.to_string()))?;
+
+        let frozen_height = raw
+            .frozen_height
+            .map(Height::try_from)
+            .transpose()
+            .map_err(|e: Ics02Error| Error::invalid_raw_client_state(e.to_string()))?;
+
+        let client_state = Self::new(raw.data, raw.code_hash, latest_height, H::default())?;
+        Ok(Self {
+            frozen_height,
+            ..client_state
+        })
+    }
+}
+
+impl<H> From<ClientState<H>> for RawClientState {
+    fn from(value: ClientState<H>) -> Self {
+        Self {
+            data: value.data,
+            code_hash: value.code_hash,
+            latest_height: Some(value.latest_height.into()),
+            frozen_height: value.frozen_height.map(Into::into),
+        }
+    }
+}
+
+impl<H: WasmVmHost> Protobuf<Any> for ClientState<H> {}
+
+impl<H: WasmVmHost> TryFrom<Any> for ClientState<H> {
+    type Error = Ics02Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        use core::ops::Deref;
+
+        match raw.type_url.as_str() {
+            WASM_CLIENT_STATE_TYPE_URL => RawClientState::decode(raw.value.deref())
+                .map_err(Error::decode)?
+                .try_into()
+                .map_err(Into::into),
+            _ => Err(Ics02Error::unknown_client_state_type(raw.type_url)),
+        }
+    }
+}
+
+impl<H: WasmVmHost> From<ClientState<H>> for Any {
+    fn from(client_state: ClientState<H>) -> Self {
+        Any {
+            type_url: WASM_CLIENT_STATE_TYPE_URL.to_string(),
+            value: Protobuf::<RawClientState>::encode_vec(&client_state)
+                .expect("encoding to `Any` from `WasmClientState`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_proto::ibc::core::commitment::v1::MerklePath;
+
+    use crate::clients::ics08_wasm::vm::{FrozenWasmState, UpdatedWasmState};
+    use crate::core::ics02_client::handler::{dispatch, ClientResult};
+    use crate::core::ics02_client::msgs::misbehaviour::MsgSubmitMisbehaviour;
+    use crate::core::ics02_client::msgs::update_client::MsgUpdateClient;
+    use crate::core::ics02_client::msgs::ClientMsg;
+    use crate::mock::client_state::MockClientRecord;
+    use crate::mock::context::MockContext;
+    use crate::test_utils::get_dummy_account_id;
+    use crate::timestamp::Timestamp;
+
+    /// Updates the client to the state carried by the header, whose first two bytes are the
+    /// revision height and timestamp of its consensus state, freezes it at the revision height
+    /// in the first byte of misbehaviour, and accepts the proofs equal to the proven value (or
+    /// empty for absence).
+    #[derive(Clone, Debug, Default)]
+    struct EchoVm;
+
+    impl WasmVmHost for EchoVm {
+        fn chain_id(&self, _code_hash: &[u8], _client_state: &[u8]) -> ChainId {
+            ChainId::new("grandpa".to_string(), 0)
+        }
+
+        fn update_state(
+            &self,
+            _code_hash: &[u8],
+            _client_state: &[u8],
+            _consensus_state: &[u8],
+            header: &[u8],
+        ) -> Result<UpdatedWasmState, Error> {
+            Ok(UpdatedWasmState {
+                client_state: header.to_vec(),
+                consensus_state: header.iter().rev().copied().collect(),
+                height: Height::new(1, header[0].into()).unwrap(),
+                timestamp: Timestamp::from_nanoseconds(header[1].into()).unwrap(),
+            })
+        }
+
+        fn check_misbehaviour(
+            &self,
+            _code_hash: &[u8],
+            _client_state: &[u8],
+            _consensus_state: &[u8],
+            misbehaviour: &[u8],
+        ) -> Result<FrozenWasmState, Error> {
+            Ok(FrozenWasmState {
+                client_state: misbehaviour.to_vec(),
+                frozen_height: Height::new(1, misbehaviour[0].into()).unwrap(),
+            })
+        }
+
+        fn verify_membership(
+            &self,
+            _code_hash: &[u8],
+            _client_state: &[u8],
+            _consensus_state: &[u8],
+            _height: Height,
+            proof: &[u8],
+            _path: &MerklePath,
+            value: &[u8],
+        ) -> Result<(), Error> {
+            if proof != value {
+                return Err(Error::vm("invalid proof".to_string()));
+            }
+            Ok(())
+        }
+
+        fn verify_non_membership(
+            &self,
+            _code_hash: &[u8],
+            _client_state: &[u8],
+            _consensus_state: &[u8],
+            _height: Height,
+            _proof: &[u8],
+            _path: &MerklePath,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    fn client_state(latest_height: Height) -> ClientState<EchoVm> {
+        ClientState::new(vec![1, 2, 3], vec![0xab; 32], latest_height, EchoVm).unwrap()
+    }
+
+    #[test]
+    fn client_state_any_round_trip() {
+        let client_state = client_state(Height::new(1, 10).unwrap());
+
+        let any = Any::from(client_state.clone());
+        assert_eq!(any.type_url, WASM_CLIENT_STATE_TYPE_URL);
+        assert_eq!(ClientState::<EchoVm>::try_from(any).unwrap(), client_state);
+
+        assert!(ClientState::new(vec![], vec![], Height::new(1, 10).unwrap(), EchoVm).is_err());
+    }
+
+    fn wasm_ctx(client_id: &ClientId, latest_height: Height) -> MockContext {
+        let ctx = MockContext::default();
+        ctx.ibc_store.lock().unwrap().clients.insert(
+            client_id.clone(),
+            MockClientRecord {
+                client_type: wasm_client_type(),
+                client_state: Some(client_state(latest_height).into_box()),
+                encoded_client_state: None,
+                consensus_states: [(
+                    latest_height,
+                    WasmConsensusState::new(vec![4, 5], Timestamp::from_nanoseconds(1).unwrap())
+                        .into_box(),
+                )]
+                .into_iter()
+                .collect(),
+            },
+        );
+        ctx
+    }
+
+    #[test]
+    fn update_runs_the_contract() {
+        let client_id = ClientId::new(wasm_client_type(), 0).unwrap();
+        let ctx = wasm_ctx(&client_id, Height::new(1, 10).unwrap());
+
+        let height = Height::new(1, 12).unwrap();
+        let update = |height: Height| MsgUpdateClient {
+            client_id: client_id.clone(),
+            header: WasmHeader {
+                data: vec![12, 2, 9],
+                height,
+                timestamp: Timestamp::from_nanoseconds(2).unwrap(),
+            }
+            .into(),
+            signer: get_dummy_account_id(),
+        };
+
+        let res = match dispatch(&ctx, ClientMsg::UpdateClient(update(height)))
+            .unwrap()
+            .result
+        {
+            ClientResult::Update(res) => res,
+            _ => panic!("update handler result has incorrect type"),
+        };
+        assert_eq!(res.consensus_heights, vec![height]);
+        let updated = res
+            .client_state
+            .as_any()
+            .downcast_ref::<ClientState<EchoVm>>()
+            .unwrap();
+        assert_eq!(updated.data, vec![12, 2, 9]);
+        assert_eq!(updated.latest_height, height);
+        assert_eq!(res.consensus_state.root().as_bytes(), [9, 2, 12].as_slice());
+        assert_eq!(
+            res.consensus_state.timestamp(),
+            Timestamp::from_nanoseconds(2).unwrap()
+        );
+
+        // The header may not claim another height than the contract's.
+        let msg = update(Height::new(1, 20).unwrap());
+        assert!(dispatch(&ctx, ClientMsg::UpdateClient(msg)).is_err());
+    }
+
+    #[test]
+    fn misbehaviour_is_checked_by_the_contract() {
+        let client_id = ClientId::new(wasm_client_type(), 0).unwrap();
+        let ctx = wasm_ctx(&client_id, Height::new(1, 10).unwrap());
+
+        let msg = MsgSubmitMisbehaviour {
+            client_id,
+            misbehaviour: WasmMisbehaviour { data: vec![8, 1] }.into(),
+            signer: get_dummy_account_id(),
+        };
+        let res = match dispatch(&ctx, ClientMsg::Misbehaviour(msg)).unwrap().result {
+            ClientResult::Misbehaviour(res) => res,
+            _ => panic!("misbehaviour handler result has incorrect type"),
+        };
+        let frozen = res
+            .client_state
+            .as_any()
+            .downcast_ref::<ClientState<EchoVm>>()
+            .unwrap();
+        assert_eq!(frozen.data, vec![8, 1]);
+        assert_eq!(frozen.frozen_height, Some(Height::new(1, 8).unwrap()));
+
+        assert!(frozen.verify_height(Height::new(1, 7).unwrap()).is_ok());
+        assert!(frozen.verify_height(Height::new(1, 8).unwrap()).is_err());
+
+        let any = Any::from(frozen.clone());
+        assert_eq!(&ClientState::<EchoVm>::try_from(any).unwrap(), frozen);
+    }
+
+    #[test]
+    fn membership_is_verified_by_the_contract() {
+        let client_state = client_state(Height::new(1, 10).unwrap());
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+        let root = CommitmentRoot::from_bytes(&[4, 5]);
+        let connection_id = ConnectionId::default();
+        let connection_end = ConnectionEnd::default();
+
+        let value = connection_end.encode_vec().unwrap();
+        let verify = |height: Height, proof: Vec<u8>| {
+            client_state.verify_connection_state(
+                height,
+                &prefix,
+                &proof.try_into().unwrap(),
+                &root,
+                &connection_id,
+                &connection_end,
+            )
+        };
+
+        assert!(verify(Height::new(1, 10).unwrap(), value.clone()).is_ok());
+        assert!(verify(Height::new(1, 10).unwrap(), vec![1]).is_err());
+        assert!(verify(Height::new(1, 11).unwrap(), value).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::prelude::*;
+
+use flex_error::{define_error, TraceError};
+
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::core::ics24_host::identifier::ClientId;
+use crate::timestamp::{Timestamp, TimestampOverflowError};
+use crate::Height;
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        InvalidRawClientState
+            { reason: String }
+            |e| { format_args!("invalid raw client state: {}", e.reason) },
+
+        InvalidRawConsensusState
+            { reason: String }
+            |e| { format_args!("invalid raw consensus state: {}", e.reason) },
+
+        InvalidRawHeader
+            { reason: String }
+            |e| { format_args!("invalid raw header: {}", e.reason) },
+
+        InvalidRawMisbehaviour
+            { reason: String }
+            |e| { format_args!("invalid raw misbehaviour: {}", e.reason) },
+
+        Decode
+            [ TraceError<prost::DecodeError> ]
+            |_| { "decode error" },
+
+        EmptyCodeHash
+            |_| { "the hash of the wasm code must not be empty" },
+
+        InsufficientHeight
+            {
+                latest_height: Height,
+                target_height: Height,
+            }
+            |e| {
+                format_args!("the height is insufficient: latest_height={0} target_height={1}",
+                    e.latest_height, e.target_height)
+            },
+
+        ClientFrozen
+            {
+                frozen_height: Height,
+                target_height: Height,
+            }
+            |e| {
+                format_args!("the client is frozen: frozen_height={0} target_height={1}",
+                    e.frozen_height, e.target_height)
+            },
+
+        MismatchedHeaderHeight
+            {
+                header_height: Height,
+                height: Height,
+            }
+            |e| {
+                format_args!("the header claims height {0}, but its consensus state is at height {1}",
+                    e.header_height, e.height)
+            },
+
+        MismatchedHeaderTimestamp
+            {
+                header_timestamp: Timestamp,
+                timestamp: Timestamp,
+            }
+            |e| {
+                format_args!("the header claims timestamp {0}, but its consensus state has timestamp {1}",
+                    e.header_timestamp, e.timestamp)
+            },
+
+        TimestampOverflow
+            [ TimestampOverflowError ]
+            |_| { "timestamp overflowed" },
+
+        NotEnoughTimeElapsed
+            {
+                current_time: Timestamp,
+                earliest_time: Timestamp,
+            }
+            |e| {
+                format_args!("not enough time elapsed, current timestamp {0} is still less than earliest acceptable timestamp {1}",
+                    e.current_time, e.earliest_time)
+            },
+
+        NotEnoughBlocksElapsed
+            {
+                current_height: Height,
+                earliest_height: Height,
+            }
+            |e| {
+                format_args!("not enough blocks elapsed, current height {0} is still less than earliest acceptable height {1}",
+                    e.current_height, e.earliest_height)
+            },
+
+        ProcessedTimeNotFound
+            {
+                client_id: ClientId,
+                height: Height,
+            }
+            |e| {
+                format_args!("processed time for the client {0} at height {1} not found",
+                    e.client_id, e.height)
+            },
+
+        ProcessedHeightNotFound
+            {
+                client_id: ClientId,
+                height: Height,
+            }
+            |e| {
+                format_args!("processed height for the client {0} at height {1} not found",
+                    e.client_id, e.height)
+            },
+
+        Vm
+            { reason: String }
+            |e| { format_args!("wasm contract execution failed: {}", e.reason) },
+
+        UpgradeNotSupported
+            |_| { "upgrades of wasm clients are not supported" },
+    }
+}
+
+impl From<Error> for Ics02Error {
+    fn from(e: Error) -> Self {
+        Self::client_specific(e.to_string())
+    }
+}
@@ -0,0 +1,99 @@
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::protobuf::Protobuf;
+use serde::{Deserialize, Serialize};
+
+use crate::clients::ics08_wasm::error::Error;
+use crate::clients::ics08_wasm::raw::Header as RawHeader;
+use crate::core::ics02_client::client_type::ClientType;
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::timestamp::Timestamp;
+use crate::Height;
+
+use super::client_type as wasm_client_type;
+
+pub const WASM_HEADER_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.Header";
+
+/// A header of the wrapped client, along with the height and timestamp of the consensus state
+/// it produces, which the core cannot read from the opaque data. Updates are rejected unless
+/// the contract produced a consensus state at that height and timestamp.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    /// The header of the wrapped client, serialized by its contract.
+    pub data: Vec<u8>,
+    pub height: Height,
+    pub timestamp: Timestamp,
+}
+
+impl crate::core::ics02_client::header::Header for Header {
+    fn client_type(&self) -> ClientType {
+        wasm_client_type()
+    }
+
+    fn height(&self) -> Height {
+        self.height
+    }
+
+    fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+}
+
+impl Protobuf<RawHeader> for Header {}
+
+impl TryFrom<RawHeader> for Header {
+    type Error = Error;
+
+    fn try_from(raw: RawHeader) -> Result<Self, Self::Error> {
+        Ok(Self {
+            data: raw.data,
+            height: raw
+                .height
+                .ok_or_else(|| Error::invalid_raw_header("missing height".into()))?
+                .try_into()
+                .map_err(|e: Ics02Error| Error::invalid_raw_header(e.to_string()))?,
+            timestamp: Timestamp::from_nanoseconds(raw.timestamp)
+                .map_err(|e| Error::invalid_raw_header(e.to_string()))?,
+        })
+    }
+}
+
+impl From<Header> for RawHeader {
+    fn from(value: Header) -> Self {
+        Self {
+            data: value.data,
+            height: Some(value.height.into()),
+            timestamp: value.timestamp.nanoseconds(),
+        }
+    }
+}
+
+impl Protobuf<Any> for Header {}
+
+impl TryFrom<Any> for Header {
+    type Error = Ics02Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        use core::ops::Deref;
+        use prost::Message;
+
+        match raw.type_url.as_str() {
+            WASM_HEADER_TYPE_URL => RawHeader::decode(raw.value.deref())
+                .map_err(Error::decode)?
+                .try_into()
+                .map_err(Into::into),
+            _ => Err(Ics02Error::unknown_header_type(raw.type_url)),
+        }
+    }
+}
+
+impl From<Header> for Any {
+    fn from(header: Header) -> Self {
+        Any {
+            type_url: WASM_HEADER_TYPE_URL.to_string(),
+            value: Protobuf::<RawHeader>::encode_vec(&header)
+                .expect("encoding to `Any` from `WasmHeader`"),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::protobuf::Protobuf;
+use serde::{Deserialize, Serialize};
+
+use crate::clients::ics08_wasm::error::Error;
+use crate::clients::ics08_wasm::raw::Misbehaviour as RawMisbehaviour;
+use crate::core::ics02_client::error::Error as Ics02Error;
+
+pub const WASM_MISBEHAVIOUR_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.Misbehaviour";
+
+/// Evidence of misbehaviour of the chain tracked by the wrapped client, verified by its contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Misbehaviour {
+    /// The misbehaviour of the wrapped client, serialized by its contract.
+    pub data: Vec<u8>,
+}
+
+impl Protobuf<RawMisbehaviour> for Misbehaviour {}
+
+impl TryFrom<RawMisbehaviour> for Misbehaviour {
+    type Error = Error;
+
+    fn try_from(raw: RawMisbehaviour) -> Result<Self, Self::Error> {
+        if raw.data.is_empty() {
+            return Err(Error::invalid_raw_misbehaviour("empty data".into()));
+        }
+
+        Ok(Self { data: raw.data })
+    }
+}
+
+impl From<Misbehaviour> for RawMisbehaviour {
+    fn from(value: Misbehaviour) -> Self {
+        Self { data: value.data }
+    }
+}
+
+impl Protobuf<Any> for Misbehaviour {}
+
+impl TryFrom<Any> for Misbehaviour {
+    type Error = Ics02Error;
+
+    fn try_from(raw: Any) -> Result<Self, Self::Error> {
+        use core::ops::Deref;
+        use prost::Message;
+
+        match raw.type_url.as_str() {
+            WASM_MISBEHAVIOUR_TYPE_URL => RawMisbehaviour::decode(raw.value.deref())
+                .map_err(Error::decode)?
+                .try_into()
+                .map_err(Into::into),
+            _ => Err(Ics02Error::unknown_misbehaviour_type(raw.type_url)),
+        }
+    }
+}
+
+impl From<Misbehaviour> for Any {
+    fn from(misbehaviour: Misbehaviour) -> Self {
+        Any {
+            type_url: WASM_MISBEHAVIOUR_TYPE_URL.to_string(),
+            value: Protobuf::<RawMisbehaviour>::encode_vec(&misbehaviour)
+                .expect("encoding to `Any` from `WasmMisbehaviour`"),
+        }
+    }
+}
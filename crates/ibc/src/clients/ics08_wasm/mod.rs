@@ -0,0 +1,26 @@
+//! ICS 08: Wasm Client wraps light clients compiled to wasm, e.g. Grandpa or Beefy clients,
+//! whose verification algorithms are executed by the host through [`WasmVmHost`].
+//!
+//! The states and headers of the wrapped client are opaque to the core: the wasm client only
+//! records the hash of the contract code along with the serialized inner states, and hands them
+//! to the contract for verification.
+
+use alloc::string::ToString;
+
+use crate::core::ics02_client::client_type::ClientType;
+
+pub mod client_state;
+pub mod consensus_state;
+pub mod error;
+pub mod header;
+pub mod misbehaviour;
+pub mod raw;
+pub mod vm;
+
+pub use vm::{FrozenWasmState, UpdatedWasmState, WasmVmHost};
+
+pub(crate) const WASM_CLIENT_TYPE: &str = "08-wasm";
+
+pub fn client_type() -> ClientType {
+    ClientType::new(WASM_CLIENT_TYPE.to_string())
+}
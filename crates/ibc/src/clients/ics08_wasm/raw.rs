@@ -0,0 +1,41 @@
+//! Protobuf definitions of the wasm client, which `ibc-proto` does not provide yet.
+
+use crate::prelude::*;
+
+use ibc_proto::ibc::core::client::v1::Height as RawHeight;
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientState {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub code_hash: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub latest_height: Option<RawHeight>,
+    #[prost(message, optional, tag = "4")]
+    pub frozen_height: Option<RawHeight>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConsensusState {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub timestamp: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Header {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub height: Option<RawHeight>,
+    #[prost(uint64, tag = "3")]
+    pub timestamp: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Misbehaviour {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+}
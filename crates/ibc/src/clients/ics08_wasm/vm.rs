@@ -0,0 +1,86 @@
+use crate::prelude::*;
+
+use core::fmt::Debug;
+
+use ibc_proto::ibc::core::commitment::v1::MerklePath;
+
+use crate::clients::ics08_wasm::error::Error;
+use crate::core::ics24_host::identifier::ChainId;
+use crate::timestamp::Timestamp;
+use crate::Height;
+
+/// The states of a wasm client after an update, serialized by its contract, along with the
+/// height and timestamp of the consensus state it produced, which the core cannot read from the
+/// opaque data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdatedWasmState {
+    pub client_state: Vec<u8>,
+    pub consensus_state: Vec<u8>,
+    pub height: Height,
+    pub timestamp: Timestamp,
+}
+
+/// The client state of a wasm client frozen by misbehaviour, serialized by its contract, along
+/// with the height from which proofs are rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrozenWasmState {
+    pub client_state: Vec<u8>,
+    pub frozen_height: Height,
+}
+
+/// Executes the entry points of the light client contracts on behalf of the wasm client.
+///
+/// Contracts are identified by the hash of their code, and receive the inner states of the client
+/// as serialized by themselves. Implementations are typically handles to the wasm VM of the host,
+/// which is why they are built through `Default` when decoding client states.
+pub trait WasmVmHost: Clone + Debug + Default + Send + Sync + 'static {
+    /// Returns the identifier of the chain tracked by the client.
+    fn chain_id(&self, code_hash: &[u8], client_state: &[u8]) -> ChainId;
+
+    /// Verifies `header` against the client state and its consensus state at the latest height,
+    /// and returns the states of the client after applying it.
+    fn update_state(
+        &self,
+        code_hash: &[u8],
+        client_state: &[u8],
+        consensus_state: &[u8],
+        header: &[u8],
+    ) -> Result<UpdatedWasmState, Error>;
+
+    /// Verifies that `misbehaviour` is valid evidence of misbehaviour of the chain tracked by the
+    /// client, against its consensus state at the latest height, and returns the client state
+    /// frozen accordingly.
+    fn check_misbehaviour(
+        &self,
+        code_hash: &[u8],
+        client_state: &[u8],
+        consensus_state: &[u8],
+        misbehaviour: &[u8],
+    ) -> Result<FrozenWasmState, Error>;
+
+    /// Verifies that `value` is stored under `path` on the counterparty chain, as proven by
+    /// `proof` against the consensus state at `height`.
+    #[allow(clippy::too_many_arguments)]
+    fn verify_membership(
+        &self,
+        code_hash: &[u8],
+        client_state: &[u8],
+        consensus_state: &[u8],
+        height: Height,
+        proof: &[u8],
+        path: &MerklePath,
+        value: &[u8],
+    ) -> Result<(), Error>;
+
+    /// Verifies that nothing is stored under `path` on the counterparty chain, as proven by
+    /// `proof` against the consensus state at `height`.
+    fn verify_non_membership(
+        &self,
+        code_hash: &[u8],
+        client_state: &[u8],
+        consensus_state: &[u8],
+        height: Height,
+        proof: &[u8],
+        path: &MerklePath,
+    ) -> Result<(), Error>;
+}
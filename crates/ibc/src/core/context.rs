@@ -0,0 +1,105 @@
+//! The two contexts through which a host exposes its store to the IBC handlers.
+//!
+//! A [`ValidationContext`] only reads the store: it is sufficient to
+//! [`validate`](crate::core::ics26_routing::handler::validate) messages, e.g. when checking
+//! transactions before they are included in a block (`CheckTx`). An [`ExecutionContext`]
+//! additionally writes to the store, to
+//! [`execute`](crate::core::ics26_routing::handler::execute) messages, e.g. when delivering the
+//! transactions of a block (`DeliverTx`).
+//!
+//! Both are made of the readers and keepers of the individual ICS modules, which hosts implement
+//! along with them.
+
+use crate::prelude::*;
+
+use crate::core::ics02_client::context::{ClientKeeper, ClientReader};
+use crate::core::ics03_connection::context::{ConnectionKeeper, ConnectionReader};
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics04_channel::error::Error as ChannelError;
+use crate::core::ics04_channel::pause;
+use crate::core::ics04_channel::Version;
+use crate::core::ics05_port::context::PortReader;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::core::ics26_routing::context::{ModuleId, Router};
+use crate::events::bus::EventBus;
+use crate::handler::HandlerOutput;
+
+/// Read-only access to the host's store and to the modules it routes messages to, as required to
+/// validate any IBC message.
+///
+/// All its methods take `&self`, so that a `Sync` context, e.g. a snapshot of the host's store,
+/// may be shared by threads validating messages or serving queries while a block is executed.
+pub trait ValidationContext: ClientReader + ConnectionReader + ChannelReader + PortReader {
+    type Router: Router;
+
+    fn router(&self) -> &Self::Router;
+
+    /// Returns the `ModuleId` of the registered module bound to the specified port, if any
+    fn module_for_port(&self, port_id: &PortId) -> Option<ModuleId> {
+        self.lookup_module_by_port(port_id)
+            .ok()
+            .filter(|module_id| self.router().has_route(module_id))
+    }
+
+    /// Returns the channel versions supported by the module bound to the specified port, the
+    /// first of which is its default, or none if the module negotiates versions itself
+    fn supported_versions(&self, port_id: &PortId) -> Vec<Version> {
+        self.module_for_port(port_id)
+            .map(|module_id| self.router().supported_versions(&module_id, port_id))
+            .unwrap_or_default()
+    }
+
+    /// Returns the ports bound to the specified module, in ascending order
+    fn ports_for_module(&self, module_id: &ModuleId) -> Vec<PortId> {
+        let mut port_ids: Vec<PortId> = self
+            .port_bindings()
+            .into_iter()
+            .filter(|(_, bound_module_id)| bound_module_id == module_id)
+            .map(|(port_id, _)| port_id)
+            .collect();
+        port_ids.sort();
+        port_ids
+    }
+}
+
+/// Read and write access to the host's store and to the modules it routes messages to, as
+/// required to execute any IBC message.
+pub trait ExecutionContext:
+    ValidationContext + ClientKeeper + ConnectionKeeper + ChannelKeeper
+{
+    fn router_mut(&mut self) -> &mut Self::Router;
+
+    /// The bus to which `dispatch` publishes the events of the messages it executes, if any
+    fn event_bus(&self) -> Option<&dyn EventBus> {
+        None
+    }
+
+    /// Pauses a channel, e.g. on a governance decision, until it is resumed: it then neither
+    /// sends nor receives packets, see [`pause`]. Returns the log and the `ChannelPaused` event,
+    /// which is also published to the event bus, if any.
+    fn pause_channel(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<HandlerOutput<()>, ChannelError> {
+        let output = pause::pause_channel(self, port_id, channel_id)?;
+        if let Some(bus) = self.event_bus() {
+            output.events.iter().for_each(|event| bus.publish(event));
+        }
+        Ok(output)
+    }
+
+    /// Resumes a channel paused via [`pause_channel`](Self::pause_channel). Returns the log and
+    /// the `ChannelResumed` event, which is also published to the event bus, if any.
+    fn resume_channel(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<HandlerOutput<()>, ChannelError> {
+        let output = pause::resume_channel(self, port_id, channel_id)?;
+        if let Some(bus) = self.event_bus() {
+            output.events.iter().for_each(|event| bus.publish(event));
+        }
+        Ok(output)
+    }
+}
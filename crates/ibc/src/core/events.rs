@@ -0,0 +1,302 @@
+//! The keys of the attributes of the IBC events, and the parsing of the events back from their
+//! ABCI form.
+//!
+//! Each IBC event converts back from the [`AbciEvent`] it is emitted as (e.g. with
+//! `SendPacket::try_from(abci_event)`), and [`parse_event`] parses any of them, so that indexing
+//! services can reconstruct the [`Packet`](crate::core::ics04_channel::packet::Packet)s and the
+//! other values of the events emitted by a chain.
+
+use core::str::FromStr;
+
+use subtle_encoding::base64;
+use tendermint::abci::tag::Tag;
+use tendermint::abci::Event as AbciEvent;
+
+use crate::events::{Error as EventError, IbcEvent, IbcEventType};
+use crate::prelude::*;
+
+pub use crate::core::ics02_client::events::{
+    CLIENT_ID_ATTRIBUTE_KEY, CLIENT_TYPE_ATTRIBUTE_KEY, CONSENSUS_HEIGHTS_ATTRIBUTE_KEY,
+    CONSENSUS_HEIGHT_ATTRIBUTE_KEY, HEADER_ATTRIBUTE_KEY, HEIGHT_GAP_ATTRIBUTE_KEY,
+    TIME_GAP_ATTRIBUTE_KEY,
+};
+pub use crate::core::ics03_connection::events::{
+    CONN_ID_ATTRIBUTE_KEY, COUNTERPARTY_CLIENT_ID_ATTRIBUTE_KEY,
+    COUNTERPARTY_CONN_ID_ATTRIBUTE_KEY, DELAY_PERIOD_ATTRIBUTE_KEY,
+};
+pub use crate::core::ics04_channel::events::{
+    CHANNEL_ID_ATTRIBUTE_KEY, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY,
+    COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY, PKT_ACK_ATTRIBUTE_KEY, PKT_ACK_HEX_ATTRIBUTE_KEY,
+    PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY, PKT_CONNECTION_ID_ATTRIBUTE_KEY, PKT_DATA_ATTRIBUTE_KEY,
+    PKT_DATA_HEX_ATTRIBUTE_KEY, PKT_DST_CHANNEL_ATTRIBUTE_KEY, PKT_DST_PORT_ATTRIBUTE_KEY,
+    PKT_RELAYER_ATTRIBUTE_KEY, PKT_SEQ_ATTRIBUTE_KEY, PKT_SRC_CHANNEL_ATTRIBUTE_KEY,
+    PKT_SRC_PORT_ATTRIBUTE_KEY, PKT_TIMEOUT_HEIGHT_ATTRIBUTE_KEY,
+    PKT_TIMEOUT_TIMESTAMP_ATTRIBUTE_KEY, PORT_ID_ATTRIBUTE_KEY,
+    UPGRADE_CONNECTION_HOPS_ATTRIBUTE_KEY, UPGRADE_ERROR_RECEIPT_ATTRIBUTE_KEY,
+    UPGRADE_ORDERING_ATTRIBUTE_KEY, UPGRADE_SEQUENCE_ATTRIBUTE_KEY, UPGRADE_VERSION_ATTRIBUTE_KEY,
+    VERSION_ATTRIBUTE_KEY,
+};
+pub use crate::core::ics26_routing::handler::MSG_INDEX_ATTRIBUTE_KEY;
+pub use crate::events::REASON_ATTRIBUTE_KEY;
+
+/// Returns the value of the attribute of `event` with the given key, if any.
+pub fn find_attribute<'a>(event: &'a AbciEvent, key: &str) -> Option<&'a str> {
+    event
+        .attributes
+        .iter()
+        .find(|tag| tag.key.as_ref() == key)
+        .map(|tag| tag.value.as_ref())
+}
+
+/// Returns the value of the attribute of `event` with the given key.
+pub fn attribute_value<'a>(event: &'a AbciEvent, key: &str) -> Result<&'a str, EventError> {
+    find_attribute(event, key).ok_or_else(|| EventError::missing_key(key.to_string()))
+}
+
+/// Parses the value of the attribute of `event` with the given key.
+pub fn parse_attribute<T: FromStr>(event: &AbciEvent, key: &str) -> Result<T, EventError> {
+    parse_value(key, attribute_value(event, key)?)
+}
+
+/// Parses the value of the attribute of `event` with the given key, if any.
+pub fn parse_optional_attribute<T: FromStr>(
+    event: &AbciEvent,
+    key: &str,
+) -> Result<Option<T>, EventError> {
+    find_attribute(event, key)
+        .map(|value| parse_value(key, value))
+        .transpose()
+}
+
+pub(crate) fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, EventError> {
+    value
+        .parse()
+        .map_err(|_| EventError::malformed_attribute(key.to_string(), value.to_string()))
+}
+
+pub(crate) fn check_event_type(
+    event: &AbciEvent,
+    event_type: IbcEventType,
+) -> Result<(), EventError> {
+    if event.type_str != event_type.as_str() {
+        return Err(EventError::incorrect_event_type(event.type_str.clone()));
+    }
+    Ok(())
+}
+
+/// Parses an IBC event from its ABCI form, according to its type.
+///
+/// Module events cannot be parsed, since their types are chosen by the applications emitting them.
+pub fn parse_event(event: AbciEvent) -> Result<IbcEvent, EventError> {
+    let event_type = IbcEventType::from_str(&event.type_str)
+        .map_err(|_| EventError::unsupported_abci_event(event.type_str.clone()))?;
+
+    Ok(match event_type {
+        IbcEventType::CreateClient => IbcEvent::CreateClient(event.try_into()?),
+        IbcEventType::UpdateClient => IbcEvent::UpdateClient(event.try_into()?),
+        IbcEventType::UpgradeClient => IbcEvent::UpgradeClient(event.try_into()?),
+        IbcEventType::ClientMisbehaviour => IbcEvent::ClientMisbehaviour(event.try_into()?),
+        IbcEventType::OpenInitConnection => IbcEvent::OpenInitConnection(event.try_into()?),
+        IbcEventType::OpenTryConnection => IbcEvent::OpenTryConnection(event.try_into()?),
+        IbcEventType::OpenAckConnection => IbcEvent::OpenAckConnection(event.try_into()?),
+        IbcEventType::OpenConfirmConnection => IbcEvent::OpenConfirmConnection(event.try_into()?),
+        IbcEventType::OpenInitChannel => IbcEvent::OpenInitChannel(event.try_into()?),
+        IbcEventType::OpenTryChannel => IbcEvent::OpenTryChannel(event.try_into()?),
+        IbcEventType::OpenAckChannel => IbcEvent::OpenAckChannel(event.try_into()?),
+        IbcEventType::OpenConfirmChannel => IbcEvent::OpenConfirmChannel(event.try_into()?),
+        IbcEventType::CloseInitChannel => IbcEvent::CloseInitChannel(event.try_into()?),
+        IbcEventType::CloseConfirmChannel => IbcEvent::CloseConfirmChannel(event.try_into()?),
+        IbcEventType::UpgradeInitChannel
+        | IbcEventType::UpgradeTryChannel
+        | IbcEventType::UpgradeAckChannel
+        | IbcEventType::UpgradeConfirmChannel
+        | IbcEventType::UpgradeOpenChannel
+        | IbcEventType::FlushCompleteChannel
+        | IbcEventType::UpgradeCancelChannel
+        | IbcEventType::UpgradeTimeoutChannel
+        | IbcEventType::UpgradeErrorChannel => IbcEvent::ChannelUpgrade(event.try_into()?),
+        IbcEventType::ChannelClosed => IbcEvent::ChannelClosed(event.try_into()?),
+        IbcEventType::ChannelPaused => IbcEvent::ChannelPaused(event.try_into()?),
+        IbcEventType::ChannelResumed => IbcEvent::ChannelResumed(event.try_into()?),
+        IbcEventType::SendPacket => IbcEvent::SendPacket(event.try_into()?),
+        IbcEventType::ReceivePacket => IbcEvent::ReceivePacket(event.try_into()?),
+        IbcEventType::WriteAck => IbcEvent::WriteAcknowledgement(event.try_into()?),
+        IbcEventType::AckPacket => IbcEvent::AcknowledgePacket(event.try_into()?),
+        IbcEventType::Timeout => IbcEvent::TimeoutPacket(event.try_into()?),
+        IbcEventType::AppModule => {
+            return Err(EventError::unsupported_abci_event(event.type_str));
+        }
+    })
+}
+
+/// Decodes the base64-encoded keys and values of the attributes of an event, as returned by the
+/// RPC of Tendermint 0.34 nodes. Note that events deserialized with `tendermint-rpc` are decoded
+/// already.
+pub fn decode_base64_attributes(event: AbciEvent) -> Result<AbciEvent, EventError> {
+    let decode = |tag: &Tag, encoded: &str| {
+        base64::decode(encoded)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| {
+                EventError::malformed_attribute(tag.key.to_string(), tag.value.to_string())
+            })
+    };
+
+    let attributes = event
+        .attributes
+        .iter()
+        .map(|tag| {
+            Ok(Tag {
+                key: decode(tag, tag.key.as_ref())?.parse().unwrap(),
+                value: decode(tag, tag.value.as_ref())?.parse().unwrap(),
+            })
+        })
+        .collect::<Result<_, EventError>>()?;
+
+    Ok(AbciEvent {
+        type_str: event.type_str,
+        attributes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::time::Duration;
+
+    use ibc_proto::google::protobuf::Any;
+
+    use crate::core::ics02_client::client_type::ClientType;
+    use crate::core::ics02_client::events::{UpdateClient, UpdateGap};
+    use crate::core::ics04_channel::channel::Order;
+    use crate::core::ics04_channel::events::{
+        ChannelClosed, ChannelPaused, ChannelResumed, ChannelUpgrade, ReceivePacket, SendPacket,
+        UpgradeStep,
+    };
+    use crate::core::ics04_channel::packet::test_utils::get_dummy_raw_packet;
+    use crate::core::ics04_channel::packet::Packet;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+    use crate::events::tests::golden_events;
+    use crate::Height;
+
+    fn round_trip(event: IbcEvent) {
+        let abci_event = AbciEvent::try_from(event).unwrap();
+        let parsed = parse_event(abci_event.clone()).unwrap();
+        assert_eq!(AbciEvent::try_from(parsed).unwrap(), abci_event);
+    }
+
+    #[test]
+    fn ibc_events_round_trip() {
+        let height = Height::new(1, 10).unwrap();
+        let client_type = ClientType::new("07-tendermint".to_string());
+        let packet: Packet = get_dummy_raw_packet(1, 6).try_into().unwrap();
+
+        let events = golden_events()
+            .into_iter()
+            .filter(|event| !matches!(event, IbcEvent::AppModule(_)))
+            .chain([
+                IbcEvent::UpdateClient(
+                    UpdateClient::new(
+                        ClientId::new(client_type.clone(), 0).unwrap(),
+                        client_type,
+                        height,
+                        vec![height, height.increment()],
+                        Any::default(),
+                    )
+                    .with_gap(UpdateGap {
+                        heights: 1000,
+                        time: Duration::from_secs(3600),
+                    }),
+                ),
+                IbcEvent::ChannelUpgrade(
+                    ChannelUpgrade::new(
+                        UpgradeStep::Error,
+                        PortId::transfer(),
+                        ChannelId::new(0),
+                        PortId::transfer(),
+                        ChannelId::new(1),
+                        2,
+                        vec![ConnectionId::new(0), ConnectionId::new(1)],
+                        Version::ics20(),
+                        Order::Ordered,
+                    )
+                    .with_error_receipt("counterparty timed out".to_string()),
+                ),
+                IbcEvent::ChannelClosed(ChannelClosed::new(
+                    PortId::transfer(),
+                    ChannelId::new(0),
+                    PortId::transfer(),
+                    None,
+                    ConnectionId::new(0),
+                    Order::Ordered,
+                )),
+                IbcEvent::ChannelPaused(ChannelPaused::new(
+                    PortId::transfer(),
+                    ChannelId::new(0),
+                    ConnectionId::new(0),
+                )),
+                IbcEvent::ChannelResumed(ChannelResumed::new(
+                    PortId::transfer(),
+                    ChannelId::new(0),
+                    ConnectionId::new(0),
+                )),
+                IbcEvent::ReceivePacket(
+                    ReceivePacket::new(packet, Order::Unordered, ConnectionId::new(0))
+                        .with_relayer("cosmos1relayer".parse().unwrap()),
+                ),
+            ]);
+
+        for event in events {
+            round_trip(event);
+        }
+    }
+
+    #[test]
+    fn packet_is_reconstructed_from_events() {
+        let packet: Packet = get_dummy_raw_packet(1, 6).try_into().unwrap();
+        let event = AbciEvent::try_from(SendPacket::new(
+            packet.clone(),
+            Order::Ordered,
+            ConnectionId::new(0),
+        ))
+        .unwrap();
+
+        let parsed = SendPacket::try_from(event.clone()).unwrap();
+        assert_eq!(parsed.packet(), packet);
+        assert_eq!(parsed.channel_ordering(), &Order::Ordered);
+
+        // Without the hex attribute, the data is taken from the deprecated UTF-8 one.
+        let mut utf8_event = event.clone();
+        utf8_event
+            .attributes
+            .retain(|tag| tag.key.as_ref() != PKT_DATA_HEX_ATTRIBUTE_KEY);
+        assert_eq!(SendPacket::try_from(utf8_event).unwrap().packet(), packet);
+
+        // As returned by the RPC of Tendermint 0.34 nodes.
+        let encode = |s: &str| String::from_utf8(base64::encode(s)).unwrap();
+        let base64_event = AbciEvent {
+            type_str: event.type_str.clone(),
+            attributes: event
+                .attributes
+                .iter()
+                .map(|tag| Tag {
+                    key: encode(tag.key.as_ref()).parse().unwrap(),
+                    value: encode(tag.value.as_ref()).parse().unwrap(),
+                })
+                .collect(),
+        };
+        assert!(SendPacket::try_from(base64_event.clone()).is_err());
+        let decoded = decode_base64_attributes(base64_event).unwrap();
+        assert_eq!(decoded, event);
+
+        let mut malformed = event;
+        for tag in malformed.attributes.iter_mut() {
+            if tag.key.as_ref() == PKT_DATA_HEX_ATTRIBUTE_KEY {
+                tag.value = "not hex".parse().unwrap();
+            }
+        }
+        assert!(SendPacket::try_from(malformed).is_err());
+    }
+}
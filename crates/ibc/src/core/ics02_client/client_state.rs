@@ -13,7 +13,9 @@ use crate::core::ics03_connection::connection::ConnectionEnd;
 use crate::core::ics04_channel::channel::ChannelEnd;
 use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCommitment};
 use crate::core::ics04_channel::context::ChannelReader;
-use crate::core::ics04_channel::packet::Sequence;
+use crate::core::ics04_channel::packet::{Receipt, Sequence};
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::upgrade::{ErrorReceipt, Upgrade};
 use crate::core::ics23_commitment::commitment::{
     CommitmentPrefix, CommitmentProofBytes, CommitmentRoot,
 };
@@ -24,6 +26,7 @@ use crate::Height;
 
 use super::consensus_state::ConsensusState;
 use super::context::ClientReader;
+use super::header::Header;
 
 pub trait ClientState:
     AsAny
@@ -57,6 +60,12 @@ pub trait ClientState:
     /// state timestamp
     fn expired(&self, elapsed: Duration) -> bool;
 
+    /// The period after the timestamp of the latest consensus state during which the client
+    /// can still be updated, or `None` if the client does not expire.
+    fn trusting_period(&self) -> Option<Duration> {
+        None
+    }
+
     /// Helper function to verify the upgrade client procedure.
     /// Resets all fields except the blockchain-specific ones,
     /// and updates the given fields.
@@ -77,6 +86,10 @@ pub trait ClientState:
 
     fn initialise(&self, consensus_state: Any) -> Result<Box<dyn ConsensusState>, Error>;
 
+    /// Decodes a header of this client type, giving the core access to its height and
+    /// timestamp without knowledge of the concrete header type.
+    fn decode_header(&self, header: Any) -> Result<Box<dyn Header>, Error>;
+
     fn check_header_and_update_state(
         &self,
         ctx: &dyn ClientReader,
@@ -84,6 +97,26 @@ pub trait ClientState:
         header: Any,
     ) -> Result<UpdatedState, Error>;
 
+    /// Checks that `misbehaviour` is valid evidence of misbehaviour of the chain tracked by the
+    /// client, and returns the client state frozen accordingly. Clients that do not handle
+    /// misbehaviour reject it.
+    fn check_misbehaviour_and_update_state(
+        &self,
+        _ctx: &dyn ClientReader,
+        _client_id: ClientId,
+        _misbehaviour: Any,
+    ) -> Result<Box<dyn ClientState>, Error> {
+        Err(Error::misbehaviour_not_supported(self.client_type()))
+    }
+
+    /// Returns the state of the client once it verified the proofs of a message, for the
+    /// clients that only accept each proof once, or `None` if verifying proofs leaves the client
+    /// unchanged. E.g. the signatures of a solo machine are bound to its sequence, which each
+    /// message proven with them advances.
+    fn consume_proofs(&self) -> Option<UpdatedState> {
+        None
+    }
+
     fn verify_upgrade_and_update_state(
         &self,
         consensus_state: Any,
@@ -135,6 +168,40 @@ pub trait ClientState:
         expected_channel_end: &ChannelEnd,
     ) -> Result<(), Error>;
 
+    /// Verify a `proof` of the upgrade in progress of a channel end on the counterparty chain.
+    /// Only required for the clients of hosts that enable channel upgrades.
+    #[cfg(any(test, feature = "unstable"))]
+    #[allow(clippy::too_many_arguments)]
+    fn verify_channel_upgrade(
+        &self,
+        _height: Height,
+        _prefix: &CommitmentPrefix,
+        _proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _expected_upgrade: &Upgrade,
+    ) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
+    /// Verify a `proof` of the receipt of a failed upgrade of a channel end on the counterparty
+    /// chain.
+    #[cfg(any(test, feature = "unstable"))]
+    #[allow(clippy::too_many_arguments)]
+    fn verify_channel_upgrade_error(
+        &self,
+        _height: Height,
+        _prefix: &CommitmentPrefix,
+        _proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _expected_error_receipt: &ErrorReceipt,
+    ) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
     /// Verify the client state for this chain that it is stored on the counterparty chain.
     #[allow(clippy::too_many_arguments)]
     fn verify_client_full_state(
@@ -204,6 +271,25 @@ pub trait ClientState:
         channel_id: &ChannelId,
         sequence: Sequence,
     ) -> Result<(), Error>;
+
+    /// Verify a `proof` that a packet has been received with the given `receipt`, e.g. skipped
+    /// because it timed out on an `ORDER_ORDERED_ALLOW_TIMEOUT` channel. Only required for the
+    /// clients of hosts that open such channels.
+    #[allow(clippy::too_many_arguments)]
+    fn verify_packet_receipt(
+        &self,
+        _ctx: &dyn ChannelReader,
+        _height: Height,
+        _connection_end: &ConnectionEnd,
+        _proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _sequence: Sequence,
+        _receipt: &Receipt,
+    ) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
 }
 
 // Implements `Clone` for `Box<dyn ClientState>`
@@ -248,10 +334,7 @@ mod sealed {
         CS: ClientState + PartialEq,
     {
         fn eq_client_state(&self, other: &dyn ClientState) -> bool {
-            other
-                .as_any()
-                .downcast_ref::<CS>()
-                .map_or(false, |h| self == h)
+            other.as_any().downcast_ref::<CS>() == Some(self)
         }
     }
 }
@@ -0,0 +1,277 @@
+//! Transparent decompression of client messages, enabled by the `decompression` feature.
+//!
+//! Headers of chains with large validator sets dominate the size of the transactions relaying
+//! them. Relayers may send them compressed instead, as an `Any` whose type URL names the codec
+//! and whose value is the compressed protobuf encoding of the original `Any`. Hosts that opt in
+//! decompress such messages before handing them to the client.
+//!
+//! Snappy is decoded by the crate itself; hosts may support other codecs (e.g. zstd) by
+//! overriding [`ClientReader::decompress`]. The decompressed size is bounded by
+//! [`ClientReader::max_decompressed_size`], checked before allocating.
+
+use crate::prelude::*;
+
+use core::fmt::{Display, Error as FmtError, Formatter};
+
+use ibc_proto::google::protobuf::Any;
+use prost::Message;
+
+use crate::core::ics02_client::context::ClientReader;
+use crate::core::ics02_client::error::Error;
+
+/// The type URL of a snappy-compressed client message.
+pub const SNAPPY_TYPE_URL: &str = "/ibc.core.client.v1.SnappyCompressedAny";
+
+/// The type URL of a zstd-compressed client message.
+pub const ZSTD_TYPE_URL: &str = "/ibc.core.client.v1.ZstdCompressedAny";
+
+/// The default bound on the size of a decompressed client message.
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 4 * 1024 * 1024;
+
+/// A compression codec of client messages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Codec {
+    Snappy,
+    Zstd,
+}
+
+impl Codec {
+    /// Returns the codec of a compressed client message of type `type_url`, or `None` if the
+    /// message is not compressed.
+    pub fn from_type_url(type_url: &str) -> Option<Self> {
+        match type_url {
+            SNAPPY_TYPE_URL => Some(Self::Snappy),
+            ZSTD_TYPE_URL => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Codec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::Snappy => write!(f, "snappy"),
+            Self::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+/// Returns the client message wrapped in `msg` if it is compressed, or `msg` itself otherwise.
+/// Compressed messages must not wrap compressed messages.
+pub fn decompress_client_message<Ctx>(ctx: &Ctx, msg: Any) -> Result<Any, Error>
+where
+    Ctx: ClientReader + ?Sized,
+{
+    let codec = match Codec::from_type_url(&msg.type_url) {
+        Some(codec) => codec,
+        None => return Ok(msg),
+    };
+
+    let bytes = ctx.decompress(codec, &msg.value, ctx.max_decompressed_size())?;
+    let inner = Any::decode(bytes.as_slice()).map_err(|e| Error::decompression(e.to_string()))?;
+    if Codec::from_type_url(&inner.type_url).is_some() {
+        return Err(Error::decompression(
+            "compressed messages must not be nested".to_string(),
+        ));
+    }
+
+    Ok(inner)
+}
+
+/// Decompresses a raw snappy block, failing if it decompresses to more than `max_size` bytes.
+pub fn decompress_snappy(input: &[u8], max_size: usize) -> Result<Vec<u8>, Error> {
+    let mut reader = Reader(input);
+
+    let size = reader.varint()?;
+    if size > max_size as u64 {
+        return Err(Error::decompressed_size_too_large(size, max_size as u64));
+    }
+    let size = size as usize;
+    let mut output = Vec::with_capacity(size);
+
+    while !reader.0.is_empty() {
+        let tag = reader.byte()?;
+        let (offset, length) = match tag & 0b11 {
+            0 => {
+                let length = match usize::from(tag >> 2) {
+                    length @ 0..=59 => length + 1,
+                    n => reader.little_endian(n - 59)? + 1,
+                };
+                if output.len() + length > size {
+                    return Err(truncated());
+                }
+                output.extend_from_slice(reader.bytes(length)?);
+                continue;
+            }
+            1 => {
+                let offset = (usize::from(tag >> 5) << 8) | usize::from(reader.byte()?);
+                (offset, 4 + usize::from((tag >> 2) & 0b111))
+            }
+            2 => (reader.little_endian(2)?, 1 + usize::from(tag >> 2)),
+            _ => (reader.little_endian(4)?, 1 + usize::from(tag >> 2)),
+        };
+
+        if offset == 0 || offset > output.len() || output.len() + length > size {
+            return Err(truncated());
+        }
+        // Copies may overlap their own output, e.g. to repeat a run of bytes.
+        let start = output.len() - offset;
+        for i in 0..length {
+            let byte = output[start + i];
+            output.push(byte);
+        }
+    }
+
+    if output.len() != size {
+        return Err(truncated());
+    }
+
+    Ok(output)
+}
+
+fn truncated() -> Error {
+    Error::decompression("corrupt snappy block".to_string())
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < n {
+            return Err(truncated());
+        }
+        let (bytes, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn little_endian(&mut self, n: usize) -> Result<usize, Error> {
+        Ok(self
+            .bytes(n)?
+            .iter()
+            .rev()
+            .fold(0, |acc, byte| (acc << 8) | usize::from(*byte)))
+    }
+
+    /// Reads the uncompressed length of the block, a varint of at most 32 bits.
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut value = 0u64;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(truncated())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics02_client::handler::{dispatch, ClientResult};
+    use crate::core::ics02_client::msgs::update_client::MsgUpdateClient;
+    use crate::core::ics02_client::msgs::ClientMsg;
+    use crate::core::ics24_host::identifier::ClientId;
+    use crate::mock::context::MockContext;
+    use crate::mock::header::MockHeader;
+    use crate::test_utils::get_dummy_account_id;
+    use crate::timestamp::Timestamp;
+    use crate::Height;
+
+    /// Encodes `data` as a snappy block made of literals only.
+    fn snappy_literals(data: &[u8]) -> Vec<u8> {
+        let mut block = Vec::new();
+        let mut size = data.len();
+        while size >= 0x80 {
+            block.push((size as u8) | 0x80);
+            size >>= 7;
+        }
+        block.push(size as u8);
+        for chunk in data.chunks(60) {
+            block.push(((chunk.len() - 1) as u8) << 2);
+            block.extend_from_slice(chunk);
+        }
+        block
+    }
+
+    #[test]
+    fn snappy_copies() {
+        // "abc" as a literal, then a copy of 9 bytes at offset 3.
+        let block = [0x0c, 0x08, b'a', b'b', b'c', 0x15, 0x03];
+        assert_eq!(
+            decompress_snappy(&block, 100).unwrap(),
+            b"abcabcabcabc".to_vec()
+        );
+
+        assert!(decompress_snappy(&block, 11).is_err());
+        assert!(decompress_snappy(&block[..6], 100).is_err());
+        // A copy reaching before the start of the output.
+        assert!(decompress_snappy(&[0x0c, 0x08, b'a', b'b', b'c', 0x15, 0x04], 100).is_err());
+    }
+
+    #[test]
+    fn compressed_client_messages_are_unwrapped() {
+        let ctx = MockContext::default();
+        let header = Any {
+            type_url: "/ibc.mock.Header".to_string(),
+            value: vec![42; 200],
+        };
+
+        let compressed = Any {
+            type_url: SNAPPY_TYPE_URL.to_string(),
+            value: snappy_literals(&header.encode_to_vec()),
+        };
+        assert_eq!(
+            decompress_client_message(&ctx, compressed.clone()).unwrap(),
+            header
+        );
+        assert_eq!(
+            decompress_client_message(&ctx, header.clone()).unwrap(),
+            header
+        );
+
+        let nested = Any {
+            type_url: SNAPPY_TYPE_URL.to_string(),
+            value: snappy_literals(&compressed.encode_to_vec()),
+        };
+        assert!(decompress_client_message(&ctx, nested).is_err());
+
+        let zstd = Any {
+            type_url: ZSTD_TYPE_URL.to_string(),
+            value: vec![],
+        };
+        assert!(decompress_client_message(&ctx, zstd).is_err());
+    }
+
+    #[test]
+    fn update_client_with_compressed_header() {
+        let client_id = ClientId::default();
+        let ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
+
+        let height = Height::new(0, 46).unwrap();
+        let header: Any = MockHeader::new(height)
+            .with_timestamp(Timestamp::now())
+            .into();
+        let msg = MsgUpdateClient {
+            client_id: client_id.clone(),
+            header: Any {
+                type_url: SNAPPY_TYPE_URL.to_string(),
+                value: snappy_literals(&header.encode_to_vec()),
+            },
+            signer: get_dummy_account_id(),
+        };
+
+        let output = dispatch(&ctx, ClientMsg::UpdateClient(msg)).unwrap();
+        match output.result {
+            ClientResult::Update(res) => assert_eq!(res.consensus_heights, vec![height]),
+            _ => panic!("update handler result has incorrect type"),
+        }
+    }
+}
@@ -83,10 +83,7 @@ mod sealed {
         CS: ConsensusState + PartialEq,
     {
         fn eq_consensus_state(&self, other: &dyn ConsensusState) -> bool {
-            other
-                .as_any()
-                .downcast_ref::<CS>()
-                .map_or(false, |h| self == h)
+            other.as_any().downcast_ref::<CS>() == Some(self)
         }
     }
 }
@@ -3,15 +3,21 @@
 //! "ADR 003: IBC protocol implementation" for more details.
 
 use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::time::Duration;
 
 use ibc_proto::google::protobuf::Any;
 
 use crate::core::ics02_client::client_state::ClientState;
 use crate::core::ics02_client::client_type::ClientType;
 use crate::core::ics02_client::consensus_state::ConsensusState;
-use crate::core::ics02_client::error::Error;
-use crate::core::ics02_client::handler::ClientResult::{self, Create, Update, Upgrade};
-use crate::core::ics24_host::identifier::ClientId;
+use crate::core::ics02_client::error::{Error, ErrorDetail};
+use crate::core::ics02_client::handler::update_client::UpdateGapThresholds;
+use crate::core::ics02_client::handler::ClientResult::{
+    self, Create, Misbehaviour, Update, Upgrade,
+};
+use crate::core::ics24_host::identifier::{ChainId, ClientId};
 use crate::timestamp::Timestamp;
 use crate::Height;
 
@@ -26,6 +32,24 @@ pub trait ClientReader {
     /// Tries to decode the given `client_state` into a concrete light client state.
     fn decode_client_state(&self, client_state: Any) -> Result<Box<dyn ClientState>, Error>;
 
+    /// Decodes the state of the client `client_id`, as encoded in the host's store. States of a
+    /// type that this build does not understand (e.g. after a partial downgrade) are reported as
+    /// `UnknownClientType` errors, so that only the messages touching the client fail.
+    fn decode_stored_client_state(
+        &self,
+        client_id: &ClientId,
+        client_state: Any,
+    ) -> Result<Box<dyn ClientState>, Error> {
+        let type_url = client_state.type_url.clone();
+        self.decode_client_state(client_state)
+            .map_err(|e| match e.detail() {
+                ErrorDetail::UnknownClientStateType(_) => {
+                    Error::unknown_client_type(client_id.clone(), type_url)
+                }
+                _ => e,
+            })
+    }
+
     /// Retrieve the consensus state for the given client ID at the specified
     /// height.
     ///
@@ -50,6 +74,14 @@ pub trait ClientReader {
         height: Height,
     ) -> Result<Option<Box<dyn ConsensusState>>, Error>;
 
+    /// Returns the heights of all consensus states stored for the given client.
+    ///
+    /// Only used for diagnostics; hosts that cannot enumerate consensus states may rely on the
+    /// default implementation, which returns no heights.
+    fn consensus_state_heights(&self, _client_id: &ClientId) -> Result<Vec<Height>, Error> {
+        Ok(Vec::new())
+    }
+
     /// Returns the current height of the local chain.
     fn host_height(&self) -> Height;
 
@@ -70,6 +102,107 @@ pub trait ClientReader {
     /// Returns a natural number, counting how many clients have been created thus far.
     /// The value of this counter should increase only via method `ClientKeeper::increase_client_counter`.
     fn client_counter(&self) -> Result<u64, Error>;
+
+    /// Returns the prefix of the identifiers generated for new clients of type `client_type`,
+    /// which defaults to the client type itself (e.g. `07-tendermint`). Hosts override it to
+    /// namespace their client identifiers, which must remain valid ICS-024 identifiers.
+    fn client_id_prefix(&self, client_type: &ClientType) -> String {
+        client_type.as_str().to_string()
+    }
+
+    /// Returns the identifier that the next client of type `client_type` is created with, i.e.
+    /// `{prefix}-{counter}` for the prefix returned by `client_id_prefix` and the current
+    /// `client_counter`, matching the numbering of ibc-go. The counter only advances through
+    /// `ClientKeeper::increase_client_counter`, once the client is stored.
+    fn next_client_id(&self, client_type: &ClientType) -> Result<ClientId, Error> {
+        let counter = self.client_counter()?;
+        ClientId::with_prefix(&self.client_id_prefix(client_type), counter)
+            .map_err(|e| Error::client_identifier_constructor(client_type.clone(), counter, e))
+    }
+
+    /// Returns the latest height of the client, i.e. the one of its client state. Hosts that
+    /// record it through `ClientKeeper::store_client_latest_height` override this method to spare
+    /// decoding the client state.
+    fn client_latest_height(&self, client_id: &ClientId) -> Result<Height, Error> {
+        self.client_state(client_id)
+            .map(|client_state| client_state.latest_height())
+    }
+
+    /// Returns the time at which the latest consensus state of the client leaves its trusting
+    /// period, after which the client can no longer be updated, or `Timestamp::none()` if the
+    /// client does not expire.
+    fn client_expiry(&self, client_id: &ClientId) -> Result<Timestamp, Error> {
+        let client_state = self.client_state(client_id)?;
+        let trusting_period = match client_state.trusting_period() {
+            Some(trusting_period) => trusting_period,
+            None => return Ok(Timestamp::none()),
+        };
+        let consensus_state = self.consensus_state(client_id, client_state.latest_height())?;
+
+        (consensus_state.timestamp() + trusting_period).map_err(Error::timestamp_overflow)
+    }
+
+    /// Returns how many host blocks, produced every `block_time`, fit before the client expires
+    /// (zero if it has already expired), or `None` if the client does not expire.
+    fn blocks_until_expiry(
+        &self,
+        client_id: &ClientId,
+        block_time: Duration,
+    ) -> Result<Option<u64>, Error> {
+        let expiry = self.client_expiry(client_id)?;
+        if expiry == Timestamp::none() {
+            return Ok(None);
+        }
+
+        let remaining = expiry
+            .duration_since(&self.host_timestamp())
+            .unwrap_or_default();
+        let blocks = remaining.as_nanos() / block_time.as_nanos().max(1);
+
+        Ok(Some(blocks.try_into().unwrap_or(u64::MAX)))
+    }
+
+    /// Returns the identifier of the chain that the client tracks.
+    fn counterparty_chain_id(&self, client_id: &ClientId) -> Result<ChainId, Error> {
+        self.client_state(client_id)
+            .map(|client_state| client_state.chain_id())
+    }
+
+    /// Returns the clients tracking the chain `chain_id`, as recorded through
+    /// `ClientKeeper::store_client_chain_id`. Hosts that do not keep this registry can rely on
+    /// the default, which reports none.
+    fn client_ids_for_chain(&self, _chain_id: &ChainId) -> Result<Vec<ClientId>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the thresholds beyond which the gap bridged by a client update is reported. Hosts
+    /// override it to tune them, e.g. to their block time.
+    fn update_gap_thresholds(&self) -> UpdateGapThresholds {
+        UpdateGapThresholds::default()
+    }
+
+    /// Returns the maximum size, in bytes, of a decompressed client message.
+    #[cfg(any(test, feature = "decompression"))]
+    fn max_decompressed_size(&self) -> usize {
+        crate::core::ics02_client::compression::DEFAULT_MAX_DECOMPRESSED_SIZE
+    }
+
+    /// Decompresses `data`, failing if it decompresses to more than `max_size` bytes. Only
+    /// snappy is supported by default; hosts override this method to support other codecs.
+    #[cfg(any(test, feature = "decompression"))]
+    fn decompress(
+        &self,
+        codec: crate::core::ics02_client::compression::Codec,
+        data: &[u8],
+        max_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        use crate::core::ics02_client::compression::{decompress_snappy, Codec};
+
+        match codec {
+            Codec::Snappy => decompress_snappy(data, max_size),
+            codec => Err(Error::unsupported_compression_codec(codec.to_string())),
+        }
+    }
 }
 
 /// Defines the write-only part of ICS2 (client functions) context.
@@ -79,12 +212,17 @@ pub trait ClientKeeper {
             Create(res) => {
                 self.store_client_type(res.client_id.clone(), res.client_type)?;
                 self.store_client_state(res.client_id.clone(), res.client_state.clone())?;
+                self.store_client_latest_height(
+                    res.client_id.clone(),
+                    res.client_state.latest_height(),
+                )?;
                 self.store_consensus_state(
                     res.client_id.clone(),
                     res.client_state.latest_height(),
                     res.consensus_state,
                 )?;
                 self.increase_client_counter();
+                self.store_client_chain_id(res.client_id.clone(), res.client_state.chain_id())?;
                 self.store_update_time(
                     res.client_id.clone(),
                     res.client_state.latest_height(),
@@ -98,31 +236,59 @@ pub trait ClientKeeper {
                 Ok(())
             }
             Update(res) => {
+                self.store_client_chain_id(res.client_id.clone(), res.client_state.chain_id())?;
                 self.store_client_state(res.client_id.clone(), res.client_state.clone())?;
-                self.store_consensus_state(
+                self.store_client_latest_height(
                     res.client_id.clone(),
                     res.client_state.latest_height(),
+                )?;
+
+                // The consensus state is the one of the header, which may be below the latest
+                // height of the client. Headers already installed write none.
+                let consensus_height = match res.consensus_heights.last() {
+                    Some(consensus_height) => *consensus_height,
+                    None => return Ok(()),
+                };
+                self.store_consensus_state(
+                    res.client_id.clone(),
+                    consensus_height,
                     res.consensus_state,
                 )?;
                 self.store_update_time(
                     res.client_id.clone(),
-                    res.client_state.latest_height(),
+                    consensus_height,
                     res.processed_time,
                 )?;
-                self.store_update_height(
-                    res.client_id,
-                    res.client_state.latest_height(),
-                    res.processed_height,
-                )?;
+                self.store_update_height(res.client_id, consensus_height, res.processed_height)?;
+                Ok(())
+            }
+            Misbehaviour(res) => {
+                // Only the client state changes, to record that the client is frozen.
+                self.store_client_state(res.client_id, res.client_state)?;
                 Ok(())
             }
             Upgrade(res) => {
+                self.store_client_chain_id(res.client_id.clone(), res.client_state.chain_id())?;
                 self.store_client_state(res.client_id.clone(), res.client_state.clone())?;
+                self.store_client_latest_height(
+                    res.client_id.clone(),
+                    res.client_state.latest_height(),
+                )?;
                 self.store_consensus_state(
                     res.client_id.clone(),
                     res.client_state.latest_height(),
                     res.consensus_state,
                 )?;
+                self.store_update_time(
+                    res.client_id.clone(),
+                    res.client_state.latest_height(),
+                    res.processed_time,
+                )?;
+                self.store_update_height(
+                    res.client_id,
+                    res.client_state.latest_height(),
+                    res.processed_height,
+                )?;
                 Ok(())
             }
         }
@@ -142,6 +308,17 @@ pub trait ClientKeeper {
         client_state: Box<dyn ClientState>,
     ) -> Result<(), Error>;
 
+    /// Called upon successful client creation, update and upgrade, to record the latest height
+    /// of the client for `ClientReader::client_latest_height`. Hosts that do not keep it apart
+    /// from the client state can rely on the default, which does nothing.
+    fn store_client_latest_height(
+        &mut self,
+        _client_id: ClientId,
+        _height: Height,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Called upon successful client creation and update
     fn store_consensus_state(
         &mut self,
@@ -150,14 +327,27 @@ pub trait ClientKeeper {
         consensus_state: Box<dyn ConsensusState>,
     ) -> Result<(), Error>;
 
+    /// Called upon successful client creation, update and upgrade, to record the chain tracked
+    /// by the client for `ClientReader::client_ids_for_chain`. The chain identifier changes
+    /// when the counterparty upgrades to a new revision, in which case the previous entry of
+    /// the client should be replaced. Hosts that do not keep this registry can rely on the
+    /// default, which does nothing.
+    fn store_client_chain_id(
+        &mut self,
+        _client_id: ClientId,
+        _chain_id: ChainId,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Called upon client creation.
     /// Increases the counter which keeps track of how many clients have been created.
     /// Should never fail.
     fn increase_client_counter(&mut self);
 
-    /// Called upon successful client update.
-    /// Implementations are expected to use this to record the specified time as the time at which
-    /// this update (or header) was processed.
+    /// Called upon successful client creation, update and upgrade, to record `timestamp`, the
+    /// host time at which the consensus state at `height` was processed, for
+    /// `ChannelReader::client_update_time`. The delay period of a connection counts from it.
     fn store_update_time(
         &mut self,
         client_id: ClientId,
@@ -165,9 +355,9 @@ pub trait ClientKeeper {
         timestamp: Timestamp,
     ) -> Result<(), Error>;
 
-    /// Called upon successful client update.
-    /// Implementations are expected to use this to record the specified height as the height at
-    /// at which this update (or header) was processed.
+    /// Called upon successful client creation, update and upgrade, to record `host_height`, the
+    /// host height at which the consensus state at `height` was processed, for
+    /// `ChannelReader::client_update_height`. See [`ClientKeeper::store_update_time`].
     fn store_update_height(
         &mut self,
         client_id: ClientId,
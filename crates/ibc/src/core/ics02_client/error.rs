@@ -1,6 +1,6 @@
 use crate::prelude::*;
 
-use flex_error::{define_error, TraceError};
+use flex_error::{define_error, DefaultTracer, ErrorSource, TraceError};
 use ibc_proto::protobuf::Error as TendermintProtoError;
 
 use crate::core::ics02_client::client_type::ClientType;
@@ -8,16 +8,20 @@ use crate::core::ics02_client::height::HeightError;
 use crate::core::ics23_commitment::error::Error as Ics23Error;
 use crate::core::ics24_host::error::ValidationError;
 use crate::core::ics24_host::identifier::ClientId;
+use crate::proofs::ProofError;
 use crate::signer::SignerError;
-use crate::timestamp::Timestamp;
+use crate::timestamp::{Timestamp, TimestampOverflowError};
 use crate::Height;
 
 define_error! {
     #[derive(Debug, PartialEq, Eq)]
     Error {
         UnknownClientType
-            { client_type: String }
-            | e | { format_args!("unknown client type: {0}", e.client_type) },
+            { client_id: ClientId, type_url: String }
+            | e | {
+                format_args!("client {0} has a state of unknown type: {1}",
+                    e.client_id, e.type_url)
+            },
 
         ClientIdentifierConstructor
             { client_type: ClientType, counter: u64 }
@@ -53,6 +57,14 @@ define_error! {
             { reason: String }
             | e | { format_args!("header verification failed with reason: {}", e.reason) },
 
+        MisbehaviourHandlingFailure
+            { reason: String }
+            | e | { format_args!("misbehaviour handling failed with reason: {}", e.reason) },
+
+        MisbehaviourNotSupported
+            { client_type: ClientType }
+            | e | { format_args!("misbehaviour is not supported by clients of type {0}", e.client_type) },
+
         InvalidTrustThreshold
             { numerator: u64, denominator: u64 }
             | e | { format_args!("failed to build trust threshold from fraction: {}/{}", e.numerator, e.denominator) },
@@ -176,6 +188,10 @@ define_error! {
             [ Ics23Error ]
             | _ | { "invalid commitment proof bytes" },
 
+        InvalidProofBytes
+            [ ProofError ]
+            | _ | { "invalid proof bytes" },
+
         InvalidPacketTimestamp
             [ crate::timestamp::ParseTimestampError ]
             | _ | { "invalid packet timeout timestamp value" },
@@ -213,6 +229,17 @@ define_error! {
                     e.header_height, e.latest_height)
             },
 
+        LatestHeightDecreased
+            {
+                client_id: ClientId,
+                latest_height: Height,
+                updated_height: Height,
+            }
+            | e | {
+                format_args!("update of client {} would decrease its latest height from {} to {}",
+                    e.client_id, e.latest_height, e.updated_height)
+            },
+
         LowUpgradeHeight
             {
                 upgraded_height: Height,
@@ -232,6 +259,10 @@ define_error! {
                 format_args!("timestamp is invalid or missing, timestamp={0},  now={1}", e.time1, e.time2)
             },
 
+        TimestampOverflow
+            [ TimestampOverflowError ]
+            | _ | { "timestamp overflowed" },
+
         HeaderNotWithinTrustPeriod
             {
                 latest_time:Timestamp,
@@ -253,6 +284,10 @@ define_error! {
             [ TraceError<TendermintProtoError>]
             | _ | { "invalid channel end" },
 
+        InvalidChannelUpgrade
+            [ TraceError<TendermintProtoError>]
+            | _ | { "invalid channel upgrade" },
+
         InvalidAnyClientState
             [ TraceError<TendermintProtoError>]
             | _ | { "invalid any client state" },
@@ -272,5 +307,33 @@ define_error! {
         ClientSpecific
             { description: String }
             | e | { format_args!("client specific error: {0}", e.description) },
+
+        Decompression
+            { reason: String }
+            | e | { format_args!("failed to decompress client message: {0}", e.reason) },
+
+        DecompressedSizeTooLarge
+            { size: u64, max_size: u64 }
+            | e | {
+                format_args!("decompressed client message of {0} bytes exceeds the limit of {1} bytes",
+                    e.size, e.max_size)
+            },
+
+        UnsupportedCompressionCodec
+            { codec: String }
+            | e | { format_args!("unsupported compression codec of client messages: {0}", e.codec) },
+    }
+}
+
+/// An error source of a client [`Error`] which boxes its detail, for the errors of the
+/// handlers that would otherwise grow by its whole size.
+pub struct BoxedError;
+
+impl ErrorSource<DefaultTracer> for BoxedError {
+    type Source = Error;
+    type Detail = Box<ErrorDetail>;
+
+    fn error_details(source: Error) -> (Box<ErrorDetail>, Option<DefaultTracer>) {
+        (Box::new(source.0), Some(source.1))
     }
 }
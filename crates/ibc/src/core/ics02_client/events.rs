@@ -1,15 +1,20 @@
 //! Types for the IBC events emitted from Tendermint Websocket by the client module.
 
+use core::time::Duration;
+
 use derive_more::From;
 use ibc_proto::google::protobuf::Any;
 use subtle_encoding::hex;
 use tendermint::abci::tag::Tag;
 use tendermint::abci::Event as AbciEvent;
 
+use crate::core::events::{
+    attribute_value, check_event_type, parse_attribute, parse_optional_attribute, parse_value,
+};
 use crate::core::ics02_client::client_type::ClientType;
 use crate::core::ics02_client::height::Height;
 use crate::core::ics24_host::identifier::ClientId;
-use crate::events::IbcEventType;
+use crate::events::{Error as EventError, EventReason, IbcEventType, REASON_ATTRIBUTE_KEY};
 use crate::prelude::*;
 
 /// The content of the `key` field for the attribute containing the client identifier.
@@ -26,7 +31,18 @@ pub const CONSENSUS_HEIGHTS_ATTRIBUTE_KEY: &str = "consensus_heights";
 /// The content of the `key` field for the header in update client event.
 pub const HEADER_ATTRIBUTE_KEY: &str = "header";
 
-#[derive(Debug, From)]
+/// The content of the `key` field for the number of heights bridged by a large update.
+pub const HEIGHT_GAP_ATTRIBUTE_KEY: &str = "height_gap";
+
+/// The content of the `key` field for the time, in seconds, bridged by a large update.
+pub const TIME_GAP_ATTRIBUTE_KEY: &str = "time_gap";
+
+fn parse_client_type(event: &AbciEvent) -> Result<ClientType, EventError> {
+    attribute_value(event, CLIENT_TYPE_ATTRIBUTE_KEY)
+        .map(|client_type| ClientType::new(client_type.to_string()))
+}
+
+#[derive(Clone, Debug, From)]
 struct ClientIdAttribute {
     client_id: ClientId,
 }
@@ -40,7 +56,7 @@ impl From<ClientIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 struct ClientTypeAttribute {
     client_type: ClientType,
 }
@@ -49,12 +65,15 @@ impl From<ClientTypeAttribute> for Tag {
     fn from(attr: ClientTypeAttribute) -> Self {
         Tag {
             key: CLIENT_TYPE_ATTRIBUTE_KEY.parse().unwrap(),
-            value: attr.client_type.to_string().parse().unwrap(),
+            // Note: use the raw client type string rather than its `Display` form, so that any
+            // client type reported by `ClientState::client_type()` (including custom clients)
+            // is emitted verbatim.
+            value: attr.client_type.as_str().parse().unwrap(),
         }
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 struct ConsensusHeightAttribute {
     consensus_height: Height,
 }
@@ -68,7 +87,7 @@ impl From<ConsensusHeightAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 struct ConsensusHeightsAttribute {
     consensus_heights: Vec<Height>,
 }
@@ -87,7 +106,7 @@ impl From<ConsensusHeightsAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 struct HeaderAttribute {
     header: Any,
 }
@@ -104,8 +123,48 @@ impl From<HeaderAttribute> for Tag {
     }
 }
 
+/// The gap between the latest consensus state of a client and the one added by an update, reported
+/// when it is large enough for the client to risk expiring, e.g. because relayers lag behind (see
+/// [`UpdateGapThresholds`](crate::core::ics02_client::handler::update_client::UpdateGapThresholds)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpdateGap {
+    /// The number of heights between the two consensus states, zero if they are of different
+    /// revisions.
+    pub heights: u64,
+    /// The time between the two consensus states.
+    pub time: Duration,
+}
+
+impl UpdateGap {
+    fn parse(event: &AbciEvent) -> Result<Option<Self>, EventError> {
+        let heights = match parse_optional_attribute(event, HEIGHT_GAP_ATTRIBUTE_KEY)? {
+            Some(heights) => heights,
+            None => return Ok(None),
+        };
+        let time = parse_attribute(event, TIME_GAP_ATTRIBUTE_KEY)?;
+
+        Ok(Some(Self {
+            heights,
+            time: Duration::from_secs(time),
+        }))
+    }
+
+    fn into_tags(self) -> [Tag; 2] {
+        [
+            Tag {
+                key: HEIGHT_GAP_ATTRIBUTE_KEY.parse().unwrap(),
+                value: self.heights.to_string().parse().unwrap(),
+            },
+            Tag {
+                key: TIME_GAP_ATTRIBUTE_KEY.parse().unwrap(),
+                value: self.time.as_secs().to_string().parse().unwrap(),
+            },
+        ]
+    }
+}
+
 /// CreateClient event signals the creation of a new on-chain client (IBC client).
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CreateClient {
     client_id: ClientIdAttribute,
     client_type: ClientTypeAttribute,
@@ -147,8 +206,21 @@ impl From<CreateClient> for AbciEvent {
     }
 }
 
+impl TryFrom<AbciEvent> for CreateClient {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::CreateClient)?;
+        Ok(Self::new(
+            parse_attribute(&event, CLIENT_ID_ATTRIBUTE_KEY)?,
+            parse_client_type(&event)?,
+            parse_attribute(&event, CONSENSUS_HEIGHT_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
 /// UpdateClient event signals a recent update of an on-chain client (IBC Client).
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct UpdateClient {
     client_id: ClientIdAttribute,
     client_type: ClientTypeAttribute,
@@ -157,6 +229,7 @@ pub struct UpdateClient {
     consensus_height: ConsensusHeightAttribute,
     consensus_heights: ConsensusHeightsAttribute,
     header: HeaderAttribute,
+    gap: Option<UpdateGap>,
 }
 
 impl UpdateClient {
@@ -173,9 +246,16 @@ impl UpdateClient {
             consensus_height: ConsensusHeightAttribute::from(consensus_height),
             consensus_heights: ConsensusHeightsAttribute::from(consensus_heights),
             header: HeaderAttribute::from(header),
+            gap: None,
         }
     }
 
+    /// Reports that the update bridged a large gap.
+    pub fn with_gap(mut self, gap: UpdateGap) -> Self {
+        self.gap = Some(gap);
+        self
+    }
+
     pub fn client_id(&self) -> &ClientId {
         &self.client_id.client_id
     }
@@ -192,32 +272,79 @@ impl UpdateClient {
         self.consensus_heights.consensus_heights.as_ref()
     }
 
+    /// The header of the update. Its type URL is not part of the ABCI event, so it is empty when
+    /// the event was parsed from one.
     pub fn header(&self) -> &Any {
         &self.header.header
     }
+
+    pub fn gap(&self) -> Option<&UpdateGap> {
+        self.gap.as_ref()
+    }
 }
 
 impl From<UpdateClient> for AbciEvent {
     fn from(u: UpdateClient) -> Self {
+        let mut attributes = vec![
+            u.client_id.into(),
+            u.client_type.into(),
+            u.consensus_height.into(),
+            u.consensus_heights.into(),
+            u.header.into(),
+        ];
+        if let Some(gap) = u.gap {
+            attributes.extend(gap.into_tags());
+        }
         AbciEvent {
             type_str: IbcEventType::UpdateClient.as_str().to_string(),
-            attributes: vec![
-                u.client_id.into(),
-                u.client_type.into(),
-                u.consensus_height.into(),
-                u.consensus_heights.into(),
-                u.header.into(),
-            ],
+            attributes,
         }
     }
 }
 
+impl TryFrom<AbciEvent> for UpdateClient {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::UpdateClient)?;
+        // Updates with an already installed header write no consensus state.
+        let consensus_heights = attribute_value(&event, CONSENSUS_HEIGHTS_ATTRIBUTE_KEY)?
+            .split(',')
+            .filter(|height| !height.is_empty())
+            .map(|height| parse_value(CONSENSUS_HEIGHTS_ATTRIBUTE_KEY, height))
+            .collect::<Result<_, _>>()?;
+        let header = attribute_value(&event, HEADER_ATTRIBUTE_KEY)?;
+        let header = Any {
+            type_url: String::new(),
+            value: hex::decode(header).map_err(|_| {
+                EventError::malformed_attribute(
+                    HEADER_ATTRIBUTE_KEY.to_string(),
+                    header.to_string(),
+                )
+            })?,
+        };
+
+        let update_client = Self::new(
+            parse_attribute(&event, CLIENT_ID_ATTRIBUTE_KEY)?,
+            parse_client_type(&event)?,
+            parse_attribute(&event, CONSENSUS_HEIGHT_ATTRIBUTE_KEY)?,
+            consensus_heights,
+            header,
+        );
+        Ok(match UpdateGap::parse(&event)? {
+            Some(gap) => update_client.with_gap(gap),
+            None => update_client,
+        })
+    }
+}
+
 /// ClientMisbehaviour event signals the update of an on-chain client (IBC Client) with evidence of
 /// misbehaviour.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ClientMisbehaviour {
     client_id: ClientIdAttribute,
     client_type: ClientTypeAttribute,
+    reason: Option<EventReason>,
 }
 
 impl ClientMisbehaviour {
@@ -225,9 +352,16 @@ impl ClientMisbehaviour {
         Self {
             client_id: ClientIdAttribute::from(client_id),
             client_type: ClientTypeAttribute::from(client_type),
+            reason: None,
         }
     }
 
+    /// Reports why the client is frozen.
+    pub fn with_reason(mut self, reason: EventReason) -> Self {
+        self.reason = Some(reason);
+        self
+    }
+
     pub fn client_id(&self) -> &ClientId {
         &self.client_id.client_id
     }
@@ -235,19 +369,45 @@ impl ClientMisbehaviour {
     pub fn client_type(&self) -> &ClientType {
         &self.client_type.client_type
     }
+
+    pub fn reason(&self) -> Option<&EventReason> {
+        self.reason.as_ref()
+    }
 }
 
 impl From<ClientMisbehaviour> for AbciEvent {
     fn from(c: ClientMisbehaviour) -> Self {
+        let mut attributes = vec![c.client_id.into(), c.client_type.into()];
+        if let Some(reason) = c.reason {
+            attributes.push(reason.into());
+        }
         AbciEvent {
             type_str: IbcEventType::ClientMisbehaviour.as_str().to_string(),
-            attributes: vec![c.client_id.into(), c.client_type.into()],
+            attributes,
         }
     }
 }
 
+impl TryFrom<AbciEvent> for ClientMisbehaviour {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::ClientMisbehaviour)?;
+        let misbehaviour = Self::new(
+            parse_attribute(&event, CLIENT_ID_ATTRIBUTE_KEY)?,
+            parse_client_type(&event)?,
+        );
+        Ok(
+            match parse_optional_attribute(&event, REASON_ATTRIBUTE_KEY)? {
+                Some(reason) => misbehaviour.with_reason(reason),
+                None => misbehaviour,
+            },
+        )
+    }
+}
+
 /// Signals a recent upgrade of an on-chain client (IBC Client).
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct UpgradeClient {
     client_id: ClientIdAttribute,
     client_type: ClientTypeAttribute,
@@ -288,3 +448,55 @@ impl From<UpgradeClient> for AbciEvent {
         }
     }
 }
+
+impl TryFrom<AbciEvent> for UpgradeClient {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::UpgradeClient)?;
+        Ok(Self::new(
+            parse_attribute(&event, CLIENT_ID_ATTRIBUTE_KEY)?,
+            parse_client_type(&event)?,
+            parse_attribute(&event, CONSENSUS_HEIGHT_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_type_attribute(event: AbciEvent) -> String {
+        event
+            .attributes
+            .iter()
+            .find(|tag| tag.key.as_ref() == CLIENT_TYPE_ATTRIBUTE_KEY)
+            .map(|tag| tag.value.to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn client_events_carry_custom_client_type() {
+        let client_type = ClientType::new("123-custom-wasm".to_string());
+        let client_id = ClientId::new(client_type.clone(), 0).unwrap();
+        let height = Height::new(0, 10).unwrap();
+
+        let events: Vec<AbciEvent> = vec![
+            CreateClient::new(client_id.clone(), client_type.clone(), height).into(),
+            UpdateClient::new(
+                client_id.clone(),
+                client_type.clone(),
+                height,
+                vec![height],
+                Any::default(),
+            )
+            .into(),
+            UpgradeClient::new(client_id.clone(), client_type.clone(), height).into(),
+            ClientMisbehaviour::new(client_id, client_type.clone()).into(),
+        ];
+
+        for event in events {
+            assert_eq!(client_type_attribute(event), client_type.as_str());
+        }
+    }
+}
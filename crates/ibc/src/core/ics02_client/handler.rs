@@ -6,6 +6,7 @@ use crate::core::ics02_client::msgs::ClientMsg;
 use crate::handler::HandlerOutput;
 
 pub mod create_client;
+pub mod misbehaviour;
 pub mod update_client;
 pub mod upgrade_client;
 
@@ -13,6 +14,7 @@ pub mod upgrade_client;
 pub enum ClientResult {
     Create(create_client::Result),
     Update(update_client::Result),
+    Misbehaviour(misbehaviour::Result),
     Upgrade(upgrade_client::Result),
 }
 
@@ -24,9 +26,7 @@ where
     match msg {
         ClientMsg::CreateClient(msg) => create_client::process(ctx, msg),
         ClientMsg::UpdateClient(msg) => update_client::process(ctx, msg),
+        ClientMsg::Misbehaviour(msg) => misbehaviour::process(ctx, msg),
         ClientMsg::UpgradeClient(msg) => upgrade_client::process(ctx, msg),
-        _ => {
-            unimplemented!()
-        }
     }
 }
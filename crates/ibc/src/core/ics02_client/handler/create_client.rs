@@ -37,16 +37,12 @@ pub fn process(ctx: &dyn ClientReader, msg: MsgCreateClient) -> HandlerResult<Cl
         signer: _,
     } = msg;
 
-    // Construct this client's identifier
-    let id_counter = ctx.client_counter()?;
-
     let client_state = ctx.decode_client_state(client_state)?;
 
     let client_type = client_state.client_type();
 
-    let client_id = ClientId::new(client_type.clone(), id_counter).map_err(|e| {
-        Error::client_identifier_constructor(client_state.client_type(), id_counter, e)
-    })?;
+    // Construct this client's identifier
+    let client_id = ctx.next_client_id(&client_type)?;
 
     let consensus_state = client_state.initialise(consensus_state)?;
 
@@ -88,12 +84,15 @@ mod tests {
     };
     use crate::clients::ics07_tendermint::consensus_state::ConsensusState as TmConsensusState;
     use crate::clients::ics07_tendermint::header::test_util::get_dummy_tendermint_header;
+    use crate::core::ics02_client::client_state::ClientState;
+    use crate::core::ics02_client::context::{ClientKeeper, ClientReader};
     use crate::core::ics02_client::handler::{dispatch, ClientResult};
     use crate::core::ics02_client::msgs::create_client::MsgCreateClient;
     use crate::core::ics02_client::msgs::ClientMsg;
     use crate::core::ics02_client::trust_threshold::TrustThreshold;
     use crate::core::ics23_commitment::specs::ProofSpecs;
     use crate::core::ics24_host::identifier::ClientId;
+    use crate::events::IbcEvent;
     use crate::handler::HandlerOutput;
     use crate::mock::client_state::{client_type as mock_client_type, MockClientState};
     use crate::mock::consensus_state::MockConsensusState;
@@ -118,8 +117,15 @@ mod tests {
         let output = dispatch(&ctx, ClientMsg::CreateClient(msg.clone()));
 
         match output {
-            Ok(HandlerOutput { result, .. }) => {
+            Ok(HandlerOutput { result, events, .. }) => {
                 let expected_client_id = ClientId::new(mock_client_type(), 0).unwrap();
+                match events.first() {
+                    Some(IbcEvent::CreateClient(e)) => {
+                        assert_eq!(e.client_type(), &mock_client_type());
+                        assert_eq!(e.client_id(), &expected_client_id);
+                    }
+                    _ => panic!("unexpected event: expected IbcEvent::CreateClient!"),
+                }
                 match result {
                     ClientResult::Create(create_result) => {
                         assert_eq!(create_result.client_type, mock_client_type());
@@ -144,6 +150,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_create_client_registers_chain_id() {
+        let mut ctx = MockContext::default();
+        let height = Height::new(0, 42).unwrap();
+        let client_state = MockClientState::new(MockHeader::new(height));
+        let chain_id = client_state.chain_id();
+
+        let msg = MsgCreateClient::new(
+            client_state.into(),
+            MockConsensusState::new(MockHeader::new(height)).into(),
+            get_dummy_account_id(),
+        )
+        .unwrap();
+        let output = dispatch(&ctx, ClientMsg::CreateClient(msg)).unwrap();
+        ctx.store_client_result(output.result).unwrap();
+
+        let client_id = ClientId::new(mock_client_type(), 0).unwrap();
+        assert_eq!(ctx.counterparty_chain_id(&client_id).unwrap(), chain_id);
+        assert_eq!(
+            ctx.client_ids_for_chain(&chain_id).unwrap(),
+            vec![client_id]
+        );
+    }
+
+    #[test]
+    fn test_create_client_allocates_sequential_ids() {
+        let mut ctx = MockContext::default();
+        let height = Height::new(0, 42).unwrap();
+
+        for counter in 0..2 {
+            let expected_client_id = ClientId::new(mock_client_type(), counter).unwrap();
+            assert_eq!(
+                ctx.next_client_id(&mock_client_type()).unwrap(),
+                expected_client_id
+            );
+
+            let msg = MsgCreateClient::new(
+                MockClientState::new(MockHeader::new(height)).into(),
+                MockConsensusState::new(MockHeader::new(height)).into(),
+                get_dummy_account_id(),
+            )
+            .unwrap();
+            let output = dispatch(&ctx, ClientMsg::CreateClient(msg)).unwrap();
+            match &output.result {
+                ClientResult::Create(res) => assert_eq!(res.client_id, expected_client_id),
+                _ => panic!("expected result of type ClientResult::CreateResult"),
+            }
+            ctx.store_client_result(output.result).unwrap();
+        }
+
+        // The counter is shared by all client types, as in ibc-go.
+        assert_eq!(
+            ctx.next_client_id(&tm_client_type()).unwrap(),
+            ClientId::new(tm_client_type(), 2).unwrap()
+        );
+    }
+
     #[test]
     fn test_create_client_ok_multiple() {
         let existing_client_id = ClientId::default();
@@ -241,7 +304,7 @@ mod tests {
 
         let msg = MsgCreateClient::new(
             tm_client_state,
-            TmConsensusState::try_from(tm_header).unwrap().into(),
+            TmConsensusState::from(tm_header).into(),
             signer,
         )
         .unwrap();
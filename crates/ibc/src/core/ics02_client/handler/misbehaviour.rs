@@ -0,0 +1,272 @@
+//! Protocol logic specific to processing ICS2 messages of type `MsgSubmitMisbehaviour`.
+
+use crate::core::ics02_client::client_state::ClientState;
+use crate::core::ics02_client::context::ClientReader;
+use crate::core::ics02_client::error::Error;
+use crate::core::ics02_client::events::ClientMisbehaviour;
+use crate::core::ics02_client::handler::ClientResult;
+use crate::core::ics02_client::msgs::misbehaviour::MsgSubmitMisbehaviour;
+use crate::core::ics24_host::identifier::ClientId;
+use crate::events::{EventReason, IbcEvent};
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+/// The result following the successful processing of a `MsgSubmitMisbehaviour` message.
+/// Preferably this data type should be used with a qualified name `misbehaviour::Result` to avoid
+/// ambiguity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Result {
+    pub client_id: ClientId,
+    /// The client state, frozen by the misbehaviour.
+    pub client_state: Box<dyn ClientState>,
+}
+
+pub fn process<Ctx: ClientReader>(
+    ctx: &Ctx,
+    msg: MsgSubmitMisbehaviour,
+) -> HandlerResult<ClientResult, Error> {
+    let mut output = HandlerOutput::builder();
+
+    let MsgSubmitMisbehaviour {
+        client_id,
+        misbehaviour,
+        signer: _,
+    } = msg;
+
+    // Read client state from the host chain store. The client should already exist.
+    let client_state = ctx.client_state(&client_id)?;
+
+    if client_state.is_frozen() {
+        return Err(Error::client_frozen(client_id));
+    }
+
+    let client_state = client_state
+        .check_misbehaviour_and_update_state(ctx, client_id.clone(), misbehaviour)
+        .map_err(|e| Error::misbehaviour_handling_failure(e.to_string()))?;
+
+    output.emit(IbcEvent::ClientMisbehaviour(
+        ClientMisbehaviour::new(client_id.clone(), client_state.client_type())
+            .with_reason(EventReason::Misbehaviour),
+    ));
+
+    let result = ClientResult::Misbehaviour(Result {
+        client_id,
+        client_state,
+    });
+
+    Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+    use core::time::Duration;
+    use ibc_proto::google::protobuf::Any;
+    use test_log::test;
+
+    use crate::clients::ics07_tendermint::client_type as tm_client_type;
+    use crate::clients::ics07_tendermint::header::Header as TmHeader;
+    use crate::clients::ics07_tendermint::misbehaviour::Misbehaviour as TmMisbehaviour;
+    use crate::core::ics02_client::error::{Error, ErrorDetail};
+    use crate::core::ics02_client::handler::dispatch;
+    use crate::core::ics02_client::handler::ClientResult;
+    use crate::core::ics02_client::msgs::misbehaviour::MsgSubmitMisbehaviour;
+    use crate::core::ics02_client::msgs::ClientMsg;
+    use crate::core::ics24_host::identifier::{ChainId, ClientId};
+    use crate::core::ics26_routing::handler::deliver;
+    use crate::events::{EventReason, IbcEvent};
+    use crate::mock::context::MockContext;
+    use crate::mock::header::MockHeader;
+    use crate::mock::host::{HostBlock, HostType};
+    use crate::mock::misbehaviour::Misbehaviour as MockMisbehaviour;
+    use crate::prelude::*;
+    use crate::test_utils::get_dummy_account_id;
+    use crate::timestamp::Timestamp;
+    use crate::tx_msg::Msg;
+    use crate::Height;
+
+    fn mock_misbehaviour(client_id: &ClientId, height: Height) -> MsgSubmitMisbehaviour {
+        let timestamp = Timestamp::now();
+        MsgSubmitMisbehaviour {
+            client_id: client_id.clone(),
+            misbehaviour: MockMisbehaviour {
+                client_id: client_id.clone(),
+                header1: MockHeader::new(height).with_timestamp(timestamp),
+                header2: MockHeader::new(height)
+                    .with_timestamp((timestamp + Duration::from_secs(1)).unwrap()),
+            }
+            .into(),
+            signer: get_dummy_account_id(),
+        }
+    }
+
+    #[test]
+    fn test_misbehaviour_freezes_mock_client() {
+        let client_id = ClientId::default();
+        let height = Height::new(0, 46).unwrap();
+        let ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
+
+        let output = dispatch(
+            &ctx,
+            ClientMsg::Misbehaviour(mock_misbehaviour(&client_id, height)),
+        )
+        .unwrap();
+
+        match output.result {
+            ClientResult::Misbehaviour(res) => {
+                assert_eq!(res.client_id, client_id);
+                assert_eq!(res.client_state.frozen_height(), Some(height));
+            }
+            _ => panic!("misbehaviour handler result has incorrect type"),
+        }
+        assert!(matches!(
+            output.events.as_slice(),
+            [IbcEvent::ClientMisbehaviour(e)]
+                if e.client_id() == &client_id && e.reason() == Some(&EventReason::Misbehaviour)
+        ));
+    }
+
+    #[test]
+    fn test_misbehaviour_with_non_conflicting_headers() {
+        let client_id = ClientId::default();
+        let height = Height::new(0, 46).unwrap();
+        let ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
+
+        let header = MockHeader::new(height).with_timestamp(Timestamp::now());
+        let msg = MsgSubmitMisbehaviour {
+            client_id: client_id.clone(),
+            misbehaviour: MockMisbehaviour {
+                client_id: client_id.clone(),
+                header1: header,
+                header2: header,
+            }
+            .into(),
+            signer: get_dummy_account_id(),
+        };
+
+        let err = dispatch(&ctx, ClientMsg::Misbehaviour(msg)).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::MisbehaviourHandlingFailure(_)
+        ));
+    }
+
+    #[test]
+    fn test_misbehaviour_nonexisting_client() {
+        let client_id = ClientId::from_str("mockclient1").unwrap();
+        let ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
+
+        let other_client_id = ClientId::from_str("nonexistingclient").unwrap();
+        let msg = mock_misbehaviour(&other_client_id, Height::new(0, 46).unwrap());
+
+        match dispatch(&ctx, ClientMsg::Misbehaviour(msg)) {
+            Err(Error(ErrorDetail::ClientNotFound(e), _)) => {
+                assert_eq!(e.client_id, other_client_id);
+            }
+            output => panic!("expected ClientNotFound error, instead got {:?}", output),
+        }
+    }
+
+    #[test]
+    fn test_misbehaviour_submission_through_routing() {
+        let client_id = ClientId::default();
+        let height = Height::new(0, 46).unwrap();
+        let mut ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
+
+        let msg = mock_misbehaviour(&client_id, height);
+        let receipt = deliver(&mut ctx, msg.clone().to_any()).unwrap();
+        assert!(matches!(
+            receipt.events.as_slice(),
+            [IbcEvent::ClientMisbehaviour(_)]
+        ));
+        assert_eq!(
+            ctx.latest_client_states(&client_id).frozen_height(),
+            Some(height)
+        );
+
+        // The client is frozen, so that further misbehaviour for it is rejected.
+        assert!(deliver(&mut ctx, msg.to_any()).is_err());
+    }
+
+    #[test]
+    fn test_misbehaviour_freezes_synthetic_tendermint_client() {
+        let client_id = ClientId::new(tm_client_type(), 0).unwrap();
+        let client_height = Height::new(1, 20).unwrap();
+        let misbehaviour_height = Height::new(1, 21).unwrap();
+
+        let ctx = MockContext::new(
+            ChainId::new("mockgaiaA".to_string(), 1),
+            HostType::Mock,
+            5,
+            Height::new(1, 1).unwrap(),
+        )
+        .with_client_parametrized_history(
+            &client_id,
+            client_height,
+            Some(tm_client_type()),
+            Some(client_height),
+        );
+
+        // Two blocks of the counterparty chain, signed by the same validators at the same height
+        // but at different times, and thus with different hashes.
+        let chain_id_b = ChainId::new("mockgaiaB".to_string(), 1);
+        let header = |height: Height, millis: u64| -> TmHeader {
+            let timestamp = (Timestamp::now() + Duration::from_millis(millis)).unwrap();
+            let mut block = HostBlock::generate_tm_block(
+                chain_id_b.clone(),
+                height.revision_height(),
+                timestamp,
+            );
+            block.trusted_height = client_height;
+            TmHeader::try_from(Any::from(HostBlock::SyntheticTendermint(block))).unwrap()
+        };
+        let misbehaviour = |header1: TmHeader, header2: TmHeader| MsgSubmitMisbehaviour {
+            client_id: client_id.clone(),
+            misbehaviour: TmMisbehaviour {
+                client_id: client_id.clone(),
+                header1,
+                header2,
+            }
+            .into(),
+            signer: get_dummy_account_id(),
+        };
+
+        let header1 = header(misbehaviour_height, 10);
+        let header2 = header(misbehaviour_height, 20);
+
+        let output = dispatch(
+            &ctx,
+            ClientMsg::Misbehaviour(misbehaviour(header1.clone(), header2)),
+        )
+        .unwrap();
+        match output.result {
+            ClientResult::Misbehaviour(res) => {
+                assert_eq!(res.client_state.frozen_height(), Some(misbehaviour_height));
+            }
+            _ => panic!("misbehaviour handler result has incorrect type"),
+        }
+
+        // A header above another with a later time is not misbehaviour.
+        let header3 = header(misbehaviour_height.increment(), 30);
+        let err = dispatch(
+            &ctx,
+            ClientMsg::Misbehaviour(misbehaviour(header3, header1.clone())),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::MisbehaviourHandlingFailure(_)
+        ));
+
+        // Neither are two copies of the same header.
+        let err = dispatch(
+            &ctx,
+            ClientMsg::Misbehaviour(misbehaviour(header1.clone(), header1)),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::MisbehaviourHandlingFailure(_)
+        ));
+    }
+}
@@ -1,12 +1,14 @@
 //! Protocol logic specific to processing ICS2 messages of type `MsgUpdateAnyClient`.
 
-use tracing::debug;
+use core::time::Duration;
+
+use tracing::{debug, warn};
 
 use crate::core::ics02_client::client_state::{ClientState, UpdatedState};
 use crate::core::ics02_client::consensus_state::ConsensusState;
 use crate::core::ics02_client::context::ClientReader;
 use crate::core::ics02_client::error::Error;
-use crate::core::ics02_client::events::UpdateClient;
+use crate::core::ics02_client::events::{UpdateClient, UpdateGap};
 use crate::core::ics02_client::handler::ClientResult;
 use crate::core::ics02_client::height::Height;
 use crate::core::ics02_client::msgs::update_client::MsgUpdateClient;
@@ -25,6 +27,67 @@ pub struct Result {
     pub consensus_state: Box<dyn ConsensusState>,
     pub processed_time: Timestamp,
     pub processed_height: Height,
+    /// The heights of the consensus states written by the update, in increasing order. A header
+    /// currently yields a single consensus state, at its own height, or none if it was already
+    /// installed: the time and height at which that consensus state was first processed, from
+    /// which the delay periods of connections count, are then kept.
+    pub consensus_heights: Vec<Height>,
+    /// Whether the update froze the client, e.g. because the header was evidence of misbehaviour.
+    /// Hosts may want to halt the packet flow on the client's channels.
+    pub frozen: bool,
+}
+
+/// The thresholds beyond which the gap between the latest consensus state of a client and the one
+/// added by an update is reported, with a warning and in the `UpdateClient` event, for operators
+/// to notice relayers lagging behind before the client expires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpdateGapThresholds {
+    /// The number of heights beyond which a gap is reported, if any.
+    pub max_height_gap: Option<u64>,
+    /// The share of the trusting period of the client, in percent, beyond which a gap is
+    /// reported.
+    pub max_trusting_period_percent: u64,
+}
+
+impl Default for UpdateGapThresholds {
+    fn default() -> Self {
+        Self {
+            max_height_gap: None,
+            max_trusting_period_percent: 66,
+        }
+    }
+}
+
+impl UpdateGapThresholds {
+    /// Returns the gap between the consensus states at heights `from` and `to` of a client with
+    /// the given trusting period, if it exceeds these thresholds.
+    pub fn exceeding_gap(
+        &self,
+        (from, from_timestamp): (Height, Timestamp),
+        (to, to_timestamp): (Height, Timestamp),
+        trusting_period: Option<Duration>,
+    ) -> Option<UpdateGap> {
+        let heights = if from.revision_number() == to.revision_number() {
+            to.revision_height().saturating_sub(from.revision_height())
+        } else {
+            0
+        };
+        let time = to_timestamp
+            .duration_since(&from_timestamp)
+            .unwrap_or_default();
+
+        let exceeds_heights = self.max_height_gap.map_or(false, |max| heights > max);
+        let exceeds_time = trusting_period.map_or(false, |trusting_period| {
+            time.as_nanos() * 100
+                > trusting_period.as_nanos() * u128::from(self.max_trusting_period_percent)
+        });
+
+        if exceeds_heights || exceeds_time {
+            Some(UpdateGap { heights, time })
+        } else {
+            None
+        }
+    }
 }
 
 pub fn process<Ctx: ClientReader>(
@@ -39,6 +102,9 @@ pub fn process<Ctx: ClientReader>(
         signer: _,
     } = msg;
 
+    #[cfg(any(test, feature = "decompression"))]
+    let header = crate::core::ics02_client::compression::decompress_client_message(ctx, header)?;
+
     // Read client type from the host chain store. The client should already exist.
     // Read client state from the host chain store.
     let client_state = ctx.client_state(&client_id)?;
@@ -47,11 +113,11 @@ pub fn process<Ctx: ClientReader>(
         return Err(Error::client_frozen(client_id));
     }
 
+    let latest_height = client_state.latest_height();
+
     // Read consensus state from the host chain store.
-    let latest_consensus_state =
-        ClientReader::consensus_state(ctx, &client_id, client_state.latest_height()).map_err(
-            |_| Error::consensus_state_not_found(client_id.clone(), client_state.latest_height()),
-        )?;
+    let latest_consensus_state = ClientReader::consensus_state(ctx, &client_id, latest_height)
+        .map_err(|_| Error::consensus_state_not_found(client_id.clone(), latest_height))?;
 
     debug!("latest consensus state: {:?}", latest_consensus_state);
 
@@ -69,6 +135,8 @@ pub fn process<Ctx: ClientReader>(
         ));
     }
 
+    let header_height = client_state.decode_header(header.clone())?.height();
+
     // Use client_state to validate the new header against the latest consensus_state.
     // This function will return the new client_state (its latest_height changed) and a
     // consensus_state obtained from header. These will be later persisted by the keeper.
@@ -79,25 +147,62 @@ pub fn process<Ctx: ClientReader>(
         .check_header_and_update_state(ctx, client_id.clone(), header.clone())
         .map_err(|e| Error::header_verification_failure(e.to_string()))?;
 
+    // Headers below the latest height fill gaps, they must not move the client backwards.
+    if client_state.latest_height() < latest_height {
+        return Err(Error::latest_height_decreased(
+            client_id,
+            latest_height,
+            client_state.latest_height(),
+        ));
+    }
+
     let client_type = client_state.client_type();
-    let consensus_height = client_state.latest_height();
+    let frozen = client_state.is_frozen();
+    let installed = !frozen
+        && ClientReader::consensus_state(ctx, &client_id, header_height)
+            .map_or(false, |installed| installed == consensus_state);
+    let consensus_heights = if installed {
+        vec![]
+    } else {
+        vec![header_height]
+    };
+
+    let mut event = UpdateClient::new(
+        client_id.clone(),
+        client_type,
+        header_height,
+        consensus_heights.clone(),
+        header,
+    );
+    // Headers below the latest height fill gaps, rather than bridging one.
+    if header_height > latest_height {
+        let gap = ctx.update_gap_thresholds().exceeding_gap(
+            (latest_height, latest_consensus_state.timestamp()),
+            (header_height, consensus_state.timestamp()),
+            client_state.trusting_period(),
+        );
+        if let Some(gap) = gap {
+            warn!(
+                "update of client {} bridged a large gap of {} heights and {}s, risking its expiry",
+                client_id,
+                gap.heights,
+                gap.time.as_secs()
+            );
+            event = event.with_gap(gap);
+        }
+    }
+    output.emit(IbcEvent::UpdateClient(event));
 
     let result = ClientResult::Update(Result {
-        client_id: client_id.clone(),
+        client_id,
         client_state,
         consensus_state,
         processed_time: ClientReader::host_timestamp(ctx),
         processed_height: ctx.host_height(),
+        consensus_heights,
+        frozen,
     });
 
-    output.emit(IbcEvent::UpdateClient(UpdateClient::new(
-        client_id,
-        client_type,
-        consensus_height,
-        vec![consensus_height],
-        header,
-    )));
-
     Ok(output.with_result(result))
 }
 
@@ -107,23 +212,33 @@ mod tests {
     use ibc_proto::google::protobuf::Any;
     use test_log::test;
 
+    use tendermint::abci::Event as AbciEvent;
+    use tendermint::validator::Set as ValidatorSet;
+    use tendermint_testgen::{Generator, Validator as TestgenValidator};
+
+    use crate::clients::ics07_tendermint::client_state::suggest_trusted_height;
+    use crate::clients::ics07_tendermint::client_state::test_util::get_dummy_tendermint_client_state;
     use crate::clients::ics07_tendermint::client_type as tm_client_type;
     use crate::clients::ics07_tendermint::consensus_state::ConsensusState as TmConsensusState;
     use crate::core::ics02_client::client_state::ClientState;
+    use crate::core::ics02_client::client_type::ClientType;
     use crate::core::ics02_client::consensus_state::downcast_consensus_state;
+    use crate::core::ics02_client::context::{ClientKeeper, ClientReader};
     use crate::core::ics02_client::error::{Error, ErrorDetail};
+    use crate::core::ics02_client::events::{UpdateClient, UpdateGap};
     use crate::core::ics02_client::handler::dispatch;
     use crate::core::ics02_client::handler::ClientResult::Update;
     use crate::core::ics02_client::msgs::update_client::MsgUpdateClient;
     use crate::core::ics02_client::msgs::ClientMsg;
     use crate::core::ics24_host::identifier::{ChainId, ClientId};
+    use crate::core::ics26_routing::msgs::Ics26Envelope;
     use crate::events::IbcEvent;
     use crate::handler::HandlerOutput;
     use crate::mock::client_state::client_type as mock_client_type;
     use crate::mock::client_state::MockClientState;
     use crate::mock::context::MockContext;
     use crate::mock::header::MockHeader;
-    use crate::mock::host::{HostBlock, HostType};
+    use crate::mock::host::{HostBlock, HostType, ValidatorRotation};
     use crate::test_utils::get_dummy_account_id;
     use crate::timestamp::Timestamp;
     use crate::Height;
@@ -161,7 +276,9 @@ mod tests {
                             upd_res.client_state,
                             MockClientState::new(MockHeader::new(height).with_timestamp(timestamp))
                                 .into_box()
-                        )
+                        );
+                        assert_eq!(upd_res.consensus_heights, vec![height]);
+                        assert!(!upd_res.frozen);
                     }
                     _ => panic!("update handler result has incorrect type"),
                 }
@@ -172,6 +289,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_client_reports_large_gaps() {
+        use core::time::Duration;
+
+        use crate::core::ics02_client::handler::update_client::UpdateGapThresholds;
+
+        let client_id = ClientId::default();
+        let ctx = MockContext::default()
+            .with_client(&client_id, Height::new(0, 42).unwrap())
+            .with_update_gap_thresholds(UpdateGapThresholds {
+                max_height_gap: Some(10),
+                ..Default::default()
+            });
+        let update = |height: u64| {
+            let msg = MsgUpdateClient {
+                client_id: client_id.clone(),
+                header: MockHeader::new(Height::new(0, height).unwrap()).into(),
+                signer: get_dummy_account_id(),
+            };
+            let output = dispatch(&ctx, ClientMsg::UpdateClient(msg)).unwrap();
+            match output.events.as_slice() {
+                [IbcEvent::UpdateClient(event)] => event.gap().copied(),
+                events => panic!("unexpected events: {:?}", events),
+            }
+        };
+
+        assert_eq!(update(52), None);
+        assert_eq!(update(53).map(|gap| gap.heights), Some(11));
+
+        // Mock clients do not expire, unlike clients with a trusting period.
+        let thresholds = UpdateGapThresholds::default();
+        let (from, to) = (Height::new(0, 1).unwrap(), Height::new(0, 2).unwrap());
+        let start = Timestamp::from_nanoseconds(1_000_000_000).unwrap();
+        let after = |secs: u64| (start + Duration::from_secs(secs)).unwrap();
+        let trusting_period = Some(Duration::from_secs(100));
+        assert_eq!(
+            thresholds.exceeding_gap((from, start), (to, after(60)), trusting_period),
+            None
+        );
+        assert_eq!(
+            thresholds.exceeding_gap((from, start), (to, after(70)), trusting_period),
+            Some(UpdateGap {
+                heights: 1,
+                time: Duration::from_secs(70)
+            })
+        );
+        assert_eq!(
+            thresholds.exceeding_gap((from, start), (to, after(70)), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_update_client_records_latest_height() {
+        let client_id = ClientId::default();
+        let mut ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
+
+        let height = Height::new(0, 46).unwrap();
+        let msg = MsgUpdateClient {
+            client_id: client_id.clone(),
+            header: MockHeader::new(height)
+                .with_timestamp(Timestamp::now())
+                .into(),
+            signer: get_dummy_account_id(),
+        };
+        ctx.deliver(Ics26Envelope::Ics2Msg(ClientMsg::UpdateClient(msg)))
+            .unwrap();
+
+        assert_eq!(
+            ctx.ibc_store
+                .lock()
+                .unwrap()
+                .client_latest_heights
+                .get(&client_id),
+            Some(&height)
+        );
+        assert_eq!(ctx.client_latest_height(&client_id).unwrap(), height);
+    }
+
     #[test]
     fn test_update_nonexisting_client() {
         let client_id = ClientId::from_str("mockclient1").unwrap();
@@ -197,6 +393,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_client_of_unknown_type() {
+        let client_id = ClientId::from_str("mockclient1").unwrap();
+        let future_client_id = ClientId::from_str("99-future-0").unwrap();
+        let future_client_state = Any {
+            type_url: "/ibc.lightclients.future.v1.ClientState".to_string(),
+            value: vec![1, 2, 3],
+        };
+        let signer = get_dummy_account_id();
+
+        let ctx = MockContext::default()
+            .with_client(&client_id, Height::new(0, 42).unwrap())
+            .with_encoded_client(
+                &future_client_id,
+                ClientType::new("99-future".to_string()),
+                future_client_state.clone(),
+            );
+
+        match ctx.client_state(&future_client_id) {
+            Err(Error(ErrorDetail::UnknownClientType(e), _)) => {
+                assert_eq!(e.client_id, future_client_id);
+                assert_eq!(e.type_url, future_client_state.type_url);
+            }
+            res => panic!("expected UnknownClientType error, instead got {:?}", res),
+        }
+
+        // Only the messages touching the client of unknown type are rejected.
+        let msg = MsgUpdateClient {
+            client_id: future_client_id,
+            header: MockHeader::new(Height::new(0, 46).unwrap()).into(),
+            signer: signer.clone(),
+        };
+        let output = dispatch(&ctx, ClientMsg::UpdateClient(msg));
+        assert!(
+            matches!(output, Err(Error(ErrorDetail::UnknownClientType(_), _))),
+            "expected UnknownClientType error, instead got {:?}",
+            output
+        );
+
+        let msg = MsgUpdateClient {
+            client_id,
+            header: MockHeader::new(Height::new(0, 46).unwrap()).into(),
+            signer,
+        };
+        assert!(dispatch(&ctx, ClientMsg::UpdateClient(msg)).is_ok());
+    }
+
     #[test]
     fn test_update_client_ok_multiple() {
         let client_ids = vec![
@@ -365,6 +608,78 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_synthetic_tendermint_client_trusted_validators_mismatch() {
+        let client_id = ClientId::new(tm_client_type(), 0).unwrap();
+        let client_height = Height::new(1, 20).unwrap();
+        let update_height = Height::new(1, 21).unwrap();
+
+        let ctx = MockContext::new(
+            ChainId::new("mockgaiaA".to_string(), 1),
+            HostType::Mock,
+            5,
+            Height::new(1, 1).unwrap(),
+        )
+        .with_client_parametrized_history(
+            &client_id,
+            client_height,
+            Some(tm_client_type()), // The target host chain (B) is synthetic TM.
+            Some(client_height),
+        );
+
+        let ctx_b = MockContext::new(
+            ChainId::new("mockgaiaB".to_string(), 1),
+            HostType::SyntheticTendermint,
+            5,
+            update_height,
+        );
+
+        let block = ctx_b.host_block(update_height).unwrap().clone();
+        let trusted_validator_set = match &block {
+            HostBlock::SyntheticTendermint(theader) => theader.light_block.next_validators.clone(),
+            _ => unreachable!(),
+        };
+        let block = match block {
+            HostBlock::SyntheticTendermint(mut theader) => {
+                theader.trusted_height = client_height;
+                theader.light_block.next_validators =
+                    ValidatorSet::without_proposer(vec![TestgenValidator::new("unknown")
+                        .generate()
+                        .unwrap()]);
+                HostBlock::SyntheticTendermint(theader)
+            }
+            _ => block,
+        };
+
+        let msg = MsgUpdateClient {
+            client_id: client_id.clone(),
+            header: block.into(),
+            signer: get_dummy_account_id(),
+        };
+
+        let err = dispatch(&ctx, ClientMsg::UpdateClient(msg)).unwrap_err();
+        match err.detail() {
+            ErrorDetail::HeaderVerificationFailure(e) => {
+                assert!(e.reason.contains("trusted validators hash"));
+                assert!(e.reason.contains(&client_height.to_string()));
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        // The validator set the header was built against is trusted at the client height.
+        assert_eq!(
+            suggest_trusted_height(
+                &ctx,
+                &client_id,
+                &trusted_validator_set,
+                Height::new(1, 100).unwrap(),
+                update_height,
+            )
+            .unwrap(),
+            Some(client_height)
+        );
+    }
+
     #[test]
     fn test_update_synthetic_tendermint_client_duplicate_ok() {
         let client_id = ClientId::new(tm_client_type(), 0).unwrap();
@@ -420,17 +735,40 @@ mod tests {
         match output {
             Ok(HandlerOutput {
                 result,
-                events: _,
+                events,
                 log,
             }) => {
                 assert!(log.is_empty());
+                let event = match events.as_slice() {
+                    [IbcEvent::UpdateClient(event)] => event.clone(),
+                    _ => panic!("unexpected events: {:?}", events),
+                };
+                assert!(event.consensus_heights().is_empty());
+                let parsed = UpdateClient::try_from(AbciEvent::from(event)).unwrap();
+                assert!(parsed.consensus_heights().is_empty());
                 // Check the result
                 match result {
                     Update(upd_res) => {
+                        use crate::core::ics04_channel::context::ChannelReader;
+
                         assert_eq!(upd_res.client_id, client_id);
                         assert!(!upd_res.client_state.is_frozen());
                         assert_eq!(upd_res.client_state, ctx.latest_client_states(&client_id));
-                        assert_eq!(upd_res.client_state.latest_height(), latest_header_height,)
+                        assert_eq!(upd_res.client_state.latest_height(), latest_header_height,);
+
+                        // The header was already installed: the time and height at which its
+                        // consensus state was processed are kept.
+                        assert!(upd_res.consensus_heights.is_empty());
+                        let mut ctx = ctx.clone();
+                        let processed_time = Timestamp::from_nanoseconds(1).unwrap();
+                        ctx.store_update_time(client_id.clone(), client_height, processed_time)
+                            .unwrap();
+                        ctx.store_client_result(Update(upd_res)).unwrap();
+                        assert_eq!(
+                            ChannelReader::client_update_time(&ctx, &client_id, client_height)
+                                .unwrap(),
+                            processed_time
+                        );
                     }
                     _ => panic!("update handler result has incorrect type"),
                 }
@@ -519,4 +857,110 @@ mod tests {
         assert_eq!(update_client_event.consensus_heights(), &vec![height]);
         assert_eq!(update_client_event.header(), &header);
     }
+
+    #[test]
+    fn test_update_client_with_foreign_header() {
+        let client_id = ClientId::default();
+        let ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
+
+        // A Tendermint header cannot update a mock client.
+        let ctx_b = MockContext::new(
+            ChainId::new("mockgaiaB".to_string(), 1),
+            HostType::SyntheticTendermint,
+            5,
+            Height::new(1, 46).unwrap(),
+        );
+        let header: Any = ctx_b
+            .host_block(Height::new(1, 46).unwrap())
+            .unwrap()
+            .clone()
+            .into();
+        let msg = MsgUpdateClient {
+            client_id,
+            header,
+            signer: get_dummy_account_id(),
+        };
+
+        let err = dispatch(&ctx, ClientMsg::UpdateClient(msg)).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::UnknownHeaderType(_)));
+    }
+
+    #[test]
+    fn test_update_synthetic_tendermint_client_across_validator_rotation() {
+        let client_id = ClientId::new(tm_client_type(), 0).unwrap();
+        let client_height = Height::new(1, 15).unwrap();
+
+        // One of the 3 validators of chain B is replaced at every block.
+        let ctx_b = MockContext::new(
+            ChainId::new("mockgaiaB".to_string(), 1),
+            HostType::SyntheticTendermint,
+            10,
+            Height::new(1, 20).unwrap(),
+        )
+        .with_validator_rotation(ValidatorRotation::new(3, 1));
+
+        let mut ctx = MockContext::new(
+            ChainId::new("mockgaiaA".to_string(), 1),
+            HostType::Mock,
+            5,
+            Height::new(1, 1).unwrap(),
+        );
+        let tm_header = match ctx_b.host_block(client_height).unwrap() {
+            HostBlock::SyntheticTendermint(block) => block.header().clone(),
+            _ => unreachable!(),
+        };
+        ctx.store_client_type(client_id.clone(), tm_client_type())
+            .unwrap();
+        ctx.store_client_state(
+            client_id.clone(),
+            get_dummy_tendermint_client_state(tm_header.clone()).into_box(),
+        )
+        .unwrap();
+        ctx.store_consensus_state(
+            client_id.clone(),
+            client_height,
+            Box::new(TmConsensusState::from(tm_header)),
+        )
+        .unwrap();
+
+        let update = |revision_height: u64| {
+            let header = ctx_b
+                .host_update_header(Height::new(1, revision_height).unwrap(), client_height)
+                .unwrap();
+            let msg = MsgUpdateClient {
+                client_id: client_id.clone(),
+                header: header.into(),
+                signer: get_dummy_account_id(),
+            };
+            dispatch(&ctx, ClientMsg::UpdateClient(msg))
+        };
+
+        // Adjacent update, to the validators trusted at the client height.
+        assert!(update(16).is_ok());
+        // 2/3 of the voting power at 17 is trusted, above the trust threshold of 1/3.
+        assert!(update(17).is_ok());
+        // Only 1/3 of the voting power at 18 is trusted, and none of it beyond.
+        for revision_height in [18, 19] {
+            let err = update(revision_height).unwrap_err();
+            match err.detail() {
+                ErrorDetail::HeaderVerificationFailure(e) => {
+                    assert!(e.reason.contains("insufficient validators overlap"))
+                }
+                _ => panic!("unexpected error: {:?}", err),
+            }
+        }
+
+        // Without the validators trusted at the client height, adjacent updates fail too.
+        let mut header = ctx_b
+            .host_block(Height::new(1, 16).unwrap())
+            .unwrap()
+            .clone();
+        header.set_trusted_height(client_height);
+        let msg = MsgUpdateClient {
+            client_id: client_id.clone(),
+            header: header.into(),
+            signer: get_dummy_account_id(),
+        };
+        assert!(dispatch(&ctx, ClientMsg::UpdateClient(msg)).is_err());
+    }
 }
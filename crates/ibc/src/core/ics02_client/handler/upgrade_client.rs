@@ -1,5 +1,7 @@
 //! Protocol logic specific to processing ICS2 messages of type `MsgUpgradeAnyClient`.
 //!
+use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+
 use crate::core::ics02_client::client_state::{ClientState, UpdatedState};
 use crate::core::ics02_client::consensus_state::ConsensusState;
 use crate::core::ics02_client::context::ClientReader;
@@ -11,6 +13,8 @@ use crate::core::ics24_host::identifier::ClientId;
 use crate::events::IbcEvent;
 use crate::handler::{HandlerOutput, HandlerResult};
 use crate::prelude::*;
+use crate::timestamp::Timestamp;
+use crate::Height;
 
 /// The result following the successful processing of a `MsgUpgradeAnyClient` message.
 /// This data type should be used with a qualified name `upgrade_client::Result` to avoid ambiguity.
@@ -19,6 +23,8 @@ pub struct Result {
     pub client_id: ClientId,
     pub client_state: Box<dyn ClientState>,
     pub consensus_state: Box<dyn ConsensusState>,
+    pub processed_time: Timestamp,
+    pub processed_height: Height,
 }
 
 pub fn process(
@@ -49,8 +55,10 @@ pub fn process(
         consensus_state,
     } = upgrade_client_state.verify_upgrade_and_update_state(
         msg.consensus_state.clone(),
-        msg.proof_upgrade_client.clone(),
-        msg.proof_upgrade_consensus_state,
+        RawMerkleProof::try_from(msg.proof_upgrade_client)
+            .map_err(Error::invalid_upgrade_client_proof)?,
+        RawMerkleProof::try_from(msg.proof_upgrade_consensus_state)
+            .map_err(Error::invalid_upgrade_consensus_state_proof)?,
     )?;
 
     // Not implemented yet: https://github.com/informalsystems/ibc-rs/issues/722
@@ -63,6 +71,8 @@ pub fn process(
         client_id: client_id.clone(),
         client_state,
         consensus_state,
+        processed_time: ctx.host_timestamp(),
+        processed_height: ctx.host_height(),
     });
 
     output.emit(IbcEvent::UpgradeClient(UpgradeClient::new(
@@ -81,11 +91,14 @@ mod tests {
 
     use core::str::FromStr;
 
+    use crate::core::ics02_client::context::{ClientKeeper, ClientReader};
     use crate::core::ics02_client::error::{Error, ErrorDetail};
     use crate::core::ics02_client::handler::dispatch;
     use crate::core::ics02_client::handler::ClientResult::Upgrade;
     use crate::core::ics02_client::msgs::upgrade_client::MsgUpgradeClient;
     use crate::core::ics02_client::msgs::ClientMsg;
+    use crate::core::ics04_channel::context::ChannelReader;
+    use crate::core::ics23_commitment::commitment::test_util::get_dummy_merkle_proof;
     use crate::core::ics24_host::identifier::ClientId;
     use crate::handler::HandlerOutput;
     use crate::mock::client_state::client_type as mock_client_type;
@@ -101,15 +114,15 @@ mod tests {
         let client_id = ClientId::default();
         let signer = get_dummy_account_id();
 
-        let ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
+        let mut ctx = MockContext::default().with_client(&client_id, Height::new(0, 42).unwrap());
 
         let msg = MsgUpgradeClient {
             client_id: client_id.clone(),
             client_state: MockClientState::new(MockHeader::new(Height::new(1, 26).unwrap())).into(),
             consensus_state: MockConsensusState::new(MockHeader::new(Height::new(1, 26).unwrap()))
                 .into(),
-            proof_upgrade_client: Default::default(),
-            proof_upgrade_consensus_state: Default::default(),
+            proof_upgrade_client: get_dummy_merkle_proof().try_into().unwrap(),
+            proof_upgrade_consensus_state: get_dummy_merkle_proof().try_into().unwrap(),
             signer,
         };
 
@@ -126,7 +139,21 @@ mod tests {
                 match result {
                     Upgrade(upg_res) => {
                         assert_eq!(upg_res.client_id, client_id);
-                        assert_eq!(upg_res.client_state.as_ref().clone_into(), msg.client_state)
+                        assert_eq!(upg_res.client_state.as_ref().clone_into(), msg.client_state);
+
+                        // The delay periods of connections count from the upgrade.
+                        let upgrade_height = Height::new(1, 26).unwrap();
+                        ctx.store_client_result(Upgrade(upg_res)).unwrap();
+                        assert_eq!(
+                            ChannelReader::client_update_time(&ctx, &client_id, upgrade_height)
+                                .unwrap(),
+                            ClientReader::host_timestamp(&ctx)
+                        );
+                        assert_eq!(
+                            ChannelReader::client_update_height(&ctx, &client_id, upgrade_height)
+                                .unwrap(),
+                            ClientReader::host_height(&ctx)
+                        );
                     }
                     _ => panic!("upgrade handler result has incorrect type"),
                 }
@@ -149,8 +176,8 @@ mod tests {
             client_state: MockClientState::new(MockHeader::new(Height::new(1, 26).unwrap())).into(),
             consensus_state: MockConsensusState::new(MockHeader::new(Height::new(1, 26).unwrap()))
                 .into(),
-            proof_upgrade_client: Default::default(),
-            proof_upgrade_consensus_state: Default::default(),
+            proof_upgrade_client: get_dummy_merkle_proof().try_into().unwrap(),
+            proof_upgrade_consensus_state: get_dummy_merkle_proof().try_into().unwrap(),
             signer,
         };
 
@@ -178,8 +205,8 @@ mod tests {
             client_state: MockClientState::new(MockHeader::new(Height::new(0, 26).unwrap())).into(),
             consensus_state: MockConsensusState::new(MockHeader::new(Height::new(0, 26).unwrap()))
                 .into(),
-            proof_upgrade_client: Default::default(),
-            proof_upgrade_consensus_state: Default::default(),
+            proof_upgrade_client: get_dummy_merkle_proof().try_into().unwrap(),
+            proof_upgrade_consensus_state: get_dummy_merkle_proof().try_into().unwrap(),
             signer,
         };
 
@@ -213,8 +240,8 @@ mod tests {
             client_id: client_id.clone(),
             client_state: MockClientState::new(MockHeader::new(upgrade_height)).into(),
             consensus_state: MockConsensusState::new(MockHeader::new(upgrade_height)).into(),
-            proof_upgrade_client: Default::default(),
-            proof_upgrade_consensus_state: Default::default(),
+            proof_upgrade_client: get_dummy_merkle_proof().try_into().unwrap(),
+            proof_upgrade_consensus_state: get_dummy_merkle_proof().try_into().unwrap(),
             signer,
         };
 
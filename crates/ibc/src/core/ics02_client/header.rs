@@ -13,6 +13,10 @@ use crate::Height;
 
 /// Abstract of consensus state update information
 ///
+/// The core only relies on the height and timestamp of a header; verifying it against the
+/// client's trusted state is left to the client (see `ClientState::decode_header` and
+/// `ClientState::check_header_and_update_state`).
+///
 /// Users are not expected to implement sealed::ErasedPartialEqHeader.
 /// Effectively, that trait bound mandates implementors to derive PartialEq,
 /// after which our blanket implementation will implement
@@ -73,10 +77,7 @@ mod sealed {
         H: Header + PartialEq,
     {
         fn eq_header(&self, other: &dyn Header) -> bool {
-            other
-                .as_any()
-                .downcast_ref::<H>()
-                .map_or(false, |h| self == h)
+            other.as_any().downcast_ref::<H>() == Some(self)
         }
     }
 }
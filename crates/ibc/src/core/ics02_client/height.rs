@@ -15,9 +15,11 @@ use crate::core::ics02_client::error::Error;
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Height {
     /// Previously known as "epoch"
+    #[serde(with = "crate::serializers::serde_u64")]
     revision_number: u64,
 
     /// The height of a block
+    #[serde(with = "crate::serializers::serde_u64")]
     revision_height: u64,
 }
 
@@ -118,7 +120,7 @@ impl core::fmt::Debug for Height {
     }
 }
 
-/// Custom debug output to omit the packet data
+/// The canonical string form of a height, used in store paths. It must never change.
 impl core::fmt::Display for Height {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(f, "{}-{}", self.revision_number, self.revision_height)
@@ -136,24 +138,45 @@ define_error! {
                     e.height)
             },
         ZeroHeight
-            |_| { "attempted to parse an invalid zero height" }
+            |_| { "attempted to parse an invalid zero height" },
+
+        NonCanonical
+            { height: String }
+            | e | {
+                format_args!("height {0} is not in the canonical `{{revision_number}}-{{revision_height}}` form",
+                    e.height)
+            },
     }
 }
 
+/// Parses a height from its canonical `{revision_number}-{revision_height}` form, i.e. the
+/// exact output of its `Display` implementation.
+///
+/// Any other spelling of the same height (e.g. with leading zeros or a `+` sign) is rejected, so
+/// that store keys derived from heights have a single string representation.
 impl TryFrom<&str> for Height {
     type Error = HeightError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let split: Vec<&str> = value.split('-').collect();
+        let (revision_number, revision_height) = value
+            .split_once('-')
+            .ok_or_else(|| HeightError::non_canonical(value.to_owned()))?;
 
-        let revision_number = split[0]
+        let revision_number = revision_number
             .parse::<u64>()
             .map_err(|e| HeightError::height_conversion(value.to_owned(), e))?;
-        let revision_height = split[1]
+        let revision_height = revision_height
             .parse::<u64>()
             .map_err(|e| HeightError::height_conversion(value.to_owned(), e))?;
 
-        Height::new(revision_number, revision_height).map_err(|_| HeightError::zero_height())
+        let height = Height::new(revision_number, revision_height)
+            .map_err(|_| HeightError::zero_height())?;
+
+        if height.to_string() != value {
+            return Err(HeightError::non_canonical(value.to_owned()));
+        }
+
+        Ok(height)
     }
 }
 
@@ -170,3 +193,37 @@ impl FromStr for Height {
         Height::try_from(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::Xorshift;
+
+    #[test]
+    fn display_is_stable() {
+        assert_eq!(Height::new(0, 1).unwrap().to_string(), "0-1");
+        assert_eq!(
+            Height::new(u64::MAX, u64::MAX).unwrap().to_string(),
+            "18446744073709551615-18446744073709551615"
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let mut rng = Xorshift::new(0x1bc);
+        for _ in 0..1000 {
+            let height = Height::new(rng.next_u64() % 1000, rng.next_u64() | 1).unwrap();
+            assert_eq!(height.to_string().parse::<Height>().unwrap(), height);
+        }
+    }
+
+    #[test]
+    fn non_canonical_strings_are_rejected() {
+        for s in [
+            "", "1", "1-", "-1", "1-2-3", "01-2", "1-02", "+1-2", "1-+2", " 1-2",
+        ] {
+            assert!(s.parse::<Height>().is_err(), "{:?} must not parse", s);
+        }
+    }
+}
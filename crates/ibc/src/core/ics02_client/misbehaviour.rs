@@ -36,10 +36,7 @@ mod sealed {
         H: Misbehaviour + PartialEq,
     {
         fn eq_misbehaviour(&self, other: &dyn Misbehaviour) -> bool {
-            other
-                .as_any()
-                .downcast_ref::<H>()
-                .map_or(false, |h| self == h)
+            other.as_any().downcast_ref::<H>() == Some(self)
         }
     }
 }
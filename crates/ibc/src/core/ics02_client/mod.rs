@@ -2,6 +2,8 @@
 
 pub mod client_state;
 pub mod client_type;
+#[cfg(any(test, feature = "decompression"))]
+pub mod compression;
 pub mod consensus_state;
 pub mod context;
 pub mod error;
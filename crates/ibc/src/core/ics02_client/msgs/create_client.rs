@@ -31,7 +31,7 @@ impl MsgCreateClient {
 }
 
 impl Msg for MsgCreateClient {
-    type ValidationError = crate::core::ics24_host::error::ValidationError;
+    type ValidationError = Error;
     type Raw = RawMsgCreateClient;
 
     fn route(&self) -> String {
@@ -41,6 +41,20 @@ impl Msg for MsgCreateClient {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        if self.client_state.type_url.is_empty() {
+            return Err(Error::unknown_client_state_type(
+                self.client_state.type_url.clone(),
+            ));
+        }
+        if self.consensus_state.type_url.is_empty() {
+            return Err(Error::unknown_consensus_state_type(
+                self.consensus_state.type_url.clone(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgCreateClient> for MsgCreateClient {}
@@ -57,11 +71,14 @@ impl TryFrom<RawMsgCreateClient> for MsgCreateClient {
             .consensus_state
             .ok_or_else(Error::missing_raw_client_state)?;
 
-        MsgCreateClient::new(
+        let msg = MsgCreateClient::new(
             raw_client_state,
             raw_consensus_state,
             raw.signer.parse().map_err(Error::signer)?,
-        )
+        )?;
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
@@ -97,7 +114,7 @@ mod tests {
 
         let msg = MsgCreateClient::new(
             tm_client_state,
-            TmConsensusState::try_from(tm_header).unwrap().into(),
+            TmConsensusState::from(tm_header).into(),
             signer,
         )
         .unwrap();
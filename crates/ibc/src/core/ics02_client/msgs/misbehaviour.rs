@@ -23,7 +23,7 @@ pub struct MsgSubmitMisbehaviour {
 }
 
 impl Msg for MsgSubmitMisbehaviour {
-    type ValidationError = crate::core::ics24_host::error::ValidationError;
+    type ValidationError = Error;
     type Raw = RawMsgSubmitMisbehaviour;
 
     fn route(&self) -> String {
@@ -33,6 +33,15 @@ impl Msg for MsgSubmitMisbehaviour {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        if self.misbehaviour.type_url.is_empty() {
+            return Err(Error::unknown_misbehaviour_type(
+                self.misbehaviour.type_url.clone(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgSubmitMisbehaviour> for MsgSubmitMisbehaviour {}
@@ -45,14 +54,17 @@ impl TryFrom<RawMsgSubmitMisbehaviour> for MsgSubmitMisbehaviour {
             .misbehaviour
             .ok_or_else(Error::missing_raw_misbehaviour)?;
 
-        Ok(MsgSubmitMisbehaviour {
+        let msg = MsgSubmitMisbehaviour {
             client_id: raw
                 .client_id
                 .parse()
                 .map_err(Error::invalid_raw_misbehaviour)?,
             misbehaviour: raw_misbehaviour,
             signer: raw.signer.parse().map_err(Error::signer)?,
-        })
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
@@ -7,7 +7,6 @@ use ibc_proto::ibc::core::client::v1::MsgUpdateClient as RawMsgUpdateClient;
 use ibc_proto::protobuf::Protobuf;
 
 use crate::core::ics02_client::error::Error;
-use crate::core::ics24_host::error::ValidationError;
 use crate::core::ics24_host::identifier::ClientId;
 use crate::signer::Signer;
 use crate::tx_msg::Msg;
@@ -33,7 +32,7 @@ impl MsgUpdateClient {
 }
 
 impl Msg for MsgUpdateClient {
-    type ValidationError = ValidationError;
+    type ValidationError = Error;
     type Raw = RawMsgUpdateClient;
 
     fn route(&self) -> String {
@@ -43,6 +42,13 @@ impl Msg for MsgUpdateClient {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        if self.header.type_url.is_empty() {
+            return Err(Error::unknown_header_type(self.header.type_url.clone()));
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgUpdateClient> for MsgUpdateClient {}
@@ -51,14 +57,17 @@ impl TryFrom<RawMsgUpdateClient> for MsgUpdateClient {
     type Error = Error;
 
     fn try_from(raw: RawMsgUpdateClient) -> Result<Self, Self::Error> {
-        Ok(MsgUpdateClient {
+        let msg = MsgUpdateClient {
             client_id: raw
                 .client_id
                 .parse()
                 .map_err(Error::invalid_msg_update_client_id)?,
             header: raw.header.ok_or_else(Error::missing_raw_header)?,
             signer: raw.signer.parse().map_err(Error::signer)?,
-        })
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
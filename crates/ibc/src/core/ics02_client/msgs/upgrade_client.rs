@@ -6,12 +6,10 @@ use core::str::FromStr;
 
 use ibc_proto::google::protobuf::Any;
 use ibc_proto::ibc::core::client::v1::MsgUpgradeClient as RawMsgUpgradeClient;
-use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
 use ibc_proto::protobuf::Protobuf;
 
 use crate::core::ics02_client::error::Error;
 use crate::core::ics23_commitment::commitment::CommitmentProofBytes;
-use crate::core::ics23_commitment::error::Error as Ics23Error;
 use crate::core::ics24_host::identifier::ClientId;
 use crate::signer::Signer;
 use crate::tx_msg::Msg;
@@ -24,8 +22,10 @@ pub struct MsgUpgradeClient {
     pub client_id: ClientId,
     pub client_state: Any,
     pub consensus_state: Any,
-    pub proof_upgrade_client: RawMerkleProof,
-    pub proof_upgrade_consensus_state: RawMerkleProof,
+    /// Proof of the upgraded client state, decoded only upon verification.
+    pub proof_upgrade_client: CommitmentProofBytes,
+    /// Proof of the upgraded consensus state, decoded only upon verification.
+    pub proof_upgrade_consensus_state: CommitmentProofBytes,
     pub signer: Signer,
 }
 
@@ -34,8 +34,8 @@ impl MsgUpgradeClient {
         client_id: ClientId,
         client_state: Any,
         consensus_state: Any,
-        proof_upgrade_client: RawMerkleProof,
-        proof_upgrade_consensus_state: RawMerkleProof,
+        proof_upgrade_client: CommitmentProofBytes,
+        proof_upgrade_consensus_state: CommitmentProofBytes,
         signer: Signer,
     ) -> Self {
         MsgUpgradeClient {
@@ -50,7 +50,7 @@ impl MsgUpgradeClient {
 }
 
 impl Msg for MsgUpgradeClient {
-    type ValidationError = crate::core::ics24_host::error::ValidationError;
+    type ValidationError = Error;
     type Raw = RawMsgUpgradeClient;
 
     fn route(&self) -> String {
@@ -60,23 +60,32 @@ impl Msg for MsgUpgradeClient {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        if self.client_state.type_url.is_empty() {
+            return Err(Error::unknown_client_state_type(
+                self.client_state.type_url.clone(),
+            ));
+        }
+        if self.consensus_state.type_url.is_empty() {
+            return Err(Error::unknown_consensus_state_type(
+                self.consensus_state.type_url.clone(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgUpgradeClient> for MsgUpgradeClient {}
 
 impl From<MsgUpgradeClient> for RawMsgUpgradeClient {
     fn from(dm_msg: MsgUpgradeClient) -> RawMsgUpgradeClient {
-        let c_bytes = CommitmentProofBytes::try_from(dm_msg.proof_upgrade_client)
-            .map_or(vec![], |c| c.into());
-        let cs_bytes = CommitmentProofBytes::try_from(dm_msg.proof_upgrade_consensus_state)
-            .map_or(vec![], |c| c.into());
-
         RawMsgUpgradeClient {
             client_id: dm_msg.client_id.to_string(),
             client_state: Some(dm_msg.client_state),
             consensus_state: Some(dm_msg.consensus_state),
-            proof_upgrade_client: c_bytes,
-            proof_upgrade_consensus_state: cs_bytes,
+            proof_upgrade_client: dm_msg.proof_upgrade_client.into(),
+            proof_upgrade_consensus_state: dm_msg.proof_upgrade_consensus_state.into(),
             signer: dm_msg.signer.to_string(),
         }
     }
@@ -94,28 +103,28 @@ impl TryFrom<RawMsgUpgradeClient> for MsgUpgradeClient {
             .consensus_state
             .ok_or_else(Error::missing_raw_client_state)?;
 
-        let c_bytes = CommitmentProofBytes::try_from(proto_msg.proof_upgrade_client)
-            .map_err(|_| Error::invalid_upgrade_client_proof(Ics23Error::empty_merkle_proof()))?;
-        let cs_bytes = CommitmentProofBytes::try_from(proto_msg.proof_upgrade_consensus_state)
-            .map_err(|_| {
-                Error::invalid_upgrade_consensus_state_proof(Ics23Error::empty_merkle_proof())
-            })?;
-
-        Ok(MsgUpgradeClient {
+        let msg = MsgUpgradeClient {
             client_id: ClientId::from_str(&proto_msg.client_id)
                 .map_err(Error::invalid_client_identifier)?,
             client_state: raw_client_state,
             consensus_state: raw_consensus_state,
-            proof_upgrade_client: RawMerkleProof::try_from(c_bytes)
-                .map_err(Error::invalid_upgrade_client_proof)?,
-            proof_upgrade_consensus_state: RawMerkleProof::try_from(cs_bytes)
-                .map_err(Error::invalid_upgrade_consensus_state_proof)?,
+            proof_upgrade_client: proto_msg
+                .proof_upgrade_client
+                .try_into()
+                .map_err(Error::invalid_proof_bytes)?,
+            proof_upgrade_consensus_state: proto_msg
+                .proof_upgrade_consensus_state
+                .try_into()
+                .map_err(Error::invalid_proof_bytes)?,
             signer: proto_msg.signer.parse().map_err(Error::signer)?,
-        })
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use ibc_proto::ibc::core::client::v1::MsgUpgradeClient as RawMsgUpgradeClient;
 
@@ -158,7 +167,9 @@ mod tests {
     use crate::{
         core::{
             ics02_client::{height::Height, msgs::upgrade_client::MsgUpgradeClient},
-            ics23_commitment::commitment::test_util::get_dummy_merkle_proof,
+            ics23_commitment::commitment::{
+                test_util::get_dummy_merkle_proof, CommitmentProofBytes,
+            },
             ics24_host::identifier::ClientId,
         },
         mock::{
@@ -177,7 +188,7 @@ mod tests {
         let client_state = MockClientState::new(MockHeader::new(height));
         let consensus_state = MockConsensusState::new(MockHeader::new(height));
 
-        let proof = get_dummy_merkle_proof();
+        let proof: CommitmentProofBytes = get_dummy_merkle_proof().try_into().unwrap();
 
         let msg = MsgUpgradeClient::new(
             client_id,
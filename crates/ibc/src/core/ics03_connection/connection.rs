@@ -1,11 +1,8 @@
 use crate::prelude::*;
 
+use core::fmt::{Display, Error as FmtError, Formatter};
 use core::str::FromStr;
 use core::time::Duration;
-use core::{
-    fmt::{Display, Error as FmtError, Formatter},
-    u64,
-};
 
 use ibc_proto::protobuf::Protobuf;
 use serde::{Deserialize, Serialize};
@@ -390,6 +387,6 @@ impl TryFrom<i32> for State {
 
 impl From<State> for i32 {
     fn from(value: State) -> Self {
-        value.into()
+        value as i32
     }
 }
@@ -2,9 +2,13 @@
 //! the interface that any host chain must implement to be able to process any `ConnectionMsg`.
 //! See "ADR 003: IBC protocol implementation" for more details.
 
+use crate::clients::ics07_tendermint::host_helpers::{
+    validate_self_tendermint_client, SelfClientParams,
+};
 use crate::core::ics02_client::client_state::ClientState;
 use crate::core::ics02_client::consensus_state::ConsensusState;
 use crate::core::ics03_connection::connection::ConnectionEnd;
+use crate::core::ics03_connection::delay::calculate_block_delay;
 use crate::core::ics03_connection::error::Error;
 use crate::core::ics03_connection::handler::ConnectionResult;
 use crate::core::ics03_connection::version::{get_compatible_versions, pick_version, Version};
@@ -12,6 +16,7 @@ use crate::core::ics23_commitment::commitment::CommitmentPrefix;
 use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
 use crate::prelude::*;
 use crate::Height;
+use core::time::Duration;
 use ibc_proto::google::protobuf::Any;
 
 use super::handler::ConnectionIdState;
@@ -63,13 +68,52 @@ pub trait ConnectionReader {
         pick_version(supported_versions, counterparty_candidate_versions)
     }
 
+    /// Returns the maximum expected time per block of the host, which bounds the number of
+    /// blocks that the delay period of a connection spans, as the `MaxExpectedTimePerBlock`
+    /// parameter of ibc-go does. Zero disables the delay in blocks.
+    fn max_expected_time_per_block(&self) -> Duration;
+
+    /// Calculates the number of blocks that `delay_period_time`, the delay period of a
+    /// connection, spans on the host, see [`calculate_block_delay`].
+    fn block_delay(&self, delay_period_time: Duration) -> u64 {
+        calculate_block_delay(delay_period_time, self.max_expected_time_per_block())
+    }
+
     /// Returns a counter on how many connections have been created thus far.
     /// The value of this counter should increase only via method
     /// `ConnectionKeeper::increase_connection_counter`.
     fn connection_counter(&self) -> Result<u64, Error>;
 
-    /// Validates the `ClientState` of the client on the counterparty chain.
-    fn validate_self_client(&self, counterparty_client_state: Any) -> Result<(), Error>;
+    /// Returns the prefix of the identifiers generated for new connections, which defaults to
+    /// `connection`. Hosts override it to namespace their connection identifiers, which must
+    /// remain valid ICS-024 identifiers.
+    fn connection_id_prefix(&self) -> String {
+        ConnectionId::prefix().to_string()
+    }
+
+    /// Validates the `ClientState` of the client of the host on the counterparty chain, as
+    /// reported by the counterparty in `ConnOpenTry` and `ConnOpenAck`. By default, it must be
+    /// the state of a Tendermint client matching the `self_client_params` of the host, see
+    /// [`validate_self_tendermint_client`].
+    fn validate_self_client(&self, counterparty_client_state: Any) -> Result<(), Error> {
+        let params = self.self_client_params().ok_or_else(|| {
+            Error::invalid_client_state(
+                "the host does not provide the parameters of its clients".to_string(),
+            )
+        })?;
+        validate_self_tendermint_client(
+            counterparty_client_state,
+            self.host_current_height(),
+            &params,
+        )
+    }
+
+    /// The parameters of the host that the Tendermint clients of it on counterparty chains must
+    /// match, as checked by the default `validate_self_client`. Hosts overriding
+    /// `validate_self_client` can rely on the default, which provides none.
+    fn self_client_params(&self) -> Option<SelfClientParams> {
+        None
+    }
 }
 
 /// A context supplying all the necessary write-only dependencies (i.e., storage writing facility)
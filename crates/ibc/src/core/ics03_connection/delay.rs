@@ -0,0 +1,46 @@
+//! The delay period of connections, in blocks.
+
+use core::time::Duration;
+
+/// Calculates the number of blocks that must pass for `delay_period_time` to elapse on a host
+/// producing a block every `max_expected_time_per_block` at most, rounded up, as ibc-go does.
+///
+/// A zero `max_expected_time_per_block` disables the delay in blocks, leaving only the delay in
+/// time to be enforced.
+pub fn calculate_block_delay(
+    delay_period_time: Duration,
+    max_expected_time_per_block: Duration,
+) -> u64 {
+    if max_expected_time_per_block.is_zero() {
+        return 0;
+    }
+
+    // Compute in nanoseconds, so that neither sub-second durations nor large ones lose precision.
+    let delay = delay_period_time.as_nanos();
+    let per_block = max_expected_time_per_block.as_nanos();
+    let blocks = delay / per_block + u128::from(delay % per_block != 0);
+    u64::try_from(blocks).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_delay_is_rounded_up() {
+        let secs = Duration::from_secs;
+        let millis = Duration::from_millis;
+
+        assert_eq!(calculate_block_delay(secs(30), Duration::ZERO), 0);
+        assert_eq!(calculate_block_delay(Duration::ZERO, secs(30)), 0);
+        assert_eq!(calculate_block_delay(secs(90), secs(30)), 3);
+        assert_eq!(calculate_block_delay(secs(91), secs(30)), 4);
+        assert_eq!(calculate_block_delay(Duration::from_nanos(1), secs(30)), 1);
+        assert_eq!(calculate_block_delay(secs(3), millis(1500)), 2);
+        assert_eq!(calculate_block_delay(millis(3001), millis(1500)), 3);
+        assert_eq!(
+            calculate_block_delay(Duration::MAX, Duration::from_nanos(1)),
+            u64::MAX
+        );
+    }
+}
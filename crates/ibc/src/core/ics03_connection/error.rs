@@ -89,10 +89,6 @@ define_error! {
             [ ProofError ]
             | _ | { "invalid connection proof" },
 
-        VerifyConnectionState
-            [ client_error::Error ]
-            | _ | { "error verifying connnection state" },
-
         Signer
             [ SignerError ]
             | _ | { "invalid signer" },
@@ -139,23 +135,22 @@ define_error! {
         ConnectionVerificationFailure
             | _ | { "the connection proof verification failed" },
 
-        ConsensusStateVerificationFailure
-            { height: Height }
-            [ client_error::Error ]
+        MissingHostConsensusState
+            {
+                height: Height,
+                reason: String,
+            }
             | e | {
-                format_args!("the consensus proof verification failed (height: {0})",
-                    e.height)
+                format_args!("the host has no consensus state at the height claimed by the client on the other party: {0} ({1})",
+                    e.height, e.reason)
             },
 
-        // TODO: use more specific error source
-        ClientStateVerificationFailure
-            {
-                client_id: ClientId,
-            }
-            [ client_error::Error ]
+        LowLevelProofVerification
+            { client_id: ClientId, height: Height, path: String }
+            [ client_error::BoxedError ]
             | e | {
-                format_args!("the client state proof verification failed for client id {0}",
-                    e.client_id)
+                format_args!("client {0} failed to verify the proof of {1} at height {2}",
+                    e.client_id, e.path, e.height)
             },
 
         ImplementationSpecific
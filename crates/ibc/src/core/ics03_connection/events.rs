@@ -1,11 +1,13 @@
 //! Types for the IBC events emitted from Tendermint Websocket by the connection module.
 
+use core::str::FromStr;
+use core::time::Duration;
 use serde_derive::{Deserialize, Serialize};
 use tendermint::abci::tag::Tag;
 use tendermint::abci::Event as AbciEvent;
 
 use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
-use crate::events::IbcEventType;
+use crate::events::{Error as EventError, IbcEventType};
 use crate::prelude::*;
 
 /// The content of the `key` field for the attribute containing the connection identifier.
@@ -13,6 +15,9 @@ pub const CONN_ID_ATTRIBUTE_KEY: &str = "connection_id";
 pub const CLIENT_ID_ATTRIBUTE_KEY: &str = "client_id";
 pub const COUNTERPARTY_CONN_ID_ATTRIBUTE_KEY: &str = "counterparty_connection_id";
 pub const COUNTERPARTY_CLIENT_ID_ATTRIBUTE_KEY: &str = "counterparty_client_id";
+/// The content of the `key` field for the attribute containing the delay period of the
+/// connection, in nanoseconds.
+pub const DELAY_PERIOD_ATTRIBUTE_KEY: &str = "delay_period";
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
 struct Attributes {
@@ -20,6 +25,7 @@ struct Attributes {
     pub client_id: ClientId,
     pub counterparty_connection_id: Option<ConnectionId>,
     pub counterparty_client_id: ClientId,
+    pub delay_period: Duration,
 }
 
 /// Convert attributes to Tendermint ABCI tags
@@ -55,15 +61,69 @@ impl From<Attributes> for Vec<Tag> {
             value: a.counterparty_client_id.to_string().parse().unwrap(),
         };
 
+        let delay_period = Tag {
+            key: DELAY_PERIOD_ATTRIBUTE_KEY.parse().unwrap(),
+            value: a.delay_period.as_nanos().to_string().parse().unwrap(),
+        };
+
         vec![
             conn_id,
             client_id,
             counterparty_client_id,
             counterparty_conn_id,
+            delay_period,
         ]
     }
 }
 
+/// Parses the attributes of an event of type `event_type`. The counterparty connection id is
+/// required unless the event is emitted on init, when it is not known yet.
+fn parse_attributes(event: AbciEvent, event_type: IbcEventType) -> Result<Attributes, EventError> {
+    if event.type_str != event_type.as_str() {
+        return Err(EventError::incorrect_event_type(event.type_str));
+    }
+
+    let value_of = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|tag| tag.key.as_ref() == key)
+            .map(|tag| tag.value.as_ref())
+            .ok_or_else(|| EventError::missing_key(key.to_string()))
+    };
+
+    let counterparty_connection_id = match value_of(COUNTERPARTY_CONN_ID_ATTRIBUTE_KEY)? {
+        "" if event_type == IbcEventType::OpenInitConnection => None,
+        "" => {
+            return Err(EventError::missing_key(
+                COUNTERPARTY_CONN_ID_ATTRIBUTE_KEY.to_string(),
+            ))
+        }
+        conn_id => Some(ConnectionId::from_str(conn_id).map_err(EventError::parse)?),
+    };
+    let delay_period = value_of(DELAY_PERIOD_ATTRIBUTE_KEY)?;
+    let delay_period = delay_period.parse::<u64>().map_err(|_| {
+        EventError::malformed_attribute(
+            DELAY_PERIOD_ATTRIBUTE_KEY.to_string(),
+            delay_period.to_string(),
+        )
+    })?;
+
+    Ok(Attributes {
+        connection_id: value_of(CONN_ID_ATTRIBUTE_KEY)?
+            .parse()
+            .map_err(EventError::parse)?,
+        client_id: value_of(CLIENT_ID_ATTRIBUTE_KEY)?
+            .parse()
+            .map_err(EventError::parse)?,
+        counterparty_connection_id,
+        counterparty_client_id: value_of(COUNTERPARTY_CLIENT_ID_ATTRIBUTE_KEY)?
+            .parse()
+            .map_err(EventError::parse)?,
+        delay_period: Duration::from_nanos(delay_period),
+    })
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct OpenInit(Attributes);
 
@@ -73,12 +133,14 @@ impl OpenInit {
         conn_id_on_a: ConnectionId,
         client_id_on_a: ClientId,
         client_id_on_b: ClientId,
+        delay_period: Duration,
     ) -> Self {
         Self(Attributes {
             connection_id: conn_id_on_a,
             client_id: client_id_on_a,
             counterparty_connection_id: None,
             counterparty_client_id: client_id_on_b,
+            delay_period,
         })
     }
 
@@ -94,6 +156,9 @@ impl OpenInit {
     pub fn counterparty_client_id(&self) -> &ClientId {
         &self.0.counterparty_client_id
     }
+    pub fn delay_period(&self) -> Duration {
+        self.0.delay_period
+    }
 }
 
 impl From<OpenInit> for AbciEvent {
@@ -106,6 +171,14 @@ impl From<OpenInit> for AbciEvent {
     }
 }
 
+impl TryFrom<AbciEvent> for OpenInit {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        parse_attributes(event, IbcEventType::OpenInitConnection).map(Self)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct OpenTry(Attributes);
 
@@ -116,12 +189,14 @@ impl OpenTry {
         client_id_on_b: ClientId,
         conn_id_on_a: ConnectionId,
         client_id_on_a: ClientId,
+        delay_period: Duration,
     ) -> Self {
         Self(Attributes {
             connection_id: conn_id_on_b,
             client_id: client_id_on_b,
             counterparty_connection_id: Some(conn_id_on_a),
             counterparty_client_id: client_id_on_a,
+            delay_period,
         })
     }
 
@@ -131,12 +206,18 @@ impl OpenTry {
     pub fn client_id(&self) -> &ClientId {
         &self.0.client_id
     }
-    pub fn counterparty_connection_id(&self) -> Option<&ConnectionId> {
-        self.0.counterparty_connection_id.as_ref()
+    pub fn counterparty_connection_id(&self) -> &ConnectionId {
+        self.0
+            .counterparty_connection_id
+            .as_ref()
+            .expect("the counterparty connection id is known after init")
     }
     pub fn counterparty_client_id(&self) -> &ClientId {
         &self.0.counterparty_client_id
     }
+    pub fn delay_period(&self) -> Duration {
+        self.0.delay_period
+    }
 }
 
 impl From<OpenTry> for AbciEvent {
@@ -149,6 +230,14 @@ impl From<OpenTry> for AbciEvent {
     }
 }
 
+impl TryFrom<AbciEvent> for OpenTry {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        parse_attributes(event, IbcEventType::OpenTryConnection).map(Self)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct OpenAck(Attributes);
 
@@ -159,12 +248,14 @@ impl OpenAck {
         client_id_on_a: ClientId,
         conn_id_on_b: ConnectionId,
         client_id_on_b: ClientId,
+        delay_period: Duration,
     ) -> Self {
         Self(Attributes {
             connection_id: conn_id_on_a,
             client_id: client_id_on_a,
             counterparty_connection_id: Some(conn_id_on_b),
             counterparty_client_id: client_id_on_b,
+            delay_period,
         })
     }
 
@@ -174,12 +265,18 @@ impl OpenAck {
     pub fn client_id(&self) -> &ClientId {
         &self.0.client_id
     }
-    pub fn counterparty_connection_id(&self) -> Option<&ConnectionId> {
-        self.0.counterparty_connection_id.as_ref()
+    pub fn counterparty_connection_id(&self) -> &ConnectionId {
+        self.0
+            .counterparty_connection_id
+            .as_ref()
+            .expect("the counterparty connection id is known after init")
     }
     pub fn counterparty_client_id(&self) -> &ClientId {
         &self.0.counterparty_client_id
     }
+    pub fn delay_period(&self) -> Duration {
+        self.0.delay_period
+    }
 }
 
 impl From<OpenAck> for AbciEvent {
@@ -192,6 +289,14 @@ impl From<OpenAck> for AbciEvent {
     }
 }
 
+impl TryFrom<AbciEvent> for OpenAck {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        parse_attributes(event, IbcEventType::OpenAckConnection).map(Self)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct OpenConfirm(Attributes);
 
@@ -202,12 +307,14 @@ impl OpenConfirm {
         client_id_on_b: ClientId,
         conn_id_on_a: ConnectionId,
         client_id_on_a: ClientId,
+        delay_period: Duration,
     ) -> Self {
         Self(Attributes {
             connection_id: conn_id_on_b,
             client_id: client_id_on_b,
             counterparty_connection_id: Some(conn_id_on_a),
             counterparty_client_id: client_id_on_a,
+            delay_period,
         })
     }
 
@@ -217,12 +324,18 @@ impl OpenConfirm {
     pub fn client_id(&self) -> &ClientId {
         &self.0.client_id
     }
-    pub fn counterparty_connection_id(&self) -> Option<&ConnectionId> {
-        self.0.counterparty_connection_id.as_ref()
+    pub fn counterparty_connection_id(&self) -> &ConnectionId {
+        self.0
+            .counterparty_connection_id
+            .as_ref()
+            .expect("the counterparty connection id is known after init")
     }
     pub fn counterparty_client_id(&self) -> &ClientId {
         &self.0.counterparty_client_id
     }
+    pub fn delay_period(&self) -> Duration {
+        self.0.delay_period
+    }
 }
 
 impl From<OpenConfirm> for AbciEvent {
@@ -234,3 +347,64 @@ impl From<OpenConfirm> for AbciEvent {
         }
     }
 }
+
+impl TryFrom<AbciEvent> for OpenConfirm {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        parse_attributes(event, IbcEventType::OpenConfirmConnection).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abci_events_round_trip() {
+        let conn_id_on_a = ConnectionId::new(0);
+        let conn_id_on_b = ConnectionId::new(1);
+        let client_id_on_a = ClientId::from_str("07-tendermint-0").unwrap();
+        let client_id_on_b = ClientId::from_str("07-tendermint-1").unwrap();
+        let delay_period = Duration::from_secs(3);
+
+        let init = OpenInit::new(
+            conn_id_on_a.clone(),
+            client_id_on_a.clone(),
+            client_id_on_b.clone(),
+            delay_period,
+        );
+        assert_eq!(
+            OpenInit::try_from(AbciEvent::from(init.clone())).unwrap(),
+            init
+        );
+
+        let ack = OpenAck::new(
+            conn_id_on_a.clone(),
+            client_id_on_a.clone(),
+            conn_id_on_b.clone(),
+            client_id_on_b.clone(),
+            delay_period,
+        );
+        let parsed = OpenAck::try_from(AbciEvent::from(ack.clone())).unwrap();
+        assert_eq!(parsed, ack);
+        assert_eq!(parsed.counterparty_connection_id(), &conn_id_on_b);
+        assert_eq!(parsed.counterparty_client_id(), &client_id_on_b);
+
+        let confirm = OpenConfirm::new(
+            conn_id_on_b,
+            client_id_on_b,
+            conn_id_on_a,
+            client_id_on_a,
+            delay_period,
+        );
+        let event = AbciEvent::from(confirm.clone());
+        assert_eq!(OpenConfirm::try_from(event.clone()).unwrap(), confirm);
+        assert!(OpenAck::try_from(event).is_err());
+
+        // Only the init event may lack the counterparty connection id.
+        let mut event = AbciEvent::from(init);
+        event.type_str = IbcEventType::OpenTryConnection.as_str().to_string();
+        assert!(OpenTry::try_from(event).is_err());
+    }
+}
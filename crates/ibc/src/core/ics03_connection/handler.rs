@@ -1,17 +1,23 @@
 //! This module implements the processing logic for ICS3 (connection open
 //! handshake) messages.
 
+use crate::core::ics02_client::consensus_state::ConsensusState;
+use crate::core::ics02_client::error as client_error;
 use crate::core::ics03_connection::connection::ConnectionEnd;
 use crate::core::ics03_connection::context::ConnectionReader;
 use crate::core::ics03_connection::error::Error;
 use crate::core::ics03_connection::msgs::ConnectionMsg;
-use crate::core::ics24_host::identifier::ConnectionId;
+use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
+use crate::core::ics24_host::path::Path;
 use crate::handler::HandlerOutput;
+use crate::prelude::*;
+use crate::Height;
 
 pub mod conn_open_ack;
 pub mod conn_open_confirm;
 pub mod conn_open_init;
 pub mod conn_open_try;
+pub mod verify;
 
 /// Defines the possible states of a connection identifier in a `ConnectionResult`.
 #[derive(Clone, Debug)]
@@ -54,3 +60,43 @@ where
         ConnectionMsg::ConnectionOpenConfirm(msg) => conn_open_confirm::process(ctx, msg),
     }
 }
+
+/// Returns the consensus state of the host at `consensus_height`, the height of the latest
+/// consensus state of the host's client on the counterparty chain, to verify the proof that the
+/// counterparty stored it. The height must be neither ahead of the host nor pruned from its
+/// history.
+pub(crate) fn host_consensus_state_at(
+    ctx: &dyn ConnectionReader,
+    consensus_height: Height,
+) -> Result<Box<dyn ConsensusState>, Error> {
+    let host_height = ctx.host_current_height();
+    if consensus_height > host_height {
+        return Err(Error::invalid_consensus_height(
+            consensus_height,
+            host_height,
+        ));
+    }
+
+    let oldest_height = ctx.host_oldest_height();
+    if consensus_height < oldest_height {
+        return Err(Error::stale_consensus_height(
+            consensus_height,
+            oldest_height,
+        ));
+    }
+
+    ctx.host_consensus_state(consensus_height)
+        .map_err(|e| Error::missing_host_consensus_state(consensus_height, e.to_string()))
+}
+
+/// Returns the error of the client `client_id` failing to verify the proof of `path` at `height`,
+/// which keeps where the verification failed along with why.
+pub(crate) fn proof_verification_failed(
+    client_id: &ClientId,
+    height: Height,
+    path: impl Into<Path>,
+) -> impl FnOnce(client_error::Error) -> Error {
+    let client_id = client_id.clone();
+    let path = path.into().to_string();
+    move |e| Error::low_level_proof_verification(client_id, height, path, e)
+}
@@ -10,7 +10,8 @@ use crate::events::IbcEvent;
 use crate::handler::{HandlerOutput, HandlerResult};
 use crate::prelude::*;
 
-use super::ConnectionIdState;
+use super::verify::ProofVerificationContext;
+use super::{host_consensus_state_at, ConnectionIdState};
 
 /// Per our convention, this message is processed on chain A.
 pub(crate) fn process(
@@ -19,18 +20,14 @@ pub(crate) fn process(
 ) -> HandlerResult<ConnectionResult, Error> {
     let mut output = HandlerOutput::builder();
 
-    if msg.consensus_height_of_a_on_b > ctx_a.host_current_height() {
-        return Err(Error::invalid_consensus_height(
-            msg.consensus_height_of_a_on_b,
-            ctx_a.host_current_height(),
-        ));
-    }
+    let expected_consensus_state_of_a_on_b =
+        host_consensus_state_at(ctx_a, msg.consensus_height_of_a_on_b)?;
 
     ctx_a.validate_self_client(msg.client_state_of_a_on_b.clone())?;
 
     let conn_end_on_a = ctx_a.connection_end(&msg.conn_id_on_a)?;
     if !(conn_end_on_a.state_matches(&State::Init)
-        && conn_end_on_a.versions().contains(&msg.version))
+        && msg.version.is_supported_by(conn_end_on_a.versions()))
     {
         return Err(Error::connection_mismatch(msg.conn_id_on_a));
     }
@@ -38,21 +35,18 @@ pub(crate) fn process(
     let client_id_on_a = conn_end_on_a.client_id();
     let client_id_on_b = conn_end_on_a.counterparty().client_id();
 
-    let conn_id_on_b = conn_end_on_a
-        .counterparty()
-        .connection_id()
-        .ok_or_else(Error::invalid_counterparty)?;
+    // The counterparty connection id is not known on init, it is set by this message.
+    let conn_id_on_b = &msg.conn_id_on_b;
 
     // Proof verification.
     {
-        let client_state_of_b_on_a = ctx_a.client_state(conn_end_on_a.client_id())?;
-        let consensus_state_of_b_on_a =
-            ctx_a.client_consensus_state(conn_end_on_a.client_id(), msg.proofs_height_on_b)?;
+        let verifier =
+            ProofVerificationContext::new(ctx_a, &conn_end_on_a, msg.proofs_height_on_b)?;
 
         let prefix_on_a = ctx_a.commitment_prefix();
-        let prefix_on_b = conn_end_on_a.counterparty().prefix();
 
         {
+            // The counterparty must have kept the delay period chosen on init.
             let expected_conn_end_on_b = ConnectionEnd::new(
                 State::TryOpen,
                 client_id_on_b.clone(),
@@ -65,44 +59,25 @@ pub(crate) fn process(
                 conn_end_on_a.delay_period(),
             );
 
-            client_state_of_b_on_a
-                .verify_connection_state(
-                    msg.proofs_height_on_b,
-                    prefix_on_b,
-                    &msg.proof_conn_end_on_b,
-                    consensus_state_of_b_on_a.root(),
-                    conn_id_on_b,
-                    &expected_conn_end_on_b,
-                )
-                .map_err(Error::verify_connection_state)?;
+            verifier.verify_connection_state(
+                &msg.proof_conn_end_on_b,
+                conn_id_on_b,
+                &expected_conn_end_on_b,
+            )?;
         }
 
-        client_state_of_b_on_a
-            .verify_client_full_state(
-                msg.proofs_height_on_b,
-                prefix_on_b,
-                &msg.proof_client_state_of_a_on_b,
-                consensus_state_of_b_on_a.root(),
-                client_id_on_b,
-                msg.client_state_of_a_on_b,
-            )
-            .map_err(|e| {
-                Error::client_state_verification_failure(conn_end_on_a.client_id().clone(), e)
-            })?;
-
-        let expected_consensus_state_of_a_on_b =
-            ctx_a.host_consensus_state(msg.consensus_height_of_a_on_b)?;
-        client_state_of_b_on_a
-            .verify_client_consensus_state(
-                msg.proofs_height_on_b,
-                prefix_on_b,
-                &msg.proof_consensus_state_of_a_on_b,
-                consensus_state_of_b_on_a.root(),
-                conn_end_on_a.counterparty().client_id(),
-                msg.consensus_height_of_a_on_b,
-                expected_consensus_state_of_a_on_b.as_ref(),
-            )
-            .map_err(|e| Error::consensus_state_verification_failure(msg.proofs_height_on_b, e))?;
+        verifier.verify_client_full_state(
+            &msg.proof_client_state_of_a_on_b,
+            client_id_on_b,
+            msg.client_state_of_a_on_b,
+        )?;
+
+        verifier.verify_client_consensus_state(
+            &msg.proof_consensus_state_of_a_on_b,
+            client_id_on_b,
+            msg.consensus_height_of_a_on_b,
+            expected_consensus_state_of_a_on_b.as_ref(),
+        )?;
     }
 
     // Success
@@ -111,6 +86,7 @@ pub(crate) fn process(
         client_id_on_a.clone(),
         conn_id_on_b.clone(),
         client_id_on_b.clone(),
+        conn_end_on_a.delay_period(),
     )));
     output.log("success: conn_open_ack verification passed");
 
@@ -186,13 +162,14 @@ mod tests {
             latest_height,
         );
 
-        // A connection end that will exercise the successful path.
+        // A connection end that will exercise the successful path. The counterparty connection
+        // id is not known on init.
         let default_conn_end = ConnectionEnd::new(
             State::Init,
             client_id.clone(),
             Counterparty::new(
                 client_id.clone(),
-                Some(msg_ack.conn_id_on_b.clone()),
+                None,
                 CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
             ),
             vec![msg_ack.version.clone()],
@@ -291,7 +268,14 @@ mod tests {
                     );
 
                     for e in proto_output.events.iter() {
-                        assert!(matches!(e, &IbcEvent::OpenAckConnection(_)));
+                        assert!(matches!(
+                            e,
+                            IbcEvent::OpenAckConnection(e)
+                                if e.delay_period() == res.connection_end.delay_period()
+                                    && e.counterparty_connection_id() == &counterparty_conn_id
+                                    && e.client_id() == &client_id
+                                    && e.counterparty_client_id() == &client_id
+                        ));
                     }
                 }
                 Err(e) => {
@@ -4,6 +4,7 @@ use crate::core::ics03_connection::connection::{ConnectionEnd, Counterparty, Sta
 use crate::core::ics03_connection::context::ConnectionReader;
 use crate::core::ics03_connection::error::Error;
 use crate::core::ics03_connection::events::OpenConfirm;
+use crate::core::ics03_connection::handler::verify::ProofVerificationContext;
 use crate::core::ics03_connection::handler::{ConnectionIdState, ConnectionResult};
 use crate::core::ics03_connection::msgs::conn_open_confirm::MsgConnectionOpenConfirm;
 use crate::events::IbcEvent;
@@ -30,11 +31,8 @@ pub(crate) fn process(
 
     // Verify proofs
     {
-        let client_state_of_a_on_b = ctx_b.client_state(conn_end_on_b.client_id())?;
-        let consensus_state_of_a_on_b =
-            ctx_b.client_consensus_state(conn_end_on_b.client_id(), msg.proof_height_on_a)?;
+        let verifier = ProofVerificationContext::new(ctx_b, &conn_end_on_b, msg.proof_height_on_a)?;
 
-        let prefix_on_a = conn_end_on_b.counterparty().prefix();
         let prefix_on_b = ctx_b.commitment_prefix();
 
         let expected_conn_end_on_a = ConnectionEnd::new(
@@ -49,16 +47,11 @@ pub(crate) fn process(
             conn_end_on_b.delay_period(),
         );
 
-        client_state_of_a_on_b
-            .verify_connection_state(
-                msg.proof_height_on_a,
-                prefix_on_a,
-                &msg.proof_conn_end_on_a,
-                consensus_state_of_a_on_b.root(),
-                conn_id_on_a,
-                &expected_conn_end_on_a,
-            )
-            .map_err(Error::verify_connection_state)?;
+        verifier.verify_connection_state(
+            &msg.proof_conn_end_on_a,
+            conn_id_on_a,
+            &expected_conn_end_on_a,
+        )?;
     }
 
     // Success
@@ -67,6 +60,7 @@ pub(crate) fn process(
         client_id_on_b.clone(),
         conn_id_on_a.clone(),
         client_id_on_a.clone(),
+        conn_end_on_b.delay_period(),
     )));
     output.log("success: conn_open_confirm verification passed");
 
@@ -187,7 +181,14 @@ mod tests {
                     assert_eq!(res.connection_end.state().clone(), State::Open);
 
                     for e in proto_output.events.iter() {
-                        assert!(matches!(e, &IbcEvent::OpenConfirmConnection(_)));
+                        assert!(matches!(
+                            e,
+                            IbcEvent::OpenConfirmConnection(e)
+                                if e.delay_period() == res.connection_end.delay_period()
+                                    && Some(e.counterparty_connection_id())
+                                        == res.connection_end.counterparty().connection_id()
+                                    && e.counterparty_client_id() == &client_id
+                        ));
                     }
                 }
                 Err(e) => {
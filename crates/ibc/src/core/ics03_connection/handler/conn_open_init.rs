@@ -43,7 +43,9 @@ pub(crate) fn process(
     );
 
     // Construct the identifier for the new connection.
-    let conn_id_on_a = ConnectionId::new(ctx_a.connection_counter()?);
+    let conn_id_on_a =
+        ConnectionId::with_prefix(&ctx_a.connection_id_prefix(), ctx_a.connection_counter()?)
+            .map_err(Error::invalid_identifier)?;
 
     let result = ConnectionResult {
         connection_id: conn_id_on_a.clone(),
@@ -63,6 +65,7 @@ pub(crate) fn process(
             conn_id_on_a,
             msg.client_id_on_a,
             client_id_on_b,
+            msg.delay_period,
         )));
     }
 
@@ -163,7 +166,11 @@ mod tests {
                     assert_eq!(res.connection_end.state().clone(), State::Init);
 
                     for e in proto_output.events.iter() {
-                        assert!(matches!(e, &IbcEvent::OpenInitConnection(_)));
+                        assert!(matches!(
+                            e,
+                            IbcEvent::OpenInitConnection(e)
+                                if e.delay_period() == res.connection_end.delay_period()
+                        ));
                     }
 
                     assert_eq!(res.connection_end.versions(), test.expected_versions);
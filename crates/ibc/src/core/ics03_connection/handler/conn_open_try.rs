@@ -11,7 +11,8 @@ use crate::events::IbcEvent;
 use crate::handler::{HandlerOutput, HandlerResult};
 use crate::prelude::*;
 
-use super::ConnectionIdState;
+use super::verify::ProofVerificationContext;
+use super::{host_consensus_state_at, ConnectionIdState};
 
 /// Per our convention, this message is processed on chain B.
 pub(crate) fn process(
@@ -20,17 +21,14 @@ pub(crate) fn process(
 ) -> HandlerResult<ConnectionResult, Error> {
     let mut output = HandlerOutput::builder();
 
-    let conn_id_on_b = ConnectionId::new(ctx_b.connection_counter()?);
+    let conn_id_on_b =
+        ConnectionId::with_prefix(&ctx_b.connection_id_prefix(), ctx_b.connection_counter()?)
+            .map_err(Error::invalid_identifier)?;
 
     ctx_b.validate_self_client(msg.client_state_of_b_on_a.clone())?;
 
-    if msg.consensus_height_of_b_on_a > ctx_b.host_current_height() {
-        // Fail if the consensus height is too advanced.
-        return Err(Error::invalid_consensus_height(
-            msg.consensus_height_of_b_on_a,
-            ctx_b.host_current_height(),
-        ));
-    }
+    let expected_consensus_state_of_b_on_a =
+        host_consensus_state_at(ctx_b, msg.consensus_height_of_b_on_a)?;
 
     let version_on_b = ctx_b.pick_version(
         ctx_b.get_compatible_versions(),
@@ -53,14 +51,14 @@ pub(crate) fn process(
 
     // Verify proofs
     {
-        let client_state_of_a_on_b = ctx_b.client_state(conn_end_on_b.client_id())?;
-        let consensus_state_of_a_on_b =
-            ctx_b.client_consensus_state(conn_end_on_b.client_id(), msg.proofs_height_on_a)?;
+        let verifier =
+            ProofVerificationContext::new(ctx_b, &conn_end_on_b, msg.proofs_height_on_a)?;
 
-        let prefix_on_a = conn_end_on_b.counterparty().prefix();
         let prefix_on_b = ctx_b.commitment_prefix();
 
         {
+            // The delay period is part of the proven connection end, so a delay period other
+            // than the one chosen on init fails verification.
             let versions_on_a = msg.counterparty_versions;
             let expected_conn_end_on_a = ConnectionEnd::new(
                 State::Init,
@@ -70,44 +68,25 @@ pub(crate) fn process(
                 msg.delay_period,
             );
 
-            client_state_of_a_on_b
-                .verify_connection_state(
-                    msg.proofs_height_on_a,
-                    prefix_on_a,
-                    &msg.proof_conn_end_on_a,
-                    consensus_state_of_a_on_b.root(),
-                    conn_id_on_a,
-                    &expected_conn_end_on_a,
-                )
-                .map_err(Error::verify_connection_state)?;
+            verifier.verify_connection_state(
+                &msg.proof_conn_end_on_a,
+                conn_id_on_a,
+                &expected_conn_end_on_a,
+            )?;
         }
 
-        client_state_of_a_on_b
-            .verify_client_full_state(
-                msg.proofs_height_on_a,
-                prefix_on_a,
-                &msg.proof_client_state_of_b_on_a,
-                consensus_state_of_a_on_b.root(),
-                client_id_on_a,
-                msg.client_state_of_b_on_a,
-            )
-            .map_err(|e| {
-                Error::client_state_verification_failure(conn_end_on_b.client_id().clone(), e)
-            })?;
-
-        let expected_consensus_state_of_b_on_a =
-            ctx_b.host_consensus_state(msg.consensus_height_of_b_on_a)?;
-        client_state_of_a_on_b
-            .verify_client_consensus_state(
-                msg.proofs_height_on_a,
-                prefix_on_a,
-                &msg.proof_consensus_state_of_b_on_a,
-                consensus_state_of_a_on_b.root(),
-                client_id_on_a,
-                msg.consensus_height_of_b_on_a,
-                expected_consensus_state_of_b_on_a.as_ref(),
-            )
-            .map_err(|e| Error::consensus_state_verification_failure(msg.proofs_height_on_a, e))?;
+        verifier.verify_client_full_state(
+            &msg.proof_client_state_of_b_on_a,
+            client_id_on_a,
+            msg.client_state_of_b_on_a,
+        )?;
+
+        verifier.verify_client_consensus_state(
+            &msg.proof_consensus_state_of_b_on_a,
+            client_id_on_a,
+            msg.consensus_height_of_b_on_a,
+            expected_consensus_state_of_b_on_a.as_ref(),
+        )?;
     }
 
     // Success
@@ -116,6 +95,7 @@ pub(crate) fn process(
         msg.client_id_on_b,
         conn_id_on_a.clone(),
         client_id_on_a.clone(),
+        msg.delay_period,
     )));
     output.log("success: conn_open_try verification passed");
 
@@ -134,7 +114,11 @@ mod tests {
 
     use test_log::test;
 
+    use crate::clients::ics07_tendermint::client_state::test_util::get_dummy_tendermint_client_state;
+    use crate::clients::ics07_tendermint::header::test_util::get_dummy_tendermint_header;
     use crate::core::ics03_connection::connection::State;
+    use crate::core::ics03_connection::context::ConnectionReader;
+    use crate::core::ics03_connection::error::ErrorDetail;
     use crate::core::ics03_connection::handler::{dispatch, ConnectionResult};
     use crate::core::ics03_connection::msgs::conn_open_try::test_util::get_dummy_raw_msg_conn_open_try;
     use crate::core::ics03_connection::msgs::conn_open_try::MsgConnectionOpenTry;
@@ -195,6 +179,14 @@ mod tests {
             ))
             .unwrap();
 
+        // The counterparty's client of the host tracks another chain.
+        let msg_client_of_other_chain = {
+            let mut msg = msg_conn_try.clone();
+            msg.client_state_of_b_on_a =
+                get_dummy_tendermint_client_state(get_dummy_tendermint_header()).into();
+            msg
+        };
+
         let tests: Vec<Test> = vec![
             Test {
                 name: "Processing fails because the height is too advanced".to_string(),
@@ -220,6 +212,12 @@ mod tests {
                 msg: ConnectionMsg::ConnectionOpenTry(Box::new(msg_proof_height_missing)),
                 want_pass: false,
             },
+            Test {
+                name: "Processing fails because the counterparty's client of the host tracks another chain".to_string(),
+                ctx: context.clone().with_client(&msg_client_of_other_chain.client_id_on_b, Height::new(0, client_consensus_state_height).unwrap()),
+                msg: ConnectionMsg::ConnectionOpenTry(Box::new(msg_client_of_other_chain)),
+                want_pass: false,
+            },
             Test {
                 name: "Good parameters (no previous_connection_id)".to_string(),
                 ctx: context.clone().with_client(&msg_conn_try.client_id_on_b, Height::new(0, client_consensus_state_height).unwrap()),
@@ -256,7 +254,11 @@ mod tests {
                     assert_eq!(res.connection_end.state().clone(), State::TryOpen);
 
                     for e in proto_output.events.iter() {
-                        assert!(matches!(e, &IbcEvent::OpenTryConnection(_)));
+                        assert!(matches!(
+                            e,
+                            IbcEvent::OpenTryConnection(e)
+                                if e.delay_period() == res.connection_end.delay_period()
+                        ));
                     }
                 }
                 Err(e) => {
@@ -272,4 +274,54 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn conn_open_try_consensus_height_bounds() {
+        let host_chain_height = Height::new(0, 35).unwrap();
+        let max_history_size = 5;
+        let client_consensus_state_height = 10;
+
+        let msg = |consensus_height: Height| {
+            MsgConnectionOpenTry::try_from(get_dummy_raw_msg_conn_open_try(
+                client_consensus_state_height,
+                consensus_height.revision_height(),
+            ))
+            .unwrap()
+        };
+        let ctx = MockContext::new(
+            ChainId::new("mockgaia".to_string(), 0),
+            HostType::Mock,
+            max_history_size,
+            host_chain_height,
+        )
+        .with_client(
+            &msg(host_chain_height).client_id_on_b,
+            Height::new(0, client_consensus_state_height).unwrap(),
+        );
+        let try_open = |consensus_height: Height| {
+            dispatch(
+                &ctx,
+                ConnectionMsg::ConnectionOpenTry(Box::new(msg(consensus_height))),
+            )
+        };
+
+        assert!(try_open(host_chain_height).is_ok());
+
+        let oldest_height = ctx.host_oldest_height();
+        assert!(try_open(oldest_height).is_ok());
+
+        let err = try_open(host_chain_height.increment()).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::InvalidConsensusHeight(e) if e.target_height == host_chain_height.increment()
+        ));
+
+        let pruned_height = oldest_height.sub(1).unwrap();
+        let err = try_open(pruned_height).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::StaleConsensusHeight(e)
+                if e.target_height == pruned_height && e.oldest_height == oldest_height
+        ));
+    }
 }
@@ -0,0 +1,186 @@
+//! Verification of the proofs bundled in the messages of the connection handshake.
+
+use ibc_proto::google::protobuf::Any;
+
+use crate::core::ics02_client::client_state::ClientState;
+use crate::core::ics02_client::consensus_state::ConsensusState;
+use crate::core::ics03_connection::connection::ConnectionEnd;
+use crate::core::ics03_connection::context::ConnectionReader;
+use crate::core::ics03_connection::error::Error;
+use crate::core::ics23_commitment::commitment::CommitmentProofBytes;
+use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
+use crate::core::ics24_host::path::{ClientConsensusStatePath, ClientStatePath, ConnectionsPath};
+use crate::prelude::*;
+use crate::Height;
+
+use super::proof_verification_failed;
+
+/// Verifies the proofs bundled in a message of the connection handshake, all at the same
+/// height, against the client of a connection end and its consensus state at that height.
+///
+/// Both states are read from the store once, when the context is created, rather than once per
+/// proof: e.g. a `MsgConnectionOpenTry` proves the counterparty connection end along with the
+/// counterparty client of the host and one of its consensus states.
+pub struct ProofVerificationContext<'a> {
+    connection_end: &'a ConnectionEnd,
+    height: Height,
+    client_state: Box<dyn ClientState>,
+    consensus_state: Box<dyn ConsensusState>,
+}
+
+impl<'a> ProofVerificationContext<'a> {
+    /// Reads the client of `connection_end` and its consensus state at the proof `height`.
+    pub fn new(
+        ctx: &dyn ConnectionReader,
+        connection_end: &'a ConnectionEnd,
+        height: Height,
+    ) -> Result<Self, Error> {
+        let client_id = connection_end.client_id();
+        let client_state = ctx.client_state(client_id)?;
+        let consensus_state = ctx.client_consensus_state(client_id, height)?;
+
+        Ok(Self {
+            connection_end,
+            height,
+            client_state,
+            consensus_state,
+        })
+    }
+
+    /// The height of the proofs.
+    pub fn height(&self) -> Height {
+        self.height
+    }
+
+    /// Verifies that `proof` proves the connection end `conn_id` of the counterparty chain to
+    /// be `expected_conn_end`.
+    pub fn verify_connection_state(
+        &self,
+        proof: &CommitmentProofBytes,
+        conn_id: &ConnectionId,
+        expected_conn_end: &ConnectionEnd,
+    ) -> Result<(), Error> {
+        self.client_state
+            .verify_connection_state(
+                self.height,
+                self.connection_end.counterparty().prefix(),
+                proof,
+                self.consensus_state.root(),
+                conn_id,
+                expected_conn_end,
+            )
+            .map_err(proof_verification_failed(
+                self.connection_end.client_id(),
+                self.height,
+                ConnectionsPath(conn_id.clone()),
+            ))
+    }
+
+    /// Verifies that `proof` proves the state of the client `client_id` of the counterparty
+    /// chain to be `expected_client_state`.
+    pub fn verify_client_full_state(
+        &self,
+        proof: &CommitmentProofBytes,
+        client_id: &ClientId,
+        expected_client_state: Any,
+    ) -> Result<(), Error> {
+        self.client_state
+            .verify_client_full_state(
+                self.height,
+                self.connection_end.counterparty().prefix(),
+                proof,
+                self.consensus_state.root(),
+                client_id,
+                expected_client_state,
+            )
+            .map_err(proof_verification_failed(
+                self.connection_end.client_id(),
+                self.height,
+                ClientStatePath(client_id.clone()),
+            ))
+    }
+
+    /// Verifies that `proof` proves the consensus state of the client `client_id` of the
+    /// counterparty chain at `consensus_height` to be `expected_consensus_state`.
+    pub fn verify_client_consensus_state(
+        &self,
+        proof: &CommitmentProofBytes,
+        client_id: &ClientId,
+        consensus_height: Height,
+        expected_consensus_state: &dyn ConsensusState,
+    ) -> Result<(), Error> {
+        self.client_state
+            .verify_client_consensus_state(
+                self.height,
+                self.connection_end.counterparty().prefix(),
+                proof,
+                self.consensus_state.root(),
+                client_id,
+                consensus_height,
+                expected_consensus_state,
+            )
+            .map_err(proof_verification_failed(
+                self.connection_end.client_id(),
+                self.height,
+                ClientConsensusStatePath::new(client_id.clone(), consensus_height),
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::core::ics03_connection::connection::{Counterparty, State};
+    use crate::core::ics03_connection::version::get_compatible_versions;
+    use crate::mock::client_state::MockClientState;
+    use crate::mock::consensus_state::MockConsensusState;
+    use crate::mock::context::MockContext;
+    use crate::mock::header::MockHeader;
+    use crate::timestamp::ZERO_DURATION;
+
+    #[test]
+    fn proofs_are_verified_against_a_single_client_read() {
+        let proof_height = Height::new(0, 10).unwrap();
+        let ctx = MockContext::default().with_client(&ClientId::default(), proof_height);
+        let conn_end = ConnectionEnd::new(
+            State::TryOpen,
+            ClientId::default(),
+            Counterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                b"ibc".to_vec().try_into().unwrap(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        let proof: CommitmentProofBytes = vec![1].try_into().unwrap();
+        let header = MockHeader::new(proof_height);
+
+        let verifier = ProofVerificationContext::new(&ctx, &conn_end, proof_height).unwrap();
+        assert_eq!(verifier.height(), proof_height);
+        assert!(verifier
+            .verify_connection_state(&proof, &ConnectionId::default(), &conn_end)
+            .is_ok());
+        assert!(verifier
+            .verify_client_full_state(
+                &proof,
+                &ClientId::default(),
+                MockClientState::new(header).into()
+            )
+            .is_ok());
+        assert!(verifier
+            .verify_client_consensus_state(
+                &proof,
+                &ClientId::default(),
+                proof_height,
+                &MockConsensusState::new(header)
+            )
+            .is_ok());
+
+        // There is no consensus state to verify proofs at other heights against.
+        assert!(ProofVerificationContext::new(&ctx, &conn_end, proof_height.increment()).is_err());
+    }
+}
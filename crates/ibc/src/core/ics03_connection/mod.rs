@@ -4,6 +4,7 @@
 pub mod connection;
 /// Context definitions (dependencies for the protocol).
 pub mod context;
+pub mod delay;
 pub mod error;
 pub mod events;
 /// Message processing logic (protocol) for ICS 03.
@@ -32,7 +32,7 @@ pub enum ConnectionMsg {
     ConnectionOpenConfirm(MsgConnectionOpenConfirm),
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
 
     use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
@@ -49,6 +49,13 @@ impl Msg for MsgConnectionOpenAck {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        if self.client_state_of_a_on_b.type_url.is_empty() {
+            return Err(Error::missing_client_state());
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgConnectionOpenAck> for MsgConnectionOpenAck {}
@@ -57,7 +64,7 @@ impl TryFrom<RawMsgConnectionOpenAck> for MsgConnectionOpenAck {
     type Error = Error;
 
     fn try_from(msg: RawMsgConnectionOpenAck) -> Result<Self, Self::Error> {
-        Ok(Self {
+        let domain_msg = Self {
             conn_id_on_a: msg
                 .connection_id
                 .parse()
@@ -86,7 +93,10 @@ impl TryFrom<RawMsgConnectionOpenAck> for MsgConnectionOpenAck {
                 .and_then(|raw_height| raw_height.try_into().ok())
                 .ok_or_else(Error::missing_consensus_height)?,
             signer: msg.signer.parse().map_err(Error::signer)?,
-        })
+        };
+        domain_msg.validate_basic()?;
+
+        Ok(domain_msg)
     }
 }
 
@@ -107,7 +117,7 @@ impl From<MsgConnectionOpenAck> for RawMsgConnectionOpenAck {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::core::ics02_client::height::Height;
     use crate::mock::client_state::MockClientState;
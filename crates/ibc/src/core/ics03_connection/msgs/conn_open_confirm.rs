@@ -70,7 +70,7 @@ impl From<MsgConnectionOpenConfirm> for RawMsgConnectionOpenConfirm {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::prelude::*;
     use ibc_proto::ibc::core::client::v1::Height;
@@ -70,7 +70,7 @@ impl From<MsgConnectionOpenInit> for RawMsgConnectionOpenInit {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::prelude::*;
     use ibc_proto::ibc::core::connection::v1::MsgConnectionOpenInit as RawMsgConnectionOpenInit;
@@ -61,6 +61,19 @@ impl Msg for MsgConnectionOpenTry {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        if self.client_state_of_b_on_a.type_url.is_empty() {
+            return Err(Error::missing_client_state());
+        }
+        if self.counterparty.connection_id().is_none() {
+            return Err(Error::invalid_counterparty());
+        }
+        if self.counterparty_versions.is_empty() {
+            return Err(Error::empty_versions());
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgConnectionOpenTry> for MsgConnectionOpenTry {}
@@ -75,11 +88,7 @@ impl TryFrom<RawMsgConnectionOpenTry> for MsgConnectionOpenTry {
             .map(Version::try_from)
             .collect::<Result<Vec<_>, _>>()?;
 
-        if counterparty_versions.is_empty() {
-            return Err(Error::empty_versions());
-        }
-
-        Ok(Self {
+        let domain_msg = Self {
             previous_connection_id: msg.previous_connection_id,
             client_id_on_b: msg.client_id.parse().map_err(Error::invalid_identifier)?,
             client_state_of_b_on_a: msg.client_state.ok_or_else(Error::missing_client_state)?,
@@ -107,7 +116,10 @@ impl TryFrom<RawMsgConnectionOpenTry> for MsgConnectionOpenTry {
                 .ok_or_else(Error::missing_consensus_height)?,
             delay_period: Duration::from_nanos(msg.delay_period),
             signer: msg.signer.parse().map_err(Error::signer)?,
-        })
+        };
+        domain_msg.validate_basic()?;
+
+        Ok(domain_msg)
     }
 }
 
@@ -134,7 +146,7 @@ impl From<MsgConnectionOpenTry> for RawMsgConnectionOpenTry {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::core::ics02_client::height::Height;
     use crate::mock::client_state::MockClientState;
@@ -260,6 +272,17 @@ mod tests {
                     },
                     want_pass: true,
                 },
+                Test {
+                    name: "Bad counterparty, missing connection id".to_string(),
+                    raw: RawMsgConnectionOpenTry {
+                        counterparty: Some(RawCounterparty {
+                            connection_id: "".to_string(),
+                            ..get_dummy_raw_counterparty()
+                        }),
+                        ..default_try_msg.clone()
+                    },
+                    want_pass: false,
+                },
                 Test {
                     name: "Bad counterparty versions, empty versions vec".to_string(),
                     raw: RawMsgConnectionOpenTry {
@@ -24,6 +24,34 @@ impl Version {
     pub fn is_supported_feature(&self, feature: String) -> bool {
         self.features.contains(&feature)
     }
+
+    /// Getter for the features supported by this version.
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// Adds the `ORDER_ORDERED_ALLOW_TIMEOUT` channel ordering to the features of this version.
+    /// Hosts that support such channels should advertise it from
+    /// [`ConnectionReader::get_compatible_versions`](crate::core::ics03_connection::context::ConnectionReader::get_compatible_versions).
+    pub fn with_ordered_allow_timeout(mut self) -> Self {
+        let feature = Order::OrderedAllowTimeout.as_str().to_owned();
+        if !self.features.contains(&feature) {
+            self.features.push(feature);
+        }
+        self
+    }
+
+    /// Checks whether this version is one of the `supported_versions`, i.e. whether one of them
+    /// has the same identifier and supports all the features of this version.
+    pub fn is_supported_by(&self, supported_versions: &[Version]) -> bool {
+        supported_versions.iter().any(|supported| {
+            supported.identifier == self.identifier
+                && self
+                    .features
+                    .iter()
+                    .all(|feature| supported.features.contains(feature))
+        })
+    }
 }
 
 impl Protobuf<RawVersion> for Version {}
@@ -83,7 +111,8 @@ pub fn get_compatible_versions() -> Vec<Version> {
     vec![Version::default()]
 }
 
-/// Selects a version from the intersection of locally supported and counterparty versions.
+/// Selects a version from the intersection of locally supported and counterparty versions. The
+/// features of the selected version are those supported by both sides.
 pub fn pick_version(
     supported_versions: Vec<Version>,
     counterparty_versions: Vec<Version>,
@@ -99,7 +128,16 @@ pub fn pick_version(
                     return Err(Error::empty_features());
                 }
             }
-            intersection.append(&mut vec![s.clone()]);
+            let features = s
+                .features
+                .iter()
+                .filter(|feature| c.features.contains(feature))
+                .cloned()
+                .collect();
+            intersection.push(Version {
+                identifier: s.identifier.clone(),
+                features,
+            });
         }
     }
     intersection.sort_by(|a, b| a.identifier.cmp(&b.identifier));
@@ -119,6 +157,7 @@ mod tests {
 
     use crate::core::ics03_connection::error::Error;
     use crate::core::ics03_connection::version::{get_compatible_versions, pick_version, Version};
+    use crate::core::ics04_channel::channel::Order;
 
     fn good_versions() -> Vec<RawVersion> {
         vec![
@@ -305,6 +344,22 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn negotiate_ordered_allow_timeout() {
+        let extended = Version::default().with_ordered_allow_timeout();
+        assert!(extended.is_supported_feature(Order::OrderedAllowTimeout.as_str().to_string()));
+        assert_eq!(extended.clone().with_ordered_allow_timeout(), extended);
+
+        // Only the features supported by both sides are negotiated.
+        let picked = pick_version(vec![extended.clone()], get_compatible_versions()).unwrap();
+        assert_eq!(picked, Version::default());
+        let picked = pick_version(vec![extended.clone()], vec![extended.clone()]).unwrap();
+        assert_eq!(picked, extended);
+
+        assert!(!extended.is_supported_by(&get_compatible_versions()));
+        assert!(Version::default().is_supported_by(&[extended]));
+    }
     #[test]
     fn serialize() {
         let def = Version::default();
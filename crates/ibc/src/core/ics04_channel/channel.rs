@@ -4,7 +4,9 @@ use crate::utils::pretty::PrettySlice;
 use core::fmt::{Display, Error as FmtError, Formatter};
 use core::str::FromStr;
 
-use ibc_proto::protobuf::Protobuf;
+use ibc_proto::protobuf::{Error as ProtoError, Protobuf};
+use prost::bytes::Buf;
+use prost::Message;
 use serde::{Deserialize, Serialize};
 
 use ibc_proto::ibc::core::channel::v1::{
@@ -12,6 +14,7 @@ use ibc_proto::ibc::core::channel::v1::{
     IdentifiedChannel as RawIdentifiedChannel,
 };
 
+use crate::core::ics04_channel::raw::Channel as RawUpgradableChannel;
 use crate::core::ics04_channel::{error::Error, Version};
 use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
 
@@ -80,14 +83,17 @@ pub struct ChannelEnd {
     pub remote: Counterparty,
     pub connection_hops: Vec<ConnectionId>,
     pub version: Version,
+    /// The sequence of the last upgrade attempted on this channel end, 0 if it was never upgraded.
+    #[serde(default)]
+    pub upgrade_sequence: u64,
 }
 
 impl Display for ChannelEnd {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         write!(
             f,
-            "ChannelEnd {{ state: {}, ordering: {}, remote: {}, connection_hops: {}, version: {} }}",
-            self.state, self.ordering, self.remote, PrettySlice(&self.connection_hops), self.version
+            "ChannelEnd {{ state: {}, ordering: {}, remote: {}, connection_hops: {}, version: {}, upgrade_sequence: {} }}",
+            self.state, self.ordering, self.remote, PrettySlice(&self.connection_hops), self.version, self.upgrade_sequence
         )
     }
 }
@@ -100,11 +106,73 @@ impl Default for ChannelEnd {
             remote: Counterparty::default(),
             connection_hops: Vec::new(),
             version: Version::default(),
+            upgrade_sequence: 0,
         }
     }
 }
 
-impl Protobuf<RawChannel> for ChannelEnd {}
+/// The channel ends are encoded as the `Channel` of ibc-go v8, which `RawChannel` predates: the
+/// upgrade sequence is encoded as well, if any, and thus covered by the proofs of channel ends.
+impl Protobuf<RawChannel> for ChannelEnd {
+    fn encode(&self, buf: &mut Vec<u8>) -> Result<(), ProtoError> {
+        RawUpgradableChannel::from(self.clone())
+            .encode(buf)
+            .map_err(ProtoError::encode_message)
+    }
+
+    fn encode_length_delimited(&self, buf: &mut Vec<u8>) -> Result<(), ProtoError> {
+        RawUpgradableChannel::from(self.clone())
+            .encode_length_delimited(buf)
+            .map_err(ProtoError::encode_message)
+    }
+
+    fn decode<B: Buf>(buf: B) -> Result<Self, ProtoError> {
+        let raw = RawUpgradableChannel::decode(buf).map_err(ProtoError::decode_message)?;
+        Self::try_from(raw).map_err(ProtoError::try_from::<RawUpgradableChannel, Self, _>)
+    }
+
+    fn decode_length_delimited<B: Buf>(buf: B) -> Result<Self, ProtoError> {
+        let raw = RawUpgradableChannel::decode_length_delimited(buf)
+            .map_err(ProtoError::decode_message)?;
+        Self::try_from(raw).map_err(ProtoError::try_from::<RawUpgradableChannel, Self, _>)
+    }
+
+    fn encoded_len(&self) -> usize {
+        RawUpgradableChannel::from(self.clone()).encoded_len()
+    }
+}
+
+impl TryFrom<RawUpgradableChannel> for ChannelEnd {
+    type Error = Error;
+
+    fn try_from(value: RawUpgradableChannel) -> Result<Self, Self::Error> {
+        let channel_end = ChannelEnd::try_from(RawChannel {
+            state: value.state,
+            ordering: value.ordering,
+            counterparty: value.counterparty,
+            connection_hops: value.connection_hops,
+            version: value.version,
+        })?;
+
+        Ok(channel_end.with_upgrade_sequence(value.upgrade_sequence))
+    }
+}
+
+impl From<ChannelEnd> for RawUpgradableChannel {
+    fn from(value: ChannelEnd) -> Self {
+        let upgrade_sequence = value.upgrade_sequence;
+        let raw = RawChannel::from(value);
+
+        RawUpgradableChannel {
+            state: raw.state,
+            ordering: raw.ordering,
+            counterparty: raw.counterparty,
+            connection_hops: raw.connection_hops,
+            version: raw.version,
+            upgrade_sequence,
+        }
+    }
+}
 
 impl TryFrom<RawChannel> for ChannelEnd {
     type Error = Error;
@@ -175,6 +243,15 @@ impl ChannelEnd {
             remote,
             connection_hops,
             version,
+            upgrade_sequence: 0,
+        }
+    }
+
+    /// Sets the sequence of the last upgrade attempted on this channel end.
+    pub fn with_upgrade_sequence(self, upgrade_sequence: u64) -> Self {
+        Self {
+            upgrade_sequence,
+            ..self
         }
     }
 
@@ -216,12 +293,14 @@ impl ChannelEnd {
         &self.version
     }
 
+    pub fn upgrade_sequence(&self) -> u64 {
+        self.upgrade_sequence
+    }
+
     pub fn validate_basic(&self) -> Result<(), Error> {
-        if self.connection_hops.len() != 1 {
-            return Err(Error::invalid_connection_hops_length(
-                1,
-                self.connection_hops.len(),
-            ));
+        // A channel goes through at least one connection, several ones for multihop channels.
+        if self.connection_hops.is_empty() {
+            return Err(Error::invalid_connection_hops_length(1, 0));
         }
         self.counterparty().validate_basic()
     }
@@ -328,6 +407,9 @@ pub enum Order {
     None = 0,
     Unordered = 1,
     Ordered = 2,
+    /// Packets are delivered in order, like on `Ordered` channels, but a timed-out packet is
+    /// skipped instead of closing the channel.
+    OrderedAllowTimeout = 3,
 }
 
 impl Default for Order {
@@ -350,15 +432,24 @@ impl Order {
             Self::None => "ORDER_NONE_UNSPECIFIED",
             Self::Unordered => "ORDER_UNORDERED",
             Self::Ordered => "ORDER_ORDERED",
+            Self::OrderedAllowTimeout => "ORDER_ORDERED_ALLOW_TIMEOUT",
         }
     }
 
+    /// Whether packets on channels with this ordering are received and acknowledged in
+    /// sequence, i.e. whether the channel keeps track of the next sequence to receive and
+    /// acknowledge.
+    pub fn is_ordered(&self) -> bool {
+        matches!(self, Self::Ordered | Self::OrderedAllowTimeout)
+    }
+
     // Parses the Order out from a i32.
     pub fn from_i32(nr: i32) -> Result<Self, Error> {
         match nr {
             0 => Ok(Self::None),
             1 => Ok(Self::Unordered),
             2 => Ok(Self::Ordered),
+            3 => Ok(Self::OrderedAllowTimeout),
             _ => Err(Error::unknown_order_type(nr.to_string())),
         }
     }
@@ -372,6 +463,7 @@ impl FromStr for Order {
             "uninitialized" => Ok(Self::None),
             "unordered" => Ok(Self::Unordered),
             "ordered" => Ok(Self::Ordered),
+            "ordered_allow_timeout" => Ok(Self::OrderedAllowTimeout),
             _ => Err(Error::unknown_order_type(s.to_string())),
         }
     }
@@ -384,6 +476,11 @@ pub enum State {
     TryOpen = 2,
     Open = 3,
     Closed = 4,
+    /// The channel is upgrading and waits for the packets in flight to be acknowledged or timed
+    /// out. Only reachable on hosts supporting channel upgrades.
+    Flushing = 5,
+    /// All packets in flight of an upgrading channel were flushed.
+    FlushComplete = 6,
 }
 
 impl State {
@@ -395,6 +492,8 @@ impl State {
             Self::TryOpen => "TRYOPEN",
             Self::Open => "OPEN",
             Self::Closed => "CLOSED",
+            Self::Flushing => "FLUSHING",
+            Self::FlushComplete => "FLUSHCOMPLETE",
         }
     }
 
@@ -406,6 +505,8 @@ impl State {
             2 => Ok(Self::TryOpen),
             3 => Ok(Self::Open),
             4 => Ok(Self::Closed),
+            5 => Ok(Self::Flushing),
+            6 => Ok(Self::FlushComplete),
             _ => Err(Error::unknown_state(s)),
         }
     }
@@ -415,6 +516,11 @@ impl State {
         self == State::Open
     }
 
+    /// Returns whether or not this channel state is one of the upgrade flushing states.
+    pub fn is_flushing(self) -> bool {
+        matches!(self, State::Flushing | State::FlushComplete)
+    }
+
     /// Returns whether or not the channel with this state
     /// has progressed less or the same than the argument.
     ///
@@ -436,7 +542,7 @@ impl Display for State {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
     use crate::prelude::*;
@@ -565,6 +671,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn channel_end_upgrade_sequence_encoding() {
+        use ibc_proto::protobuf::Protobuf;
+        use prost::Message;
+
+        let raw = get_dummy_raw_channel_end();
+        let channel_end = ChannelEnd::try_from(raw.clone()).unwrap();
+
+        // Channel ends that were never upgraded are encoded as before.
+        assert_eq!(channel_end.encode_vec().unwrap(), raw.encode_to_vec());
+
+        let upgraded = channel_end.with_upgrade_sequence(3);
+        let encoded = upgraded.encode_vec().unwrap();
+        assert_ne!(encoded, raw.encode_to_vec());
+        assert_eq!(
+            <ChannelEnd as Protobuf<RawChannel>>::decode_vec(&encoded).unwrap(),
+            upgraded
+        );
+    }
+
     #[test]
     fn parse_channel_ordering_type() {
         use super::Order;
@@ -590,6 +716,11 @@ mod tests {
                 want_res: Order::Ordered,
                 want_err: false,
             },
+            Test {
+                ordering: "ORDER_ORDERED_ALLOW_TIMEOUT",
+                want_res: Order::OrderedAllowTimeout,
+                want_err: false,
+            },
             Test {
                 ordering: "UNKNOWN_ORDER",
                 want_res: Order::None,
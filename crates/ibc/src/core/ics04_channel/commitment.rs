@@ -2,6 +2,50 @@ use crate::prelude::*;
 
 use serde_derive::{Deserialize, Serialize};
 
+use crate::core::ics04_channel::timeout::TimeoutHeight;
+use crate::timestamp::Timestamp;
+
+/// The layout of the preimage hashed into a packet commitment.
+///
+/// Counterparties must agree on the layout for proofs of packet commitments to verify, so a
+/// change to it has to be rolled out as a new version rather than an edit of an existing one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub enum PacketCommitmentVersion {
+    /// `hash(timeout_timestamp || timeout_revision_number || timeout_revision_height ||
+    /// hash(data))`, with all integers big-endian `u64`s, as in ibc-go's `CommitPacket`.
+    V1,
+}
+
+impl Default for PacketCommitmentVersion {
+    fn default() -> Self {
+        Self::V1
+    }
+}
+
+impl PacketCommitmentVersion {
+    /// Computes the commitment for a packet, with `hash` being the host's commitment hash
+    /// function.
+    pub fn commit_packet(
+        &self,
+        hash: impl Fn(Vec<u8>) -> Vec<u8>,
+        packet_data: Vec<u8>,
+        timeout_height: TimeoutHeight,
+        timeout_timestamp: Timestamp,
+    ) -> PacketCommitment {
+        match self {
+            Self::V1 => {
+                let mut hash_input = timeout_timestamp.nanoseconds().to_be_bytes().to_vec();
+                hash_input.extend(timeout_height.commitment_revision_number().to_be_bytes());
+                hash_input.extend(timeout_height.commitment_revision_height().to_be_bytes());
+                hash_input.extend(hash(packet_data));
+
+                hash(hash_input).into()
+            }
+        }
+    }
+}
+
 /// Packet commitment
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PacketCommitment(Vec<u8>);
@@ -45,3 +89,50 @@ impl From<Vec<u8>> for AcknowledgementCommitment {
         Self(bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sha2::Digest;
+    use subtle_encoding::hex;
+
+    use crate::Height;
+
+    fn sha256(value: Vec<u8>) -> Vec<u8> {
+        sha2::Sha256::digest(value).to_vec()
+    }
+
+    /// Expected values follow ibc-go's `channeltypes.CommitPacket` for the same inputs.
+    #[test]
+    fn v1_matches_ibc_go() {
+        let tests = [
+            (
+                br#"{"amount":"100","denom":"uatom","receiver":"cosmos1receiver","sender":"cosmos1sender"}"#
+                    .to_vec(),
+                TimeoutHeight::At(Height::new(1, 100).unwrap()),
+                Timestamp::none(),
+                "0f1d7204e4479dade4546e370332e09973c463da21bfa4e06bd71cc95e7997e6",
+            ),
+            (
+                b"hello".to_vec(),
+                TimeoutHeight::Never,
+                Timestamp::from_nanoseconds(1_700_000_000_000_000_000).unwrap(),
+                "da813c01f2c4ba8409b3a7bb9cb5756dbf875bbb31da91770d5de73180bb0828",
+            ),
+        ];
+
+        for (data, timeout_height, timeout_timestamp, expected) in tests {
+            let commitment = PacketCommitmentVersion::V1.commit_packet(
+                sha256,
+                data,
+                timeout_height,
+                timeout_timestamp,
+            );
+            assert_eq!(
+                String::from_utf8(hex::encode(commitment.as_ref())).unwrap(),
+                expected
+            );
+        }
+    }
+}
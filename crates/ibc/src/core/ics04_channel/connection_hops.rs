@@ -0,0 +1,230 @@
+//! Resolution of the `connection_hops` of a channel into the connection ends they refer to.
+
+use crate::core::ics03_connection::connection::{ConnectionEnd, State as ConnectionState};
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::multihop::MultihopProof;
+use crate::core::ics23_commitment::commitment::CommitmentProofBytes;
+use crate::core::ics24_host::identifier::ConnectionId;
+use crate::prelude::*;
+
+/// The connection ends along which a channel is routed, ordered from the host chain outwards.
+///
+/// Only the connection end on the host chain is loaded from the host. The ones of the other hops
+/// of a multihop channel are stored on intermediate chains, and are attached from the multihop
+/// proof of a message with [`ConnectionHops::with_remote_hops`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionHops {
+    hops: Vec<(ConnectionId, ConnectionEnd)>,
+    connection_hops: Vec<ConnectionId>,
+}
+
+impl ConnectionHops {
+    /// Loads the connection end of the first hop in `connection_hops`, which is the one on the
+    /// host chain.
+    pub fn resolve<Ctx>(ctx: &Ctx, connection_hops: &[ConnectionId]) -> Result<Self, Error>
+    where
+        Ctx: ChannelReader + ?Sized,
+    {
+        let connection_id = connection_hops
+            .first()
+            .ok_or_else(|| Error::invalid_connection_hops_length(1, 0))?;
+        let connection_end = ctx.connection_end(connection_id)?;
+
+        Ok(Self {
+            hops: vec![(connection_id.clone(), connection_end)],
+            connection_hops: connection_hops.to_vec(),
+        })
+    }
+
+    /// Loads the connection end on the host chain and checks that it is open.
+    pub fn resolve_open<Ctx>(ctx: &Ctx, connection_hops: &[ConnectionId]) -> Result<Self, Error>
+    where
+        Ctx: ChannelReader + ?Sized,
+    {
+        let hops = Self::resolve(ctx, connection_hops)?;
+
+        for (connection_id, connection_end) in hops.iter() {
+            if !connection_end.state_matches(&ConnectionState::Open) {
+                return Err(Error::connection_not_open(connection_id.clone()));
+            }
+        }
+
+        Ok(hops)
+    }
+
+    /// Attaches the connection ends of the other hops of a multihop channel, as claimed by the
+    /// multihop `proof` of a message, so that the counterparty hops can be derived from them.
+    /// They are only trusted once the proof is verified, along with the key it proves.
+    ///
+    /// Does nothing for a single-hop channel.
+    pub fn with_remote_hops(mut self, proof: &CommitmentProofBytes) -> Result<Self, Error> {
+        if !self.is_multihop() || self.hops.len() == self.connection_hops.len() {
+            return Ok(self);
+        }
+
+        let proof = MultihopProof::decode_proof(proof)?;
+        self.check_remote_hops(&proof)?;
+        self.hops.extend(
+            proof
+                .hops
+                .into_iter()
+                .map(|hop| (hop.connection_id, hop.connection_end)),
+        );
+
+        Ok(self)
+    }
+
+    /// Checks that the hops of a multihop `proof` are the ones of the channel.
+    pub(crate) fn check_remote_hops(&self, proof: &MultihopProof) -> Result<(), Error> {
+        let remote_hops = &self.connection_hops[1..];
+        if proof.hops.len() != remote_hops.len() {
+            return Err(Error::invalid_multihop_proof(format!(
+                "proof of {} intermediate chains for a channel with {} connection hops",
+                proof.hops.len(),
+                self.connection_hops.len()
+            )));
+        }
+        for (hop, connection_id) in proof.hops.iter().zip(remote_hops) {
+            if &hop.connection_id != connection_id {
+                return Err(Error::invalid_multihop_proof(format!(
+                    "proof of connection {} instead of connection hop {}",
+                    hop.connection_id, connection_id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the channel goes through intermediate chains.
+    pub fn is_multihop(&self) -> bool {
+        self.connection_hops.len() > 1
+    }
+
+    /// The identifier of the connection on the host chain.
+    pub fn connection_id(&self) -> &ConnectionId {
+        &self.hops[0].0
+    }
+
+    /// The connection end on the host chain.
+    pub fn connection_end(&self) -> &ConnectionEnd {
+        &self.hops[0].1
+    }
+
+    /// The `connection_hops` that the counterparty channel end is expected to hold. The remote
+    /// hops of a multihop channel must have been attached first.
+    pub fn counterparty_hops(&self) -> Result<Vec<ConnectionId>, Error> {
+        if self.hops.len() != self.connection_hops.len() {
+            return Err(Error::invalid_multihop_proof(
+                "the connection ends of the intermediate chains are unknown".to_string(),
+            ));
+        }
+
+        self.hops
+            .iter()
+            .rev()
+            .map(|(connection_id, connection_end)| {
+                connection_end
+                    .counterparty()
+                    .connection_id()
+                    .cloned()
+                    .ok_or_else(|| Error::undefined_connection_counterparty(connection_id.clone()))
+            })
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(ConnectionId, ConnectionEnd)> {
+        self.hops.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::time::Duration;
+
+    use crate::core::ics03_connection::connection::Counterparty;
+    use crate::core::ics03_connection::version::get_compatible_versions;
+    use crate::core::ics04_channel::error::ErrorDetail;
+    use crate::core::ics04_channel::multihop::test_util::get_dummy_multihop_proof;
+    use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+    use crate::core::ics24_host::identifier::ClientId;
+    use crate::mock::context::MockContext;
+
+    fn connection_end(state: ConnectionState) -> ConnectionEnd {
+        ConnectionEnd::new(
+            state,
+            ClientId::default(),
+            Counterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::new(7)),
+                CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
+            ),
+            get_compatible_versions(),
+            Duration::ZERO,
+        )
+    }
+
+    #[test]
+    fn resolves_open_hop() {
+        let ctx = MockContext::default()
+            .with_connection(ConnectionId::new(0), connection_end(ConnectionState::Open));
+
+        let hops = ConnectionHops::resolve_open(&ctx, &[ConnectionId::new(0)]).unwrap();
+        assert_eq!(hops.connection_id(), &ConnectionId::new(0));
+        assert_eq!(
+            hops.counterparty_hops().unwrap(),
+            vec![ConnectionId::new(7)]
+        );
+    }
+
+    #[test]
+    fn resolves_multihop_with_remote_hops() {
+        let ctx = MockContext::default()
+            .with_connection(ConnectionId::new(0), connection_end(ConnectionState::Open));
+        let connection_hops = [ConnectionId::new(0), ConnectionId::new(1)];
+        let proof = get_dummy_multihop_proof(ConnectionId::new(1), ConnectionId::new(8), 10)
+            .encode_proof()
+            .unwrap();
+
+        let hops = ConnectionHops::resolve_open(&ctx, &connection_hops).unwrap();
+        assert!(hops.is_multihop());
+        assert!(hops.counterparty_hops().is_err());
+
+        let hops = hops.with_remote_hops(&proof).unwrap();
+        assert_eq!(hops.connection_id(), &ConnectionId::new(0));
+        assert_eq!(
+            hops.counterparty_hops().unwrap(),
+            vec![ConnectionId::new(8), ConnectionId::new(7)]
+        );
+
+        // The proof must be the one of the intermediate connection of the channel.
+        let other_hops = [ConnectionId::new(0), ConnectionId::new(2)];
+        let err = ConnectionHops::resolve_open(&ctx, &other_hops)
+            .unwrap()
+            .with_remote_hops(&proof)
+            .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::InvalidMultihopProof(_)));
+    }
+
+    #[test]
+    fn rejects_invalid_hops() {
+        let ctx = MockContext::default()
+            .with_connection(ConnectionId::new(0), connection_end(ConnectionState::Init));
+
+        let err = ConnectionHops::resolve(&ctx, &[]).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::InvalidConnectionHopsLength(_)
+        ));
+
+        let err = ConnectionHops::resolve(&ctx, &[ConnectionId::new(1)]).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::Ics03Connection(_)));
+
+        assert!(ConnectionHops::resolve(&ctx, &[ConnectionId::new(0)]).is_ok());
+        let err = ConnectionHops::resolve_open(&ctx, &[ConnectionId::new(0)]).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::ConnectionNotOpen(_)));
+    }
+}
@@ -3,22 +3,31 @@
 //!
 use crate::core::ics02_client::client_state::ClientState;
 use core::time::Duration;
-use num_traits::float::FloatCore;
 
 use crate::core::ics02_client::consensus_state::ConsensusState;
 use crate::core::ics03_connection::connection::ConnectionEnd;
+pub use crate::core::ics03_connection::delay::calculate_block_delay;
 use crate::core::ics04_channel::channel::ChannelEnd;
-use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCommitment};
+use crate::core::ics04_channel::commitment::{
+    AcknowledgementCommitment, PacketCommitment, PacketCommitmentVersion,
+};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::handler::recv_packet::RecvPacketResult;
 use crate::core::ics04_channel::handler::{ChannelIdState, ChannelResult};
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::handler::{UpgradeChange, UpgradeResult};
+use crate::core::ics04_channel::handshake::HandshakeStart;
 use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement;
+use crate::core::ics04_channel::pruning::PacketRecord;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::upgrade::{ErrorReceipt, Upgrade};
 use crate::core::ics04_channel::{error::Error, packet::Receipt};
-use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+use crate::core::ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId};
 use crate::prelude::*;
 use crate::timestamp::Timestamp;
 use crate::Height;
 
-use super::packet::{PacketResult, Sequence};
+use super::packet::{Packet, PacketResult, Sequence};
 use super::timeout::TimeoutHeight;
 
 /// A context supplying all the necessary read-only dependencies for processing any `ChannelMsg`.
@@ -41,6 +50,13 @@ pub trait ChannelReader {
         height: Height,
     ) -> Result<Box<dyn ConsensusState>, Error>;
 
+    /// Returns the latest height of the client, as `ClientReader::client_latest_height`. Hosts
+    /// that record it apart override this method to spare decoding the client state.
+    fn client_latest_height(&self, client_id: &ClientId) -> Result<Height, Error> {
+        self.client_state(client_id)
+            .map(|client_state| client_state.latest_height())
+    }
+
     fn get_next_sequence_send(
         &self,
         port_id: &PortId,
@@ -91,18 +107,96 @@ pub trait ChannelReader {
         timeout_height: TimeoutHeight,
         timeout_timestamp: Timestamp,
     ) -> PacketCommitment {
-        let mut hash_input = timeout_timestamp.nanoseconds().to_be_bytes().to_vec();
+        self.packet_commitment_version().commit_packet(
+            |value| self.hash(value),
+            packet_data,
+            timeout_height,
+            timeout_timestamp,
+        )
+    }
+
+    /// The packet commitment layout used by this host. Defaults to the one of the current spec.
+    fn packet_commitment_version(&self) -> PacketCommitmentVersion {
+        PacketCommitmentVersion::default()
+    }
+
+    /// Whether this host supports channel upgrades. When disabled (the default), packets are only
+    /// handled on channels that are not in one of the upgrade flushing states.
+    #[cfg(any(test, feature = "unstable"))]
+    fn channel_upgrades_enabled(&self) -> bool {
+        false
+    }
+
+    /// The next send sequence the counterparty recorded when it started flushing the channel
+    /// for an upgrade, if it did. Packets from this sequence onwards were sent after the
+    /// counterparty started flushing and must not be received. Defaults to the one of the
+    /// counterparty upgrade recorded by the upgrade handshake.
+    #[cfg(any(test, feature = "unstable"))]
+    fn counterparty_upgrade_next_sequence_send(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Option<Sequence> {
+        self.counterparty_upgrade(port_id, channel_id)
+            .ok()
+            .flatten()
+            .map(|upgrade| upgrade.next_sequence_send)
+    }
+
+    /// Returns the upgrade in progress of the given channel end, if any, as stored via
+    /// `ChannelKeeper::store_upgrade`. Only required for hosts that enable channel upgrades.
+    #[cfg(any(test, feature = "unstable"))]
+    fn channel_upgrade(
+        &self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<Option<Upgrade>, Error> {
+        Ok(None)
+    }
+
+    /// Returns the upgrade of the counterparty of the given channel end, as recorded once it
+    /// started flushing via `ChannelKeeper::store_counterparty_upgrade`.
+    #[cfg(any(test, feature = "unstable"))]
+    fn counterparty_upgrade(
+        &self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<Option<Upgrade>, Error> {
+        Ok(None)
+    }
 
-        let revision_number = timeout_height.commitment_revision_number().to_be_bytes();
-        hash_input.append(&mut revision_number.to_vec());
+    /// Returns the receipt of the last failed upgrade of the given channel end, if any.
+    #[cfg(any(test, feature = "unstable"))]
+    fn upgrade_error_receipt(
+        &self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<Option<ErrorReceipt>, Error> {
+        Ok(None)
+    }
 
-        let revision_height = timeout_height.commitment_revision_height().to_be_bytes();
-        hash_input.append(&mut revision_height.to_vec());
+    /// The time after which the counterparty may time out an upgrade once the channel end
+    /// started flushing. Defaults to 10 minutes, as in ibc-go.
+    #[cfg(any(test, feature = "unstable"))]
+    fn upgrade_timeout(&self) -> Duration {
+        Duration::from_secs(600)
+    }
 
-        let packet_data_hash = self.hash(packet_data);
-        hash_input.append(&mut packet_data_hash.to_vec());
+    /// Whether packets sent on the given channel end are still neither acknowledged nor timed
+    /// out. The default looks up the commitments of all the packets sent so far, which hosts
+    /// enabling channel upgrades should replace with a lookup in their packet commitment store.
+    #[cfg(any(test, feature = "unstable"))]
+    fn has_inflight_packets(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<bool, Error> {
+        let next_sequence_send = u64::from(self.get_next_sequence_send(port_id, channel_id)?);
 
-        self.hash(hash_input).into()
+        Ok((1..next_sequence_send).any(|sequence| {
+            self.get_packet_commitment(port_id, channel_id, sequence.into())
+                .is_ok()
+        }))
     }
 
     fn ack_commitment(&self, ack: Acknowledgement) -> AcknowledgementCommitment {
@@ -129,10 +223,14 @@ pub trait ChannelReader {
     /// Returns the pending `ConsensusState` of the host (local) chain.
     fn pending_host_consensus_state(&self) -> Result<Box<dyn ConsensusState>, Error>;
 
-    /// Returns the time when the client state for the given [`ClientId`] was updated with a header for the given [`Height`]
+    /// Returns the time when the client state for the given [`ClientId`] was updated with a header for the given [`Height`],
+    /// as recorded by `ClientKeeper::store_update_time`. The delay period of a connection counts
+    /// from it.
     fn client_update_time(&self, client_id: &ClientId, height: Height) -> Result<Timestamp, Error>;
 
-    /// Returns the height when the client state for the given [`ClientId`] was updated with a header for the given [`Height`]
+    /// Returns the height when the client state for the given [`ClientId`] was updated with a header for the given [`Height`],
+    /// as recorded by `ClientKeeper::store_update_height`. See
+    /// [`ChannelReader::client_update_time`].
     fn client_update_height(&self, client_id: &ClientId, height: Height) -> Result<Height, Error>;
 
     /// Returns a counter on the number of channel ids have been created thus far.
@@ -140,14 +238,143 @@ pub trait ChannelReader {
     /// `ChannelKeeper::increase_channel_counter`.
     fn channel_counter(&self) -> Result<u64, Error>;
 
-    /// Returns the maximum expected time per block
+    /// Returns the prefix of the identifiers generated for new channels on `port_id`, which
+    /// defaults to `channel`. Hosts override it to namespace their channel identifiers, e.g. per
+    /// contract, which must remain valid ICS-024 identifiers.
+    fn channel_id_prefix(&self, _port_id: &PortId) -> String {
+        ChannelId::prefix().to_string()
+    }
+
+    /// Returns the maximum expected time per block of the host, see
+    /// [`ConnectionReader::max_expected_time_per_block`](crate::core::ics03_connection::context::ConnectionReader::max_expected_time_per_block),
+    /// which hosts implementing both traits forward this method to.
     fn max_expected_time_per_block(&self) -> Duration;
 
     /// Calculates the block delay period using the connection's delay period and the maximum
-    /// expected time per block.
+    /// expected time per block, see [`calculate_block_delay`].
     fn block_delay(&self, delay_period_time: Duration) -> u64 {
         calculate_block_delay(delay_period_time, self.max_expected_time_per_block())
     }
+
+    /// Whether `ReceivePacket`, `AcknowledgePacket` and `TimeoutPacket` events should carry a
+    /// `packet_relayer` attribute with the address of the relayer that submitted the message.
+    /// Disabled by default, for byte-compatibility with the events emitted by ibc-go.
+    fn packet_events_with_relayer(&self) -> bool {
+        false
+    }
+
+    /// Returns the identifier of the chain at the other end of the channel, as tracked by the
+    /// client of its connection.
+    fn channel_counterparty_chain_id(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ChainId, Error> {
+        let channel_end = self.channel_end(port_id, channel_id)?;
+        let hops = ConnectionHops::resolve(self, channel_end.connection_hops())?;
+        let client_state = self.client_state(hops.connection_end().client_id())?;
+
+        Ok(client_state.chain_id())
+    }
+
+    /// Opt-in policy closing channels whose packets keep timing out: once this many packets sent
+    /// on the channel timed out in a row, the channel is closed as if its application had sent a
+    /// `MsgChannelCloseInit`, see [`timeout_close`](crate::core::ics04_channel::timeout_close).
+    /// Disabled (`None`) by default.
+    fn max_consecutive_timeouts(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Option<u64> {
+        None
+    }
+
+    /// Returns the number of packets sent on the channel that timed out since the last one was
+    /// acknowledged, as recorded via `ChannelKeeper::store_consecutive_timeouts`. Only required
+    /// for hosts that enable `max_consecutive_timeouts`.
+    fn consecutive_timeouts(
+        &self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<u64, Error> {
+        Ok(0)
+    }
+
+    /// Whether the channel was paused via `ChannelKeeper::store_channel_paused`, in which case
+    /// it neither sends nor receives packets, see [`pause`](crate::core::ics04_channel::pause).
+    /// Hosts that do not support pausing channels can rely on the default, which reports none.
+    fn is_channel_paused(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    /// Returns all channel handshakes whose start was recorded via
+    /// `ChannelKeeper::store_channel_handshake_start` and not yet deleted.
+    /// Hosts that do not support handshake expiry can rely on the default, which reports none.
+    fn pending_channel_handshakes(
+        &self,
+    ) -> Result<Vec<(PortId, ChannelId, HandshakeStart)>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Whether this host implements `ChannelKeeper::delete_channel`. Hosts recording handshake
+    /// starts without it have `cleanup_stale_handshakes` keep the stale channel ends, with a
+    /// warning.
+    fn supports_channel_deletion(&self) -> bool {
+        false
+    }
+
+    /// Returns all packet records stored via `ChannelKeeper::store_packet_record` and not yet
+    /// deleted, oldest first. Hosts that do not support pruning can rely on the default, which
+    /// reports none.
+    fn packet_records(&self) -> Result<Vec<PacketRecord>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the trusting period of the client of the host on the counterparty chain of the
+    /// channel, during which the counterparty may verify the proofs of packet records at past
+    /// heights of the host: they are never pruned within it.
+    ///
+    /// Defaults to the trusting period of the client of the connection of the channel, i.e. of
+    /// the host client of the counterparty chain, as both are typically configured alike.
+    fn counterparty_trusting_period(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Option<Duration>, Error> {
+        let channel_end = self.channel_end(port_id, channel_id)?;
+        let connection_end = self.connection_end(&channel_end.connection_hops()[0])?;
+        Ok(self
+            .client_state(connection_end.client_id())?
+            .trusting_period())
+    }
+}
+
+/// Same as [`ChannelReader::channel_upgrades_enabled`], which only exists with the `unstable`
+/// feature: channel upgrades are disabled otherwise.
+pub(crate) fn channel_upgrades_enabled<Ctx: ChannelReader + ?Sized>(ctx: &Ctx) -> bool {
+    #[cfg(any(test, feature = "unstable"))]
+    {
+        ctx.channel_upgrades_enabled()
+    }
+    #[cfg(not(any(test, feature = "unstable")))]
+    {
+        let _ = ctx;
+        false
+    }
+}
+
+/// Same as [`ChannelReader::counterparty_upgrade_next_sequence_send`], which only exists with the
+/// `unstable` feature.
+pub(crate) fn counterparty_upgrade_next_sequence_send<Ctx: ChannelReader + ?Sized>(
+    ctx: &Ctx,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> Option<Sequence> {
+    #[cfg(any(test, feature = "unstable"))]
+    {
+        ctx.counterparty_upgrade_next_sequence_send(port_id, channel_id)
+    }
+    #[cfg(not(any(test, feature = "unstable")))]
+    {
+        let _ = (ctx, port_id, channel_id);
+        None
+    }
 }
 
 /// A context supplying all the necessary write-only dependencies (i.e., storage writing facility)
@@ -209,16 +436,30 @@ pub trait ChannelKeeper {
                     channel_id,
                     next_seq_recv,
                 } => self.store_next_sequence_recv(port_id, channel_id, next_seq_recv)?,
+                RecvPacketResult::TimedOut {
+                    port_id,
+                    channel_id,
+                    sequence,
+                    next_seq_recv,
+                } => {
+                    self.store_packet_receipt(
+                        port_id.clone(),
+                        channel_id.clone(),
+                        sequence,
+                        Receipt::Timeout,
+                    )?;
+                    self.store_next_sequence_recv(port_id, channel_id, next_seq_recv)?;
+                }
                 RecvPacketResult::Unordered {
                     port_id,
                     channel_id,
                     sequence,
                     receipt,
                 } => self.store_packet_receipt(port_id, channel_id, sequence, receipt)?,
-                RecvPacketResult::NoOp => unreachable!(),
+                RecvPacketResult::NoOp => {}
             },
             PacketResult::WriteAck(res) => {
-                self.store_packet_acknowledgement(
+                self.queue_packet_acknowledgement(
                     res.port_id,
                     res.channel_id,
                     res.seq,
@@ -234,11 +475,16 @@ pub trait ChannelKeeper {
             }
             PacketResult::Timeout(res) => {
                 self.delete_packet_commitment(&res.port_id, &res.channel_id, res.seq)?;
+                if let Some(s) = res.next_seq_ack {
+                    // Ordered-allow-timeout channel: skips the timed-out packet
+                    self.store_next_sequence_ack(res.port_id.clone(), res.channel_id.clone(), s)?;
+                }
                 if let Some(c) = res.channel {
                     // Ordered Channel: closes channel
                     self.store_channel(res.port_id, res.channel_id, c)?;
                 }
             }
+            PacketResult::NoOp => {}
         }
         Ok(())
     }
@@ -281,6 +527,48 @@ pub trait ChannelKeeper {
         sequence: Sequence,
     ) -> Result<(), Error>;
 
+    /// Queues an acknowledgement commitment, to be written along with the others of the block by
+    /// [`ChannelKeeper::flush_acks`]. By default, the commitment is written right away.
+    ///
+    /// Hosts batching acknowledgements, e.g. for high-throughput unordered channels, must serve
+    /// the queued commitments from `get_packet_acknowledgement` and flush them before committing
+    /// the block: the stored commitments, and thus their proofs, are then the same as with
+    /// per-acknowledgement writes.
+    fn queue_packet_acknowledgement(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        sequence: Sequence,
+        ack_commitment: AcknowledgementCommitment,
+    ) -> Result<(), Error> {
+        self.store_packet_acknowledgement(port_id, channel_id, sequence, ack_commitment)
+    }
+
+    /// Writes the acknowledgement commitments queued by
+    /// [`ChannelKeeper::queue_packet_acknowledgement`] in a single batch. Hosts call it once per
+    /// block, before committing it.
+    fn flush_acks(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Queues `packet`, requested by the callback of a module, to be sent once the current message
+    /// completed, as SDK modules do from `OnRecvPacket`: see
+    /// [`reentrancy`](crate::core::ics26_routing::reentrancy). `dispatch` then sends it through
+    /// the same checks as any other packet, and emits its `SendPacket` event. Its sequence is
+    /// assigned then, as the next send sequence of its channel, so that callbacks may queue
+    /// several packets on the same channel.
+    ///
+    /// By default, packets cannot be queued: hosts supporting it keep them until
+    /// [`ChannelKeeper::take_queued_send_packets`].
+    fn queue_send_packet(&mut self, _packet: Packet) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
+    /// Takes the packets queued by [`ChannelKeeper::queue_send_packet`], in order.
+    fn take_queued_send_packets(&mut self) -> Vec<Packet> {
+        Vec::new()
+    }
+
     fn store_connection_channels(
         &mut self,
         conn_id: ConnectionId,
@@ -321,16 +609,158 @@ pub trait ChannelKeeper {
     /// Increases the counter which keeps track of how many channels have been created.
     /// Should never fail.
     fn increase_channel_counter(&mut self);
-}
 
-pub fn calculate_block_delay(
-    delay_period_time: Duration,
-    max_expected_time_per_block: Duration,
-) -> u64 {
-    if max_expected_time_per_block.is_zero() {
-        return 0;
+    /// Records the start of the handshake of a channel end created in `INIT` or `TRYOPEN` state.
+    /// Only required for hosts that support handshake expiry, see
+    /// [`cleanup_stale_handshakes`](crate::core::ics04_channel::handshake::cleanup_stale_handshakes).
+    fn store_channel_handshake_start(
+        &mut self,
+        _port_id: PortId,
+        _channel_id: ChannelId,
+        _start: HandshakeStart,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Forgets the handshake start of a channel end, once its handshake completed or expired.
+    fn delete_channel_handshake_start(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Records the height and time at which a packet acknowledgement commitment was written.
+    /// Only required for hosts that support pruning, see
+    /// [`prune_packet_records`](crate::core::ics04_channel::pruning::prune_packet_records).
+    fn store_packet_record(&mut self, _record: PacketRecord) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Forgets a packet record, once its acknowledgement commitment was pruned.
+    fn delete_packet_record(&mut self, _record: &PacketRecord) -> Result<(), Error> {
+        Ok(())
     }
 
-    FloatCore::ceil(delay_period_time.as_secs_f64() / max_expected_time_per_block.as_secs_f64())
-        as u64
+    /// Records the number of packets sent on the channel that timed out since the last one was
+    /// acknowledged. Only required for hosts that enable
+    /// [`ChannelReader::max_consecutive_timeouts`].
+    fn store_consecutive_timeouts(
+        &mut self,
+        _port_id: PortId,
+        _channel_id: ChannelId,
+        _count: u64,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Records whether the channel is paused. Only required for hosts that support pausing
+    /// channels.
+    fn store_channel_paused(
+        &mut self,
+        _port_id: PortId,
+        _channel_id: ChannelId,
+        _paused: bool,
+    ) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
+    /// Removes a channel end (and its association to its connection) from the store.
+    /// Only required for hosts that support handshake expiry, which must also enable
+    /// [`ChannelReader::supports_channel_deletion`].
+    fn delete_channel(&mut self, _port_id: &PortId, _channel_id: &ChannelId) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
+    /// Stores the outcome of a step of the channel upgrade handshake.
+    #[cfg(any(test, feature = "unstable"))]
+    fn store_upgrade_result(&mut self, result: UpgradeResult) -> Result<(), Error> {
+        let UpgradeResult {
+            step: _,
+            port_id,
+            channel_id,
+            channel_end,
+            upgrade,
+            counterparty_upgrade,
+            error_receipt,
+            next_sequence_recv,
+            next_sequence_ack,
+        } = result;
+
+        self.store_channel(port_id.clone(), channel_id.clone(), channel_end)?;
+        match upgrade {
+            UpgradeChange::Keep => {}
+            UpgradeChange::Store(upgrade) => {
+                self.store_upgrade(port_id.clone(), channel_id.clone(), upgrade)?
+            }
+            UpgradeChange::Delete => self.delete_upgrade(&port_id, &channel_id)?,
+        }
+        match counterparty_upgrade {
+            UpgradeChange::Keep => {}
+            UpgradeChange::Store(upgrade) => {
+                self.store_counterparty_upgrade(port_id.clone(), channel_id.clone(), upgrade)?
+            }
+            UpgradeChange::Delete => self.delete_counterparty_upgrade(&port_id, &channel_id)?,
+        }
+        if let Some(error_receipt) = error_receipt {
+            self.store_upgrade_error_receipt(port_id.clone(), channel_id.clone(), error_receipt)?;
+        }
+        if let Some(seq) = next_sequence_recv {
+            self.store_next_sequence_recv(port_id.clone(), channel_id.clone(), seq)?;
+        }
+        if let Some(seq) = next_sequence_ack {
+            self.store_next_sequence_ack(port_id, channel_id, seq)?;
+        }
+
+        Ok(())
+    }
+
+    /// Stores the upgrade in progress of a channel end. Only required for hosts that enable
+    /// channel upgrades, as are the other upgrade storage methods.
+    #[cfg(any(test, feature = "unstable"))]
+    fn store_upgrade(
+        &mut self,
+        _port_id: PortId,
+        _channel_id: ChannelId,
+        _upgrade: Upgrade,
+    ) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn delete_upgrade(&mut self, _port_id: &PortId, _channel_id: &ChannelId) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn store_counterparty_upgrade(
+        &mut self,
+        _port_id: PortId,
+        _channel_id: ChannelId,
+        _upgrade: Upgrade,
+    ) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn delete_counterparty_upgrade(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+    ) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
+
+    /// Stores the receipt of a failed upgrade, replacing the previous one. The receipt is
+    /// committed to at `ChannelUpgradePath::UpgradeError` for the counterparty to prove it.
+    #[cfg(any(test, feature = "unstable"))]
+    fn store_upgrade_error_receipt(
+        &mut self,
+        _port_id: PortId,
+        _channel_id: ChannelId,
+        _error_receipt: ErrorReceipt,
+    ) -> Result<(), Error> {
+        Err(Error::implementation_specific())
+    }
 }
@@ -1,5 +1,6 @@
 use super::packet::Sequence;
 use super::timeout::TimeoutHeight;
+use super::Version;
 use crate::core::ics02_client::error as client_error;
 use crate::core::ics03_connection::error as connection_error;
 use crate::core::ics04_channel::channel::State;
@@ -92,6 +93,15 @@ define_error! {
         InvalidPacket
             | _ | { "invalid packet" },
 
+        InvalidPacketField
+            { field: String }
+            [ ValidationError ]
+            | e | { format_args!("invalid packet field `{}`", e.field) },
+
+        MissingPacketField
+            { field: String }
+            | e | { format_args!("missing packet field `{}`", e.field) },
+
         MissingPacket
             | _ | { "there is no packet in this message" },
 
@@ -104,6 +114,17 @@ define_error! {
         NoCommonVersion
             | _ | { "no commong version" },
 
+        UnsupportedVersion
+            { port_id: PortId, version: Version }
+            | e | { format_args!("version `{0}` is not supported on port {1}", e.version, e.port_id) },
+
+        UnsupportedCounterpartyVersion
+            { port_id: PortId, version: Version }
+            | e | {
+                format_args!("counterparty version `{0}` is not supported on port {1}",
+                    e.version, e.port_id)
+            },
+
         MissingChannel
             | _ | { "missing channel end" },
 
@@ -145,19 +166,27 @@ define_error! {
                     e.connection_id)
             },
 
-        PacketVerificationFailed
-            { sequence: Sequence }
-            [ client_error::Error ]
+
+        LowLevelProofVerification
+            { client_id: ClientId, height: Height, path: String }
+            [ client_error::BoxedError ]
             | e | {
                 format_args!(
-                    "Verification fails for the packet with the sequence number {0}",
-                    e.sequence)
+                    "client {0} failed to verify the proof of {1} at height {2}",
+                    e.client_id, e.path, e.height)
             },
 
-        VerifyChannelFailed
+        InvalidMultihopProof
+            { reason: String }
+            | e | { format_args!("invalid multihop proof: {}", e.reason) },
+
+        VerifyMultihopFailed
+            { hop: usize }
             [ client_error::Error ]
-            | _ | {
-                "Error verifying channel state"
+            | e | {
+                format_args!(
+                    "error verifying the intermediate chain {0} of a multihop channel",
+                    e.hop)
             },
 
         InvalidAcknowledgement
@@ -188,6 +217,14 @@ define_error! {
                     e.value)
             },
 
+        NonCanonicalSequence
+            { value: String }
+            | e | {
+                format_args!(
+                    "String {0} is not the canonical decimal form of a packet sequence",
+                    e.value)
+            },
+
         InvalidPacketSequence
             {
                 given_sequence: Sequence,
@@ -199,6 +236,17 @@ define_error! {
                     e.given_sequence, e.next_sequence)
             },
 
+        PacketAlreadyReceived
+            {
+                sequence: Sequence,
+                next_sequence_recv: Sequence
+            }
+            | e | {
+                format_args!(
+                    "packet {0} was already received: the next sequence to receive is {1}",
+                    e.sequence, e.next_sequence_recv)
+            },
+
         LowPacketHeight
             {
                 chain_height: Height,
@@ -250,6 +298,14 @@ define_error! {
                     e.client_id)
             },
 
+        ProofHeightAfterLatestHeight
+            { client_id: ClientId, proof_height: Height, latest_height: Height }
+            | e | {
+                format_args!(
+                    "proof height {1} is after the latest height {2} of client {0}",
+                    e.client_id, e.proof_height, e.latest_height)
+            },
+
         InvalidCounterpartyChannelId
             | _ | { "Invalid channel id in counterparty" },
 
@@ -269,6 +325,83 @@ define_error! {
                     e.channel_id)
             },
 
+        ChannelPaused
+            { port_id: PortId, channel_id: ChannelId }
+            | e | {
+                format_args!(
+                    "channel {0}/{1} is paused",
+                    e.port_id, e.channel_id)
+            },
+
+        ChannelNotPaused
+            { port_id: PortId, channel_id: ChannelId }
+            | e | {
+                format_args!(
+                    "channel {0}/{1} is not paused",
+                    e.port_id, e.channel_id)
+            },
+
+        PacketSentAfterCounterpartyFlush
+            { sequence: Sequence, counterparty_next_sequence_send: Sequence }
+            | e | {
+                format_args!(
+                    "packet {0} was sent after the counterparty started flushing the channel at sequence {1}",
+                    e.sequence, e.counterparty_next_sequence_send)
+            },
+
+        ChannelUpgradesDisabled
+            | _ | { "channel upgrades are not supported by this host" },
+
+        UpgradeNotSupported
+            { port_id: PortId }
+            | e | {
+                format_args!(
+                    "the application bound to port {0} does not support channel upgrades",
+                    e.port_id)
+            },
+
+        MissingUpgradeField
+            { field: String }
+            | e | { format_args!("missing field {} of the channel upgrade", e.field) },
+
+        InvalidUpgradeTimeout
+            | _ | { "the timeout of a channel upgrade must have a height or a timestamp" },
+
+        UpgradeNotFound
+            { port_id: PortId, channel_id: ChannelId }
+            | e | {
+                format_args!(
+                    "no upgrade of the channel {0} on port {1} is in progress",
+                    e.channel_id, e.port_id)
+            },
+
+        UpgradeFieldsUnchanged
+            | _ | { "the proposed upgrade fields are the same as the ones of the channel" },
+
+        IncompatibleUpgrade
+            { reason: String }
+            | e | { format_args!("the upgrades of the channel ends are incompatible: {}", e.reason) },
+
+        InvalidUpgradeSequence
+            { expected: u64, actual: u64 }
+            | e | {
+                format_args!(
+                    "invalid upgrade sequence {1} of the counterparty, expected {0}",
+                    e.expected, e.actual)
+            },
+
+        UpgradeTimedOut
+            | _ | { "the upgrade of the counterparty timed out" },
+
+        UpgradeTimeoutNotReached
+            | _ | { "the upgrade of the counterparty has not timed out yet at the proof height" },
+
+        UpgradeCancelled
+            | _ | { "the upgrade was cancelled after it failed on the counterparty" },
+
+        CounterpartyAlreadyUpgraded
+            | _ | { "the counterparty channel end is already upgraded" },
+
         ChanOpenAckProofVerification
             | _ | { "Handshake proof verification fails at ChannelOpenAck" },
 
@@ -296,6 +429,14 @@ define_error! {
                     e.sequence)
             },
 
+        PacketNotReceived
+            { sequence: Sequence }
+            | e | {
+                format_args!(
+                    "the packet {0} was not received",
+                    e.sequence)
+            },
+
         PacketAcknowledgementNotFound
             { sequence: Sequence }
             | e | {
@@ -334,6 +475,17 @@ define_error! {
                     e.client_id, e.height)
             },
 
+        DelayPeriodNotElapsed
+            {
+                client_id: ClientId,
+                height: Height,
+            }
+            | e | {
+                format_args!(
+                    "the delay period of the consensus state of the client {0} at height {1} has not elapsed yet",
+                    e.client_id, e.height)
+            },
+
         RouteNotFound
             | _ | { "route not found" },
 
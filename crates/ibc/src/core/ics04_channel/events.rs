@@ -3,31 +3,90 @@
 mod channel_attributes;
 mod packet_attributes;
 
+use core::str::FromStr;
+
+use subtle_encoding::hex;
 use tendermint::abci::tag::Tag;
 use tendermint::abci::Event as AbciEvent;
 
+use crate::core::events::{
+    attribute_value, check_event_type, find_attribute, parse_attribute, parse_optional_attribute,
+    parse_value,
+};
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics04_channel::timeout::TimeoutHeight;
 use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
-use crate::events::IbcEventType;
+use crate::events::{Error as EventError, EventReason, IbcEventType, REASON_ATTRIBUTE_KEY};
 use crate::prelude::*;
+use crate::signer::Signer;
+use crate::timestamp::Timestamp;
 
 use self::channel_attributes::{
     ChannelIdAttribute, ConnectionIdAttribute, CounterpartyChannelIdAttribute,
-    CounterpartyPortIdAttribute, PortIdAttribute, VersionAttribute,
-    COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY,
+    CounterpartyPortIdAttribute, PortIdAttribute, UpgradeConnectionHopsAttribute,
+    UpgradeErrorReceiptAttribute, UpgradeOrderingAttribute, UpgradeSequenceAttribute,
+    UpgradeVersionAttribute, VersionAttribute, CONNECTION_ID_ATTRIBUTE_KEY,
 };
 use self::packet_attributes::{
     AcknowledgementAttribute, ChannelOrderingAttribute, DstChannelIdAttribute, DstPortIdAttribute,
-    PacketConnectionIdAttribute, PacketDataAttribute, SequenceAttribute, SrcChannelIdAttribute,
-    SrcPortIdAttribute, TimeoutHeightAttribute, TimeoutTimestampAttribute,
+    PacketConnectionIdAttribute, PacketDataAttribute, RelayerAttribute, SequenceAttribute,
+    SrcChannelIdAttribute, SrcPortIdAttribute, TimeoutHeightAttribute, TimeoutTimestampAttribute,
+};
+
+pub use self::channel_attributes::{
+    CHANNEL_ID_ATTRIBUTE_KEY, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY,
+    COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY, PORT_ID_ATTRIBUTE_KEY,
+    UPGRADE_CONNECTION_HOPS_ATTRIBUTE_KEY, UPGRADE_ERROR_RECEIPT_ATTRIBUTE_KEY,
+    UPGRADE_ORDERING_ATTRIBUTE_KEY, UPGRADE_SEQUENCE_ATTRIBUTE_KEY, UPGRADE_VERSION_ATTRIBUTE_KEY,
+    VERSION_ATTRIBUTE_KEY,
+};
+pub use self::packet_attributes::{
+    PKT_ACK_ATTRIBUTE_KEY, PKT_ACK_HEX_ATTRIBUTE_KEY, PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY,
+    PKT_CONNECTION_ID_ATTRIBUTE_KEY, PKT_DATA_ATTRIBUTE_KEY, PKT_DATA_HEX_ATTRIBUTE_KEY,
+    PKT_DST_CHANNEL_ATTRIBUTE_KEY, PKT_DST_PORT_ATTRIBUTE_KEY, PKT_RELAYER_ATTRIBUTE_KEY,
+    PKT_SEQ_ATTRIBUTE_KEY, PKT_SRC_CHANNEL_ATTRIBUTE_KEY, PKT_SRC_PORT_ATTRIBUTE_KEY,
+    PKT_TIMEOUT_HEIGHT_ATTRIBUTE_KEY, PKT_TIMEOUT_TIMESTAMP_ATTRIBUTE_KEY,
 };
 
 use super::channel::Order;
 use super::msgs::acknowledgement::Acknowledgement;
+use super::packet::Sequence;
 use super::Version;
 
-#[derive(Debug)]
+/// Parses the reason attribute of an event, if any.
+fn parse_reason(event: &AbciEvent) -> Result<Option<EventReason>, EventError> {
+    parse_optional_attribute(event, REASON_ATTRIBUTE_KEY)
+}
+
+/// Decodes the bytes of the hex attribute with key `hex_key`. Events that lack it (e.g. emitted by
+/// older hosts) carry them verbatim in the deprecated attribute with key `key` instead.
+fn parse_bytes(event: &AbciEvent, key: &str, hex_key: &str) -> Result<Option<Vec<u8>>, EventError> {
+    if let Some(value) = find_attribute(event, hex_key) {
+        return hex::decode(value.to_ascii_lowercase())
+            .map(Some)
+            .map_err(|_| EventError::malformed_attribute(hex_key.to_string(), value.to_string()));
+    }
+    Ok(find_attribute(event, key).map(|value| value.as_bytes().to_vec()))
+}
+
+/// Reconstructs the packet of a packet event. Its data is left empty for the events that do not
+/// carry it.
+fn parse_packet(event: &AbciEvent) -> Result<Packet, EventError> {
+    Ok(Packet {
+        sequence: parse_attribute(event, PKT_SEQ_ATTRIBUTE_KEY)?,
+        source_port: parse_attribute(event, PKT_SRC_PORT_ATTRIBUTE_KEY)?,
+        source_channel: parse_attribute(event, PKT_SRC_CHANNEL_ATTRIBUTE_KEY)?,
+        destination_port: parse_attribute(event, PKT_DST_PORT_ATTRIBUTE_KEY)?,
+        destination_channel: parse_attribute(event, PKT_DST_CHANNEL_ATTRIBUTE_KEY)?,
+        data: parse_bytes(event, PKT_DATA_ATTRIBUTE_KEY, PKT_DATA_HEX_ATTRIBUTE_KEY)?
+            .unwrap_or_default(),
+        timeout_height: parse_attribute(event, PKT_TIMEOUT_HEIGHT_ATTRIBUTE_KEY)?,
+        timeout_timestamp: parse_attribute(event, PKT_TIMEOUT_TIMESTAMP_ATTRIBUTE_KEY)?,
+    })
+}
+
+#[derive(Clone, Debug)]
 pub struct OpenInit {
     port_id: PortIdAttribute,
     channel_id: ChannelIdAttribute,
@@ -88,7 +147,22 @@ impl From<OpenInit> for AbciEvent {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for OpenInit {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::OpenInitChannel)?;
+        Ok(Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, VERSION_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct OpenTry {
     port_id: PortIdAttribute,
     channel_id: ChannelIdAttribute,
@@ -152,7 +226,23 @@ impl From<OpenTry> for AbciEvent {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for OpenTry {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::OpenTryChannel)?;
+        Ok(Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, VERSION_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct OpenAck {
     port_id: PortIdAttribute,
     channel_id: ChannelIdAttribute,
@@ -209,7 +299,22 @@ impl From<OpenAck> for AbciEvent {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for OpenAck {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::OpenAckChannel)?;
+        Ok(Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct OpenConfirm {
     port_id: PortIdAttribute,
     channel_id: ChannelIdAttribute,
@@ -266,13 +371,29 @@ impl From<OpenConfirm> for AbciEvent {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for OpenConfirm {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::OpenConfirmChannel)?;
+        Ok(Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct CloseInit {
     port_id: PortIdAttribute,
     channel_id: ChannelIdAttribute,
     counterparty_port_id: CounterpartyPortIdAttribute,
     counterparty_channel_id: CounterpartyChannelIdAttribute,
     connection_id: ConnectionIdAttribute,
+    reason: Option<EventReason>,
 }
 
 impl CloseInit {
@@ -289,8 +410,15 @@ impl CloseInit {
             counterparty_port_id: counterparty_port_id.into(),
             counterparty_channel_id: counterparty_channel_id.into(),
             connection_id: connection_id.into(),
+            reason: None,
         }
     }
+
+    /// Reports why the channel is closed.
+    pub fn with_reason(mut self, reason: EventReason) -> Self {
+        self.reason = Some(reason);
+        self
+    }
     pub fn port_id(&self) -> &PortId {
         &self.port_id.port_id
     }
@@ -306,30 +434,57 @@ impl CloseInit {
     pub fn connection_id(&self) -> &ConnectionId {
         &self.connection_id.connection_id
     }
+    pub fn reason(&self) -> Option<&EventReason> {
+        self.reason.as_ref()
+    }
 }
 
 impl From<CloseInit> for AbciEvent {
     fn from(o: CloseInit) -> Self {
+        let mut attributes = vec![
+            o.port_id.into(),
+            o.channel_id.into(),
+            o.counterparty_port_id.into(),
+            o.counterparty_channel_id.into(),
+            o.connection_id.into(),
+        ];
+        if let Some(reason) = o.reason {
+            attributes.push(reason.into());
+        }
         AbciEvent {
             type_str: IbcEventType::CloseInitChannel.as_str().to_string(),
-            attributes: vec![
-                o.port_id.into(),
-                o.channel_id.into(),
-                o.counterparty_port_id.into(),
-                o.counterparty_channel_id.into(),
-                o.connection_id.into(),
-            ],
+            attributes,
         }
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for CloseInit {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::CloseInitChannel)?;
+        let close = Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+        );
+        Ok(match parse_reason(&event)? {
+            Some(reason) => close.with_reason(reason),
+            None => close,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct CloseConfirm {
     port_id: PortIdAttribute,
     channel_id: ChannelIdAttribute,
     counterparty_port_id: CounterpartyPortIdAttribute,
     counterparty_channel_id: CounterpartyChannelIdAttribute,
     connection_id: ConnectionIdAttribute,
+    reason: Option<EventReason>,
 }
 
 impl CloseConfirm {
@@ -346,8 +501,15 @@ impl CloseConfirm {
             counterparty_port_id: counterparty_port_id.into(),
             counterparty_channel_id: counterparty_channel_id.into(),
             connection_id: connection_id.into(),
+            reason: None,
         }
     }
+
+    /// Reports why the channel is closed.
+    pub fn with_reason(mut self, reason: EventReason) -> Self {
+        self.reason = Some(reason);
+        self
+    }
     pub fn port_id(&self) -> &PortId {
         &self.port_id.port_id
     }
@@ -363,28 +525,54 @@ impl CloseConfirm {
     pub fn connection_id(&self) -> &ConnectionId {
         &self.connection_id.connection_id
     }
+    pub fn reason(&self) -> Option<&EventReason> {
+        self.reason.as_ref()
+    }
 }
 
 impl From<CloseConfirm> for AbciEvent {
     fn from(o: CloseConfirm) -> Self {
+        let mut attributes = vec![
+            o.port_id.into(),
+            o.channel_id.into(),
+            o.counterparty_port_id.into(),
+            o.counterparty_channel_id.into(),
+            o.connection_id.into(),
+        ];
+        if let Some(reason) = o.reason {
+            attributes.push(reason.into());
+        }
         AbciEvent {
             type_str: IbcEventType::CloseConfirmChannel.as_str().to_string(),
-            attributes: vec![
-                o.port_id.into(),
-                o.channel_id.into(),
-                o.counterparty_port_id.into(),
-                o.counterparty_channel_id.into(),
-                o.connection_id.into(),
-            ],
+            attributes,
         }
     }
 }
 
+impl TryFrom<AbciEvent> for CloseConfirm {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::CloseConfirmChannel)?;
+        let close = Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+        );
+        Ok(match parse_reason(&event)? {
+            Some(reason) => close.with_reason(reason),
+            None => close,
+        })
+    }
+}
+
 /// A `ChannelClosed` event is emitted when a channel is closed as a result of a packet timing out. Note that
 /// since optimistic packet sends (i.e. send a packet before channel handshake is complete) are supported,
 /// we might not have a counterparty channel id value yet. This would happen if a packet is sent right
 /// after a `ChannelOpenInit` message.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ChannelClosed {
     port_id: PortIdAttribute,
     channel_id: ChannelIdAttribute,
@@ -392,6 +580,7 @@ pub struct ChannelClosed {
     maybe_counterparty_channel_id: Option<CounterpartyChannelIdAttribute>,
     connection_id: ConnectionIdAttribute,
     channel_ordering: ChannelOrderingAttribute,
+    reason: Option<EventReason>,
 }
 
 impl ChannelClosed {
@@ -410,8 +599,15 @@ impl ChannelClosed {
             maybe_counterparty_channel_id: maybe_counterparty_channel_id.map(|c| c.into()),
             connection_id: connection_id.into(),
             channel_ordering: channel_ordering.into(),
+            reason: None,
         }
     }
+
+    /// Reports why the channel is closed.
+    pub fn with_reason(mut self, reason: EventReason) -> Self {
+        self.reason = Some(reason);
+        self
+    }
     pub fn port_id(&self) -> &PortId {
         &self.port_id.port_id
     }
@@ -432,31 +628,368 @@ impl ChannelClosed {
     pub fn channel_ordering(&self) -> &Order {
         &self.channel_ordering.order
     }
+    pub fn reason(&self) -> Option<&EventReason> {
+        self.reason.as_ref()
+    }
 }
 
 impl From<ChannelClosed> for AbciEvent {
     fn from(ev: ChannelClosed) -> Self {
+        let mut attributes = vec![
+            ev.port_id.into(),
+            ev.channel_id.into(),
+            ev.counterparty_port_id.into(),
+            ev.maybe_counterparty_channel_id.map_or(
+                Tag {
+                    key: COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY.parse().unwrap(),
+                    value: "".parse().unwrap(),
+                },
+                |c| c.into(),
+            ),
+            ev.connection_id.into(),
+            ev.channel_ordering.into(),
+        ];
+        if let Some(reason) = ev.reason {
+            attributes.push(reason.into());
+        }
         AbciEvent {
             type_str: IbcEventType::ChannelClosed.as_str().to_string(),
+            attributes,
+        }
+    }
+}
+
+impl TryFrom<AbciEvent> for ChannelClosed {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::ChannelClosed)?;
+        // The counterparty channel id is empty if the channel was closed before it was known.
+        let maybe_counterparty_channel_id =
+            match attribute_value(&event, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY)? {
+                "" => None,
+                channel_id => Some(parse_value(
+                    COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY,
+                    channel_id,
+                )?),
+            };
+        let closed = Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY)?,
+            maybe_counterparty_channel_id,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY)?,
+        );
+        Ok(match parse_reason(&event)? {
+            Some(reason) => closed.with_reason(reason),
+            None => closed,
+        })
+    }
+}
+
+/// A `ChannelPaused` event is emitted when the host pauses a channel, which then neither sends nor
+/// receives packets until it is resumed.
+#[derive(Clone, Debug)]
+pub struct ChannelPaused {
+    port_id: PortIdAttribute,
+    channel_id: ChannelIdAttribute,
+    connection_id: ConnectionIdAttribute,
+}
+
+impl ChannelPaused {
+    pub fn new(port_id: PortId, channel_id: ChannelId, connection_id: ConnectionId) -> Self {
+        Self {
+            port_id: port_id.into(),
+            channel_id: channel_id.into(),
+            connection_id: connection_id.into(),
+        }
+    }
+    pub fn port_id(&self) -> &PortId {
+        &self.port_id.port_id
+    }
+    pub fn channel_id(&self) -> &ChannelId {
+        &self.channel_id.channel_id
+    }
+    pub fn connection_id(&self) -> &ConnectionId {
+        &self.connection_id.connection_id
+    }
+}
+
+impl From<ChannelPaused> for AbciEvent {
+    fn from(ev: ChannelPaused) -> Self {
+        AbciEvent {
+            type_str: IbcEventType::ChannelPaused.as_str().to_string(),
+            attributes: vec![
+                ev.port_id.into(),
+                ev.channel_id.into(),
+                ev.connection_id.into(),
+            ],
+        }
+    }
+}
+
+impl TryFrom<AbciEvent> for ChannelPaused {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::ChannelPaused)?;
+        Ok(Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+/// A `ChannelResumed` event is emitted when the host resumes a paused channel.
+#[derive(Clone, Debug)]
+pub struct ChannelResumed {
+    port_id: PortIdAttribute,
+    channel_id: ChannelIdAttribute,
+    connection_id: ConnectionIdAttribute,
+}
+
+impl ChannelResumed {
+    pub fn new(port_id: PortId, channel_id: ChannelId, connection_id: ConnectionId) -> Self {
+        Self {
+            port_id: port_id.into(),
+            channel_id: channel_id.into(),
+            connection_id: connection_id.into(),
+        }
+    }
+    pub fn port_id(&self) -> &PortId {
+        &self.port_id.port_id
+    }
+    pub fn channel_id(&self) -> &ChannelId {
+        &self.channel_id.channel_id
+    }
+    pub fn connection_id(&self) -> &ConnectionId {
+        &self.connection_id.connection_id
+    }
+}
+
+impl From<ChannelResumed> for AbciEvent {
+    fn from(ev: ChannelResumed) -> Self {
+        AbciEvent {
+            type_str: IbcEventType::ChannelResumed.as_str().to_string(),
             attributes: vec![
                 ev.port_id.into(),
                 ev.channel_id.into(),
-                ev.counterparty_port_id.into(),
-                ev.maybe_counterparty_channel_id.map_or(
-                    Tag {
-                        key: COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY.parse().unwrap(),
-                        value: "".parse().unwrap(),
-                    },
-                    |c| c.into(),
-                ),
                 ev.connection_id.into(),
-                ev.channel_ordering.into(),
             ],
         }
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for ChannelResumed {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::ChannelResumed)?;
+        Ok(Self::new(
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CONNECTION_ID_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+/// The steps of the channel upgrade handshake, each reported by a [`ChannelUpgrade`] event of
+/// its own type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpgradeStep {
+    Init,
+    Try,
+    Ack,
+    Confirm,
+    Open,
+    /// The last packet in flight of a flushing channel end was acknowledged or timed out.
+    FlushComplete,
+    /// The upgrade was cancelled by `MsgChannelUpgradeCancel`.
+    Cancel,
+    /// The upgrade was timed out by `MsgChannelUpgradeTimeout`.
+    Timeout,
+    /// The upgrade was aborted, as it is incompatible with the one of the counterparty, or the
+    /// counterparty did not start flushing in time.
+    Error,
+}
+
+impl UpgradeStep {
+    /// Returns the step reported by events of the given type, if any.
+    pub fn from_event_type(event_type: &IbcEventType) -> Option<Self> {
+        match event_type {
+            IbcEventType::UpgradeInitChannel => Some(UpgradeStep::Init),
+            IbcEventType::UpgradeTryChannel => Some(UpgradeStep::Try),
+            IbcEventType::UpgradeAckChannel => Some(UpgradeStep::Ack),
+            IbcEventType::UpgradeConfirmChannel => Some(UpgradeStep::Confirm),
+            IbcEventType::UpgradeOpenChannel => Some(UpgradeStep::Open),
+            IbcEventType::FlushCompleteChannel => Some(UpgradeStep::FlushComplete),
+            IbcEventType::UpgradeCancelChannel => Some(UpgradeStep::Cancel),
+            IbcEventType::UpgradeTimeoutChannel => Some(UpgradeStep::Timeout),
+            IbcEventType::UpgradeErrorChannel => Some(UpgradeStep::Error),
+            _ => None,
+        }
+    }
+
+    pub fn event_type(&self) -> IbcEventType {
+        match self {
+            UpgradeStep::Init => IbcEventType::UpgradeInitChannel,
+            UpgradeStep::Try => IbcEventType::UpgradeTryChannel,
+            UpgradeStep::Ack => IbcEventType::UpgradeAckChannel,
+            UpgradeStep::Confirm => IbcEventType::UpgradeConfirmChannel,
+            UpgradeStep::Open => IbcEventType::UpgradeOpenChannel,
+            UpgradeStep::FlushComplete => IbcEventType::FlushCompleteChannel,
+            UpgradeStep::Cancel => IbcEventType::UpgradeCancelChannel,
+            UpgradeStep::Timeout => IbcEventType::UpgradeTimeoutChannel,
+            UpgradeStep::Error => IbcEventType::UpgradeErrorChannel,
+        }
+    }
+}
+
+/// Emitted by each step of the channel upgrade handshake.
+///
+/// The upgrade fields are the proposed ones until the channel end reopens, and those with which
+/// it reopened afterwards: the new ones for `Open`, the previous ones for `Cancel`, `Timeout`
+/// and `Error`.
+#[derive(Clone, Debug)]
+pub struct ChannelUpgrade {
+    step: UpgradeStep,
+    port_id: PortIdAttribute,
+    channel_id: ChannelIdAttribute,
+    counterparty_port_id: CounterpartyPortIdAttribute,
+    counterparty_channel_id: CounterpartyChannelIdAttribute,
+    upgrade_sequence: UpgradeSequenceAttribute,
+    upgrade_connection_hops: UpgradeConnectionHopsAttribute,
+    upgrade_version: UpgradeVersionAttribute,
+    upgrade_ordering: UpgradeOrderingAttribute,
+    error_receipt: Option<UpgradeErrorReceiptAttribute>,
+}
+
+impl ChannelUpgrade {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        step: UpgradeStep,
+        port_id: PortId,
+        channel_id: ChannelId,
+        counterparty_port_id: PortId,
+        counterparty_channel_id: ChannelId,
+        upgrade_sequence: u64,
+        upgrade_connection_hops: Vec<ConnectionId>,
+        upgrade_version: Version,
+        upgrade_ordering: Order,
+    ) -> Self {
+        Self {
+            step,
+            port_id: port_id.into(),
+            channel_id: channel_id.into(),
+            counterparty_port_id: counterparty_port_id.into(),
+            counterparty_channel_id: counterparty_channel_id.into(),
+            upgrade_sequence: upgrade_sequence.into(),
+            upgrade_connection_hops: upgrade_connection_hops.into(),
+            upgrade_version: upgrade_version.into(),
+            upgrade_ordering: upgrade_ordering.into(),
+            error_receipt: None,
+        }
+    }
+
+    /// Reports the message of the error receipt written by the step.
+    pub fn with_error_receipt(mut self, message: String) -> Self {
+        self.error_receipt = Some(message.into());
+        self
+    }
+    pub fn step(&self) -> UpgradeStep {
+        self.step
+    }
+    pub fn port_id(&self) -> &PortId {
+        &self.port_id.port_id
+    }
+    pub fn channel_id(&self) -> &ChannelId {
+        &self.channel_id.channel_id
+    }
+    pub fn counterparty_port_id(&self) -> &PortId {
+        &self.counterparty_port_id.counterparty_port_id
+    }
+    pub fn counterparty_channel_id(&self) -> &ChannelId {
+        &self.counterparty_channel_id.counterparty_channel_id
+    }
+    pub fn upgrade_sequence(&self) -> u64 {
+        self.upgrade_sequence.upgrade_sequence
+    }
+    pub fn upgrade_connection_hops(&self) -> &[ConnectionId] {
+        &self.upgrade_connection_hops.connection_hops
+    }
+    pub fn upgrade_version(&self) -> &Version {
+        &self.upgrade_version.version
+    }
+    pub fn upgrade_ordering(&self) -> &Order {
+        &self.upgrade_ordering.ordering
+    }
+    pub fn error_receipt(&self) -> Option<&str> {
+        self.error_receipt
+            .as_ref()
+            .map(|attr| attr.message.as_str())
+    }
+}
+
+impl From<ChannelUpgrade> for AbciEvent {
+    fn from(ev: ChannelUpgrade) -> Self {
+        let mut attributes = vec![
+            ev.port_id.into(),
+            ev.channel_id.into(),
+            ev.counterparty_port_id.into(),
+            ev.counterparty_channel_id.into(),
+            ev.upgrade_sequence.into(),
+            ev.upgrade_connection_hops.into(),
+            ev.upgrade_version.into(),
+            ev.upgrade_ordering.into(),
+        ];
+        if let Some(error_receipt) = ev.error_receipt {
+            attributes.push(error_receipt.into());
+        }
+        AbciEvent {
+            type_str: ev.step.event_type().as_str().to_string(),
+            attributes,
+        }
+    }
+}
+
+impl TryFrom<AbciEvent> for ChannelUpgrade {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        let step = IbcEventType::from_str(&event.type_str)
+            .ok()
+            .and_then(|event_type| UpgradeStep::from_event_type(&event_type))
+            .ok_or_else(|| EventError::incorrect_event_type(event.type_str.clone()))?;
+        let upgrade_connection_hops =
+            attribute_value(&event, UPGRADE_CONNECTION_HOPS_ATTRIBUTE_KEY)?
+                .split(',')
+                .filter(|conn_id| !conn_id.is_empty())
+                .map(|conn_id| parse_value(UPGRADE_CONNECTION_HOPS_ATTRIBUTE_KEY, conn_id))
+                .collect::<Result<_, _>>()?;
+
+        let upgrade = Self::new(
+            step,
+            parse_attribute(&event, PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, UPGRADE_SEQUENCE_ATTRIBUTE_KEY)?,
+            upgrade_connection_hops,
+            parse_attribute(&event, UPGRADE_VERSION_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, UPGRADE_ORDERING_ATTRIBUTE_KEY)?,
+        );
+        Ok(
+            match find_attribute(&event, UPGRADE_ERROR_RECEIPT_ATTRIBUTE_KEY) {
+                Some(message) => upgrade.with_error_receipt(message.to_string()),
+                None => upgrade,
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct SendPacket {
     packet_data: PacketDataAttribute,
     timeout_height: TimeoutHeightAttribute,
@@ -485,6 +1018,50 @@ impl SendPacket {
             src_connection_id: src_connection_id.into(),
         }
     }
+    pub fn sequence(&self) -> &Sequence {
+        &self.sequence.sequence
+    }
+    pub fn src_port_id(&self) -> &PortId {
+        &self.src_port_id.src_port_id
+    }
+    pub fn src_channel_id(&self) -> &ChannelId {
+        &self.src_channel_id.src_channel_id
+    }
+    pub fn dst_port_id(&self) -> &PortId {
+        &self.dst_port_id.dst_port_id
+    }
+    pub fn dst_channel_id(&self) -> &ChannelId {
+        &self.dst_channel_id.dst_channel_id
+    }
+    pub fn packet_data(&self) -> &[u8] {
+        &self.packet_data.packet_data
+    }
+    pub fn timeout_height(&self) -> &TimeoutHeight {
+        &self.timeout_height.timeout_height
+    }
+    pub fn timeout_timestamp(&self) -> &Timestamp {
+        &self.timeout_timestamp.timeout_timestamp
+    }
+    pub fn channel_ordering(&self) -> &Order {
+        &self.channel_ordering.order
+    }
+    pub fn src_connection_id(&self) -> &ConnectionId {
+        &self.src_connection_id.connection_id
+    }
+
+    /// Reconstructs the packet reported by the event.
+    pub fn packet(&self) -> Packet {
+        Packet {
+            sequence: *self.sequence(),
+            source_port: self.src_port_id().clone(),
+            source_channel: self.src_channel_id().clone(),
+            destination_port: self.dst_port_id().clone(),
+            destination_channel: self.dst_channel_id().clone(),
+            data: self.packet_data().to_vec(),
+            timeout_height: *self.timeout_height(),
+            timeout_timestamp: *self.timeout_timestamp(),
+        }
+    }
 }
 
 impl TryFrom<SendPacket> for AbciEvent {
@@ -510,7 +1087,20 @@ impl TryFrom<SendPacket> for AbciEvent {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for SendPacket {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::SendPacket)?;
+        Ok(Self::new(
+            parse_packet(&event)?,
+            parse_attribute(&event, PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, PKT_CONNECTION_ID_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct ReceivePacket {
     packet_data: PacketDataAttribute,
     timeout_height: TimeoutHeightAttribute,
@@ -522,6 +1112,7 @@ pub struct ReceivePacket {
     dst_channel_id: DstChannelIdAttribute,
     channel_ordering: ChannelOrderingAttribute,
     dst_connection_id: PacketConnectionIdAttribute,
+    relayer: Option<RelayerAttribute>,
 }
 
 impl ReceivePacket {
@@ -537,8 +1128,65 @@ impl ReceivePacket {
             dst_channel_id: packet.destination_channel.into(),
             channel_ordering: channel_ordering.into(),
             dst_connection_id: dst_connection_id.into(),
+            relayer: None,
+        }
+    }
+    pub fn sequence(&self) -> &Sequence {
+        &self.sequence.sequence
+    }
+    pub fn src_port_id(&self) -> &PortId {
+        &self.src_port_id.src_port_id
+    }
+    pub fn src_channel_id(&self) -> &ChannelId {
+        &self.src_channel_id.src_channel_id
+    }
+    pub fn dst_port_id(&self) -> &PortId {
+        &self.dst_port_id.dst_port_id
+    }
+    pub fn dst_channel_id(&self) -> &ChannelId {
+        &self.dst_channel_id.dst_channel_id
+    }
+    pub fn packet_data(&self) -> &[u8] {
+        &self.packet_data.packet_data
+    }
+    pub fn timeout_height(&self) -> &TimeoutHeight {
+        &self.timeout_height.timeout_height
+    }
+    pub fn timeout_timestamp(&self) -> &Timestamp {
+        &self.timeout_timestamp.timeout_timestamp
+    }
+    pub fn channel_ordering(&self) -> &Order {
+        &self.channel_ordering.order
+    }
+    pub fn dst_connection_id(&self) -> &ConnectionId {
+        &self.dst_connection_id.connection_id
+    }
+
+    /// Reconstructs the packet reported by the event.
+    pub fn packet(&self) -> Packet {
+        Packet {
+            sequence: *self.sequence(),
+            source_port: self.src_port_id().clone(),
+            source_channel: self.src_channel_id().clone(),
+            destination_port: self.dst_port_id().clone(),
+            destination_channel: self.dst_channel_id().clone(),
+            data: self.packet_data().to_vec(),
+            timeout_height: *self.timeout_height(),
+            timeout_timestamp: *self.timeout_timestamp(),
         }
     }
+
+    /// Attributes the event to the relayer that submitted the message (i.e. the message signer).
+    /// This attribute is not part of the events emitted by ibc-go, so hosts only set it when
+    /// relayer attribution was enabled.
+    pub fn with_relayer(mut self, relayer: Signer) -> Self {
+        self.relayer = Some(relayer.into());
+        self
+    }
+
+    pub fn relayer(&self) -> Option<&Signer> {
+        self.relayer.as_ref().map(|attr| &attr.relayer)
+    }
 }
 
 impl TryFrom<ReceivePacket> for AbciEvent {
@@ -556,6 +1204,9 @@ impl TryFrom<ReceivePacket> for AbciEvent {
         attributes.push(v.dst_channel_id.into());
         attributes.push(v.channel_ordering.into());
         attributes.push(v.dst_connection_id.into());
+        if let Some(relayer) = v.relayer {
+            attributes.push(relayer.into());
+        }
 
         Ok(AbciEvent {
             type_str: IbcEventType::ReceivePacket.as_str().to_string(),
@@ -564,7 +1215,26 @@ impl TryFrom<ReceivePacket> for AbciEvent {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for ReceivePacket {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::ReceivePacket)?;
+        let receive = Self::new(
+            parse_packet(&event)?,
+            parse_attribute(&event, PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, PKT_CONNECTION_ID_ATTRIBUTE_KEY)?,
+        );
+        Ok(
+            match parse_optional_attribute(&event, PKT_RELAYER_ATTRIBUTE_KEY)? {
+                Some(relayer) => receive.with_relayer(relayer),
+                None => receive,
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct WriteAcknowledgement {
     packet_data: PacketDataAttribute,
     timeout_height: TimeoutHeightAttribute,
@@ -597,6 +1267,50 @@ impl WriteAcknowledgement {
             dst_connection_id: dst_connection_id.into(),
         }
     }
+    pub fn sequence(&self) -> &Sequence {
+        &self.sequence.sequence
+    }
+    pub fn src_port_id(&self) -> &PortId {
+        &self.src_port_id.src_port_id
+    }
+    pub fn src_channel_id(&self) -> &ChannelId {
+        &self.src_channel_id.src_channel_id
+    }
+    pub fn dst_port_id(&self) -> &PortId {
+        &self.dst_port_id.dst_port_id
+    }
+    pub fn dst_channel_id(&self) -> &ChannelId {
+        &self.dst_channel_id.dst_channel_id
+    }
+    pub fn packet_data(&self) -> &[u8] {
+        &self.packet_data.packet_data
+    }
+    pub fn timeout_height(&self) -> &TimeoutHeight {
+        &self.timeout_height.timeout_height
+    }
+    pub fn timeout_timestamp(&self) -> &Timestamp {
+        &self.timeout_timestamp.timeout_timestamp
+    }
+    pub fn dst_connection_id(&self) -> &ConnectionId {
+        &self.dst_connection_id.connection_id
+    }
+
+    /// Reconstructs the packet reported by the event.
+    pub fn packet(&self) -> Packet {
+        Packet {
+            sequence: *self.sequence(),
+            source_port: self.src_port_id().clone(),
+            source_channel: self.src_channel_id().clone(),
+            destination_port: self.dst_port_id().clone(),
+            destination_channel: self.dst_channel_id().clone(),
+            data: self.packet_data().to_vec(),
+            timeout_height: *self.timeout_height(),
+            timeout_timestamp: *self.timeout_timestamp(),
+        }
+    }
+    pub fn acknowledgement(&self) -> &Acknowledgement {
+        &self.acknowledgement.acknowledgement
+    }
 }
 
 impl TryFrom<WriteAcknowledgement> for AbciEvent {
@@ -622,7 +1336,23 @@ impl TryFrom<WriteAcknowledgement> for AbciEvent {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for WriteAcknowledgement {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::WriteAck)?;
+        let acknowledgement =
+            parse_bytes(&event, PKT_ACK_ATTRIBUTE_KEY, PKT_ACK_HEX_ATTRIBUTE_KEY)?
+                .ok_or_else(|| EventError::missing_key(PKT_ACK_HEX_ATTRIBUTE_KEY.to_string()))?;
+        Ok(Self::new(
+            parse_packet(&event)?,
+            acknowledgement.into(),
+            parse_attribute(&event, PKT_CONNECTION_ID_ATTRIBUTE_KEY)?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct AcknowledgePacket {
     timeout_height: TimeoutHeightAttribute,
     timeout_timestamp: TimeoutTimestampAttribute,
@@ -633,6 +1363,7 @@ pub struct AcknowledgePacket {
     dst_channel_id: DstChannelIdAttribute,
     channel_ordering: ChannelOrderingAttribute,
     src_connection_id: PacketConnectionIdAttribute,
+    relayer: Option<RelayerAttribute>,
 }
 
 impl AcknowledgePacket {
@@ -647,32 +1378,96 @@ impl AcknowledgePacket {
             dst_channel_id: packet.destination_channel.into(),
             channel_ordering: channel_ordering.into(),
             src_connection_id: src_connection_id.into(),
+            relayer: None,
         }
     }
+    pub fn sequence(&self) -> &Sequence {
+        &self.sequence.sequence
+    }
+    pub fn src_port_id(&self) -> &PortId {
+        &self.src_port_id.src_port_id
+    }
+    pub fn src_channel_id(&self) -> &ChannelId {
+        &self.src_channel_id.src_channel_id
+    }
+    pub fn dst_port_id(&self) -> &PortId {
+        &self.dst_port_id.dst_port_id
+    }
+    pub fn dst_channel_id(&self) -> &ChannelId {
+        &self.dst_channel_id.dst_channel_id
+    }
+    pub fn timeout_height(&self) -> &TimeoutHeight {
+        &self.timeout_height.timeout_height
+    }
+    pub fn timeout_timestamp(&self) -> &Timestamp {
+        &self.timeout_timestamp.timeout_timestamp
+    }
+    pub fn channel_ordering(&self) -> &Order {
+        &self.channel_ordering.order
+    }
+    pub fn src_connection_id(&self) -> &ConnectionId {
+        &self.src_connection_id.connection_id
+    }
+
+    /// Attributes the event to the relayer that submitted the message (i.e. the message signer).
+    /// This attribute is not part of the events emitted by ibc-go, so hosts only set it when
+    /// relayer attribution was enabled.
+    pub fn with_relayer(mut self, relayer: Signer) -> Self {
+        self.relayer = Some(relayer.into());
+        self
+    }
+
+    pub fn relayer(&self) -> Option<&Signer> {
+        self.relayer.as_ref().map(|attr| &attr.relayer)
+    }
 }
 
 impl TryFrom<AcknowledgePacket> for AbciEvent {
     type Error = Error;
 
     fn try_from(v: AcknowledgePacket) -> Result<Self, Self::Error> {
+        let mut attributes: Vec<Tag> = vec![
+            v.timeout_height.into(),
+            v.timeout_timestamp.into(),
+            v.sequence.into(),
+            v.src_port_id.into(),
+            v.src_channel_id.into(),
+            v.dst_port_id.into(),
+            v.dst_channel_id.into(),
+            v.channel_ordering.into(),
+            v.src_connection_id.into(),
+        ];
+        if let Some(relayer) = v.relayer {
+            attributes.push(relayer.into());
+        }
+
         Ok(AbciEvent {
             type_str: IbcEventType::AckPacket.as_str().to_string(),
-            attributes: vec![
-                v.timeout_height.into(),
-                v.timeout_timestamp.into(),
-                v.sequence.into(),
-                v.src_port_id.into(),
-                v.src_channel_id.into(),
-                v.dst_port_id.into(),
-                v.dst_channel_id.into(),
-                v.channel_ordering.into(),
-                v.src_connection_id.into(),
-            ],
+            attributes,
         })
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<AbciEvent> for AcknowledgePacket {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::AckPacket)?;
+        let acknowledge = Self::new(
+            parse_packet(&event)?,
+            parse_attribute(&event, PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY)?,
+            parse_attribute(&event, PKT_CONNECTION_ID_ATTRIBUTE_KEY)?,
+        );
+        Ok(
+            match parse_optional_attribute(&event, PKT_RELAYER_ATTRIBUTE_KEY)? {
+                Some(relayer) => acknowledge.with_relayer(relayer),
+                None => acknowledge,
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct TimeoutPacket {
     timeout_height: TimeoutHeightAttribute,
     timeout_timestamp: TimeoutTimestampAttribute,
@@ -682,6 +1477,7 @@ pub struct TimeoutPacket {
     dst_port_id: DstPortIdAttribute,
     dst_channel_id: DstChannelIdAttribute,
     channel_ordering: ChannelOrderingAttribute,
+    relayer: Option<RelayerAttribute>,
 }
 
 impl TimeoutPacket {
@@ -695,26 +1491,86 @@ impl TimeoutPacket {
             dst_port_id: packet.destination_port.into(),
             dst_channel_id: packet.destination_channel.into(),
             channel_ordering: channel_ordering.into(),
+            relayer: None,
         }
     }
+    pub fn sequence(&self) -> &Sequence {
+        &self.sequence.sequence
+    }
+    pub fn src_port_id(&self) -> &PortId {
+        &self.src_port_id.src_port_id
+    }
+    pub fn src_channel_id(&self) -> &ChannelId {
+        &self.src_channel_id.src_channel_id
+    }
+    pub fn dst_port_id(&self) -> &PortId {
+        &self.dst_port_id.dst_port_id
+    }
+    pub fn dst_channel_id(&self) -> &ChannelId {
+        &self.dst_channel_id.dst_channel_id
+    }
+    pub fn timeout_height(&self) -> &TimeoutHeight {
+        &self.timeout_height.timeout_height
+    }
+    pub fn timeout_timestamp(&self) -> &Timestamp {
+        &self.timeout_timestamp.timeout_timestamp
+    }
+    pub fn channel_ordering(&self) -> &Order {
+        &self.channel_ordering.order
+    }
+
+    /// Attributes the event to the relayer that submitted the message (i.e. the message signer).
+    /// This attribute is not part of the events emitted by ibc-go, so hosts only set it when
+    /// relayer attribution was enabled.
+    pub fn with_relayer(mut self, relayer: Signer) -> Self {
+        self.relayer = Some(relayer.into());
+        self
+    }
+
+    pub fn relayer(&self) -> Option<&Signer> {
+        self.relayer.as_ref().map(|attr| &attr.relayer)
+    }
 }
 
 impl TryFrom<TimeoutPacket> for AbciEvent {
     type Error = Error;
 
     fn try_from(v: TimeoutPacket) -> Result<Self, Self::Error> {
+        let mut attributes: Vec<Tag> = vec![
+            v.timeout_height.into(),
+            v.timeout_timestamp.into(),
+            v.sequence.into(),
+            v.src_port_id.into(),
+            v.src_channel_id.into(),
+            v.dst_port_id.into(),
+            v.dst_channel_id.into(),
+            v.channel_ordering.into(),
+        ];
+        if let Some(relayer) = v.relayer {
+            attributes.push(relayer.into());
+        }
+
         Ok(AbciEvent {
             type_str: IbcEventType::Timeout.as_str().to_string(),
-            attributes: vec![
-                v.timeout_height.into(),
-                v.timeout_timestamp.into(),
-                v.sequence.into(),
-                v.src_port_id.into(),
-                v.src_channel_id.into(),
-                v.dst_port_id.into(),
-                v.dst_channel_id.into(),
-                v.channel_ordering.into(),
-            ],
+            attributes,
         })
     }
 }
+
+impl TryFrom<AbciEvent> for TimeoutPacket {
+    type Error = EventError;
+
+    fn try_from(event: AbciEvent) -> Result<Self, Self::Error> {
+        check_event_type(&event, IbcEventType::Timeout)?;
+        let timeout = Self::new(
+            parse_packet(&event)?,
+            parse_attribute(&event, PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY)?,
+        );
+        Ok(
+            match parse_optional_attribute(&event, PKT_RELAYER_ATTRIBUTE_KEY)? {
+                Some(relayer) => timeout.with_relayer(relayer),
+                None => timeout,
+            },
+        )
+    }
+}
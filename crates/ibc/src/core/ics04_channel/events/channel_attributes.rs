@@ -1,24 +1,27 @@
-///! This module holds all the abci event attributes for IBC events emitted
-///! during the channel handshake.
-use alloc::string::ToString;
+//! This module holds all the abci event attributes for IBC events emitted
+//! during the channel handshake.
 use derive_more::From;
 use tendermint::abci::tag::Tag;
 
 use crate::core::{
-    ics04_channel::Version,
+    ics04_channel::{channel::Order, Version},
     ics24_host::identifier::{ChannelId, ConnectionId, PortId},
 };
+use crate::prelude::*;
 
-const CONNECTION_ID_ATTRIBUTE_KEY: &str = "connection_id";
-const CHANNEL_ID_ATTRIBUTE_KEY: &str = "channel_id";
-const PORT_ID_ATTRIBUTE_KEY: &str = "port_id";
-/// This attribute key is public so that OpenInit can use it to convert itself
-/// to an `AbciEvent`
+pub const CONNECTION_ID_ATTRIBUTE_KEY: &str = "connection_id";
+pub const CHANNEL_ID_ATTRIBUTE_KEY: &str = "channel_id";
+pub const PORT_ID_ATTRIBUTE_KEY: &str = "port_id";
 pub const COUNTERPARTY_CHANNEL_ID_ATTRIBUTE_KEY: &str = "counterparty_channel_id";
-const COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY: &str = "counterparty_port_id";
-const VERSION_ATTRIBUTE_KEY: &str = "version";
+pub const COUNTERPARTY_PORT_ID_ATTRIBUTE_KEY: &str = "counterparty_port_id";
+pub const VERSION_ATTRIBUTE_KEY: &str = "version";
+pub const UPGRADE_SEQUENCE_ATTRIBUTE_KEY: &str = "upgrade_sequence";
+pub const UPGRADE_CONNECTION_HOPS_ATTRIBUTE_KEY: &str = "upgrade_connection_hops";
+pub const UPGRADE_VERSION_ATTRIBUTE_KEY: &str = "upgrade_version";
+pub const UPGRADE_ORDERING_ATTRIBUTE_KEY: &str = "upgrade_ordering";
+pub const UPGRADE_ERROR_RECEIPT_ATTRIBUTE_KEY: &str = "upgrade_error_receipt";
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct PortIdAttribute {
     pub port_id: PortId,
 }
@@ -46,7 +49,7 @@ impl From<ChannelIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct CounterpartyPortIdAttribute {
     pub counterparty_port_id: PortId,
 }
@@ -60,7 +63,7 @@ impl From<CounterpartyPortIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct CounterpartyChannelIdAttribute {
     pub counterparty_channel_id: ChannelId,
 }
@@ -80,7 +83,7 @@ impl AsRef<ChannelId> for CounterpartyChannelIdAttribute {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct ConnectionIdAttribute {
     pub connection_id: ConnectionId,
 }
@@ -94,7 +97,7 @@ impl From<ConnectionIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct VersionAttribute {
     pub version: Version,
 }
@@ -107,3 +110,78 @@ impl From<VersionAttribute> for Tag {
         }
     }
 }
+
+#[derive(Clone, Debug, From)]
+pub struct UpgradeSequenceAttribute {
+    pub upgrade_sequence: u64,
+}
+
+impl From<UpgradeSequenceAttribute> for Tag {
+    fn from(attr: UpgradeSequenceAttribute) -> Self {
+        Tag {
+            key: UPGRADE_SEQUENCE_ATTRIBUTE_KEY.parse().unwrap(),
+            value: attr.upgrade_sequence.to_string().parse().unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, From)]
+pub struct UpgradeConnectionHopsAttribute {
+    pub connection_hops: Vec<ConnectionId>,
+}
+
+impl From<UpgradeConnectionHopsAttribute> for Tag {
+    fn from(attr: UpgradeConnectionHopsAttribute) -> Self {
+        let connection_hops: Vec<String> = attr
+            .connection_hops
+            .iter()
+            .map(|conn_id| conn_id.to_string())
+            .collect();
+        Tag {
+            key: UPGRADE_CONNECTION_HOPS_ATTRIBUTE_KEY.parse().unwrap(),
+            value: connection_hops.join(",").parse().unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, From)]
+pub struct UpgradeVersionAttribute {
+    pub version: Version,
+}
+
+impl From<UpgradeVersionAttribute> for Tag {
+    fn from(attr: UpgradeVersionAttribute) -> Self {
+        Tag {
+            key: UPGRADE_VERSION_ATTRIBUTE_KEY.parse().unwrap(),
+            value: attr.version.to_string().parse().unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, From)]
+pub struct UpgradeOrderingAttribute {
+    pub ordering: Order,
+}
+
+impl From<UpgradeOrderingAttribute> for Tag {
+    fn from(attr: UpgradeOrderingAttribute) -> Self {
+        Tag {
+            key: UPGRADE_ORDERING_ATTRIBUTE_KEY.parse().unwrap(),
+            value: attr.ordering.as_str().parse().unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, From)]
+pub struct UpgradeErrorReceiptAttribute {
+    pub message: String,
+}
+
+impl From<UpgradeErrorReceiptAttribute> for Tag {
+    fn from(attr: UpgradeErrorReceiptAttribute) -> Self {
+        Tag {
+            key: UPGRADE_ERROR_RECEIPT_ATTRIBUTE_KEY.parse().unwrap(),
+            value: attr.message.parse().unwrap(),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+//! This module holds all the abci event attributes for IBC events emitted
+//! during packet-related datagrams.
+
 use crate::{
     core::{
         ics04_channel::{
@@ -7,6 +10,7 @@ use crate::{
         ics24_host::identifier::{ChannelId, ConnectionId, PortId},
     },
     prelude::*,
+    signer::Signer,
     timestamp::Timestamp,
 };
 use derive_more::From;
@@ -15,24 +19,22 @@ use tendermint::abci::tag::Tag;
 
 use crate::core::ics04_channel::error::Error;
 
-///! This module holds all the abci event attributes for IBC events emitted
-///! during packet-related datagrams.
-
-const PKT_SEQ_ATTRIBUTE_KEY: &str = "packet_sequence";
-const PKT_DATA_ATTRIBUTE_KEY: &str = "packet_data";
-const PKT_DATA_HEX_ATTRIBUTE_KEY: &str = "packet_data_hex";
-const PKT_SRC_PORT_ATTRIBUTE_KEY: &str = "packet_src_port";
-const PKT_SRC_CHANNEL_ATTRIBUTE_KEY: &str = "packet_src_channel";
-const PKT_DST_PORT_ATTRIBUTE_KEY: &str = "packet_dst_port";
-const PKT_DST_CHANNEL_ATTRIBUTE_KEY: &str = "packet_dst_channel";
-const PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY: &str = "packet_channel_ordering";
-const PKT_TIMEOUT_HEIGHT_ATTRIBUTE_KEY: &str = "packet_timeout_height";
-const PKT_TIMEOUT_TIMESTAMP_ATTRIBUTE_KEY: &str = "packet_timeout_timestamp";
-const PKT_ACK_ATTRIBUTE_KEY: &str = "packet_ack";
-const PKT_ACK_HEX_ATTRIBUTE_KEY: &str = "packet_ack_hex";
-const PKT_CONNECTION_ID_ATTRIBUTE_KEY: &str = "packet_connection";
-
-#[derive(Debug, From)]
+pub const PKT_SEQ_ATTRIBUTE_KEY: &str = "packet_sequence";
+pub const PKT_DATA_ATTRIBUTE_KEY: &str = "packet_data";
+pub const PKT_DATA_HEX_ATTRIBUTE_KEY: &str = "packet_data_hex";
+pub const PKT_SRC_PORT_ATTRIBUTE_KEY: &str = "packet_src_port";
+pub const PKT_SRC_CHANNEL_ATTRIBUTE_KEY: &str = "packet_src_channel";
+pub const PKT_DST_PORT_ATTRIBUTE_KEY: &str = "packet_dst_port";
+pub const PKT_DST_CHANNEL_ATTRIBUTE_KEY: &str = "packet_dst_channel";
+pub const PKT_CHANNEL_ORDERING_ATTRIBUTE_KEY: &str = "packet_channel_ordering";
+pub const PKT_TIMEOUT_HEIGHT_ATTRIBUTE_KEY: &str = "packet_timeout_height";
+pub const PKT_TIMEOUT_TIMESTAMP_ATTRIBUTE_KEY: &str = "packet_timeout_timestamp";
+pub const PKT_ACK_ATTRIBUTE_KEY: &str = "packet_ack";
+pub const PKT_ACK_HEX_ATTRIBUTE_KEY: &str = "packet_ack_hex";
+pub const PKT_CONNECTION_ID_ATTRIBUTE_KEY: &str = "packet_connection";
+pub const PKT_RELAYER_ATTRIBUTE_KEY: &str = "packet_relayer";
+
+#[derive(Clone, Debug, From)]
 pub struct PacketDataAttribute {
     pub packet_data: Vec<u8>,
 }
@@ -66,7 +68,7 @@ impl TryFrom<PacketDataAttribute> for Vec<Tag> {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct TimeoutHeightAttribute {
     pub timeout_height: TimeoutHeight,
 }
@@ -85,7 +87,7 @@ impl From<TimeoutHeightAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct TimeoutTimestampAttribute {
     pub timeout_timestamp: Timestamp,
 }
@@ -104,7 +106,7 @@ impl From<TimeoutTimestampAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct SequenceAttribute {
     pub sequence: Sequence,
 }
@@ -118,7 +120,7 @@ impl From<SequenceAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct SrcPortIdAttribute {
     pub src_port_id: PortId,
 }
@@ -132,7 +134,7 @@ impl From<SrcPortIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct SrcChannelIdAttribute {
     pub src_channel_id: ChannelId,
 }
@@ -146,7 +148,7 @@ impl From<SrcChannelIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct DstPortIdAttribute {
     pub dst_port_id: PortId,
 }
@@ -160,7 +162,7 @@ impl From<DstPortIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct DstChannelIdAttribute {
     pub dst_channel_id: ChannelId,
 }
@@ -174,7 +176,7 @@ impl From<DstChannelIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct ChannelOrderingAttribute {
     pub order: Order,
 }
@@ -188,7 +190,7 @@ impl From<ChannelOrderingAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
 pub struct PacketConnectionIdAttribute {
     pub connection_id: ConnectionId,
 }
@@ -202,7 +204,21 @@ impl From<PacketConnectionIdAttribute> for Tag {
     }
 }
 
-#[derive(Debug, From)]
+#[derive(Clone, Debug, From)]
+pub struct RelayerAttribute {
+    pub relayer: Signer,
+}
+
+impl From<RelayerAttribute> for Tag {
+    fn from(attr: RelayerAttribute) -> Self {
+        Tag {
+            key: PKT_RELAYER_ATTRIBUTE_KEY.parse().unwrap(),
+            value: attr.relayer.as_ref().parse().unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, From)]
 pub struct AcknowledgementAttribute {
     pub acknowledgement: Acknowledgement,
 }
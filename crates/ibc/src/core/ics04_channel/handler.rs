@@ -1,22 +1,37 @@
 //! This module implements the processing logic for ICS4 (channel) messages.
-use crate::events::{IbcEvent, ModuleEvent};
+use crate::events::{EventReason, IbcEvent, ModuleEvent};
 use crate::prelude::*;
 
+use crate::core::context::{ExecutionContext, ValidationContext};
 use crate::core::ics04_channel::channel::ChannelEnd;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::channel::State;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::context::ChannelReader;
 use crate::core::ics04_channel::error::Error;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::events::{ChannelUpgrade, UpgradeStep};
 use crate::core::ics04_channel::msgs::ChannelMsg;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
 use crate::core::ics04_channel::packet::Packet;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::packet::Sequence;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::timeout::TimeoutHeight;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::upgrade::{ErrorReceipt, Upgrade, UpgradeFields, UpgradeTimeout};
 use crate::core::ics04_channel::{msgs::PacketMsg, packet::PacketResult};
 use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
 use crate::core::ics26_routing::context::{
-    Acknowledgement, Ics26Context, ModuleId, ModuleOutputBuilder, OnRecvPacketAck, Router,
+    catch_module_panic, Acknowledgement, ModuleId, ModuleOutputBuilder, Router, WrittenAck,
 };
 use crate::handler::{HandlerOutput, HandlerOutputBuilder};
 
 use super::channel::Counterparty;
 use super::events::{CloseConfirm, CloseInit, OpenAck, OpenConfirm, OpenInit, OpenTry};
-use super::Version;
+use super::{negotiate_version, verify_counterparty_version, Version};
 
 pub mod acknowledgement;
 pub mod chan_close_confirm;
@@ -25,6 +40,20 @@ pub mod chan_open_ack;
 pub mod chan_open_confirm;
 pub mod chan_open_init;
 pub mod chan_open_try;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_ack;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_cancel;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_confirm;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_init;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_open;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_timeout;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_try;
 pub mod recv_packet;
 pub mod send_packet;
 pub mod timeout;
@@ -51,6 +80,265 @@ pub struct ChannelResult {
     pub channel_end: ChannelEnd,
 }
 
+/// How a step of the channel upgrade handshake changes a stored upgrade.
+#[cfg(any(test, feature = "unstable"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UpgradeChange {
+    Keep,
+    Store(Upgrade),
+    Delete,
+}
+
+/// The outcome of a step of the channel upgrade handshake, stored by
+/// [`ChannelKeeper::store_upgrade_result`](crate::core::ics04_channel::context::ChannelKeeper::store_upgrade_result).
+#[cfg(any(test, feature = "unstable"))]
+#[derive(Clone, Debug)]
+pub struct UpgradeResult {
+    /// The step that was taken, which is [`UpgradeStep::Error`] if the upgrade was aborted.
+    pub step: UpgradeStep,
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub channel_end: ChannelEnd,
+    pub upgrade: UpgradeChange,
+    pub counterparty_upgrade: UpgradeChange,
+    pub error_receipt: Option<ErrorReceipt>,
+    /// Set when an unordered channel is upgraded to an ordered one, which then receives and
+    /// acknowledges the packets sent once the upgrade completed.
+    pub next_sequence_recv: Option<Sequence>,
+    pub next_sequence_ack: Option<Sequence>,
+}
+
+#[cfg(any(test, feature = "unstable"))]
+impl UpgradeResult {
+    pub(crate) fn new(
+        step: UpgradeStep,
+        port_id: PortId,
+        channel_id: ChannelId,
+        channel_end: ChannelEnd,
+    ) -> Self {
+        Self {
+            step,
+            port_id,
+            channel_id,
+            channel_end,
+            upgrade: UpgradeChange::Keep,
+            counterparty_upgrade: UpgradeChange::Keep,
+            error_receipt: None,
+            next_sequence_recv: None,
+            next_sequence_ack: None,
+        }
+    }
+
+    /// Whether the channel end reopened with its upgraded fields.
+    pub fn is_upgraded(&self) -> bool {
+        matches!(self.step, UpgradeStep::Confirm | UpgradeStep::Open)
+            && self.channel_end.state_matches(&State::Open)
+    }
+}
+
+#[cfg(any(test, feature = "unstable"))]
+pub(crate) fn check_upgrades_enabled<Ctx: ChannelReader>(ctx: &Ctx) -> Result<(), Error> {
+    if ctx.channel_upgrades_enabled() {
+        Ok(())
+    } else {
+        Err(Error::channel_upgrades_disabled())
+    }
+}
+
+/// Checks that the upgrade `fields` of the channel end are compatible with the ones of its
+/// counterparty: they must agree on the ordering and version, and the proposed connections must
+/// be the two ends of the same open connection.
+#[cfg(any(test, feature = "unstable"))]
+pub(crate) fn check_upgrade_compatibility<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    fields: &UpgradeFields,
+    counterparty_fields: &UpgradeFields,
+) -> Result<(), Error> {
+    if fields.ordering != counterparty_fields.ordering {
+        return Err(Error::incompatible_upgrade(format!(
+            "ordering {} differs from the counterparty one {}",
+            fields.ordering, counterparty_fields.ordering
+        )));
+    }
+    if fields.version != counterparty_fields.version {
+        return Err(Error::incompatible_upgrade(format!(
+            "version {} differs from the counterparty one {}",
+            fields.version, counterparty_fields.version
+        )));
+    }
+
+    let hops = ConnectionHops::resolve_open(ctx, &fields.connection_hops)?;
+    if hops.counterparty_hops()? != counterparty_fields.connection_hops {
+        return Err(Error::incompatible_upgrade(format!(
+            "connection {} is not the counterparty of the proposed connection {}",
+            counterparty_fields
+                .connection_hops
+                .first()
+                .map_or_else(String::new, |conn_id| conn_id.to_string()),
+            hops.connection_id()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Starts flushing the packets in flight on the channel end for `upgrade`, after which the
+/// counterparty may time it out.
+#[cfg(any(test, feature = "unstable"))]
+pub(crate) fn start_flushing<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    channel_end: &mut ChannelEnd,
+    upgrade: &mut Upgrade,
+) -> Result<(), Error> {
+    let timeout_timestamp = (ctx.host_timestamp() + ctx.upgrade_timeout())
+        .map_err(|_| Error::invalid_upgrade_timeout())?;
+
+    channel_end.set_state(State::Flushing);
+    upgrade.next_sequence_send = ctx.get_next_sequence_send(port_id, channel_id)?;
+    upgrade.timeout = UpgradeTimeout::new(TimeoutHeight::Never, timeout_timestamp);
+
+    Ok(())
+}
+
+/// Reopens the channel end with its previous fields, forgetting the upgrade in progress, and
+/// records `error_receipt` for the counterparty to cancel its own.
+#[cfg(any(test, feature = "unstable"))]
+pub(crate) fn restore_channel(
+    step: UpgradeStep,
+    port_id: PortId,
+    channel_id: ChannelId,
+    mut channel_end: ChannelEnd,
+    error_receipt: ErrorReceipt,
+) -> UpgradeResult {
+    channel_end.set_state(State::Open);
+
+    UpgradeResult {
+        upgrade: UpgradeChange::Delete,
+        counterparty_upgrade: UpgradeChange::Delete,
+        error_receipt: Some(error_receipt),
+        ..UpgradeResult::new(step, port_id, channel_id, channel_end)
+    }
+}
+
+/// Aborts the upgrade of the channel end, which failed with `error` once the counterparty had
+/// proven its own: the channel end is restored, and the failure recorded for the counterparty.
+///
+/// The message taking the step then succeeds, so that the receipt is committed.
+#[cfg(any(test, feature = "unstable"))]
+pub(crate) fn abort_upgrade(
+    port_id: PortId,
+    channel_id: ChannelId,
+    channel_end: ChannelEnd,
+    error: &Error,
+) -> UpgradeResult {
+    let error_receipt = ErrorReceipt::new(channel_end.upgrade_sequence, error);
+    restore_channel(
+        UpgradeStep::Error,
+        port_id,
+        channel_id,
+        channel_end,
+        error_receipt,
+    )
+}
+
+/// Completes the upgrade of a channel end whose packets, and the ones of its counterparty, were
+/// all flushed: the channel end reopens with the fields of `upgrade`.
+#[cfg(any(test, feature = "unstable"))]
+pub(crate) fn open_upgraded_channel(
+    result: &mut UpgradeResult,
+    upgrade: Upgrade,
+    counterparty_upgrade: &Upgrade,
+) {
+    let channel_end = &mut result.channel_end;
+
+    // An ordered channel receives and acknowledges the packets in sequence, from the first one
+    // sent once the ends started flushing.
+    if !channel_end.ordering().is_ordered() && upgrade.fields.ordering.is_ordered() {
+        result.next_sequence_recv = Some(counterparty_upgrade.next_sequence_send);
+        result.next_sequence_ack = Some(upgrade.next_sequence_send);
+    }
+
+    channel_end.set_state(State::Open);
+    channel_end.ordering = upgrade.fields.ordering;
+    channel_end.connection_hops = upgrade.fields.connection_hops;
+    channel_end.version = upgrade.fields.version;
+
+    result.upgrade = UpgradeChange::Delete;
+    result.counterparty_upgrade = UpgradeChange::Delete;
+}
+
+/// Updates the channel end after one of its packets was acknowledged or timed out while it is
+/// flushing for an upgrade acknowledged by the counterparty: the upgrade is aborted if the
+/// counterparty timed it out meanwhile or if the timeout of an ordered packet closed the channel
+/// end, and the flush completes once no packet is in flight anymore.
+#[cfg(any(test, feature = "unstable"))]
+pub(crate) fn flush_packets<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> Result<Option<UpgradeResult>, Error> {
+    if !ctx.channel_upgrades_enabled() {
+        return Ok(None);
+    }
+
+    let mut channel_end = ctx.channel_end(port_id, channel_id)?;
+    // A timed out packet closed the ordered channel end while it was flushing: its upgrade can
+    // no longer complete, and is aborted along with the one of the counterparty.
+    if channel_end.state_matches(&State::Closed) {
+        if ctx.channel_upgrade(port_id, channel_id)?.is_none() {
+            return Ok(None);
+        }
+        let error_receipt = ErrorReceipt::new(
+            channel_end.upgrade_sequence,
+            &Error::channel_closed(channel_id.clone()),
+        );
+        return Ok(Some(UpgradeResult {
+            upgrade: UpgradeChange::Delete,
+            counterparty_upgrade: UpgradeChange::Delete,
+            error_receipt: Some(error_receipt),
+            ..UpgradeResult::new(
+                UpgradeStep::Error,
+                port_id.clone(),
+                channel_id.clone(),
+                channel_end,
+            )
+        }));
+    }
+    if !channel_end.state_matches(&State::Flushing) {
+        return Ok(None);
+    }
+    let counterparty_upgrade = match ctx.counterparty_upgrade(port_id, channel_id)? {
+        Some(counterparty_upgrade) => counterparty_upgrade,
+        None => return Ok(None),
+    };
+
+    if counterparty_upgrade
+        .timeout
+        .has_elapsed(ctx.host_height(), ctx.host_timestamp())
+    {
+        return Ok(Some(abort_upgrade(
+            port_id.clone(),
+            channel_id.clone(),
+            channel_end,
+            &Error::upgrade_timed_out(),
+        )));
+    }
+
+    if ctx.has_inflight_packets(port_id, channel_id)? {
+        return Ok(None);
+    }
+    channel_end.set_state(State::FlushComplete);
+
+    Ok(Some(UpgradeResult::new(
+        UpgradeStep::FlushComplete,
+        port_id.clone(),
+        channel_id.clone(),
+        channel_end,
+    )))
+}
+
 pub struct ModuleExtras {
     pub events: Vec<ModuleEvent>,
     pub log: Vec<String>,
@@ -67,7 +355,7 @@ impl ModuleExtras {
 
 pub fn channel_validate<Ctx>(ctx: &Ctx, msg: &ChannelMsg) -> Result<ModuleId, Error>
 where
-    Ctx: Ics26Context,
+    Ctx: ValidationContext,
 {
     let module_id = msg.lookup_module(ctx)?;
     if ctx.router().has_route(&module_id) {
@@ -106,28 +394,36 @@ pub fn channel_callback<Ctx>(
     result: &mut ChannelResult,
 ) -> Result<ModuleExtras, Error>
 where
-    Ctx: Ics26Context,
+    Ctx: ExecutionContext,
 {
+    let supported_versions = ctx.router().supported_versions(module_id, &result.port_id);
     let cb = ctx
         .router_mut()
         .get_route_mut(module_id)
         .ok_or_else(Error::route_not_found)?;
 
-    match msg {
+    catch_module_panic(|| match msg {
         ChannelMsg::ChannelOpenInit(msg) => {
+            let version =
+                negotiate_version(&msg.port_id, &supported_versions, &msg.channel.version)?;
             let (extras, version) = cb.on_chan_open_init(
                 msg.channel.ordering,
                 &msg.channel.connection_hops,
                 &msg.port_id,
                 &result.channel_id,
                 msg.channel.counterparty(),
-                &msg.channel.version,
+                &version,
             )?;
             result.channel_end.version = version;
 
             Ok(extras)
         }
         ChannelMsg::ChannelOpenTry(msg) => {
+            verify_counterparty_version(
+                &msg.port_id,
+                &supported_versions,
+                &msg.counterparty_version,
+            )?;
             let (extras, version) = cb.on_chan_open_try(
                 msg.channel.ordering,
                 &msg.channel.connection_hops,
@@ -141,6 +437,11 @@ where
             Ok(extras)
         }
         ChannelMsg::ChannelOpenAck(msg) => {
+            verify_counterparty_version(
+                &msg.port_id,
+                &supported_versions,
+                &msg.counterparty_version,
+            )?;
             cb.on_chan_open_ack(&msg.port_id, &result.channel_id, &msg.counterparty_version)
         }
         ChannelMsg::ChannelOpenConfirm(msg) => {
@@ -152,7 +453,7 @@ where
         ChannelMsg::ChannelCloseConfirm(msg) => {
             cb.on_chan_close_confirm(&msg.port_id, &result.channel_id)
         }
-    }
+    })?
 }
 
 /// Constructs the proper channel event
@@ -199,15 +500,20 @@ pub fn channel_events(
                 .expect("counterparty channel id must exist after channel open confirm"),
             connection_id,
         )),
-        ChannelMsg::ChannelCloseInit(msg) => IbcEvent::CloseInitChannel(CloseInit::new(
-            msg.port_id.clone(),
-            channel_id,
-            counterparty.port_id,
-            counterparty
-                .channel_id
-                .expect("counterparty channel id must exist after channel open ack"),
-            connection_id,
-        )),
+        ChannelMsg::ChannelCloseInit(msg) => IbcEvent::CloseInitChannel(
+            CloseInit::new(
+                msg.port_id.clone(),
+                channel_id,
+                counterparty.port_id,
+                counterparty
+                    .channel_id
+                    .expect("counterparty channel id must exist after channel open ack"),
+                connection_id,
+            )
+            // Closing handshakes are started by the application, which approves them through
+            // its `on_chan_close_init` callback.
+            .with_reason(EventReason::AppRequested),
+        ),
         ChannelMsg::ChannelCloseConfirm(msg) => IbcEvent::CloseConfirmChannel(CloseConfirm::new(
             msg.port_id.clone(),
             channel_id,
@@ -222,9 +528,217 @@ pub fn channel_events(
     vec![event]
 }
 
+#[cfg(any(test, feature = "unstable"))]
+pub fn channel_upgrade_validate<Ctx>(ctx: &Ctx, msg: &ChannelUpgradeMsg) -> Result<ModuleId, Error>
+where
+    Ctx: ValidationContext,
+{
+    let module_id = msg.lookup_module(ctx)?;
+    if ctx.router().has_route(&module_id) {
+        Ok(module_id)
+    } else {
+        Err(Error::route_not_found())
+    }
+}
+
+/// General entry point for processing any type of message related to the ICS4 channel upgrade
+/// handshake protocol.
+#[cfg(any(test, feature = "unstable"))]
+pub fn channel_upgrade_dispatch<Ctx>(
+    ctx: &Ctx,
+    msg: &ChannelUpgradeMsg,
+) -> Result<(Vec<String>, UpgradeResult), Error>
+where
+    Ctx: ChannelReader,
+{
+    let output = match msg {
+        ChannelUpgradeMsg::ChannelUpgradeInit(msg) => chan_upgrade_init::process(ctx, msg),
+        ChannelUpgradeMsg::ChannelUpgradeTry(msg) => chan_upgrade_try::process(ctx, msg),
+        ChannelUpgradeMsg::ChannelUpgradeAck(msg) => chan_upgrade_ack::process(ctx, msg),
+        ChannelUpgradeMsg::ChannelUpgradeConfirm(msg) => chan_upgrade_confirm::process(ctx, msg),
+        ChannelUpgradeMsg::ChannelUpgradeOpen(msg) => chan_upgrade_open::process(ctx, msg),
+        ChannelUpgradeMsg::ChannelUpgradeTimeout(msg) => chan_upgrade_timeout::process(ctx, msg),
+        ChannelUpgradeMsg::ChannelUpgradeCancel(msg) => chan_upgrade_cancel::process(ctx, msg),
+    }?;
+
+    let HandlerOutput { result, log, .. } = output;
+    Ok((log, result))
+}
+
+/// Runs the callbacks of the module for the step taken by `result`. The modules are not told
+/// about the upgrades that were aborted, cancelled or timed out, whose channel ends keep their
+/// fields.
+#[cfg(any(test, feature = "unstable"))]
+pub fn channel_upgrade_callback<Ctx>(
+    ctx: &mut Ctx,
+    module_id: &ModuleId,
+    msg: &ChannelUpgradeMsg,
+    result: &mut UpgradeResult,
+) -> Result<ModuleExtras, Error>
+where
+    Ctx: ExecutionContext,
+{
+    let cb = ctx
+        .router_mut()
+        .get_route_mut(module_id)
+        .ok_or_else(Error::route_not_found)?;
+
+    if result.is_upgraded() {
+        let channel_end = &result.channel_end;
+        return catch_module_panic(|| {
+            cb.on_chan_upgrade_open(
+                &result.port_id,
+                &result.channel_id,
+                channel_end.ordering,
+                &channel_end.connection_hops,
+                &channel_end.version,
+            )
+        })?;
+    }
+    if result.step == UpgradeStep::Error {
+        return Ok(ModuleExtras::empty());
+    }
+
+    match (msg, &mut result.upgrade) {
+        (ChannelUpgradeMsg::ChannelUpgradeInit(_), UpgradeChange::Store(upgrade)) => {
+            let (extras, version) = catch_module_panic(|| {
+                cb.on_chan_upgrade_init(
+                    &result.port_id,
+                    &result.channel_id,
+                    upgrade.fields.ordering,
+                    &upgrade.fields.connection_hops,
+                    &upgrade.fields.version,
+                )
+            })??;
+            upgrade.fields.version = version;
+
+            Ok(extras)
+        }
+        (ChannelUpgradeMsg::ChannelUpgradeTry(_), UpgradeChange::Store(upgrade)) => {
+            let (extras, version) = catch_module_panic(|| {
+                cb.on_chan_upgrade_try(
+                    &result.port_id,
+                    &result.channel_id,
+                    upgrade.fields.ordering,
+                    &upgrade.fields.connection_hops,
+                    &upgrade.fields.version,
+                )
+            })??;
+            upgrade.fields.version = version;
+
+            Ok(extras)
+        }
+        (ChannelUpgradeMsg::ChannelUpgradeAck(msg), _) => {
+            // As in ibc-go, the upgrade is aborted if the module rejects the version of the
+            // counterparty, whose upgrade was proven already.
+            let ack = catch_module_panic(|| {
+                cb.on_chan_upgrade_ack(
+                    &result.port_id,
+                    &result.channel_id,
+                    &msg.counterparty_upgrade.fields.version,
+                )
+            })?;
+            match ack {
+                Ok(extras) => Ok(extras),
+                Err(e) => {
+                    *result = abort_upgrade(
+                        result.port_id.clone(),
+                        result.channel_id.clone(),
+                        result.channel_end.clone(),
+                        &e,
+                    );
+                    Ok(ModuleExtras::empty())
+                }
+            }
+        }
+        _ => Ok(ModuleExtras::empty()),
+    }
+}
+
+/// Constructs the channel upgrade events of `result`, before it is stored: the upgrade in
+/// progress is looked up in `ctx` when the step did not change it.
+#[cfg(any(test, feature = "unstable"))]
+pub fn channel_upgrade_events<Ctx>(
+    ctx: &Ctx,
+    result: &UpgradeResult,
+) -> Result<Vec<IbcEvent>, Error>
+where
+    Ctx: ChannelReader,
+{
+    let channel_end = &result.channel_end;
+    let counterparty = channel_end.counterparty();
+    let counterparty_channel_id = counterparty
+        .channel_id()
+        .cloned()
+        .ok_or_else(Error::invalid_counterparty_channel_id)?;
+
+    let upgrade = match &result.upgrade {
+        _ if channel_end.state_matches(&State::Open) => None,
+        UpgradeChange::Store(upgrade) => Some(upgrade.clone()),
+        _ => ctx.channel_upgrade(&result.port_id, &result.channel_id)?,
+    };
+    let fields = upgrade.map(|upgrade| upgrade.fields).unwrap_or_else(|| {
+        UpgradeFields::new(
+            channel_end.ordering,
+            channel_end.connection_hops.clone(),
+            channel_end.version.clone(),
+        )
+    });
+
+    let event = |step: UpgradeStep| {
+        ChannelUpgrade::new(
+            step,
+            result.port_id.clone(),
+            result.channel_id.clone(),
+            counterparty.port_id().clone(),
+            counterparty_channel_id.clone(),
+            channel_end.upgrade_sequence,
+            fields.connection_hops.clone(),
+            fields.version.clone(),
+            fields.ordering,
+        )
+    };
+
+    let mut step_event = event(result.step);
+    if let Some(error_receipt) = &result.error_receipt {
+        step_event = step_event.with_error_receipt(error_receipt.message.clone());
+    }
+    let mut events = vec![IbcEvent::ChannelUpgrade(step_event)];
+    // A confirmation that completes the upgrade also reopens the channel end.
+    if result.step == UpgradeStep::Confirm && result.is_upgraded() {
+        events.push(IbcEvent::ChannelUpgrade(event(UpgradeStep::Open)));
+    }
+
+    Ok(events)
+}
+
+/// Completes the flush of the channel end of an acknowledged or timed out packet, if it is
+/// flushing for an upgrade, storing the outcome and returning its events.
+#[cfg(any(test, feature = "unstable"))]
+pub(crate) fn packet_flush<Ctx>(ctx: &mut Ctx, msg: &PacketMsg) -> Result<Vec<IbcEvent>, Error>
+where
+    Ctx: ExecutionContext,
+{
+    let packet = match msg {
+        PacketMsg::RecvPacket(_) => return Ok(Vec::new()),
+        PacketMsg::AckPacket(msg) => &msg.packet,
+        PacketMsg::TimeoutPacket(msg) => &msg.packet,
+        PacketMsg::TimeoutOnClosePacket(msg) => &msg.packet,
+    };
+
+    match flush_packets(ctx, &packet.source_port, &packet.source_channel)? {
+        Some(result) => {
+            let events = channel_upgrade_events(ctx, &result)?;
+            ctx.store_upgrade_result(result)?;
+            Ok(events)
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
 pub fn get_module_for_packet_msg<Ctx>(ctx: &Ctx, msg: &PacketMsg) -> Result<ModuleId, Error>
 where
-    Ctx: Ics26Context,
+    Ctx: ValidationContext,
 {
     let module_id = match msg {
         PacketMsg::RecvPacket(msg) => ctx
@@ -278,7 +792,7 @@ pub fn packet_callback<Ctx>(
     output: &mut HandlerOutputBuilder<()>,
 ) -> Result<(), Error>
 where
-    Ctx: Ics26Context,
+    Ctx: ExecutionContext,
 {
     let mut module_output = ModuleOutputBuilder::new();
     let mut core_output = HandlerOutputBuilder::new();
@@ -291,7 +805,7 @@ where
 }
 
 fn do_packet_callback(
-    ctx: &mut impl Ics26Context,
+    ctx: &mut impl ExecutionContext,
     module_id: &ModuleId,
     msg: &PacketMsg,
     module_output: &mut ModuleOutputBuilder,
@@ -304,57 +818,48 @@ fn do_packet_callback(
 
     match msg {
         PacketMsg::RecvPacket(msg) => {
-            let result = cb.on_recv_packet(module_output, &msg.packet, &msg.signer);
-            match result {
-                OnRecvPacketAck::Nil(write_fn) => {
-                    write_fn(cb.as_any_mut()).map_err(Error::app_module)
-                }
-                OnRecvPacketAck::Successful(ack, write_fn) => {
-                    write_fn(cb.as_any_mut()).map_err(Error::app_module)?;
+            let ack = catch_module_panic(|| {
+                cb.on_recv_packet(module_output, &msg.packet, &msg.signer)
+                    .write(cb.as_any_mut(), module_output)
+            })?
+            .map_err(Error::app_module)?;
 
+            match ack {
+                WrittenAck::Successful(ack) | WrittenAck::Failed(ack) => {
                     process_write_ack(ctx, msg.packet.clone(), ack.as_ref(), core_output)
                 }
-                OnRecvPacketAck::Failed(ack) => {
-                    process_write_ack(ctx, msg.packet.clone(), ack.as_ref(), core_output)
-                }
+                WrittenAck::Nil => Ok(()),
             }
         }
-        PacketMsg::AckPacket(msg) => cb.on_acknowledgement_packet(
-            module_output,
-            &msg.packet,
-            &msg.acknowledgement,
-            &msg.signer,
-        ),
+        PacketMsg::AckPacket(msg) => catch_module_panic(|| {
+            cb.on_acknowledgement_packet(
+                module_output,
+                &msg.packet,
+                &msg.acknowledgement,
+                &msg.signer,
+            )
+        })?,
         PacketMsg::TimeoutPacket(msg) => {
-            cb.on_timeout_packet(module_output, &msg.packet, &msg.signer)
+            catch_module_panic(|| cb.on_timeout_packet(module_output, &msg.packet, &msg.signer))?
         }
         PacketMsg::TimeoutOnClosePacket(msg) => {
-            cb.on_timeout_packet(module_output, &msg.packet, &msg.signer)
+            catch_module_panic(|| cb.on_timeout_packet(module_output, &msg.packet, &msg.signer))?
         }
     }
 }
 
 fn process_write_ack(
-    ctx: &mut impl Ics26Context,
+    ctx: &mut impl ExecutionContext,
     packet: Packet,
     acknowledgement: &dyn Acknowledgement,
     core_output: &mut HandlerOutputBuilder<()>,
 ) -> Result<(), Error> {
-    let HandlerOutput {
-        result,
-        log,
-        events,
-    } = write_acknowledgement::process(ctx, packet, acknowledgement.as_ref().to_vec().into())?;
-
-    // store write ack result
-    ctx.store_packet_result(result)?;
-
-    core_output.merge_output(
-        HandlerOutput::builder()
-            .with_log(log)
-            .with_events(events)
-            .with_result(()),
-    );
+    let output = write_acknowledgement::emit_acknowledgement(
+        ctx,
+        packet,
+        acknowledgement.as_ref().to_vec().into(),
+    )?;
+    core_output.merge_output(output);
 
     Ok(())
 }
@@ -1,11 +1,12 @@
-use crate::core::ics03_connection::connection::State as ConnectionState;
+use crate::core::ics04_channel::channel::Counterparty;
 use crate::core::ics04_channel::channel::State;
-use crate::core::ics04_channel::channel::{Counterparty, Order};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::{channel_upgrades_enabled, ChannelReader};
+use crate::core::ics04_channel::error::{Error, ErrorDetail};
 use crate::core::ics04_channel::events::AcknowledgePacket;
-use crate::core::ics04_channel::handler::verify::verify_packet_acknowledgement_proofs;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
 use crate::core::ics04_channel::msgs::acknowledgement::MsgAcknowledgement;
 use crate::core::ics04_channel::packet::{PacketResult, Sequence};
-use crate::core::ics04_channel::{context::ChannelReader, error::Error};
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::events::IbcEvent;
 use crate::handler::{HandlerOutput, HandlerResult};
@@ -29,7 +30,10 @@ pub fn process<Ctx: ChannelReader>(
 
     let source_channel_end = ctx.channel_end(&packet.source_port, &packet.source_channel)?;
 
-    if !source_channel_end.state_matches(&State::Open) {
+    // The packets in flight of a channel end flushing for an upgrade may still be settled.
+    let settling_state = source_channel_end.state_matches(&State::Open)
+        || (source_channel_end.state_matches(&State::Flushing) && channel_upgrades_enabled(ctx));
+    if !settling_state {
         return Err(Error::channel_closed(packet.source_channel.clone()));
     }
 
@@ -45,18 +49,37 @@ pub fn process<Ctx: ChannelReader>(
         ));
     }
 
-    let source_connection_id = &source_channel_end.connection_hops()[0];
-    let connection_end = ctx.connection_end(source_connection_id)?;
+    let hops = ConnectionHops::resolve_open(ctx, source_channel_end.connection_hops())?;
+    let source_connection_id = hops.connection_id();
 
-    if !connection_end.state_matches(&ConnectionState::Open) {
-        return Err(Error::connection_not_open(
-            source_channel_end.connection_hops()[0].clone(),
-        ));
-    }
+    let acknowledge_packet_event = || {
+        let event = AcknowledgePacket::new(
+            packet.clone(),
+            source_channel_end.ordering,
+            source_connection_id.clone(),
+        );
+        if ctx.packet_events_with_relayer() {
+            IbcEvent::AcknowledgePacket(event.with_relayer(msg.signer.clone()))
+        } else {
+            IbcEvent::AcknowledgePacket(event)
+        }
+    };
 
-    // Verify packet commitment
-    let packet_commitment =
-        ctx.get_packet_commitment(&packet.source_port, &packet.source_channel, packet.sequence)?;
+    // Verify packet commitment. A packet already acknowledged or timed out has none left, and
+    // its acknowledgement is a no-op.
+    let packet_commitment = match ctx.get_packet_commitment(
+        &packet.source_port,
+        &packet.source_channel,
+        packet.sequence,
+    ) {
+        Ok(packet_commitment) => packet_commitment,
+        Err(e) if matches!(e.detail(), ErrorDetail::PacketCommitmentNotFound(_)) => {
+            output.emit(acknowledge_packet_event());
+
+            return Ok(output.with_result(PacketResult::NoOp));
+        }
+        Err(e) => return Err(e),
+    };
 
     if packet_commitment
         != ctx.packet_commitment(
@@ -69,16 +92,10 @@ pub fn process<Ctx: ChannelReader>(
     }
 
     // Verify the acknowledgement proof
-    verify_packet_acknowledgement_proofs(
-        ctx,
-        msg.proofs.height(),
-        packet,
-        msg.acknowledgement.clone(),
-        &connection_end,
-        &msg.proofs,
-    )?;
-
-    let result = if source_channel_end.order_matches(&Order::Ordered) {
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?
+        .verify_packet_acknowledgement_proofs(packet, msg.acknowledgement.clone())?;
+
+    let result = if source_channel_end.ordering().is_ordered() {
         let next_seq_ack =
             ctx.get_next_sequence_ack(&packet.source_port, &packet.source_channel)?;
 
@@ -106,11 +123,7 @@ pub fn process<Ctx: ChannelReader>(
 
     output.log("success: packet ack");
 
-    output.emit(IbcEvent::AcknowledgePacket(AcknowledgePacket::new(
-        packet.clone(),
-        source_channel_end.ordering,
-        source_connection_id.clone(),
-    )));
+    output.emit(acknowledge_packet_event());
 
     Ok(output.with_result(result))
 }
@@ -125,10 +138,11 @@ mod tests {
     use crate::core::ics03_connection::connection::State as ConnectionState;
     use crate::core::ics03_connection::version::get_compatible_versions;
     use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
-    use crate::core::ics04_channel::context::ChannelReader;
+    use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
     use crate::core::ics04_channel::handler::acknowledgement::process;
     use crate::core::ics04_channel::msgs::acknowledgement::test_util::get_dummy_raw_msg_acknowledgement;
     use crate::core::ics04_channel::msgs::acknowledgement::MsgAcknowledgement;
+    use crate::core::ics04_channel::packet::PacketResult;
     use crate::core::ics04_channel::Version;
     use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
     use crate::events::IbcEvent;
@@ -251,4 +265,55 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn ack_of_settled_packet_is_noop() {
+        let client_height = Height::new(0, 2).unwrap();
+        let msg = MsgAcknowledgement::try_from(get_dummy_raw_msg_acknowledgement(
+            client_height.revision_height(),
+        ))
+        .unwrap();
+        let packet = msg.packet.clone();
+
+        let source_channel_end = ChannelEnd::new(
+            State::Open,
+            Order::default(),
+            Counterparty::new(
+                packet.destination_port.clone(),
+                Some(packet.destination_channel.clone()),
+            ),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        // No packet commitment: the packet was already acknowledged.
+        let mut ctx = MockContext::default()
+            .with_client(&ClientId::default(), client_height)
+            .with_connection(ConnectionId::default(), connection_end)
+            .with_channel(
+                packet.source_port,
+                packet.source_channel,
+                source_channel_end,
+            );
+
+        let output = process(&ctx, &msg).unwrap();
+
+        assert!(matches!(output.result, PacketResult::NoOp));
+        assert!(matches!(
+            output.events.as_slice(),
+            [IbcEvent::AcknowledgePacket(_)]
+        ));
+        // Hosts composing the handler with the store write nothing.
+        ctx.store_packet_result(output.result).unwrap();
+    }
 }
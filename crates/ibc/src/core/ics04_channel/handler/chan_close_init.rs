@@ -1,6 +1,6 @@
 //! Protocol logic specific to ICS4 messages of type `MsgChannelCloseInit`.
-use crate::core::ics03_connection::connection::State as ConnectionState;
 use crate::core::ics04_channel::channel::State;
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::context::ChannelReader;
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::handler::{ChannelIdState, ChannelResult};
@@ -25,20 +25,7 @@ pub(crate) fn process<Ctx: ChannelReader>(
     }
 
     // An OPEN IBC connection running on the local (host) chain should exist.
-    if channel_end.connection_hops().len() != 1 {
-        return Err(Error::invalid_connection_hops_length(
-            1,
-            channel_end.connection_hops().len(),
-        ));
-    }
-
-    let conn = ctx.connection_end(&channel_end.connection_hops()[0])?;
-
-    if !conn.state_matches(&ConnectionState::Open) {
-        return Err(Error::connection_not_open(
-            channel_end.connection_hops()[0].clone(),
-        ));
-    }
+    ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?;
 
     output.log("success: channel close init");
 
@@ -1,9 +1,9 @@
 //! Protocol logic specific to ICS4 messages of type `MsgChannelOpenAck`.
-use crate::core::ics03_connection::connection::State as ConnectionState;
 use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, State};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::context::ChannelReader;
 use crate::core::ics04_channel::error::Error;
-use crate::core::ics04_channel::handler::verify::verify_channel_proofs;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
 use crate::core::ics04_channel::handler::{ChannelIdState, ChannelResult};
 use crate::core::ics04_channel::msgs::chan_open_ack::MsgChannelOpenAck;
 use crate::handler::{HandlerOutput, HandlerResult};
@@ -27,21 +27,8 @@ pub(crate) fn process<Ctx: ChannelReader>(
     }
 
     // An OPEN IBC connection running on the local (host) chain should exist.
-
-    if channel_end.connection_hops().len() != 1 {
-        return Err(Error::invalid_connection_hops_length(
-            1,
-            channel_end.connection_hops().len(),
-        ));
-    }
-
-    let conn = ctx.connection_end(&channel_end.connection_hops()[0])?;
-
-    if !conn.state_matches(&ConnectionState::Open) {
-        return Err(Error::connection_not_open(
-            channel_end.connection_hops()[0].clone(),
-        ));
-    }
+    let hops = ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?
+        .with_remote_hops(msg.proofs.object_proof())?;
 
     // Proof verification in two steps:
     // 1. Setup: build the Channel as we expect to find it on the other party.
@@ -49,12 +36,7 @@ pub(crate) fn process<Ctx: ChannelReader>(
     let expected_counterparty =
         Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone()));
 
-    let counterparty = conn.counterparty();
-    let ccid = counterparty.connection_id().ok_or_else(|| {
-        Error::undefined_connection_counterparty(channel_end.connection_hops()[0].clone())
-    })?;
-
-    let expected_connection_hops = vec![ccid.clone()];
+    let expected_connection_hops = hops.counterparty_hops()?;
 
     let expected_channel_end = ChannelEnd::new(
         State::TryOpen,
@@ -68,14 +50,8 @@ pub(crate) fn process<Ctx: ChannelReader>(
     channel_end.set_counterparty_channel_id(msg.counterparty_channel_id.clone());
 
     //2. Verify proofs
-    verify_channel_proofs(
-        ctx,
-        msg.proofs.height(),
-        &channel_end,
-        &conn,
-        &expected_channel_end,
-        &msg.proofs,
-    )?;
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?
+        .verify_channel_proofs(&channel_end, &expected_channel_end)?;
 
     output.log("success: channel open ack ");
 
@@ -151,10 +127,7 @@ mod tests {
         );
 
         let ccid = <ConnectionId as FromStr>::from_str("defaultConnection-0");
-        let cid = match ccid {
-            Ok(v) => v,
-            Err(_e) => ConnectionId::default(),
-        };
+        let cid = ccid.unwrap_or_default();
 
         let mut connection_vec0 = Vec::new();
         connection_vec0.insert(
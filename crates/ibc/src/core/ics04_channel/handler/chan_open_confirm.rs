@@ -1,9 +1,9 @@
 //! Protocol logic specific to ICS4 messages of type `MsgChannelOpenConfirm`.
-use crate::core::ics03_connection::connection::State as ConnectionState;
 use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, State};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::context::ChannelReader;
 use crate::core::ics04_channel::error::Error;
-use crate::core::ics04_channel::handler::verify::verify_channel_proofs;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
 use crate::core::ics04_channel::handler::{ChannelIdState, ChannelResult};
 use crate::core::ics04_channel::msgs::chan_open_confirm::MsgChannelOpenConfirm;
 use crate::handler::{HandlerOutput, HandlerResult};
@@ -27,20 +27,8 @@ pub(crate) fn process<Ctx: ChannelReader>(
     }
 
     // An OPEN IBC connection running on the local (host) chain should exist.
-    if channel_end.connection_hops().len() != 1 {
-        return Err(Error::invalid_connection_hops_length(
-            1,
-            channel_end.connection_hops().len(),
-        ));
-    }
-
-    let conn = ctx.connection_end(&channel_end.connection_hops()[0])?;
-
-    if !conn.state_matches(&ConnectionState::Open) {
-        return Err(Error::connection_not_open(
-            channel_end.connection_hops()[0].clone(),
-        ));
-    }
+    let hops = ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?
+        .with_remote_hops(msg.proofs.object_proof())?;
 
     // Proof verification in two steps:
     // 1. Setup: build the Channel as we expect to find it on the other party.
@@ -48,12 +36,7 @@ pub(crate) fn process<Ctx: ChannelReader>(
     let expected_counterparty =
         Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone()));
 
-    let connection_counterparty = conn.counterparty();
-    let ccid = connection_counterparty.connection_id().ok_or_else(|| {
-        Error::undefined_connection_counterparty(channel_end.connection_hops()[0].clone())
-    })?;
-
-    let expected_connection_hops = vec![ccid.clone()];
+    let expected_connection_hops = hops.counterparty_hops()?;
 
     let expected_channel_end = ChannelEnd::new(
         State::Open,
@@ -63,15 +46,9 @@ pub(crate) fn process<Ctx: ChannelReader>(
         channel_end.version().clone(),
     );
     //2. Verify proofs
-    verify_channel_proofs(
-        ctx,
-        msg.proofs.height(),
-        &channel_end,
-        &conn,
-        &expected_channel_end,
-        &msg.proofs,
-    )
-    .map_err(Error::chan_open_confirm_proof_verification)?;
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)
+        .and_then(|verifier| verifier.verify_channel_proofs(&channel_end, &expected_channel_end))
+        .map_err(Error::chan_open_confirm_proof_verification)?;
 
     output.log("success: channel open confirm ");
 
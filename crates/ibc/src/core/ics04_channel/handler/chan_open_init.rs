@@ -1,6 +1,7 @@
 //! Protocol logic specific to ICS4 messages of type `MsgChannelOpenInit`.
 
 use crate::core::ics04_channel::channel::{ChannelEnd, State};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::context::ChannelReader;
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::handler::{ChannelIdState, ChannelResult};
@@ -15,15 +16,9 @@ pub(crate) fn process<Ctx: ChannelReader>(
 ) -> HandlerResult<ChannelResult, Error> {
     let mut output = HandlerOutput::builder();
 
-    if msg.channel.connection_hops().len() != 1 {
-        return Err(Error::invalid_connection_hops_length(
-            1,
-            msg.channel.connection_hops().len(),
-        ));
-    }
-
     // An IBC connection running on the local (host) chain should exist.
-    let conn = ctx.connection_end(&msg.channel.connection_hops()[0])?;
+    let hops = ConnectionHops::resolve(ctx, msg.channel.connection_hops())?;
+    let conn = hops.connection_end();
     let get_versions = conn.versions();
     let version = match get_versions {
         [version] => version,
@@ -37,7 +32,8 @@ pub(crate) fn process<Ctx: ChannelReader>(
 
     // Channel identifier construction.
     let id_counter = ctx.channel_counter()?;
-    let chan_id = ChannelId::new(id_counter);
+    let chan_id = ChannelId::with_prefix(&ctx.channel_id_prefix(&msg.port_id), id_counter)
+        .map_err(Error::identifier)?;
 
     let new_channel_end = ChannelEnd::new(
         State::Init,
@@ -72,8 +68,8 @@ mod tests {
     use crate::core::ics03_connection::connection::State as ConnectionState;
     use crate::core::ics03_connection::msgs::conn_open_init::test_util::get_dummy_raw_msg_conn_open_init;
     use crate::core::ics03_connection::msgs::conn_open_init::MsgConnectionOpenInit;
-    use crate::core::ics03_connection::version::get_compatible_versions;
-    use crate::core::ics04_channel::channel::State;
+    use crate::core::ics03_connection::version::{get_compatible_versions, Version};
+    use crate::core::ics04_channel::channel::{Order, State};
     use crate::core::ics04_channel::handler::channel_dispatch;
     use crate::core::ics04_channel::msgs::chan_open_init::test_util::get_dummy_raw_msg_chan_open_init;
     use crate::core::ics04_channel::msgs::chan_open_init::MsgChannelOpenInit;
@@ -106,6 +102,17 @@ mod tests {
             msg_conn_init.delay_period,
         );
 
+        let allow_timeout_conn_end = ConnectionEnd::new(
+            ConnectionState::Init,
+            msg_conn_init.client_id_on_a.clone(),
+            msg_conn_init.counterparty.clone(),
+            vec![Version::default().with_ordered_allow_timeout()],
+            msg_conn_init.delay_period,
+        );
+
+        let mut msg_chan_init_allow_timeout = msg_chan_init.clone();
+        msg_chan_init_allow_timeout.channel.ordering = Order::OrderedAllowTimeout;
+
         let cid = ConnectionId::default();
 
         let tests: Vec<Test> = vec![
@@ -115,6 +122,22 @@ mod tests {
                 msg: ChannelMsg::ChannelOpenInit(msg_chan_init.clone()),
                 want_pass: false,
             },
+            Test {
+                name: "Ordered-allow-timeout channel not supported by the connection".to_string(),
+                ctx: context
+                    .clone()
+                    .with_connection(cid.clone(), init_conn_end.clone()),
+                msg: ChannelMsg::ChannelOpenInit(msg_chan_init_allow_timeout.clone()),
+                want_pass: false,
+            },
+            Test {
+                name: "Ordered-allow-timeout channel supported by the connection".to_string(),
+                ctx: context
+                    .clone()
+                    .with_connection(cid.clone(), allow_timeout_conn_end),
+                msg: ChannelMsg::ChannelOpenInit(msg_chan_init_allow_timeout),
+                want_pass: true,
+            },
             Test {
                 name: "Good parameters".to_string(),
                 ctx: context.with_connection(cid, init_conn_end),
@@ -1,10 +1,10 @@
 //! Protocol logic specific to ICS4 messages of type `MsgChannelOpenTry`.
 
-use crate::core::ics03_connection::connection::State as ConnectionState;
 use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, State};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::context::ChannelReader;
 use crate::core::ics04_channel::error::Error;
-use crate::core::ics04_channel::handler::verify::verify_channel_proofs;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
 use crate::core::ics04_channel::handler::{ChannelIdState, ChannelResult};
 use crate::core::ics04_channel::msgs::chan_open_try::MsgChannelOpenTry;
 use crate::core::ics04_channel::Version;
@@ -19,19 +19,9 @@ pub(crate) fn process<Ctx: ChannelReader>(
     let mut output = HandlerOutput::builder();
 
     // An IBC connection running on the local (host) chain should exist.
-    if msg.channel.connection_hops().len() != 1 {
-        return Err(Error::invalid_connection_hops_length(
-            1,
-            msg.channel.connection_hops().len(),
-        ));
-    }
-
-    let conn = ctx.connection_end(&msg.channel.connection_hops()[0])?;
-    if !conn.state_matches(&ConnectionState::Open) {
-        return Err(Error::connection_not_open(
-            msg.channel.connection_hops()[0].clone(),
-        ));
-    }
+    let hops = ConnectionHops::resolve_open(ctx, msg.channel.connection_hops())?
+        .with_remote_hops(msg.proofs.object_proof())?;
+    let conn = hops.connection_end();
 
     let get_versions = conn.versions();
     let version = match get_versions {
@@ -49,11 +39,7 @@ pub(crate) fn process<Ctx: ChannelReader>(
     //      the port should be identical with the port we're using; the channel id should not be set
     //      since the counterparty cannot know yet which ID did we choose.
     let expected_counterparty = Counterparty::new(msg.port_id.clone(), None);
-    let counterparty = conn.counterparty();
-    let ccid = counterparty.connection_id().ok_or_else(|| {
-        Error::undefined_connection_counterparty(msg.channel.connection_hops()[0].clone())
-    })?;
-    let expected_connection_hops = vec![ccid.clone()];
+    let expected_connection_hops = hops.counterparty_hops()?;
 
     // The other party should be storing a channel end in this configuration.
     let expected_channel_end = ChannelEnd::new(
@@ -65,18 +51,14 @@ pub(crate) fn process<Ctx: ChannelReader>(
     );
 
     // 2. Actual proofs are verified now.
-    verify_channel_proofs(
-        ctx,
-        msg.proofs.height(),
-        &msg.channel,
-        &conn,
-        &expected_channel_end,
-        &msg.proofs,
-    )?;
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?
+        .verify_channel_proofs(&msg.channel, &expected_channel_end)?;
 
     output.log("success: channel open try");
 
-    let channel_id = ChannelId::new(ctx.channel_counter()?);
+    let channel_id =
+        ChannelId::with_prefix(&ctx.channel_id_prefix(&msg.port_id), ctx.channel_counter()?)
+            .map_err(Error::identifier)?;
     let channel_end = ChannelEnd::new(
         State::TryOpen,
         *msg.channel.ordering(),
@@ -116,9 +98,11 @@ mod tests {
     use crate::core::ics04_channel::msgs::chan_open_try::test_util::get_dummy_raw_msg_chan_open_try;
     use crate::core::ics04_channel::msgs::chan_open_try::MsgChannelOpenTry;
     use crate::core::ics04_channel::msgs::ChannelMsg;
+    use crate::core::ics04_channel::multihop::test_util::get_dummy_multihop_proof;
     use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId};
     use crate::mock::client_state::client_type as mock_client_type;
     use crate::mock::context::MockContext;
+    use crate::proofs::Proofs;
     use crate::timestamp::ZERO_DURATION;
     use crate::Height;
 
@@ -325,4 +309,59 @@ mod tests {
         // Makes sure we don't crash
         let _ = chan_open_try::process(&context, &msg);
     }
+
+    #[test]
+    fn chan_open_try_multihop() {
+        let proof_height = 10;
+        let client_id = ClientId::new(mock_client_type(), 45).unwrap();
+        let conn_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            client_id.clone(),
+            ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        let context = MockContext::default()
+            .with_client(&client_id, Height::new(0, proof_height).unwrap())
+            .with_connection(ConnectionId::new(2), conn_end);
+
+        // The channel goes through connection 2 of the host chain, then through connection 3 of
+        // an intermediate chain.
+        let mut msg =
+            MsgChannelOpenTry::try_from(get_dummy_raw_msg_chan_open_try(proof_height)).unwrap();
+        msg.channel.connection_hops = vec![ConnectionId::new(2), ConnectionId::new(3)];
+        let with_proof = |msg: &MsgChannelOpenTry, hop: ConnectionId| {
+            let proof = get_dummy_multihop_proof(hop, ConnectionId::new(8), proof_height - 1);
+            MsgChannelOpenTry {
+                proofs: Proofs::new(
+                    proof.encode_proof().unwrap(),
+                    None,
+                    None,
+                    None,
+                    msg.proofs.height(),
+                )
+                .unwrap(),
+                ..msg.clone()
+            }
+        };
+
+        let output =
+            chan_open_try::process(&context, &with_proof(&msg, ConnectionId::new(3))).unwrap();
+        assert_eq!(output.result.channel_end.state, State::TryOpen);
+        assert_eq!(
+            output.result.channel_end.connection_hops(),
+            msg.channel.connection_hops()
+        );
+
+        // The proof must go through the intermediate connection of the channel.
+        let err =
+            chan_open_try::process(&context, &with_proof(&msg, ConnectionId::new(4))).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            error::ErrorDetail::InvalidMultihopProof(_)
+        ));
+
+        // A single-hop proof is no multihop proof.
+        assert!(chan_open_try::process(&context, &msg).is_err());
+    }
 }
@@ -0,0 +1,258 @@
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeAck`.
+use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, State};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::UpgradeStep;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
+use crate::core::ics04_channel::handler::{
+    abort_upgrade, check_upgrade_compatibility, check_upgrades_enabled, start_flushing,
+    UpgradeChange, UpgradeResult,
+};
+use crate::core::ics04_channel::msgs::chan_upgrade_ack::MsgChannelUpgradeAck;
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+pub(crate) fn process<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    msg: &MsgChannelUpgradeAck,
+) -> HandlerResult<UpgradeResult, Error> {
+    let mut output = HandlerOutput::builder();
+
+    check_upgrades_enabled(ctx)?;
+
+    let mut channel_end = ctx.channel_end(&msg.port_id, &msg.channel_id)?;
+
+    // The channel end either proposed the upgrade, or took it up while the counterparty did too.
+    if !channel_end.state_matches(&State::Open) && !channel_end.state_matches(&State::Flushing) {
+        return Err(Error::invalid_channel_state(
+            msg.channel_id.clone(),
+            channel_end.state,
+        ));
+    }
+
+    let hops = ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?
+        .with_remote_hops(msg.proofs.object_proof())?;
+
+    let mut upgrade = ctx
+        .channel_upgrade(&msg.port_id, &msg.channel_id)?
+        .ok_or_else(|| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id.clone()))?;
+
+    // The counterparty must be flushing for the same upgrade.
+    let expected_counterparty =
+        Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone()));
+    let expected_channel_end = ChannelEnd::new(
+        State::Flushing,
+        *channel_end.ordering(),
+        expected_counterparty,
+        hops.counterparty_hops()?,
+        channel_end.version().clone(),
+    )
+    .with_upgrade_sequence(channel_end.upgrade_sequence);
+
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?.verify_channel_upgrade_proofs(
+        &channel_end,
+        &expected_channel_end,
+        &msg.counterparty_upgrade,
+    )?;
+
+    let failure =
+        check_upgrade_compatibility(ctx, &upgrade.fields, &msg.counterparty_upgrade.fields)
+            .err()
+            .or_else(|| {
+                msg.counterparty_upgrade
+                    .timeout
+                    .has_elapsed(ctx.host_height(), ctx.host_timestamp())
+                    .then(Error::upgrade_timed_out)
+            });
+    if let Some(error) = failure {
+        let result = abort_upgrade(
+            msg.port_id.clone(),
+            msg.channel_id.clone(),
+            channel_end,
+            &error,
+        );
+
+        output.log("failure: channel upgrade ack");
+        return Ok(output.with_result(result));
+    }
+
+    let mut result_upgrade = UpgradeChange::Keep;
+    if channel_end.state_matches(&State::Open) {
+        start_flushing(
+            ctx,
+            &msg.port_id,
+            &msg.channel_id,
+            &mut channel_end,
+            &mut upgrade,
+        )?;
+        result_upgrade = UpgradeChange::Store(upgrade);
+    }
+
+    if !ctx.has_inflight_packets(&msg.port_id, &msg.channel_id)? {
+        channel_end.set_state(State::FlushComplete);
+    }
+
+    output.log("success: channel upgrade ack");
+
+    let mut result = UpgradeResult::new(
+        UpgradeStep::Ack,
+        msg.port_id.clone(),
+        msg.channel_id.clone(),
+        channel_end,
+    );
+    result.upgrade = result_upgrade;
+    result.counterparty_upgrade = UpgradeChange::Store(msg.counterparty_upgrade.clone());
+
+    Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use test_log::test;
+
+    use crate::core::ics04_channel::channel::State;
+    use crate::core::ics04_channel::commitment::PacketCommitment;
+    use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+    use crate::core::ics04_channel::events::UpgradeStep;
+    use crate::core::ics04_channel::handler::{
+        channel_upgrade_dispatch, flush_packets, UpgradeChange,
+    };
+    use crate::core::ics04_channel::msgs::chan_upgrade_ack::test_util::get_dummy_raw_msg_chan_upgrade_ack;
+    use crate::core::ics04_channel::msgs::chan_upgrade_ack::MsgChannelUpgradeAck;
+    use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+    use crate::core::ics04_channel::timeout::TimeoutHeight;
+    use crate::core::ics04_channel::upgrade::test_util::{
+        get_dummy_channel_end, get_upgradable_context, PROOF_HEIGHT,
+    };
+    use crate::core::ics04_channel::upgrade::Upgrade;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::mock::context::MockContext;
+    use crate::Height;
+
+    fn get_dummy_msg() -> MsgChannelUpgradeAck {
+        let mut msg =
+            MsgChannelUpgradeAck::try_from(get_dummy_raw_msg_chan_upgrade_ack(PROOF_HEIGHT))
+                .unwrap();
+        // The counterparty may complete the upgrade until long after the host height.
+        msg.counterparty_upgrade.timeout.height =
+            TimeoutHeight::At(Height::new(0, 1_000_000).unwrap());
+        msg
+    }
+
+    /// A context in which the channel end proposed the upgrade of `get_dummy_msg`.
+    fn get_context() -> MockContext {
+        let mut ctx = get_upgradable_context().with_channel(
+            PortId::default(),
+            ChannelId::default(),
+            get_dummy_channel_end(State::Open, 1),
+        );
+        ctx.store_upgrade(
+            PortId::default(),
+            ChannelId::default(),
+            Upgrade::proposed(get_dummy_msg().counterparty_upgrade.fields),
+        )
+        .unwrap();
+        ctx
+    }
+
+    #[test]
+    fn chan_upgrade_ack_completes_flush() {
+        let (_, result) = channel_upgrade_dispatch(
+            &get_context(),
+            &ChannelUpgradeMsg::ChannelUpgradeAck(get_dummy_msg()),
+        )
+        .unwrap();
+
+        assert_eq!(result.step, UpgradeStep::Ack);
+        assert_eq!(result.channel_end.state, State::FlushComplete);
+        assert!(matches!(result.upgrade, UpgradeChange::Store(_)));
+        assert_eq!(
+            result.counterparty_upgrade,
+            UpgradeChange::Store(get_dummy_msg().counterparty_upgrade)
+        );
+    }
+
+    #[test]
+    fn chan_upgrade_ack_flushes_packets_in_flight() {
+        let mut ctx = get_context()
+            .with_send_sequence(PortId::default(), ChannelId::default(), 2.into())
+            .with_packet_commitment(
+                PortId::default(),
+                ChannelId::default(),
+                1.into(),
+                PacketCommitment::from(vec![1]),
+            );
+
+        let (_, result) =
+            channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeAck(get_dummy_msg()))
+                .unwrap();
+        assert_eq!(result.channel_end.state, State::Flushing);
+        ctx.store_upgrade_result(result).unwrap();
+
+        // Nothing changes while the packet is in flight.
+        let flushed = flush_packets(&ctx, &PortId::default(), &ChannelId::default()).unwrap();
+        assert!(flushed.is_none());
+
+        ctx.delete_packet_commitment(&PortId::default(), &ChannelId::default(), 1.into())
+            .unwrap();
+        let flushed = flush_packets(&ctx, &PortId::default(), &ChannelId::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(flushed.step, UpgradeStep::FlushComplete);
+        assert_eq!(flushed.channel_end.state, State::FlushComplete);
+        assert!(ctx
+            .counterparty_upgrade(&PortId::default(), &ChannelId::default())
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn chan_upgrade_ack_aborts_timed_out_upgrade() {
+        let mut msg = get_dummy_msg();
+        msg.counterparty_upgrade.timeout.height = TimeoutHeight::At(Height::new(0, 1).unwrap());
+
+        let (_, result) =
+            channel_upgrade_dispatch(&get_context(), &ChannelUpgradeMsg::ChannelUpgradeAck(msg))
+                .unwrap();
+
+        assert_eq!(result.step, UpgradeStep::Error);
+        assert_eq!(result.channel_end.state, State::Open);
+        assert_eq!(result.upgrade, UpgradeChange::Delete);
+        assert_eq!(result.error_receipt.unwrap().sequence, 1);
+    }
+
+    #[test]
+    fn chan_upgrade_ack_aborts_upgrade_of_closed_channel() {
+        let mut ctx = get_context();
+        let (_, result) =
+            channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeAck(get_dummy_msg()))
+                .unwrap();
+        let mut channel_end = result.channel_end.clone();
+        ctx.store_upgrade_result(result).unwrap();
+
+        // A packet timed out on the ordered channel end, which closed it.
+        channel_end.state = State::Closed;
+        ctx.store_channel(PortId::default(), ChannelId::default(), channel_end)
+            .unwrap();
+
+        let aborted = flush_packets(&ctx, &PortId::default(), &ChannelId::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(aborted.step, UpgradeStep::Error);
+        assert_eq!(aborted.channel_end.state, State::Closed);
+        assert_eq!(aborted.counterparty_upgrade, UpgradeChange::Delete);
+        ctx.store_upgrade_result(aborted).unwrap();
+
+        assert!(ctx
+            .channel_upgrade(&PortId::default(), &ChannelId::default())
+            .unwrap()
+            .is_none());
+        assert!(ctx
+            .upgrade_error_receipt(&PortId::default(), &ChannelId::default())
+            .unwrap()
+            .is_some());
+    }
+}
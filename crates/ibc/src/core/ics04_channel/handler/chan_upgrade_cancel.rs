@@ -0,0 +1,160 @@
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeCancel`.
+use crate::core::ics04_channel::channel::State;
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::UpgradeStep;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
+use crate::core::ics04_channel::handler::{check_upgrades_enabled, restore_channel, UpgradeResult};
+use crate::core::ics04_channel::msgs::chan_upgrade_cancel::MsgChannelUpgradeCancel;
+use crate::core::ics04_channel::upgrade::ErrorReceipt;
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+pub(crate) fn process<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    msg: &MsgChannelUpgradeCancel,
+) -> HandlerResult<UpgradeResult, Error> {
+    let mut output = HandlerOutput::builder();
+
+    check_upgrades_enabled(ctx)?;
+
+    let mut channel_end = ctx.channel_end(&msg.port_id, &msg.channel_id)?;
+
+    ctx.channel_upgrade(&msg.port_id, &msg.channel_id)?
+        .ok_or_else(|| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id.clone()))?;
+
+    // Once both ends flushed their packets, the upgrade can only complete.
+    if channel_end.state_matches(&State::FlushComplete) {
+        return Err(Error::invalid_channel_state(
+            msg.channel_id.clone(),
+            channel_end.state,
+        ));
+    }
+
+    // The receipt must be for the upgrade in progress, or a later one.
+    if msg.error_receipt.sequence < channel_end.upgrade_sequence {
+        return Err(Error::invalid_upgrade_sequence(
+            channel_end.upgrade_sequence,
+            msg.error_receipt.sequence,
+        ));
+    }
+
+    let hops = ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?;
+
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?
+        .verify_upgrade_error_receipt_proof(&channel_end, &msg.error_receipt)?;
+
+    output.log("success: channel upgrade cancel");
+
+    // The channel end catches up with the upgrades that the counterparty attempted.
+    channel_end.upgrade_sequence = msg.error_receipt.sequence;
+    let error_receipt =
+        ErrorReceipt::new(channel_end.upgrade_sequence, &Error::upgrade_cancelled());
+    let result = restore_channel(
+        UpgradeStep::Cancel,
+        msg.port_id.clone(),
+        msg.channel_id.clone(),
+        channel_end,
+        error_receipt,
+    );
+
+    Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use test_log::test;
+
+    use crate::core::ics04_channel::channel::State;
+    use crate::core::ics04_channel::context::ChannelKeeper;
+    use crate::core::ics04_channel::events::UpgradeStep;
+    use crate::core::ics04_channel::handler::{channel_upgrade_dispatch, UpgradeChange};
+    use crate::core::ics04_channel::msgs::chan_upgrade_ack::test_util::get_dummy_raw_upgrade;
+    use crate::core::ics04_channel::msgs::chan_upgrade_cancel::test_util::get_dummy_raw_msg_chan_upgrade_cancel;
+    use crate::core::ics04_channel::msgs::chan_upgrade_cancel::MsgChannelUpgradeCancel;
+    use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+    use crate::core::ics04_channel::upgrade::test_util::{
+        get_dummy_channel_end, get_upgradable_context, PROOF_HEIGHT,
+    };
+    use crate::core::ics04_channel::upgrade::Upgrade;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::mock::context::MockContext;
+
+    /// A context in which the channel end is upgrading with the given state and sequence.
+    fn get_context(state: State, upgrade_sequence: u64) -> MockContext {
+        let mut ctx = get_upgradable_context().with_channel(
+            PortId::default(),
+            ChannelId::default(),
+            get_dummy_channel_end(state, upgrade_sequence),
+        );
+        ctx.store_upgrade(
+            PortId::default(),
+            ChannelId::default(),
+            Upgrade::try_from(get_dummy_raw_upgrade()).unwrap(),
+        )
+        .unwrap();
+        ctx
+    }
+
+    #[test]
+    fn chan_upgrade_cancel_msg_processing() {
+        let msg =
+            MsgChannelUpgradeCancel::try_from(get_dummy_raw_msg_chan_upgrade_cancel(PROOF_HEIGHT))
+                .unwrap();
+        let later_receipt = {
+            let mut msg = msg.clone();
+            msg.error_receipt.sequence = 4;
+            msg
+        };
+
+        let tests: Vec<(&str, MockContext, MsgChannelUpgradeCancel, bool)> = vec![
+            (
+                "no upgrade is in progress",
+                get_upgradable_context(),
+                msg.clone(),
+                false,
+            ),
+            (
+                "the channel end completed flushing",
+                get_context(State::FlushComplete, 1),
+                msg.clone(),
+                false,
+            ),
+            (
+                "the receipt is for a previous upgrade",
+                get_context(State::Flushing, 2),
+                msg.clone(),
+                false,
+            ),
+            (
+                "good parameters",
+                get_context(State::Flushing, 1),
+                msg,
+                true,
+            ),
+            (
+                "the receipt is for a later upgrade",
+                get_context(State::Open, 1),
+                later_receipt,
+                true,
+            ),
+        ];
+
+        for (name, ctx, msg, want_pass) in tests {
+            let receipt_sequence = msg.error_receipt.sequence;
+            let res = channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeCancel(msg));
+            assert_eq!(res.is_ok(), want_pass, "{}: {:?}", name, res.err());
+
+            if let Ok((_, result)) = res {
+                assert_eq!(result.step, UpgradeStep::Cancel);
+                assert_eq!(result.channel_end.state, State::Open);
+                assert_eq!(result.channel_end.upgrade_sequence, receipt_sequence);
+                assert_eq!(result.upgrade, UpgradeChange::Delete);
+                assert_eq!(result.error_receipt.unwrap().sequence, receipt_sequence);
+            }
+        }
+    }
+}
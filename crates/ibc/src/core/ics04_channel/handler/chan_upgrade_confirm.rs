@@ -0,0 +1,230 @@
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeConfirm`.
+use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, State};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::UpgradeStep;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
+use crate::core::ics04_channel::handler::{
+    abort_upgrade, check_upgrades_enabled, open_upgraded_channel, UpgradeChange, UpgradeResult,
+};
+use crate::core::ics04_channel::msgs::chan_upgrade_confirm::MsgChannelUpgradeConfirm;
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+pub(crate) fn process<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    msg: &MsgChannelUpgradeConfirm,
+) -> HandlerResult<UpgradeResult, Error> {
+    let mut output = HandlerOutput::builder();
+
+    check_upgrades_enabled(ctx)?;
+
+    let mut channel_end = ctx.channel_end(&msg.port_id, &msg.channel_id)?;
+
+    if !channel_end.state_matches(&State::Flushing) {
+        return Err(Error::invalid_channel_state(
+            msg.channel_id.clone(),
+            channel_end.state,
+        ));
+    }
+
+    // The counterparty acknowledged the upgrade, and may have flushed its packets already.
+    if !msg.counterparty_channel_state.is_flushing() {
+        return Err(Error::invalid_channel_state(
+            channel_end
+                .counterparty()
+                .channel_id()
+                .cloned()
+                .ok_or_else(Error::invalid_counterparty_channel_id)?,
+            msg.counterparty_channel_state,
+        ));
+    }
+
+    let hops = ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?
+        .with_remote_hops(msg.proofs.object_proof())?;
+
+    let upgrade = ctx
+        .channel_upgrade(&msg.port_id, &msg.channel_id)?
+        .ok_or_else(|| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id.clone()))?;
+
+    let expected_counterparty =
+        Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone()));
+    let expected_channel_end = ChannelEnd::new(
+        msg.counterparty_channel_state,
+        *channel_end.ordering(),
+        expected_counterparty,
+        hops.counterparty_hops()?,
+        channel_end.version().clone(),
+    )
+    .with_upgrade_sequence(channel_end.upgrade_sequence);
+
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?.verify_channel_upgrade_proofs(
+        &channel_end,
+        &expected_channel_end,
+        &msg.counterparty_upgrade,
+    )?;
+
+    if msg
+        .counterparty_upgrade
+        .timeout
+        .has_elapsed(ctx.host_height(), ctx.host_timestamp())
+    {
+        let result = abort_upgrade(
+            msg.port_id.clone(),
+            msg.channel_id.clone(),
+            channel_end,
+            &Error::upgrade_timed_out(),
+        );
+
+        output.log("failure: channel upgrade confirm: the upgrade timed out");
+        return Ok(output.with_result(result));
+    }
+
+    if !ctx.has_inflight_packets(&msg.port_id, &msg.channel_id)? {
+        channel_end.set_state(State::FlushComplete);
+    }
+    let flush_complete = channel_end.state_matches(&State::FlushComplete)
+        && msg.counterparty_channel_state == State::FlushComplete;
+
+    let mut result = UpgradeResult::new(
+        UpgradeStep::Confirm,
+        msg.port_id.clone(),
+        msg.channel_id.clone(),
+        channel_end,
+    );
+    result.counterparty_upgrade = UpgradeChange::Store(msg.counterparty_upgrade.clone());
+
+    // Both ends flushed their packets: the upgrade completes right away.
+    if flush_complete {
+        open_upgraded_channel(&mut result, upgrade, &msg.counterparty_upgrade);
+    }
+
+    output.log("success: channel upgrade confirm");
+
+    Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use test_log::test;
+
+    use crate::core::ics04_channel::channel::{Order, State};
+    use crate::core::ics04_channel::context::ChannelKeeper;
+    use crate::core::ics04_channel::events::UpgradeStep;
+    use crate::core::ics04_channel::handler::{channel_upgrade_dispatch, UpgradeChange};
+    use crate::core::ics04_channel::msgs::chan_upgrade_confirm::test_util::get_dummy_raw_msg_chan_upgrade_confirm;
+    use crate::core::ics04_channel::msgs::chan_upgrade_confirm::MsgChannelUpgradeConfirm;
+    use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+    use crate::core::ics04_channel::timeout::TimeoutHeight;
+    use crate::core::ics04_channel::upgrade::test_util::{
+        get_dummy_channel_end, get_upgradable_context, PROOF_HEIGHT,
+    };
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::mock::context::MockContext;
+    use crate::Height;
+
+    fn get_dummy_msg() -> MsgChannelUpgradeConfirm {
+        let mut msg = MsgChannelUpgradeConfirm::try_from(get_dummy_raw_msg_chan_upgrade_confirm(
+            PROOF_HEIGHT,
+        ))
+        .unwrap();
+        msg.counterparty_upgrade.timeout.height =
+            TimeoutHeight::At(Height::new(0, 1_000_000).unwrap());
+        msg
+    }
+
+    /// A context in which the channel end is flushing for the upgrade of `get_dummy_msg`.
+    fn get_context(state: State) -> MockContext {
+        let mut ctx = get_upgradable_context().with_channel(
+            PortId::default(),
+            ChannelId::default(),
+            get_dummy_channel_end(state, 1),
+        );
+        ctx.store_upgrade(
+            PortId::default(),
+            ChannelId::default(),
+            get_dummy_msg().counterparty_upgrade,
+        )
+        .unwrap();
+        ctx
+    }
+
+    #[test]
+    fn chan_upgrade_confirm_msg_processing() {
+        let flush_complete = MsgChannelUpgradeConfirm {
+            counterparty_channel_state: State::FlushComplete,
+            ..get_dummy_msg()
+        };
+        let counterparty_open = MsgChannelUpgradeConfirm {
+            counterparty_channel_state: State::Open,
+            ..get_dummy_msg()
+        };
+
+        let tests: Vec<(&str, MockContext, MsgChannelUpgradeConfirm, bool)> = vec![
+            (
+                "the channel end is not flushing",
+                get_context(State::FlushComplete),
+                get_dummy_msg(),
+                false,
+            ),
+            (
+                "the counterparty is not flushing",
+                get_context(State::Flushing),
+                counterparty_open,
+                false,
+            ),
+            (
+                "the counterparty is flushing",
+                get_context(State::Flushing),
+                get_dummy_msg(),
+                true,
+            ),
+            (
+                "the counterparty completed flushing",
+                get_context(State::Flushing),
+                flush_complete,
+                true,
+            ),
+        ];
+
+        for (name, ctx, msg, want_pass) in tests {
+            let counterparty_state = msg.counterparty_channel_state;
+            let res =
+                channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeConfirm(msg));
+            assert_eq!(res.is_ok(), want_pass, "{}: {:?}", name, res.err());
+
+            if let Ok((_, result)) = res {
+                assert_eq!(result.step, UpgradeStep::Confirm);
+                if counterparty_state == State::FlushComplete {
+                    // Both ends flushed their packets: the channel end is upgraded.
+                    assert!(result.is_upgraded());
+                    assert_eq!(result.channel_end.ordering, Order::Ordered);
+                    assert_eq!(result.upgrade, UpgradeChange::Delete);
+                    assert_eq!(result.next_sequence_recv, Some(1.into()));
+                } else {
+                    assert!(!result.is_upgraded());
+                    assert_eq!(result.channel_end.state, State::FlushComplete);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn chan_upgrade_confirm_aborts_timed_out_upgrade() {
+        let mut msg = get_dummy_msg();
+        msg.counterparty_upgrade.timeout.height = TimeoutHeight::At(Height::new(0, 1).unwrap());
+
+        let (_, result) = channel_upgrade_dispatch(
+            &get_context(State::Flushing),
+            &ChannelUpgradeMsg::ChannelUpgradeConfirm(msg),
+        )
+        .unwrap();
+
+        assert_eq!(result.step, UpgradeStep::Error);
+        assert_eq!(result.channel_end.state, State::Open);
+        assert_eq!(result.channel_end.ordering, Order::Unordered);
+    }
+}
@@ -0,0 +1,165 @@
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeInit`.
+use crate::core::ics04_channel::channel::State;
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::UpgradeStep;
+use crate::core::ics04_channel::handler::{check_upgrades_enabled, UpgradeChange, UpgradeResult};
+use crate::core::ics04_channel::msgs::chan_upgrade_init::MsgChannelUpgradeInit;
+use crate::core::ics04_channel::upgrade::{ErrorReceipt, Upgrade};
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+pub(crate) fn process<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    msg: &MsgChannelUpgradeInit,
+) -> HandlerResult<UpgradeResult, Error> {
+    let mut output = HandlerOutput::builder();
+
+    check_upgrades_enabled(ctx)?;
+
+    let mut channel_end = ctx.channel_end(&msg.port_id, &msg.channel_id)?;
+
+    // Only open channel ends may be upgraded.
+    if !channel_end.state_matches(&State::Open) {
+        return Err(Error::invalid_channel_state(
+            msg.channel_id.clone(),
+            channel_end.state,
+        ));
+    }
+
+    msg.fields.validate_basic()?;
+    if msg.fields.ordering == channel_end.ordering
+        && msg.fields.connection_hops == channel_end.connection_hops
+        && msg.fields.version == channel_end.version
+    {
+        return Err(Error::upgrade_fields_unchanged());
+    }
+
+    // The channel end must be upgradable to an OPEN connection of the host.
+    ConnectionHops::resolve_open(ctx, &msg.fields.connection_hops)?;
+
+    let mut result = UpgradeResult::new(
+        UpgradeStep::Init,
+        msg.port_id.clone(),
+        msg.channel_id.clone(),
+        channel_end.clone(),
+    );
+
+    // A proposal that the counterparty did not take up yet is replaced, and its failure recorded
+    // for the counterparty, which may have taken it up meanwhile.
+    if ctx
+        .channel_upgrade(&msg.port_id, &msg.channel_id)?
+        .is_some()
+    {
+        result.error_receipt = Some(ErrorReceipt::new(
+            channel_end.upgrade_sequence,
+            &Error::incompatible_upgrade("superseded by a new proposal".to_string()),
+        ));
+    }
+
+    channel_end.upgrade_sequence += 1;
+    result.channel_end = channel_end;
+    result.upgrade = UpgradeChange::Store(Upgrade::proposed(msg.fields.clone()));
+
+    output.log("success: channel upgrade init");
+
+    Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use test_log::test;
+
+    use crate::core::ics04_channel::channel::{Order, State};
+    use crate::core::ics04_channel::context::ChannelKeeper;
+    use crate::core::ics04_channel::events::UpgradeStep;
+    use crate::core::ics04_channel::handler::{channel_upgrade_dispatch, UpgradeChange};
+    use crate::core::ics04_channel::msgs::chan_upgrade_init::test_util::get_dummy_raw_msg_chan_upgrade_init;
+    use crate::core::ics04_channel::msgs::chan_upgrade_init::MsgChannelUpgradeInit;
+    use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+    use crate::core::ics04_channel::upgrade::test_util::{
+        get_dummy_channel_end, get_upgradable_context,
+    };
+    use crate::core::ics04_channel::upgrade::Upgrade;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::mock::context::MockContext;
+
+    #[test]
+    fn chan_upgrade_init_msg_processing() {
+        let msg = MsgChannelUpgradeInit::try_from(get_dummy_raw_msg_chan_upgrade_init()).unwrap();
+        let unchanged = MsgChannelUpgradeInit {
+            fields: {
+                let mut fields = msg.fields.clone();
+                fields.ordering = Order::Unordered;
+                fields.version = Version::new("ics20-1".to_string());
+                fields
+            },
+            ..msg.clone()
+        };
+
+        let tests: Vec<(&str, MockContext, MsgChannelUpgradeInit, bool)> = vec![
+            (
+                "channel upgrades are disabled",
+                MockContext::default(),
+                msg.clone(),
+                false,
+            ),
+            (
+                "the channel end is not open",
+                get_upgradable_context().with_channel(
+                    PortId::default(),
+                    ChannelId::default(),
+                    get_dummy_channel_end(State::Flushing, 1),
+                ),
+                msg.clone(),
+                false,
+            ),
+            (
+                "the proposed fields are the ones of the channel end",
+                get_upgradable_context(),
+                unchanged,
+                false,
+            ),
+            ("good parameters", get_upgradable_context(), msg, true),
+        ];
+
+        for (name, ctx, msg, want_pass) in tests {
+            let res = channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeInit(msg));
+            assert_eq!(res.is_ok(), want_pass, "{}: {:?}", name, res.err());
+
+            if let Ok((_, result)) = res {
+                assert_eq!(result.step, UpgradeStep::Init);
+                assert_eq!(result.channel_end.state, State::Open);
+                assert_eq!(result.channel_end.upgrade_sequence, 1);
+                assert!(matches!(result.upgrade, UpgradeChange::Store(_)));
+                assert!(result.error_receipt.is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn chan_upgrade_init_supersedes_proposal() {
+        let msg = MsgChannelUpgradeInit::try_from(get_dummy_raw_msg_chan_upgrade_init()).unwrap();
+        let mut ctx = get_upgradable_context().with_channel(
+            PortId::default(),
+            ChannelId::default(),
+            get_dummy_channel_end(State::Open, 1),
+        );
+        ctx.store_upgrade(
+            PortId::default(),
+            ChannelId::default(),
+            Upgrade::proposed(msg.fields.clone()),
+        )
+        .unwrap();
+
+        let (_, result) =
+            channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeInit(msg)).unwrap();
+
+        assert_eq!(result.channel_end.upgrade_sequence, 2);
+        assert_eq!(result.error_receipt.unwrap().sequence, 1);
+    }
+}
@@ -0,0 +1,207 @@
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeOpen`.
+use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, State};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::UpgradeStep;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
+use crate::core::ics04_channel::handler::{
+    check_upgrades_enabled, open_upgraded_channel, UpgradeResult,
+};
+use crate::core::ics04_channel::msgs::chan_upgrade_open::MsgChannelUpgradeOpen;
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+pub(crate) fn process<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    msg: &MsgChannelUpgradeOpen,
+) -> HandlerResult<UpgradeResult, Error> {
+    let mut output = HandlerOutput::builder();
+
+    check_upgrades_enabled(ctx)?;
+
+    let channel_end = ctx.channel_end(&msg.port_id, &msg.channel_id)?;
+
+    if !channel_end.state_matches(&State::FlushComplete) {
+        return Err(Error::invalid_channel_state(
+            msg.channel_id.clone(),
+            channel_end.state,
+        ));
+    }
+
+    let hops = ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?
+        .with_remote_hops(msg.proofs.object_proof())?;
+
+    let upgrade = ctx
+        .channel_upgrade(&msg.port_id, &msg.channel_id)?
+        .ok_or_else(|| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id.clone()))?;
+    let counterparty_upgrade = ctx
+        .counterparty_upgrade(&msg.port_id, &msg.channel_id)?
+        .ok_or_else(|| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id.clone()))?;
+
+    // The counterparty either completed flushing as well, or already reopened its channel end
+    // with the upgraded fields.
+    let expected_counterparty =
+        Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone()));
+    let expected_channel_end = match msg.counterparty_channel_state {
+        State::FlushComplete => ChannelEnd::new(
+            State::FlushComplete,
+            *channel_end.ordering(),
+            expected_counterparty,
+            hops.counterparty_hops()?,
+            channel_end.version().clone(),
+        )
+        .with_upgrade_sequence(channel_end.upgrade_sequence),
+        State::Open => {
+            if msg.counterparty_upgrade_sequence < channel_end.upgrade_sequence {
+                return Err(Error::invalid_upgrade_sequence(
+                    channel_end.upgrade_sequence,
+                    msg.counterparty_upgrade_sequence,
+                ));
+            }
+            let upgrade_hops = ConnectionHops::resolve_open(ctx, &upgrade.fields.connection_hops)?;
+
+            ChannelEnd::new(
+                State::Open,
+                upgrade.fields.ordering,
+                expected_counterparty,
+                upgrade_hops.counterparty_hops()?,
+                upgrade.fields.version.clone(),
+            )
+            .with_upgrade_sequence(msg.counterparty_upgrade_sequence)
+        }
+        state => {
+            return Err(Error::invalid_channel_state(
+                channel_end
+                    .counterparty()
+                    .channel_id()
+                    .cloned()
+                    .ok_or_else(Error::invalid_counterparty_channel_id)?,
+                state,
+            ))
+        }
+    };
+
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?
+        .verify_channel_proofs(&channel_end, &expected_channel_end)?;
+
+    output.log("success: channel upgrade open");
+
+    let mut result = UpgradeResult::new(
+        UpgradeStep::Open,
+        msg.port_id.clone(),
+        msg.channel_id.clone(),
+        channel_end,
+    );
+    open_upgraded_channel(&mut result, upgrade, &counterparty_upgrade);
+
+    Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use test_log::test;
+
+    use crate::core::ics04_channel::channel::{Order, State};
+    use crate::core::ics04_channel::context::ChannelKeeper;
+    use crate::core::ics04_channel::events::UpgradeStep;
+    use crate::core::ics04_channel::handler::{channel_upgrade_dispatch, UpgradeChange};
+    use crate::core::ics04_channel::msgs::chan_upgrade_ack::test_util::get_dummy_raw_upgrade;
+    use crate::core::ics04_channel::msgs::chan_upgrade_open::test_util::get_dummy_raw_msg_chan_upgrade_open;
+    use crate::core::ics04_channel::msgs::chan_upgrade_open::MsgChannelUpgradeOpen;
+    use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+    use crate::core::ics04_channel::upgrade::test_util::{
+        get_dummy_channel_end, get_upgradable_context, PROOF_HEIGHT,
+    };
+    use crate::core::ics04_channel::upgrade::Upgrade;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::mock::context::MockContext;
+
+    /// A context in which both ends of the channel are flushing for the same upgrade.
+    fn get_context(state: State) -> MockContext {
+        let upgrade = Upgrade::try_from(get_dummy_raw_upgrade()).unwrap();
+        let mut ctx = get_upgradable_context().with_channel(
+            PortId::default(),
+            ChannelId::default(),
+            get_dummy_channel_end(state, 1),
+        );
+        ctx.store_upgrade(PortId::default(), ChannelId::default(), upgrade.clone())
+            .unwrap();
+        ctx.store_counterparty_upgrade(PortId::default(), ChannelId::default(), upgrade)
+            .unwrap();
+        ctx
+    }
+
+    #[test]
+    fn chan_upgrade_open_msg_processing() {
+        let msg =
+            MsgChannelUpgradeOpen::try_from(get_dummy_raw_msg_chan_upgrade_open(PROOF_HEIGHT))
+                .unwrap();
+        let counterparty_open = MsgChannelUpgradeOpen {
+            counterparty_channel_state: State::Open,
+            ..msg.clone()
+        };
+        let counterparty_behind = MsgChannelUpgradeOpen {
+            counterparty_upgrade_sequence: 0,
+            ..counterparty_open.clone()
+        };
+        let counterparty_flushing = MsgChannelUpgradeOpen {
+            counterparty_channel_state: State::Flushing,
+            ..msg.clone()
+        };
+
+        let tests: Vec<(&str, MockContext, MsgChannelUpgradeOpen, bool)> = vec![
+            (
+                "the channel end did not complete flushing",
+                get_context(State::Flushing),
+                msg.clone(),
+                false,
+            ),
+            (
+                "the counterparty did not complete flushing",
+                get_context(State::FlushComplete),
+                counterparty_flushing,
+                false,
+            ),
+            (
+                "the counterparty is behind in its upgrades",
+                get_context(State::FlushComplete),
+                counterparty_behind,
+                false,
+            ),
+            (
+                "the counterparty completed flushing",
+                get_context(State::FlushComplete),
+                msg,
+                true,
+            ),
+            (
+                "the counterparty is upgraded",
+                get_context(State::FlushComplete),
+                counterparty_open,
+                true,
+            ),
+        ];
+
+        for (name, ctx, msg, want_pass) in tests {
+            let res = channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeOpen(msg));
+            assert_eq!(res.is_ok(), want_pass, "{}: {:?}", name, res.err());
+
+            if let Ok((_, result)) = res {
+                assert_eq!(result.step, UpgradeStep::Open);
+                assert!(result.is_upgraded());
+                assert_eq!(result.channel_end.ordering, Order::Ordered);
+                assert_eq!(
+                    result.channel_end.version,
+                    Version::new("ics20-2".to_string())
+                );
+                assert_eq!(result.channel_end.upgrade_sequence, 1);
+                assert_eq!(result.upgrade, UpgradeChange::Delete);
+                assert_eq!(result.counterparty_upgrade, UpgradeChange::Delete);
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeTimeout`.
+use crate::core::ics04_channel::channel::State;
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::UpgradeStep;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
+use crate::core::ics04_channel::handler::{check_upgrades_enabled, restore_channel, UpgradeResult};
+use crate::core::ics04_channel::msgs::chan_upgrade_timeout::MsgChannelUpgradeTimeout;
+use crate::core::ics04_channel::upgrade::ErrorReceipt;
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+pub(crate) fn process<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    msg: &MsgChannelUpgradeTimeout,
+) -> HandlerResult<UpgradeResult, Error> {
+    let mut output = HandlerOutput::builder();
+
+    check_upgrades_enabled(ctx)?;
+
+    let channel_end = ctx.channel_end(&msg.port_id, &msg.channel_id)?;
+
+    // Only upgrades that started flushing have a timeout.
+    if !channel_end.state.is_flushing() {
+        return Err(Error::invalid_channel_state(
+            msg.channel_id.clone(),
+            channel_end.state,
+        ));
+    }
+
+    let upgrade = ctx
+        .channel_upgrade(&msg.port_id, &msg.channel_id)?
+        .ok_or_else(|| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id.clone()))?;
+
+    let hops = ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?;
+    let verifier = ProofVerificationContext::new(ctx, &hops, &msg.proofs)?;
+
+    // The upgrade must have timed out on the counterparty at the proof height.
+    if !upgrade
+        .timeout
+        .has_elapsed(verifier.height(), verifier.consensus_state().timestamp())
+    {
+        return Err(Error::upgrade_timeout_not_reached());
+    }
+
+    // The counterparty must not have completed flushing, nor the upgrade, by then.
+    let counterparty_channel = &msg.counterparty_channel;
+    if !counterparty_channel.state_matches(&State::Open)
+        && !counterparty_channel.state_matches(&State::Flushing)
+    {
+        return Err(Error::invalid_channel_state(
+            channel_end
+                .counterparty()
+                .channel_id()
+                .cloned()
+                .ok_or_else(Error::invalid_counterparty_channel_id)?,
+            counterparty_channel.state,
+        ));
+    }
+    if counterparty_channel.upgrade_sequence < channel_end.upgrade_sequence {
+        return Err(Error::invalid_upgrade_sequence(
+            channel_end.upgrade_sequence,
+            counterparty_channel.upgrade_sequence,
+        ));
+    }
+    if counterparty_channel.state_matches(&State::Open)
+        && counterparty_channel.upgrade_sequence == channel_end.upgrade_sequence
+    {
+        return Err(Error::counterparty_already_upgraded());
+    }
+
+    verifier.verify_channel_proofs(&channel_end, counterparty_channel)?;
+
+    output.log("success: channel upgrade timeout");
+
+    let error_receipt =
+        ErrorReceipt::new(channel_end.upgrade_sequence, &Error::upgrade_timed_out());
+    let result = restore_channel(
+        UpgradeStep::Timeout,
+        msg.port_id.clone(),
+        msg.channel_id.clone(),
+        channel_end,
+        error_receipt,
+    );
+
+    Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use test_log::test;
+
+    use crate::core::ics04_channel::channel::State;
+    use crate::core::ics04_channel::context::ChannelKeeper;
+    use crate::core::ics04_channel::events::UpgradeStep;
+    use crate::core::ics04_channel::handler::{channel_upgrade_dispatch, UpgradeChange};
+    use crate::core::ics04_channel::msgs::chan_upgrade_ack::test_util::get_dummy_raw_upgrade;
+    use crate::core::ics04_channel::msgs::chan_upgrade_timeout::test_util::get_dummy_raw_msg_chan_upgrade_timeout;
+    use crate::core::ics04_channel::msgs::chan_upgrade_timeout::MsgChannelUpgradeTimeout;
+    use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+    use crate::core::ics04_channel::timeout::TimeoutHeight;
+    use crate::core::ics04_channel::upgrade::test_util::{
+        get_dummy_channel_end, get_upgradable_context, PROOF_HEIGHT,
+    };
+    use crate::core::ics04_channel::upgrade::Upgrade;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::mock::context::MockContext;
+    use crate::Height;
+
+    /// A context in which the channel end is flushing for an upgrade that times out at
+    /// `timeout_height` on the counterparty.
+    fn get_context(timeout_height: u64) -> MockContext {
+        let mut upgrade = Upgrade::try_from(get_dummy_raw_upgrade()).unwrap();
+        upgrade.timeout.height = TimeoutHeight::At(Height::new(0, timeout_height).unwrap());
+        let mut ctx = get_upgradable_context().with_channel(
+            PortId::default(),
+            ChannelId::default(),
+            get_dummy_channel_end(State::Flushing, 1),
+        );
+        ctx.store_upgrade(PortId::default(), ChannelId::default(), upgrade)
+            .unwrap();
+        ctx
+    }
+
+    #[test]
+    fn chan_upgrade_timeout_msg_processing() {
+        let msg = MsgChannelUpgradeTimeout::try_from(get_dummy_raw_msg_chan_upgrade_timeout(
+            PROOF_HEIGHT,
+        ))
+        .unwrap();
+        let counterparty_upgraded = MsgChannelUpgradeTimeout {
+            counterparty_channel: msg.counterparty_channel.clone().with_upgrade_sequence(1),
+            ..msg.clone()
+        };
+        let counterparty_flushed = MsgChannelUpgradeTimeout {
+            counterparty_channel: {
+                let mut channel_end = msg.counterparty_channel.clone().with_upgrade_sequence(1);
+                channel_end.set_state(State::FlushComplete);
+                channel_end
+            },
+            ..msg.clone()
+        };
+        let counterparty_flushing = MsgChannelUpgradeTimeout {
+            counterparty_channel: {
+                let mut channel_end = msg.counterparty_channel.clone().with_upgrade_sequence(1);
+                channel_end.set_state(State::Flushing);
+                channel_end
+            },
+            ..msg.clone()
+        };
+
+        let tests: Vec<(&str, MockContext, MsgChannelUpgradeTimeout, bool)> = vec![
+            (
+                "the upgrade did not time out at the proof height",
+                get_context(PROOF_HEIGHT + 1),
+                counterparty_flushing.clone(),
+                false,
+            ),
+            (
+                "the counterparty is behind in its upgrades",
+                get_context(PROOF_HEIGHT),
+                msg,
+                false,
+            ),
+            (
+                "the counterparty completed the upgrade",
+                get_context(PROOF_HEIGHT),
+                counterparty_upgraded,
+                false,
+            ),
+            (
+                "the counterparty completed flushing",
+                get_context(PROOF_HEIGHT),
+                counterparty_flushed,
+                false,
+            ),
+            (
+                "the counterparty is still flushing",
+                get_context(PROOF_HEIGHT),
+                counterparty_flushing,
+                true,
+            ),
+        ];
+
+        for (name, ctx, msg, want_pass) in tests {
+            let res =
+                channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeTimeout(msg));
+            assert_eq!(res.is_ok(), want_pass, "{}: {:?}", name, res.err());
+
+            if let Ok((_, result)) = res {
+                assert_eq!(result.step, UpgradeStep::Timeout);
+                assert_eq!(result.channel_end.state, State::Open);
+                assert_eq!(result.upgrade, UpgradeChange::Delete);
+                assert_eq!(result.error_receipt.unwrap().sequence, 1);
+            }
+        }
+    }
+}
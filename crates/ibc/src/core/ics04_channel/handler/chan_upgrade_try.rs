@@ -0,0 +1,224 @@
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeTry`.
+use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, State};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::UpgradeStep;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
+use crate::core::ics04_channel::handler::{
+    abort_upgrade, check_upgrade_compatibility, check_upgrades_enabled, start_flushing,
+    UpgradeChange, UpgradeResult,
+};
+use crate::core::ics04_channel::msgs::chan_upgrade_try::MsgChannelUpgradeTry;
+use crate::core::ics04_channel::upgrade::{ErrorReceipt, Upgrade, UpgradeFields};
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+pub(crate) fn process<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    msg: &MsgChannelUpgradeTry,
+) -> HandlerResult<UpgradeResult, Error> {
+    let mut output = HandlerOutput::builder();
+
+    check_upgrades_enabled(ctx)?;
+
+    let mut channel_end = ctx.channel_end(&msg.port_id, &msg.channel_id)?;
+
+    if !channel_end.state_matches(&State::Open) {
+        return Err(Error::invalid_channel_state(
+            msg.channel_id.clone(),
+            channel_end.state,
+        ));
+    }
+
+    // An OPEN IBC connection running on the local (host) chain should exist.
+    let hops = ConnectionHops::resolve_open(ctx, channel_end.connection_hops())?
+        .with_remote_hops(msg.proofs.object_proof())?;
+
+    let mut upgrade = match ctx.channel_upgrade(&msg.port_id, &msg.channel_id)? {
+        // Both ends proposed an upgrade: the proposals must then be on the same connection.
+        Some(upgrade) => {
+            if upgrade.fields.connection_hops != msg.proposed_upgrade_connection_hops {
+                return Err(Error::incompatible_upgrade(
+                    "the proposed connection hops differ from the ones of the upgrade in progress"
+                        .to_string(),
+                ));
+            }
+            upgrade
+        }
+        // Otherwise, the channel end proposes the counterparty fields on its own connection, as
+        // if it had been sent a `MsgChannelUpgradeInit`.
+        None => {
+            let fields = UpgradeFields::new(
+                msg.counterparty_upgrade_fields.ordering,
+                msg.proposed_upgrade_connection_hops.clone(),
+                msg.counterparty_upgrade_fields.version.clone(),
+            );
+            fields.validate_basic()?;
+            ConnectionHops::resolve_open(ctx, &fields.connection_hops)?;
+
+            channel_end.upgrade_sequence += 1;
+            Upgrade::proposed(fields)
+        }
+    };
+
+    // Proof verification in two steps:
+    // 1. Setup: build the channel end and the upgrade as we expect to find them on the
+    // counterparty, which only proposed the upgrade.
+    let expected_counterparty =
+        Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone()));
+    let expected_channel_end = ChannelEnd::new(
+        State::Open,
+        *channel_end.ordering(),
+        expected_counterparty,
+        hops.counterparty_hops()?,
+        channel_end.version().clone(),
+    )
+    .with_upgrade_sequence(msg.counterparty_upgrade_sequence);
+    let expected_upgrade = Upgrade::proposed(msg.counterparty_upgrade_fields.clone());
+
+    // 2. Verify proofs
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?.verify_channel_upgrade_proofs(
+        &channel_end,
+        &expected_channel_end,
+        &expected_upgrade,
+    )?;
+
+    // The counterparty must be at least as far as the channel end in its upgrades: it is told the
+    // last sequence the channel end attempted otherwise, so that it can catch up.
+    if msg.counterparty_upgrade_sequence < channel_end.upgrade_sequence {
+        let error = Error::invalid_upgrade_sequence(
+            channel_end.upgrade_sequence,
+            msg.counterparty_upgrade_sequence,
+        );
+        let mut result = abort_upgrade(
+            msg.port_id.clone(),
+            msg.channel_id.clone(),
+            channel_end.clone(),
+            &error,
+        );
+        result.error_receipt = Some(ErrorReceipt::new(channel_end.upgrade_sequence - 1, &error));
+
+        output.log("failure: channel upgrade try: upgrade sequence of the counterparty is behind");
+        return Ok(output.with_result(result));
+    }
+    channel_end.upgrade_sequence = msg.counterparty_upgrade_sequence;
+
+    if let Err(error) =
+        check_upgrade_compatibility(ctx, &upgrade.fields, &msg.counterparty_upgrade_fields)
+    {
+        let result = abort_upgrade(
+            msg.port_id.clone(),
+            msg.channel_id.clone(),
+            channel_end,
+            &error,
+        );
+
+        output.log("failure: channel upgrade try: incompatible upgrades");
+        return Ok(output.with_result(result));
+    }
+
+    start_flushing(
+        ctx,
+        &msg.port_id,
+        &msg.channel_id,
+        &mut channel_end,
+        &mut upgrade,
+    )?;
+
+    output.log("success: channel upgrade try");
+
+    let mut result = UpgradeResult::new(
+        UpgradeStep::Try,
+        msg.port_id.clone(),
+        msg.channel_id.clone(),
+        channel_end,
+    );
+    result.upgrade = UpgradeChange::Store(upgrade);
+
+    Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use test_log::test;
+
+    use crate::core::ics04_channel::channel::{Order, State};
+    use crate::core::ics04_channel::context::ChannelKeeper;
+    use crate::core::ics04_channel::events::UpgradeStep;
+    use crate::core::ics04_channel::handler::{channel_upgrade_dispatch, UpgradeChange};
+    use crate::core::ics04_channel::msgs::chan_upgrade_try::test_util::get_dummy_raw_msg_chan_upgrade_try;
+    use crate::core::ics04_channel::msgs::chan_upgrade_try::MsgChannelUpgradeTry;
+    use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+    use crate::core::ics04_channel::upgrade::test_util::{
+        get_dummy_channel_end, get_upgradable_context, PROOF_HEIGHT,
+    };
+    use crate::core::ics04_channel::upgrade::Upgrade;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+
+    fn get_dummy_msg() -> MsgChannelUpgradeTry {
+        MsgChannelUpgradeTry::try_from(get_dummy_raw_msg_chan_upgrade_try(PROOF_HEIGHT)).unwrap()
+    }
+
+    #[test]
+    fn chan_upgrade_try_starts_flushing() {
+        let ctx = get_upgradable_context();
+
+        let (_, result) =
+            channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeTry(get_dummy_msg()))
+                .unwrap();
+
+        assert_eq!(result.step, UpgradeStep::Try);
+        assert_eq!(result.channel_end.state, State::Flushing);
+        assert_eq!(result.channel_end.upgrade_sequence, 1);
+        match result.upgrade {
+            UpgradeChange::Store(upgrade) => {
+                assert_eq!(upgrade.fields.ordering, Order::Ordered);
+                assert!(upgrade.timeout.is_set());
+                assert_eq!(upgrade.next_sequence_send, 1.into());
+            }
+            change => panic!("unexpected upgrade change {:?}", change),
+        }
+    }
+
+    #[test]
+    fn chan_upgrade_try_aborts() {
+        // The counterparty is behind the channel end in its upgrades.
+        let ctx = get_upgradable_context().with_channel(
+            PortId::default(),
+            ChannelId::default(),
+            get_dummy_channel_end(State::Open, 3),
+        );
+
+        let (_, result) =
+            channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeTry(get_dummy_msg()))
+                .unwrap();
+
+        assert_eq!(result.step, UpgradeStep::Error);
+        assert_eq!(result.channel_end.state, State::Open);
+        assert_eq!(result.channel_end.upgrade_sequence, 4);
+        assert_eq!(result.error_receipt.unwrap().sequence, 3);
+
+        // The channel end proposed another version.
+        let msg = get_dummy_msg();
+        let mut fields = msg.counterparty_upgrade_fields.clone();
+        fields.version = Version::new("ics20-3".to_string());
+        let mut ctx = get_upgradable_context();
+        ctx.store_upgrade(
+            PortId::default(),
+            ChannelId::default(),
+            Upgrade::proposed(fields),
+        )
+        .unwrap();
+
+        let (_, result) =
+            channel_upgrade_dispatch(&ctx, &ChannelUpgradeMsg::ChannelUpgradeTry(msg)).unwrap();
+
+        assert_eq!(result.step, UpgradeStep::Error);
+        assert_eq!(result.upgrade, UpgradeChange::Delete);
+        assert_eq!(result.error_receipt.unwrap().sequence, 1);
+    }
+}
@@ -1,9 +1,11 @@
-use crate::core::ics03_connection::connection::State as ConnectionState;
 use crate::core::ics04_channel::channel::{Counterparty, Order, State};
-use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::{
+    channel_upgrades_enabled, counterparty_upgrade_next_sequence_send, ChannelReader,
+};
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::events::ReceivePacket;
-use crate::core::ics04_channel::handler::verify::verify_packet_recv_proofs;
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
 use crate::core::ics04_channel::msgs::recv_packet::MsgRecvPacket;
 use crate::core::ics04_channel::packet::{PacketResult, Receipt, Sequence};
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
@@ -25,6 +27,15 @@ pub enum RecvPacketResult {
         channel_id: ChannelId,
         next_seq_recv: Sequence,
     },
+    /// A packet that timed out on an `ORDER_ORDERED_ALLOW_TIMEOUT` channel. It is skipped
+    /// without invoking the application, so that the packets that follow it can be received,
+    /// and a timeout receipt is written for the sender to prove its timeout.
+    TimedOut {
+        port_id: PortId,
+        channel_id: ChannelId,
+        sequence: Sequence,
+        next_seq_recv: Sequence,
+    },
 }
 
 pub fn process<Ctx: ChannelReader>(
@@ -38,13 +49,36 @@ pub fn process<Ctx: ChannelReader>(
     let dest_channel_end =
         ctx.channel_end(&packet.destination_port, &packet.destination_channel)?;
 
-    if !dest_channel_end.state_matches(&State::Open) {
+    let receiving_state = dest_channel_end.state_matches(&State::Open)
+        || (dest_channel_end.state.is_flushing() && channel_upgrades_enabled(ctx));
+    if !receiving_state {
         return Err(Error::invalid_channel_state(
             packet.source_channel.clone(),
             dest_channel_end.state,
         ));
     }
 
+    if ctx.is_channel_paused(&packet.destination_port, &packet.destination_channel)? {
+        return Err(Error::channel_paused(
+            packet.destination_port.clone(),
+            packet.destination_channel.clone(),
+        ));
+    }
+
+    // Once the counterparty started flushing, only the packets it sent before may be received.
+    if let Some(counterparty_next_sequence_send) = counterparty_upgrade_next_sequence_send(
+        ctx,
+        &packet.destination_port,
+        &packet.destination_channel,
+    ) {
+        if packet.sequence >= counterparty_next_sequence_send {
+            return Err(Error::packet_sent_after_counterparty_flush(
+                packet.sequence,
+                counterparty_next_sequence_send,
+            ));
+        }
+    }
+
     let counterparty = Counterparty::new(
         packet.source_port.clone(),
         Some(packet.source_channel.clone()),
@@ -57,46 +91,50 @@ pub fn process<Ctx: ChannelReader>(
         ));
     }
 
-    let dest_connection_id = &dest_channel_end.connection_hops()[0];
-    let connection_end = ctx.connection_end(dest_connection_id)?;
-
-    if !connection_end.state_matches(&ConnectionState::Open) {
-        return Err(Error::connection_not_open(
-            dest_channel_end.connection_hops()[0].clone(),
-        ));
-    }
+    let hops = ConnectionHops::resolve_open(ctx, dest_channel_end.connection_hops())?;
+    let dest_connection_id = hops.connection_id();
 
     let latest_height = ChannelReader::host_height(ctx);
-    if packet.timeout_height.has_expired(latest_height) {
-        return Err(Error::low_packet_height(
+    let latest_timestamp = ChannelReader::host_timestamp(ctx);
+    let timeout_err = if packet.timeout_height.has_expired(latest_height) {
+        Some(Error::low_packet_height(
             latest_height,
             packet.timeout_height,
-        ));
-    }
+        ))
+    } else if let Expiry::Expired = latest_timestamp.check_expiry(&packet.timeout_timestamp) {
+        Some(Error::low_packet_timestamp())
+    } else {
+        None
+    };
 
-    let latest_timestamp = ChannelReader::host_timestamp(ctx);
-    if let Expiry::Expired = latest_timestamp.check_expiry(&packet.timeout_timestamp) {
-        return Err(Error::low_packet_timestamp());
-    }
+    // Timed-out packets may only be received (and skipped) on ordered-allow-timeout channels.
+    let timed_out = match timeout_err {
+        Some(e) if !dest_channel_end.order_matches(&Order::OrderedAllowTimeout) => return Err(e),
+        Some(_) => true,
+        None => false,
+    };
 
-    verify_packet_recv_proofs(
-        ctx,
-        msg.proofs.height(),
-        packet,
-        &connection_end,
-        &msg.proofs,
-    )?;
+    ProofVerificationContext::new(ctx, &hops, &msg.proofs)?.verify_packet_recv_proofs(packet)?;
 
-    let result = if dest_channel_end.order_matches(&Order::Ordered) {
+    let receive_packet_event = || {
+        let event = ReceivePacket::new(
+            msg.packet.clone(),
+            dest_channel_end.ordering,
+            dest_connection_id.clone(),
+        );
+        if ctx.packet_events_with_relayer() {
+            IbcEvent::ReceivePacket(event.with_relayer(msg.signer.clone()))
+        } else {
+            IbcEvent::ReceivePacket(event)
+        }
+    };
+
+    let result = if dest_channel_end.ordering().is_ordered() {
         let next_seq_recv =
             ctx.get_next_sequence_recv(&packet.destination_port, &packet.destination_channel)?;
 
         if packet.sequence < next_seq_recv {
-            output.emit(IbcEvent::ReceivePacket(ReceivePacket::new(
-                msg.packet.clone(),
-                dest_channel_end.ordering,
-                dest_connection_id.clone(),
-            )));
+            output.emit(receive_packet_event());
 
             return Ok(output.with_result(PacketResult::Recv(RecvPacketResult::NoOp)));
         } else if packet.sequence != next_seq_recv {
@@ -106,6 +144,19 @@ pub fn process<Ctx: ChannelReader>(
             ));
         }
 
+        if timed_out {
+            output.log("success: timed-out packet skipped");
+
+            return Ok(
+                output.with_result(PacketResult::Recv(RecvPacketResult::TimedOut {
+                    port_id: packet.destination_port.clone(),
+                    channel_id: packet.destination_channel.clone(),
+                    sequence: packet.sequence,
+                    next_seq_recv: next_seq_recv.increment(),
+                })),
+            );
+        }
+
         PacketResult::Recv(RecvPacketResult::Ordered {
             port_id: packet.destination_port.clone(),
             channel_id: packet.destination_channel.clone(),
@@ -120,11 +171,7 @@ pub fn process<Ctx: ChannelReader>(
 
         match packet_rec {
             Ok(_receipt) => {
-                output.emit(IbcEvent::ReceivePacket(ReceivePacket::new(
-                    msg.packet.clone(),
-                    dest_channel_end.ordering,
-                    dest_connection_id.clone(),
-                )));
+                output.emit(receive_packet_event());
 
                 return Ok(output.with_result(PacketResult::Recv(RecvPacketResult::NoOp)));
             }
@@ -143,11 +190,7 @@ pub fn process<Ctx: ChannelReader>(
 
     output.log("success: packet receive");
 
-    output.emit(IbcEvent::ReceivePacket(ReceivePacket::new(
-        msg.packet.clone(),
-        dest_channel_end.ordering,
-        dest_connection_id.clone(),
-    )));
+    output.emit(receive_packet_event());
 
     Ok(output.with_result(result))
 }
@@ -158,14 +201,18 @@ mod tests {
 
     use test_log::test;
 
+    use crate::core::context::ExecutionContext;
     use crate::core::ics03_connection::connection::ConnectionEnd;
     use crate::core::ics03_connection::connection::Counterparty as ConnectionCounterparty;
     use crate::core::ics03_connection::connection::State as ConnectionState;
     use crate::core::ics03_connection::version::get_compatible_versions;
     use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
-    use crate::core::ics04_channel::handler::recv_packet::process;
+    use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+    use crate::core::ics04_channel::error::ErrorDetail;
+    use crate::core::ics04_channel::handler::recv_packet::{process, RecvPacketResult};
     use crate::core::ics04_channel::msgs::recv_packet::test_util::get_dummy_raw_msg_recv_packet;
     use crate::core::ics04_channel::msgs::recv_packet::MsgRecvPacket;
+    use crate::core::ics04_channel::packet::{PacketResult, Receipt};
     use crate::core::ics04_channel::Version;
     use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
     use crate::mock::context::MockContext;
@@ -174,6 +221,7 @@ mod tests {
     use crate::timestamp::Timestamp;
     use crate::timestamp::ZERO_DURATION;
     use crate::{core::ics04_channel::packet::Packet, events::IbcEvent};
+    use tendermint::abci::Event as AbciEvent;
 
     #[test]
     fn recv_packet_processing() {
@@ -238,6 +286,31 @@ mod tests {
                 msg: msg.clone(),
                 want_pass: false,
             },
+            Test {
+                name: "Processing fails because the channel is paused".to_string(),
+                ctx: {
+                    let mut ctx = context
+                        .clone()
+                        .with_client(&ClientId::default(), client_height)
+                        .with_connection(ConnectionId::default(), connection_end.clone())
+                        .with_channel(
+                            packet.destination_port.clone(),
+                            packet.destination_channel.clone(),
+                            dest_channel_end.clone(),
+                        )
+                        .with_height(host_height)
+                        .with_recv_sequence(
+                            packet.destination_port.clone(),
+                            packet.destination_channel.clone(),
+                            packet.sequence,
+                        );
+                    ctx.pause_channel(&packet.destination_port, &packet.destination_channel)
+                        .unwrap();
+                    ctx
+                },
+                msg: msg.clone(),
+                want_pass: false,
+            },
             Test {
                 name: "Good parameters".to_string(),
                 ctx: context
@@ -311,4 +384,236 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn recv_packet_relayer_attribution() {
+        let context = MockContext::default();
+        let host_height = context.query_latest_height().increment();
+        let client_height = host_height.increment();
+
+        let msg = MsgRecvPacket::try_from(get_dummy_raw_msg_recv_packet(
+            client_height.revision_height(),
+        ))
+        .unwrap();
+        let packet = msg.packet.clone();
+
+        let dest_channel_end = ChannelEnd::new(
+            State::Open,
+            Order::default(),
+            Counterparty::new(packet.source_port.clone(), Some(packet.source_channel)),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        let context = context
+            .with_client(&ClientId::default(), client_height)
+            .with_connection(ConnectionId::default(), connection_end)
+            .with_channel(
+                packet.destination_port.clone(),
+                packet.destination_channel.clone(),
+                dest_channel_end,
+            )
+            .with_height(host_height);
+
+        let relayer_attribute = |event: IbcEvent| {
+            AbciEvent::try_from(event)
+                .unwrap()
+                .attributes
+                .into_iter()
+                .find(|tag| tag.key.as_ref() == "packet_relayer")
+                .map(|tag| tag.value.to_string())
+        };
+
+        // Off by default
+        let output = process(&context, &msg).unwrap();
+        match output.events.into_iter().next() {
+            Some(IbcEvent::ReceivePacket(e)) => {
+                assert_eq!(e.relayer(), None);
+                assert_eq!(relayer_attribute(IbcEvent::ReceivePacket(e)), None);
+            }
+            e => panic!("unexpected event: {:?}", e),
+        }
+
+        let context = context.with_packet_events_relayer();
+        let output = process(&context, &msg).unwrap();
+        match output.events.into_iter().next() {
+            Some(IbcEvent::ReceivePacket(e)) => {
+                assert_eq!(e.relayer(), Some(&msg.signer));
+                assert_eq!(
+                    relayer_attribute(IbcEvent::ReceivePacket(e)),
+                    Some(msg.signer.to_string())
+                );
+            }
+            e => panic!("unexpected event: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn recv_packet_ordered_allow_timeout() {
+        let context = MockContext::default();
+        let host_height = context.query_latest_height().increment();
+        let client_height = host_height.increment();
+
+        let msg = MsgRecvPacket::try_from(get_dummy_raw_msg_recv_packet(
+            client_height.revision_height(),
+        ))
+        .unwrap();
+
+        // The packet has timed out on the receiving chain.
+        let mut msg_timed_out = msg.clone();
+        msg_timed_out.packet.timeout_timestamp = Timestamp::from_nanoseconds(1).unwrap();
+        let packet = msg_timed_out.packet.clone();
+
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        let channel_end = |ordering| {
+            ChannelEnd::new(
+                State::Open,
+                ordering,
+                Counterparty::new(
+                    packet.source_port.clone(),
+                    Some(packet.source_channel.clone()),
+                ),
+                vec![ConnectionId::default()],
+                Version::ics20(),
+            )
+        };
+        let context_with = |ordering| {
+            context
+                .clone()
+                .with_client(&ClientId::default(), client_height)
+                .with_connection(ConnectionId::default(), connection_end.clone())
+                .with_channel(
+                    packet.destination_port.clone(),
+                    packet.destination_channel.clone(),
+                    channel_end(ordering),
+                )
+                .with_recv_sequence(
+                    packet.destination_port.clone(),
+                    packet.destination_channel.clone(),
+                    packet.sequence,
+                )
+                .with_height(host_height)
+        };
+
+        // Ordered channels refuse timed-out packets.
+        assert!(process(&context_with(Order::Ordered), &msg_timed_out).is_err());
+
+        // Ordered-allow-timeout channels skip them, without emitting a receive event.
+        let mut context = context_with(Order::OrderedAllowTimeout);
+        let output = process(&context, &msg_timed_out).unwrap();
+        assert!(output.events.is_empty());
+        match &output.result {
+            PacketResult::Recv(RecvPacketResult::TimedOut { next_seq_recv, .. }) => {
+                assert_eq!(*next_seq_recv, packet.sequence.increment())
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+
+        // A timeout receipt is written, which proves the timeout to the sender.
+        context.store_packet_result(output.result).unwrap();
+        let receipt = context.get_packet_receipt(
+            &packet.destination_port,
+            &packet.destination_channel,
+            packet.sequence,
+        );
+        assert_eq!(receipt.unwrap(), Receipt::Timeout);
+
+        // Packets that have not timed out are received as on ordered channels.
+        let output = process(&context_with(Order::OrderedAllowTimeout), &msg).unwrap();
+        assert!(matches!(
+            output.result,
+            PacketResult::Recv(RecvPacketResult::Ordered { .. })
+        ));
+    }
+
+    #[test]
+    fn recv_packet_on_flushing_channel() {
+        let context = MockContext::default();
+        let host_height = context.query_latest_height().increment();
+        let client_height = host_height.increment();
+
+        let msg = MsgRecvPacket::try_from(get_dummy_raw_msg_recv_packet(
+            client_height.revision_height(),
+        ))
+        .unwrap();
+        let packet = msg.packet.clone();
+
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        let channel_end = ChannelEnd::new(
+            State::Flushing,
+            Order::Unordered,
+            Counterparty::new(
+                packet.source_port.clone(),
+                Some(packet.source_channel.clone()),
+            ),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        let context = context
+            .with_client(&ClientId::default(), client_height)
+            .with_connection(ConnectionId::default(), connection_end)
+            .with_channel(
+                packet.destination_port.clone(),
+                packet.destination_channel.clone(),
+                channel_end,
+            )
+            .with_height(host_height);
+
+        // Hosts without channel upgrades keep refusing packets on channels that are not open.
+        assert!(process(&context, &msg).is_err());
+
+        let context = context.with_channel_upgrades();
+        process(&context, &msg).unwrap();
+
+        // Packets sent after the counterparty started flushing are refused.
+        let flushing_context = context
+            .clone()
+            .with_counterparty_upgrade_next_sequence_send(
+                packet.destination_port.clone(),
+                packet.destination_channel.clone(),
+                packet.sequence,
+            );
+        let err = process(&flushing_context, &msg).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::PacketSentAfterCounterpartyFlush(_)
+        ));
+
+        let flushing_context = context.with_counterparty_upgrade_next_sequence_send(
+            packet.destination_port.clone(),
+            packet.destination_channel.clone(),
+            packet.sequence.increment(),
+        );
+        process(&flushing_context, &msg).unwrap();
+    }
 }
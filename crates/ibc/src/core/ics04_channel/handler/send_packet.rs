@@ -1,9 +1,11 @@
 use crate::core::ics04_channel::channel::Counterparty;
 use crate::core::ics04_channel::channel::State;
 use crate::core::ics04_channel::commitment::PacketCommitment;
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::{channel_upgrades_enabled, ChannelReader};
 use crate::core::ics04_channel::events::SendPacket;
 use crate::core::ics04_channel::packet::{PacketResult, Sequence};
-use crate::core::ics04_channel::{context::ChannelReader, error::Error, packet::Packet};
+use crate::core::ics04_channel::{error::Error, packet::Packet};
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::events::IbcEvent;
 use crate::handler::{HandlerOutput, HandlerResult};
@@ -28,6 +30,24 @@ pub fn send_packet(ctx: &dyn ChannelReader, packet: Packet) -> HandlerResult<Pac
         return Err(Error::channel_closed(packet.source_channel));
     }
 
+    if ctx.is_channel_paused(&packet.source_port, &packet.source_channel)? {
+        return Err(Error::channel_paused(
+            packet.source_port,
+            packet.source_channel,
+        ));
+    }
+
+    // While upgrading, packets may still be sent as long as the channel is flushing: they are
+    // then flushed along with the ones already in flight.
+    if source_channel_end.state_matches(&State::FlushComplete)
+        || (source_channel_end.state_matches(&State::Flushing) && !channel_upgrades_enabled(ctx))
+    {
+        return Err(Error::invalid_channel_state(
+            packet.source_channel,
+            source_channel_end.state,
+        ));
+    }
+
     let counterparty = Counterparty::new(
         packet.destination_port.clone(),
         Some(packet.destination_channel.clone()),
@@ -39,8 +59,9 @@ pub fn send_packet(ctx: &dyn ChannelReader, packet: Packet) -> HandlerResult<Pac
             packet.destination_channel,
         ));
     }
-    let source_connection_id = &source_channel_end.connection_hops()[0];
-    let connection_end = ctx.connection_end(source_connection_id)?;
+    let hops = ConnectionHops::resolve(ctx, source_channel_end.connection_hops())?;
+    let source_connection_id = hops.connection_id();
+    let connection_end = hops.connection_end();
 
     let client_id = connection_end.client_id().clone();
 
@@ -134,13 +155,16 @@ mod tests {
 
         let context = MockContext::default();
 
-        let channel_end = ChannelEnd::new(
-            State::TryOpen,
-            Order::default(),
-            Counterparty::new(PortId::default(), Some(ChannelId::default())),
-            vec![ConnectionId::default()],
-            Version::ics20(),
-        );
+        let channel_end_in = |state| {
+            ChannelEnd::new(
+                state,
+                Order::default(),
+                Counterparty::new(PortId::default(), Some(ChannelId::default())),
+                vec![ConnectionId::default()],
+                Version::ics20(),
+            )
+        };
+        let channel_end = channel_end_in(State::TryOpen);
 
         let connection_end = ConnectionEnd::new(
             ConnectionState::Open,
@@ -200,7 +224,7 @@ mod tests {
                     .with_connection(ConnectionId::default(), connection_end.clone())
                     .with_channel(PortId::default(), ChannelId::default(), channel_end.clone())
                     .with_send_sequence(PortId::default(), ChannelId::default(), 1.into()),
-                packet,
+                packet: packet.clone(),
                 want_pass: true,
             },
             Test {
@@ -225,6 +249,53 @@ mod tests {
                 packet: packet_timeout_one_before_client_height,
                 want_pass: false,
             },
+            Test {
+                name: "Flushing channel on a host without channel upgrades".to_string(),
+                ctx: context
+                    .clone()
+                    .with_client(&ClientId::default(), client_height)
+                    .with_connection(ConnectionId::default(), connection_end.clone())
+                    .with_channel(
+                        PortId::default(),
+                        ChannelId::default(),
+                        channel_end_in(State::Flushing),
+                    )
+                    .with_send_sequence(PortId::default(), ChannelId::default(), 1.into()),
+                packet: packet.clone(),
+                want_pass: false,
+            },
+            Test {
+                name: "Flushing channel on a host with channel upgrades".to_string(),
+                ctx: context
+                    .clone()
+                    .with_channel_upgrades()
+                    .with_client(&ClientId::default(), client_height)
+                    .with_connection(ConnectionId::default(), connection_end.clone())
+                    .with_channel(
+                        PortId::default(),
+                        ChannelId::default(),
+                        channel_end_in(State::Flushing),
+                    )
+                    .with_send_sequence(PortId::default(), ChannelId::default(), 1.into()),
+                packet: packet.clone(),
+                want_pass: true,
+            },
+            Test {
+                name: "Flushed channel on a host with channel upgrades".to_string(),
+                ctx: context
+                    .clone()
+                    .with_channel_upgrades()
+                    .with_client(&ClientId::default(), client_height)
+                    .with_connection(ConnectionId::default(), connection_end.clone())
+                    .with_channel(
+                        PortId::default(),
+                        ChannelId::default(),
+                        channel_end_in(State::FlushComplete),
+                    )
+                    .with_send_sequence(PortId::default(), ChannelId::default(), 1.into()),
+                packet: packet.clone(),
+                want_pass: false,
+            },
             Test {
                 name: "Packet timeout due to timestamp".to_string(),
                 ctx: context
@@ -1,14 +1,14 @@
 use crate::core::ics04_channel::channel::State;
 use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
+use crate::core::ics04_channel::context::{channel_upgrades_enabled, ChannelReader};
+use crate::core::ics04_channel::error::{Error, ErrorDetail};
 use crate::core::ics04_channel::events::{ChannelClosed, TimeoutPacket};
-use crate::core::ics04_channel::handler::verify::{
-    verify_next_sequence_recv, verify_packet_receipt_absence,
-};
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
 use crate::core::ics04_channel::msgs::timeout::MsgTimeout;
-use crate::core::ics04_channel::packet::{PacketResult, Sequence};
-use crate::core::ics04_channel::{context::ChannelReader, error::Error};
+use crate::core::ics04_channel::packet::{PacketResult, Receipt, Sequence};
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
-use crate::events::IbcEvent;
+use crate::events::{EventReason, IbcEvent};
 use crate::handler::{HandlerOutput, HandlerResult};
 use crate::prelude::*;
 use crate::timestamp::Expiry;
@@ -19,6 +19,44 @@ pub struct TimeoutPacketResult {
     pub channel_id: ChannelId,
     pub seq: Sequence,
     pub channel: Option<ChannelEnd>,
+    /// The next sequence to acknowledge, if the timed-out packet is skipped on an
+    /// ordered-allow-timeout channel.
+    pub next_seq_ack: Option<Sequence>,
+}
+
+/// On ordered-allow-timeout channels a timed-out packet does not close the channel; instead, the
+/// next sequence to acknowledge moves past it so that the acknowledgements of the packets that
+/// follow it can be processed. Packets are settled in order, so only the packet at the next
+/// sequence to acknowledge may time out.
+pub(super) fn next_seq_ack_after_timeout<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    channel_end: &ChannelEnd,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    seq: Sequence,
+) -> Result<Option<Sequence>, Error> {
+    if !channel_end.order_matches(&Order::OrderedAllowTimeout) {
+        return Ok(None);
+    }
+
+    let next_seq_ack = ctx.get_next_sequence_ack(port_id, channel_id)?;
+    if seq != next_seq_ack {
+        return Err(Error::invalid_packet_sequence(seq, next_seq_ack));
+    }
+    Ok(Some(seq.increment()))
+}
+
+fn timeout_packet_event<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    msg: &MsgTimeout,
+    source_channel_end: &ChannelEnd,
+) -> IbcEvent {
+    let event = TimeoutPacket::new(msg.packet.clone(), source_channel_end.ordering);
+    if ctx.packet_events_with_relayer() {
+        IbcEvent::TimeoutPacket(event.with_relayer(msg.signer.clone()))
+    } else {
+        IbcEvent::TimeoutPacket(event)
+    }
 }
 
 /// TimeoutPacket is called by a module which originally attempted to send a
@@ -36,7 +74,10 @@ pub fn process<Ctx: ChannelReader>(
 
     let mut source_channel_end = ctx.channel_end(&packet.source_port, &packet.source_channel)?;
 
-    if !source_channel_end.state_matches(&State::Open) {
+    // The packets in flight of a channel end flushing for an upgrade may still be settled.
+    let settling_state = source_channel_end.state_matches(&State::Open)
+        || (source_channel_end.state_matches(&State::Flushing) && channel_upgrades_enabled(ctx));
+    if !settling_state {
         return Err(Error::channel_closed(packet.source_channel.clone()));
     }
 
@@ -52,13 +93,12 @@ pub fn process<Ctx: ChannelReader>(
         ));
     }
 
-    let source_connection_id = source_channel_end.connection_hops()[0].clone();
-    let connection_end = ctx.connection_end(&source_connection_id)?;
-
-    let client_id = connection_end.client_id().clone();
+    let hops = ConnectionHops::resolve(ctx, source_channel_end.connection_hops())?;
+    let source_connection_id = hops.connection_id().clone();
 
     // check that timeout height or timeout timestamp has passed on the other end
-    let proof_height = msg.proofs.height();
+    let verifier = ProofVerificationContext::new(ctx, &hops, &msg.proofs)?;
+    let (proof_height, proof_timestamp) = verifier.counterparty_height_and_timestamp()?;
 
     if packet.timeout_height.has_expired(proof_height) {
         return Err(Error::packet_timeout_height_not_reached(
@@ -67,10 +107,6 @@ pub fn process<Ctx: ChannelReader>(
         ));
     }
 
-    let consensus_state = ctx.client_consensus_state(&client_id, proof_height)?;
-
-    let proof_timestamp = consensus_state.timestamp();
-
     let packet_timestamp = packet.timeout_timestamp;
     if let Expiry::Expired = packet_timestamp.check_expiry(&proof_timestamp) {
         return Err(Error::packet_timeout_timestamp_not_reached(
@@ -79,9 +115,20 @@ pub fn process<Ctx: ChannelReader>(
         ));
     }
 
-    //verify packet commitment
-    let packet_commitment =
-        ctx.get_packet_commitment(&packet.source_port, &packet.source_channel, packet.sequence)?;
+    //verify packet commitment; a packet already acknowledged or timed out has none left
+    let packet_commitment = match ctx.get_packet_commitment(
+        &packet.source_port,
+        &packet.source_channel,
+        packet.sequence,
+    ) {
+        Ok(packet_commitment) => packet_commitment,
+        Err(e) if matches!(e.detail(), ErrorDetail::PacketCommitmentNotFound(_)) => {
+            output.emit(timeout_packet_event(ctx, msg, &source_channel_end));
+
+            return Ok(output.with_result(PacketResult::NoOp));
+        }
+        Err(e) => return Err(e),
+    };
 
     let expected_commitment = ctx.packet_commitment(
         packet.data.clone(),
@@ -92,21 +139,15 @@ pub fn process<Ctx: ChannelReader>(
         return Err(Error::incorrect_packet_commitment(packet.sequence));
     }
 
-    let result = if source_channel_end.order_matches(&Order::Ordered) {
-        if packet.sequence < msg.next_sequence_recv {
-            return Err(Error::invalid_packet_sequence(
+    let next_sequence_recv = msg.proven_next_sequence_recv(source_channel_end.ordering());
+    let result = if let Some(next_sequence_recv) = next_sequence_recv {
+        if packet.sequence < next_sequence_recv {
+            return Err(Error::packet_already_received(
                 packet.sequence,
-                msg.next_sequence_recv,
+                next_sequence_recv,
             ));
         }
-        verify_next_sequence_recv(
-            ctx,
-            msg.proofs.height(),
-            &connection_end,
-            packet.clone(),
-            msg.next_sequence_recv,
-            &msg.proofs,
-        )?;
+        verifier.verify_next_sequence_recv(packet, next_sequence_recv)?;
 
         source_channel_end.state = State::Closed;
         PacketResult::Timeout(TimeoutPacketResult {
@@ -114,40 +155,53 @@ pub fn process<Ctx: ChannelReader>(
             channel_id: packet.source_channel.clone(),
             seq: packet.sequence,
             channel: Some(source_channel_end.clone()),
+            next_seq_ack: None,
+        })
+    } else if source_channel_end.order_matches(&Order::OrderedAllowTimeout) {
+        // The counterparty wrote a timeout receipt when it skipped the timed-out packet.
+        verifier.verify_packet_receipt(packet, &Receipt::Timeout)?;
+
+        PacketResult::Timeout(TimeoutPacketResult {
+            port_id: packet.source_port.clone(),
+            channel_id: packet.source_channel.clone(),
+            seq: packet.sequence,
+            channel: None,
+            next_seq_ack: next_seq_ack_after_timeout(
+                ctx,
+                &source_channel_end,
+                &packet.source_port,
+                &packet.source_channel,
+                packet.sequence,
+            )?,
         })
     } else {
-        verify_packet_receipt_absence(
-            ctx,
-            msg.proofs.height(),
-            &connection_end,
-            packet.clone(),
-            &msg.proofs,
-        )?;
+        verifier.verify_packet_receipt_absence(packet)?;
 
         PacketResult::Timeout(TimeoutPacketResult {
             port_id: packet.source_port.clone(),
             channel_id: packet.source_channel.clone(),
             seq: packet.sequence,
             channel: None,
+            next_seq_ack: None,
         })
     };
 
     output.log("success: packet timeout ");
 
-    output.emit(IbcEvent::TimeoutPacket(TimeoutPacket::new(
-        packet.clone(),
-        source_channel_end.ordering,
-    )));
+    output.emit(timeout_packet_event(ctx, msg, &source_channel_end));
 
     if source_channel_end.order_matches(&Order::Ordered) {
-        output.emit(IbcEvent::ChannelClosed(ChannelClosed::new(
-            msg.packet.source_port.clone(),
-            msg.packet.source_channel.clone(),
-            source_channel_end.counterparty().port_id.clone(),
-            source_channel_end.counterparty().channel_id.clone(),
-            source_connection_id,
-            source_channel_end.ordering,
-        )));
+        output.emit(IbcEvent::ChannelClosed(
+            ChannelClosed::new(
+                msg.packet.source_port.clone(),
+                msg.packet.source_channel.clone(),
+                source_channel_end.counterparty().port_id.clone(),
+                source_channel_end.counterparty().channel_id.clone(),
+                source_connection_id,
+                source_channel_end.ordering,
+            )
+            .with_reason(EventReason::Timeout),
+        ));
     }
 
     Ok(output.with_result(result))
@@ -164,12 +218,14 @@ mod tests {
     use crate::core::ics03_connection::version::get_compatible_versions;
     use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
     use crate::core::ics04_channel::context::ChannelReader;
+    use crate::core::ics04_channel::error::ErrorDetail;
     use crate::core::ics04_channel::handler::timeout::process;
     use crate::core::ics04_channel::msgs::timeout::test_util::get_dummy_raw_msg_timeout;
     use crate::core::ics04_channel::msgs::timeout::MsgTimeout;
+    use crate::core::ics04_channel::packet::PacketResult;
     use crate::core::ics04_channel::Version;
     use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
-    use crate::events::IbcEvent;
+    use crate::events::{EventReason, IbcEvent};
     use crate::mock::context::MockContext;
     use crate::prelude::*;
     use crate::timestamp::ZERO_DURATION;
@@ -262,10 +318,28 @@ mod tests {
                     source_channel_end.clone(),
                 )
                 .with_client(&ClientId::default(), client_height)
-                .with_connection(ConnectionId::default(), connection_end.clone()),
-                msg,
+                .with_connection(ConnectionId::default(), connection_end.clone())
+                .with_packet_commitment(
+                    msg.packet.source_port.clone(),
+                    msg.packet.source_channel.clone(),
+                    msg.packet.sequence,
+                    data.clone(),
+                ),
+                msg: msg.clone(),
                 want_pass: false,
             },
+            Test {
+                name: "No-op because the packet was already settled".to_string(),
+                ctx: context.clone().with_channel(
+                    PortId::default(),
+                    ChannelId::default(),
+                    source_channel_end.clone(),
+                )
+                .with_client(&ClientId::default(), client_height)
+                .with_connection(ConnectionId::default(), connection_end.clone()),
+                msg: msg_ok.clone(),
+                want_pass: true,
+            },
             Test {
                 name: "Good parameters Unordered channel".to_string(),
                 ctx: context.clone()
@@ -332,11 +406,16 @@ mod tests {
                         .channel_end(&packet.source_port, &packet.source_channel)
                         .unwrap();
 
-                    if src_channel_end.order_matches(&Order::Ordered) {
+                    if let PacketResult::NoOp = proto_output.result {
+                        assert!(matches!(events.as_slice(), [IbcEvent::TimeoutPacket(_)]));
+                    } else if src_channel_end.order_matches(&Order::Ordered) {
                         assert_eq!(events.len(), 2);
 
                         assert!(matches!(events[0], IbcEvent::TimeoutPacket(_)));
-                        assert!(matches!(events[1], IbcEvent::ChannelClosed(_)));
+                        assert!(matches!(
+                            &events[1],
+                            IbcEvent::ChannelClosed(e) if e.reason() == Some(&EventReason::Timeout)
+                        ));
                     } else {
                         assert_eq!(events.len(), 1);
                         assert!(matches!(
@@ -358,4 +437,162 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn timeout_packet_ordered_allow_timeout() {
+        let context = MockContext::default();
+        let client_height = Height::new(0, 2).unwrap();
+
+        let mut msg = MsgTimeout::try_from(get_dummy_raw_msg_timeout(2, 5, 5)).unwrap();
+        msg.packet.timeout_timestamp = Default::default();
+        let packet = msg.packet.clone();
+
+        let data = context.packet_commitment(
+            packet.data.clone(),
+            packet.timeout_height,
+            packet.timeout_timestamp,
+        );
+
+        let source_channel_end = ChannelEnd::new(
+            State::Open,
+            Order::OrderedAllowTimeout,
+            Counterparty::new(
+                packet.destination_port.clone(),
+                Some(packet.destination_channel.clone()),
+            ),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        let context = context
+            .with_client(&ClientId::default(), client_height)
+            .with_connection(ConnectionId::default(), connection_end)
+            .with_channel(
+                packet.source_port.clone(),
+                packet.source_channel.clone(),
+                source_channel_end,
+            )
+            .with_packet_commitment(
+                packet.source_port.clone(),
+                packet.source_channel.clone(),
+                packet.sequence,
+                data,
+            )
+            .with_ack_sequence(
+                packet.source_port.clone(),
+                packet.source_channel.clone(),
+                packet.sequence,
+            );
+
+        // Packets time out in order, at the next sequence to acknowledge.
+        let settled = context.clone().with_ack_sequence(
+            packet.source_port.clone(),
+            packet.source_channel.clone(),
+            packet.sequence.increment(),
+        );
+        let err = process(&settled, &msg).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::InvalidPacketSequence(_)
+        ));
+
+        let output = process(&context, &msg).unwrap();
+
+        // The channel stays open, and acknowledgements move past the timed-out packet.
+        assert_eq!(output.events.len(), 1);
+        assert!(matches!(output.events[0], IbcEvent::TimeoutPacket(_)));
+        match output.result {
+            PacketResult::Timeout(res) => {
+                assert!(res.channel.is_none());
+                assert_eq!(res.next_seq_ack, Some(packet.sequence.increment()));
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn timeout_packet_next_sequence_recv() {
+        let mut msg = MsgTimeout::try_from(get_dummy_raw_msg_timeout(2, 5, 5)).unwrap();
+        msg.packet.timeout_timestamp = Default::default();
+        let packet = msg.packet.clone();
+
+        let context = |ordering: Order| {
+            let context = MockContext::default();
+            let data = context.packet_commitment(
+                packet.data.clone(),
+                packet.timeout_height,
+                packet.timeout_timestamp,
+            );
+            let source_channel_end = ChannelEnd::new(
+                State::Open,
+                ordering,
+                Counterparty::new(
+                    packet.destination_port.clone(),
+                    Some(packet.destination_channel.clone()),
+                ),
+                vec![ConnectionId::default()],
+                Version::ics20(),
+            );
+            let connection_end = ConnectionEnd::new(
+                ConnectionState::Open,
+                ClientId::default(),
+                ConnectionCounterparty::new(
+                    ClientId::default(),
+                    Some(ConnectionId::default()),
+                    Default::default(),
+                ),
+                get_compatible_versions(),
+                ZERO_DURATION,
+            );
+            context
+                .with_client(&ClientId::default(), Height::new(0, 2).unwrap())
+                .with_connection(ConnectionId::default(), connection_end)
+                .with_channel(
+                    packet.source_port.clone(),
+                    packet.source_channel.clone(),
+                    source_channel_end,
+                )
+                .with_packet_commitment(
+                    packet.source_port.clone(),
+                    packet.source_channel.clone(),
+                    packet.sequence,
+                    data,
+                )
+        };
+
+        // The counterparty of an ordered channel has not received the packet yet, which closes
+        // the channel end.
+        let output = process(&context(Order::Ordered), &msg).unwrap();
+        match output.result {
+            PacketResult::Timeout(res) => {
+                assert_eq!(res.channel.unwrap().state, State::Closed);
+                assert_eq!(res.next_seq_ack, None);
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+
+        // The counterparty has received the packet.
+        let mut received = msg.clone();
+        received.next_sequence_recv = packet.sequence.increment();
+        let err = process(&context(Order::Ordered), &received).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::PacketAlreadyReceived(e)
+                if e.sequence == packet.sequence && e.next_sequence_recv == received.next_sequence_recv
+        ));
+
+        // The field is ignored on unordered channels.
+        assert_eq!(received.proven_next_sequence_recv(&Order::Unordered), None);
+        assert!(process(&context(Order::Unordered), &received).is_ok());
+    }
 }
@@ -1,19 +1,18 @@
 use crate::core::ics04_channel::channel::State;
 use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order};
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::events::{ChannelClosed, TimeoutPacket};
-use crate::core::ics04_channel::handler::verify::verify_channel_proofs;
-use crate::core::ics04_channel::handler::verify::{
-    verify_next_sequence_recv, verify_packet_receipt_absence,
-};
+use crate::core::ics04_channel::handler::verify::ProofVerificationContext;
 use crate::core::ics04_channel::msgs::timeout_on_close::MsgTimeoutOnClose;
 use crate::core::ics04_channel::packet::PacketResult;
 use crate::core::ics04_channel::{
-    context::ChannelReader, error::Error, handler::timeout::TimeoutPacketResult,
+    context::ChannelReader,
+    error::{Error, ErrorDetail},
+    handler::timeout::{next_seq_ack_after_timeout, TimeoutPacketResult},
 };
-use crate::events::IbcEvent;
+use crate::events::{EventReason, IbcEvent};
 use crate::handler::{HandlerOutput, HandlerResult};
 use crate::prelude::*;
-use crate::proofs::{ProofError, Proofs};
 
 pub fn process<Ctx: ChannelReader>(
     ctx: &Ctx,
@@ -37,12 +36,39 @@ pub fn process<Ctx: ChannelReader>(
         ));
     }
 
-    let source_connection_id = source_channel_end.connection_hops()[0].clone();
-    let connection_end = ctx.connection_end(&source_connection_id)?;
+    // The channel proof of a timeout-on-close message, carried as `other_proof`, is the one that
+    // holds the connection ends of the remote hops of a multihop channel.
+    let hops = ConnectionHops::resolve(ctx, source_channel_end.connection_hops())?;
+    let hops = match msg.proofs.other_proof() {
+        Some(proof_close) => hops.with_remote_hops(proof_close)?,
+        None => hops,
+    };
+    let source_connection_id = hops.connection_id().clone();
+
+    let timeout_packet_event = || {
+        let event = TimeoutPacket::new(packet.clone(), source_channel_end.ordering);
+        if ctx.packet_events_with_relayer() {
+            IbcEvent::TimeoutPacket(event.with_relayer(msg.signer.clone()))
+        } else {
+            IbcEvent::TimeoutPacket(event)
+        }
+    };
 
-    //verify the packet was sent, check the store
-    let packet_commitment =
-        ctx.get_packet_commitment(&packet.source_port, &packet.source_channel, packet.sequence)?;
+    //verify the packet was sent, check the store; a packet already acknowledged or timed out
+    //has no commitment left
+    let packet_commitment = match ctx.get_packet_commitment(
+        &packet.source_port,
+        &packet.source_channel,
+        packet.sequence,
+    ) {
+        Ok(packet_commitment) => packet_commitment,
+        Err(e) if matches!(e.detail(), ErrorDetail::PacketCommitmentNotFound(_)) => {
+            output.emit(timeout_packet_event());
+
+            return Ok(output.with_result(PacketResult::NoOp));
+        }
+        Err(e) => return Err(e),
+    };
 
     let expected_commitment = ctx.packet_commitment(
         packet.data.clone(),
@@ -58,12 +84,7 @@ pub fn process<Ctx: ChannelReader>(
         Some(packet.source_channel.clone()),
     );
 
-    let counterparty = connection_end.counterparty();
-    let ccid = counterparty.connection_id().ok_or_else(|| {
-        Error::undefined_connection_counterparty(source_channel_end.connection_hops()[0].clone())
-    })?;
-
-    let expected_connection_hops = vec![ccid.clone()];
+    let expected_connection_hops = hops.counterparty_hops()?;
 
     let expected_channel_end = ChannelEnd::new(
         State::Closed,
@@ -73,77 +94,68 @@ pub fn process<Ctx: ChannelReader>(
         source_channel_end.version().clone(),
     );
 
-    // The message's proofs have the channel proof as `other_proof`
-    let proof_close = match msg.proofs.other_proof() {
-        Some(p) => p.clone(),
-        None => return Err(Error::invalid_proof(ProofError::empty_proof())),
-    };
-    let proofs = Proofs::new(proof_close, None, None, None, msg.proofs.height())
-        .map_err(Error::invalid_proof)?;
-    verify_channel_proofs(
-        ctx,
-        msg.proofs.height(),
-        &source_channel_end,
-        &connection_end,
-        &expected_channel_end,
-        &proofs,
-    )?;
-
-    let result = if source_channel_end.order_matches(&Order::Ordered) {
-        if packet.sequence < msg.next_sequence_recv {
-            return Err(Error::invalid_packet_sequence(
+    // Both the counterparty channel closure and the non-receipt (or next sequence) of the
+    // packet are proven at the same height; the latter is also subject to the connection delay.
+    let verifier = ProofVerificationContext::new(ctx, &hops, &msg.proofs)?;
+    verifier.verify_channel_closed_proof(packet, &expected_channel_end)?;
+
+    let next_sequence_recv = msg.proven_next_sequence_recv(source_channel_end.ordering());
+    let result = if let Some(next_sequence_recv) = next_sequence_recv {
+        if packet.sequence < next_sequence_recv {
+            return Err(Error::packet_already_received(
                 packet.sequence,
-                msg.next_sequence_recv,
+                next_sequence_recv,
             ));
         }
-        verify_next_sequence_recv(
-            ctx,
-            msg.proofs.height(),
-            &connection_end,
-            packet.clone(),
-            msg.next_sequence_recv,
-            &msg.proofs,
-        )?;
+        verifier.verify_next_sequence_recv(packet, next_sequence_recv)?;
 
         PacketResult::Timeout(TimeoutPacketResult {
             port_id: packet.source_port.clone(),
             channel_id: packet.source_channel.clone(),
             seq: packet.sequence,
-            channel: Some(source_channel_end.clone()),
+            // As on timeout, the channel end closes on the timeout of a packet of an ordered
+            // channel.
+            channel: source_channel_end.order_matches(&Order::Ordered).then(|| {
+                let mut channel_end = source_channel_end.clone();
+                channel_end.set_state(State::Closed);
+                channel_end
+            }),
+            next_seq_ack: next_seq_ack_after_timeout(
+                ctx,
+                &source_channel_end,
+                &packet.source_port,
+                &packet.source_channel,
+                packet.sequence,
+            )?,
         })
     } else {
-        verify_packet_receipt_absence(
-            ctx,
-            msg.proofs.height(),
-            &connection_end,
-            packet.clone(),
-            &msg.proofs,
-        )?;
+        verifier.verify_packet_receipt_absence(packet)?;
 
         PacketResult::Timeout(TimeoutPacketResult {
             port_id: packet.source_port.clone(),
             channel_id: packet.source_channel.clone(),
             seq: packet.sequence,
             channel: None,
+            next_seq_ack: None,
         })
     };
 
     output.log("success: packet timeout");
 
-    output.emit(IbcEvent::TimeoutPacket(TimeoutPacket::new(
-        packet.clone(),
-        source_channel_end.ordering,
-    )));
+    output.emit(timeout_packet_event());
 
     if source_channel_end.order_matches(&Order::Ordered) {
-        output.emit(IbcEvent::ChannelClosed(ChannelClosed::new(
-            msg.packet.source_port.clone(),
-            msg.packet.source_channel.clone(),
-            source_channel_end.counterparty().port_id.clone(),
-            source_channel_end.counterparty().channel_id.clone(),
-            source_connection_id,
-            source_channel_end.ordering,
-        )));
+        output.emit(IbcEvent::ChannelClosed(
+            ChannelClosed::new(
+                msg.packet.source_port.clone(),
+                msg.packet.source_channel.clone(),
+                source_channel_end.counterparty().port_id.clone(),
+                source_channel_end.counterparty().channel_id.clone(),
+                source_connection_id,
+                source_channel_end.ordering,
+            )
+            .with_reason(EventReason::Timeout),
+        ));
     }
 
     Ok(output.with_result(result))
@@ -154,6 +166,7 @@ mod tests {
     use crate::prelude::*;
     use test_log::test;
 
+    use crate::clients::ics07_tendermint::client_type as tm_client_type;
     use crate::core::ics02_client::height::Height;
     use crate::core::ics03_connection::connection::ConnectionEnd;
     use crate::core::ics03_connection::connection::Counterparty as ConnectionCounterparty;
@@ -161,12 +174,15 @@ mod tests {
     use crate::core::ics03_connection::version::get_compatible_versions;
     use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
     use crate::core::ics04_channel::context::ChannelReader;
+    use crate::core::ics04_channel::error::ErrorDetail;
     use crate::core::ics04_channel::handler::timeout_on_close::process;
     use crate::core::ics04_channel::msgs::timeout_on_close::test_util::get_dummy_raw_msg_timeout_on_close;
     use crate::core::ics04_channel::msgs::timeout_on_close::MsgTimeoutOnClose;
+    use crate::core::ics04_channel::packet::PacketResult;
     use crate::core::ics04_channel::Version;
     use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
-    use crate::events::IbcEvent;
+    use crate::core::ics24_host::path::ChannelEndsPath;
+    use crate::events::{EventReason, IbcEvent};
     use crate::mock::context::MockContext;
     use crate::timestamp::ZERO_DURATION;
 
@@ -230,7 +246,7 @@ mod tests {
                 want_pass: false,
             },
             Test {
-                name: "Processing fails no packet commitment is found".to_string(),
+                name: "No-op because no packet commitment is found".to_string(),
                 ctx: context
                     .clone()
                     .with_channel(
@@ -240,7 +256,7 @@ mod tests {
                     )
                     .with_connection(ConnectionId::default(), connection_end.clone()),
                 msg: msg.clone(),
-                want_pass: false,
+                want_pass: true,
             },
             Test {
                 name: "Good parameters".to_string(),
@@ -284,11 +300,16 @@ mod tests {
                         .channel_end(&msg.packet.source_port, &msg.packet.source_channel)
                         .unwrap();
 
-                    if src_channel_end.order_matches(&Order::Ordered) {
+                    if let PacketResult::NoOp = proto_output.result {
+                        assert!(matches!(events.as_slice(), [IbcEvent::TimeoutPacket(_)]));
+                    } else if src_channel_end.order_matches(&Order::Ordered) {
                         assert_eq!(events.len(), 2);
 
                         assert!(matches!(events[0], IbcEvent::TimeoutPacket(_)));
-                        assert!(matches!(events[1], IbcEvent::ChannelClosed(_)));
+                        assert!(matches!(
+                            &events[1],
+                            IbcEvent::ChannelClosed(e) if e.reason() == Some(&EventReason::Timeout)
+                        ));
                     } else {
                         assert_eq!(events.len(), 1);
                         assert!(matches!(
@@ -310,4 +331,142 @@ mod tests {
             }
         }
     }
+
+    fn ordered_setup(order: Order, tendermint_client: bool) -> (MockContext, MsgTimeoutOnClose) {
+        let msg = MsgTimeoutOnClose::try_from(get_dummy_raw_msg_timeout_on_close(2, 5)).unwrap();
+        let packet = msg.packet.clone();
+
+        let source_channel_end = ChannelEnd::new(
+            State::Open,
+            order,
+            Counterparty::new(
+                packet.destination_port.clone(),
+                Some(packet.destination_channel.clone()),
+            ),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+
+        let context = MockContext::default();
+        let data = context.packet_commitment(
+            packet.data.clone(),
+            packet.timeout_height,
+            packet.timeout_timestamp,
+        );
+        let client_type = tendermint_client.then(tm_client_type);
+        let context = context
+            .with_client_parametrized(
+                &ClientId::default(),
+                Height::new(0, 2).unwrap(),
+                client_type,
+                None,
+            )
+            .with_connection(ConnectionId::default(), connection_end)
+            .with_channel(
+                packet.source_port.clone(),
+                packet.source_channel.clone(),
+                source_channel_end,
+            )
+            .with_packet_commitment(
+                packet.source_port,
+                packet.source_channel,
+                packet.sequence,
+                data,
+            );
+
+        (context, msg)
+    }
+
+    #[test]
+    fn timeout_on_close_rejects_forged_channel_proof() {
+        // A Tendermint client actually checks the closed-channel proof against its
+        // consensus state root, which the dummy proof bytes cannot satisfy.
+        let (context, msg) = ordered_setup(Order::Ordered, true);
+
+        let err = process(&context, &msg).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::LowLevelProofVerification(e)
+                if e.client_id == ClientId::default()
+                    && e.height == msg.proofs.height()
+                    && e.path == ChannelEndsPath(
+                        msg.packet.destination_port.clone(),
+                        msg.packet.destination_channel.clone(),
+                    )
+                    .to_string()
+        ));
+    }
+
+    #[test]
+    fn timeout_on_close_rejects_received_packet() {
+        let (context, mut msg) = ordered_setup(Order::Ordered, false);
+
+        // The counterparty has already received the packet.
+        msg.next_sequence_recv = msg.packet.sequence.increment();
+
+        let err = process(&context, &msg).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::PacketAlreadyReceived(e) if e.sequence == msg.packet.sequence
+        ));
+    }
+
+    #[test]
+    fn timeout_on_close_closes_ordered_channel() {
+        let (context, msg) = ordered_setup(Order::Ordered, false);
+
+        let output = process(&context, &msg).unwrap();
+        match output.result {
+            PacketResult::Timeout(res) => {
+                assert_eq!(res.channel.unwrap().state, State::Closed);
+                assert_eq!(res.next_seq_ack, None);
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn timeout_on_close_proves_next_sequence_recv_on_ordered_allow_timeout_channel() {
+        let (context, mut msg) = ordered_setup(Order::OrderedAllowTimeout, false);
+        let context = context.with_ack_sequence(
+            msg.packet.source_port.clone(),
+            msg.packet.source_channel.clone(),
+            msg.packet.sequence,
+        );
+
+        // The closed counterparty never wrote a timeout receipt, so its next sequence to receive
+        // is checked instead, as on ordered channels.
+        msg.next_sequence_recv = msg.packet.sequence.increment();
+        let err = process(&context, &msg).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::PacketAlreadyReceived(e) if e.sequence == msg.packet.sequence
+        ));
+
+        msg.next_sequence_recv = msg.packet.sequence;
+        let output = process(&context, &msg).unwrap();
+        match output.result {
+            PacketResult::Timeout(res) => {
+                // Unlike ordered channels, the channel end stays open.
+                assert_eq!(res.channel, None);
+                assert_eq!(res.next_seq_ack, Some(msg.packet.sequence.increment()));
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert!(matches!(
+            output.events.as_slice(),
+            [IbcEvent::TimeoutPacket(_)]
+        ));
+    }
 }
@@ -1,42 +1,155 @@
-use crate::core::ics03_connection::connection::ConnectionEnd;
+use core::time::Duration;
+
+use crate::core::ics02_client::client_state::ClientState;
+use crate::core::ics02_client::consensus_state::ConsensusState;
+use crate::core::ics02_client::error as client_error;
+use crate::core::ics03_connection::connection::{ConnectionEnd, State as ConnectionState};
 use crate::core::ics04_channel::channel::ChannelEnd;
+use crate::core::ics04_channel::connection_hops::ConnectionHops;
 use crate::core::ics04_channel::context::ChannelReader;
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement;
-use crate::core::ics04_channel::packet::{Packet, Sequence};
+use crate::core::ics04_channel::multihop::MultihopProof;
+use crate::core::ics04_channel::packet::{Packet, Receipt, Sequence};
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::upgrade::{ErrorReceipt, Upgrade};
+use crate::core::ics23_commitment::commitment::CommitmentProofBytes;
+use crate::core::ics24_host::identifier::{ChannelId, ClientId, PortId};
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics24_host::path::ChannelUpgradePath;
+use crate::core::ics24_host::path::{
+    AcksPath, ChannelEndsPath, ClientConsensusStatePath, CommitmentsPath, ConnectionsPath, Path,
+    ReceiptsPath, SeqRecvsPath,
+};
 use crate::prelude::*;
-use crate::proofs::Proofs;
+use crate::proofs::{ProofError, Proofs};
+use crate::timestamp::Timestamp;
 use crate::Height;
 
-/// Entry point for verifying all proofs bundled in any ICS4 message for channel protocols.
-pub fn verify_channel_proofs<Ctx: ChannelReader>(
-    ctx: &Ctx,
+/// The state of the counterparty chain of a channel that the proof of one of its keys is
+/// verified against.
+struct CounterpartyState {
+    /// The consensus state of the counterparty chain, at `height`.
     height: Height,
-    channel_end: &ChannelEnd,
-    connection_end: &ConnectionEnd,
-    expected_chan: &ChannelEnd,
-    proofs: &Proofs,
-) -> Result<(), Error> {
-    // This is the client which will perform proof verification.
-    let client_id = connection_end.client_id().clone();
+    consensus_state: Box<dyn ConsensusState>,
+    /// A connection end of the host client, whose counterparty holds the commitment prefix of
+    /// the counterparty chain, and whose delay period remains to be checked by the client.
+    connection_end: ConnectionEnd,
+    /// The proof of the key itself.
+    proof: CommitmentProofBytes,
+}
 
-    let client_state = ctx.client_state(&client_id)?;
+/// Verifies the proofs bundled in a message, all at the same height, against the client of the
+/// host connection of a channel and its consensus state at that height.
+///
+/// Both states are read from the store once, when the context is created, rather than once per
+/// proof, so that the handlers of messages carrying several proofs (e.g. a `MsgTimeout`, whose
+/// proof height also bounds the timeout) build a single context for all of them. The hops of
+/// multihop proofs are likewise decoded and verified once.
+pub struct ProofVerificationContext<'a, Ctx> {
+    ctx: &'a Ctx,
+    proofs: &'a Proofs,
+    client_state: Box<dyn ClientState>,
+    consensus_state: Box<dyn ConsensusState>,
+    /// The counterparty states that the object proof and the other proof, if any, are verified
+    /// against.
+    object: CounterpartyState,
+    other: Option<CounterpartyState>,
+}
 
-    // The client must not be frozen.
-    if client_state.is_frozen() {
-        return Err(Error::frozen_client(client_id));
+impl<'a, Ctx: ChannelReader> ProofVerificationContext<'a, Ctx> {
+    /// Reads the client of the channel routed through `hops`, which must not be frozen, and its
+    /// consensus state at the height of `proofs`.
+    pub fn new(ctx: &'a Ctx, hops: &'a ConnectionHops, proofs: &'a Proofs) -> Result<Self, Error> {
+        let client_id = hops.connection_end().client_id();
+
+        // Proofs beyond the latest height of the client are rejected before its state is decoded.
+        let latest_height = ctx.client_latest_height(client_id)?;
+        if proofs.height() > latest_height {
+            return Err(Error::proof_height_after_latest_height(
+                client_id.clone(),
+                proofs.height(),
+                latest_height,
+            ));
+        }
+
+        let client_state = ctx.client_state(client_id)?;
+
+        // The client must not be frozen.
+        if client_state.is_frozen() {
+            return Err(Error::frozen_client(client_id.clone()));
+        }
+
+        let consensus_state = ctx.client_consensus_state(client_id, proofs.height())?;
+        let counterparty = |proof| {
+            resolve_counterparty(
+                ctx,
+                hops,
+                client_state.as_ref(),
+                consensus_state.as_ref(),
+                proofs.height(),
+                proof,
+            )
+        };
+        let object = counterparty(proofs.object_proof())?;
+        let other = proofs
+            .other_proof()
+            .as_ref()
+            .map(counterparty)
+            .transpose()?;
+
+        Ok(Self {
+            ctx,
+            proofs,
+            client_state,
+            consensus_state,
+            object,
+            other,
+        })
+    }
+
+    /// The height of the proofs.
+    pub fn height(&self) -> Height {
+        self.proofs.height()
     }
 
-    let consensus_state = ctx.client_consensus_state(&client_id, proofs.height())?;
+    /// The consensus state of the host client at the proof height.
+    pub fn consensus_state(&self) -> &dyn ConsensusState {
+        self.consensus_state.as_ref()
+    }
 
-    // Verify the proof for the channel state against the expected channel end.
-    // A counterparty channel id of None in not possible, and is checked by validate_basic in msg.
-    client_state
-        .verify_channel_state(
-            height,
-            connection_end.counterparty().prefix(),
-            proofs.object_proof(),
-            consensus_state.root(),
+    fn other(&self) -> Result<&CounterpartyState, Error> {
+        self.other
+            .as_ref()
+            .ok_or_else(|| Error::invalid_proof(ProofError::empty_proof()))
+    }
+
+    /// Returns the height and the timestamp of the counterparty chain at which the object proof
+    /// is verified. It is the proof height for a single-hop channel, or the height of the
+    /// consensus state proven by the last hop of a multihop channel.
+    pub fn counterparty_height_and_timestamp(&self) -> Result<(Height, Timestamp), Error> {
+        let counterparty = &self.object;
+        Ok((
+            counterparty.height,
+            counterparty.consensus_state.timestamp(),
+        ))
+    }
+
+    /// Verifies the proof that the counterparty channel end of `channel_end` is `expected_chan`.
+    pub fn verify_channel_proofs(
+        &self,
+        channel_end: &ChannelEnd,
+        expected_chan: &ChannelEnd,
+    ) -> Result<(), Error> {
+        let counterparty = &self.object;
+
+        // A counterparty channel id of None in not possible, and is checked by validate_basic in msg.
+        verify_channel_state(
+            self.client_state.as_ref(),
+            counterparty.consensus_state.as_ref(),
+            &counterparty.connection_end,
+            counterparty.height,
+            &counterparty.proof,
             channel_end.counterparty().port_id(),
             channel_end
                 .counterparty()
@@ -44,156 +157,646 @@ pub fn verify_channel_proofs<Ctx: ChannelReader>(
                 .ok_or_else(Error::invalid_counterparty_channel_id)?,
             expected_chan,
         )
-        .map_err(Error::verify_channel_failed)
+    }
+
+    /// Verifies the proof, bundled in a timeout-on-close message, that the counterparty channel
+    /// end of `packet` is closed.
+    pub fn verify_channel_closed_proof(
+        &self,
+        packet: &Packet,
+        expected_chan: &ChannelEnd,
+    ) -> Result<(), Error> {
+        // The channel proof of a timeout-on-close message is carried as `other_proof`.
+        let counterparty = self.other()?;
+
+        self.client_state
+            .verify_channel_state(
+                counterparty.height,
+                counterparty.connection_end.counterparty().prefix(),
+                &counterparty.proof,
+                counterparty.consensus_state.root(),
+                &packet.destination_port,
+                &packet.destination_channel,
+                expected_chan,
+            )
+            .map_err(proof_verification_failed(
+                counterparty.connection_end.client_id(),
+                counterparty.height,
+                ChannelEndsPath(
+                    packet.destination_port.clone(),
+                    packet.destination_channel.clone(),
+                ),
+            ))
+    }
+
+    /// Verifies the proofs, bundled in a message of the channel upgrade handshake, of the
+    /// counterparty channel end `expected_chan` of `channel_end`, and of its upgrade
+    /// `expected_upgrade`, which is carried as `other_proof`.
+    #[cfg(any(test, feature = "unstable"))]
+    pub fn verify_channel_upgrade_proofs(
+        &self,
+        channel_end: &ChannelEnd,
+        expected_chan: &ChannelEnd,
+        expected_upgrade: &Upgrade,
+    ) -> Result<(), Error> {
+        self.verify_channel_proofs(channel_end, expected_chan)?;
+
+        let counterparty = self.other()?;
+        let port_id = channel_end.counterparty().port_id();
+        let channel_id = channel_end
+            .counterparty()
+            .channel_id()
+            .ok_or_else(Error::invalid_counterparty_channel_id)?;
+
+        self.client_state
+            .verify_channel_upgrade(
+                counterparty.height,
+                counterparty.connection_end.counterparty().prefix(),
+                &counterparty.proof,
+                counterparty.consensus_state.root(),
+                port_id,
+                channel_id,
+                expected_upgrade,
+            )
+            .map_err(proof_verification_failed(
+                counterparty.connection_end.client_id(),
+                counterparty.height,
+                ChannelUpgradePath::Upgrade(port_id.clone(), channel_id.clone()),
+            ))
+    }
+
+    /// Verifies the proof of the receipt `expected_receipt` of the failed upgrade of the
+    /// counterparty channel end of `channel_end`.
+    #[cfg(any(test, feature = "unstable"))]
+    pub fn verify_upgrade_error_receipt_proof(
+        &self,
+        channel_end: &ChannelEnd,
+        expected_receipt: &ErrorReceipt,
+    ) -> Result<(), Error> {
+        let counterparty = &self.object;
+        let port_id = channel_end.counterparty().port_id();
+        let channel_id = channel_end
+            .counterparty()
+            .channel_id()
+            .ok_or_else(Error::invalid_counterparty_channel_id)?;
+
+        self.client_state
+            .verify_channel_upgrade_error(
+                counterparty.height,
+                counterparty.connection_end.counterparty().prefix(),
+                &counterparty.proof,
+                counterparty.consensus_state.root(),
+                port_id,
+                channel_id,
+                expected_receipt,
+            )
+            .map_err(proof_verification_failed(
+                counterparty.connection_end.client_id(),
+                counterparty.height,
+                ChannelUpgradePath::UpgradeError(port_id.clone(), channel_id.clone()),
+            ))
+    }
+
+    /// Verifies the proof of the commitment of `packet` on the counterparty chain.
+    pub fn verify_packet_recv_proofs(&self, packet: &Packet) -> Result<(), Error> {
+        let counterparty = &self.object;
+
+        let commitment = self.ctx.packet_commitment(
+            packet.data.clone(),
+            packet.timeout_height,
+            packet.timeout_timestamp,
+        );
+
+        // Verify the proof for the packet against the chain store.
+        self.client_state
+            .verify_packet_data(
+                self.ctx,
+                counterparty.height,
+                &counterparty.connection_end,
+                &counterparty.proof,
+                counterparty.consensus_state.root(),
+                &packet.source_port,
+                &packet.source_channel,
+                packet.sequence,
+                commitment,
+            )
+            .map_err(proof_verification_failed(
+                counterparty.connection_end.client_id(),
+                counterparty.height,
+                CommitmentsPath::new(
+                    packet.source_port.clone(),
+                    packet.source_channel.clone(),
+                    packet.sequence,
+                ),
+            ))
+    }
+
+    /// Verifies the proof of the acknowledgement of `packet` on the counterparty chain.
+    pub fn verify_packet_acknowledgement_proofs(
+        &self,
+        packet: &Packet,
+        acknowledgement: Acknowledgement,
+    ) -> Result<(), Error> {
+        let counterparty = &self.object;
+
+        let ack_commitment = self.ctx.ack_commitment(acknowledgement);
+
+        // Verify the proof for the packet against the chain store.
+        self.client_state
+            .verify_packet_acknowledgement(
+                self.ctx,
+                counterparty.height,
+                &counterparty.connection_end,
+                &counterparty.proof,
+                counterparty.consensus_state.root(),
+                &packet.destination_port,
+                &packet.destination_channel,
+                packet.sequence,
+                ack_commitment,
+            )
+            .map_err(proof_verification_failed(
+                counterparty.connection_end.client_id(),
+                counterparty.height,
+                AcksPath::new(
+                    packet.destination_port.clone(),
+                    packet.destination_channel.clone(),
+                    packet.sequence,
+                ),
+            ))
+    }
+
+    /// Verifies the proof that the next sequence to be received on the counterparty channel
+    /// end of `packet` is `seq`.
+    pub fn verify_next_sequence_recv(&self, packet: &Packet, seq: Sequence) -> Result<(), Error> {
+        let counterparty = &self.object;
+
+        // Verify the proof for the packet against the chain store.
+        self.client_state
+            .verify_next_sequence_recv(
+                self.ctx,
+                counterparty.height,
+                &counterparty.connection_end,
+                &counterparty.proof,
+                counterparty.consensus_state.root(),
+                &packet.destination_port,
+                &packet.destination_channel,
+                seq,
+            )
+            .map_err(proof_verification_failed(
+                counterparty.connection_end.client_id(),
+                counterparty.height,
+                SeqRecvsPath(
+                    packet.destination_port.clone(),
+                    packet.destination_channel.clone(),
+                ),
+            ))
+    }
+
+    /// Verifies the proof that the counterparty chain received `packet` with `receipt`.
+    pub fn verify_packet_receipt(&self, packet: &Packet, receipt: &Receipt) -> Result<(), Error> {
+        let counterparty = &self.object;
+
+        self.client_state
+            .verify_packet_receipt(
+                self.ctx,
+                counterparty.height,
+                &counterparty.connection_end,
+                &counterparty.proof,
+                counterparty.consensus_state.root(),
+                &packet.destination_port,
+                &packet.destination_channel,
+                packet.sequence,
+                receipt,
+            )
+            .map_err(proof_verification_failed(
+                counterparty.connection_end.client_id(),
+                counterparty.height,
+                ReceiptsPath::new(
+                    packet.destination_port.clone(),
+                    packet.destination_channel.clone(),
+                    packet.sequence,
+                ),
+            ))
+    }
+
+    /// Verifies the proof that the counterparty chain did not receive `packet`.
+    pub fn verify_packet_receipt_absence(&self, packet: &Packet) -> Result<(), Error> {
+        let counterparty = &self.object;
+
+        // Verify the proof for the packet against the chain store.
+        self.client_state
+            .verify_packet_receipt_absence(
+                self.ctx,
+                counterparty.height,
+                &counterparty.connection_end,
+                &counterparty.proof,
+                counterparty.consensus_state.root(),
+                &packet.destination_port,
+                &packet.destination_channel,
+                packet.sequence,
+            )
+            .map_err(proof_verification_failed(
+                counterparty.connection_end.client_id(),
+                counterparty.height,
+                ReceiptsPath::new(
+                    packet.destination_port.clone(),
+                    packet.destination_channel.clone(),
+                    packet.sequence,
+                ),
+            ))
+    }
 }
 
-/// Entry point for verifying all proofs bundled in a ICS4 packet recv. message.
-pub fn verify_packet_recv_proofs<Ctx: ChannelReader>(
+/// Resolves the state of the counterparty chain of the channel routed through `hops`, against
+/// which `proof` is verified.
+///
+/// The `proof` of a key on the counterparty of a multihop channel is a [`MultihopProof`]: the
+/// connection end and the consensus state of the next chain are verified on every intermediate
+/// chain, each against the consensus state proven on the previous one, at its height. As the
+/// key proof is then verified against the consensus state of another chain than the one of the
+/// host client, the delay period of the hops is checked here, on the host client.
+fn resolve_counterparty<Ctx: ChannelReader>(
     ctx: &Ctx,
+    hops: &ConnectionHops,
+    client_state: &dyn ClientState,
+    consensus_state: &dyn ConsensusState,
     height: Height,
-    packet: &Packet,
-    connection_end: &ConnectionEnd,
-    proofs: &Proofs,
-) -> Result<(), Error> {
-    let client_id = connection_end.client_id();
-    let client_state = ctx.client_state(client_id)?;
+    proof: &CommitmentProofBytes,
+) -> Result<CounterpartyState, Error> {
+    let host_connection_end = hops.connection_end();
+    let client_id = host_connection_end.client_id();
 
-    // The client must not be frozen.
-    if client_state.is_frozen() {
-        return Err(Error::frozen_client(client_id.clone()));
+    let mut consensus_state = dyn_clone::clone_box(consensus_state);
+    if !hops.is_multihop() {
+        return Ok(CounterpartyState {
+            height,
+            consensus_state,
+            connection_end: host_connection_end.clone(),
+            proof: proof.clone(),
+        });
     }
 
-    let consensus_state = ctx.client_consensus_state(client_id, proofs.height())?;
+    let multihop_proof = MultihopProof::decode_proof(proof)?;
+    hops.check_remote_hops(&multihop_proof)?;
 
-    let commitment = ctx.packet_commitment(
-        packet.data.clone(),
-        packet.timeout_height,
-        packet.timeout_timestamp,
-    );
+    let mut counterparty_height = height;
+    let mut counterparty = host_connection_end.counterparty().clone();
+    let mut delay_period = host_connection_end.delay_period();
+    for (index, hop) in multihop_proof.hops.into_iter().enumerate() {
+        let connection_id = hop.connection_id;
+        if &hop.prefix != counterparty.prefix() {
+            return Err(Error::invalid_multihop_proof(format!(
+                "the proofs of connection {} are not of the chain of prefix {:?}",
+                connection_id,
+                counterparty.prefix()
+            )));
+        }
+        if !hop.connection_end.state_matches(&ConnectionState::Open) {
+            return Err(Error::connection_not_open(connection_id));
+        }
 
-    // Verify the proof for the packet against the chain store.
-    client_state
-        .verify_packet_data(
-            ctx,
-            height,
-            connection_end,
-            proofs.object_proof(),
-            consensus_state.root(),
-            &packet.source_port,
-            &packet.source_channel,
-            packet.sequence,
-            commitment,
-        )
-        .map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
+        client_state
+            .verify_connection_state(
+                counterparty_height,
+                counterparty.prefix(),
+                &hop.connection_proof,
+                consensus_state.root(),
+                &connection_id,
+                &hop.connection_end,
+            )
+            .map_err(proof_verification_failed(
+                client_id,
+                counterparty_height,
+                ConnectionsPath(connection_id.clone()),
+            ))?;
 
-    Ok(())
+        let next_consensus_state = client_state
+            .initialise(hop.consensus_state)
+            .map_err(|e| Error::verify_multihop_failed(index, e))?;
+        client_state
+            .verify_client_consensus_state(
+                counterparty_height,
+                counterparty.prefix(),
+                &hop.consensus_proof,
+                consensus_state.root(),
+                hop.connection_end.client_id(),
+                hop.consensus_height,
+                next_consensus_state.as_ref(),
+            )
+            .map_err(proof_verification_failed(
+                client_id,
+                counterparty_height,
+                ClientConsensusStatePath::new(
+                    hop.connection_end.client_id().clone(),
+                    hop.consensus_height,
+                ),
+            ))?;
+
+        counterparty_height = hop.consensus_height;
+        consensus_state = next_consensus_state;
+        counterparty = hop.connection_end.counterparty().clone();
+        delay_period = delay_period.max(hop.connection_end.delay_period());
+    }
+
+    verify_delay_passed(ctx, client_id, height, delay_period)?;
+
+    Ok(CounterpartyState {
+        height: counterparty_height,
+        consensus_state,
+        connection_end: ConnectionEnd::new(
+            *host_connection_end.state(),
+            client_id.clone(),
+            counterparty,
+            host_connection_end.versions().to_vec(),
+            Duration::ZERO,
+        ),
+        proof: multihop_proof.key_proof,
+    })
 }
 
-/// Entry point for verifying all proofs bundled in an ICS4 packet ack message.
-pub fn verify_packet_acknowledgement_proofs<Ctx: ChannelReader>(
+/// Verifies that `delay_period`, and the number of blocks it spans, elapsed on the host since
+/// the consensus state of the client `client_id` at `height` was processed.
+fn verify_delay_passed<Ctx: ChannelReader>(
     ctx: &Ctx,
+    client_id: &ClientId,
     height: Height,
-    packet: &Packet,
-    acknowledgement: Acknowledgement,
-    connection_end: &ConnectionEnd,
-    proofs: &Proofs,
+    delay_period: Duration,
 ) -> Result<(), Error> {
-    let client_id = connection_end.client_id();
-    let client_state = ctx.client_state(client_id)?;
-
-    // The client must not be frozen.
-    if client_state.is_frozen() {
-        return Err(Error::frozen_client(client_id.clone()));
+    if delay_period.is_zero() {
+        return Ok(());
     }
 
-    let consensus_state = ctx.client_consensus_state(client_id, proofs.height())?;
+    let processed_time = ctx.client_update_time(client_id, height)?;
+    let processed_height = ctx.client_update_height(client_id, height)?;
 
-    let ack_commitment = ctx.ack_commitment(acknowledgement);
+    let current_time = ctx.host_timestamp();
+    let earliest_time = (processed_time + delay_period)
+        .map_err(|_| Error::delay_period_not_elapsed(client_id.clone(), height))?;
+    if !(current_time == earliest_time || current_time.after(&earliest_time)) {
+        return Err(Error::delay_period_not_elapsed(client_id.clone(), height));
+    }
 
-    // Verify the proof for the packet against the chain store.
-    client_state
-        .verify_packet_acknowledgement(
-            ctx,
-            height,
-            connection_end,
-            proofs.object_proof(),
-            consensus_state.root(),
-            &packet.destination_port,
-            &packet.destination_channel,
-            packet.sequence,
-            ack_commitment,
-        )
-        .map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
+    let earliest_height = processed_height.add(ctx.block_delay(delay_period));
+    if ctx.host_height() < earliest_height {
+        return Err(Error::delay_period_not_elapsed(client_id.clone(), height));
+    }
 
     Ok(())
 }
 
-/// Entry point for verifying all timeout proofs.
-pub fn verify_next_sequence_recv<Ctx: ChannelReader>(
-    ctx: &Ctx,
-    height: Height,
+/// Verifies that `proof` proves, at `height`, the channel end `expected_chan` of `port_id` and
+/// `channel_id` on the counterparty of `connection_end`, against the client and consensus states
+/// of that counterparty.
+///
+/// Unlike [`ProofVerificationContext`], it takes no context, so that audit tools can check the
+/// channel end claimed by a counterparty against a header that they trust.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_channel_state(
+    client_state: &dyn ClientState,
+    consensus_state: &dyn ConsensusState,
     connection_end: &ConnectionEnd,
-    packet: Packet,
-    seq: Sequence,
-    proofs: &Proofs,
+    height: Height,
+    proof: &CommitmentProofBytes,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    expected_chan: &ChannelEnd,
 ) -> Result<(), Error> {
-    let client_id = connection_end.client_id();
-    let client_state = ctx.client_state(client_id)?;
-
     // The client must not be frozen.
     if client_state.is_frozen() {
-        return Err(Error::frozen_client(client_id.clone()));
+        return Err(Error::frozen_client(connection_end.client_id().clone()));
     }
 
-    let consensus_state = ctx.client_consensus_state(client_id, proofs.height())?;
-
-    // Verify the proof for the packet against the chain store.
+    // Verify the proof for the channel state against the expected channel end.
     client_state
-        .verify_next_sequence_recv(
-            ctx,
+        .verify_channel_state(
             height,
-            connection_end,
-            proofs.object_proof(),
+            connection_end.counterparty().prefix(),
+            proof,
             consensus_state.root(),
-            &packet.destination_port,
-            &packet.destination_channel,
-            packet.sequence,
+            port_id,
+            channel_id,
+            expected_chan,
         )
-        .map_err(|e| Error::packet_verification_failed(seq, e))?;
-
-    Ok(())
+        .map_err(proof_verification_failed(
+            connection_end.client_id(),
+            height,
+            ChannelEndsPath(port_id.clone(), channel_id.clone()),
+        ))
 }
 
-pub fn verify_packet_receipt_absence<Ctx: ChannelReader>(
-    ctx: &Ctx,
+/// Returns the error of the client `client_id` failing to verify the proof of `path` at `height`,
+/// which keeps where the verification failed along with why.
+fn proof_verification_failed(
+    client_id: &ClientId,
     height: Height,
-    connection_end: &ConnectionEnd,
-    packet: Packet,
-    proofs: &Proofs,
-) -> Result<(), Error> {
-    let client_id = connection_end.client_id();
-    let client_state = ctx.client_state(client_id)?;
+    path: impl Into<Path>,
+) -> impl FnOnce(client_error::Error) -> Error {
+    let client_id = client_id.clone();
+    let path = path.into().to_string();
+    move |e| Error::low_level_proof_verification(client_id, height, path, e)
+}
 
-    // The client must not be frozen.
-    if client_state.is_frozen() {
-        return Err(Error::frozen_client(client_id.clone()));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics02_client::context::ClientKeeper;
+    use crate::core::ics03_connection::connection::{
+        Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics04_channel::channel::{Counterparty, Order, State};
+    use crate::core::ics04_channel::error::ErrorDetail;
+    use crate::core::ics04_channel::multihop::test_util::get_dummy_multihop_proof;
+    use crate::core::ics04_channel::packet::test_utils::get_dummy_raw_packet;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+    use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
+    use crate::mock::client_state::MockClientState;
+    use crate::mock::consensus_state::MockConsensusState;
+    use crate::mock::context::MockContext;
+    use crate::mock::header::MockHeader;
+
+    #[test]
+    fn channel_state_is_verified_without_context() {
+        let height = Height::new(0, 10).unwrap();
+        let header = MockHeader::new(height);
+        let consensus_state = MockConsensusState::new(header);
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
+            ),
+            vec![],
+            Default::default(),
+        );
+        let expected_chan = ChannelEnd::new(
+            State::TryOpen,
+            Order::Unordered,
+            Counterparty::new(PortId::transfer(), Some(ChannelId::default())),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        let proof = vec![1].try_into().unwrap();
+
+        let verify = |client_state: &MockClientState| {
+            verify_channel_state(
+                client_state,
+                &consensus_state,
+                &connection_end,
+                height,
+                &proof,
+                &PortId::transfer(),
+                &ChannelId::default(),
+                &expected_chan,
+            )
+        };
+
+        let mut client_state = MockClientState::new(header);
+        assert!(verify(&client_state).is_ok());
+
+        client_state.frozen_height = Some(height);
+        assert!(verify(&client_state).is_err());
     }
 
-    let consensus_state = ctx.client_consensus_state(client_id, proofs.height())?;
+    #[test]
+    fn multihop_proofs_are_verified_through_every_hop() {
+        let proof_height = Height::new(0, 10).unwrap();
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
+            ),
+            vec![],
+            Default::default(),
+        );
+        let ctx = MockContext::default()
+            .with_client(&ClientId::default(), proof_height)
+            .with_connection(ConnectionId::default(), connection_end);
+        let counterparty_at = |hops: &ConnectionHops, proof: MultihopProof| {
+            let proofs = Proofs::new(proof.encode_proof()?, None, None, None, proof_height)
+                .map_err(Error::invalid_proof)?;
+            ProofVerificationContext::new(&ctx, hops, &proofs)?.counterparty_height_and_timestamp()
+        };
 
-    // Verify the proof for the packet against the chain store.
-    client_state
-        .verify_packet_receipt_absence(
-            ctx,
-            height,
-            connection_end,
-            proofs.object_proof(),
-            consensus_state.root(),
-            &packet.destination_port,
-            &packet.destination_channel,
-            packet.sequence,
-        )
-        .map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
+        // The object proof of a single-hop channel is verified against the host client.
+        let single_hop = ConnectionHops::resolve(&ctx, &[ConnectionId::default()]).unwrap();
+        let proof = get_dummy_multihop_proof(ConnectionId::new(1), ConnectionId::new(2), 3);
+        let (height, _) = counterparty_at(&single_hop, proof.clone()).unwrap();
+        assert_eq!(height, proof_height);
 
-    Ok(())
+        // The one of a multihop channel is verified against the consensus state proven by its
+        // last hop.
+        let multihop =
+            ConnectionHops::resolve(&ctx, &[ConnectionId::default(), ConnectionId::new(1)])
+                .unwrap();
+        let (height, timestamp) = counterparty_at(&multihop, proof.clone()).unwrap();
+        assert_eq!(height, Height::new(0, 3).unwrap());
+        let proven_consensus_state =
+            MockConsensusState::try_from(proof.hops[0].consensus_state.clone()).unwrap();
+        assert_eq!(timestamp, proven_consensus_state.timestamp());
+
+        // Every connection along the hops must be open.
+        let mut closed = proof.clone();
+        closed.hops[0]
+            .connection_end
+            .set_state(ConnectionState::Init);
+        assert!(counterparty_at(&multihop, closed).is_err());
+
+        // The delay period of every hop elapses on the host client, at the proof height.
+        let mut delayed = proof.clone();
+        delayed.hops[0].connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            delayed.hops[0].connection_end.client_id().clone(),
+            delayed.hops[0].connection_end.counterparty().clone(),
+            vec![],
+            Duration::from_secs(3600),
+        );
+        let err = counterparty_at(&multihop, delayed).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::ProcessedTimeNotFound(e) if e.height == proof_height
+        ));
+
+        // The proofs of a hop must be the ones of the chain the previous hop leads to.
+        let mut other_chain = proof;
+        other_chain.hops[0].prefix = CommitmentPrefix::try_from(b"other".to_vec()).unwrap();
+        assert!(counterparty_at(&multihop, other_chain).is_err());
+    }
+
+    #[test]
+    fn proofs_are_verified_against_a_single_client_read() {
+        let proof_height = Height::new(0, 10).unwrap();
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
+            ),
+            vec![],
+            Default::default(),
+        );
+        let mut ctx = MockContext::default()
+            .with_client(&ClientId::default(), proof_height)
+            .with_connection(ConnectionId::default(), connection_end);
+        let hops = ConnectionHops::resolve(&ctx, &[ConnectionId::default()]).unwrap();
+        let packet: Packet = get_dummy_raw_packet(10, 0).try_into().unwrap();
+        let proofs_at = |height: Height| {
+            Proofs::new(vec![1].try_into().unwrap(), None, None, None, height).unwrap()
+        };
+
+        // All the proofs of a timeout are verified through the same context.
+        let proofs = proofs_at(proof_height);
+        let verifier = ProofVerificationContext::new(&ctx, &hops, &proofs).unwrap();
+        assert_eq!(verifier.height(), proof_height);
+        assert_eq!(
+            verifier.counterparty_height_and_timestamp().unwrap(),
+            (proof_height, verifier.consensus_state().timestamp())
+        );
+        assert!(verifier.verify_packet_receipt_absence(&packet).is_ok());
+        assert!(verifier
+            .verify_next_sequence_recv(&packet, packet.sequence)
+            .is_ok());
+
+        // The proof that the counterparty channel is closed is carried as `other_proof`.
+        let expected_chan = ChannelEnd::new(
+            State::Closed,
+            Order::Unordered,
+            Counterparty::new(PortId::default(), Some(ChannelId::default())),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        assert!(verifier
+            .verify_channel_closed_proof(&packet, &expected_chan)
+            .is_err());
+
+        // Proofs beyond the latest height of the client are rejected upfront.
+        let proofs = proofs_at(proof_height.increment());
+        assert!(matches!(
+            ProofVerificationContext::new(&ctx, &hops, &proofs),
+            Err(e) if matches!(e.detail(), ErrorDetail::ProofHeightAfterLatestHeight(_))
+        ));
+
+        // There is no consensus state to verify proofs at other heights against.
+        let proofs = proofs_at(proof_height.decrement().unwrap());
+        assert!(ProofVerificationContext::new(&ctx, &hops, &proofs).is_err());
+
+        // Nor any proof to verify with a frozen client.
+        let mut client_state = MockClientState::new(MockHeader::new(proof_height));
+        client_state.frozen_height = Some(proof_height);
+        ctx.store_client_state(ClientId::default(), Box::new(client_state))
+            .unwrap();
+        let proofs = proofs_at(proof_height);
+        assert!(matches!(
+            ProofVerificationContext::new(&ctx, &hops, &proofs),
+            Err(e) if matches!(e.detail(), ErrorDetail::FrozenClient(_))
+        ));
+    }
 }
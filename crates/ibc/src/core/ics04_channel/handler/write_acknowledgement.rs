@@ -1,9 +1,11 @@
 use crate::core::ics04_channel::channel::State;
 use crate::core::ics04_channel::commitment::AcknowledgementCommitment;
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics04_channel::error::{Error, ErrorDetail};
 use crate::core::ics04_channel::events::WriteAcknowledgement;
 use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement;
 use crate::core::ics04_channel::packet::{Packet, PacketResult, Sequence};
-use crate::core::ics04_channel::{context::ChannelReader, error::Error};
+use crate::core::ics04_channel::pruning::track_packet_record;
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::prelude::*;
 use crate::{
@@ -76,6 +78,62 @@ pub fn process<Ctx: ChannelReader>(
     Ok(output.with_result(result))
 }
 
+/// Stores the commitment of the acknowledgement written by [`process`] and returns the log and the
+/// `WriteAcknowledgement` event, which the caller is responsible for emitting.
+pub(crate) fn emit_acknowledgement<Ctx>(
+    ctx: &mut Ctx,
+    packet: Packet,
+    ack: Acknowledgement,
+) -> HandlerResult<(), Error>
+where
+    Ctx: ChannelReader + ChannelKeeper,
+{
+    let HandlerOutput {
+        result,
+        log,
+        events,
+    } = process(ctx, packet, ack)?;
+
+    track_packet_record(ctx, &result)?;
+    ctx.store_packet_result(result)?;
+
+    Ok(HandlerOutput::builder()
+        .with_log(log)
+        .with_events(events)
+        .with_result(()))
+}
+
+/// Checks that `packet` was received on its channel end, before its acknowledgement is written
+/// outside of the `RecvPacket` flow.
+pub(crate) fn check_packet_received<Ctx: ChannelReader>(
+    ctx: &Ctx,
+    packet: &Packet,
+) -> Result<(), Error> {
+    let dest_channel_end =
+        ctx.channel_end(&packet.destination_port, &packet.destination_channel)?;
+
+    let received = if dest_channel_end.ordering().is_ordered() {
+        let next_seq_recv =
+            ctx.get_next_sequence_recv(&packet.destination_port, &packet.destination_channel)?;
+        packet.sequence < next_seq_recv
+    } else {
+        match ctx.get_packet_receipt(
+            &packet.destination_port,
+            &packet.destination_channel,
+            packet.sequence,
+        ) {
+            Ok(_) => true,
+            Err(e) if matches!(e.detail(), ErrorDetail::PacketReceiptNotFound(_)) => false,
+            Err(e) => return Err(e),
+        }
+    };
+    if !received {
+        return Err(Error::packet_not_received(packet.sequence));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
@@ -88,7 +146,11 @@ mod tests {
     use crate::core::ics03_connection::connection::State as ConnectionState;
     use crate::core::ics03_connection::version::get_compatible_versions;
     use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
-    use crate::core::ics04_channel::handler::write_acknowledgement::process;
+    use crate::core::ics04_channel::context::ChannelReader;
+    use crate::core::ics04_channel::error::ErrorDetail;
+    use crate::core::ics04_channel::handler::write_acknowledgement::{
+        emit_acknowledgement, process,
+    };
     use crate::core::ics04_channel::packet::test_utils::get_dummy_raw_packet;
     use crate::core::ics04_channel::Version;
     use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
@@ -209,4 +271,60 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn emit_acknowledgement_outside_recv_flow() {
+        let mut packet: Packet = get_dummy_raw_packet(1, 6).try_into().unwrap();
+        packet.data = vec![0];
+
+        let dest_channel_end = ChannelEnd::new(
+            State::Open,
+            Order::default(),
+            Counterparty::new(
+                packet.source_port.clone(),
+                Some(packet.source_channel.clone()),
+            ),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+
+        let mut ctx = MockContext::default()
+            .with_client(&ClientId::default(), Height::new(0, 1).unwrap())
+            .with_connection(ConnectionId::default(), connection_end)
+            .with_channel(
+                packet.destination_port.clone(),
+                packet.destination_channel.clone(),
+                dest_channel_end,
+            );
+
+        let output = emit_acknowledgement(&mut ctx, packet.clone(), vec![1].into()).unwrap();
+        assert!(matches!(
+            output.events.as_slice(),
+            [IbcEvent::WriteAcknowledgement(_)]
+        ));
+        assert!(ctx
+            .get_packet_acknowledgement(
+                &packet.destination_port,
+                &packet.destination_channel,
+                packet.sequence,
+            )
+            .is_ok());
+
+        let err = emit_acknowledgement(&mut ctx, packet, vec![1].into()).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::AcknowledgementExists(_)
+        ));
+    }
 }
@@ -0,0 +1,258 @@
+//! Optional expiry of abandoned channel handshakes.
+//!
+//! When a host keeps track of the time at which channel handshakes were started (see
+//! [`ChannelKeeper::store_channel_handshake_start`]), it can periodically call
+//! [`cleanup_stale_handshakes`] (e.g. at end-block) to remove the channel ends that are still in
+//! `INIT` or `TRYOPEN` state after the configured [`HandshakeExpiry`] window.
+
+use core::time::Duration;
+
+use tracing::warn;
+
+use crate::core::ics04_channel::channel::State;
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics04_channel::error::{Error, ErrorDetail};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
+use crate::timestamp::Timestamp;
+use crate::Height;
+
+/// Host height and time at which a channel handshake was started (i.e. the channel end was
+/// created by `ChanOpenInit` or `ChanOpenTry`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandshakeStart {
+    pub height: Height,
+    pub timestamp: Timestamp,
+}
+
+/// Window after which a pending channel handshake is considered abandoned.
+///
+/// A handshake is stale once *both* `max_blocks` blocks and `max_age` time have elapsed since it
+/// was started, mirroring how connection delays are enforced.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandshakeExpiry {
+    pub max_blocks: u64,
+    pub max_age: Duration,
+}
+
+impl HandshakeExpiry {
+    pub fn new(max_blocks: u64, max_age: Duration) -> Self {
+        Self {
+            max_blocks,
+            max_age,
+        }
+    }
+
+    /// Returns `true` if a handshake started at `start` has expired at the given host height and
+    /// time.
+    pub fn is_expired(
+        &self,
+        start: &HandshakeStart,
+        host_height: Height,
+        host_timestamp: Timestamp,
+    ) -> bool {
+        let height_expired = host_height.revision_number() > start.height.revision_number()
+            || host_height.revision_height()
+                >= start
+                    .height
+                    .revision_height()
+                    .saturating_add(self.max_blocks);
+
+        let time_expired = host_timestamp
+            .duration_since(&start.timestamp)
+            .map_or(false, |elapsed| elapsed >= self.max_age);
+
+        height_expired && time_expired
+    }
+}
+
+/// Records the start of a handshake for channel ends freshly created in `INIT` or `TRYOPEN`
+/// state, and forgets it once the channel end leaves these states.
+pub(crate) fn track_handshake<Ctx>(
+    ctx: &mut Ctx,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    state: &State,
+) -> Result<(), Error>
+where
+    Ctx: ChannelReader + ChannelKeeper,
+{
+    match state {
+        State::Init | State::TryOpen => {
+            let start = HandshakeStart {
+                height: ctx.host_height(),
+                timestamp: ctx.host_timestamp(),
+            };
+            ctx.store_channel_handshake_start(port_id.clone(), channel_id.clone(), start)
+        }
+        _ => ctx.delete_channel_handshake_start(port_id, channel_id),
+    }
+}
+
+/// Removes every channel end whose handshake was started more than `expiry` ago and that is still
+/// in `INIT` or `TRYOPEN` state. Returns the identifiers of the removed channel ends.
+///
+/// Handshake records of channels that made progress or are gone in the meantime are dropped as
+/// well. Stale handshakes are kept, with a warning, on hosts that do not support deleting channel
+/// ends (see [`ChannelReader::supports_channel_deletion`]).
+///
+/// The modules bound to the ports of the removed channel ends are not notified, as no channel
+/// closing callback applies to a channel that never opened: hosts whose modules keep state for
+/// pending channels should clean it up for the returned channel ends.
+pub fn cleanup_stale_handshakes<Ctx>(
+    ctx: &mut Ctx,
+    expiry: &HandshakeExpiry,
+) -> Result<Vec<(PortId, ChannelId)>, Error>
+where
+    Ctx: ChannelReader + ChannelKeeper,
+{
+    let host_height = ctx.host_height();
+    let host_timestamp = ctx.host_timestamp();
+    let mut removed = Vec::new();
+
+    for (port_id, channel_id, start) in ctx.pending_channel_handshakes()? {
+        let pending = match ctx.channel_end(&port_id, &channel_id) {
+            Ok(channel_end) => {
+                channel_end.state_matches(&State::Init)
+                    || channel_end.state_matches(&State::TryOpen)
+            }
+            Err(e) if matches!(e.detail(), ErrorDetail::ChannelNotFound(_)) => false,
+            Err(e) => return Err(e),
+        };
+
+        if !pending {
+            ctx.delete_channel_handshake_start(&port_id, &channel_id)?;
+            continue;
+        }
+
+        if expiry.is_expired(&start, host_height, host_timestamp) {
+            if !ctx.supports_channel_deletion() {
+                warn!(
+                    "stale handshake of channel {}/{} not cleaned up: the host does not support deleting channel ends",
+                    port_id, channel_id
+                );
+                continue;
+            }
+            ctx.delete_channel(&port_id, &channel_id)?;
+            ctx.delete_channel_handshake_start(&port_id, &channel_id)?;
+            removed.push((port_id, channel_id));
+        }
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics03_connection::version::Version as ConnectionVersion;
+    use crate::core::ics04_channel::msgs::chan_open_init::test_util::get_dummy_raw_msg_chan_open_init;
+    use crate::core::ics04_channel::msgs::chan_open_init::MsgChannelOpenInit;
+    use crate::core::ics04_channel::msgs::ChannelMsg;
+    use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+    use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
+    use crate::core::ics26_routing::context::{ModuleId, RouterBuilder};
+    use crate::core::ics26_routing::handler::dispatch;
+    use crate::core::ics26_routing::msgs::Ics26Envelope;
+    use crate::mock::context::{MockContext, MockRouterBuilder};
+    use crate::test_utils::DummyTransferModule;
+
+    fn ctx_with_pending_handshake() -> MockContext {
+        let module_id: ModuleId = "transfer".parse().unwrap();
+        let mut ctx = MockContext::default()
+            .with_client(&ClientId::default(), Height::new(0, 1).unwrap())
+            .with_connection(
+                ConnectionId::new(0),
+                ConnectionEnd::new(
+                    ConnectionState::Open,
+                    ClientId::default(),
+                    ConnectionCounterparty::new(
+                        ClientId::default(),
+                        Some(ConnectionId::new(0)),
+                        CommitmentPrefix::default(),
+                    ),
+                    vec![ConnectionVersion::default()],
+                    Duration::ZERO,
+                ),
+            );
+        let module = DummyTransferModule::new(ctx.ibc_store_share());
+        let router = MockRouterBuilder::default()
+            .add_route(module_id.clone(), module)
+            .unwrap()
+            .build();
+        ctx.scope_port_to_module(PortId::default(), module_id);
+        let mut ctx = ctx.with_router(router);
+
+        let msg = MsgChannelOpenInit::try_from(get_dummy_raw_msg_chan_open_init()).unwrap();
+        dispatch(
+            &mut ctx,
+            Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelOpenInit(msg)),
+        )
+        .unwrap();
+
+        ctx
+    }
+
+    #[test]
+    fn stale_handshake_is_removed() {
+        let mut ctx = ctx_with_pending_handshake();
+        let expiry = HandshakeExpiry::new(2, Duration::ZERO);
+
+        let pending = ctx.pending_channel_handshakes().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].2.height, ctx.host_height());
+
+        // Not expired yet
+        ctx.advance_host_chain_height();
+        assert!(cleanup_stale_handshakes(&mut ctx, &expiry)
+            .unwrap()
+            .is_empty());
+
+        ctx.advance_host_chain_height();
+        let removed = cleanup_stale_handshakes(&mut ctx, &expiry).unwrap();
+        assert_eq!(removed, vec![(PortId::default(), ChannelId::new(0))]);
+        assert!(ctx
+            .channel_end(&PortId::default(), &ChannelId::new(0))
+            .is_err());
+        assert!(ctx.pending_channel_handshakes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn progressed_handshake_is_kept() {
+        let mut ctx = ctx_with_pending_handshake();
+        let expiry = HandshakeExpiry::new(0, Duration::ZERO);
+
+        let mut channel_end = ctx
+            .channel_end(&PortId::default(), &ChannelId::new(0))
+            .unwrap();
+        channel_end.set_state(State::Open);
+        ctx.store_channel(PortId::default(), ChannelId::new(0), channel_end)
+            .unwrap();
+
+        assert!(cleanup_stale_handshakes(&mut ctx, &expiry)
+            .unwrap()
+            .is_empty());
+        assert!(ctx
+            .channel_end(&PortId::default(), &ChannelId::new(0))
+            .is_ok());
+        assert!(ctx.pending_channel_handshakes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn handshake_of_removed_channel_is_dropped() {
+        let mut ctx = ctx_with_pending_handshake();
+        let expiry = HandshakeExpiry::new(0, Duration::ZERO);
+
+        ctx.delete_channel(&PortId::default(), &ChannelId::new(0))
+            .unwrap();
+
+        assert!(cleanup_stale_handshakes(&mut ctx, &expiry)
+            .unwrap()
+            .is_empty());
+        assert!(ctx.pending_channel_handshakes().unwrap().is_empty());
+    }
+}
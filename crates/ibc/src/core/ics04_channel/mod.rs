@@ -2,15 +2,24 @@
 //! applications and the chains those applications are built upon.
 
 pub mod channel;
+pub mod connection_hops;
 pub mod context;
 pub mod error;
 pub mod events;
 
 pub mod handler;
+pub mod handshake;
 pub mod msgs;
+pub mod multihop;
 pub mod packet;
+pub mod pause;
+pub mod pruning;
+pub mod raw;
 pub mod timeout;
+pub mod timeout_close;
+#[cfg(any(test, feature = "unstable"))]
+pub mod upgrade;
 
 pub mod commitment;
 mod version;
-pub use version::Version;
+pub use version::{negotiate_version, verify_counterparty_version, Version};
@@ -1,6 +1,7 @@
 //! Message definitions for all ICS4 domain types: channel open & close handshake datagrams, as well
 //! as packets.
 
+use crate::core::context::ValidationContext;
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::msgs::acknowledgement::MsgAcknowledgement;
 use crate::core::ics04_channel::msgs::chan_close_confirm::MsgChannelCloseConfirm;
@@ -12,7 +13,17 @@ use crate::core::ics04_channel::msgs::chan_open_try::MsgChannelOpenTry;
 use crate::core::ics04_channel::msgs::recv_packet::MsgRecvPacket;
 use crate::core::ics04_channel::msgs::timeout::MsgTimeout;
 use crate::core::ics04_channel::msgs::timeout_on_close::MsgTimeoutOnClose;
-use crate::core::ics26_routing::context::{Ics26Context, ModuleId};
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::msgs::{
+    chan_upgrade_ack::MsgChannelUpgradeAck, chan_upgrade_cancel::MsgChannelUpgradeCancel,
+    chan_upgrade_confirm::MsgChannelUpgradeConfirm, chan_upgrade_init::MsgChannelUpgradeInit,
+    chan_upgrade_open::MsgChannelUpgradeOpen, chan_upgrade_timeout::MsgChannelUpgradeTimeout,
+    chan_upgrade_try::MsgChannelUpgradeTry,
+};
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::core::ics26_routing::context::ModuleId;
+use crate::signer::Signer;
 
 // Opening handshake messages.
 pub mod chan_open_ack;
@@ -24,6 +35,22 @@ pub mod chan_open_try;
 pub mod chan_close_confirm;
 pub mod chan_close_init;
 
+// Upgrade handshake messages.
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_ack;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_cancel;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_confirm;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_init;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_open;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_timeout;
+#[cfg(any(test, feature = "unstable"))]
+pub mod chan_upgrade_try;
+
 // Packet specific messages.
 pub mod acknowledgement;
 pub mod recv_packet;
@@ -42,7 +69,7 @@ pub enum ChannelMsg {
 }
 
 impl ChannelMsg {
-    pub(super) fn lookup_module(&self, ctx: &impl Ics26Context) -> Result<ModuleId, Error> {
+    pub(super) fn lookup_module(&self, ctx: &impl ValidationContext) -> Result<ModuleId, Error> {
         let module_id = match self {
             ChannelMsg::ChannelOpenInit(msg) => ctx
                 .lookup_module_by_port(&msg.port_id)
@@ -67,6 +94,63 @@ impl ChannelMsg {
     }
 }
 
+/// Enumeration of the messages of the channel upgrade handshake.
+#[cfg(any(test, feature = "unstable"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChannelUpgradeMsg {
+    ChannelUpgradeInit(MsgChannelUpgradeInit),
+    ChannelUpgradeTry(MsgChannelUpgradeTry),
+    ChannelUpgradeAck(MsgChannelUpgradeAck),
+    ChannelUpgradeConfirm(MsgChannelUpgradeConfirm),
+    ChannelUpgradeOpen(MsgChannelUpgradeOpen),
+    ChannelUpgradeTimeout(MsgChannelUpgradeTimeout),
+    ChannelUpgradeCancel(MsgChannelUpgradeCancel),
+}
+
+#[cfg(any(test, feature = "unstable"))]
+impl ChannelUpgradeMsg {
+    pub fn port_id(&self) -> &PortId {
+        match self {
+            ChannelUpgradeMsg::ChannelUpgradeInit(msg) => &msg.port_id,
+            ChannelUpgradeMsg::ChannelUpgradeTry(msg) => &msg.port_id,
+            ChannelUpgradeMsg::ChannelUpgradeAck(msg) => &msg.port_id,
+            ChannelUpgradeMsg::ChannelUpgradeConfirm(msg) => &msg.port_id,
+            ChannelUpgradeMsg::ChannelUpgradeOpen(msg) => &msg.port_id,
+            ChannelUpgradeMsg::ChannelUpgradeTimeout(msg) => &msg.port_id,
+            ChannelUpgradeMsg::ChannelUpgradeCancel(msg) => &msg.port_id,
+        }
+    }
+
+    pub fn channel_id(&self) -> &ChannelId {
+        match self {
+            ChannelUpgradeMsg::ChannelUpgradeInit(msg) => &msg.channel_id,
+            ChannelUpgradeMsg::ChannelUpgradeTry(msg) => &msg.channel_id,
+            ChannelUpgradeMsg::ChannelUpgradeAck(msg) => &msg.channel_id,
+            ChannelUpgradeMsg::ChannelUpgradeConfirm(msg) => &msg.channel_id,
+            ChannelUpgradeMsg::ChannelUpgradeOpen(msg) => &msg.channel_id,
+            ChannelUpgradeMsg::ChannelUpgradeTimeout(msg) => &msg.channel_id,
+            ChannelUpgradeMsg::ChannelUpgradeCancel(msg) => &msg.channel_id,
+        }
+    }
+
+    pub fn type_url(&self) -> &'static str {
+        match self {
+            ChannelUpgradeMsg::ChannelUpgradeInit(_) => chan_upgrade_init::TYPE_URL,
+            ChannelUpgradeMsg::ChannelUpgradeTry(_) => chan_upgrade_try::TYPE_URL,
+            ChannelUpgradeMsg::ChannelUpgradeAck(_) => chan_upgrade_ack::TYPE_URL,
+            ChannelUpgradeMsg::ChannelUpgradeConfirm(_) => chan_upgrade_confirm::TYPE_URL,
+            ChannelUpgradeMsg::ChannelUpgradeOpen(_) => chan_upgrade_open::TYPE_URL,
+            ChannelUpgradeMsg::ChannelUpgradeTimeout(_) => chan_upgrade_timeout::TYPE_URL,
+            ChannelUpgradeMsg::ChannelUpgradeCancel(_) => chan_upgrade_cancel::TYPE_URL,
+        }
+    }
+
+    pub(super) fn lookup_module(&self, ctx: &impl ValidationContext) -> Result<ModuleId, Error> {
+        ctx.lookup_module_by_port(self.port_id())
+            .map_err(Error::ics05_port)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PacketMsg {
     RecvPacket(MsgRecvPacket),
@@ -74,3 +158,15 @@ pub enum PacketMsg {
     TimeoutPacket(MsgTimeout),
     TimeoutOnClosePacket(MsgTimeoutOnClose),
 }
+
+impl PacketMsg {
+    /// The relayer submitting the packet message.
+    pub(crate) fn signer_mut(&mut self) -> &mut Signer {
+        match self {
+            PacketMsg::RecvPacket(msg) => &mut msg.signer,
+            PacketMsg::AckPacket(msg) => &mut msg.signer,
+            PacketMsg::TimeoutPacket(msg) => &mut msg.signer,
+            PacketMsg::TimeoutOnClosePacket(msg) => &mut msg.signer,
+        }
+    }
+}
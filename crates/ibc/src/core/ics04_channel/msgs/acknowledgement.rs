@@ -74,6 +74,14 @@ impl Msg for MsgAcknowledgement {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        self.packet.validate_basic()?;
+        if self.acknowledgement.as_ref().is_empty() {
+            return Err(Error::invalid_acknowledgement());
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgAcknowledgement> for MsgAcknowledgement {}
@@ -97,7 +105,7 @@ impl TryFrom<RawMsgAcknowledgement> for MsgAcknowledgement {
         )
         .map_err(Error::invalid_proof)?;
 
-        Ok(MsgAcknowledgement {
+        let msg = MsgAcknowledgement {
             packet: raw_msg
                 .packet
                 .ok_or_else(Error::missing_packet)?
@@ -105,7 +113,10 @@ impl TryFrom<RawMsgAcknowledgement> for MsgAcknowledgement {
             acknowledgement: raw_msg.acknowledgement.into(),
             signer: raw_msg.signer.parse().map_err(Error::signer)?,
             proofs,
-        })
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
@@ -121,7 +132,7 @@ impl From<MsgAcknowledgement> for RawMsgAcknowledgement {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use ibc_proto::ibc::core::channel::v1::MsgAcknowledgement as RawMsgAcknowledgement;
     use ibc_proto::ibc::core::channel::v1::Packet as RawPacket;
@@ -199,6 +210,14 @@ mod test {
                 },
                 want_pass: false,
             },
+            Test {
+                name: "Empty acknowledgement".to_string(),
+                raw: RawMsgAcknowledgement {
+                    acknowledgement: Vec::new(),
+                    ..default_raw_msg.clone()
+                },
+                want_pass: false,
+            },
             Test {
                 name: "Empty signer".to_string(),
                 raw: RawMsgAcknowledgement {
@@ -90,7 +90,7 @@ impl From<MsgChannelCloseConfirm> for RawMsgChannelCloseConfirm {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::prelude::*;
     use ibc_proto::ibc::core::channel::v1::MsgChannelCloseConfirm as RawMsgChannelCloseConfirm;
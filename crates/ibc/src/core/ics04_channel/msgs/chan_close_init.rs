@@ -68,7 +68,7 @@ impl From<MsgChannelCloseInit> for RawMsgChannelCloseInit {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::prelude::*;
     use ibc_proto::ibc::core::channel::v1::MsgChannelCloseInit as RawMsgChannelCloseInit;
@@ -85,7 +85,7 @@ impl From<MsgChannelOpenConfirm> for RawMsgChannelOpenConfirm {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::prelude::*;
     use ibc_proto::ibc::core::channel::v1::MsgChannelOpenConfirm as RawMsgChannelOpenConfirm;
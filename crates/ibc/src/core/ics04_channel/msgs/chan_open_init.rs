@@ -41,6 +41,10 @@ impl Msg for MsgChannelOpenInit {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        self.channel.validate_basic()
+    }
 }
 
 impl Protobuf<RawMsgChannelOpenInit> for MsgChannelOpenInit {}
@@ -49,14 +53,17 @@ impl TryFrom<RawMsgChannelOpenInit> for MsgChannelOpenInit {
     type Error = Error;
 
     fn try_from(raw_msg: RawMsgChannelOpenInit) -> Result<Self, Self::Error> {
-        Ok(MsgChannelOpenInit {
+        let msg = MsgChannelOpenInit {
             port_id: raw_msg.port_id.parse().map_err(Error::identifier)?,
             channel: raw_msg
                 .channel
                 .ok_or_else(Error::missing_channel)?
                 .try_into()?,
             signer: raw_msg.signer.parse().map_err(Error::signer)?,
-        })
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
@@ -70,7 +77,7 @@ impl From<MsgChannelOpenInit> for RawMsgChannelOpenInit {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::prelude::*;
     use ibc_proto::ibc::core::channel::v1::MsgChannelOpenInit as RawMsgChannelOpenInit;
@@ -1,7 +1,6 @@
 use crate::core::ics04_channel::channel::ChannelEnd;
 use crate::core::ics04_channel::error::Error as ChannelError;
 use crate::core::ics04_channel::Version;
-use crate::core::ics24_host::error::ValidationError;
 use crate::core::ics24_host::identifier::PortId;
 use crate::prelude::*;
 use crate::proofs::Proofs;
@@ -60,11 +59,12 @@ impl Msg for MsgChannelOpenTry {
         TYPE_URL.to_string()
     }
 
-    fn validate_basic(&self) -> Result<(), ValidationError> {
-        match self.channel.counterparty().channel_id() {
-            None => Err(ValidationError::invalid_counterparty_channel_id()),
-            Some(_c) => Ok(()),
+    fn validate_basic(&self) -> Result<(), ChannelError> {
+        self.channel.validate_basic()?;
+        if self.channel.counterparty().channel_id().is_none() {
+            return Err(ChannelError::invalid_counterparty_channel_id());
         }
+        Ok(())
     }
 }
 
@@ -101,8 +101,7 @@ impl TryFrom<RawMsgChannelOpenTry> for MsgChannelOpenTry {
             signer: raw_msg.signer.parse().map_err(ChannelError::signer)?,
         };
 
-        msg.validate_basic()
-            .map_err(|_| ChannelError::invalid_counterparty_channel_id())?;
+        msg.validate_basic()?;
 
         Ok(msg)
     }
@@ -122,7 +121,7 @@ impl From<MsgChannelOpenTry> for RawMsgChannelOpenTry {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::prelude::*;
     use ibc_proto::ibc::core::channel::v1::MsgChannelOpenTry as RawMsgChannelOpenTry;
@@ -0,0 +1,200 @@
+use crate::prelude::*;
+
+use ibc_proto::protobuf::Protobuf;
+
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::raw::MsgChannelUpgradeInit as RawMsgChannelUpgradeInit;
+use crate::core::ics04_channel::upgrade::UpgradeFields;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::signer::Signer;
+use crate::tx_msg::Msg;
+
+pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelUpgradeInit";
+
+///
+/// Message definition for the first step in the channel upgrade handshake (`ChanUpgradeInit`
+/// datagram), which proposes new fields for an open channel end.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgChannelUpgradeInit {
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub fields: UpgradeFields,
+    pub signer: Signer,
+}
+
+impl MsgChannelUpgradeInit {
+    pub fn new(
+        port_id: PortId,
+        channel_id: ChannelId,
+        fields: UpgradeFields,
+        signer: Signer,
+    ) -> Self {
+        Self {
+            port_id,
+            channel_id,
+            fields,
+            signer,
+        }
+    }
+}
+
+impl Msg for MsgChannelUpgradeInit {
+    type ValidationError = Error;
+    type Raw = RawMsgChannelUpgradeInit;
+
+    fn route(&self) -> String {
+        crate::keys::ROUTER_KEY.to_string()
+    }
+
+    fn type_url(&self) -> String {
+        TYPE_URL.to_string()
+    }
+}
+
+impl Protobuf<RawMsgChannelUpgradeInit> for MsgChannelUpgradeInit {}
+
+impl TryFrom<RawMsgChannelUpgradeInit> for MsgChannelUpgradeInit {
+    type Error = Error;
+
+    fn try_from(raw_msg: RawMsgChannelUpgradeInit) -> Result<Self, Self::Error> {
+        Ok(MsgChannelUpgradeInit {
+            port_id: raw_msg.port_id.parse().map_err(Error::identifier)?,
+            channel_id: raw_msg.channel_id.parse().map_err(Error::identifier)?,
+            fields: raw_msg
+                .fields
+                .ok_or_else(|| Error::missing_upgrade_field("fields".to_string()))?
+                .try_into()?,
+            signer: raw_msg.signer.parse().map_err(Error::signer)?,
+        })
+    }
+}
+
+impl From<MsgChannelUpgradeInit> for RawMsgChannelUpgradeInit {
+    fn from(domain_msg: MsgChannelUpgradeInit) -> Self {
+        RawMsgChannelUpgradeInit {
+            port_id: domain_msg.port_id.to_string(),
+            channel_id: domain_msg.channel_id.to_string(),
+            fields: Some(domain_msg.fields.into()),
+            signer: domain_msg.signer.to_string(),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "mocks"))]
+pub mod test_util {
+    use crate::prelude::*;
+
+    use crate::core::ics04_channel::raw::{
+        MsgChannelUpgradeInit as RawMsgChannelUpgradeInit, UpgradeFields as RawUpgradeFields,
+    };
+    use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+    use crate::test_utils::get_dummy_bech32_account;
+
+    /// Returns dummy `RawUpgradeFields`, for testing only!
+    pub fn get_dummy_raw_upgrade_fields() -> RawUpgradeFields {
+        RawUpgradeFields {
+            ordering: 2,
+            connection_hops: vec![ConnectionId::default().to_string()],
+            version: "ics20-2".to_string(),
+        }
+    }
+
+    /// Returns a dummy `RawMsgChannelUpgradeInit`, for testing only!
+    pub fn get_dummy_raw_msg_chan_upgrade_init() -> RawMsgChannelUpgradeInit {
+        RawMsgChannelUpgradeInit {
+            port_id: PortId::default().to_string(),
+            channel_id: ChannelId::default().to_string(),
+            fields: Some(get_dummy_raw_upgrade_fields()),
+            signer: get_dummy_bech32_account(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use test_log::test;
+
+    use crate::core::ics04_channel::msgs::chan_upgrade_init::test_util::{
+        get_dummy_raw_msg_chan_upgrade_init, get_dummy_raw_upgrade_fields,
+    };
+    use crate::core::ics04_channel::msgs::chan_upgrade_init::MsgChannelUpgradeInit;
+    use crate::core::ics04_channel::raw::{
+        MsgChannelUpgradeInit as RawMsgChannelUpgradeInit, UpgradeFields as RawUpgradeFields,
+    };
+
+    #[test]
+    fn parse_channel_upgrade_init_msg() {
+        let default_raw_msg = get_dummy_raw_msg_chan_upgrade_init();
+
+        let tests: Vec<(&str, RawMsgChannelUpgradeInit, bool)> = vec![
+            ("Good parameters", default_raw_msg.clone(), true),
+            (
+                "Missing upgrade fields",
+                RawMsgChannelUpgradeInit {
+                    fields: None,
+                    ..default_raw_msg.clone()
+                },
+                false,
+            ),
+            (
+                "Unspecified ordering",
+                RawMsgChannelUpgradeInit {
+                    fields: Some(RawUpgradeFields {
+                        ordering: 0,
+                        ..get_dummy_raw_upgrade_fields()
+                    }),
+                    ..default_raw_msg.clone()
+                },
+                false,
+            ),
+            (
+                "Two connection hops",
+                RawMsgChannelUpgradeInit {
+                    fields: Some(RawUpgradeFields {
+                        connection_hops: vec![
+                            "connection-1".to_string(),
+                            "connection-2".to_string(),
+                        ],
+                        ..get_dummy_raw_upgrade_fields()
+                    }),
+                    ..default_raw_msg.clone()
+                },
+                false,
+            ),
+            (
+                "Bad channel id",
+                RawMsgChannelUpgradeInit {
+                    channel_id: "chshort".to_string(),
+                    ..default_raw_msg
+                },
+                false,
+            ),
+        ];
+
+        for (name, raw, want_pass) in tests {
+            let res = MsgChannelUpgradeInit::try_from(raw.clone());
+
+            assert_eq!(
+                want_pass,
+                res.is_ok(),
+                "MsgChannelUpgradeInit::try_from failed for test {}, \nraw msg {:?} with error {:?}",
+                name,
+                raw,
+                res.err(),
+            );
+        }
+    }
+
+    #[test]
+    fn to_and_from() {
+        let raw = get_dummy_raw_msg_chan_upgrade_init();
+        let msg = MsgChannelUpgradeInit::try_from(raw.clone()).unwrap();
+        let raw_back = RawMsgChannelUpgradeInit::from(msg.clone());
+        let msg_back = MsgChannelUpgradeInit::try_from(raw_back.clone()).unwrap();
+        assert_eq!(raw, raw_back);
+        assert_eq!(msg, msg_back);
+    }
+}
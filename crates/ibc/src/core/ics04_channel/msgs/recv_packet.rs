@@ -43,6 +43,10 @@ impl Msg for MsgRecvPacket {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        self.packet.validate_basic()
+    }
 }
 
 impl Protobuf<RawMsgRecvPacket> for MsgRecvPacket {}
@@ -66,14 +70,17 @@ impl TryFrom<RawMsgRecvPacket> for MsgRecvPacket {
         )
         .map_err(Error::invalid_proof)?;
 
-        Ok(MsgRecvPacket {
+        let msg = MsgRecvPacket {
             packet: raw_msg
                 .packet
                 .ok_or_else(Error::missing_packet)?
                 .try_into()?,
             proofs,
             signer: raw_msg.signer.parse().map_err(Error::signer)?,
-        })
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
@@ -88,7 +95,7 @@ impl From<MsgRecvPacket> for RawMsgRecvPacket {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use ibc_proto::ibc::core::channel::v1::MsgRecvPacket as RawMsgRecvPacket;
     use ibc_proto::ibc::core::client::v1::Height as RawHeight;
@@ -4,6 +4,7 @@ use ibc_proto::protobuf::Protobuf;
 
 use ibc_proto::ibc::core::channel::v1::MsgTimeout as RawMsgTimeout;
 
+use crate::core::ics04_channel::channel::Order;
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::packet::{Packet, Sequence};
 use crate::proofs::Proofs;
@@ -18,6 +19,8 @@ pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgTimeout";
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MsgTimeout {
     pub packet: Packet,
+    /// The next sequence to receive of the counterparty channel end, as proven by the message on
+    /// ordered channels, see [`MsgTimeout::proven_next_sequence_recv`].
     pub next_sequence_recv: Sequence,
     pub proofs: Proofs,
     pub signer: Signer,
@@ -37,6 +40,20 @@ impl MsgTimeout {
             signer,
         }
     }
+
+    /// Returns the next sequence to receive of the counterparty channel end, if the channel is
+    /// ordered: the timeout of a packet is then proven by the counterparty not having received it
+    /// yet, i.e. by its next sequence to receive being at most the sequence of the packet.
+    ///
+    /// The field is meaningless on unordered channels, whose timeouts are proven by the absence of
+    /// the receipt of the packet, and on ordered-allow-timeout channels, whose timeouts are proven
+    /// by the timeout receipt written when the counterparty skipped the packet. Relayers usually
+    /// set it to the sequence of the packet there. Timeouts on close are proven differently on
+    /// ordered-allow-timeout channels, see
+    /// [`MsgTimeoutOnClose::proven_next_sequence_recv`](super::timeout_on_close::MsgTimeoutOnClose::proven_next_sequence_recv).
+    pub fn proven_next_sequence_recv(&self, ordering: &Order) -> Option<Sequence> {
+        matches!(ordering, Order::Ordered).then(|| self.next_sequence_recv)
+    }
 }
 
 impl Msg for MsgTimeout {
@@ -50,6 +67,25 @@ impl Msg for MsgTimeout {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        self.packet.validate_basic()?;
+        if self.next_sequence_recv.is_zero() {
+            return Err(Error::missing_next_recv_seq(
+                self.packet.destination_port.clone(),
+                self.packet.destination_channel.clone(),
+            ));
+        }
+        // Whatever the ordering of the channel, the next sequence to receive can only exceed the
+        // sequence of the packet if the packet was received, and thus did not time out.
+        if self.next_sequence_recv > self.packet.sequence {
+            return Err(Error::packet_already_received(
+                self.packet.sequence,
+                self.next_sequence_recv,
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgTimeout> for MsgTimeout {}
@@ -73,9 +109,7 @@ impl TryFrom<RawMsgTimeout> for MsgTimeout {
         )
         .map_err(Error::invalid_proof)?;
 
-        // TODO: Domain type verification for the next sequence: this should probably be > 0.
-
-        Ok(MsgTimeout {
+        let msg = MsgTimeout {
             packet: raw_msg
                 .packet
                 .ok_or_else(Error::missing_packet)?
@@ -83,7 +117,10 @@ impl TryFrom<RawMsgTimeout> for MsgTimeout {
             next_sequence_recv: Sequence::from(raw_msg.next_sequence_recv),
             signer: raw_msg.signer.parse().map_err(Error::signer)?,
             proofs,
-        })
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
@@ -99,7 +136,7 @@ impl From<MsgTimeout> for RawMsgTimeout {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use ibc_proto::ibc::core::channel::v1::MsgTimeout as RawMsgTimeout;
     use ibc_proto::ibc::core::client::v1::Height as RawHeight;
@@ -184,6 +221,22 @@ mod test {
                 },
                 want_pass: false,
             },
+            Test {
+                name: "Zero next sequence recv".to_string(),
+                raw: RawMsgTimeout {
+                    next_sequence_recv: 0,
+                    ..default_raw_msg.clone()
+                },
+                want_pass: false,
+            },
+            Test {
+                name: "Next sequence recv beyond the packet".to_string(),
+                raw: RawMsgTimeout {
+                    next_sequence_recv: 2,
+                    ..default_raw_msg.clone()
+                },
+                want_pass: false,
+            },
             Test {
                 name: "Empty signer".to_string(),
                 raw: RawMsgTimeout {
@@ -3,6 +3,7 @@ use crate::prelude::*;
 use ibc_proto::ibc::core::channel::v1::MsgTimeoutOnClose as RawMsgTimeoutOnClose;
 use ibc_proto::protobuf::Protobuf;
 
+use crate::core::ics04_channel::channel::Order;
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::packet::{Packet, Sequence};
 use crate::proofs::Proofs;
@@ -17,6 +18,8 @@ pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgTimeoutOnClose";
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MsgTimeoutOnClose {
     pub packet: Packet,
+    /// The next sequence to receive of the counterparty channel end, as proven by the message on
+    /// ordered channels, see [`MsgTimeoutOnClose::proven_next_sequence_recv`].
     pub next_sequence_recv: Sequence,
     pub proofs: Proofs,
     pub signer: Signer,
@@ -36,6 +39,21 @@ impl MsgTimeoutOnClose {
             signer,
         }
     }
+
+    /// Returns the next sequence to receive of the counterparty channel end, if the channel is
+    /// ordered or ordered-allow-timeout: the timeout of a packet is then proven by the
+    /// counterparty not having received it yet, i.e. by its next sequence to receive being at most
+    /// the sequence of the packet.
+    ///
+    /// Unlike [`MsgTimeout::proven_next_sequence_recv`](super::timeout::MsgTimeout::proven_next_sequence_recv),
+    /// this includes ordered-allow-timeout channels: a closed counterparty never writes the timeout
+    /// receipt of a packet it skipped, so only its next sequence to receive proves the timeout.
+    ///
+    /// The field is meaningless on unordered channels, whose timeouts are proven by the absence of
+    /// the receipt of the packet. Relayers usually set it to the sequence of the packet there.
+    pub fn proven_next_sequence_recv(&self, ordering: &Order) -> Option<Sequence> {
+        ordering.is_ordered().then(|| self.next_sequence_recv)
+    }
 }
 
 impl Msg for MsgTimeoutOnClose {
@@ -49,6 +67,25 @@ impl Msg for MsgTimeoutOnClose {
     fn type_url(&self) -> String {
         TYPE_URL.to_string()
     }
+
+    fn validate_basic(&self) -> Result<(), Error> {
+        self.packet.validate_basic()?;
+        if self.next_sequence_recv.is_zero() {
+            return Err(Error::missing_next_recv_seq(
+                self.packet.destination_port.clone(),
+                self.packet.destination_channel.clone(),
+            ));
+        }
+        // Whatever the ordering of the channel, the next sequence to receive can only exceed the
+        // sequence of the packet if the packet was received, and thus did not time out.
+        if self.next_sequence_recv > self.packet.sequence {
+            return Err(Error::packet_already_received(
+                self.packet.sequence,
+                self.next_sequence_recv,
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Protobuf<RawMsgTimeoutOnClose> for MsgTimeoutOnClose {}
@@ -77,9 +114,7 @@ impl TryFrom<RawMsgTimeoutOnClose> for MsgTimeoutOnClose {
         )
         .map_err(Error::invalid_proof)?;
 
-        // TODO: Domain type verification for the next sequence: this should probably be > 0.
-
-        Ok(MsgTimeoutOnClose {
+        let msg = MsgTimeoutOnClose {
             packet: raw_msg
                 .packet
                 .ok_or_else(Error::missing_packet)?
@@ -87,7 +122,10 @@ impl TryFrom<RawMsgTimeoutOnClose> for MsgTimeoutOnClose {
             next_sequence_recv: Sequence::from(raw_msg.next_sequence_recv),
             signer: raw_msg.signer.parse().map_err(Error::signer)?,
             proofs,
-        })
+        };
+        msg.validate_basic()?;
+
+        Ok(msg)
     }
 }
 
@@ -170,6 +208,22 @@ mod tests {
                 },
                 want_pass: false,
             },
+            Test {
+                name: "Zero next sequence recv".to_string(),
+                raw: RawMsgTimeoutOnClose {
+                    next_sequence_recv: 0,
+                    ..default_raw_msg.clone()
+                },
+                want_pass: false,
+            },
+            Test {
+                name: "Next sequence recv beyond the packet".to_string(),
+                raw: RawMsgTimeoutOnClose {
+                    next_sequence_recv: 2,
+                    ..default_raw_msg.clone()
+                },
+                want_pass: false,
+            },
             Test {
                 name: "Missing proof height".to_string(),
                 raw: RawMsgTimeoutOnClose {
@@ -195,7 +249,7 @@ mod tests {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use ibc_proto::ibc::core::channel::v1::MsgTimeoutOnClose as RawMsgTimeoutOnClose;
     use ibc_proto::ibc::core::client::v1::Height as RawHeight;
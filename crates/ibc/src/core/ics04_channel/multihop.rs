@@ -0,0 +1,321 @@
+//! Proofs of the state of the counterparty of a multihop channel, whose `connection_hops` go
+//! through intermediate chains between the host chain and the counterparty chain.
+
+use crate::prelude::*;
+
+use core::str::FromStr;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::core::commitment::v1::MerklePath;
+use ibc_proto::ibc::core::connection::v1::ConnectionEnd as RawConnectionEnd;
+use ibc_proto::protobuf::Protobuf;
+use prost::Message;
+
+use crate::core::ics03_connection::connection::ConnectionEnd;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::raw::{
+    MsgMultihopProofs as RawMsgMultihopProofs, MultihopProof as RawMultihopProof,
+};
+use crate::core::ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes};
+use crate::core::ics23_commitment::merkle::apply_prefix;
+use crate::core::ics24_host::identifier::ConnectionId;
+use crate::core::ics24_host::path::{ClientConsensusStatePath, ConnectionsPath, Path};
+use crate::Height;
+
+/// The proofs of the state of an intermediate chain of a multihop channel: the connection end
+/// towards the next chain of the path, and the consensus state of that chain, as stored by the
+/// client of the connection end.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HopProof {
+    /// The commitment prefix of the intermediate chain.
+    pub prefix: CommitmentPrefix,
+    pub connection_id: ConnectionId,
+    pub connection_end: ConnectionEnd,
+    pub connection_proof: CommitmentProofBytes,
+    pub consensus_height: Height,
+    pub consensus_state: Any,
+    pub consensus_proof: CommitmentProofBytes,
+}
+
+/// The proof of a key on the counterparty chain of a multihop channel, which is carried in place
+/// of the proof of a message.
+///
+/// The proofs of the intermediate chains are ordered from the host chain outwards: the first one
+/// is verified against the consensus state of the client of the host connection, each other one
+/// against the consensus state proven by the previous one, and `key_proof` against the consensus
+/// state proven by the last one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultihopProof {
+    pub hops: Vec<HopProof>,
+    pub key_proof: CommitmentProofBytes,
+}
+
+impl MultihopProof {
+    /// Decodes the multihop proof carried as the proof of a message.
+    pub fn decode_proof(proof: &CommitmentProofBytes) -> Result<Self, Error> {
+        Self::decode_vec(proof.as_bytes()).map_err(|e| Error::invalid_multihop_proof(e.to_string()))
+    }
+
+    /// Encodes the multihop proof, to be carried as the proof of a message.
+    pub fn encode_proof(self) -> Result<CommitmentProofBytes, Error> {
+        Protobuf::<RawMsgMultihopProofs>::encode_vec(&self)
+            .map_err(|e| Error::invalid_multihop_proof(e.to_string()))?
+            .try_into()
+            .map_err(Error::invalid_proof)
+    }
+}
+
+impl Protobuf<RawMsgMultihopProofs> for MultihopProof {}
+
+impl TryFrom<RawMsgMultihopProofs> for MultihopProof {
+    type Error = Error;
+
+    fn try_from(raw: RawMsgMultihopProofs) -> Result<Self, Self::Error> {
+        if raw.connection_proofs.len() != raw.consensus_proofs.len() {
+            return Err(Error::invalid_multihop_proof(format!(
+                "{} connection proofs for {} consensus proofs",
+                raw.connection_proofs.len(),
+                raw.consensus_proofs.len()
+            )));
+        }
+
+        let hops = raw
+            .connection_proofs
+            .into_iter()
+            .zip(raw.consensus_proofs)
+            .map(|(connection_proof, consensus_proof)| {
+                HopProof::try_from_raw(connection_proof, consensus_proof)
+            })
+            .collect::<Result<_, _>>()?;
+        let key_proof = raw
+            .key_proof
+            .ok_or_else(|| Error::invalid_multihop_proof("missing key proof".to_string()))?
+            .proof
+            .try_into()
+            .map_err(Error::invalid_proof)?;
+
+        Ok(Self { hops, key_proof })
+    }
+}
+
+impl From<MultihopProof> for RawMsgMultihopProofs {
+    fn from(value: MultihopProof) -> Self {
+        let (connection_proofs, consensus_proofs) =
+            value.hops.into_iter().map(HopProof::into_raw).unzip();
+
+        RawMsgMultihopProofs {
+            key_proof: Some(RawMultihopProof {
+                proof: value.key_proof.into(),
+                value: Vec::new(),
+                prefixed_key: None,
+            }),
+            connection_proofs,
+            consensus_proofs,
+        }
+    }
+}
+
+impl HopProof {
+    fn try_from_raw(
+        connection_proof: RawMultihopProof,
+        consensus_proof: RawMultihopProof,
+    ) -> Result<Self, Error> {
+        let (prefix, connection_path) = parse_prefixed_key(connection_proof.prefixed_key)?;
+        let connection_id = match connection_path {
+            Path::Connections(ConnectionsPath(connection_id)) => connection_id,
+            path => {
+                return Err(Error::invalid_multihop_proof(format!(
+                    "{} is not the key of a connection end",
+                    path
+                )))
+            }
+        };
+        let connection_end: ConnectionEnd =
+            RawConnectionEnd::decode(connection_proof.value.as_slice())
+                .map_err(|e| Error::invalid_multihop_proof(e.to_string()))?
+                .try_into()
+                .map_err(Error::ics03_connection)?;
+
+        let (consensus_prefix, consensus_path) = parse_prefixed_key(consensus_proof.prefixed_key)?;
+        if consensus_prefix != prefix {
+            return Err(Error::invalid_multihop_proof(format!(
+                "the connection and consensus proofs of connection {} are not of the same chain",
+                connection_id
+            )));
+        }
+        let consensus_height = match consensus_path {
+            Path::ClientConsensusState(ClientConsensusStatePath {
+                client_id,
+                epoch,
+                height,
+            }) if connection_end.client_id_matches(&client_id) => Height::new(epoch, height)
+                .map_err(|e| {
+                    Error::invalid_multihop_proof(format!("invalid consensus height: {}", e))
+                })?,
+            path => {
+                return Err(Error::invalid_multihop_proof(format!(
+                    "{} is not the key of a consensus state of the client of connection {}",
+                    path, connection_id
+                )))
+            }
+        };
+        let consensus_state = Any::decode(consensus_proof.value.as_slice())
+            .map_err(|e| Error::invalid_multihop_proof(e.to_string()))?;
+
+        Ok(Self {
+            prefix,
+            connection_id,
+            connection_end,
+            connection_proof: connection_proof
+                .proof
+                .try_into()
+                .map_err(Error::invalid_proof)?,
+            consensus_height,
+            consensus_state,
+            consensus_proof: consensus_proof
+                .proof
+                .try_into()
+                .map_err(Error::invalid_proof)?,
+        })
+    }
+
+    fn into_raw(self) -> (RawMultihopProof, RawMultihopProof) {
+        let connection_path = Path::Connections(ConnectionsPath(self.connection_id));
+        let consensus_path = Path::ClientConsensusState(ClientConsensusStatePath::new(
+            self.connection_end.client_id().clone(),
+            self.consensus_height,
+        ));
+
+        let connection_proof = RawMultihopProof {
+            proof: self.connection_proof.into(),
+            value: RawConnectionEnd::from(self.connection_end).encode_to_vec(),
+            prefixed_key: Some(apply_prefix(
+                &self.prefix,
+                vec![connection_path.to_string()],
+            )),
+        };
+        let consensus_proof = RawMultihopProof {
+            proof: self.consensus_proof.into(),
+            value: self.consensus_state.encode_to_vec(),
+            prefixed_key: Some(apply_prefix(&self.prefix, vec![consensus_path.to_string()])),
+        };
+
+        (connection_proof, consensus_proof)
+    }
+}
+
+/// Splits the key of a multihop proof into the commitment prefix of the chain storing it, and the
+/// path it is stored under.
+fn parse_prefixed_key(prefixed_key: Option<MerklePath>) -> Result<(CommitmentPrefix, Path), Error> {
+    let key_path = prefixed_key.map(|key| key.key_path).unwrap_or_default();
+    match key_path.as_slice() {
+        [prefix, path] => {
+            let prefix = CommitmentPrefix::try_from(prefix.as_bytes().to_vec())
+                .map_err(|e| Error::invalid_multihop_proof(e.to_string()))?;
+            let path =
+                Path::from_str(path).map_err(|e| Error::invalid_multihop_proof(e.to_string()))?;
+            Ok((prefix, path))
+        }
+        _ => Err(Error::invalid_multihop_proof(format!(
+            "invalid key {}",
+            key_path.join("/")
+        ))),
+    }
+}
+
+#[cfg(any(test, feature = "mocks"))]
+pub mod test_util {
+    use crate::prelude::*;
+
+    use core::time::Duration;
+
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics03_connection::version::get_compatible_versions;
+    use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+    use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
+    use crate::mock::consensus_state::MockConsensusState;
+    use crate::mock::header::MockHeader;
+    use crate::Height;
+
+    use super::{HopProof, MultihopProof};
+
+    /// Returns the proof of a key on the counterparty of a multihop channel, through one
+    /// intermediate chain whose connection `hop` leads to `counterparty_hop` on the counterparty
+    /// chain, whose state is proven at `height`.
+    pub fn get_dummy_multihop_proof(
+        hop: ConnectionId,
+        counterparty_hop: ConnectionId,
+        height: u64,
+    ) -> MultihopProof {
+        let height = Height::new(0, height).unwrap();
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(counterparty_hop),
+                CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
+            ),
+            get_compatible_versions(),
+            Duration::ZERO,
+        );
+
+        MultihopProof {
+            hops: vec![HopProof {
+                prefix: CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap(),
+                connection_id: hop,
+                connection_end,
+                connection_proof: vec![1].try_into().unwrap(),
+                consensus_height: height,
+                consensus_state: MockConsensusState::new(MockHeader::new(height)).into(),
+                consensus_proof: vec![1].try_into().unwrap(),
+            }],
+            key_proof: vec![1].try_into().unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_util::get_dummy_multihop_proof;
+
+    #[test]
+    fn multihop_proof_roundtrip() {
+        let proof = get_dummy_multihop_proof(ConnectionId::new(1), ConnectionId::new(2), 10);
+
+        let encoded = proof.clone().encode_proof().unwrap();
+        assert_eq!(MultihopProof::decode_proof(&encoded).unwrap(), proof);
+    }
+
+    #[test]
+    fn multihop_proof_rejects_inconsistent_hops() {
+        let proof = get_dummy_multihop_proof(ConnectionId::new(1), ConnectionId::new(2), 10);
+        let decode = |raw: RawMsgMultihopProofs| MultihopProof::try_from(raw);
+
+        let mut raw = RawMsgMultihopProofs::from(proof.clone());
+        raw.consensus_proofs.clear();
+        assert!(decode(raw).is_err());
+
+        // The keys of the proofs of a hop must be the ones of its connection end and consensus
+        // state, on the same chain.
+        let mut raw = RawMsgMultihopProofs::from(proof.clone());
+        raw.consensus_proofs[0].prefixed_key = raw.connection_proofs[0].prefixed_key.clone();
+        assert!(decode(raw).is_err());
+
+        let mut raw = RawMsgMultihopProofs::from(proof.clone());
+        raw.consensus_proofs[0]
+            .prefixed_key
+            .as_mut()
+            .unwrap()
+            .key_path[0] = "other".to_string();
+        assert!(decode(raw).is_err());
+
+        let mut raw = RawMsgMultihopProofs::from(proof);
+        raw.key_proof = None;
+        assert!(decode(raw).is_err());
+    }
+}
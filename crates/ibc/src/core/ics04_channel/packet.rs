@@ -12,6 +12,7 @@ use super::handler::{
 };
 use super::timeout::TimeoutHeight;
 use crate::core::ics04_channel::error::Error;
+use crate::core::ics24_host::error::ValidationError;
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::timestamp::{Expiry::Expired, Timestamp};
 use crate::Height;
@@ -33,11 +34,34 @@ pub enum PacketResult {
     WriteAck(WriteAckPacketResult),
     Ack(AckPacketResult),
     Timeout(TimeoutPacketResult),
+    /// The acknowledgement or timeout of a packet already settled, whose commitment is gone.
+    NoOp,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Receipt {
     Ok,
+    /// The sentinel receipt of a packet skipped because it timed out on an
+    /// `ORDER_ORDERED_ALLOW_TIMEOUT` channel, which proves its timeout to the sender.
+    Timeout,
+}
+
+impl Receipt {
+    /// The value stored under the receipt path of the packet, which proofs commit to.
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            Self::Ok => &[1],
+            Self::Timeout => &[2],
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [1] => Some(Self::Ok),
+            [2] => Some(Self::Timeout),
+            _ => None,
+        }
+    }
 }
 
 impl core::fmt::Display for PacketMsgType {
@@ -56,15 +80,24 @@ impl core::fmt::Display for PacketMsgType {
 #[derive(
     Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize,
 )]
-pub struct Sequence(u64);
+pub struct Sequence(#[serde(with = "crate::serializers::serde_u64")] u64);
 
+/// Parses a sequence from its canonical decimal form, i.e. the exact output of its `Display`
+/// implementation. Leading zeros and signs are rejected.
 impl FromStr for Sequence {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self::from(s.parse::<u64>().map_err(|e| {
-            Error::invalid_string_as_sequence(s.to_string(), e)
-        })?))
+        let sequence = Self::from(
+            s.parse::<u64>()
+                .map_err(|e| Error::invalid_string_as_sequence(s.to_string(), e))?,
+        );
+
+        if sequence.to_string() != s {
+            return Err(Error::non_canonical_sequence(s.to_string()));
+        }
+
+        Ok(sequence)
     }
 }
 
@@ -96,6 +129,30 @@ impl core::fmt::Display for Sequence {
     }
 }
 
+/// Identifies a packet by the port and channel it was sent on and its sequence.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct PacketId {
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub sequence: Sequence,
+}
+
+impl PacketId {
+    pub fn new(port_id: PortId, channel_id: ChannelId, sequence: Sequence) -> Self {
+        Self {
+            port_id,
+            channel_id,
+            sequence,
+        }
+    }
+}
+
+impl core::fmt::Display for PacketId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}/{}/{}", self.port_id, self.channel_id, self.sequence)
+    }
+}
+
 #[derive(Clone, Default, Hash, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Packet {
     pub sequence: Sequence,
@@ -150,6 +207,19 @@ impl core::fmt::Debug for Packet {
 }
 
 impl Packet {
+    pub fn builder() -> PacketBuilder {
+        PacketBuilder::default()
+    }
+
+    /// Returns the identifier of the packet, as sent on its source channel.
+    pub fn id(&self) -> PacketId {
+        PacketId::new(
+            self.source_port.clone(),
+            self.source_channel.clone(),
+            self.sequence,
+        )
+    }
+
     /// Checks whether a packet from a
     /// [`SendPacket`](crate::core::ics04_channel::events::SendPacket)
     /// event is timed-out relative to the current state of the
@@ -171,6 +241,92 @@ impl Packet {
 
         height_timed_out || timestamp_timed_out
     }
+
+    /// Performs the stateless checks on this packet, which are shared by all packet messages.
+    pub fn validate_basic(&self) -> Result<(), Error> {
+        if self.sequence.is_zero() {
+            return Err(Error::zero_packet_sequence());
+        }
+        if self.data.is_empty() {
+            return Err(Error::zero_packet_data());
+        }
+        Ok(())
+    }
+}
+
+/// Builds a [`Packet`], checking that all of its fields are set and valid.
+///
+/// The timeouts default to none, as for [`Packet::default`].
+#[derive(Clone, Debug, Default)]
+pub struct PacketBuilder {
+    sequence: Sequence,
+    source: Option<(PortId, ChannelId)>,
+    destination: Option<(PortId, ChannelId)>,
+    data: Vec<u8>,
+    timeout_height: TimeoutHeight,
+    timeout_timestamp: Timestamp,
+}
+
+impl PacketBuilder {
+    pub fn sequence(self, sequence: Sequence) -> Self {
+        Self { sequence, ..self }
+    }
+
+    pub fn source(self, port_id: PortId, channel_id: ChannelId) -> Self {
+        Self {
+            source: Some((port_id, channel_id)),
+            ..self
+        }
+    }
+
+    pub fn destination(self, port_id: PortId, channel_id: ChannelId) -> Self {
+        Self {
+            destination: Some((port_id, channel_id)),
+            ..self
+        }
+    }
+
+    pub fn data(self, data: Vec<u8>) -> Self {
+        Self { data, ..self }
+    }
+
+    pub fn timeout_height(self, timeout_height: TimeoutHeight) -> Self {
+        Self {
+            timeout_height,
+            ..self
+        }
+    }
+
+    pub fn timeout_timestamp(self, timeout_timestamp: Timestamp) -> Self {
+        Self {
+            timeout_timestamp,
+            ..self
+        }
+    }
+
+    /// Returns the packet, or the first missing or invalid field.
+    pub fn build(self) -> Result<Packet, Error> {
+        let (source_port, source_channel) = self
+            .source
+            .ok_or_else(|| Error::missing_packet_field("source".to_string()))?;
+        let (destination_port, destination_channel) = self
+            .destination
+            .ok_or_else(|| Error::missing_packet_field("destination".to_string()))?;
+
+        let packet = Packet {
+            sequence: self.sequence,
+            source_port,
+            source_channel,
+            destination_port,
+            destination_channel,
+            data: self.data,
+            timeout_height: self.timeout_height,
+            timeout_timestamp: self.timeout_timestamp,
+        };
+        packet.validate_basic()?;
+
+        Ok(packet)
+    }
 }
 
 /// Custom debug output to omit the packet data
@@ -194,10 +350,6 @@ impl TryFrom<RawPacket> for Packet {
     type Error = Error;
 
     fn try_from(raw_pkt: RawPacket) -> Result<Self, Self::Error> {
-        if Sequence::from(raw_pkt.sequence).is_zero() {
-            return Err(Error::zero_packet_sequence());
-        }
-
         // Note: ibc-go currently (July 2022) incorrectly treats the timeout
         // heights `{revision_number : >0, revision_height: 0}` as valid
         // timeouts. However, heights with `revision_height == 0` are invalid in
@@ -211,29 +363,29 @@ impl TryFrom<RawPacket> for Packet {
             .try_into()
             .map_err(|_| Error::invalid_timeout_height())?;
 
-        if raw_pkt.data.is_empty() {
-            return Err(Error::zero_packet_data());
-        }
-
         let timeout_timestamp = Timestamp::from_nanoseconds(raw_pkt.timeout_timestamp)
             .map_err(Error::invalid_packet_timestamp)?;
 
-        Ok(Packet {
-            sequence: Sequence::from(raw_pkt.sequence),
-            source_port: raw_pkt.source_port.parse().map_err(Error::identifier)?,
-            source_channel: raw_pkt.source_channel.parse().map_err(Error::identifier)?,
-            destination_port: raw_pkt
-                .destination_port
+        fn parse<T: FromStr<Err = ValidationError>>(field: &str, value: &str) -> Result<T, Error> {
+            value
                 .parse()
-                .map_err(Error::identifier)?,
-            destination_channel: raw_pkt
-                .destination_channel
-                .parse()
-                .map_err(Error::identifier)?,
-            data: raw_pkt.data,
-            timeout_height: packet_timeout_height,
-            timeout_timestamp,
-        })
+                .map_err(|e| Error::invalid_packet_field(field.to_string(), e))
+        }
+
+        Packet::builder()
+            .sequence(raw_pkt.sequence.into())
+            .source(
+                parse("source_port", &raw_pkt.source_port)?,
+                parse("source_channel", &raw_pkt.source_channel)?,
+            )
+            .destination(
+                parse("destination_port", &raw_pkt.destination_port)?,
+                parse("destination_channel", &raw_pkt.destination_channel)?,
+            )
+            .data(raw_pkt.data)
+            .timeout_height(packet_timeout_height)
+            .timeout_timestamp(timeout_timestamp)
+            .build()
     }
 }
 
@@ -252,7 +404,7 @@ impl From<Packet> for RawPacket {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_utils {
     use crate::prelude::*;
     use ibc_proto::ibc::core::channel::v1::Packet as RawPacket;
@@ -287,8 +439,24 @@ mod tests {
     use ibc_proto::ibc::core::channel::v1::Packet as RawPacket;
     use ibc_proto::ibc::core::client::v1::Height as RawHeight;
 
+    use crate::core::ics04_channel::error::ErrorDetail;
     use crate::core::ics04_channel::packet::test_utils::get_dummy_raw_packet;
-    use crate::core::ics04_channel::packet::Packet;
+    use crate::core::ics04_channel::packet::{Packet, Sequence};
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::test_utils::Xorshift;
+
+    #[test]
+    fn sequence_display_round_trips() {
+        let mut rng = Xorshift::new(0x5e9);
+        for _ in 0..1000 {
+            let sequence = Sequence::from(rng.next_u64());
+            assert_eq!(sequence.to_string().parse::<Sequence>().unwrap(), sequence);
+        }
+
+        for s in ["", "-1", "+1", "01", " 1"] {
+            assert!(s.parse::<Sequence>().is_err(), "{:?} must not parse", s);
+        }
+    }
 
     #[test]
     fn packet_try_from_raw() {
@@ -462,4 +630,51 @@ mod tests {
         assert_eq!(raw, raw_back);
         assert_eq!(msg, msg_back);
     }
+
+    #[test]
+    fn packet_try_from_raw_names_invalid_field() {
+        let raw = RawPacket {
+            destination_channel: "channel".to_string(),
+            ..get_dummy_raw_packet(10, 0)
+        };
+
+        match Packet::try_from(raw).unwrap_err().into_detail() {
+            ErrorDetail::InvalidPacketField(e) => assert_eq!(e.field, "destination_channel"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn packet_builder() {
+        let builder = Packet::builder()
+            .sequence(1.into())
+            .source(PortId::transfer(), ChannelId::new(0))
+            .data(vec![1]);
+
+        match builder.clone().build().unwrap_err().into_detail() {
+            ErrorDetail::MissingPacketField(e) => assert_eq!(e.field, "destination"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let builder = builder.destination(PortId::transfer(), ChannelId::new(1));
+        let packet = builder.clone().build().unwrap();
+        assert_eq!(
+            Packet::try_from(RawPacket::from(packet.clone())).unwrap(),
+            packet
+        );
+
+        assert!(matches!(
+            builder
+                .clone()
+                .sequence(0.into())
+                .build()
+                .unwrap_err()
+                .into_detail(),
+            ErrorDetail::ZeroPacketSequence(_)
+        ));
+        assert!(matches!(
+            builder.data(vec![]).build().unwrap_err().into_detail(),
+            ErrorDetail::ZeroPacketData(_)
+        ));
+    }
 }
@@ -0,0 +1,169 @@
+//! Pausing and resuming individual channels, e.g. through governance.
+//!
+//! A paused channel neither sends nor receives packets: `send_packet` and `MsgRecvPacket` fail on
+//! it until it is resumed. Unlike freezing its client, this only halts the channel, and unlike
+//! closing it, this is reversible. The packets already in flight are still acknowledged or timed
+//! out, so that e.g. the tokens they carry are refunded.
+//!
+//! Hosts that support it record the paused channels (see
+//! [`ChannelKeeper::store_channel_paused`]) and call [`pause_channel`] and [`resume_channel`],
+//! usually through [`ExecutionContext`](crate::core::context::ExecutionContext), which also
+//! publishes the emitted events.
+
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::{ChannelPaused, ChannelResumed};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::events::IbcEvent;
+use crate::handler::{HandlerOutput, HandlerResult};
+use crate::prelude::*;
+
+/// Pauses an existing channel, emitting a `ChannelPaused` event. Fails if it is already paused.
+pub fn pause_channel<Ctx>(
+    ctx: &mut Ctx,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> HandlerResult<(), Error>
+where
+    Ctx: ChannelReader + ChannelKeeper + ?Sized,
+{
+    let channel_end = ctx.channel_end(port_id, channel_id)?;
+    if ctx.is_channel_paused(port_id, channel_id)? {
+        return Err(Error::channel_paused(port_id.clone(), channel_id.clone()));
+    }
+
+    ctx.store_channel_paused(port_id.clone(), channel_id.clone(), true)?;
+
+    let mut output = HandlerOutput::builder();
+    output.log(format!("channel {}/{} paused", port_id, channel_id));
+    output.emit(IbcEvent::ChannelPaused(ChannelPaused::new(
+        port_id.clone(),
+        channel_id.clone(),
+        channel_end.connection_hops()[0].clone(),
+    )));
+
+    Ok(output.with_result(()))
+}
+
+/// Resumes a paused channel, emitting a `ChannelResumed` event. Fails if it is not paused.
+pub fn resume_channel<Ctx>(
+    ctx: &mut Ctx,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> HandlerResult<(), Error>
+where
+    Ctx: ChannelReader + ChannelKeeper + ?Sized,
+{
+    let channel_end = ctx.channel_end(port_id, channel_id)?;
+    if !ctx.is_channel_paused(port_id, channel_id)? {
+        return Err(Error::channel_not_paused(
+            port_id.clone(),
+            channel_id.clone(),
+        ));
+    }
+
+    ctx.store_channel_paused(port_id.clone(), channel_id.clone(), false)?;
+
+    let mut output = HandlerOutput::builder();
+    output.log(format!("channel {}/{} resumed", port_id, channel_id));
+    output.emit(IbcEvent::ChannelResumed(ChannelResumed::new(
+        port_id.clone(),
+        channel_id.clone(),
+        channel_end.connection_hops()[0].clone(),
+    )));
+
+    Ok(output.with_result(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::core::context::ExecutionContext;
+    use crate::core::ics02_client::height::Height;
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics03_connection::version::get_compatible_versions;
+    use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+    use crate::core::ics04_channel::error::ErrorDetail;
+    use crate::core::ics04_channel::handler::send_packet::send_packet;
+    use crate::core::ics04_channel::packet::test_utils::get_dummy_raw_packet;
+    use crate::core::ics04_channel::packet::Packet;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
+    use crate::mock::context::MockContext;
+    use crate::timestamp::ZERO_DURATION;
+
+    fn context() -> MockContext {
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            ClientId::default(),
+            ConnectionCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        let channel_end = ChannelEnd::new(
+            State::Open,
+            Order::Unordered,
+            Counterparty::new(PortId::default(), Some(ChannelId::default())),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+
+        MockContext::default()
+            .with_client(&ClientId::default(), Height::new(0, 5).unwrap())
+            .with_connection(ConnectionId::default(), connection_end)
+            .with_channel(PortId::default(), ChannelId::default(), channel_end)
+            .with_send_sequence(PortId::default(), ChannelId::default(), 1.into())
+    }
+
+    #[test]
+    fn paused_channel_does_not_send_packets() {
+        let mut ctx = context();
+        let (port_id, channel_id) = (PortId::default(), ChannelId::default());
+        let mut packet: Packet = get_dummy_raw_packet(10, 0).try_into().unwrap();
+        packet.sequence = 1.into();
+
+        let output = ctx.pause_channel(&port_id, &channel_id).unwrap();
+        assert!(matches!(
+            &output.events[..],
+            [IbcEvent::ChannelPaused(e)] if e.connection_id() == &ConnectionId::default()
+        ));
+        assert!(ctx.is_channel_paused(&port_id, &channel_id).unwrap());
+        assert!(matches!(
+            ctx.pause_channel(&port_id, &channel_id).unwrap_err().0,
+            ErrorDetail::ChannelPaused(_)
+        ));
+        assert!(matches!(
+            send_packet(&ctx, packet.clone()).unwrap_err().0,
+            ErrorDetail::ChannelPaused(_)
+        ));
+
+        let output = ctx.resume_channel(&port_id, &channel_id).unwrap();
+        assert!(matches!(&output.events[..], [IbcEvent::ChannelResumed(_)]));
+        assert!(!ctx.is_channel_paused(&port_id, &channel_id).unwrap());
+        assert!(matches!(
+            ctx.resume_channel(&port_id, &channel_id).unwrap_err().0,
+            ErrorDetail::ChannelNotPaused(_)
+        ));
+        assert!(send_packet(&ctx, packet).is_ok());
+    }
+
+    #[test]
+    fn unknown_channel_is_not_paused() {
+        let mut ctx = context();
+        let channel_id = ChannelId::new(7);
+
+        assert!(ctx.pause_channel(&PortId::default(), &channel_id).is_err());
+        assert!(!ctx
+            .is_channel_paused(&PortId::default(), &channel_id)
+            .unwrap());
+    }
+}
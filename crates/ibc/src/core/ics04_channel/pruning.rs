@@ -0,0 +1,313 @@
+//! Optional pruning of old packet acknowledgements.
+//!
+//! Acknowledgement commitments are otherwise kept forever. When a host keeps track of the height
+//! and time at which they were written (see [`ChannelKeeper::store_packet_record`]), it can
+//! periodically call [`prune_packet_records`] (e.g. at end-block) to delete the ones older than the
+//! configured [`PruningWindow`], once they were handed over to an archival callback.
+//!
+//! Packet receipts are never pruned, as ibc-go does: without its receipt, a packet received on an
+//! unordered channel could be received again, or its timeout proven by the sender.
+
+use core::time::Duration;
+
+use crate::core::ics04_channel::commitment::AcknowledgementCommitment;
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::packet::{PacketResult, Sequence};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
+use crate::timestamp::Timestamp;
+use crate::Height;
+
+/// An acknowledgement commitment, with the host height and time at which it was written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PacketRecord {
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub sequence: Sequence,
+    pub height: Height,
+    pub timestamp: Timestamp,
+}
+
+/// Window within which packet records are kept.
+///
+/// A record is prunable once *both* `min_blocks` blocks and `min_age` time have elapsed since it
+/// was written, like for the expiry of channel handshakes. The window is widened to the trusting
+/// period of the counterparty (see [`ChannelReader::counterparty_trusting_period`]), during which
+/// the counterparty may still verify proofs of the record at the height it was written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PruningWindow {
+    pub min_blocks: u64,
+    pub min_age: Duration,
+}
+
+impl PruningWindow {
+    pub fn new(min_blocks: u64, min_age: Duration) -> Self {
+        Self {
+            min_blocks,
+            min_age,
+        }
+    }
+
+    /// Returns `true` if `record` is out of the window at the given host height and time, given
+    /// the trusting period of the counterparty of its channel.
+    pub fn is_prunable(
+        &self,
+        record: &PacketRecord,
+        host_height: Height,
+        host_timestamp: Timestamp,
+        trusting_period: Option<Duration>,
+    ) -> bool {
+        let height_elapsed = host_height.revision_number() > record.height.revision_number()
+            || host_height.revision_height()
+                >= record
+                    .height
+                    .revision_height()
+                    .saturating_add(self.min_blocks);
+
+        let min_age = trusting_period.map_or(self.min_age, |period| period.max(self.min_age));
+        let time_elapsed = host_timestamp
+            .duration_since(&record.timestamp)
+            .map_or(false, |elapsed| elapsed >= min_age);
+
+        height_elapsed && time_elapsed
+    }
+}
+
+/// Records the height and time at which the acknowledgement commitment of a packet result is
+/// written, if any.
+pub(crate) fn track_packet_record<Ctx>(ctx: &mut Ctx, result: &PacketResult) -> Result<(), Error>
+where
+    Ctx: ChannelReader + ChannelKeeper,
+{
+    let res = match result {
+        PacketResult::WriteAck(res) => res,
+        _ => return Ok(()),
+    };
+
+    let record = PacketRecord {
+        port_id: res.port_id.clone(),
+        channel_id: res.channel_id.clone(),
+        sequence: res.seq,
+        height: ctx.host_height(),
+        timestamp: ctx.host_timestamp(),
+    };
+    ctx.store_packet_record(record)
+}
+
+/// Deletes every acknowledgement commitment written out of `window`, after handing it over to
+/// `archive`. Returns the pruned records.
+///
+/// A record is kept if `archive` fails, which aborts the pruning. Records whose acknowledgement
+/// commitment is gone in the meantime are dropped.
+pub fn prune_packet_records<Ctx, F>(
+    ctx: &mut Ctx,
+    window: &PruningWindow,
+    mut archive: F,
+) -> Result<Vec<PacketRecord>, Error>
+where
+    Ctx: ChannelReader + ChannelKeeper,
+    F: FnMut(&PacketRecord, AcknowledgementCommitment) -> Result<(), Error>,
+{
+    let host_height = ctx.host_height();
+    let host_timestamp = ctx.host_timestamp();
+    let mut pruned = Vec::new();
+
+    for record in ctx.packet_records()? {
+        let trusting_period =
+            ctx.counterparty_trusting_period(&record.port_id, &record.channel_id)?;
+        if !window.is_prunable(&record, host_height, host_timestamp, trusting_period) {
+            continue;
+        }
+
+        let (port_id, channel_id, sequence) =
+            (&record.port_id, &record.channel_id, record.sequence);
+        let ack_commitment = match ctx.get_packet_acknowledgement(port_id, channel_id, sequence) {
+            Ok(ack_commitment) => ack_commitment,
+            Err(_) => {
+                ctx.delete_packet_record(&record)?;
+                continue;
+            }
+        };
+
+        archive(&record, ack_commitment)?;
+        ctx.delete_packet_acknowledgement(port_id, channel_id, sequence)?;
+        ctx.delete_packet_record(&record)?;
+        pruned.push(record);
+    }
+
+    Ok(pruned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics03_connection::version::get_compatible_versions;
+    use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+    use crate::core::ics04_channel::handler::recv_packet::{self, RecvPacketResult};
+    use crate::core::ics04_channel::handler::write_acknowledgement::emit_acknowledgement;
+    use crate::core::ics04_channel::msgs::recv_packet::test_util::get_dummy_raw_msg_recv_packet;
+    use crate::core::ics04_channel::msgs::recv_packet::MsgRecvPacket;
+    use crate::core::ics04_channel::packet::test_utils::get_dummy_raw_packet;
+    use crate::core::ics04_channel::packet::{Packet, Receipt};
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::{ClientId, ConnectionId};
+    use crate::mock::context::MockContext;
+
+    /// A context in which the packets of sequences 1 and 2 were received on an unordered channel,
+    /// and acknowledged one block apart.
+    fn ctx_with_packet_records() -> MockContext {
+        let packet: Packet = get_dummy_raw_packet(1, 6).try_into().unwrap();
+        let channel_end = ChannelEnd::new(
+            State::Open,
+            Order::Unordered,
+            Counterparty::new(
+                packet.source_port.clone(),
+                Some(packet.source_channel.clone()),
+            ),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+        let mut ctx = MockContext::default()
+            .with_client(&ClientId::default(), Height::new(0, 1).unwrap())
+            .with_connection(
+                ConnectionId::default(),
+                ConnectionEnd::new(
+                    ConnectionState::Open,
+                    ClientId::default(),
+                    ConnectionCounterparty::new(
+                        ClientId::default(),
+                        Some(ConnectionId::default()),
+                        Default::default(),
+                    ),
+                    get_compatible_versions(),
+                    Duration::ZERO,
+                ),
+            )
+            .with_channel(
+                packet.destination_port.clone(),
+                packet.destination_channel.clone(),
+                channel_end,
+            );
+
+        for sequence in [1, 2] {
+            let result = PacketResult::Recv(RecvPacketResult::Unordered {
+                port_id: packet.destination_port.clone(),
+                channel_id: packet.destination_channel.clone(),
+                sequence: sequence.into(),
+                receipt: Receipt::Ok,
+            });
+            track_packet_record(&mut ctx, &result).unwrap();
+            ctx.store_packet_result(result).unwrap();
+            let packet = Packet {
+                sequence: sequence.into(),
+                ..packet.clone()
+            };
+            emit_acknowledgement(&mut ctx, packet, vec![1].into()).unwrap();
+            ctx.advance_host_chain_height();
+        }
+
+        ctx
+    }
+
+    #[test]
+    fn old_packet_records_are_pruned() {
+        let mut ctx = ctx_with_packet_records();
+        let packet: Packet = get_dummy_raw_packet(1, 6).try_into().unwrap();
+        let (port_id, channel_id) = (&packet.destination_port, &packet.destination_channel);
+        let records = ctx.packet_records().unwrap();
+        assert_eq!(records.len(), 2);
+
+        // Only the acknowledgement of the first packet is two blocks old.
+        let window = PruningWindow::new(2, Duration::ZERO);
+        let mut archived = Vec::new();
+        let pruned = prune_packet_records(&mut ctx, &window, |record, ack_commitment| {
+            archived.push((record.clone(), ack_commitment));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(pruned, vec![records[0].clone()]);
+        assert_eq!(archived.len(), 1);
+        assert!(ctx
+            .get_packet_acknowledgement(port_id, channel_id, 1.into())
+            .is_err());
+        assert!(ctx
+            .get_packet_acknowledgement(port_id, channel_id, 2.into())
+            .is_ok());
+        assert_eq!(ctx.packet_records().unwrap(), records[1..].to_vec());
+
+        // Records are kept when they cannot be archived.
+        ctx.advance_host_chain_height();
+        assert!(prune_packet_records(&mut ctx, &window, |_, _| {
+            Err(Error::implementation_specific())
+        })
+        .is_err());
+        assert_eq!(ctx.packet_records().unwrap(), records[1..].to_vec());
+
+        let pruned = prune_packet_records(&mut ctx, &window, |_, _| Ok(())).unwrap();
+        assert_eq!(pruned, records[1..].to_vec());
+        assert!(ctx.packet_records().unwrap().is_empty());
+
+        // The receipts are kept.
+        for sequence in [1, 2] {
+            assert!(ctx
+                .get_packet_receipt(port_id, channel_id, sequence.into())
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn packets_are_not_received_again_after_pruning() {
+        let mut ctx = ctx_with_packet_records();
+        ctx.advance_host_chain_height();
+        let window = PruningWindow::new(0, Duration::ZERO);
+        let pruned = prune_packet_records(&mut ctx, &window, |_, _| Ok(())).unwrap();
+        assert_eq!(pruned.len(), 2);
+
+        // A relayer replays the first packet, proven at a still stored consensus state.
+        let mut raw_msg = get_dummy_raw_msg_recv_packet(1);
+        if let Some(packet) = raw_msg.packet.as_mut() {
+            packet.timeout_height.as_mut().unwrap().revision_height = 100;
+            packet.timeout_timestamp = 0;
+        }
+        let msg = MsgRecvPacket::try_from(raw_msg).unwrap();
+
+        let output = recv_packet::process(&ctx, &msg).unwrap();
+        assert!(matches!(
+            output.result,
+            PacketResult::Recv(RecvPacketResult::NoOp)
+        ));
+    }
+
+    #[test]
+    fn packet_records_are_kept_within_trusting_period() {
+        let window = PruningWindow::new(0, Duration::from_secs(10));
+        let record = PacketRecord {
+            port_id: PortId::default(),
+            channel_id: ChannelId::default(),
+            sequence: 1.into(),
+            height: Height::new(0, 1).unwrap(),
+            timestamp: Timestamp::from_nanoseconds(1).unwrap(),
+        };
+        let host_height = Height::new(0, 10).unwrap();
+        let host_timestamp = (record.timestamp + Duration::from_secs(60)).unwrap();
+
+        assert!(window.is_prunable(&record, host_height, host_timestamp, None));
+        assert!(window.is_prunable(
+            &record,
+            host_height,
+            host_timestamp,
+            Some(Duration::from_secs(60))
+        ));
+        assert!(!window.is_prunable(
+            &record,
+            host_height,
+            host_timestamp,
+            Some(Duration::from_secs(61))
+        ));
+    }
+}
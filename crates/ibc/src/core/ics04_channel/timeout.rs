@@ -1,11 +1,15 @@
 use core::fmt::{Display, Error as FmtError, Formatter};
+use core::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
 use ibc_proto::ibc::core::client::v1::Height as RawHeight;
 use tendermint::abci::tag::Value as TagValue;
 
-use crate::core::ics02_client::{error::Error as ICS2Error, height::Height};
+use crate::core::ics02_client::{
+    error::Error as ICS2Error,
+    height::{Height, HeightError},
+};
 use crate::prelude::*;
 
 /// Indicates a consensus height on the destination chain after which the packet
@@ -120,6 +124,18 @@ impl From<TimeoutHeight> for TagValue {
     }
 }
 
+/// Parses the timeout height from its tag value, i.e. `0-0` for no timeout.
+impl FromStr for TimeoutHeight {
+    type Err = HeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0-0" => Ok(TimeoutHeight::Never),
+            _ => Height::from_str(s).map(TimeoutHeight::At),
+        }
+    }
+}
+
 impl Display for TimeoutHeight {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         match self {
@@ -0,0 +1,351 @@
+//! Optional closing of channels whose packets keep timing out.
+//!
+//! Hosts opt in per channel through [`ChannelReader::max_consecutive_timeouts`]. The packets sent
+//! on such a channel that time out are counted (see [`ChannelKeeper::store_consecutive_timeouts`])
+//! until one is acknowledged, and once the limit is reached the channel is closed as if its
+//! application had sent a `MsgChannelCloseInit`, provided that the application approves it in its
+//! `on_chan_close_init` callback. This lets e.g. interchain account controllers recycle dead
+//! ordered-allow-timeout channels quickly. Ordered channels are closed by their first timeout
+//! anyway.
+
+use crate::core::context::ExecutionContext;
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::events::CloseInit;
+use crate::core::ics04_channel::handler::{channel_callback, channel_dispatch};
+use crate::core::ics04_channel::msgs::chan_close_init::MsgChannelCloseInit;
+use crate::core::ics04_channel::msgs::{ChannelMsg, PacketMsg};
+use crate::core::ics04_channel::packet::PacketResult;
+use crate::core::ics26_routing::context::ModuleId;
+use crate::events::{EventReason, IbcEvent};
+use crate::handler::{HandlerOutput, HandlerOutputBuilder};
+use crate::prelude::*;
+
+/// Updates the count of consecutive timeouts of the channel of a processed packet message, before
+/// its `result` is stored. Returns the message closing the channel on behalf of the relayer, once
+/// the channel reached its `max_consecutive_timeouts`.
+pub(crate) fn count_timeouts<Ctx>(
+    ctx: &mut Ctx,
+    msg: &PacketMsg,
+    result: &PacketResult,
+) -> Result<Option<MsgChannelCloseInit>, Error>
+where
+    Ctx: ChannelReader + ChannelKeeper,
+{
+    let (port_id, channel_id, signer) = match (msg, result) {
+        // Timeouts closing the channel by themselves are not counted.
+        (PacketMsg::TimeoutPacket(msg), PacketResult::Timeout(res)) if res.channel.is_none() => {
+            (&res.port_id, &res.channel_id, Some(&msg.signer))
+        }
+        (PacketMsg::TimeoutOnClosePacket(msg), PacketResult::Timeout(res))
+            if res.channel.is_none() =>
+        {
+            (&res.port_id, &res.channel_id, Some(&msg.signer))
+        }
+        (_, PacketResult::Ack(res)) => (&res.port_id, &res.channel_id, None),
+        _ => return Ok(None),
+    };
+
+    let max_timeouts = match ctx.max_consecutive_timeouts(port_id, channel_id) {
+        Some(max_timeouts) => max_timeouts,
+        None => return Ok(None),
+    };
+    let timeouts = ctx.consecutive_timeouts(port_id, channel_id)?;
+
+    let signer = match signer {
+        Some(signer) => signer,
+        None => {
+            // An acknowledgement breaks the series of timeouts.
+            if timeouts != 0 {
+                ctx.store_consecutive_timeouts(port_id.clone(), channel_id.clone(), 0)?;
+            }
+            return Ok(None);
+        }
+    };
+
+    let timeouts = timeouts.saturating_add(1);
+    ctx.store_consecutive_timeouts(port_id.clone(), channel_id.clone(), timeouts)?;
+
+    Ok((timeouts >= max_timeouts).then(|| MsgChannelCloseInit {
+        port_id: port_id.clone(),
+        channel_id: channel_id.clone(),
+        signer: signer.clone(),
+    }))
+}
+
+/// Closes a channel whose packets kept timing out, through the `msg` returned by
+/// [`count_timeouts`]. The channel stays open if its application declines closing it, or if it
+/// cannot be closed (e.g. its connection is no longer open), in which case closing it is attempted
+/// again on the next timeout.
+pub(crate) fn close_on_timeouts<Ctx>(
+    ctx: &mut Ctx,
+    module_id: &ModuleId,
+    msg: MsgChannelCloseInit,
+) -> Result<HandlerOutputBuilder<()>, Error>
+where
+    Ctx: ExecutionContext,
+{
+    let mut output = HandlerOutput::builder();
+    let (port_id, channel_id) = (msg.port_id.clone(), msg.channel_id.clone());
+    let msg = ChannelMsg::ChannelCloseInit(msg);
+
+    let closed = channel_dispatch(ctx, &msg).and_then(|(log, mut result)| {
+        let extras = channel_callback(ctx, module_id, &msg, &mut result)?;
+        Ok((log, result, extras))
+    });
+    let (log, result, extras) = match closed {
+        Ok(closed) => closed,
+        Err(e) => {
+            output.log(format!(
+                "channel {}/{} not closed after consecutive timeouts: {}",
+                port_id, channel_id, e
+            ));
+            return Ok(output);
+        }
+    };
+
+    let counterparty = result.channel_end.counterparty().clone();
+    output.emit(IbcEvent::CloseInitChannel(
+        CloseInit::new(
+            port_id.clone(),
+            channel_id.clone(),
+            counterparty.port_id,
+            counterparty
+                .channel_id
+                .expect("counterparty channel id must exist on channels sending packets"),
+            result.channel_end.connection_hops[0].clone(),
+        )
+        .with_reason(EventReason::Timeout),
+    ));
+    for event in extras.events {
+        output.emit(IbcEvent::AppModule(event));
+    }
+    for log in log.into_iter().chain(extras.log) {
+        output.log(log);
+    }
+
+    ctx.store_channel_result(result)?;
+    ctx.store_consecutive_timeouts(port_id, channel_id, 0)?;
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::core::ics02_client::height::Height;
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics03_connection::version::get_compatible_versions;
+    use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+    use crate::core::ics04_channel::handler::ModuleExtras;
+    use crate::core::ics04_channel::msgs::acknowledgement::test_util::get_dummy_raw_msg_ack_with_packet;
+    use crate::core::ics04_channel::msgs::acknowledgement::MsgAcknowledgement;
+    use crate::core::ics04_channel::msgs::timeout::test_util::get_dummy_raw_msg_timeout;
+    use crate::core::ics04_channel::msgs::timeout::MsgTimeout;
+    use crate::core::ics04_channel::packet::test_utils::get_dummy_raw_packet;
+    use crate::core::ics04_channel::packet::Sequence;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+    use crate::core::ics26_routing::context::{Module, RouterBuilder};
+    use crate::core::ics26_routing::handler::dispatch;
+    use crate::core::ics26_routing::msgs::Ics26Envelope;
+    use crate::mock::context::{MockContext, MockRouterBuilder};
+    use crate::timestamp::ZERO_DURATION;
+
+    /// An application approving, or not, the closing of its channels.
+    #[derive(Debug)]
+    struct App {
+        approve_close: bool,
+    }
+
+    impl Module for App {
+        fn on_chan_open_init(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            version: &Version,
+        ) -> Result<(ModuleExtras, Version), Error> {
+            Ok((ModuleExtras::empty(), version.clone()))
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            counterparty_version: &Version,
+        ) -> Result<(ModuleExtras, Version), Error> {
+            Ok((ModuleExtras::empty(), counterparty_version.clone()))
+        }
+
+        fn on_chan_close_init(
+            &mut self,
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+        ) -> Result<ModuleExtras, Error> {
+            if self.approve_close {
+                Ok(ModuleExtras::empty())
+            } else {
+                Err(Error::implementation_specific())
+            }
+        }
+    }
+
+    const SEQUENCES: u64 = 4;
+
+    /// A context with an ordered-allow-timeout channel on which packets `1..=SEQUENCES` are in
+    /// flight, closed after two consecutive timeouts.
+    fn ctx(approve_close: bool) -> MockContext {
+        let module_id: ModuleId = "app".parse().unwrap();
+        let ctx = MockContext::default();
+        let mut ctx = ctx
+            .with_client(&ClientId::default(), Height::new(0, 2).unwrap())
+            .with_connection(
+                ConnectionId::default(),
+                ConnectionEnd::new(
+                    ConnectionState::Open,
+                    ClientId::default(),
+                    ConnectionCounterparty::new(
+                        ClientId::default(),
+                        Some(ConnectionId::default()),
+                        Default::default(),
+                    ),
+                    get_compatible_versions(),
+                    ZERO_DURATION,
+                ),
+            )
+            .with_channel(
+                PortId::default(),
+                ChannelId::default(),
+                ChannelEnd::new(
+                    State::Open,
+                    Order::OrderedAllowTimeout,
+                    Counterparty::new(PortId::default(), Some(ChannelId::default())),
+                    vec![ConnectionId::default()],
+                    Version::ics20(),
+                ),
+            )
+            .with_ack_sequence(PortId::default(), ChannelId::default(), 1.into())
+            .with_max_consecutive_timeouts(2)
+            .with_router(
+                MockRouterBuilder::default()
+                    .add_route(module_id.clone(), App { approve_close })
+                    .unwrap()
+                    .build(),
+            );
+        ctx.scope_port_to_module(PortId::default(), module_id);
+
+        for seq in 1..=SEQUENCES {
+            let packet = timeout(seq).packet;
+            let commitment =
+                ctx.packet_commitment(packet.data, packet.timeout_height, packet.timeout_timestamp);
+            ctx = ctx.with_packet_commitment(
+                PortId::default(),
+                ChannelId::default(),
+                seq.into(),
+                commitment,
+            );
+        }
+        ctx
+    }
+
+    fn timeout(seq: u64) -> MsgTimeout {
+        let mut raw = get_dummy_raw_msg_timeout(2, 5, 0);
+        raw.packet.as_mut().unwrap().sequence = seq;
+        raw.next_sequence_recv = seq;
+        MsgTimeout::try_from(raw).unwrap()
+    }
+
+    fn ack(seq: u64) -> MsgAcknowledgement {
+        let mut packet = get_dummy_raw_packet(5, 0);
+        packet.sequence = seq;
+        MsgAcknowledgement::try_from(get_dummy_raw_msg_ack_with_packet(packet, 2)).unwrap()
+    }
+
+    fn deliver(ctx: &mut MockContext, msg: PacketMsg) -> Vec<IbcEvent> {
+        dispatch(ctx, Ics26Envelope::Ics4PacketMsg(msg))
+            .unwrap()
+            .events
+    }
+
+    fn channel_state(ctx: &MockContext) -> State {
+        *ctx.channel_end(&PortId::default(), &ChannelId::default())
+            .unwrap()
+            .state()
+    }
+
+    fn is_closed_on_timeouts(event: &IbcEvent) -> bool {
+        matches!(
+            event,
+            IbcEvent::CloseInitChannel(e) if e.reason() == Some(&EventReason::Timeout)
+        )
+    }
+
+    #[test]
+    fn channel_is_closed_after_consecutive_timeouts() {
+        let mut ctx = ctx(true);
+
+        let events = deliver(&mut ctx, PacketMsg::TimeoutPacket(timeout(1)));
+        assert!(!events.iter().any(is_closed_on_timeouts));
+        assert_eq!(channel_state(&ctx), State::Open);
+
+        let events = deliver(&mut ctx, PacketMsg::TimeoutPacket(timeout(2)));
+        assert!(matches!(
+            events.as_slice(),
+            [IbcEvent::TimeoutPacket(_), e] if is_closed_on_timeouts(e)
+        ));
+        assert_eq!(channel_state(&ctx), State::Closed);
+        assert_eq!(
+            ctx.consecutive_timeouts(&PortId::default(), &ChannelId::default())
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn acknowledgements_reset_the_count() {
+        let mut ctx = ctx(true);
+
+        deliver(&mut ctx, PacketMsg::TimeoutPacket(timeout(1)));
+        deliver(&mut ctx, PacketMsg::AckPacket(ack(2)));
+        let events = deliver(&mut ctx, PacketMsg::TimeoutPacket(timeout(3)));
+
+        assert!(!events.iter().any(is_closed_on_timeouts));
+        assert_eq!(channel_state(&ctx), State::Open);
+        assert_eq!(
+            ctx.consecutive_timeouts(&PortId::default(), &ChannelId::default())
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn closing_is_subject_to_the_application() {
+        let mut ctx = ctx(false);
+
+        deliver(&mut ctx, PacketMsg::TimeoutPacket(timeout(1)));
+        let events = deliver(&mut ctx, PacketMsg::TimeoutPacket(timeout(2)));
+
+        // The timeout itself is processed, but the channel stays open.
+        assert!(matches!(events.as_slice(), [IbcEvent::TimeoutPacket(_)]));
+        assert_eq!(channel_state(&ctx), State::Open);
+        assert_eq!(
+            ctx.consecutive_timeouts(&PortId::default(), &ChannelId::default())
+                .unwrap(),
+            2
+        );
+        assert!(ctx
+            .get_packet_commitment(&PortId::default(), &ChannelId::default(), Sequence::from(2))
+            .is_err());
+    }
+}
@@ -0,0 +1,497 @@
+//! Types of the channel upgrade handshake, through which a standing channel changes its version,
+//! ordering or connection without being closed, as specified in
+//! [ICS 4](https://github.com/cosmos/ibc/tree/main/spec/core/ics-004-channel-and-packet-semantics/UPGRADES.md).
+//!
+//! The upgrade of a channel end goes through the following steps:
+//! - `MsgChannelUpgradeInit` proposes new [`UpgradeFields`] for an `OPEN` channel end, and
+//!   increments its upgrade sequence;
+//! - `MsgChannelUpgradeTry` proves the proposal of the counterparty, and starts `FLUSHING` the
+//!   packets in flight;
+//! - `MsgChannelUpgradeAck` and `MsgChannelUpgradeConfirm` prove the [`Upgrade`] of the
+//!   counterparty, which is then flushing too, and record it: the channel end is `FLUSHCOMPLETE`
+//!   once all its packets in flight are acknowledged or timed out;
+//! - `MsgChannelUpgradeOpen` proves that both ends completed flushing, and reopens the channel
+//!   end with the new fields.
+//!
+//! An upgrade that failed on the counterparty, as proven by its [`ErrorReceipt`], is cancelled
+//! with `MsgChannelUpgradeCancel`, and one that the counterparty did not complete before its
+//! timeout with `MsgChannelUpgradeTimeout`. The channel end is then restored to `OPEN` with its
+//! previous fields.
+
+use crate::prelude::*;
+
+use core::fmt::{Display, Error as FmtError, Formatter};
+use core::str::FromStr;
+
+use ibc_proto::protobuf::Protobuf;
+
+use crate::core::ics04_channel::channel::Order;
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::packet::Sequence;
+use crate::core::ics04_channel::raw::{
+    ErrorReceipt as RawErrorReceipt, Timeout as RawTimeout, Upgrade as RawUpgrade,
+    UpgradeFields as RawUpgradeFields,
+};
+use crate::core::ics04_channel::timeout::TimeoutHeight;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::identifier::ConnectionId;
+use crate::timestamp::Timestamp;
+use crate::Height;
+
+/// The fields of a channel end that an upgrade changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpgradeFields {
+    pub ordering: Order,
+    pub connection_hops: Vec<ConnectionId>,
+    pub version: Version,
+}
+
+impl UpgradeFields {
+    pub fn new(ordering: Order, connection_hops: Vec<ConnectionId>, version: Version) -> Self {
+        Self {
+            ordering,
+            connection_hops,
+            version,
+        }
+    }
+
+    pub fn validate_basic(&self) -> Result<(), Error> {
+        if self.ordering == Order::None {
+            return Err(Error::unknown_order_type(self.ordering.to_string()));
+        }
+        if self.connection_hops.len() != 1 {
+            return Err(Error::invalid_connection_hops_length(
+                1,
+                self.connection_hops.len(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Protobuf<RawUpgradeFields> for UpgradeFields {}
+
+impl TryFrom<RawUpgradeFields> for UpgradeFields {
+    type Error = Error;
+
+    fn try_from(value: RawUpgradeFields) -> Result<Self, Self::Error> {
+        let fields = UpgradeFields {
+            ordering: Order::from_i32(value.ordering)?,
+            connection_hops: value
+                .connection_hops
+                .iter()
+                .map(|conn_id| ConnectionId::from_str(conn_id))
+                .collect::<Result<_, _>>()
+                .map_err(Error::identifier)?,
+            version: value.version.into(),
+        };
+        fields.validate_basic()?;
+
+        Ok(fields)
+    }
+}
+
+impl From<UpgradeFields> for RawUpgradeFields {
+    fn from(value: UpgradeFields) -> Self {
+        RawUpgradeFields {
+            ordering: value.ordering as i32,
+            connection_hops: value
+                .connection_hops
+                .iter()
+                .map(|conn_id| conn_id.as_str().to_string())
+                .collect(),
+            version: value.version.to_string(),
+        }
+    }
+}
+
+/// The height and/or time on the counterparty after which it may not complete the upgrade of its
+/// channel end anymore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpgradeTimeout {
+    pub height: TimeoutHeight,
+    pub timestamp: Timestamp,
+}
+
+impl UpgradeTimeout {
+    pub fn new(height: TimeoutHeight, timestamp: Timestamp) -> Self {
+        Self { height, timestamp }
+    }
+
+    /// The timeout of the upgrades that are not flushing yet, i.e. that were only proposed.
+    pub fn unset() -> Self {
+        Self::new(TimeoutHeight::Never, Timestamp::none())
+    }
+
+    /// Whether a height or a timestamp is set.
+    pub fn is_set(&self) -> bool {
+        self.height != TimeoutHeight::Never || self.timestamp != Timestamp::none()
+    }
+
+    /// Whether the timeout is reached at `height` and `timestamp`, as in ibc-go: at the timeout
+    /// height or time, and not only past them.
+    pub fn has_elapsed(&self, height: Height, timestamp: Timestamp) -> bool {
+        let height_elapsed = match self.height {
+            TimeoutHeight::At(timeout_height) => height >= timeout_height,
+            TimeoutHeight::Never => false,
+        };
+        let timestamp_elapsed = self.timestamp != Timestamp::none()
+            && timestamp != Timestamp::none()
+            && timestamp.nanoseconds() >= self.timestamp.nanoseconds();
+
+        height_elapsed || timestamp_elapsed
+    }
+}
+
+impl Display for UpgradeTimeout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(f, "UpgradeTimeout({}, {})", self.height, self.timestamp)
+    }
+}
+
+impl TryFrom<RawTimeout> for UpgradeTimeout {
+    type Error = Error;
+
+    fn try_from(value: RawTimeout) -> Result<Self, Self::Error> {
+        Ok(UpgradeTimeout {
+            height: value
+                .height
+                .try_into()
+                .map_err(|_| Error::invalid_timeout_height())?,
+            timestamp: Timestamp::from_nanoseconds(value.timestamp)
+                .map_err(Error::invalid_packet_timestamp)?,
+        })
+    }
+}
+
+impl From<UpgradeTimeout> for RawTimeout {
+    fn from(value: UpgradeTimeout) -> Self {
+        RawTimeout {
+            height: value.height.into(),
+            timestamp: value.timestamp.nanoseconds(),
+        }
+    }
+}
+
+/// An upgrade of a channel end, as proposed by `MsgChannelUpgradeInit` or
+/// `MsgChannelUpgradeTry`, and completed once the channel end started flushing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upgrade {
+    pub fields: UpgradeFields,
+    /// Unset until the channel end starts flushing.
+    pub timeout: UpgradeTimeout,
+    /// The next sequence to send on the channel end when it started flushing: the packets in
+    /// flight are the ones sent before, 0 until then.
+    pub next_sequence_send: Sequence,
+}
+
+impl Upgrade {
+    /// An upgrade to `fields` that was only proposed, and whose channel end is not flushing yet.
+    pub fn proposed(fields: UpgradeFields) -> Self {
+        Self {
+            fields,
+            timeout: UpgradeTimeout::unset(),
+            next_sequence_send: 0.into(),
+        }
+    }
+}
+
+impl Protobuf<RawUpgrade> for Upgrade {}
+
+impl TryFrom<RawUpgrade> for Upgrade {
+    type Error = Error;
+
+    fn try_from(value: RawUpgrade) -> Result<Self, Self::Error> {
+        Ok(Upgrade {
+            fields: value
+                .fields
+                .ok_or_else(|| Error::missing_upgrade_field("fields".to_string()))?
+                .try_into()?,
+            timeout: value
+                .timeout
+                .ok_or_else(|| Error::missing_upgrade_field("timeout".to_string()))?
+                .try_into()?,
+            next_sequence_send: value.next_sequence_send.into(),
+        })
+    }
+}
+
+impl From<Upgrade> for RawUpgrade {
+    fn from(value: Upgrade) -> Self {
+        RawUpgrade {
+            fields: Some(value.fields.into()),
+            timeout: Some(value.timeout.into()),
+            next_sequence_send: value.next_sequence_send.into(),
+        }
+    }
+}
+
+/// Records that the upgrade of a channel end with the given sequence failed, so that its
+/// counterparty can cancel its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorReceipt {
+    pub sequence: u64,
+    pub message: String,
+}
+
+impl ErrorReceipt {
+    /// The receipt of the upgrade with the given sequence, which failed with `error`. Only the
+    /// kind of error is recorded, the state of the host being committed to by the receipt.
+    pub fn new(sequence: u64, error: &Error) -> Self {
+        Self {
+            sequence,
+            message: format!("upgrade {} failed: {}", sequence, error.detail()),
+        }
+    }
+}
+
+impl Protobuf<RawErrorReceipt> for ErrorReceipt {}
+
+impl TryFrom<RawErrorReceipt> for ErrorReceipt {
+    type Error = Error;
+
+    fn try_from(value: RawErrorReceipt) -> Result<Self, Self::Error> {
+        Ok(ErrorReceipt {
+            sequence: value.sequence,
+            message: value.message,
+        })
+    }
+}
+
+impl From<ErrorReceipt> for RawErrorReceipt {
+    fn from(value: ErrorReceipt) -> Self {
+        RawErrorReceipt {
+            sequence: value.sequence,
+            message: value.message,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use crate::prelude::*;
+
+    use core::time::Duration;
+
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics03_connection::version::Version as ConnectionVersion;
+    use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+    use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+    use crate::core::ics26_routing::context::{ModuleId, RouterBuilder};
+    use crate::mock::context::{MockContext, MockRouterBuilder};
+    use crate::test_utils::DummyTransferModule;
+    use crate::Height;
+
+    /// The height at which the upgrade messages returned by the `test_util` of their modules
+    /// should be proven in the context of `get_upgradable_context`.
+    pub const PROOF_HEIGHT: u64 = 10;
+
+    /// Returns an unordered `ics20-1` channel end on the default connection, whose counterparty
+    /// is the default channel on the default port.
+    pub fn get_dummy_channel_end(state: State, upgrade_sequence: u64) -> ChannelEnd {
+        ChannelEnd::new(
+            state,
+            Order::Unordered,
+            Counterparty::new(PortId::default(), Some(ChannelId::default())),
+            vec![ConnectionId::default()],
+            Version::new("ics20-1".to_string()),
+        )
+        .with_upgrade_sequence(upgrade_sequence)
+    }
+
+    /// Returns a context enabling channel upgrades, with an open `get_dummy_channel_end` on the
+    /// default port bound to a transfer module, whose connection is its own counterparty.
+    pub fn get_upgradable_context() -> MockContext {
+        let module_id: ModuleId = "transfer".parse().unwrap();
+        let mut ctx = MockContext::default()
+            .with_channel_upgrades()
+            .with_client(&ClientId::default(), Height::new(0, PROOF_HEIGHT).unwrap())
+            .with_connection(
+                ConnectionId::default(),
+                ConnectionEnd::new(
+                    ConnectionState::Open,
+                    ClientId::default(),
+                    ConnectionCounterparty::new(
+                        ClientId::default(),
+                        Some(ConnectionId::default()),
+                        CommitmentPrefix::default(),
+                    ),
+                    vec![ConnectionVersion::default()],
+                    Duration::ZERO,
+                ),
+            )
+            .with_channel(
+                PortId::default(),
+                ChannelId::default(),
+                get_dummy_channel_end(State::Open, 0),
+            )
+            .with_send_sequence(PortId::default(), ChannelId::default(), 1.into())
+            .with_recv_sequence(PortId::default(), ChannelId::default(), 1.into())
+            .with_ack_sequence(PortId::default(), ChannelId::default(), 1.into());
+        let module = DummyTransferModule::new(ctx.ibc_store_share());
+        let router = MockRouterBuilder::default()
+            .add_route(module_id.clone(), module)
+            .unwrap()
+            .build();
+        ctx.scope_port_to_module(PortId::default(), module_id);
+        ctx.with_router(router)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_timeout_elapses_at_the_timeout() {
+        let timeout = UpgradeTimeout::new(
+            Height::new(0, 10).unwrap().into(),
+            Timestamp::from_nanoseconds(1_000).unwrap(),
+        );
+        let before = Timestamp::from_nanoseconds(999).unwrap();
+        let at = Timestamp::from_nanoseconds(1_000).unwrap();
+
+        assert!(!timeout.has_elapsed(Height::new(0, 9).unwrap(), before));
+        assert!(timeout.has_elapsed(Height::new(0, 10).unwrap(), before));
+        assert!(timeout.has_elapsed(Height::new(0, 9).unwrap(), at));
+
+        assert!(!UpgradeTimeout::unset().is_set());
+        assert!(!UpgradeTimeout::unset().has_elapsed(Height::new(5, 5).unwrap(), at));
+    }
+
+    #[test]
+    fn test_channel_upgrade_handshake() {
+        use crate::core::ics04_channel::channel::State;
+        use crate::core::ics04_channel::context::ChannelReader;
+        use crate::core::ics04_channel::events::UpgradeStep;
+        use crate::core::ics04_channel::msgs::chan_upgrade_ack::test_util::get_dummy_raw_msg_chan_upgrade_ack;
+        use crate::core::ics04_channel::msgs::chan_upgrade_ack::MsgChannelUpgradeAck;
+        use crate::core::ics04_channel::msgs::chan_upgrade_confirm::test_util::get_dummy_raw_msg_chan_upgrade_confirm;
+        use crate::core::ics04_channel::msgs::chan_upgrade_confirm::MsgChannelUpgradeConfirm;
+        use crate::core::ics04_channel::msgs::chan_upgrade_init::test_util::get_dummy_raw_msg_chan_upgrade_init;
+        use crate::core::ics04_channel::msgs::chan_upgrade_init::MsgChannelUpgradeInit;
+        use crate::core::ics04_channel::msgs::chan_upgrade_open::test_util::get_dummy_raw_msg_chan_upgrade_open;
+        use crate::core::ics04_channel::msgs::chan_upgrade_open::MsgChannelUpgradeOpen;
+        use crate::core::ics04_channel::msgs::chan_upgrade_try::test_util::get_dummy_raw_msg_chan_upgrade_try;
+        use crate::core::ics04_channel::msgs::chan_upgrade_try::MsgChannelUpgradeTry;
+        use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+        use crate::core::ics24_host::identifier::{ChannelId, PortId};
+        use crate::core::ics26_routing::handler::dispatch;
+        use crate::core::ics26_routing::msgs::Ics26Envelope;
+        use crate::events::IbcEvent;
+        use crate::mock::context::MockContext;
+        use test_util::{get_upgradable_context, PROOF_HEIGHT};
+
+        fn deliver(ctx: &mut MockContext, msg: ChannelUpgradeMsg) -> Vec<UpgradeStep> {
+            let output = dispatch(ctx, Ics26Envelope::Ics4ChannelUpgradeMsg(msg)).unwrap();
+            output
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    IbcEvent::ChannelUpgrade(e) => Some(e.step()),
+                    _ => None,
+                })
+                .collect()
+        }
+        let (port_id, channel_id) = (PortId::default(), ChannelId::default());
+        let upgrade_of = |ctx: &MockContext| ctx.channel_upgrade(&port_id, &channel_id).unwrap();
+
+        // Chain A proposes to upgrade the channel to an ordered one, which chain B takes up.
+        let (mut ctx_a, mut ctx_b) = (get_upgradable_context(), get_upgradable_context());
+        let init = MsgChannelUpgradeInit::try_from(get_dummy_raw_msg_chan_upgrade_init()).unwrap();
+        assert_eq!(
+            deliver(&mut ctx_a, ChannelUpgradeMsg::ChannelUpgradeInit(init)),
+            vec![UpgradeStep::Init]
+        );
+
+        let try_msg =
+            MsgChannelUpgradeTry::try_from(get_dummy_raw_msg_chan_upgrade_try(PROOF_HEIGHT))
+                .unwrap();
+        assert_eq!(
+            deliver(&mut ctx_b, ChannelUpgradeMsg::ChannelUpgradeTry(try_msg)),
+            vec![UpgradeStep::Try]
+        );
+        let channel_b = ctx_b.channel_end(&port_id, &channel_id).unwrap();
+        assert_eq!(channel_b.state, State::Flushing);
+
+        // No packet is in flight on chain A, which completes flushing right away.
+        let mut ack =
+            MsgChannelUpgradeAck::try_from(get_dummy_raw_msg_chan_upgrade_ack(PROOF_HEIGHT))
+                .unwrap();
+        ack.counterparty_upgrade = upgrade_of(&ctx_b).unwrap();
+        assert_eq!(
+            deliver(&mut ctx_a, ChannelUpgradeMsg::ChannelUpgradeAck(ack)),
+            vec![UpgradeStep::Ack]
+        );
+        let channel_a = ctx_a.channel_end(&port_id, &channel_id).unwrap();
+        assert_eq!(channel_a.state, State::FlushComplete);
+
+        // Chain B then completes the upgrade when confirming it ...
+        let mut confirm = MsgChannelUpgradeConfirm::try_from(
+            get_dummy_raw_msg_chan_upgrade_confirm(PROOF_HEIGHT),
+        )
+        .unwrap();
+        confirm.counterparty_channel_state = State::FlushComplete;
+        confirm.counterparty_upgrade = upgrade_of(&ctx_a).unwrap();
+        assert_eq!(
+            deliver(
+                &mut ctx_b,
+                ChannelUpgradeMsg::ChannelUpgradeConfirm(confirm)
+            ),
+            vec![UpgradeStep::Confirm, UpgradeStep::Open]
+        );
+
+        // ... and chain A once it is proven.
+        let mut open =
+            MsgChannelUpgradeOpen::try_from(get_dummy_raw_msg_chan_upgrade_open(PROOF_HEIGHT))
+                .unwrap();
+        open.counterparty_channel_state = State::Open;
+        assert_eq!(
+            deliver(&mut ctx_a, ChannelUpgradeMsg::ChannelUpgradeOpen(open)),
+            vec![UpgradeStep::Open]
+        );
+
+        for ctx in [&ctx_a, &ctx_b] {
+            let channel_end = ctx.channel_end(&port_id, &channel_id).unwrap();
+            assert_eq!(channel_end.state, State::Open);
+            assert_eq!(channel_end.ordering, Order::Ordered);
+            assert_eq!(channel_end.version, Version::new("ics20-2".to_string()));
+            assert_eq!(channel_end.upgrade_sequence, 1);
+            assert!(upgrade_of(ctx).is_none());
+            assert!(ctx
+                .counterparty_upgrade(&port_id, &channel_id)
+                .unwrap()
+                .is_none());
+            assert_eq!(
+                ctx.get_next_sequence_recv(&port_id, &channel_id).unwrap(),
+                1.into()
+            );
+        }
+    }
+
+    #[test]
+    fn test_proposed_upgrade_encoding() {
+        let upgrade = Upgrade::proposed(UpgradeFields::new(
+            Order::Ordered,
+            vec![ConnectionId::new(1)],
+            Version::new("ics20-2".to_string()),
+        ));
+        let encoded = upgrade.encode_vec().unwrap();
+
+        // As with ibc-go, whose timeouts are not nullable, the unset timeout is encoded as an
+        // empty height, and the next sequence to send is omitted.
+        assert!(encoded.ends_with(&[0x12, 0x02, 0x0a, 0x00]));
+        assert_eq!(Upgrade::decode_vec(&encoded).unwrap(), upgrade);
+
+        let raw = RawUpgrade {
+            fields: None,
+            ..upgrade.into()
+        };
+        assert!(Upgrade::try_from(raw).is_err());
+    }
+}
@@ -2,38 +2,82 @@
 //! version field of a channel end.
 //!
 
+use alloc::borrow::Cow;
 use core::convert::Infallible;
 use core::fmt::{Display, Error as FmtError, Formatter};
 use core::str::FromStr;
 use serde_derive::{Deserialize, Serialize};
 
-use crate::applications::transfer;
+use crate::applications::{fee, nft_transfer, transfer};
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics24_host::identifier::PortId;
 use crate::prelude::*;
 
+/// Version string of the ICS27 (interchain accounts) application.
+const ICS27_VERSION: &str = "ics27-1";
+
+/// Well-known versions that are interned, i.e. never allocated when parsed or decoded.
+const INTERNED_VERSIONS: [&str; 4] = [
+    transfer::VERSION,
+    ICS27_VERSION,
+    fee::VERSION,
+    nft_transfer::VERSION,
+];
+
 /// The version field for a `ChannelEnd`.
 ///
 /// This field is opaque to the core IBC protocol.
 /// No explicit validation is necessary, and the
 /// spec (v1) currently allows empty strings.
+///
+/// Well-known versions (e.g. `ics20-1`) are stored as borrowed static strings,
+/// so cloning and comparing them during the handshake does not allocate.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
-pub struct Version(String);
+#[serde(from = "String")]
+pub struct Version(Cow<'static, str>);
 
 impl Version {
     pub fn new(v: String) -> Self {
-        Self(v)
+        match Self::interned(&v) {
+            Some(version) => version,
+            None => Self(Cow::Owned(v)),
+        }
+    }
+
+    /// Builds a version from a static string without allocating.
+    pub const fn from_static(v: &'static str) -> Self {
+        Self(Cow::Borrowed(v))
     }
 
     pub fn ics20() -> Self {
-        Self::new(transfer::VERSION.to_string())
+        Self::from_static(transfer::VERSION)
+    }
+
+    pub fn ics721() -> Self {
+        Self::from_static(nft_transfer::VERSION)
     }
 
     pub fn empty() -> Self {
-        Self::new("".to_string())
+        Self::from_static("")
     }
 
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn interned(v: &str) -> Option<Self> {
+        if v.is_empty() {
+            return Some(Self::empty());
+        }
+        INTERNED_VERSIONS
+            .iter()
+            .find(|known| **known == v)
+            .map(|known| Self::from_static(known))
+    }
 }
 
 impl From<String> for Version {
@@ -42,11 +86,35 @@ impl From<String> for Version {
     }
 }
 
+impl From<Version> for String {
+    fn from(v: Version) -> Self {
+        v.0.into_owned()
+    }
+}
+
 impl FromStr for Version {
     type Err = Infallible;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self::new(s.to_string()))
+        Ok(Self::interned(s).unwrap_or_else(|| Self(Cow::Owned(s.to_string()))))
+    }
+}
+
+impl AsRef<str> for Version {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for Version {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Version {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
     }
 }
 
@@ -62,3 +130,96 @@ impl Display for Version {
         write!(f, "{}", self.0)
     }
 }
+
+/// Returns the version to open a channel on `port_id` with, given the versions supported by the
+/// module bound to the port, the first of which is its default: the default if the relayer left
+/// `version` empty, or `version` itself if it is supported. Modules that support no versions
+/// negotiate them in their callbacks, so `version` is returned unchanged.
+pub fn negotiate_version(
+    port_id: &PortId,
+    supported: &[Version],
+    version: &Version,
+) -> Result<Version, Error> {
+    match supported.first() {
+        Some(default) if version.is_empty() => Ok(default.clone()),
+        Some(_) if !supported.contains(version) => {
+            Err(Error::unsupported_version(port_id.clone(), version.clone()))
+        }
+        _ => Ok(version.clone()),
+    }
+}
+
+/// Checks that the version of the counterparty of a channel on `port_id` is among the versions
+/// supported by the module bound to the port, if it supports any.
+pub fn verify_counterparty_version(
+    port_id: &PortId,
+    supported: &[Version],
+    counterparty_version: &Version,
+) -> Result<(), Error> {
+    if !supported.is_empty() && !supported.contains(counterparty_version) {
+        return Err(Error::unsupported_counterparty_version(
+            port_id.clone(),
+            counterparty_version.clone(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::Cow;
+
+    use super::*;
+
+    #[test]
+    fn well_known_versions_are_interned() {
+        for v in ["ics20-1", "ics27-1", "ics29-1", "ics721-1", ""] {
+            let from_string = Version::new(v.to_string());
+            let parsed: Version = v.parse().unwrap();
+            assert!(matches!(from_string.0, Cow::Borrowed(_)));
+            assert!(matches!(parsed.0, Cow::Borrowed(_)));
+            assert_eq!(from_string, v);
+        }
+
+        let custom = Version::new("custom-1".to_string());
+        assert!(matches!(custom.0, Cow::Owned(_)));
+        assert_eq!(custom, Version::from_static("custom-1"));
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let version = Version::ics20();
+        let json = serde_json::to_string(&version).unwrap();
+        assert_eq!(json, r#""ics20-1""#);
+
+        let decoded: Version = serde_json::from_str(&json).unwrap();
+        assert!(matches!(decoded.0, Cow::Borrowed(_)));
+        assert_eq!(decoded, version);
+    }
+
+    #[test]
+    fn negotiate_supported_versions() {
+        let port_id = PortId::transfer();
+        let supported = [Version::ics20(), Version::from_static("ics20-2")];
+        let unsupported = Version::from_static("ics20-3");
+
+        assert_eq!(
+            negotiate_version(&port_id, &supported, &Version::empty()).unwrap(),
+            Version::ics20()
+        );
+        assert_eq!(
+            negotiate_version(&port_id, &supported, &supported[1]).unwrap(),
+            supported[1]
+        );
+        assert!(negotiate_version(&port_id, &supported, &unsupported).is_err());
+        assert_eq!(
+            negotiate_version(&port_id, &[], &unsupported).unwrap(),
+            unsupported
+        );
+
+        assert!(verify_counterparty_version(&port_id, &supported, &supported[1]).is_ok());
+        assert!(verify_counterparty_version(&port_id, &supported, &unsupported).is_err());
+        assert!(verify_counterparty_version(&port_id, &[], &unsupported).is_ok());
+    }
+}
@@ -7,4 +7,7 @@ use crate::prelude::*;
 pub trait PortReader {
     /// Return the module_id associated with a given port_id
     fn lookup_module_by_port(&self, port_id: &PortId) -> Result<ModuleId, Error>;
+
+    /// Return all port bindings, as `(port_id, module_id)` pairs
+    fn port_bindings(&self) -> Vec<(PortId, ModuleId)>;
 }
@@ -1,4 +1,5 @@
 use crate::core::ics24_host::identifier::PortId;
+use crate::core::ics26_routing::context::ModuleId;
 use flex_error::define_error;
 
 define_error! {
@@ -12,6 +13,13 @@ define_error! {
             { port_id: PortId }
             | e | { format_args!("port '{0}' is already bound", e.port_id) },
 
+        ReservedPort
+            { port_id: PortId, module_id: ModuleId, owner: ModuleId }
+            | e | {
+                format_args!("port '{0}' cannot be bound by module '{1}' as it is reserved for module '{2}'",
+                    e.port_id, e.module_id, e.owner)
+            },
+
         ModuleNotFound
             { port_id: PortId }
             | e | { format_args!("could not retrieve module from port '{0}'", e.port_id) },
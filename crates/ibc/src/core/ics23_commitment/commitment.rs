@@ -3,11 +3,13 @@ use crate::prelude::*;
 use crate::proofs::ProofError;
 
 use core::{convert::TryFrom, fmt};
-use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+use ibc_proto::ibc::core::commitment::v1::{MerklePath, MerkleProof as RawMerkleProof};
 use serde::{Deserialize, Serialize};
 use subtle_encoding::{Encoding, Hex};
 
+use super::hasher::{DefaultHostHasher, HostHasher};
 use super::merkle::MerkleProof;
+use super::specs::ProofSpecs;
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -48,6 +50,14 @@ impl From<Vec<u8>> for CommitmentRoot {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CommitmentPath;
 
+/// Default upper bound on the size of `CommitmentProofBytes`. Proofs produced by Cosmos SDK chains
+/// are a few kilobytes at most.
+pub const DEFAULT_MAX_PROOF_SIZE: usize = 128 * 1024;
+
+/// Encoded commitment proof, as found in IBC messages.
+///
+/// The proof is kept encoded until it is verified, and its size is bounded upon construction, so
+/// that oversized proofs are rejected before any decoding work.
 #[derive(Clone, PartialEq, Eq, Serialize)]
 #[serde(transparent)]
 pub struct CommitmentProofBytes {
@@ -62,16 +72,89 @@ impl fmt::Debug for CommitmentProofBytes {
     }
 }
 
-impl TryFrom<Vec<u8>> for CommitmentProofBytes {
-    type Error = ProofError;
-
-    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+impl CommitmentProofBytes {
+    /// Wraps the given proof bytes, rejecting empty proofs and proofs larger than `max_size`.
+    /// Use this instead of `TryFrom<Vec<u8>>` to enforce a limit other than
+    /// `DEFAULT_MAX_PROOF_SIZE`.
+    pub fn try_from_bytes(bytes: Vec<u8>, max_size: usize) -> Result<Self, ProofError> {
         if bytes.is_empty() {
-            Err(Self::Error::empty_proof())
+            Err(ProofError::empty_proof())
+        } else if bytes.len() > max_size {
+            Err(ProofError::proof_too_large(bytes.len(), max_size))
         } else {
             Ok(Self { bytes })
         }
     }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Decodes the `MerkleProof` that the bytes encode.
+    pub fn merkle_proof(&self) -> Result<MerkleProof, Error> {
+        decode_merkle_proof(self.as_bytes()).map(Into::into)
+    }
+
+    /// Verifies that the proof proves `value` at `path` under `root`, hashing with the
+    /// [`DefaultHostHasher`].
+    ///
+    /// ICS-23 trees do not store empty values, so a path holds the empty value if and only if it
+    /// is absent: an empty `value` is verified as the absence of `path`.
+    pub fn verify_membership(
+        &self,
+        specs: &ProofSpecs,
+        root: &CommitmentRoot,
+        path: MerklePath,
+        value: Vec<u8>,
+    ) -> Result<(), Error> {
+        self.verify_membership_with::<DefaultHostHasher>(specs, root, path, value)
+    }
+
+    /// Same as [`Self::verify_membership`], hashing with `H`.
+    pub fn verify_membership_with<H: HostHasher>(
+        &self,
+        specs: &ProofSpecs,
+        root: &CommitmentRoot,
+        path: MerklePath,
+        value: Vec<u8>,
+    ) -> Result<(), Error> {
+        if value.is_empty() {
+            return self.verify_non_membership_with::<H>(specs, root, path);
+        }
+        self.merkle_proof()?
+            .verify_membership_with::<H>(specs, root.clone().into(), path, value, 0)
+    }
+
+    /// Verifies that the proof proves the absence of `path` under `root`, hashing with the
+    /// [`DefaultHostHasher`]. It is how the timeout of a packet on an unordered channel, or the
+    /// absence of an acknowledgement, is proven.
+    pub fn verify_non_membership(
+        &self,
+        specs: &ProofSpecs,
+        root: &CommitmentRoot,
+        path: MerklePath,
+    ) -> Result<(), Error> {
+        self.verify_non_membership_with::<DefaultHostHasher>(specs, root, path)
+    }
+
+    /// Same as [`Self::verify_non_membership`], hashing with `H`.
+    pub fn verify_non_membership_with<H: HostHasher>(
+        &self,
+        specs: &ProofSpecs,
+        root: &CommitmentRoot,
+        path: MerklePath,
+    ) -> Result<(), Error> {
+        self.merkle_proof()?
+            .verify_non_membership_with::<H>(specs, root.clone().into(), path)
+    }
+}
+
+impl TryFrom<Vec<u8>> for CommitmentProofBytes {
+    type Error = ProofError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(bytes, DEFAULT_MAX_PROOF_SIZE)
+    }
 }
 
 impl From<CommitmentProofBytes> for Vec<u8> {
@@ -102,13 +185,45 @@ impl TryFrom<CommitmentProofBytes> for RawMerkleProof {
     type Error = Error;
 
     fn try_from(value: CommitmentProofBytes) -> Result<Self, Self::Error> {
-        let value: Vec<u8> = value.into();
-        let res: RawMerkleProof =
-            prost::Message::decode(value.as_ref()).map_err(Error::invalid_raw_merkle_proof)?;
-        Ok(res)
+        decode_merkle_proof(value.as_bytes())
     }
 }
 
+/// Decodes a `MerkleProof` one `CommitmentProof` at a time, so that decoding errors report the
+/// byte offset (and index) of the offending proof.
+fn decode_merkle_proof(bytes: &[u8]) -> Result<RawMerkleProof, Error> {
+    use prost::encoding::{decode_key, decode_varint, skip_field, DecodeContext, WireType};
+
+    let mut proofs = Vec::new();
+    let mut buf = bytes;
+    while !buf.is_empty() {
+        let offset = bytes.len() - buf.len();
+        let (tag, wire_type) =
+            decode_key(&mut buf).map_err(|e| Error::malformed_merkle_proof(offset, e))?;
+
+        // Skip unknown fields, like `prost` does.
+        if tag != 1 || wire_type != WireType::LengthDelimited {
+            skip_field(wire_type, tag, &mut buf, DecodeContext::default())
+                .map_err(|e| Error::malformed_merkle_proof(offset, e))?;
+            continue;
+        }
+
+        let len = decode_varint(&mut buf).map_err(|e| Error::malformed_merkle_proof(offset, e))?;
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= buf.len())
+            .ok_or_else(|| Error::truncated_merkle_proof(offset, len, buf.len()))?;
+
+        let (proof_bytes, rest) = buf.split_at(len);
+        let proof = prost::Message::decode(proof_bytes)
+            .map_err(|e| Error::invalid_commitment_proof(proofs.len(), offset, e))?;
+        proofs.push(proof);
+        buf = rest;
+    }
+
+    Ok(RawMerkleProof { proofs })
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Deserialize, Default)]
 pub struct CommitmentPrefix {
     bytes: Vec<u8>,
@@ -155,7 +270,7 @@ impl Serialize for CommitmentPrefix {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "mocks"))]
 pub mod test_util {
     use crate::prelude::*;
     use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
@@ -168,3 +283,137 @@ pub mod test_util {
         RawMerkleProof { proofs: mproofs }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ics04_channel::packet::Sequence;
+    use crate::core::ics23_commitment::error::ErrorDetail;
+    use crate::core::ics23_commitment::merkle::apply_prefix;
+    use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+    use crate::core::ics24_host::path::{ConnectionsPath, Path, ReceiptsPath};
+    use crate::mock::context::MockIbcStore;
+    use crate::mock::state_root;
+    use crate::proofs::ProofErrorDetail;
+    use ibc_proto::protobuf::Protobuf;
+    use test_util::get_dummy_merkle_proof;
+
+    #[test]
+    fn proof_size_is_bounded() {
+        let proof = vec![0x0a; 16];
+        assert!(CommitmentProofBytes::try_from_bytes(proof.clone(), 16).is_ok());
+
+        let err = CommitmentProofBytes::try_from_bytes(proof, 15).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ProofErrorDetail::ProofTooLarge(e) if e.size == 16 && e.max_size == 15
+        ));
+    }
+
+    #[test]
+    fn proof_decoding_errors_report_offsets() {
+        let proof = get_dummy_merkle_proof();
+        let mut bytes: Vec<u8> = CommitmentProofBytes::try_from(proof.clone())
+            .unwrap()
+            .into();
+        assert_eq!(bytes, vec![0x0a, 0x00]);
+
+        let decoded =
+            RawMerkleProof::try_from(CommitmentProofBytes::try_from(bytes.clone()).unwrap());
+        assert_eq!(decoded.unwrap(), proof);
+
+        // A second proof whose only field has an invalid wire type.
+        bytes.extend([0x0a, 0x01, 0x0f]);
+        let err = RawMerkleProof::try_from(CommitmentProofBytes::try_from(bytes.clone()).unwrap())
+            .unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::InvalidCommitmentProof(e) if e.index == 1 && e.offset == 2
+        ));
+
+        // A third proof claiming more bytes than are left.
+        bytes.truncate(2);
+        bytes.extend([0x0a, 0x00, 0x0a, 0x05, 0x00]);
+        let err =
+            RawMerkleProof::try_from(CommitmentProofBytes::try_from(bytes).unwrap()).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::TruncatedMerkleProof(e) if e.offset == 4 && e.length == 5 && e.remaining == 1
+        ));
+    }
+
+    #[test]
+    fn proofs_verify_membership_and_absence_of_paths() {
+        let prefix = CommitmentPrefix::try_from(b"mock".to_vec()).unwrap();
+        let specs = state_root::proof_specs();
+
+        let mut ibc_store = MockIbcStore::default();
+        ibc_store
+            .connections
+            .insert(ConnectionId::default(), Default::default());
+        let value = ibc_store.connections[&ConnectionId::default()]
+            .encode_vec()
+            .unwrap();
+        let root = state_root::app_hash(&ibc_store, &prefix);
+
+        let prove = |path: Path| {
+            let proof = state_root::prove(&ibc_store, &prefix, path.clone()).unwrap();
+            let proof = CommitmentProofBytes::try_from(proof).unwrap();
+            (proof, apply_prefix(&prefix, vec![path.to_string()]))
+        };
+
+        let (proof, path) = prove(ConnectionsPath(ConnectionId::default()).into());
+        assert_eq!(proof.merkle_proof().unwrap().proofs.len(), 2);
+        proof
+            .verify_membership(&specs, &root, path.clone(), value.clone())
+            .unwrap();
+        assert!(proof
+            .verify_membership(&specs, &root, path.clone(), b"other".to_vec())
+            .is_err());
+        let err = proof
+            .verify_non_membership(&specs, &root, path.clone())
+            .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::KeyExists(_)));
+        // A present path does not hold the empty value.
+        let err = proof
+            .verify_membership(&specs, &root, path, vec![])
+            .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::KeyExists(_)));
+
+        // The receipt of a packet that was not received.
+        let (proof, path) = prove(
+            ReceiptsPath::new(PortId::default(), ChannelId::default(), Sequence::from(1)).into(),
+        );
+        proof
+            .verify_non_membership(&specs, &root, path.clone())
+            .unwrap();
+        proof
+            .verify_membership(&specs, &root, path.clone(), vec![])
+            .unwrap();
+        assert!(proof
+            .verify_membership(&specs, &root, path.clone(), value)
+            .is_err());
+        // Nor is its absence proven against another root, or under another prefix.
+        assert!(proof
+            .verify_non_membership(&specs, &CommitmentRoot::from(vec![1; 32]), path)
+            .is_err());
+        let other_prefix = CommitmentPrefix::try_from(b"other".to_vec()).unwrap();
+        let path = apply_prefix(
+            &other_prefix,
+            vec![Path::from(ReceiptsPath::new(
+                PortId::default(),
+                ChannelId::default(),
+                Sequence::from(1),
+            ))
+            .to_string()],
+        );
+        assert!(proof.verify_non_membership(&specs, &root, path).is_err());
+
+        // Malformed proofs fail to decode.
+        let proof = CommitmentProofBytes::try_from(vec![0x0a, 0x05, 0x00]).unwrap();
+        let err = proof
+            .verify_non_membership(&specs, &root, apply_prefix(&prefix, vec![]))
+            .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::TruncatedMerkleProof(_)));
+    }
+}
@@ -1,6 +1,8 @@
 use flex_error::{define_error, TraceError};
 use prost::DecodeError;
 
+use crate::prelude::*;
+
 define_error! {
     #[derive(Debug, PartialEq, Eq)]
     Error {
@@ -8,6 +10,26 @@ define_error! {
             [ TraceError<DecodeError> ]
             |_| { "invalid raw merkle proof" },
 
+        MalformedMerkleProof
+            { offset: usize }
+            [ TraceError<DecodeError> ]
+            |e| { format_args!("malformed merkle proof at byte offset {}", e.offset) },
+
+        TruncatedMerkleProof
+            { offset: usize, length: u64, remaining: usize }
+            |e| {
+                format_args!("commitment proof at byte offset {} has length {} but only {} bytes remain",
+                    e.offset, e.length, e.remaining)
+            },
+
+        InvalidCommitmentProof
+            { index: usize, offset: usize }
+            [ TraceError<DecodeError> ]
+            |e| {
+                format_args!("failed to decode commitment proof #{} at byte offset {}",
+                    e.index, e.offset)
+            },
+
         CommitmentProofDecodingFailed
             [ TraceError<DecodeError> ]
             |_| { "failed to decode commitment proof" },
@@ -33,7 +55,22 @@ define_error! {
         InvalidMerkleProof
             |_| { "invalid merkle proof" },
 
+        MalformedNonExistenceProof
+            { reason: String }
+            |e| { format_args!("malformed non-existence proof: {}", e.reason) },
+
+        KeyExists
+            { key: String }
+            |e| { format_args!("key {} is present in the tree, its absence cannot be proven", e.key) },
+
         VerificationFailure
-            |_| { "proof verification failed" }
+            |_| { "proof verification failed" },
+
+        EmptyProofSpecs
+            |_| { "empty proof specs" },
+
+        InvalidProofSpec
+            { index: usize, reason: String }
+            |e| { format_args!("invalid proof spec #{}: {}", e.index, e.reason) },
     }
 }
@@ -0,0 +1,71 @@
+//! The hash functions that ICS-23 proofs are verified with.
+//!
+//! The operations of a proof name the hash function that they apply (see `ics23::HashOp`), which
+//! [`HostHasher`] implements. Hosts provide their own implementation where the defaults, i.e. the
+//! Rust implementations of the `ics23` crate, do not fit: e.g. the host functions of a Substrate
+//! runtime, or the precompiles of a zkVM.
+
+use core::marker::PhantomData;
+
+use ics23::{HostFunctionsManager, HostFunctionsProvider};
+
+/// The hash functions that the operations of ICS-23 proofs apply. Every function defaults to
+/// the implementation of the `ics23` crate.
+pub trait HostHasher {
+    /// SHA-256, for the `Sha256` operations and the first round of the `Bitcoin` ones.
+    fn sha2_256(message: &[u8]) -> [u8; 32] {
+        HostFunctionsManager::sha2_256(message)
+    }
+
+    /// SHA-512, for the `Sha512` operations.
+    fn sha2_512(message: &[u8]) -> [u8; 64] {
+        HostFunctionsManager::sha2_512(message)
+    }
+
+    /// SHA-512/256, for the `Sha512256` operations.
+    fn sha2_512_truncated(message: &[u8]) -> [u8; 32] {
+        HostFunctionsManager::sha2_512_truncated(message)
+    }
+
+    /// SHA3-512, for the `Keccak` operations.
+    fn sha3_512(message: &[u8]) -> [u8; 64] {
+        HostFunctionsManager::sha3_512(message)
+    }
+
+    /// RIPEMD-160, for the `Ripemd160` operations and the second round of the `Bitcoin` ones.
+    fn ripemd160(message: &[u8]) -> [u8; 20] {
+        HostFunctionsManager::ripemd160(message)
+    }
+}
+
+/// The hasher of the `ics23` crate, which proofs are verified with unless a host provides its
+/// own.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultHostHasher;
+
+impl HostHasher for DefaultHostHasher {}
+
+/// Adapts a [`HostHasher`] to the host functions of the `ics23` crate.
+pub(crate) struct Ics23HostFunctions<H>(PhantomData<H>);
+
+impl<H: HostHasher> HostFunctionsProvider for Ics23HostFunctions<H> {
+    fn sha2_256(message: &[u8]) -> [u8; 32] {
+        H::sha2_256(message)
+    }
+
+    fn sha2_512(message: &[u8]) -> [u8; 64] {
+        H::sha2_512(message)
+    }
+
+    fn sha2_512_truncated(message: &[u8]) -> [u8; 32] {
+        H::sha2_512_truncated(message)
+    }
+
+    fn sha3_512(message: &[u8]) -> [u8; 64] {
+        H::sha3_512(message)
+    }
+
+    fn ripemd160(message: &[u8]) -> [u8; 20] {
+        H::ripemd160(message)
+    }
+}
@@ -7,11 +7,12 @@ use ibc_proto::ibc::core::commitment::v1::MerkleRoot;
 use ics23::commitment_proof::Proof;
 use ics23::{
     calculate_existence_root, verify_membership, verify_non_membership, CommitmentProof,
-    NonExistenceProof,
+    ExistenceProof, NonExistenceProof,
 };
 
 use crate::core::ics23_commitment::commitment::{CommitmentPrefix, CommitmentRoot};
 use crate::core::ics23_commitment::error::Error;
+use crate::core::ics23_commitment::hasher::{DefaultHostHasher, HostHasher, Ics23HostFunctions};
 use crate::core::ics23_commitment::specs::ProofSpecs;
 
 pub fn apply_prefix(prefix: &CommitmentPrefix, mut path: Vec<String>) -> MerklePath {
@@ -68,6 +69,8 @@ impl From<MerkleProof> for RawMerkleProof {
 }
 
 impl MerkleProof {
+    /// Verifies that the proof proves `value` at `keys` under `root`, hashing with the
+    /// [`DefaultHostHasher`].
     pub fn verify_membership(
         &self,
         specs: &ProofSpecs,
@@ -75,6 +78,18 @@ impl MerkleProof {
         keys: MerklePath,
         value: Vec<u8>,
         start_index: usize,
+    ) -> Result<(), Error> {
+        self.verify_membership_with::<DefaultHostHasher>(specs, root, keys, value, start_index)
+    }
+
+    /// Verifies that the proof proves `value` at `keys` under `root`, hashing with `H`.
+    pub fn verify_membership_with<H: HostHasher>(
+        &self,
+        specs: &ProofSpecs,
+        root: MerkleRoot,
+        keys: MerklePath,
+        value: Vec<u8>,
+        start_index: usize,
     ) -> Result<(), Error> {
         // validate arguments
         if self.proofs.is_empty() {
@@ -107,11 +122,10 @@ impl MerkleProof {
         {
             match &proof.proof {
                 Some(Proof::Exist(existence_proof)) => {
-                    subroot =
-                        calculate_existence_root::<ics23::HostFunctionsManager>(existence_proof)
-                            .map_err(|_| Error::invalid_merkle_proof())?;
+                    subroot = calculate_existence_root::<Ics23HostFunctions<H>>(existence_proof)
+                        .map_err(|_| Error::invalid_merkle_proof())?;
 
-                    if !verify_membership::<ics23::HostFunctionsManager>(
+                    if !verify_membership::<Ics23HostFunctions<H>>(
                         proof,
                         spec,
                         &subroot,
@@ -133,11 +147,23 @@ impl MerkleProof {
         Ok(())
     }
 
+    /// Verifies that the proof proves the absence of `keys` under `root`, hashing with the
+    /// [`DefaultHostHasher`].
     pub fn verify_non_membership(
         &self,
         specs: &ProofSpecs,
         root: MerkleRoot,
         keys: MerklePath,
+    ) -> Result<(), Error> {
+        self.verify_non_membership_with::<DefaultHostHasher>(specs, root, keys)
+    }
+
+    /// Verifies that the proof proves the absence of `keys` under `root`, hashing with `H`.
+    pub fn verify_non_membership_with<H: HostHasher>(
+        &self,
+        specs: &ProofSpecs,
+        root: MerkleRoot,
+        keys: MerklePath,
     ) -> Result<(), Error> {
         // validate arguments
         if self.proofs.is_empty() {
@@ -156,45 +182,88 @@ impl MerkleProof {
         }
 
         // verify the absence of key in lowest subtree
-        let proof = self.proofs.get(0).ok_or_else(Error::invalid_merkle_proof)?;
-        let spec = ics23_specs.get(0).ok_or_else(Error::invalid_merkle_proof)?;
+        let proof = self
+            .proofs
+            .first()
+            .ok_or_else(Error::invalid_merkle_proof)?;
+        let spec = ics23_specs
+            .first()
+            .ok_or_else(Error::invalid_merkle_proof)?;
         // keys are represented from root-to-leaf
         let key = keys
             .key_path
             .get(num - 1)
             .ok_or_else(Error::invalid_merkle_proof)?;
-        match &proof.proof {
-            Some(Proof::Nonexist(non_existence_proof)) => {
-                let subroot = calculate_non_existence_root(non_existence_proof)?;
-
-                if !verify_non_membership::<ics23::HostFunctionsManager>(
-                    proof,
-                    spec,
-                    &subroot,
-                    key.as_bytes(),
-                ) {
-                    return Err(Error::verification_failure());
-                }
+        let subroot = verify_non_existence_with::<H>(proof, spec, key.as_bytes())?;
 
-                // verify membership proofs starting from index 1 with value = subroot
-                self.verify_membership(specs, root, keys, subroot, 1)
-            }
-            _ => Err(Error::invalid_merkle_proof()),
-        }
+        // verify membership proofs starting from index 1 with value = subroot
+        self.verify_membership_with::<H>(specs, root, keys, subroot, 1)
     }
 }
 
-// TODO move to ics23
-fn calculate_non_existence_root(proof: &NonExistenceProof) -> Result<Vec<u8>, Error> {
-    if let Some(left) = &proof.left {
-        calculate_existence_root::<ics23::HostFunctionsManager>(left)
-            .map_err(|_| Error::invalid_merkle_proof())
-    } else if let Some(right) = &proof.right {
-        calculate_existence_root::<ics23::HostFunctionsManager>(right)
-            .map_err(|_| Error::invalid_merkle_proof())
-    } else {
-        Err(Error::invalid_merkle_proof())
+/// Verifies that `proof` proves the absence of `key` from a tree of the given `spec`, and returns
+/// the root of the tree.
+///
+/// The absence of a key is proven by its neighbours in the tree, the closest keys below and above
+/// it, only one of which exists if the key lies before the first or after the last key of the
+/// tree. The absence of a key from an empty tree thus cannot be proven.
+pub fn verify_non_existence(
+    proof: &CommitmentProof,
+    spec: &ics23::ProofSpec,
+    key: &[u8],
+) -> Result<Vec<u8>, Error> {
+    verify_non_existence_with::<DefaultHostHasher>(proof, spec, key)
+}
+
+/// Same as [`verify_non_existence`], hashing with `H`.
+pub fn verify_non_existence_with<H: HostHasher>(
+    proof: &CommitmentProof,
+    spec: &ics23::ProofSpec,
+    key: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let malformed = |reason: &str| Error::malformed_non_existence_proof(reason.to_string());
+    let key_exists = || Error::key_exists(String::from_utf8_lossy(key).to_string());
+
+    let non_existence_proof = match &proof.proof {
+        Some(Proof::Nonexist(non_existence_proof)) => non_existence_proof,
+        Some(Proof::Exist(existence_proof)) if existence_proof.key == key => {
+            return Err(key_exists())
+        }
+        _ => return Err(malformed("not a non-existence proof")),
+    };
+    if non_existence_proof.key != key {
+        return Err(malformed("proves the absence of another key"));
+    }
+
+    let NonExistenceProof { left, right, .. } = non_existence_proof;
+    if left
+        .iter()
+        .chain(right)
+        .any(|neighbour| neighbour.key == key)
+    {
+        return Err(key_exists());
     }
+    let neighbour_root = |neighbour: &ExistenceProof| {
+        calculate_existence_root::<Ics23HostFunctions<H>>(neighbour)
+            .map_err(|e| Error::malformed_non_existence_proof(format!("invalid neighbour: {}", e)))
+    };
+    let root = match (left, right) {
+        (Some(left), Some(right)) => {
+            let root = neighbour_root(left)?;
+            if neighbour_root(right)? != root {
+                return Err(malformed("neighbours are proven against different roots"));
+            }
+            root
+        }
+        (Some(neighbour), None) | (None, Some(neighbour)) => neighbour_root(neighbour)?,
+        (None, None) => return Err(malformed("no neighbours, as for an empty tree")),
+    };
+
+    if !verify_non_membership::<Ics23HostFunctions<H>>(proof, spec, &root, key) {
+        return Err(Error::verification_failure());
+    }
+
+    Ok(root)
 }
 
 // Merkle Proof serialization notes:
@@ -262,3 +331,183 @@ pub fn convert_tm_to_ics_merkle_proof(tm_proof: &TendermintProof) -> Result<Merk
 
     Ok(MerkleProof::from(RawMerkleProof { proofs }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics23_commitment::error::ErrorDetail;
+    use crate::mock::state_root::SimpleMerkleStore;
+
+    fn store(keys: &[&str]) -> SimpleMerkleStore {
+        let mut store = SimpleMerkleStore::default();
+        for key in keys {
+            store.insert(*key, key.as_bytes().to_vec());
+        }
+        store
+    }
+
+    fn non_existence(non_existence_proof: NonExistenceProof) -> CommitmentProof {
+        CommitmentProof {
+            proof: Some(Proof::Nonexist(non_existence_proof)),
+        }
+    }
+
+    fn verify(proof: &CommitmentProof, key: &str) -> Result<Vec<u8>, Error> {
+        verify_non_existence(proof, &ics23::tendermint_spec(), key.as_bytes())
+    }
+
+    #[test]
+    fn test_non_existence_at_the_edges() {
+        let store = store(&["b", "d", "f"]);
+        let root = store.root();
+
+        // Before the first key, between two keys, after the last key.
+        for key in ["a", "c", "g"] {
+            let proof = non_existence(store.non_existence_proof(key.as_bytes()).unwrap());
+            assert_eq!(verify(&proof, key).unwrap(), root);
+        }
+
+        let store = self::store(&["b"]);
+        for key in ["a", "c"] {
+            let proof = non_existence(store.non_existence_proof(key.as_bytes()).unwrap());
+            assert_eq!(verify(&proof, key).unwrap(), store.root());
+        }
+
+        // Nothing proves the absence of a key from an empty tree.
+        let proof = non_existence(NonExistenceProof {
+            key: b"a".to_vec(),
+            left: None,
+            right: None,
+        });
+        assert!(matches!(
+            verify(&proof, "a").unwrap_err().detail(),
+            ErrorDetail::MalformedNonExistenceProof(_)
+        ));
+    }
+
+    #[test]
+    fn test_non_existence_of_present_key() {
+        let store = store(&["b", "d", "f"]);
+
+        let proof = CommitmentProof {
+            proof: Some(Proof::Exist(store.existence_proof(b"d").unwrap())),
+        };
+        assert!(matches!(
+            verify(&proof, "d").unwrap_err().detail(),
+            ErrorDetail::KeyExists(_)
+        ));
+
+        // The key passed off as its own neighbour.
+        let proof = non_existence(NonExistenceProof {
+            key: b"d".to_vec(),
+            left: store.existence_proof(b"b"),
+            right: store.existence_proof(b"d"),
+        });
+        assert!(matches!(
+            verify(&proof, "d").unwrap_err().detail(),
+            ErrorDetail::KeyExists(_)
+        ));
+    }
+
+    #[test]
+    fn test_malformed_non_existence_proofs() {
+        let store = store(&["b", "d", "f"]);
+
+        let proof = non_existence(store.non_existence_proof(b"c").unwrap());
+        assert!(matches!(
+            verify(&proof, "e").unwrap_err().detail(),
+            ErrorDetail::MalformedNonExistenceProof(_)
+        ));
+
+        let proof = CommitmentProof {
+            proof: Some(Proof::Exist(store.existence_proof(b"d").unwrap())),
+        };
+        assert!(matches!(
+            verify(&proof, "c").unwrap_err().detail(),
+            ErrorDetail::MalformedNonExistenceProof(_)
+        ));
+
+        // Neighbours from different trees.
+        let other = self::store(&["b", "f"]);
+        let proof = non_existence(NonExistenceProof {
+            key: b"c".to_vec(),
+            left: store.existence_proof(b"b"),
+            right: other.existence_proof(b"f"),
+        });
+        assert!(matches!(
+            verify(&proof, "c").unwrap_err().detail(),
+            ErrorDetail::MalformedNonExistenceProof(_)
+        ));
+
+        // Neighbours that are not adjacent, hiding the key in between.
+        let store = self::store(&["b", "c", "d"]);
+        let proof = non_existence(NonExistenceProof {
+            key: b"c".to_vec(),
+            left: store.existence_proof(b"b"),
+            right: store.existence_proof(b"d"),
+        });
+        assert!(matches!(
+            verify(&proof, "c").unwrap_err().detail(),
+            ErrorDetail::VerificationFailure(_)
+        ));
+    }
+
+    /// A hasher that computes SHA-256 on its own, as hosts plugging in their host functions do.
+    struct Sha256Hasher;
+
+    impl HostHasher for Sha256Hasher {
+        fn sha2_256(message: &[u8]) -> [u8; 32] {
+            use sha2::Digest;
+            sha2::Sha256::digest(message).into()
+        }
+    }
+
+    /// A hasher whose SHA-256 differs from that of the tree the proofs are for.
+    struct BrokenHasher;
+
+    impl HostHasher for BrokenHasher {
+        fn sha2_256(_message: &[u8]) -> [u8; 32] {
+            [0; 32]
+        }
+    }
+
+    #[test]
+    fn test_verification_with_host_hasher() {
+        let store = store(&["b", "d", "f"]);
+        let specs = ProofSpecs::from(vec![ics23::tendermint_spec()]);
+        let root = MerkleRoot { hash: store.root() };
+        let keys = MerklePath {
+            key_path: vec!["d".to_string()],
+        };
+
+        let proof = MerkleProof {
+            proofs: vec![CommitmentProof {
+                proof: Some(Proof::Exist(store.existence_proof(b"d").unwrap())),
+            }],
+        };
+        assert!(proof
+            .verify_membership_with::<Sha256Hasher>(
+                &specs,
+                root.clone(),
+                keys.clone(),
+                b"d".to_vec(),
+                0
+            )
+            .is_ok());
+        assert!(proof
+            .verify_membership_with::<BrokenHasher>(&specs, root, keys, b"d".to_vec(), 0)
+            .is_err());
+
+        let proof = non_existence(store.non_existence_proof(b"c").unwrap());
+        assert_eq!(
+            verify_non_existence_with::<Sha256Hasher>(&proof, &ics23::tendermint_spec(), b"c")
+                .unwrap(),
+            store.root()
+        );
+        assert!(
+            verify_non_existence_with::<BrokenHasher>(&proof, &ics23::tendermint_spec(), b"c")
+                .map_or(true, |root| root != store.root())
+        );
+    }
+}
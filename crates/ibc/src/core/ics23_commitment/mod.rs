@@ -3,6 +3,7 @@
 
 pub mod commitment;
 pub mod error;
+pub mod hasher;
 pub mod merkle;
 pub mod mock;
 pub mod specs;
@@ -1,3 +1,4 @@
+use crate::core::ics23_commitment::error::Error;
 use crate::prelude::*;
 use ibc_proto::ics23::{InnerSpec as IbcInnerSpec, LeafOp as IbcLeafOp, ProofSpec as IbcProofSpec};
 use ics23::{InnerSpec as Ics23InnerSpec, LeafOp as Ics23LeafOp, ProofSpec as Ics23ProofSpec};
@@ -23,9 +24,29 @@ impl ProofSpecs {
         .into()
     }
 
+    /// Returns the specification for the proofs of a sparse Merkle tree, as of hosts that keep
+    /// their state in a single SMT rather than in Cosmos-SDK stores
+    pub fn smt() -> Self {
+        vec![ics23::smt_spec()].into()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Checks that the specifications are well-formed, i.e. that proofs can be verified against
+    /// them, without restricting them to any predefined set: hosts are free to pick the hash
+    /// functions and the tree parameters of their commitments.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.0.is_empty() {
+            return Err(Error::empty_proof_specs());
+        }
+        for (index, spec) in self.0.iter().enumerate() {
+            spec.validate()
+                .map_err(|reason| Error::invalid_proof_spec(index, reason.to_string()))?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for ProofSpecs {
@@ -66,6 +87,37 @@ impl From<ProofSpecs> for Vec<IbcProofSpec> {
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 struct ProofSpec(IbcProofSpec);
 
+impl ProofSpec {
+    fn validate(&self) -> Result<(), &'static str> {
+        let spec = &self.0;
+        if spec.leaf_spec.is_none() {
+            return Err("missing leaf spec");
+        }
+        let inner_spec = spec.inner_spec.as_ref().ok_or("missing inner spec")?;
+        if spec.min_depth < 0 || spec.max_depth < 0 {
+            return Err("negative depth");
+        }
+        if spec.max_depth > 0 && spec.min_depth > spec.max_depth {
+            return Err("min depth exceeds max depth");
+        }
+        if inner_spec.child_size <= 0 {
+            return Err("child size must be positive");
+        }
+        if inner_spec.min_prefix_length < 0
+            || inner_spec.min_prefix_length > inner_spec.max_prefix_length
+        {
+            return Err("invalid prefix length bounds");
+        }
+        // The child order must be a permutation of the children of an inner node.
+        let mut child_order = inner_spec.child_order.clone();
+        child_order.sort_unstable();
+        if child_order.len() < 2 || !child_order.iter().zip(0..).all(|(&c, i)| c == i) {
+            return Err("child order must be a permutation of at least two children");
+        }
+        Ok(())
+    }
+}
+
 impl From<Ics23ProofSpec> for ProofSpec {
     fn from(spec: Ics23ProofSpec) -> Self {
         Self(IbcProofSpec {
@@ -146,3 +198,49 @@ impl From<InnerSpec> for Ics23InnerSpec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics23_commitment::error::ErrorDetail;
+
+    #[test]
+    fn test_proof_specs_validation() {
+        for specs in [
+            ProofSpecs::cosmos(),
+            ProofSpecs::smt(),
+            crate::mock::state_root::proof_specs(),
+        ] {
+            assert!(specs.validate().is_ok());
+        }
+
+        assert!(matches!(
+            ProofSpecs::from(Vec::<Ics23ProofSpec>::new())
+                .validate()
+                .unwrap_err()
+                .detail(),
+            ErrorDetail::EmptyProofSpecs(_)
+        ));
+
+        let invalid = |f: fn(&mut Ics23ProofSpec)| {
+            let mut spec = ics23::tendermint_spec();
+            f(&mut spec);
+            ProofSpecs::from(vec![ics23::iavl_spec(), spec]).validate()
+        };
+        let cases: [fn(&mut Ics23ProofSpec); 6] = [
+            |spec| spec.leaf_spec = None,
+            |spec| spec.inner_spec = None,
+            |spec| (spec.min_depth, spec.max_depth) = (3, 2),
+            |spec| spec.inner_spec.as_mut().unwrap().child_size = 0,
+            |spec| spec.inner_spec.as_mut().unwrap().max_prefix_length = 0,
+            |spec| spec.inner_spec.as_mut().unwrap().child_order = vec![0, 2],
+        ];
+        for case in cases {
+            match invalid(case).unwrap_err().detail() {
+                ErrorDetail::InvalidProofSpec(e) => assert_eq!(e.index, 1),
+                e => panic!("unexpected error: {:?}", e),
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use core::borrow::Borrow;
 use core::convert::{From, Infallible};
 use core::fmt::{Debug, Display, Error as FmtError, Formatter};
 use core::str::FromStr;
@@ -113,7 +114,13 @@ impl FromStr for ChainId {
 
 impl Display for ChainId {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-        write!(f, "{}", self.id)
+        f.write_str(&self.id)
+    }
+}
+
+impl AsRef<str> for ChainId {
+    fn as_ref(&self) -> &str {
+        &self.id
     }
 }
 
@@ -157,7 +164,18 @@ impl ClientId {
     /// tm_client_id.map(|id| { assert_eq!(&id, "07-tendermint-0") });
     /// ```
     pub fn new(client_type: ClientType, counter: u64) -> Result<Self, ValidationError> {
-        let prefix = client_type.as_str();
+        Self::with_prefix(client_type.as_str(), counter)
+    }
+
+    /// Builds a new client identifier from a custom `prefix` and a `counter`, separated by a dash
+    /// "-". Fails if the result is not a valid ICS-024 client identifier.
+    ///
+    /// ```
+    /// # use ibc::core::ics24_host::identifier::ClientId;
+    /// let client_id = ClientId::with_prefix("solo-client", 3).unwrap();
+    /// assert_eq!(&client_id, "solo-client-3");
+    /// ```
+    pub fn with_prefix(prefix: &str, counter: u64) -> Result<Self, ValidationError> {
         let id = format!("{}-{}", prefix, counter);
         Self::from_str(id.as_str())
     }
@@ -176,7 +194,20 @@ impl ClientId {
 /// This implementation provides a `to_string` method.
 impl Display for ClientId {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-        write!(f, "{}", self.0)
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ClientId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Allows looking up maps keyed by this identifier with a `&str`, without allocating.
+impl Borrow<str> for ClientId {
+    fn borrow(&self) -> &str {
+        &self.0
     }
 }
 
@@ -223,11 +254,23 @@ impl ConnectionId {
     /// assert_eq!(&conn_id, "connection-11");
     /// ```
     pub fn new(counter: u64) -> Self {
-        let id = format!("{}-{}", Self::prefix(), counter);
-        Self::from_str(id.as_str()).unwrap()
+        Self::with_prefix(Self::prefix(), counter).unwrap()
+    }
+
+    /// Builds a new connection identifier from a custom `prefix` and a `counter`, separated by a
+    /// dash "-". Fails if the result is not a valid ICS-024 connection identifier.
+    ///
+    /// ```
+    /// # use ibc::core::ics24_host::identifier::ConnectionId;
+    /// let conn_id = ConnectionId::with_prefix("rollup1-connection", 11).unwrap();
+    /// assert_eq!(&conn_id, "rollup1-connection-11");
+    /// ```
+    pub fn with_prefix(prefix: &str, counter: u64) -> Result<Self, ValidationError> {
+        let id = format!("{}-{}", prefix, counter);
+        Self::from_str(id.as_str())
     }
 
-    /// Returns the static prefix to be used across all connection identifiers.
+    /// Returns the default prefix of connection identifiers.
     pub fn prefix() -> &'static str {
         "connection"
     }
@@ -246,7 +289,20 @@ impl ConnectionId {
 /// This implementation provides a `to_string` method.
 impl Display for ConnectionId {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-        write!(f, "{}", self.0)
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ConnectionId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Allows looking up maps keyed by this identifier with a `&str`, without allocating.
+impl Borrow<str> for ConnectionId {
+    fn borrow(&self) -> &str {
+        &self.0
     }
 }
 
@@ -287,6 +343,11 @@ impl PortId {
         Self("transfer".to_string())
     }
 
+    /// Infallible creation of the well-known NFT transfer port
+    pub fn nft_transfer() -> Self {
+        Self("nft-transfer".to_string())
+    }
+
     /// Get this identifier as a borrowed `&str`
     pub fn as_str(&self) -> &str {
         &self.0
@@ -301,7 +362,14 @@ impl PortId {
 /// This implementation provides a `to_string` method.
 impl Display for PortId {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-        write!(f, "{}", self.0)
+        f.write_str(&self.0)
+    }
+}
+
+/// Allows looking up maps keyed by this identifier with a `&str`, without allocating.
+impl Borrow<str> for PortId {
+    fn borrow(&self) -> &str {
+        &self.0
     }
 }
 
@@ -329,8 +397,6 @@ impl Default for PortId {
 pub struct ChannelId(String);
 
 impl ChannelId {
-    const PREFIX: &'static str = "channel-";
-
     /// Builds a new channel identifier. Like client and connection identifiers, channel ids are
     /// deterministically formed from two elements: a prefix `prefix`, and a monotonically
     /// increasing `counter`, separated by a dash "-".
@@ -343,10 +409,28 @@ impl ChannelId {
     /// assert_eq!(chan_id.to_string(), "channel-27");
     /// ```
     pub fn new(counter: u64) -> Self {
-        let id = format!("{}{}", Self::PREFIX, counter);
+        let id = format!("{}-{}", Self::prefix(), counter);
         Self(id)
     }
 
+    /// Builds a new channel identifier from a custom `prefix` and a `counter`, separated by a dash
+    /// "-". Fails if the result is not a valid ICS-024 channel identifier.
+    ///
+    /// ```
+    /// # use ibc::core::ics24_host::identifier::ChannelId;
+    /// let chan_id = ChannelId::with_prefix("wasm-channel", 27).unwrap();
+    /// assert_eq!(chan_id.to_string(), "wasm-channel-27");
+    /// ```
+    pub fn with_prefix(prefix: &str, counter: u64) -> Result<Self, ValidationError> {
+        let id = format!("{}-{}", prefix, counter);
+        Self::from_str(id.as_str())
+    }
+
+    /// Returns the default prefix of channel identifiers.
+    pub fn prefix() -> &'static str {
+        "channel"
+    }
+
     /// Get this identifier as a borrowed `&str`
     pub fn as_str(&self) -> &str {
         &self.0
@@ -361,7 +445,14 @@ impl ChannelId {
 /// This implementation provides a `to_string` method.
 impl Display for ChannelId {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-        write!(f, "{}", self.0)
+        f.write_str(&self.0)
+    }
+}
+
+/// Allows looking up maps keyed by this identifier with a `&str`, without allocating.
+impl Borrow<str> for ChannelId {
+    fn borrow(&self) -> &str {
+        &self.0
     }
 }
 
@@ -420,3 +511,55 @@ impl Display for PortChannelId {
         write!(f, "{}/{}", self.port_id, self.channel_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::collections::BTreeMap;
+    use std::collections::HashMap;
+
+    #[test]
+    fn identifiers_key_str_lookups() {
+        let ports: BTreeMap<PortId, u8> = [(PortId::transfer(), 1)].into_iter().collect();
+        assert_eq!(ports.get("transfer"), Some(&1));
+
+        let channels: HashMap<ChannelId, u8> = [(ChannelId::new(7), 2)].into_iter().collect();
+        assert_eq!(channels.get("channel-7"), Some(&2));
+
+        let connections: HashMap<ConnectionId, u8> =
+            [(ConnectionId::new(3), 3)].into_iter().collect();
+        assert_eq!(connections.get("connection-3"), Some(&3));
+
+        let clients: BTreeMap<ClientId, u8> = [(ClientId::default(), 4)].into_iter().collect();
+        assert_eq!(clients.get("07-tendermint-0"), Some(&4));
+    }
+
+    #[test]
+    fn display_matches_as_str() {
+        let chain_id = ChainId::new("ibc".to_string(), 1);
+        assert_eq!(chain_id.to_string(), chain_id.as_str());
+        assert_eq!(
+            ClientId::default().to_string(),
+            ClientId::default().as_str()
+        );
+        assert_eq!(PortId::transfer().to_string(), PortId::transfer().as_str());
+    }
+
+    #[test]
+    fn prefixed_identifiers_are_validated() {
+        assert_eq!(
+            ChannelId::with_prefix(ChannelId::prefix(), 7).unwrap(),
+            ChannelId::new(7)
+        );
+        assert_eq!(
+            ConnectionId::with_prefix(ConnectionId::prefix(), 3).unwrap(),
+            ConnectionId::new(3)
+        );
+
+        // Invalid characters, and too short or too long identifiers are rejected.
+        assert!(ChannelId::with_prefix("chan/nel", 0).is_err());
+        assert!(ClientId::with_prefix("solo", 0).is_err());
+        assert!(ConnectionId::with_prefix(&"c".repeat(64), 0).is_err());
+    }
+}
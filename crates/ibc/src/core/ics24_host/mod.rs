@@ -2,8 +2,10 @@
 //! state machine hosting an IBC-enabled chain must implement.
 
 pub use path::{ClientUpgradePath, Path, IBC_QUERY_PATH, SDK_UPGRADE_QUERY_PATH};
+pub use store_prefix::StorePrefix;
 
 pub mod error;
 pub mod identifier;
 pub mod path;
+pub mod store_prefix;
 pub mod validate;
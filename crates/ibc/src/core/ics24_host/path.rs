@@ -6,6 +6,7 @@ use crate::prelude::*;
 ///
 use core::str::FromStr;
 
+use crate::core::ics02_client::height::Height;
 use crate::core::ics04_channel::packet::Sequence;
 use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
 
@@ -27,6 +28,11 @@ const UPGRADED_CLIENT_STATE: &str = "upgradedClient";
 /// - The key identifying the upgraded consensus state
 const UPGRADED_CLIENT_CONSENSUS_STATE: &str = "upgradedConsState";
 
+/// Keys of the counters of the identifiers of the clients, connections and channels
+const NEXT_CLIENT_SEQUENCE: &str = "nextClientSequence";
+const NEXT_CONNECTION_SEQUENCE: &str = "nextConnectionSequence";
+const NEXT_CHANNEL_SEQUENCE: &str = "nextChannelSequence";
+
 /// The Path enum abstracts out the different sub-paths.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From, Display)]
 pub enum Path {
@@ -34,6 +40,11 @@ pub enum Path {
     ClientState(ClientStatePath),
     ClientConsensusState(ClientConsensusStatePath),
     ClientConnections(ClientConnectionsPath),
+    ClientUpdateTime(ClientUpdateTimePath),
+    ClientUpdateHeight(ClientUpdateHeightPath),
+    NextClientSequence(NextClientSequencePath),
+    NextConnectionSequence(NextConnectionSequencePath),
+    NextChannelSequence(NextChannelSequencePath),
     Connections(ConnectionsPath),
     Ports(PortsPath),
     ChannelEnds(ChannelEndsPath),
@@ -44,13 +55,14 @@ pub enum Path {
     Acks(AcksPath),
     Receipts(ReceiptsPath),
     Upgrade(ClientUpgradePath),
+    ChannelUpgrade(ChannelUpgradePath),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "clients/{}/clientType", _0)]
 pub struct ClientTypePath(pub ClientId);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "clients/{}/clientState", _0)]
 pub struct ClientStatePath(pub ClientId);
 
@@ -67,35 +79,126 @@ pub struct ClientConsensusStatePath {
     pub height: u64,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+impl ClientConsensusStatePath {
+    pub fn new(client_id: ClientId, height: Height) -> Self {
+        Self {
+            client_id,
+            epoch: height.revision_number(),
+            height: height.revision_height(),
+        }
+    }
+}
+
+impl From<(ClientId, Height)> for ClientConsensusStatePath {
+    fn from((client_id, height): (ClientId, Height)) -> Self {
+        Self::new(client_id, height)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "clients/{}/connections", _0)]
 pub struct ClientConnectionsPath(pub ClientId);
 
+/// The host time at which a consensus state of a client was stored, as recorded by ibc-go.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(
+    fmt = "clients/{}/consensusStates/{}-{}/processedTime",
+    "client_id",
+    "epoch",
+    "height"
+)]
+pub struct ClientUpdateTimePath {
+    pub client_id: ClientId,
+    pub epoch: u64,
+    pub height: u64,
+}
+
+impl ClientUpdateTimePath {
+    pub fn new(client_id: ClientId, height: Height) -> Self {
+        Self {
+            client_id,
+            epoch: height.revision_number(),
+            height: height.revision_height(),
+        }
+    }
+}
+
+impl From<(ClientId, Height)> for ClientUpdateTimePath {
+    fn from((client_id, height): (ClientId, Height)) -> Self {
+        Self::new(client_id, height)
+    }
+}
+
+/// The host height at which a consensus state of a client was stored, as recorded by ibc-go.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(
+    fmt = "clients/{}/consensusStates/{}-{}/processedHeight",
+    "client_id",
+    "epoch",
+    "height"
+)]
+pub struct ClientUpdateHeightPath {
+    pub client_id: ClientId,
+    pub epoch: u64,
+    pub height: u64,
+}
+
+impl ClientUpdateHeightPath {
+    pub fn new(client_id: ClientId, height: Height) -> Self {
+        Self {
+            client_id,
+            epoch: height.revision_number(),
+            height: height.revision_height(),
+        }
+    }
+}
+
+impl From<(ClientId, Height)> for ClientUpdateHeightPath {
+    fn from((client_id, height): (ClientId, Height)) -> Self {
+        Self::new(client_id, height)
+    }
+}
+
+/// The counter from which the identifier of the next client is derived.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "{}", NEXT_CLIENT_SEQUENCE)]
+pub struct NextClientSequencePath;
+
+/// The counter from which the identifier of the next connection is derived.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "{}", NEXT_CONNECTION_SEQUENCE)]
+pub struct NextConnectionSequencePath;
+
+/// The counter from which the identifier of the next channel is derived.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "{}", NEXT_CHANNEL_SEQUENCE)]
+pub struct NextChannelSequencePath;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "connections/{}", _0)]
 pub struct ConnectionsPath(pub ConnectionId);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "ports/{}", _0)]
 pub struct PortsPath(pub PortId);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "channelEnds/ports/{}/channels/{}", _0, _1)]
 pub struct ChannelEndsPath(pub PortId, pub ChannelId);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "nextSequenceSend/ports/{}/channels/{}", _0, _1)]
 pub struct SeqSendsPath(pub PortId, pub ChannelId);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "nextSequenceRecv/ports/{}/channels/{}", _0, _1)]
 pub struct SeqRecvsPath(pub PortId, pub ChannelId);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(fmt = "nextSequenceAck/ports/{}/channels/{}", _0, _1)]
 pub struct SeqAcksPath(pub PortId, pub ChannelId);
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(
     fmt = "commitments/ports/{}/channels/{}/sequences/{}",
     "port_id",
@@ -108,7 +211,17 @@ pub struct CommitmentsPath {
     pub sequence: Sequence,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+impl CommitmentsPath {
+    pub fn new(port_id: PortId, channel_id: ChannelId, sequence: Sequence) -> Self {
+        Self {
+            port_id,
+            channel_id,
+            sequence,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(
     fmt = "acks/ports/{}/channels/{}/sequences/{}",
     "port_id",
@@ -121,7 +234,17 @@ pub struct AcksPath {
     pub sequence: Sequence,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+impl AcksPath {
+    pub fn new(port_id: PortId, channel_id: ChannelId, sequence: Sequence) -> Self {
+        Self {
+            port_id,
+            channel_id,
+            sequence,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From)]
 #[display(
     fmt = "receipts/ports/{}/channels/{}/sequences/{}",
     "port_id",
@@ -134,6 +257,16 @@ pub struct ReceiptsPath {
     pub sequence: Sequence,
 }
 
+impl ReceiptsPath {
+    pub fn new(port_id: PortId, channel_id: ChannelId, sequence: Sequence) -> Self {
+        Self {
+            port_id,
+            channel_id,
+            sequence,
+        }
+    }
+}
+
 /// Paths that are specific for client upgrades.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
 pub enum ClientUpgradePath {
@@ -148,6 +281,17 @@ pub enum ClientUpgradePath {
     UpgradedClientConsensusState(u64),
 }
 
+/// Paths that are specific for channel upgrades.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+pub enum ChannelUpgradePath {
+    /// The upgrade proposed for a channel end.
+    #[display(fmt = "channelUpgrades/upgrades/ports/{}/channels/{}", _0, _1)]
+    Upgrade(PortId, ChannelId),
+    /// The receipt of the last upgrade of a channel end that was cancelled.
+    #[display(fmt = "channelUpgrades/upgradeError/ports/{}/channels/{}", _0, _1)]
+    UpgradeError(PortId, ChannelId),
+}
+
 /// Sub-paths which are not part of the specification, but are still
 /// useful to represent for parsing purposes.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -157,15 +301,31 @@ enum SubPath {
 }
 
 impl Path {
-    /// Indication if the path is provable.
+    /// Indication if the path is provable, i.e. if counterparties verify the state stored under it.
     pub fn is_provable(&self) -> bool {
-        !matches!(&self, Path::ClientConnections(_) | Path::Ports(_))
+        !matches!(
+            &self,
+            Path::ClientConnections(_)
+                | Path::ClientUpdateTime(_)
+                | Path::ClientUpdateHeight(_)
+                | Path::NextClientSequence(_)
+                | Path::NextConnectionSequence(_)
+                | Path::NextChannelSequence(_)
+                | Path::Ports(_)
+        )
     }
 
-    /// into_bytes implementation
+    /// Returns the store key of the path, i.e. its UTF-8 encoded string.
     pub fn into_bytes(self) -> Vec<u8> {
         self.to_string().into_bytes()
     }
+
+    /// Parses the path of a store key, as returned by [`Path::into_bytes`].
+    pub fn from_bytes(key: &[u8]) -> Result<Self, PathError> {
+        core::str::from_utf8(key)
+            .map_err(|_| PathError::parse_failure(String::from_utf8_lossy(key).into_owned()))?
+            .parse()
+    }
 }
 
 define_error! {
@@ -184,7 +344,8 @@ impl FromStr for Path {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let components: Vec<&str> = s.split('/').collect();
 
-        parse_client_paths(&components)
+        parse_next_sequences(&components)
+            .or_else(|| parse_client_paths(&components))
             .or_else(|| parse_connections(&components))
             .or_else(|| parse_ports(&components))
             .or_else(|| parse_channel_ends(&components))
@@ -193,17 +354,27 @@ impl FromStr for Path {
             .or_else(|| parse_acks(&components))
             .or_else(|| parse_receipts(&components))
             .or_else(|| parse_upgrades(&components))
+            .or_else(|| parse_channel_upgrades(&components))
             .ok_or_else(|| PathError::parse_failure(s.to_string()))
     }
 }
 
+fn parse_next_sequences(components: &[&str]) -> Option<Path> {
+    match components {
+        [NEXT_CLIENT_SEQUENCE] => Some(NextClientSequencePath.into()),
+        [NEXT_CONNECTION_SEQUENCE] => Some(NextConnectionSequencePath.into()),
+        [NEXT_CHANNEL_SEQUENCE] => Some(NextChannelSequencePath.into()),
+        _ => None,
+    }
+}
+
 fn parse_client_paths(components: &[&str]) -> Option<Path> {
     let first = match components.first() {
         Some(f) => *f,
         None => return None,
     };
 
-    if first != "clients" {
+    if first != "clients" || components.len() < 3 {
         return None;
     }
 
@@ -219,15 +390,12 @@ fn parse_client_paths(components: &[&str]) -> Option<Path> {
             "connections" => Some(ClientConnectionsPath(client_id).into()),
             _ => None,
         }
-    } else if components.len() == 4 {
+    } else if components.len() == 4 || components.len() == 5 {
         if "consensusStates" != components[2] {
             return None;
         }
 
-        let epoch_height = match components.last() {
-            Some(eh) => *eh,
-            None => return None,
-        };
+        let epoch_height = components[3];
 
         let epoch_height: Vec<&str> = epoch_height.split('-').collect();
 
@@ -238,24 +406,36 @@ fn parse_client_paths(components: &[&str]) -> Option<Path> {
         let epoch = epoch_height[0];
         let height = epoch_height[1];
 
-        let epoch = match epoch.parse::<u64>() {
-            Ok(ep) => ep,
-            Err(_) => return None,
-        };
-
-        let height = match height.parse::<u64>() {
-            Ok(h) => h,
-            Err(_) => return None,
-        };
-
-        Some(
-            ClientConsensusStatePath {
-                client_id,
-                epoch,
-                height,
-            }
-            .into(),
-        )
+        let epoch = parse_canonical_u64(epoch)?;
+        let height = parse_canonical_u64(height)?;
+
+        match components.get(4) {
+            None => Some(
+                ClientConsensusStatePath {
+                    client_id,
+                    epoch,
+                    height,
+                }
+                .into(),
+            ),
+            Some(&"processedTime") => Some(
+                ClientUpdateTimePath {
+                    client_id,
+                    epoch,
+                    height,
+                }
+                .into(),
+            ),
+            Some(&"processedHeight") => Some(
+                ClientUpdateHeightPath {
+                    client_id,
+                    epoch,
+                    height,
+                }
+                .into(),
+            ),
+            Some(_) => None,
+        }
     } else {
         None
     }
@@ -589,10 +769,7 @@ fn parse_upgrades(components: &[&str]) -> Option<Path> {
         None => return None,
     };
 
-    let height = match components[1].parse::<u64>() {
-        Ok(h) => h,
-        Err(_) => return None,
-    };
+    let height = parse_canonical_u64(components[1])?;
 
     match last {
         UPGRADED_CLIENT_STATE => Some(ClientUpgradePath::UpgradedClientState(height).into()),
@@ -603,11 +780,41 @@ fn parse_upgrades(components: &[&str]) -> Option<Path> {
     }
 }
 
+fn parse_channel_upgrades(components: &[&str]) -> Option<Path> {
+    if components.len() != 6 || components[0] != "channelUpgrades" {
+        return None;
+    }
+
+    let port_id = match parse_ports(&components[2..=3]) {
+        Some(Path::Ports(PortsPath(port_id))) => port_id,
+        _ => return None,
+    };
+
+    let channel_id = match parse_channels(&components[4..=5]) {
+        Some(SubPath::Channels(channel_id)) => channel_id,
+        _ => return None,
+    };
+
+    match components[1] {
+        "upgrades" => Some(ChannelUpgradePath::Upgrade(port_id, channel_id).into()),
+        "upgradeError" => Some(ChannelUpgradePath::UpgradeError(port_id, channel_id).into()),
+        _ => None,
+    }
+}
+
+/// Parses a number only if it is in canonical decimal form, so that every parsed path formats
+/// back to the exact same string.
+fn parse_canonical_u64(s: &str) -> Option<u64> {
+    s.parse::<u64>().ok().filter(|n| n.to_string() == s)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use core::str::FromStr;
 
+    use crate::test_utils::Xorshift;
+
     #[test]
     fn invalid_path_doesnt_parse() {
         let invalid_path = Path::from_str("clients/clientType");
@@ -1014,4 +1221,176 @@ mod tests {
             Path::Upgrade(ClientUpgradePath::UpgradedClientConsensusState(0)),
         );
     }
+
+    #[test]
+    fn display_is_stable() {
+        let port_id = PortId::transfer();
+        let channel_id = ChannelId::new(7);
+        let paths: [(Path, &str); 9] = [
+            (
+                ClientConsensusStatePath {
+                    client_id: ClientId::default(),
+                    epoch: 1,
+                    height: 42,
+                }
+                .into(),
+                "clients/07-tendermint-0/consensusStates/1-42",
+            ),
+            (
+                ClientUpdateTimePath::new(ClientId::default(), Height::new(1, 42).unwrap()).into(),
+                "clients/07-tendermint-0/consensusStates/1-42/processedTime",
+            ),
+            (NextChannelSequencePath.into(), "nextChannelSequence"),
+            (
+                ConnectionsPath(ConnectionId::new(3)).into(),
+                "connections/connection-3",
+            ),
+            (
+                ChannelEndsPath(port_id.clone(), channel_id.clone()).into(),
+                "channelEnds/ports/transfer/channels/channel-7",
+            ),
+            (
+                SeqRecvsPath(port_id.clone(), channel_id.clone()).into(),
+                "nextSequenceRecv/ports/transfer/channels/channel-7",
+            ),
+            (
+                CommitmentsPath {
+                    port_id: port_id.clone(),
+                    channel_id: channel_id.clone(),
+                    sequence: 18.into(),
+                }
+                .into(),
+                "commitments/ports/transfer/channels/channel-7/sequences/18",
+            ),
+            (
+                ChannelUpgradePath::UpgradeError(port_id, channel_id).into(),
+                "channelUpgrades/upgradeError/ports/transfer/channels/channel-7",
+            ),
+            (
+                ClientUpgradePath::UpgradedClientState(5).into(),
+                "upgradedIBCState/5/upgradedClient",
+            ),
+        ];
+
+        for (path, expected) in paths {
+            assert_eq!(path.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let mut rng = Xorshift::new(0x1bc24);
+
+        for _ in 0..500 {
+            let client_id = ClientId::from_str(&rng.identifier(9, 64)).unwrap();
+            let connection_id = ConnectionId::from_str(&rng.identifier(10, 64)).unwrap();
+            let port_id = PortId::from_str(&rng.identifier(2, 128)).unwrap();
+            let channel_id = ChannelId::from_str(&rng.identifier(8, 64)).unwrap();
+            let sequence = Sequence::from(rng.next_u64());
+            let (epoch, height) = (rng.next_u64(), rng.next_u64());
+
+            let paths: Vec<Path> = vec![
+                ClientTypePath(client_id.clone()).into(),
+                ClientStatePath(client_id.clone()).into(),
+                ClientConsensusStatePath {
+                    client_id: client_id.clone(),
+                    epoch,
+                    height,
+                }
+                .into(),
+                ClientConnectionsPath(client_id.clone()).into(),
+                ClientUpdateTimePath {
+                    client_id: client_id.clone(),
+                    epoch,
+                    height,
+                }
+                .into(),
+                ClientUpdateHeightPath {
+                    client_id,
+                    epoch,
+                    height,
+                }
+                .into(),
+                NextClientSequencePath.into(),
+                NextConnectionSequencePath.into(),
+                NextChannelSequencePath.into(),
+                ConnectionsPath(connection_id).into(),
+                PortsPath(port_id.clone()).into(),
+                ChannelEndsPath(port_id.clone(), channel_id.clone()).into(),
+                SeqSendsPath(port_id.clone(), channel_id.clone()).into(),
+                SeqRecvsPath(port_id.clone(), channel_id.clone()).into(),
+                SeqAcksPath(port_id.clone(), channel_id.clone()).into(),
+                CommitmentsPath {
+                    port_id: port_id.clone(),
+                    channel_id: channel_id.clone(),
+                    sequence,
+                }
+                .into(),
+                AcksPath {
+                    port_id: port_id.clone(),
+                    channel_id: channel_id.clone(),
+                    sequence,
+                }
+                .into(),
+                ReceiptsPath {
+                    port_id: port_id.clone(),
+                    channel_id: channel_id.clone(),
+                    sequence,
+                }
+                .into(),
+                ChannelUpgradePath::Upgrade(port_id.clone(), channel_id.clone()).into(),
+                ChannelUpgradePath::UpgradeError(port_id, channel_id).into(),
+                ClientUpgradePath::UpgradedClientState(height).into(),
+                ClientUpgradePath::UpgradedClientConsensusState(height).into(),
+            ];
+
+            for path in paths {
+                let s = path.to_string();
+                assert_eq!(Path::from_str(&s).unwrap(), path, "{}", s);
+                assert_eq!(Path::from_bytes(&path.clone().into_bytes()).unwrap(), path);
+            }
+        }
+    }
+
+    #[test]
+    fn domain_types_convert_into_paths() {
+        let (port_id, channel_id) = (PortId::transfer(), ChannelId::new(7));
+        let height = Height::new(1, 42).unwrap();
+
+        assert_eq!(
+            ClientStatePath::from(ClientId::default()),
+            ClientStatePath(ClientId::default())
+        );
+        assert_eq!(
+            ClientConsensusStatePath::from((ClientId::default(), height)),
+            ClientConsensusStatePath {
+                client_id: ClientId::default(),
+                epoch: 1,
+                height: 42,
+            }
+        );
+        assert_eq!(
+            SeqSendsPath::from((port_id.clone(), channel_id.clone())),
+            SeqSendsPath(port_id.clone(), channel_id.clone())
+        );
+        assert_eq!(
+            ReceiptsPath::from((port_id.clone(), channel_id.clone(), 3.into())),
+            ReceiptsPath::new(port_id, channel_id, 3.into())
+        );
+    }
+
+    #[test]
+    fn non_canonical_paths_are_rejected() {
+        for s in [
+            "clients",
+            "clients/07-tendermint-0/consensusStates/01-2",
+            "clients/07-tendermint-0/consensusStates/1-+2",
+            "commitments/ports/transfer/channels/channel-0/sequences/007",
+            "upgradedIBCState/05/upgradedClient",
+            "clients/07-tendermint-0/consensusStates/1-2/processedBlock",
+            "nextClientSequence/0",
+        ] {
+            assert!(Path::from_str(s).is_err(), "{:?} must not parse", s);
+        }
+    }
 }
@@ -0,0 +1,129 @@
+//! Namespacing of the IBC state, for hosts that run several isolated IBC instances (e.g. a rollup
+//! hub with one IBC stack per rollup).
+//!
+//! Every instance is given its own [`StorePrefix`]. Hosts persisting state by path string derive
+//! the storage keys of an instance with [`StorePrefix::key`], so that the clients, connections,
+//! channels and counters of different instances never collide, and advertise the prefix to
+//! counterparties as the instance's commitment prefix.
+
+use core::fmt::{Display, Error as FmtError, Formatter};
+use core::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+use crate::core::ics24_host::error::ValidationError;
+use crate::core::ics24_host::path::{Path, PathError};
+use crate::core::ics24_host::validate::validate_identifier;
+use crate::prelude::*;
+
+/// The store prefix of the default (single) IBC instance, as used by the Cosmos SDK.
+pub const DEFAULT_STORE_PREFIX: &str = "ibc";
+
+/// The namespace under which an IBC instance keeps its state.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StorePrefix(String);
+
+impl StorePrefix {
+    /// Builds a store prefix, which must be a valid identifier of 1 to 64 characters.
+    pub fn new(prefix: String) -> Result<Self, ValidationError> {
+        validate_identifier(&prefix, 1, 64)?;
+        Ok(Self(prefix))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the storage key of `path` within this namespace, i.e. `{prefix}/{path}`.
+    pub fn key(&self, path: &Path) -> String {
+        format!("{}/{}", self.0, path)
+    }
+
+    /// Parses a storage key produced by [`StorePrefix::key`], failing if it belongs to another
+    /// namespace.
+    pub fn parse_key(&self, key: &str) -> Result<Path, PathError> {
+        key.strip_prefix(self.0.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .ok_or_else(|| PathError::parse_failure(key.to_string()))?
+            .parse()
+    }
+
+    /// Parses a raw storage key of this namespace, e.g. as iterated over in the host's store.
+    pub fn parse_raw_key(&self, key: &[u8]) -> Result<Path, PathError> {
+        key.strip_prefix(self.0.as_bytes())
+            .and_then(|rest| rest.strip_prefix(b"/"))
+            .ok_or_else(|| PathError::parse_failure(String::from_utf8_lossy(key).into_owned()))
+            .and_then(Path::from_bytes)
+    }
+
+    /// The ABCI query path of this namespace, e.g. `store/ibc/key` for the default instance.
+    pub fn query_path(&self) -> String {
+        format!("store/{}/key", self.0)
+    }
+
+    /// The commitment prefix under which counterparties verify the state of this instance.
+    pub fn commitment_prefix(&self) -> CommitmentPrefix {
+        // Never fails, the prefix is not empty.
+        CommitmentPrefix::try_from(self.0.as_bytes().to_vec()).unwrap()
+    }
+}
+
+impl Default for StorePrefix {
+    fn default() -> Self {
+        Self(DEFAULT_STORE_PREFIX.to_string())
+    }
+}
+
+impl FromStr for StorePrefix {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.to_string())
+    }
+}
+
+impl Display for StorePrefix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics24_host::identifier::ClientId;
+    use crate::core::ics24_host::path::ClientStatePath;
+
+    #[test]
+    fn instances_do_not_share_keys() {
+        let path: Path = ClientStatePath(ClientId::default()).into();
+        let hub = StorePrefix::default();
+        let rollup: StorePrefix = "ibc-rollup-1".parse().unwrap();
+
+        assert_eq!(hub.key(&path), "ibc/clients/07-tendermint-0/clientState");
+        assert_eq!(
+            rollup.key(&path),
+            "ibc-rollup-1/clients/07-tendermint-0/clientState"
+        );
+        assert_eq!(rollup.query_path(), "store/ibc-rollup-1/key");
+        assert_eq!(rollup.commitment_prefix().as_bytes(), b"ibc-rollup-1");
+
+        assert_eq!(rollup.parse_key(&rollup.key(&path)).unwrap(), path);
+        assert_eq!(
+            rollup.parse_raw_key(rollup.key(&path).as_bytes()).unwrap(),
+            path
+        );
+        assert!(hub.parse_raw_key(rollup.key(&path).as_bytes()).is_err());
+        assert!(hub.parse_raw_key(b"ibc/clients/\xff/clientState").is_err());
+        assert!(hub.parse_key(&rollup.key(&path)).is_err());
+        assert!(rollup.parse_key(&hub.key(&path)).is_err());
+    }
+
+    #[test]
+    fn invalid_prefixes_are_rejected() {
+        assert!(StorePrefix::from_str("").is_err());
+        assert!(StorePrefix::from_str("ibc/a").is_err());
+    }
+}
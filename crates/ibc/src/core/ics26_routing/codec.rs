@@ -0,0 +1,154 @@
+//! Decoding of the IBC messages of a host, for hosts that do not receive them as protobuf `Any`s,
+//! e.g. Substrate chains encoding them with SCALE, or hosts accepting JSON.
+//!
+//! The handlers only ever see the domain messages of an [`Ics26Envelope`]: a [`MessageCodec`]
+//! builds them from the messages of the host's transport, and is handed to
+//! [`deliver_with_codec`](super::handler::deliver_with_codec) and
+//! [`validate_with_codec`](super::handler::validate_with_codec). [`ProtobufCodec`] is the codec
+//! of the Cosmos SDK, used by [`deliver`](super::handler::deliver) and
+//! [`validate`](super::handler::validate).
+
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+
+use crate::core::ics26_routing::context::ModuleId;
+use crate::core::ics26_routing::error::Error;
+use crate::core::ics26_routing::msgs::Ics26Envelope;
+
+/// Converts the IBC messages of a host's transport from and to ICS26 envelopes.
+pub trait MessageCodec {
+    /// The messages as received by the host.
+    type Message;
+
+    /// Decodes `message` into the envelope of a core IBC message or, for messages of other
+    /// types, into an [`Ics26Envelope::Custom`] envelope for the module that `resolve_module`
+    /// returns for their type URL, if any.
+    fn decode(
+        &self,
+        message: Self::Message,
+        resolve_module: &dyn Fn(&str) -> Option<ModuleId>,
+    ) -> Result<Ics26Envelope, Error>;
+
+    /// Encodes the message of `envelope`, e.g. for relayers or tests to submit domain messages
+    /// to the host.
+    fn encode(&self, envelope: Ics26Envelope) -> Result<Self::Message, Error>;
+}
+
+/// The codec of protobuf `Any` messages, as found in the transactions of the Cosmos SDK.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProtobufCodec;
+
+impl MessageCodec for ProtobufCodec {
+    type Message = Any;
+
+    fn decode(
+        &self,
+        message: Any,
+        resolve_module: &dyn Fn(&str) -> Option<ModuleId>,
+    ) -> Result<Ics26Envelope, Error> {
+        Ics26Envelope::decode(message, resolve_module)
+    }
+
+    fn encode(&self, envelope: Ics26Envelope) -> Result<Any, Error> {
+        Ok(envelope.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::core::ics02_client::msgs::create_client::MsgCreateClient;
+    use crate::core::ics02_client::msgs::ClientMsg;
+    use crate::core::ics26_routing::handler::{deliver_with_codec, validate_with_codec};
+    use crate::mock::client_state::MockClientState;
+    use crate::mock::consensus_state::MockConsensusState;
+    use crate::mock::context::MockContext;
+    use crate::mock::header::MockHeader;
+    use crate::test_utils::get_dummy_account_id;
+    use crate::tx_msg::Msg;
+    use crate::Height;
+
+    /// A host transport carrying the heights of the mock clients to create, as text.
+    struct TextCodec;
+
+    impl MessageCodec for TextCodec {
+        type Message = String;
+
+        fn decode(
+            &self,
+            message: String,
+            _resolve_module: &dyn Fn(&str) -> Option<ModuleId>,
+        ) -> Result<Ics26Envelope, Error> {
+            let height = message
+                .strip_prefix("create-client ")
+                .and_then(|height| height.parse::<Height>().ok())
+                .ok_or_else(|| Error::codec(format!("unknown message {}", message)))?;
+            let header = MockHeader::new(height);
+            let msg = MsgCreateClient::new(
+                MockClientState::new(header).into(),
+                MockConsensusState::new(header).into(),
+                get_dummy_account_id(),
+            )
+            .map_err(Error::ics02_client)?;
+
+            Ok(Ics26Envelope::Ics2Msg(ClientMsg::CreateClient(msg)))
+        }
+
+        fn encode(&self, envelope: Ics26Envelope) -> Result<String, Error> {
+            match envelope {
+                Ics26Envelope::Ics2Msg(ClientMsg::CreateClient(msg)) => {
+                    let client_state =
+                        MockClientState::try_from(msg.client_state).map_err(Error::ics02_client)?;
+                    Ok(format!("create-client {}", client_state.latest_height()))
+                }
+                _ => Err(Error::codec("unsupported message".to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_deliver_with_codec() {
+        let mut ctx = MockContext::default();
+
+        assert!(validate_with_codec(&ctx, &TextCodec, "create-client 0-42".to_string()).is_ok());
+        assert!(validate_with_codec(&ctx, &TextCodec, "update-client 0-42".to_string()).is_err());
+
+        let receipt =
+            deliver_with_codec(&mut ctx, &TextCodec, "create-client 0-42".to_string()).unwrap();
+        assert_eq!(receipt.events.len(), 1);
+
+        let envelope = TextCodec
+            .decode("create-client 1-5".to_string(), &|_| None)
+            .unwrap();
+        assert_eq!(TextCodec.encode(envelope).unwrap(), "create-client 1-5");
+    }
+
+    #[test]
+    fn test_protobuf_codec_round_trip() {
+        let height = Height::new(0, 42).unwrap();
+        let msg = MsgCreateClient::new(
+            MockClientState::new(MockHeader::new(height)).into(),
+            MockConsensusState::new(MockHeader::new(height)).into(),
+            get_dummy_account_id(),
+        )
+        .unwrap();
+        let any = msg.to_any();
+
+        let envelope = ProtobufCodec.decode(any.clone(), &|_| None).unwrap();
+        assert_eq!(ProtobufCodec.encode(envelope).unwrap(), any);
+
+        let unknown = Any {
+            type_url: "/unknown.Msg".to_string(),
+            value: vec![],
+        };
+        assert!(ProtobufCodec.decode(unknown.clone(), &|_| None).is_err());
+        assert!(matches!(
+            ProtobufCodec.decode(unknown, &|_| "custom".parse().ok()),
+            Ok(Ics26Envelope::Custom(..))
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+//! Conformance tests, replaying fixtures of the messages delivered by ibc-go's core handler tests,
+//! and of the state transitions they cause, against [`deliver`] on a [`MockContext`].
+//!
+//! A fixture is a JSON file under `tests/support/ibc-go`, of the form
+//!
+//! ```json
+//! {
+//!   "description": "...",
+//!   "ports": ["transfer"],
+//!   "steps": [
+//!     {
+//!       "name": "...",
+//!       "msg": { "type_url": "/ibc.core.connection.v1.MsgConnectionOpenInit", "value": "<base64>" },
+//!       "pass": true,
+//!       "state": {
+//!         "connections": { "connection-0": "<base64>" },
+//!         "channels": { "transfer/channel-0": "<base64>" },
+//!         "next_sequence_recv": { "transfer/channel-0": 1 },
+//!         "packet_receipts": ["transfer/channel-0/1"]
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! where the messages are protobuf encoded as they are signed, and the expected connection and
+//! channel ends as they are stored by ibc-go. The steps are delivered in order to a single context,
+//! whose `ports` are bound to a transfer module, and each is checked to pass or fail as expected,
+//! and to leave the host's store in the expected `state`, if any.
+//!
+//! The handshakes of ibc-go's tests are carried out between two chains, each running light clients
+//! of the other. The fixtures checked in here replay both ends of a handshake on the mock chain
+//! instead, against mock clients of itself, which accept any proof, so that only the checks and
+//! state transitions of the handlers themselves are exercised.
+
+use crate::prelude::*;
+
+use alloc::collections::BTreeMap;
+use core::str::FromStr;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::core::channel::v1::Channel as RawChannel;
+use ibc_proto::ibc::core::connection::v1::ConnectionEnd as RawConnectionEnd;
+use prost::Message;
+use serde_derive::Deserialize;
+use subtle_encoding::base64;
+
+use crate::core::ics03_connection::context::ConnectionReader;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::packet::Sequence;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::{ModuleId, RouterBuilder};
+use crate::core::ics26_routing::handler::deliver;
+use crate::mock::context::{MockContext, MockRouterBuilder};
+use crate::test_utils::DummyTransferModule;
+
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    description: String,
+    ports: Vec<String>,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Step {
+    name: String,
+    msg: EncodedAny,
+    pass: bool,
+    #[serde(default)]
+    state: Option<ExpectedState>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EncodedAny {
+    type_url: String,
+    value: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ExpectedState {
+    connections: BTreeMap<String, String>,
+    channels: BTreeMap<String, String>,
+    next_sequence_recv: BTreeMap<String, u64>,
+    packet_receipts: Vec<String>,
+}
+
+fn decode_base64(encoded: &str) -> Vec<u8> {
+    base64::decode(encoded).expect("fixture values must be base64 encoded")
+}
+
+/// Splits a `port/channel[/sequence]` key of the expected state.
+fn channel_key(key: &str) -> (PortId, ChannelId, Option<Sequence>) {
+    let mut parts = key.split('/');
+    let port_id = PortId::from_str(parts.next().unwrap()).unwrap();
+    let channel_id = ChannelId::from_str(parts.next().unwrap()).unwrap();
+    let sequence = parts.next().map(|seq| Sequence::from_str(seq).unwrap());
+    (port_id, channel_id, sequence)
+}
+
+fn check_state(ctx: &MockContext, step: &str, state: &ExpectedState) {
+    for (connection_id, encoded) in &state.connections {
+        let expected = RawConnectionEnd::decode(decode_base64(encoded).as_slice()).unwrap();
+        let connection_id = ConnectionId::from_str(connection_id).unwrap();
+        let stored = ConnectionReader::connection_end(ctx, &connection_id)
+            .unwrap_or_else(|e| panic!("{}: connection {}: {}", step, connection_id, e));
+        assert_eq!(
+            RawConnectionEnd::from(stored),
+            expected,
+            "{}: connection {}",
+            step,
+            connection_id
+        );
+    }
+
+    for (key, encoded) in &state.channels {
+        let expected = RawChannel::decode(decode_base64(encoded).as_slice()).unwrap();
+        let (port_id, channel_id, _) = channel_key(key);
+        let stored = ctx
+            .channel_end(&port_id, &channel_id)
+            .unwrap_or_else(|e| panic!("{}: channel {}: {}", step, key, e));
+        assert_eq!(
+            RawChannel::from(stored),
+            expected,
+            "{}: channel {}",
+            step,
+            key
+        );
+    }
+
+    for (key, expected) in &state.next_sequence_recv {
+        let (port_id, channel_id, _) = channel_key(key);
+        let stored = ctx
+            .get_next_sequence_recv(&port_id, &channel_id)
+            .unwrap_or_else(|e| panic!("{}: next sequence recv {}: {}", step, key, e));
+        assert_eq!(
+            u64::from(stored),
+            *expected,
+            "{}: next sequence recv {}",
+            step,
+            key
+        );
+    }
+
+    for key in &state.packet_receipts {
+        let (port_id, channel_id, sequence) = channel_key(key);
+        let sequence = sequence.expect("packet receipt keys must include a sequence");
+        assert!(
+            ctx.get_packet_receipt(&port_id, &channel_id, sequence)
+                .is_ok(),
+            "{}: missing packet receipt {}",
+            step,
+            key
+        );
+    }
+}
+
+/// Replays the steps of a fixture against a fresh [`MockContext`].
+fn replay(fixture: &str) {
+    let fixture: Fixture = serde_json::from_str(fixture).expect("malformed fixture");
+
+    let transfer_module_id: ModuleId = "transfer".parse().unwrap();
+    let mut ctx = {
+        let ctx = MockContext::default();
+        let module = DummyTransferModule::new(ctx.ibc_store_share());
+        let router = MockRouterBuilder::default()
+            .add_route(transfer_module_id.clone(), module)
+            .unwrap()
+            .build();
+        ctx.with_router(router)
+    };
+    for port_id in &fixture.ports {
+        ctx.scope_port_to_module(
+            PortId::from_str(port_id).unwrap(),
+            transfer_module_id.clone(),
+        );
+    }
+
+    for step in &fixture.steps {
+        let msg = Any {
+            type_url: step.msg.type_url.clone(),
+            value: decode_base64(&step.msg.value),
+        };
+        let res = deliver(&mut ctx, msg);
+        assert_eq!(
+            res.is_ok(),
+            step.pass,
+            "{}: {}: unexpected result {:?}",
+            fixture.description,
+            step.name,
+            res.err()
+        );
+
+        if let Some(state) = &step.state {
+            check_state(&ctx, &step.name, state);
+        }
+    }
+}
+
+#[test]
+fn connection_handshake() {
+    replay(include_str!(
+        "../../../tests/support/ibc-go/connection_handshake.json"
+    ));
+}
+
+#[test]
+fn channel_handshake() {
+    replay(include_str!(
+        "../../../tests/support/ibc-go/channel_handshake.json"
+    ));
+}
+
+#[test]
+fn packet_recv() {
+    replay(include_str!(
+        "../../../tests/support/ibc-go/packet_recv.json"
+    ));
+}
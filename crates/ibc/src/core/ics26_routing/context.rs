@@ -8,40 +8,32 @@ use core::{
     str::FromStr,
 };
 
+use ibc_proto::google::protobuf::Any as ProtoAny;
 use serde::{Deserialize, Serialize};
 
-use crate::core::ics02_client::context::{ClientKeeper, ClientReader};
-use crate::core::ics03_connection::context::{ConnectionKeeper, ConnectionReader};
+use crate::core::context::ExecutionContext;
 use crate::core::ics04_channel::channel::{Counterparty, Order};
-use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
 use crate::core::ics04_channel::packet::Packet;
 use crate::core::ics04_channel::Version;
-use crate::core::ics05_port::context::PortReader;
+use crate::core::ics05_port::error::Error as PortError;
 use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::error::Error as RoutingError;
+use crate::core::ics26_routing::reentrancy::NestedDispatchGuard;
+use crate::core::registry::check_port_binding;
 use crate::events::ModuleEvent;
 use crate::handler::HandlerOutputBuilder;
 use crate::signer::Signer;
 
-/// This trait captures all the functional dependencies (i.e., context) which the ICS26 module
-/// requires to be able to dispatch and process IBC messages. In other words, this is the
-/// representation of a chain from the perspective of the IBC module of that chain.
-pub trait Ics26Context:
-    ClientReader
-    + ClientKeeper
-    + ConnectionReader
-    + ConnectionKeeper
-    + ChannelKeeper
-    + ChannelReader
-    + PortReader
-{
-    type Router: Router;
-
-    fn router(&self) -> &Self::Router;
+/// All the functional dependencies (i.e., context) which the ICS26 module requires to dispatch
+/// and process IBC messages, i.e. an [`ExecutionContext`], which implements it.
+///
+/// Kept for the hosts and modules written against it: new code should be written against a
+/// [`ValidationContext`](crate::core::context::ValidationContext) or an [`ExecutionContext`].
+pub trait Ics26Context: ExecutionContext {}
 
-    fn router_mut(&mut self) -> &mut Self::Router;
-}
+impl<Ctx: ExecutionContext> Ics26Context for Ctx {}
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct InvalidModuleId;
@@ -82,22 +74,110 @@ impl Borrow<str> for ModuleId {
 /// Types implementing this trait are expected to implement `From<GenericAcknowledgement>`
 pub trait Acknowledgement: AsRef<[u8]> {}
 
+impl Acknowledgement for GenericAcknowledgement {}
+
+/// The code of the errors without a stable public reason, e.g. the failures of the bank or of the
+/// storage of the host, like the internal ABCI error code of the Cosmos SDK.
+pub const INTERNAL_ACK_ERROR_CODE: u32 = 1;
+
+/// An error with which an application rejects a packet in an error acknowledgement.
+///
+/// Acknowledgements are written into the state and relayed to the counterparty, so they only carry
+/// the stable public reason code of the error: its message may include details of the host that
+/// differ between its nodes, which would break consensus, or must not be disclosed.
+pub trait AckError: Display {
+    /// Returns the public reason code of the error. Codes are state machine breaking, and must
+    /// not be changed once assigned.
+    fn ack_error_code(&self) -> u32 {
+        INTERNAL_ACK_ERROR_CODE
+    }
+}
+
+/// Returns the message of an error acknowledgement for `err`, i.e. its code and `ack_err_str` in
+/// the `ABCI code: <code>: <ack_err_str>` format of ibc-go. The error itself is only logged.
+pub fn sanitized_ack_error(err: &dyn AckError, ack_err_str: &str) -> String {
+    let code = err.ack_error_code();
+    tracing::info!(code, error = %err, "packet rejected with an error acknowledgement");
+    format!("ABCI code: {}: {}", code, ack_err_str)
+}
+
 pub type WriteFn = dyn FnOnce(&mut dyn Any) -> Result<(), String>;
 
+/// Like a [`WriteFn`], but returns the acknowledgement of the state changes it made, and may emit
+/// the events of their outcome.
+pub type AckFn =
+    dyn FnOnce(&mut dyn Any, &mut ModuleOutputBuilder) -> Result<OnRecvPacketAck, String>;
+
 pub enum OnRecvPacketAck {
     Nil(Box<WriteFn>),
     Successful(Box<dyn Acknowledgement>, Box<WriteFn>),
     Failed(Box<dyn Acknowledgement>),
+    /// The acknowledgement depends on the outcome of the state changes, e.g. of a transaction
+    /// executed on receipt of the packet. The module must discard the changes made by the
+    /// `AckFn` before it returns a `Failed` acknowledgement.
+    Deferred(Box<AckFn>),
 }
 
 impl OnRecvPacketAck {
     pub fn is_successful(&self) -> bool {
         matches!(self, OnRecvPacketAck::Successful(_, _))
     }
+
+    /// Applies the state changes of the acknowledgement to `module`, and returns the
+    /// acknowledgement to write for the packet.
+    pub fn write(
+        self,
+        module: &mut dyn Any,
+        output: &mut ModuleOutputBuilder,
+    ) -> Result<WrittenAck, String> {
+        match self {
+            OnRecvPacketAck::Nil(write_fn) => write_fn(module).map(|_| WrittenAck::Nil),
+            OnRecvPacketAck::Successful(ack, write_fn) => {
+                write_fn(module).map(|_| WrittenAck::Successful(ack))
+            }
+            OnRecvPacketAck::Failed(ack) => Ok(WrittenAck::Failed(ack)),
+            OnRecvPacketAck::Deferred(ack_fn) => ack_fn(module, output)?.write(module, output),
+        }
+    }
+}
+
+/// The acknowledgement of a received packet whose state changes were written.
+pub enum WrittenAck {
+    /// The acknowledgement is written asynchronously.
+    Nil,
+    Successful(Box<dyn Acknowledgement>),
+    Failed(Box<dyn Acknowledgement>),
 }
 
 pub type ModuleOutputBuilder = HandlerOutputBuilder<(), ModuleEvent>;
 
+/// Runs the callback of a module, turning a panic into an [`Error::app_module`] in `std` builds.
+///
+/// The panic may have left the module in an inconsistent state, so the message must fail and the
+/// host revert its changes.
+pub(crate) fn catch_module_panic<T>(callback: impl FnOnce() -> T) -> Result<T, Error> {
+    #[cfg(feature = "std")]
+    {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(callback)).map_err(|payload| {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .map(|reason| reason.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Error::app_module(format!("module callback panicked: {}", reason))
+        })
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        Ok(callback())
+    }
+}
+
+/// An IBC application, whose callbacks are invoked by the handlers of the messages routed to it.
+///
+/// Callbacks should report failures through their result rather than panic: in `std` builds, a
+/// panic is caught and fails the message with an `AppModule` error, but builds without `std`
+/// (or with `panic = "abort"`) cannot contain it.
 pub trait Module: Send + Sync + AsAnyMut {
     #[allow(clippy::too_many_arguments)]
     fn on_chan_open_init(
@@ -154,6 +234,59 @@ pub trait Module: Send + Sync + AsAnyMut {
         Ok(ModuleExtras::empty())
     }
 
+    /// Approves the upgrade of a channel end bound to the module to the given fields, returning
+    /// the version to propose, e.g. after negotiating it. Modules must opt into channel upgrades,
+    /// which are refused by default.
+    #[cfg(any(test, feature = "unstable"))]
+    fn on_chan_upgrade_init(
+        &mut self,
+        port_id: &PortId,
+        _channel_id: &ChannelId,
+        _order: Order,
+        _connection_hops: &[ConnectionId],
+        _version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        Err(Error::upgrade_not_supported(port_id.clone()))
+    }
+
+    /// Approves the upgrade of a channel end bound to the module to the fields proposed by the
+    /// counterparty, returning the version to upgrade to.
+    #[cfg(any(test, feature = "unstable"))]
+    fn on_chan_upgrade_try(
+        &mut self,
+        port_id: &PortId,
+        _channel_id: &ChannelId,
+        _order: Order,
+        _connection_hops: &[ConnectionId],
+        _counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        Err(Error::upgrade_not_supported(port_id.clone()))
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn on_chan_upgrade_ack(
+        &mut self,
+        port_id: &PortId,
+        _channel_id: &ChannelId,
+        _counterparty_version: &Version,
+    ) -> Result<ModuleExtras, Error> {
+        Err(Error::upgrade_not_supported(port_id.clone()))
+    }
+
+    /// Called once the channel end reopened with its upgraded fields, for the module to migrate
+    /// its state if need be.
+    #[cfg(any(test, feature = "unstable"))]
+    fn on_chan_upgrade_open(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _order: Order,
+        _connection_hops: &[ConnectionId],
+        _version: &Version,
+    ) -> Result<ModuleExtras, Error> {
+        Ok(ModuleExtras::empty())
+    }
+
     fn on_recv_packet(
         &self,
         _output: &mut ModuleOutputBuilder,
@@ -181,6 +314,34 @@ pub trait Module: Send + Sync + AsAnyMut {
     ) -> Result<(), Error> {
         Ok(())
     }
+
+    /// Returns the channel versions that this module supports on `port_id`, the first of which is
+    /// its default. The core then opens channels with relayer-provided empty versions at the
+    /// default, and rejects unsupported (counterparty) versions before invoking the handshake
+    /// callbacks. Modules that negotiate versions themselves support none, the default.
+    fn supported_versions(&self, _port_id: &PortId) -> Vec<Version> {
+        Vec::new()
+    }
+
+    /// Returns `true` if this module handles (non core IBC) messages of type `type_url`.
+    fn supports_custom_msg(&self, _type_url: &str) -> bool {
+        false
+    }
+
+    /// Processes a message routed to this module in an `Ics26Envelope::Custom` envelope.
+    fn on_custom_msg(
+        &mut self,
+        _output: &mut ModuleOutputBuilder,
+        msg: ProtoAny,
+    ) -> Result<(), RoutingError> {
+        Err(RoutingError::unknown_message_type_url(msg.type_url))
+    }
+
+    /// Returns the guard in which the callbacks of this module queue the messages that they
+    /// dispatch, if they dispatch any, see [`NestedDispatchGuard`].
+    fn nested_dispatch_guard(&mut self) -> Option<&mut NestedDispatchGuard> {
+        None
+    }
 }
 
 pub trait RouterBuilder: Sized {
@@ -192,14 +353,64 @@ pub trait RouterBuilder: Sized {
     /// Returns an error if a `Module` has already been registered against the specified `ModuleId`
     fn add_route(self, module_id: ModuleId, module: impl Module) -> Result<Self, String>;
 
+    /// Registers `Module` against the specified `ModuleId` in the `Router`'s internal map, after
+    /// checking that the module may bind each of the specified ports
+    ///
+    /// Returns an error if one of the ports is reserved for another application in the
+    /// [registry](crate::core::registry) or already bound to another module, or if `add_route`
+    /// fails
+    fn add_route_with_ports(
+        self,
+        module_id: ModuleId,
+        module: impl Module,
+        port_ids: &[PortId],
+    ) -> Result<Self, String> {
+        for port_id in port_ids {
+            check_port_binding(&module_id, port_id).map_err(|e| e.to_string())?;
+            match self.port_module(port_id) {
+                Some(owner) if owner != &module_id => {
+                    return Err(
+                        PortError::reserved_port(port_id.clone(), module_id, owner.clone())
+                            .to_string(),
+                    )
+                }
+                _ => {}
+            }
+        }
+        let mut builder = self.add_route(module_id.clone(), module)?;
+        for port_id in port_ids {
+            builder.record_port_binding(port_id.clone(), module_id.clone());
+        }
+        Ok(builder)
+    }
+
+    /// Returns the module that bound `port_id` in `add_route_with_ports`, if any. Builders that
+    /// do not record the port bindings, the default, only check ports against the registry.
+    fn port_module(&self, _port_id: &PortId) -> Option<&ModuleId> {
+        None
+    }
+
+    /// Records that `module_id` bound `port_id` in `add_route_with_ports`.
+    fn record_port_binding(&mut self, _port_id: PortId, _module_id: ModuleId) {}
+
     /// Consumes the `RouterBuilder` and returns a `Router` as configured
     fn build(self) -> Self::Router;
 }
 
-pub trait AsAnyMut: Any {
+/// Implemented for all modules, to downcast them in the write callbacks of `on_recv_packet`.
+/// Sealed, so that it can be extended without breaking hosts.
+pub trait AsAnyMut: Any + sealed::Sealed {
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
+mod sealed {
+    use super::Module;
+
+    pub trait Sealed {}
+
+    impl<M: core::any::Any + Module> Sealed for M {}
+}
+
 impl<M: Any + Module> AsAnyMut for M {
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
@@ -210,9 +421,39 @@ impl<M: Any + Module> AsAnyMut for M {
 /// expose APIs to add new routes once constructed. Routes may only be added at the time of
 /// instantiation using the `RouterBuilder`.
 pub trait Router {
+    /// Returns the `ModuleId`s of all registered modules, in ascending order
+    fn routes(&self) -> Vec<ModuleId>;
+
+    /// Returns a reference to a `Module` registered against the specified `ModuleId`, e.g. to
+    /// query it while validating messages against a shared context
+    fn get_route(&self, module_id: &impl Borrow<ModuleId>) -> Option<&dyn Module>;
+
     /// Returns a mutable reference to a `Module` registered against the specified `ModuleId`
     fn get_route_mut(&mut self, module_id: &impl Borrow<ModuleId>) -> Option<&mut dyn Module>;
 
     /// Returns true if the `Router` has a `Module` registered against the specified `ModuleId`
-    fn has_route(&self, module_id: &impl Borrow<ModuleId>) -> bool;
+    fn has_route(&self, module_id: &impl Borrow<ModuleId>) -> bool {
+        self.get_route(module_id).is_some()
+    }
+
+    /// Returns the `ModuleId` of the `Module` that handles custom messages of type `type_url`, if
+    /// any (see [`Module::supports_custom_msg`])
+    fn lookup_module_by_msg_type_url(&self, type_url: &str) -> Option<ModuleId> {
+        self.routes().into_iter().find(|module_id| {
+            self.get_route(module_id)
+                .map_or(false, |module| module.supports_custom_msg(type_url))
+        })
+    }
+
+    /// Returns the channel versions that the module registered against `module_id` supports on
+    /// `port_id` (see [`Module::supported_versions`])
+    fn supported_versions(
+        &self,
+        module_id: &impl Borrow<ModuleId>,
+        port_id: &PortId,
+    ) -> Vec<Version> {
+        self.get_route(module_id)
+            .map(|module| module.supported_versions(port_id))
+            .unwrap_or_default()
+    }
 }
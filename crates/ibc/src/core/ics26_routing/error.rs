@@ -5,6 +5,7 @@ use crate::applications::transfer;
 use crate::core::ics02_client;
 use crate::core::ics03_connection;
 use crate::core::ics04_channel;
+use crate::core::ics26_routing::context::ModuleId;
 
 define_error! {
     #[derive(Debug, PartialEq, Eq)]
@@ -29,8 +30,32 @@ define_error! {
             { url: String }
             | e | { format_args!("unknown type URL {0}", e.url) },
 
+        ModuleNotFound
+            { module_id: ModuleId }
+            | e | { format_args!("no module registered under id {0}", e.module_id) },
+
+        UnsupportedCustomMessage
+            { module_id: ModuleId, type_url: String }
+            | e | { format_args!("module {0} does not support messages of type {1}", e.module_id, e.type_url) },
+
         MalformedMessageBytes
             [ TraceError<ibc_proto::protobuf::Error> ]
             | _ | { "the message is malformed and cannot be decoded" },
+
+        Codec
+            { reason: String }
+            | e | { format_args!("the message cannot be converted by the codec of the host: {0}", e.reason) },
+
+        NestedDispatchLimit
+            { limit: usize }
+            | e | { format_args!("module callbacks queued more than {0} nested messages", e.limit) },
+
+        NestedDispatch
+            { type_url: String, reason: String }
+            | e | { format_args!("nested message of type {0} failed: {1}", e.type_url, e.reason) },
+
+        QueuedSendPacket
+            [ ics04_channel::error::Error ]
+            | _ | { "a packet queued by a module callback could not be sent" },
     }
 }
@@ -1,30 +1,104 @@
 use crate::handler::HandlerOutputBuilder;
 use crate::prelude::*;
 
+use alloc::collections::VecDeque;
 use ibc_proto::google::protobuf::Any;
+use tendermint::abci::tag::Tag;
+use tendermint::abci::Event as AbciEvent;
 
+use crate::core::context::{ExecutionContext, ValidationContext};
+use crate::core::ics02_client::client_state::UpdatedState;
+use crate::core::ics02_client::context::ClientReader;
 use crate::core::ics02_client::handler::dispatch as ics2_msg_dispatcher;
 use crate::core::ics03_connection::handler::dispatch as ics3_msg_dispatcher;
+use crate::core::ics03_connection::msgs::ConnectionMsg;
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::handler::write_acknowledgement::{
+    check_packet_received, emit_acknowledgement,
+};
 use crate::core::ics04_channel::handler::{
     channel_callback, channel_dispatch, channel_validate, recv_packet::RecvPacketResult,
+    send_packet::send_packet,
 };
 use crate::core::ics04_channel::handler::{
     channel_events, get_module_for_packet_msg, packet_callback as ics4_packet_callback,
     packet_dispatch as ics4_packet_msg_dispatcher,
 };
-use crate::core::ics04_channel::packet::PacketResult;
-use crate::core::ics26_routing::context::Ics26Context;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::handler::{
+    channel_upgrade_callback, channel_upgrade_dispatch, channel_upgrade_events,
+    channel_upgrade_validate, packet_flush,
+};
+use crate::core::ics04_channel::handshake::track_handshake;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::msgs::ChannelUpgradeMsg;
+use crate::core::ics04_channel::msgs::{ChannelMsg, PacketMsg};
+use crate::core::ics04_channel::packet::{Packet, PacketResult};
+use crate::core::ics04_channel::pruning::track_packet_record;
+use crate::core::ics04_channel::timeout_close::{close_on_timeouts, count_timeouts};
+use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+use crate::core::ics26_routing::codec::{MessageCodec, ProtobufCodec};
+use crate::core::ics26_routing::context::{catch_module_panic, ModuleOutputBuilder, Router};
 use crate::core::ics26_routing::error::Error;
 use crate::core::ics26_routing::msgs::Ics26Envelope::{
-    self, Ics2Msg, Ics3Msg, Ics4ChannelMsg, Ics4PacketMsg,
+    self, Custom, Ics2Msg, Ics3Msg, Ics4ChannelMsg, Ics4PacketMsg,
 };
-use crate::{events::IbcEvent, handler::HandlerOutput};
+use crate::core::ics26_routing::reentrancy::MAX_NESTED_DISPATCHES;
+use crate::events::{Error as EventError, IbcEvent};
+use crate::handler::HandlerOutput;
+use crate::signer::SignerInterner;
 
 /// Result of message execution - comprises of events emitted and logs entries created during the
 /// execution of a transaction message.
 pub struct MsgReceipt {
     pub events: Vec<IbcEvent>,
     pub log: Vec<String>,
+    /// Placement of the message within its transaction. Only set by [`deliver_batch`].
+    pub index: Option<MsgIndex>,
+}
+
+/// Placement of a message, and of the events it emitted, within a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MsgIndex {
+    /// Position of the message in the transaction.
+    pub msg_index: usize,
+    /// Position of the first event of the message among all events of the transaction.
+    pub first_event_index: usize,
+}
+
+/// Key of the attribute through which the Cosmos SDK attributes events to messages.
+pub const MSG_INDEX_ATTRIBUTE_KEY: &str = "msg_index";
+
+impl MsgReceipt {
+    /// Returns the events of the message along with their position among all events of the
+    /// transaction, which is their position in `events` if the message was delivered on its own.
+    pub fn indexed_events(&self) -> impl Iterator<Item = (usize, &IbcEvent)> {
+        let offset = self.index.map_or(0, |index| index.first_event_index);
+        self.events
+            .iter()
+            .enumerate()
+            .map(move |(i, event)| (offset + i, event))
+    }
+
+    /// Converts the events into ABCI events, each carrying a `msg_index` attribute when the
+    /// message index is known, as done by the Cosmos SDK.
+    pub fn into_abci_events(self) -> Result<Vec<AbciEvent>, EventError> {
+        let index = self.index;
+        self.events
+            .into_iter()
+            .map(|event| {
+                let mut event = AbciEvent::try_from(event)?;
+                if let Some(index) = index {
+                    event.attributes.push(Tag {
+                        key: MSG_INDEX_ATTRIBUTE_KEY.parse().unwrap(),
+                        value: index.msg_index.to_string().parse().unwrap(),
+                    });
+                }
+                Ok(event)
+            })
+            .collect()
+    }
 }
 
 /// Mimics the DeliverTx ABCI interface, but for a single message and at a slightly lower level.
@@ -32,15 +106,127 @@ pub struct MsgReceipt {
 /// Returns a vector of all events that got generated as a byproduct of processing `message`.
 pub fn deliver<Ctx>(ctx: &mut Ctx, message: Any) -> Result<MsgReceipt, Error>
 where
-    Ctx: Ics26Context,
+    Ctx: ExecutionContext,
 {
-    // Decode the proto message into a domain message, creating an ICS26 envelope.
-    let envelope = decode(message)?;
+    execute(ctx, message)
+}
+
+/// Same as [`deliver`], for a host receiving messages in the transport of `codec` rather than as
+/// protobuf `Any`s.
+pub fn deliver_with_codec<Ctx, C>(
+    ctx: &mut Ctx,
+    codec: &C,
+    message: C::Message,
+) -> Result<MsgReceipt, Error>
+where
+    Ctx: ExecutionContext,
+    C: MessageCodec,
+{
+    let envelope = decode_with_codec(ctx, codec, message)?;
 
-    // Process the envelope, and accumulate any events that were generated.
     let HandlerOutput { log, events, .. } = dispatch(ctx, envelope)?;
 
-    Ok(MsgReceipt { events, log })
+    Ok(MsgReceipt {
+        events,
+        log,
+        index: None,
+    })
+}
+
+/// Checks that `message` can be executed against the current state of the host, without
+/// modifying it, e.g. to reject invalid messages in CheckTx: the message is decoded, routed to its
+/// module and checked by its handler. The callbacks of the modules, which may modify their state,
+/// only run when the message is executed.
+pub fn validate<Ctx>(ctx: &Ctx, message: Any) -> Result<(), Error>
+where
+    Ctx: ValidationContext,
+{
+    validate_with_codec(ctx, &ProtobufCodec, message)
+}
+
+/// Same as [`validate`], for a host receiving messages in the transport of `codec` rather than as
+/// protobuf `Any`s.
+pub fn validate_with_codec<Ctx, C>(ctx: &Ctx, codec: &C, message: C::Message) -> Result<(), Error>
+where
+    Ctx: ValidationContext,
+    C: MessageCodec,
+{
+    match decode_with_codec(ctx, codec, message)? {
+        Ics2Msg(msg) => {
+            ics2_msg_dispatcher(ctx, msg).map_err(Error::ics02_client)?;
+        }
+        Ics3Msg(msg) => {
+            ics3_msg_dispatcher(ctx, msg).map_err(Error::ics03_connection)?;
+        }
+        Ics4ChannelMsg(msg) => {
+            channel_validate(ctx, &msg).map_err(Error::ics04_channel)?;
+            channel_dispatch(ctx, &msg).map_err(Error::ics04_channel)?;
+        }
+        #[cfg(any(test, feature = "unstable"))]
+        Ics26Envelope::Ics4ChannelUpgradeMsg(msg) => {
+            channel_upgrade_validate(ctx, &msg).map_err(Error::ics04_channel)?;
+            channel_upgrade_dispatch(ctx, &msg).map_err(Error::ics04_channel)?;
+        }
+        Ics4PacketMsg(msg) => {
+            get_module_for_packet_msg(ctx, &msg).map_err(Error::ics04_channel)?;
+            ics4_packet_msg_dispatcher(ctx, &msg).map_err(Error::ics04_channel)?;
+        }
+        Custom(module_id, _) => {
+            if !ctx.router().has_route(&module_id) {
+                return Err(Error::module_not_found(module_id));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Executes `message`, e.g. in DeliverTx, writing its results to the store of the host and
+/// running the callbacks of its module. The state transitions are derived from the same checks as
+/// in [`validate`], which are thus carried out again, so that a message that was valid when it was
+/// validated but no longer is fails.
+pub fn execute<Ctx>(ctx: &mut Ctx, message: Any) -> Result<MsgReceipt, Error>
+where
+    Ctx: ExecutionContext,
+{
+    deliver_with_codec(ctx, &ProtobufCodec, message)
+}
+
+/// Delivers all messages of a transaction in order, tagging each receipt with the placement of
+/// the message and its events in the transaction.
+/// The relayers of the packet messages of the transaction, usually all the same, are interned, so
+/// that they are not copied over for each packet.
+/// If this method returns an error, the runtime is expected to rollback all state modifications
+/// to the `Ctx` caused by the transaction.
+pub fn deliver_batch<Ctx>(ctx: &mut Ctx, messages: Vec<Any>) -> Result<Vec<MsgReceipt>, Error>
+where
+    Ctx: ExecutionContext,
+{
+    let mut relayers = SignerInterner::default();
+    let mut first_event_index = 0;
+    messages
+        .into_iter()
+        .enumerate()
+        .map(|(msg_index, message)| {
+            let mut envelope = decode_with_router(ctx, message)?;
+            if let Ics4PacketMsg(msg) = &mut envelope {
+                relayers.intern(msg.signer_mut());
+            }
+            let HandlerOutput { log, events, .. } = dispatch(ctx, envelope)?;
+
+            let index = MsgIndex {
+                msg_index,
+                first_event_index,
+            };
+            first_event_index += events.len();
+
+            Ok(MsgReceipt {
+                events,
+                log,
+                index: Some(index),
+            })
+        })
+        .collect()
 }
 
 /// Attempts to convert a message into a [Ics26Envelope] message
@@ -48,14 +234,292 @@ pub fn decode(message: Any) -> Result<Ics26Envelope, Error> {
     message.try_into()
 }
 
+/// Same as [`decode`], but messages that are not core IBC messages are wrapped in a
+/// [`Ics26Envelope::Custom`] envelope for the module that supports their type URL, if any.
+pub fn decode_with_router<Ctx>(ctx: &Ctx, message: Any) -> Result<Ics26Envelope, Error>
+where
+    Ctx: ValidationContext,
+{
+    decode_with_codec(ctx, &ProtobufCodec, message)
+}
+
+/// Same as [`decode_with_router`], for a message in the transport of `codec`.
+pub fn decode_with_codec<Ctx, C>(
+    ctx: &Ctx,
+    codec: &C,
+    message: C::Message,
+) -> Result<Ics26Envelope, Error>
+where
+    Ctx: ValidationContext,
+    C: MessageCodec,
+{
+    codec.decode(message, &|type_url| {
+        ctx.router().lookup_module_by_msg_type_url(type_url)
+    })
+}
+
 /// Top-level ICS dispatch function. Routes incoming IBC messages to their corresponding module.
 /// Returns a handler output with empty result of type `HandlerOutput<()>` which contains the log
-/// and events produced after processing the input `msg`.
+/// and events produced after processing the input `msg`, which are also published to the
+/// [`event_bus`](ExecutionContext::event_bus) of the context, if any.
 /// If this method returns an error, the runtime is expected to rollback all state modifications to
 /// the `Ctx` caused by all messages from the transaction that this `msg` is a part of.
 pub fn dispatch<Ctx>(ctx: &mut Ctx, msg: Ics26Envelope) -> Result<HandlerOutput<()>, Error>
 where
-    Ctx: Ics26Context,
+    Ctx: ExecutionContext,
+{
+    let HandlerOutput { log, events, .. } = dispatch_with_outcome(ctx, msg)?;
+
+    if let Some(bus) = ctx.event_bus() {
+        events.iter().for_each(|event| bus.publish(event));
+    }
+
+    Ok(HandlerOutput::builder()
+        .with_log(log)
+        .with_events(events)
+        .with_result(()))
+}
+
+/// Writes the acknowledgement `ack` of `packet`, whose module deferred it by returning
+/// [`OnRecvPacketAck::Nil`](crate::core::ics26_routing::context::OnRecvPacketAck::Nil) from
+/// `on_recv_packet`, for applications that execute packets asynchronously, e.g. an interchain
+/// account host.
+/// The packet must have been received and not acknowledged yet. Returns the log and the
+/// `WriteAcknowledgement` event, which is also published to the
+/// [`event_bus`](ExecutionContext::event_bus) of the context, if any.
+pub fn write_acknowledgement<Ctx>(
+    ctx: &mut Ctx,
+    packet: Packet,
+    ack: Acknowledgement,
+) -> Result<HandlerOutput<()>, Error>
+where
+    Ctx: ExecutionContext,
+{
+    check_packet_received(ctx, &packet).map_err(Error::ics04_channel)?;
+    let output = emit_acknowledgement(ctx, packet, ack).map_err(Error::ics04_channel)?;
+
+    if let Some(bus) = ctx.event_bus() {
+        output.events.iter().for_each(|event| bus.publish(event));
+    }
+
+    Ok(output)
+}
+
+/// Whether the dispatched message was fully executed or was a no-op (e.g. a packet that had
+/// already been relayed).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DispatchOutcome {
+    Success,
+    NoOp,
+}
+
+/// Same as [`dispatch`], but additionally reports the [`DispatchOutcome`] of the message.
+///
+/// The packets that the callbacks of modules queued with
+/// [`ChannelKeeper::queue_send_packet`](crate::core::ics04_channel::context::ChannelKeeper::queue_send_packet)
+/// are sent, and the messages that they queued in their
+/// [`NestedDispatchGuard`](crate::core::ics26_routing::reentrancy::NestedDispatchGuard) are
+/// dispatched, once the message completed, and their logs and events follow its own.
+pub(crate) fn dispatch_with_outcome<Ctx>(
+    ctx: &mut Ctx,
+    msg: Ics26Envelope,
+) -> Result<HandlerOutput<DispatchOutcome>, Error>
+where
+    Ctx: ExecutionContext,
+{
+    let result = dispatch_msg(ctx, msg).and_then(|mut output| {
+        dispatch_nested(ctx, &mut output)?;
+        Ok(output)
+    });
+
+    // The packets and messages queued by a failed message are dropped, rather than dispatched
+    // after the next.
+    if result.is_err() {
+        ctx.take_queued_send_packets();
+        take_nested_msgs(ctx);
+    }
+
+    result
+}
+
+/// Dispatches the messages queued by the callbacks of modules, in order, along with the ones that
+/// their own callbacks queue, up to [`MAX_NESTED_DISPATCHES`] of them. The packets queued by a
+/// message are sent right after it, before the messages that it queued.
+fn dispatch_nested<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutput<DispatchOutcome>,
+) -> Result<(), Error>
+where
+    Ctx: ExecutionContext,
+{
+    send_queued_packets(ctx, output)?;
+    let mut queue: VecDeque<Any> = take_nested_msgs(ctx).into();
+    let mut dispatched = 0;
+
+    while let Some(msg) = queue.pop_front() {
+        if dispatched == MAX_NESTED_DISPATCHES {
+            return Err(Error::nested_dispatch_limit(MAX_NESTED_DISPATCHES));
+        }
+        dispatched += 1;
+
+        let type_url = msg.type_url.clone();
+        let HandlerOutput { log, events, .. } = decode_with_router(ctx, msg)
+            .and_then(|envelope| dispatch_msg(ctx, envelope))
+            .map_err(|e| Error::nested_dispatch(type_url, e.to_string()))?;
+        output.log.extend(log);
+        output.events.extend(events);
+
+        send_queued_packets(ctx, output)?;
+        queue.extend(take_nested_msgs(ctx));
+    }
+
+    Ok(())
+}
+
+/// Sends the packets queued by the callbacks of modules, in order, each with the next send
+/// sequence of its channel.
+fn send_queued_packets<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutput<DispatchOutcome>,
+) -> Result<(), Error>
+where
+    Ctx: ExecutionContext,
+{
+    for mut packet in ctx.take_queued_send_packets() {
+        packet.sequence = ctx
+            .get_next_sequence_send(&packet.source_port, &packet.source_channel)
+            .map_err(Error::queued_send_packet)?;
+        let HandlerOutput {
+            result,
+            log,
+            events,
+        } = send_packet(ctx, packet).map_err(Error::queued_send_packet)?;
+        ctx.store_packet_result(result)
+            .map_err(Error::queued_send_packet)?;
+
+        output.log.extend(log);
+        output.events.extend(events);
+    }
+
+    Ok(())
+}
+
+/// Takes the messages queued in the guards of all modules, in the order of their routes.
+fn take_nested_msgs<Ctx>(ctx: &mut Ctx) -> Vec<Any>
+where
+    Ctx: ExecutionContext,
+{
+    let router = ctx.router_mut();
+    let mut msgs = Vec::new();
+    for module_id in router.routes() {
+        if let Some(guard) = router
+            .get_route_mut(&module_id)
+            .and_then(|module| module.nested_dispatch_guard())
+        {
+            msgs.append(&mut guard.take());
+        }
+    }
+    msgs
+}
+
+fn dispatch_msg<Ctx>(
+    ctx: &mut Ctx,
+    msg: Ics26Envelope,
+) -> Result<HandlerOutput<DispatchOutcome>, Error>
+where
+    Ctx: ExecutionContext,
+{
+    // Looked up before the message changes the ends it names, e.g. `ChanCloseConfirm`.
+    let proof_client = proof_client(ctx, &msg);
+    let output = dispatch_core_msg(ctx, msg)?;
+
+    if let (DispatchOutcome::Success, Some(client_id)) = (output.result, proof_client) {
+        consume_proofs(ctx, client_id)?;
+    }
+
+    Ok(output)
+}
+
+/// Returns the client that verifies the proofs of `msg`, i.e. the client of the connection of
+/// the end that the message acts on, if the message carries proofs.
+fn proof_client(ctx: &impl ValidationContext, msg: &Ics26Envelope) -> Option<ClientId> {
+    let connection_client = |connection_id: &ConnectionId| {
+        ChannelReader::connection_end(ctx, connection_id)
+            .ok()
+            .map(|connection_end| connection_end.client_id().clone())
+    };
+    let channel_client = |port_id: &PortId, channel_id: &ChannelId| {
+        let channel_end = ctx.channel_end(port_id, channel_id).ok()?;
+        connection_client(channel_end.connection_hops().first()?)
+    };
+
+    match msg {
+        Ics3Msg(ConnectionMsg::ConnectionOpenInit(_)) => None,
+        Ics3Msg(ConnectionMsg::ConnectionOpenTry(msg)) => Some(msg.client_id_on_b.clone()),
+        Ics3Msg(ConnectionMsg::ConnectionOpenAck(msg)) => connection_client(&msg.conn_id_on_a),
+        Ics3Msg(ConnectionMsg::ConnectionOpenConfirm(msg)) => connection_client(&msg.conn_id_on_b),
+        Ics4ChannelMsg(ChannelMsg::ChannelOpenInit(_) | ChannelMsg::ChannelCloseInit(_)) => None,
+        Ics4ChannelMsg(ChannelMsg::ChannelOpenTry(msg)) => {
+            connection_client(msg.channel.connection_hops().first()?)
+        }
+        Ics4ChannelMsg(ChannelMsg::ChannelOpenAck(msg)) => {
+            channel_client(&msg.port_id, &msg.channel_id)
+        }
+        Ics4ChannelMsg(ChannelMsg::ChannelOpenConfirm(msg)) => {
+            channel_client(&msg.port_id, &msg.channel_id)
+        }
+        Ics4ChannelMsg(ChannelMsg::ChannelCloseConfirm(msg)) => {
+            channel_client(&msg.port_id, &msg.channel_id)
+        }
+        #[cfg(any(test, feature = "unstable"))]
+        Ics26Envelope::Ics4ChannelUpgradeMsg(ChannelUpgradeMsg::ChannelUpgradeInit(_)) => None,
+        #[cfg(any(test, feature = "unstable"))]
+        Ics26Envelope::Ics4ChannelUpgradeMsg(msg) => {
+            channel_client(msg.port_id(), msg.channel_id())
+        }
+        Ics4PacketMsg(PacketMsg::RecvPacket(msg)) => channel_client(
+            &msg.packet.destination_port,
+            &msg.packet.destination_channel,
+        ),
+        Ics4PacketMsg(PacketMsg::AckPacket(msg)) => {
+            channel_client(&msg.packet.source_port, &msg.packet.source_channel)
+        }
+        Ics4PacketMsg(PacketMsg::TimeoutPacket(msg)) => {
+            channel_client(&msg.packet.source_port, &msg.packet.source_channel)
+        }
+        Ics4PacketMsg(PacketMsg::TimeoutOnClosePacket(msg)) => {
+            channel_client(&msg.packet.source_port, &msg.packet.source_channel)
+        }
+        Ics2Msg(_) | Custom(_, _) => None,
+    }
+}
+
+/// Stores the state of `client_id` once it verified the proofs of a message, for the clients
+/// that only accept each proof once (see
+/// [`ClientState::consume_proofs`](crate::core::ics02_client::client_state::ClientState::consume_proofs)).
+fn consume_proofs(ctx: &mut impl ExecutionContext, client_id: ClientId) -> Result<(), Error> {
+    let client_state = ClientReader::client_state(ctx, &client_id).map_err(Error::ics02_client)?;
+    if let Some(UpdatedState {
+        client_state,
+        consensus_state,
+    }) = client_state.consume_proofs()
+    {
+        let height = client_state.latest_height();
+        ctx.store_client_state(client_id.clone(), client_state)
+            .map_err(Error::ics02_client)?;
+        ctx.store_consensus_state(client_id, height, consensus_state)
+            .map_err(Error::ics02_client)?;
+    }
+
+    Ok(())
+}
+
+fn dispatch_core_msg<Ctx>(
+    ctx: &mut Ctx,
+    msg: Ics26Envelope,
+) -> Result<HandlerOutput<DispatchOutcome>, Error>
+where
+    Ctx: ExecutionContext,
 {
     let output = match msg {
         Ics2Msg(msg) => {
@@ -68,7 +532,7 @@ where
             HandlerOutput::builder()
                 .with_log(handler_output.log)
                 .with_events(handler_output.events)
-                .with_result(())
+                .with_result(DispatchOutcome::Success)
         }
 
         Ics3Msg(msg) => {
@@ -81,12 +545,12 @@ where
             HandlerOutput::builder()
                 .with_log(handler_output.log)
                 .with_events(handler_output.events)
-                .with_result(())
+                .with_result(DispatchOutcome::Success)
         }
 
         Ics4ChannelMsg(msg) => {
             let module_id = channel_validate(ctx, &msg).map_err(Error::ics04_channel)?;
-            let dispatch_output = HandlerOutputBuilder::<()>::new();
+            let dispatch_output = HandlerOutputBuilder::<DispatchOutcome>::new();
 
             let (dispatch_log, mut channel_result) =
                 channel_dispatch(ctx, &msg).map_err(Error::ics04_channel)?;
@@ -110,6 +574,13 @@ where
             );
 
             // Apply any results to the host chain store.
+            track_handshake(
+                ctx,
+                &channel_result.port_id,
+                &channel_result.channel_id,
+                channel_result.channel_end.state(),
+            )
+            .map_err(Error::ics04_channel)?;
             ctx.store_channel_result(channel_result)
                 .map_err(Error::ics04_channel)?;
 
@@ -124,7 +595,41 @@ where
                 )
                 .with_log(dispatch_log)
                 .with_log(callback_extras.log)
-                .with_result(())
+                .with_result(DispatchOutcome::Success)
+        }
+
+        #[cfg(any(test, feature = "unstable"))]
+        Ics26Envelope::Ics4ChannelUpgradeMsg(msg) => {
+            let module_id = channel_upgrade_validate(ctx, &msg).map_err(Error::ics04_channel)?;
+
+            let (dispatch_log, mut upgrade_result) =
+                channel_upgrade_dispatch(ctx, &msg).map_err(Error::ics04_channel)?;
+
+            // The callbacks of `Init` and `Try` set the version of the upgrade, and the one of
+            // `Ack` may abort it.
+            let callback_extras =
+                channel_upgrade_callback(ctx, &module_id, &msg, &mut upgrade_result)
+                    .map_err(Error::ics04_channel)?;
+
+            let dispatch_events =
+                channel_upgrade_events(ctx, &upgrade_result).map_err(Error::ics04_channel)?;
+
+            // Apply any results to the host chain store.
+            ctx.store_upgrade_result(upgrade_result)
+                .map_err(Error::ics04_channel)?;
+
+            HandlerOutput::builder()
+                .with_events(dispatch_events)
+                .with_events(
+                    callback_extras
+                        .events
+                        .into_iter()
+                        .map(IbcEvent::AppModule)
+                        .collect(),
+                )
+                .with_log(dispatch_log)
+                .with_log(callback_extras.log)
+                .with_result(DispatchOutcome::Success)
         }
 
         Ics4PacketMsg(msg) => {
@@ -132,18 +637,67 @@ where
             let (mut handler_builder, packet_result) =
                 ics4_packet_msg_dispatcher(ctx, &msg).map_err(Error::ics04_channel)?;
 
-            if matches!(packet_result, PacketResult::Recv(RecvPacketResult::NoOp)) {
-                return Ok(handler_builder.with_result(()));
+            let mut output = HandlerOutput::builder();
+
+            if matches!(
+                packet_result,
+                PacketResult::Recv(RecvPacketResult::NoOp) | PacketResult::NoOp
+            ) {
+                output.merge(handler_builder);
+                return Ok(output.with_result(DispatchOutcome::NoOp));
+            }
+
+            // Timed-out packets on ordered-allow-timeout channels never reach the application.
+            if matches!(
+                packet_result,
+                PacketResult::Recv(RecvPacketResult::TimedOut { .. })
+            ) {
+                ctx.store_packet_result(packet_result)
+                    .map_err(Error::ics04_channel)?;
+                output.merge(handler_builder);
+                return Ok(output.with_result(DispatchOutcome::Success));
             }
 
             let cb_result = ics4_packet_callback(ctx, &module_id, &msg, &mut handler_builder);
             cb_result.map_err(Error::ics04_channel)?;
 
             // Apply any results to the host chain store.
+            let close_init =
+                count_timeouts(ctx, &msg, &packet_result).map_err(Error::ics04_channel)?;
+            track_packet_record(ctx, &packet_result).map_err(Error::ics04_channel)?;
             ctx.store_packet_result(packet_result)
                 .map_err(Error::ics04_channel)?;
 
-            handler_builder.with_result(())
+            output.merge(handler_builder);
+
+            // The channel end may have been flushing its last packet in flight for an upgrade.
+            #[cfg(any(test, feature = "unstable"))]
+            for event in packet_flush(ctx, &msg).map_err(Error::ics04_channel)? {
+                output.emit(event);
+            }
+
+            if let Some(close_init) = close_init {
+                let close_output =
+                    close_on_timeouts(ctx, &module_id, close_init).map_err(Error::ics04_channel)?;
+                output.merge(close_output);
+            }
+
+            output.with_result(DispatchOutcome::Success)
+        }
+
+        Custom(module_id, msg) => {
+            let module = ctx
+                .router_mut()
+                .get_route_mut(&module_id)
+                .ok_or_else(|| Error::module_not_found(module_id.clone()))?;
+
+            let mut module_output = ModuleOutputBuilder::new();
+            catch_module_panic(|| module.on_custom_msg(&mut module_output, msg))
+                .map_err(Error::ics04_channel)??;
+
+            let mut output = HandlerOutput::builder();
+            output.merge(module_output);
+            output.with_result(DispatchOutcome::Success)
         }
     };
 
@@ -162,6 +716,7 @@ mod tests {
         context::test::deliver as ics20_deliver, msgs::transfer::test_util::get_dummy_msg_transfer,
         msgs::transfer::MsgTransfer, packet::PacketData, PrefixedCoin, MODULE_ID_STR,
     };
+    use crate::core::context::ExecutionContext;
     use crate::core::ics02_client::msgs::{
         create_client::MsgCreateClient, update_client::MsgUpdateClient,
         upgrade_client::MsgUpgradeClient, ClientMsg,
@@ -175,14 +730,20 @@ mod tests {
         conn_open_try::{test_util::get_dummy_raw_msg_conn_open_try, MsgConnectionOpenTry},
         ConnectionMsg,
     };
-    use crate::core::ics03_connection::version::Version as ConnVersion;
+    use crate::core::ics03_connection::version::{get_compatible_versions, Version as ConnVersion};
     use crate::core::ics04_channel::channel::ChannelEnd;
     use crate::core::ics04_channel::channel::Counterparty as ChannelCounterparty;
     use crate::core::ics04_channel::channel::Order as ChannelOrder;
     use crate::core::ics04_channel::channel::State as ChannelState;
-    use crate::core::ics04_channel::context::ChannelReader;
+    use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+    use crate::core::ics04_channel::error::{
+        Error as ChannelError, ErrorDetail as ChannelErrorDetail,
+    };
+    use crate::core::ics04_channel::handler::ModuleExtras;
     use crate::core::ics04_channel::msgs::acknowledgement::test_util::get_dummy_raw_msg_ack_with_packet;
-    use crate::core::ics04_channel::msgs::acknowledgement::MsgAcknowledgement;
+    use crate::core::ics04_channel::msgs::acknowledgement::{
+        Acknowledgement as GenericAcknowledgement, MsgAcknowledgement,
+    };
     use crate::core::ics04_channel::msgs::chan_open_confirm::test_util::get_dummy_raw_msg_chan_open_confirm;
     use crate::core::ics04_channel::msgs::chan_open_confirm::MsgChannelOpenConfirm;
     use crate::core::ics04_channel::msgs::{
@@ -197,25 +758,36 @@ mod tests {
         timeout_on_close::{test_util::get_dummy_raw_msg_timeout_on_close, MsgTimeoutOnClose},
         ChannelMsg, PacketMsg,
     };
+    use crate::core::ics04_channel::packet::test_utils::get_dummy_raw_packet;
+    use crate::core::ics04_channel::packet::{Packet, Receipt, Sequence};
     use crate::core::ics04_channel::timeout::TimeoutHeight;
     use crate::core::ics04_channel::Version as ChannelVersion;
     use crate::core::ics23_commitment::commitment::test_util::get_dummy_merkle_proof;
     use crate::core::ics23_commitment::commitment::CommitmentPrefix;
     use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
-    use crate::core::ics26_routing::context::{Ics26Context, ModuleId, Router, RouterBuilder};
-    use crate::core::ics26_routing::error::Error;
-    use crate::core::ics26_routing::handler::dispatch;
+    use crate::core::ics26_routing::context::{
+        Module, ModuleId, ModuleOutputBuilder, OnRecvPacketAck, Router, RouterBuilder,
+    };
+    use crate::core::ics26_routing::error::{Error, ErrorDetail};
+    use crate::core::ics26_routing::handler::{
+        decode, decode_with_router, deliver_batch, dispatch, execute, validate,
+        write_acknowledgement, MsgIndex, MSG_INDEX_ATTRIBUTE_KEY,
+    };
     use crate::core::ics26_routing::msgs::Ics26Envelope;
-    use crate::events::IbcEvent;
+    use crate::core::ics26_routing::reentrancy::{NestedDispatchGuard, MAX_NESTED_DISPATCHES};
+    use crate::events::{EventReason, IbcEvent, ModuleEvent};
     use crate::handler::HandlerOutputBuilder;
-    use crate::mock::client_state::MockClientState;
+    use crate::mock::client_state::{client_type as mock_client_type, MockClientState};
     use crate::mock::consensus_state::MockConsensusState;
     use crate::mock::context::{MockContext, MockRouterBuilder};
     use crate::mock::header::MockHeader;
     use crate::prelude::*;
+    use crate::signer::Signer;
     use crate::test_utils::{get_dummy_account_id, DummyTransferModule};
     use crate::timestamp::Timestamp;
     use crate::Height;
+    use ibc_proto::google::protobuf::Any;
+    use tendermint::abci::Event as AbciEvent;
 
     #[test]
     /// These tests exercise two main paths: (1) the ability of the ICS26 routing module to dispatch
@@ -345,14 +917,14 @@ mod tests {
 
         let denom = msg_transfer_two.token.denom.clone();
         let packet_data = {
-            let data = PacketData {
-                token: PrefixedCoin {
+            let data = PacketData::new(
+                PrefixedCoin {
                     denom,
                     amount: msg_transfer_two.token.amount,
                 },
-                sender: msg_transfer_two.sender.clone(),
-                receiver: msg_transfer_two.receiver.clone(),
-            };
+                msg_transfer_two.sender.clone(),
+                msg_transfer_two.receiver.clone(),
+            );
             serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
         };
         msg_to_on_close.packet.data = packet_data;
@@ -595,8 +1167,8 @@ mod tests {
                     client_id.clone(),
                     MockClientState::new(MockHeader::new(upgrade_client_height)).into(),
                     MockConsensusState::new(MockHeader::new(upgrade_client_height)).into(),
-                    get_dummy_merkle_proof(),
-                    get_dummy_merkle_proof(),
+                    get_dummy_merkle_proof().try_into().unwrap(),
+                    get_dummy_merkle_proof().try_into().unwrap(),
                     default_signer.clone(),
                 )))
                 .into(),
@@ -609,8 +1181,8 @@ mod tests {
                     client_id,
                     MockClientState::new(MockHeader::new(upgrade_client_height_second)).into(),
                     MockConsensusState::new(MockHeader::new(upgrade_client_height_second)).into(),
-                    get_dummy_merkle_proof(),
-                    get_dummy_merkle_proof(),
+                    get_dummy_merkle_proof().try_into().unwrap(),
+                    get_dummy_merkle_proof().try_into().unwrap(),
                     default_signer,
                 )))
                 .into(),
@@ -792,6 +1364,103 @@ mod tests {
         assert!(matches!(event, IbcEvent::OpenConfirmChannel(_)));
     }
 
+    /// A JSON-encoded middleware version, as negotiated by an ICS-29 fee middleware wrapping
+    /// ICS-20. The core handlers must carry it through the handshake without interpreting it.
+    const WRAPPED_FEE_VERSION: &str = r#"{"fee_version":"ics29-1","app_version":"ics20-1"}"#;
+
+    fn abci_version_attribute(event: IbcEvent) -> String {
+        let abci_event = AbciEvent::try_from(event).unwrap();
+        abci_event
+            .attributes
+            .iter()
+            .find(|tag| tag.key.to_string() == "version")
+            .map(|tag| tag.value.to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_chan_handshake_preserves_wrapped_version() {
+        let version: ChannelVersion = WRAPPED_FEE_VERSION.parse().unwrap();
+
+        // ChanOpenInit on chain A
+        let mut ctx_a = get_channel_events_ctx();
+        let mut raw_msg_init = get_dummy_raw_msg_chan_open_init();
+        raw_msg_init.channel.as_mut().unwrap().version = WRAPPED_FEE_VERSION.to_string();
+        let msg_init = MsgChannelOpenInit::try_from(raw_msg_init).unwrap();
+
+        let res = dispatch(
+            &mut ctx_a,
+            Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelOpenInit(msg_init)),
+        )
+        .unwrap();
+        let event = res.events.into_iter().next().unwrap();
+        match &event {
+            IbcEvent::OpenInitChannel(e) => assert_eq!(e.version(), &version),
+            _ => panic!("unexpected event: {:?}", event),
+        }
+        assert_eq!(abci_version_attribute(event), WRAPPED_FEE_VERSION);
+        let chan_end_a = ctx_a
+            .channel_end(&PortId::default(), &ChannelId::new(0))
+            .unwrap();
+        assert_eq!(chan_end_a.version(), &version);
+
+        // ChanOpenTry on chain B
+        let mut ctx_b = get_channel_events_ctx();
+        let mut raw_msg_try = get_dummy_raw_msg_chan_open_try(1);
+        raw_msg_try.counterparty_version = WRAPPED_FEE_VERSION.to_string();
+        let msg_try = MsgChannelOpenTry::try_from(raw_msg_try).unwrap();
+
+        let res = dispatch(
+            &mut ctx_b,
+            Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelOpenTry(msg_try)),
+        )
+        .unwrap();
+        let event = res.events.into_iter().next().unwrap();
+        match &event {
+            IbcEvent::OpenTryChannel(e) => assert_eq!(e.version(), &version),
+            _ => panic!("unexpected event: {:?}", event),
+        }
+        assert_eq!(abci_version_attribute(event), WRAPPED_FEE_VERSION);
+        let chan_end_b = ctx_b
+            .channel_end(&PortId::default(), &ChannelId::new(0))
+            .unwrap();
+        assert_eq!(chan_end_b.state(), &ChannelState::TryOpen);
+        assert_eq!(chan_end_b.version(), &version);
+
+        // ChanOpenAck on chain A
+        let mut raw_msg_ack = get_dummy_raw_msg_chan_open_ack(1);
+        raw_msg_ack.channel_id = ChannelId::new(0).to_string();
+        raw_msg_ack.counterparty_version = WRAPPED_FEE_VERSION.to_string();
+        let msg_ack = MsgChannelOpenAck::try_from(raw_msg_ack).unwrap();
+
+        dispatch(
+            &mut ctx_a,
+            Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelOpenAck(msg_ack)),
+        )
+        .unwrap();
+        let chan_end_a = ctx_a
+            .channel_end(&PortId::default(), &ChannelId::new(0))
+            .unwrap();
+        assert_eq!(chan_end_a.state(), &ChannelState::Open);
+        assert_eq!(chan_end_a.version(), &version);
+
+        // ChanOpenConfirm on chain B
+        let mut raw_msg_confirm = get_dummy_raw_msg_chan_open_confirm(1);
+        raw_msg_confirm.channel_id = ChannelId::new(0).to_string();
+        let msg_confirm = MsgChannelOpenConfirm::try_from(raw_msg_confirm).unwrap();
+
+        dispatch(
+            &mut ctx_b,
+            Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelOpenConfirm(msg_confirm)),
+        )
+        .unwrap();
+        let chan_end_b = ctx_b
+            .channel_end(&PortId::default(), &ChannelId::new(0))
+            .unwrap();
+        assert_eq!(chan_end_b.state(), &ChannelState::Open);
+        assert_eq!(chan_end_b.version(), &version);
+    }
+
     #[test]
     fn test_chan_close_init_event() {
         let mut ctx = get_channel_events_ctx().with_channel(
@@ -819,7 +1488,10 @@ mod tests {
 
         let event = res.events.first().unwrap();
 
-        assert!(matches!(event, IbcEvent::CloseInitChannel(_)));
+        assert!(matches!(
+            event,
+            IbcEvent::CloseInitChannel(e) if e.reason() == Some(&EventReason::AppRequested)
+        ));
     }
 
     #[test]
@@ -851,4 +1523,739 @@ mod tests {
 
         assert!(matches!(event, IbcEvent::CloseConfirmChannel(_)));
     }
+
+    const PING_TYPE_URL: &str = "/test.ping.v1.MsgPing";
+
+    #[derive(Debug)]
+    struct PingModule;
+
+    impl Module for PingModule {
+        fn on_chan_open_init(
+            &mut self,
+            _order: ChannelOrder,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &ChannelCounterparty,
+            version: &ChannelVersion,
+        ) -> Result<(ModuleExtras, ChannelVersion), ChannelError> {
+            Ok((ModuleExtras::empty(), version.clone()))
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: ChannelOrder,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &ChannelCounterparty,
+            counterparty_version: &ChannelVersion,
+        ) -> Result<(ModuleExtras, ChannelVersion), ChannelError> {
+            Ok((ModuleExtras::empty(), counterparty_version.clone()))
+        }
+
+        fn supported_versions(&self, _port_id: &PortId) -> Vec<ChannelVersion> {
+            vec![ChannelVersion::from_static("ping-1")]
+        }
+
+        fn supports_custom_msg(&self, type_url: &str) -> bool {
+            type_url == PING_TYPE_URL
+        }
+
+        fn on_custom_msg(
+            &mut self,
+            output: &mut ModuleOutputBuilder,
+            _msg: Any,
+        ) -> Result<(), Error> {
+            output.emit(ModuleEvent {
+                kind: "pong".to_string(),
+                module_name: "ping".parse().unwrap(),
+                attributes: vec![],
+            });
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_chan_open_versions_are_negotiated() {
+        let ctx = || {
+            let router = MockRouterBuilder::default()
+                .add_route(MODULE_ID_STR.parse().unwrap(), PingModule)
+                .unwrap()
+                .build();
+            get_channel_events_ctx().with_router(router)
+        };
+
+        let open_init = |version: &str| {
+            let mut raw_msg = get_dummy_raw_msg_chan_open_init();
+            raw_msg.channel.as_mut().unwrap().version = version.to_string();
+            let msg = MsgChannelOpenInit::try_from(raw_msg).unwrap();
+            dispatch(
+                &mut ctx(),
+                Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelOpenInit(msg)),
+            )
+        };
+
+        // An empty version opens the channel at the default version of the module
+        let res = open_init("").unwrap();
+        match res.events.first().unwrap() {
+            IbcEvent::OpenInitChannel(e) => assert_eq!(e.version(), &"ping-1"),
+            event => panic!("unexpected event: {:?}", event),
+        }
+        assert!(open_init("ping-2").is_err());
+
+        let mut raw_msg_try = get_dummy_raw_msg_chan_open_try(1);
+        raw_msg_try.counterparty_version = "ping-2".to_string();
+        let msg_try = MsgChannelOpenTry::try_from(raw_msg_try).unwrap();
+        assert!(dispatch(
+            &mut ctx(),
+            Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelOpenTry(msg_try)),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_custom_msg_routing() {
+        let module_id: ModuleId = "ping".parse().unwrap();
+        let router = MockRouterBuilder::default()
+            .add_route(module_id.clone(), PingModule)
+            .unwrap()
+            .build();
+        let mut ctx = MockContext::default().with_router(router);
+
+        let ping = Any {
+            type_url: PING_TYPE_URL.to_string(),
+            value: vec![],
+        };
+        let envelope = decode_with_router(&ctx, ping.clone()).unwrap();
+        assert!(matches!(&envelope, Ics26Envelope::Custom(id, _) if id == &module_id));
+
+        let res = dispatch(&mut ctx, envelope).unwrap();
+        assert!(matches!(
+            res.events.as_slice(),
+            [IbcEvent::AppModule(event)] if event.kind == "pong"
+        ));
+
+        // Without router-based resolution, unknown type URLs are still rejected
+        assert!(decode(ping).is_err());
+
+        let unknown = Any {
+            type_url: "/test.unknown.v1.MsgUnknown".to_string(),
+            value: vec![],
+        };
+        assert!(decode_with_router(&ctx, unknown.clone()).is_err());
+
+        let err = dispatch(
+            &mut ctx,
+            Ics26Envelope::Custom("unknown".parse().unwrap(), unknown),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::ModuleNotFound(_)));
+    }
+
+    #[test]
+    fn test_validate_then_execute() {
+        use crate::core::ics02_client::context::ClientReader;
+        use crate::tx_msg::Msg;
+
+        let mut ctx = MockContext::default();
+        let height = Height::new(0, 42).unwrap();
+        let create_client = MsgCreateClient::new(
+            MockClientState::new(MockHeader::new(height)).into(),
+            MockConsensusState::new(MockHeader::new(height)).into(),
+            get_dummy_account_id(),
+        )
+        .unwrap()
+        .to_any();
+        let update_client = MsgUpdateClient {
+            client_id: ClientId::new(mock_client_type(), 0).unwrap(),
+            header: MockHeader::new(height.increment()).into(),
+            signer: get_dummy_account_id(),
+        }
+        .to_any();
+
+        // Validation leaves the store untouched, so that the client does not exist yet.
+        validate(&ctx, create_client.clone()).unwrap();
+        assert_eq!(ctx.client_counter().unwrap(), 0);
+        assert!(validate(&ctx, update_client.clone()).is_err());
+
+        let receipt = execute(&mut ctx, create_client).unwrap();
+        assert!(matches!(
+            receipt.events.as_slice(),
+            [IbcEvent::CreateClient(_)]
+        ));
+        assert_eq!(ctx.client_counter().unwrap(), 1);
+
+        validate(&ctx, update_client.clone()).unwrap();
+        execute(&mut ctx, update_client).unwrap();
+        assert_eq!(
+            ctx.latest_client_states(&ClientId::new(mock_client_type(), 0).unwrap())
+                .latest_height(),
+            height.increment()
+        );
+    }
+
+    #[test]
+    fn test_concurrent_validation() {
+        use alloc::sync::Arc;
+
+        use crate::core::context::ValidationContext;
+        use crate::tx_msg::Msg;
+
+        let module_id: ModuleId = "ping".parse().unwrap();
+        let router = MockRouterBuilder::default()
+            .add_route(module_id.clone(), PingModule)
+            .unwrap()
+            .build();
+        let ctx = MockContext::default().with_router(router);
+
+        let height = Height::new(0, 42).unwrap();
+        let create_client = MsgCreateClient::new(
+            MockClientState::new(MockHeader::new(height)).into(),
+            MockConsensusState::new(MockHeader::new(height)).into(),
+            get_dummy_account_id(),
+        )
+        .unwrap()
+        .to_any();
+        let ping = Any {
+            type_url: PING_TYPE_URL.to_string(),
+            value: vec![],
+        };
+
+        // Modules are queried through shared references, for threads to validate messages
+        // against the same context.
+        let ctx = Arc::new(ctx);
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let (ctx, create_client, ping) = (ctx.clone(), create_client.clone(), ping.clone());
+                let module_id = module_id.clone();
+                std::thread::spawn(move || {
+                    validate(ctx.as_ref(), create_client).unwrap();
+                    validate(ctx.as_ref(), ping).unwrap();
+                    assert_eq!(
+                        ctx.router()
+                            .supported_versions(&module_id, &PortId::transfer()),
+                        vec![ChannelVersion::from_static("ping-1")]
+                    );
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_deliver_batch_indexes_events() {
+        let router = MockRouterBuilder::default()
+            .add_route("ping".parse().unwrap(), PingModule)
+            .unwrap()
+            .build();
+        let mut ctx = MockContext::default().with_router(router);
+
+        let ping = Any {
+            type_url: PING_TYPE_URL.to_string(),
+            value: vec![],
+        };
+        let receipts = deliver_batch(&mut ctx, vec![ping.clone(), ping.clone(), ping]).unwrap();
+
+        for (i, receipt) in receipts.into_iter().enumerate() {
+            assert_eq!(
+                receipt.index,
+                Some(MsgIndex {
+                    msg_index: i,
+                    first_event_index: i,
+                })
+            );
+            assert_eq!(
+                receipt.indexed_events().map(|(j, _)| j).collect::<Vec<_>>(),
+                vec![i]
+            );
+
+            let abci_events = receipt.into_abci_events().unwrap();
+            let msg_index = abci_events[0].attributes.last().unwrap();
+            assert_eq!(msg_index.key.as_ref(), MSG_INDEX_ATTRIBUTE_KEY);
+            assert_eq!(msg_index.value.to_string(), i.to_string());
+        }
+    }
+
+    #[derive(Debug)]
+    struct PanicModule;
+
+    impl Module for PanicModule {
+        fn on_chan_open_init(
+            &mut self,
+            _order: ChannelOrder,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &ChannelCounterparty,
+            _version: &ChannelVersion,
+        ) -> Result<(ModuleExtras, ChannelVersion), ChannelError> {
+            panic!("on_chan_open_init")
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: ChannelOrder,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &ChannelCounterparty,
+            _counterparty_version: &ChannelVersion,
+        ) -> Result<(ModuleExtras, ChannelVersion), ChannelError> {
+            panic!("on_chan_open_try")
+        }
+
+        fn supports_custom_msg(&self, type_url: &str) -> bool {
+            type_url == PING_TYPE_URL
+        }
+
+        fn on_custom_msg(
+            &mut self,
+            _output: &mut ModuleOutputBuilder,
+            msg: Any,
+        ) -> Result<(), Error> {
+            panic!("{}", msg.type_url)
+        }
+    }
+
+    #[test]
+    fn test_module_panics_are_contained() {
+        let router = MockRouterBuilder::default()
+            .add_route(MODULE_ID_STR.parse().unwrap(), PanicModule)
+            .unwrap()
+            .build();
+        let mut ctx = get_channel_events_ctx().with_router(router);
+
+        let msg_chan_open_init =
+            MsgChannelOpenInit::try_from(get_dummy_raw_msg_chan_open_init()).unwrap();
+        let err = dispatch(
+            &mut ctx,
+            Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelOpenInit(msg_chan_open_init)),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("module callback panicked: on_chan_open_init"));
+
+        let err = dispatch(
+            &mut ctx,
+            Ics26Envelope::Custom(
+                MODULE_ID_STR.parse().unwrap(),
+                Any {
+                    type_url: PING_TYPE_URL.to_string(),
+                    value: vec![],
+                },
+            ),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(PING_TYPE_URL));
+    }
+
+    const ECHO_TYPE_URL: &str = "/test.echo.v1.MsgEcho";
+
+    /// A message echoed `n` more times.
+    fn echo(n: u8) -> Any {
+        Any {
+            type_url: ECHO_TYPE_URL.to_string(),
+            value: vec![n],
+        }
+    }
+
+    /// Echoes its messages as nested messages, and queues one once it received a packet. Echoes
+    /// without a count queue a message that no module handles.
+    #[derive(Debug, Default)]
+    struct EchoModule {
+        guard: NestedDispatchGuard,
+    }
+
+    impl Module for EchoModule {
+        fn on_chan_open_init(
+            &mut self,
+            _order: ChannelOrder,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &ChannelCounterparty,
+            version: &ChannelVersion,
+        ) -> Result<(ModuleExtras, ChannelVersion), ChannelError> {
+            Ok((ModuleExtras::empty(), version.clone()))
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: ChannelOrder,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &ChannelCounterparty,
+            counterparty_version: &ChannelVersion,
+        ) -> Result<(ModuleExtras, ChannelVersion), ChannelError> {
+            Ok((ModuleExtras::empty(), counterparty_version.clone()))
+        }
+
+        fn on_recv_packet(
+            &self,
+            _output: &mut ModuleOutputBuilder,
+            _packet: &Packet,
+            _relayer: &Signer,
+        ) -> OnRecvPacketAck {
+            OnRecvPacketAck::Successful(
+                Box::new(GenericAcknowledgement::from(vec![1])),
+                Box::new(|module| {
+                    let module = module.downcast_mut::<EchoModule>().unwrap();
+                    module.guard.dispatch(echo(0));
+                    Ok(())
+                }),
+            )
+        }
+
+        fn supports_custom_msg(&self, type_url: &str) -> bool {
+            type_url == ECHO_TYPE_URL
+        }
+
+        fn on_custom_msg(
+            &mut self,
+            output: &mut ModuleOutputBuilder,
+            msg: Any,
+        ) -> Result<(), Error> {
+            // Nested messages are only dispatched once this one completed.
+            assert!(self.guard.queued().is_empty());
+
+            let left = msg.value.first().copied();
+            output.emit(ModuleEvent {
+                kind: "echo".to_string(),
+                module_name: "echo".parse().unwrap(),
+                attributes: vec![("left", left.unwrap_or_default()).into()],
+            });
+            match left {
+                Some(0) => {}
+                Some(n) => self.guard.dispatch(echo(n - 1)),
+                None => self.guard.dispatch(Any {
+                    type_url: "/test.unknown.v1.MsgUnknown".to_string(),
+                    value: vec![],
+                }),
+            }
+            Ok(())
+        }
+
+        fn nested_dispatch_guard(&mut self) -> Option<&mut NestedDispatchGuard> {
+            Some(&mut self.guard)
+        }
+    }
+
+    fn echo_ctx() -> MockContext {
+        let router = MockRouterBuilder::default()
+            .add_route("echo".parse().unwrap(), EchoModule::default())
+            .unwrap()
+            .build();
+        MockContext::default().with_router(router)
+    }
+
+    #[test]
+    fn test_nested_messages_are_dispatched_after_their_message() {
+        let mut ctx = echo_ctx();
+
+        let receipt = execute(&mut ctx, echo(2)).unwrap();
+        let left: Vec<_> = receipt
+            .events
+            .iter()
+            .map(|event| match event {
+                IbcEvent::AppModule(event) => event.attributes[0].value.clone(),
+                event => panic!("unexpected event: {:?}", event),
+            })
+            .collect();
+        assert_eq!(left, ["2", "1", "0"]);
+
+        // Modules queueing messages endlessly fail the message.
+        let err = execute(&mut ctx, echo(u8::MAX)).err().unwrap();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::NestedDispatchLimit(e) if e.limit == MAX_NESTED_DISPATCHES
+        ));
+
+        // So do failing nested messages.
+        let err = execute(
+            &mut ctx,
+            Any {
+                type_url: ECHO_TYPE_URL.to_string(),
+                value: vec![],
+            },
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err.detail(), ErrorDetail::NestedDispatch(_)));
+
+        // The messages queued by failed messages are dropped.
+        assert_eq!(execute(&mut ctx, echo(0)).unwrap().events.len(), 1);
+    }
+
+    /// Opens the channel of the packet of a dummy `MsgRecvPacket` on `ctx`, bound to `module_id`,
+    /// and returns the message.
+    fn recv_packet_ctx(ctx: MockContext, module_id: &str) -> (MockContext, MsgRecvPacket) {
+        use crate::core::ics03_connection::version::get_compatible_versions;
+        use crate::relayer::ics18_relayer::context::Ics18Context;
+        use crate::timestamp::ZERO_DURATION;
+
+        let host_height = ctx.query_latest_height().increment();
+        let client_height = host_height.increment();
+        let msg = MsgRecvPacket::try_from(get_dummy_raw_msg_recv_packet(
+            client_height.revision_height(),
+        ))
+        .unwrap();
+        let packet = msg.packet.clone();
+
+        let mut ctx = ctx
+            .with_client(&ClientId::default(), client_height)
+            .with_connection(
+                ConnectionId::default(),
+                ConnectionEnd::new(
+                    ConnState::Open,
+                    ClientId::default(),
+                    ConnCounterparty::new(
+                        ClientId::default(),
+                        Some(ConnectionId::default()),
+                        Default::default(),
+                    ),
+                    get_compatible_versions(),
+                    ZERO_DURATION,
+                ),
+            )
+            .with_channel(
+                packet.destination_port.clone(),
+                packet.destination_channel.clone(),
+                ChannelEnd::new(
+                    ChannelState::Open,
+                    ChannelOrder::default(),
+                    ChannelCounterparty::new(packet.source_port, Some(packet.source_channel)),
+                    vec![ConnectionId::default()],
+                    ChannelVersion::ics20(),
+                ),
+            )
+            .with_height(host_height);
+        ctx.scope_port_to_module(packet.destination_port, module_id.parse().unwrap());
+
+        (ctx, msg)
+    }
+
+    #[test]
+    fn test_messages_queued_on_recv_packet_follow_the_packet() {
+        let (mut ctx, msg) = recv_packet_ctx(echo_ctx(), "echo");
+
+        let res = dispatch(
+            &mut ctx,
+            Ics26Envelope::Ics4PacketMsg(PacketMsg::RecvPacket(msg)),
+        )
+        .unwrap();
+        assert!(matches!(
+            res.events.as_slice(),
+            [
+                IbcEvent::ReceivePacket(_),
+                IbcEvent::WriteAcknowledgement(_),
+                IbcEvent::AppModule(echo),
+            ] if echo.kind == "echo"
+        ));
+    }
+
+    /// Replies to the packets it receives with `replies` packets, queued through its context.
+    #[derive(Debug)]
+    struct ReplyModule {
+        ctx: DummyTransferModule,
+        replies: usize,
+    }
+
+    impl Module for ReplyModule {
+        fn on_chan_open_init(
+            &mut self,
+            _order: ChannelOrder,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &ChannelCounterparty,
+            version: &ChannelVersion,
+        ) -> Result<(ModuleExtras, ChannelVersion), ChannelError> {
+            Ok((ModuleExtras::empty(), version.clone()))
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: ChannelOrder,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &ChannelCounterparty,
+            counterparty_version: &ChannelVersion,
+        ) -> Result<(ModuleExtras, ChannelVersion), ChannelError> {
+            Ok((ModuleExtras::empty(), counterparty_version.clone()))
+        }
+
+        fn on_recv_packet(
+            &self,
+            _output: &mut ModuleOutputBuilder,
+            packet: &Packet,
+            _relayer: &Signer,
+        ) -> OnRecvPacketAck {
+            let reply = Packet {
+                sequence: Sequence::default(),
+                source_port: packet.destination_port.clone(),
+                source_channel: packet.destination_channel.clone(),
+                destination_port: packet.source_port.clone(),
+                destination_channel: packet.source_channel.clone(),
+                data: b"pong".to_vec(),
+                timeout_height: TimeoutHeight::Never,
+                timeout_timestamp: Timestamp::from_nanoseconds(u64::MAX).unwrap(),
+            };
+            OnRecvPacketAck::Successful(
+                Box::new(GenericAcknowledgement::from(vec![1])),
+                Box::new(move |module| {
+                    let module = module.downcast_mut::<ReplyModule>().unwrap();
+                    for _ in 0..module.replies {
+                        module
+                            .ctx
+                            .queue_send_packet(reply.clone())
+                            .map_err(|e| e.to_string())?;
+                    }
+                    Ok(())
+                }),
+            )
+        }
+    }
+
+    fn reply_ctx(replies: usize) -> (MockContext, MsgRecvPacket) {
+        let ctx = MockContext::default();
+        let router = MockRouterBuilder::default()
+            .add_route(
+                "reply".parse().unwrap(),
+                ReplyModule {
+                    ctx: DummyTransferModule::new(ctx.ibc_store_share()),
+                    replies,
+                },
+            )
+            .unwrap()
+            .build();
+        recv_packet_ctx(ctx.with_router(router), "reply")
+    }
+
+    #[test]
+    fn test_packets_queued_on_recv_packet_are_sent_after_it() {
+        let (ctx, msg) = reply_ctx(2);
+        let (port_id, channel_id) = (
+            msg.packet.destination_port.clone(),
+            msg.packet.destination_channel.clone(),
+        );
+        let mut ctx = ctx.with_send_sequence(port_id.clone(), channel_id.clone(), 1.into());
+
+        let res = dispatch(
+            &mut ctx,
+            Ics26Envelope::Ics4PacketMsg(PacketMsg::RecvPacket(msg)),
+        )
+        .unwrap();
+        let sent: Vec<_> = res
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                IbcEvent::SendPacket(e) => Some(e.packet().sequence),
+                _ => None,
+            })
+            .collect();
+        assert!(matches!(
+            res.events.as_slice(),
+            [
+                IbcEvent::ReceivePacket(_),
+                IbcEvent::WriteAcknowledgement(_),
+                IbcEvent::SendPacket(_),
+                IbcEvent::SendPacket(_),
+            ]
+        ));
+        assert_eq!(sent, [1.into(), 2.into()]);
+        assert_eq!(
+            ctx.get_next_sequence_send(&port_id, &channel_id).unwrap(),
+            3.into()
+        );
+        for seq in [1, 2] {
+            assert!(ctx
+                .get_packet_commitment(&port_id, &channel_id, seq.into())
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_queued_packets_failing_to_be_sent_fail_their_message() {
+        // Without a send sequence, the channel cannot send packets.
+        let (mut ctx, msg) = reply_ctx(1);
+
+        let err = dispatch(
+            &mut ctx,
+            Ics26Envelope::Ics4PacketMsg(PacketMsg::RecvPacket(msg)),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::QueuedSendPacket(_)));
+        assert!(ctx.take_queued_send_packets().is_empty());
+    }
+
+    #[test]
+    fn test_write_acknowledgement_of_received_packet() {
+        let mut packet: Packet = get_dummy_raw_packet(1, 6).try_into().unwrap();
+        packet.data = vec![0];
+
+        let dest_channel_end = ChannelEnd::new(
+            ChannelState::Open,
+            ChannelOrder::Unordered,
+            ChannelCounterparty::new(
+                packet.source_port.clone(),
+                Some(packet.source_channel.clone()),
+            ),
+            vec![ConnectionId::default()],
+            ChannelVersion::ics20(),
+        );
+        let connection_end = ConnectionEnd::new(
+            ConnState::Open,
+            ClientId::default(),
+            ConnCounterparty::new(
+                ClientId::default(),
+                Some(ConnectionId::default()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            Duration::ZERO,
+        );
+
+        let mut ctx = MockContext::default()
+            .with_client(&ClientId::default(), Height::new(0, 1).unwrap())
+            .with_connection(ConnectionId::default(), connection_end)
+            .with_channel(
+                packet.destination_port.clone(),
+                packet.destination_channel.clone(),
+                dest_channel_end,
+            );
+
+        // The acknowledgement of a packet that was never received cannot be written.
+        let err = write_acknowledgement(&mut ctx, packet.clone(), vec![1].into()).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::Ics04Channel(e)
+                if matches!(&e.source, ChannelErrorDetail::PacketNotReceived(e) if e.sequence == packet.sequence)
+        ));
+
+        ctx.store_packet_receipt(
+            packet.destination_port.clone(),
+            packet.destination_channel.clone(),
+            packet.sequence,
+            Receipt::Ok,
+        )
+        .unwrap();
+        let output = write_acknowledgement(&mut ctx, packet.clone(), vec![1].into()).unwrap();
+        assert!(matches!(
+            output.events.as_slice(),
+            [IbcEvent::WriteAcknowledgement(_)]
+        ));
+
+        let err = write_acknowledgement(&mut ctx, packet, vec![1].into()).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::Ics04Channel(e)
+                if matches!(e.source, ChannelErrorDetail::AcknowledgementExists(_))
+        ));
+    }
 }
@@ -0,0 +1,246 @@
+//! Post-dispatch audit of the IBC store, enabled by the `invariant-checks` feature.
+//!
+//! [`dispatch_checked`] dispatches a message like [`dispatch`] and then checks that the store it
+//! left behind is consistent:
+//! - every key of the IBC store parses as a [`Path`];
+//! - every OPEN channel references an OPEN connection;
+//! - no next sequence (send, recv or ack) of a channel went backwards;
+//! - the amounts escrowed by ICS20 match the balances of the escrow accounts.
+//!
+//! A violation means that a handler or the host's storage is broken, so hosts that opt in should
+//! halt rather than commit the transaction.
+
+use crate::prelude::*;
+
+use alloc::collections::BTreeMap;
+use core::str::FromStr;
+use flex_error::define_error;
+
+use crate::core::context::ExecutionContext;
+use crate::core::ics03_connection::connection::State as ConnectionState;
+use crate::core::ics04_channel::channel::{ChannelEnd, State as ChannelState};
+use crate::core::ics04_channel::context::ChannelReader;
+use crate::core::ics04_channel::packet::Sequence;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics24_host::path::Path;
+use crate::core::ics26_routing::error::Error as RoutingError;
+use crate::core::ics26_routing::handler::dispatch;
+use crate::core::ics26_routing::msgs::Ics26Envelope;
+use crate::handler::HandlerOutput;
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        Dispatch
+            [ RoutingError ]
+            | _ | { "dispatch failed" },
+
+        UnparsableStorePath
+            { path: String }
+            | e | { format_args!("store key {0} is not a valid IBC path", e.path) },
+
+        OpenChannelWithoutOpenConnection
+            {
+                port_id: PortId,
+                channel_id: ChannelId,
+                connection_id: ConnectionId,
+            }
+            | e | {
+                format_args!("channel {0}/{1} is OPEN but its connection {2} is not",
+                    e.port_id, e.channel_id, e.connection_id)
+            },
+
+        SequenceDecreased
+            {
+                port_id: PortId,
+                channel_id: ChannelId,
+                kind: String,
+                before: Sequence,
+                after: Sequence,
+            }
+            | e | {
+                format_args!("next sequence {0} of channel {1}/{2} went from {3} back to {4}",
+                    e.kind, e.port_id, e.channel_id, e.before, e.after)
+            },
+
+        EscrowMismatch
+            { reason: String }
+            | e | { format_args!("escrow totals do not match the escrow balances: {0}", e.reason) },
+    }
+}
+
+/// Read access to the parts of the store that cannot be enumerated through an
+/// [`ExecutionContext`].
+pub trait InvariantReader: ExecutionContext {
+    /// Returns all channel ends of the store.
+    fn channel_ends(&self) -> Vec<(PortId, ChannelId, ChannelEnd)>;
+
+    /// Returns the keys of the IBC store, as stored.
+    fn ibc_store_keys(&self) -> Vec<String>;
+
+    /// Checks that the amounts escrowed by ICS20 match the balances of the escrow accounts,
+    /// reporting the first mismatch. Hosts without ICS20 can rely on the default.
+    fn check_escrow_totals(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// The next sequences (send, recv and ack) of every channel, as of some point in time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SequenceSnapshot(BTreeMap<(PortId, ChannelId), [Option<Sequence>; 3]>);
+
+const SEQUENCE_KINDS: [&str; 3] = ["send", "recv", "ack"];
+
+impl SequenceSnapshot {
+    pub fn take<Ctx: InvariantReader>(ctx: &Ctx) -> Self {
+        let sequences = ctx
+            .channel_ends()
+            .into_iter()
+            .map(|(port_id, channel_id, _)| {
+                let sequences = [
+                    ctx.get_next_sequence_send(&port_id, &channel_id),
+                    ctx.get_next_sequence_recv(&port_id, &channel_id),
+                    ctx.get_next_sequence_ack(&port_id, &channel_id),
+                ]
+                .map(Result::ok);
+                ((port_id, channel_id), sequences)
+            })
+            .collect();
+
+        Self(sequences)
+    }
+}
+
+/// Checks all invariants against the store of `ctx`, with `before` the sequences taken before
+/// the message was dispatched.
+pub fn check_invariants<Ctx: InvariantReader>(
+    ctx: &Ctx,
+    before: &SequenceSnapshot,
+) -> Result<(), Error> {
+    for key in ctx.ibc_store_keys() {
+        if Path::from_str(&key).is_err() {
+            return Err(Error::unparsable_store_path(key));
+        }
+    }
+
+    for (port_id, channel_id, channel_end) in ctx.channel_ends() {
+        if !channel_end.state_matches(&ChannelState::Open) {
+            continue;
+        }
+        let connection_id = channel_end.connection_hops()[0].clone();
+        let connection_open = ChannelReader::connection_end(ctx, &connection_id)
+            .map_or(false, |connection_end| {
+                connection_end.state_matches(&ConnectionState::Open)
+            });
+        if !connection_open {
+            return Err(Error::open_channel_without_open_connection(
+                port_id,
+                channel_id,
+                connection_id,
+            ));
+        }
+    }
+
+    let after = SequenceSnapshot::take(ctx);
+    for (key, sequences_before) in &before.0 {
+        let sequences_after = match after.0.get(key) {
+            Some(sequences_after) => sequences_after,
+            None => continue,
+        };
+        for ((kind, before), after) in SEQUENCE_KINDS
+            .iter()
+            .zip(sequences_before)
+            .zip(sequences_after)
+        {
+            match (before, after) {
+                (Some(before), Some(after)) if after < before => {
+                    return Err(Error::sequence_decreased(
+                        key.0.clone(),
+                        key.1.clone(),
+                        kind.to_string(),
+                        *before,
+                        *after,
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    ctx.check_escrow_totals().map_err(Error::escrow_mismatch)
+}
+
+/// Same as [`dispatch`], but audits the store once the message was processed. A violated
+/// invariant is reported as an error, upon which the runtime is expected to rollback the
+/// transaction.
+pub fn dispatch_checked<Ctx>(ctx: &mut Ctx, msg: Ics26Envelope) -> Result<HandlerOutput<()>, Error>
+where
+    Ctx: InvariantReader,
+{
+    let before = SequenceSnapshot::take(ctx);
+    let output = dispatch(ctx, msg).map_err(Error::dispatch)?;
+    check_invariants(ctx, &before)?;
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics03_connection::connection::{ConnectionEnd, State as ConnectionState};
+    use crate::core::ics04_channel::channel::{Counterparty, Order};
+    use crate::core::ics04_channel::Version;
+    use crate::mock::context::MockContext;
+
+    fn channel_end(state: ChannelState) -> ChannelEnd {
+        ChannelEnd::new(
+            state,
+            Order::Unordered,
+            Counterparty::new(PortId::transfer(), Some(ChannelId::default())),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        )
+    }
+
+    #[test]
+    fn open_channel_requires_open_connection() {
+        let ctx = MockContext::default().with_channel(
+            PortId::transfer(),
+            ChannelId::default(),
+            channel_end(ChannelState::Open),
+        );
+        let err = check_invariants(&ctx, &SequenceSnapshot::default()).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::OpenChannelWithoutOpenConnection(_)
+        ));
+
+        let mut connection_end = ConnectionEnd::default();
+        connection_end.set_state(ConnectionState::Open);
+        let ctx = ctx.with_connection(ConnectionId::default(), connection_end);
+        check_invariants(&ctx, &SequenceSnapshot::default()).unwrap();
+    }
+
+    #[test]
+    fn sequences_must_not_decrease() {
+        let ctx = MockContext::default()
+            .with_channel(
+                PortId::transfer(),
+                ChannelId::default(),
+                channel_end(ChannelState::Init),
+            )
+            .with_send_sequence(PortId::transfer(), ChannelId::default(), 5.into());
+        let before = SequenceSnapshot::take(&ctx);
+
+        let ctx = ctx.with_send_sequence(PortId::transfer(), ChannelId::default(), 6.into());
+        check_invariants(&ctx, &before).unwrap();
+
+        let ctx = ctx.with_send_sequence(PortId::transfer(), ChannelId::default(), 4.into());
+        let err = check_invariants(&ctx, &before).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::SequenceDecreased(e) if e.kind == "send"
+        ));
+    }
+}
@@ -0,0 +1,597 @@
+//! Composition of middlewares around the `Module` of an application.
+//!
+//! A [`Middleware`] sees every callback before the module it wraps, and decides whether and how
+//! to call into it: by default, all callbacks are passed through unchanged. A [`ModuleStack`]
+//! stacks middlewares around a base module, e.g. fee → packet-forward → transfer, and builds a
+//! single `Module` to register with the router.
+
+use crate::prelude::*;
+
+use core::any::Any;
+
+use ibc_proto::google::protobuf::Any as ProtoAny;
+
+use crate::core::ics04_channel::channel::{Counterparty, Order};
+use crate::core::ics04_channel::error::Error;
+use crate::core::ics04_channel::handler::ModuleExtras;
+use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics04_channel::Version;
+use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use crate::core::ics26_routing::context::{Module, ModuleOutputBuilder, OnRecvPacketAck, WriteFn};
+use crate::core::ics26_routing::error::Error as RoutingError;
+use crate::core::ics26_routing::reentrancy::NestedDispatchGuard;
+use crate::signer::Signer;
+
+/// A layer of a [`ModuleStack`], whose callbacks receive the module it wraps as `app`.
+///
+/// The `write_fn` of the acknowledgement returned by `on_recv_packet` is applied to `app`, so a
+/// middleware may return the one of `app` as is, or wrap it.
+pub trait Middleware: Send + Sync + 'static {
+    #[allow(clippy::too_many_arguments)]
+    fn on_chan_open_init(
+        &mut self,
+        app: &mut dyn Module,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        app.on_chan_open_init(
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            version,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_chan_open_try(
+        &mut self,
+        app: &mut dyn Module,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        app.on_chan_open_try(
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            counterparty_version,
+        )
+    }
+
+    fn supported_versions(&self, app: &dyn Module, port_id: &PortId) -> Vec<Version> {
+        app.supported_versions(port_id)
+    }
+
+    fn on_chan_open_ack(
+        &mut self,
+        app: &mut dyn Module,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty_version: &Version,
+    ) -> Result<ModuleExtras, Error> {
+        app.on_chan_open_ack(port_id, channel_id, counterparty_version)
+    }
+
+    fn on_chan_open_confirm(
+        &mut self,
+        app: &mut dyn Module,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, Error> {
+        app.on_chan_open_confirm(port_id, channel_id)
+    }
+
+    fn on_chan_close_init(
+        &mut self,
+        app: &mut dyn Module,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, Error> {
+        app.on_chan_close_init(port_id, channel_id)
+    }
+
+    fn on_chan_close_confirm(
+        &mut self,
+        app: &mut dyn Module,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, Error> {
+        app.on_chan_close_confirm(port_id, channel_id)
+    }
+
+    fn on_recv_packet(
+        &self,
+        app: &dyn Module,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> OnRecvPacketAck {
+        app.on_recv_packet(output, packet, relayer)
+    }
+
+    fn on_acknowledgement_packet(
+        &mut self,
+        app: &mut dyn Module,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        acknowledgement: &GenericAcknowledgement,
+        relayer: &Signer,
+    ) -> Result<(), Error> {
+        app.on_acknowledgement_packet(output, packet, acknowledgement, relayer)
+    }
+
+    fn on_timeout_packet(
+        &mut self,
+        app: &mut dyn Module,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> Result<(), Error> {
+        app.on_timeout_packet(output, packet, relayer)
+    }
+
+    fn supports_custom_msg(&self, app: &dyn Module, type_url: &str) -> bool {
+        app.supports_custom_msg(type_url)
+    }
+
+    fn on_custom_msg(
+        &mut self,
+        app: &mut dyn Module,
+        output: &mut ModuleOutputBuilder,
+        msg: ProtoAny,
+    ) -> Result<(), RoutingError> {
+        app.on_custom_msg(output, msg)
+    }
+
+    /// Returns the guard in which the callbacks of the middleware, or else of `app`, queue the
+    /// messages that they dispatch.
+    fn nested_dispatch_guard<'a>(
+        &'a mut self,
+        app: &'a mut dyn Module,
+    ) -> Option<&'a mut NestedDispatchGuard> {
+        app.nested_dispatch_guard()
+    }
+}
+
+/// The `Module` made of a [`Middleware`] wrapping the module `app`.
+#[derive(Debug)]
+pub struct MiddlewareModule<W, M> {
+    middleware: W,
+    app: M,
+}
+
+impl<W, M> MiddlewareModule<W, M> {
+    pub fn new(middleware: W, app: M) -> Self {
+        Self { middleware, app }
+    }
+
+    pub fn middleware(&self) -> &W {
+        &self.middleware
+    }
+
+    pub fn middleware_mut(&mut self) -> &mut W {
+        &mut self.middleware
+    }
+
+    pub fn app(&self) -> &M {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut M {
+        &mut self.app
+    }
+}
+
+/// Wraps a `write_fn` returned by the module that a `T` wraps, e.g. the application of a
+/// middleware, so that it is applied to the module returned by `app` rather than to the `T`.
+pub fn forward_write_fn<T, M>(write_fn: Box<WriteFn>, app: fn(&mut T) -> &mut M) -> Box<WriteFn>
+where
+    T: Any,
+    M: Module,
+{
+    Box::new(move |module: &mut dyn Any| {
+        let module = module
+            .downcast_mut::<T>()
+            .ok_or_else(|| format!("{} expected", core::any::type_name::<T>()))?;
+        write_fn(app(module).as_any_mut())
+    })
+}
+
+/// Wraps the `write_fn` of an acknowledgement returned by the module that a `T` wraps, see
+/// [`forward_write_fn`].
+pub fn forward_recv_packet_ack<T, M>(
+    ack: OnRecvPacketAck,
+    app: fn(&mut T) -> &mut M,
+) -> OnRecvPacketAck
+where
+    T: Any,
+    M: Module,
+{
+    match ack {
+        OnRecvPacketAck::Successful(ack, write_fn) => {
+            OnRecvPacketAck::Successful(ack, forward_write_fn(write_fn, app))
+        }
+        OnRecvPacketAck::Nil(write_fn) => OnRecvPacketAck::Nil(forward_write_fn(write_fn, app)),
+        ack @ OnRecvPacketAck::Failed(_) => ack,
+        OnRecvPacketAck::Deferred(ack_fn) => {
+            OnRecvPacketAck::Deferred(Box::new(move |module: &mut dyn Any, output| {
+                let module = module
+                    .downcast_mut::<T>()
+                    .ok_or_else(|| format!("{} expected", core::any::type_name::<T>()))?;
+                let ack = ack_fn(app(module).as_any_mut(), output)?;
+                Ok(forward_recv_packet_ack(ack, app))
+            }))
+        }
+    }
+}
+
+impl<W, M> Module for MiddlewareModule<W, M>
+where
+    W: Middleware,
+    M: Module,
+{
+    fn on_chan_open_init(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        self.middleware.on_chan_open_init(
+            &mut self.app,
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            version,
+        )
+    }
+
+    fn on_chan_open_try(
+        &mut self,
+        order: Order,
+        connection_hops: &[ConnectionId],
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty: &Counterparty,
+        counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        self.middleware.on_chan_open_try(
+            &mut self.app,
+            order,
+            connection_hops,
+            port_id,
+            channel_id,
+            counterparty,
+            counterparty_version,
+        )
+    }
+
+    fn supported_versions(&self, port_id: &PortId) -> Vec<Version> {
+        self.middleware.supported_versions(&self.app, port_id)
+    }
+
+    fn on_chan_open_ack(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        counterparty_version: &Version,
+    ) -> Result<ModuleExtras, Error> {
+        self.middleware
+            .on_chan_open_ack(&mut self.app, port_id, channel_id, counterparty_version)
+    }
+
+    fn on_chan_open_confirm(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, Error> {
+        self.middleware
+            .on_chan_open_confirm(&mut self.app, port_id, channel_id)
+    }
+
+    fn on_chan_close_init(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, Error> {
+        self.middleware
+            .on_chan_close_init(&mut self.app, port_id, channel_id)
+    }
+
+    fn on_chan_close_confirm(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ModuleExtras, Error> {
+        self.middleware
+            .on_chan_close_confirm(&mut self.app, port_id, channel_id)
+    }
+
+    fn on_recv_packet(
+        &self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> OnRecvPacketAck {
+        let ack = self
+            .middleware
+            .on_recv_packet(&self.app, output, packet, relayer);
+        forward_recv_packet_ack(ack, Self::app_mut)
+    }
+
+    fn on_acknowledgement_packet(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        acknowledgement: &GenericAcknowledgement,
+        relayer: &Signer,
+    ) -> Result<(), Error> {
+        self.middleware.on_acknowledgement_packet(
+            &mut self.app,
+            output,
+            packet,
+            acknowledgement,
+            relayer,
+        )
+    }
+
+    fn on_timeout_packet(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        packet: &Packet,
+        relayer: &Signer,
+    ) -> Result<(), Error> {
+        self.middleware
+            .on_timeout_packet(&mut self.app, output, packet, relayer)
+    }
+
+    fn supports_custom_msg(&self, type_url: &str) -> bool {
+        self.middleware.supports_custom_msg(&self.app, type_url)
+    }
+
+    fn on_custom_msg(
+        &mut self,
+        output: &mut ModuleOutputBuilder,
+        msg: ProtoAny,
+    ) -> Result<(), RoutingError> {
+        self.middleware.on_custom_msg(&mut self.app, output, msg)
+    }
+
+    fn nested_dispatch_guard(&mut self) -> Option<&mut NestedDispatchGuard> {
+        self.middleware.nested_dispatch_guard(&mut self.app)
+    }
+}
+
+/// Builds a `Module` out of a base module and the middlewares stacked around it, innermost
+/// first:
+///
+/// ```ignore
+/// let module = ModuleStack::new(transfer)
+///     .with(packet_forward)
+///     .with(fee)
+///     .build();
+/// ```
+///
+/// The callbacks of the resulting module run through `fee`, then `packet_forward`, before
+/// reaching `transfer`.
+#[derive(Debug)]
+pub struct ModuleStack<M>(M);
+
+impl<M: Module> ModuleStack<M> {
+    pub fn new(app: M) -> Self {
+        Self(app)
+    }
+
+    /// Wraps the stack in `middleware`, which becomes its outermost layer.
+    pub fn with<W: Middleware>(self, middleware: W) -> ModuleStack<MiddlewareModule<W, M>> {
+        ModuleStack(MiddlewareModule::new(middleware, self.0))
+    }
+
+    pub fn build(self) -> M {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics26_routing::context::WrittenAck;
+
+    #[derive(Debug, Default)]
+    struct DummyApp {
+        received: Vec<Vec<u8>>,
+    }
+
+    impl Module for DummyApp {
+        fn on_chan_open_init(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            version: &Version,
+        ) -> Result<(ModuleExtras, Version), Error> {
+            Ok((ModuleExtras::empty(), version.clone()))
+        }
+
+        fn on_chan_open_try(
+            &mut self,
+            _order: Order,
+            _connection_hops: &[ConnectionId],
+            _port_id: &PortId,
+            _channel_id: &ChannelId,
+            _counterparty: &Counterparty,
+            counterparty_version: &Version,
+        ) -> Result<(ModuleExtras, Version), Error> {
+            Ok((ModuleExtras::empty(), counterparty_version.clone()))
+        }
+
+        fn on_recv_packet(
+            &self,
+            _output: &mut ModuleOutputBuilder,
+            packet: &Packet,
+            _relayer: &Signer,
+        ) -> OnRecvPacketAck {
+            let data = packet.data.clone();
+            let ack = Box::new(GenericAcknowledgement::from(vec![1]));
+            // Packets starting with a zero are only acknowledged once received.
+            if data.first() == Some(&0) {
+                return OnRecvPacketAck::Deferred(Box::new(move |module, _| {
+                    let app = module.downcast_mut::<DummyApp>().unwrap();
+                    app.received.push(data);
+                    Ok(OnRecvPacketAck::Successful(ack, Box::new(|_| Ok(()))))
+                }));
+            }
+            OnRecvPacketAck::Successful(
+                ack,
+                Box::new(move |module| {
+                    let app = module.downcast_mut::<DummyApp>().unwrap();
+                    app.received.push(data);
+                    Ok(())
+                }),
+            )
+        }
+    }
+
+    /// Prefixes the version with its name on the way in, and strips it on the way out.
+    #[derive(Debug)]
+    struct VersionPrefix(&'static str);
+
+    impl Middleware for VersionPrefix {
+        fn on_chan_open_init(
+            &mut self,
+            app: &mut dyn Module,
+            order: Order,
+            connection_hops: &[ConnectionId],
+            port_id: &PortId,
+            channel_id: &ChannelId,
+            counterparty: &Counterparty,
+            version: &Version,
+        ) -> Result<(ModuleExtras, Version), Error> {
+            let app_version = version
+                .as_str()
+                .strip_prefix(self.0)
+                .ok_or_else(|| Error::app_module(format!("expected {} version", self.0)))?;
+            let (extras, app_version) = app.on_chan_open_init(
+                order,
+                connection_hops,
+                port_id,
+                channel_id,
+                counterparty,
+                &Version::new(app_version.to_string()),
+            )?;
+            Ok((extras, Version::new(format!("{}{}", self.0, app_version))))
+        }
+    }
+
+    /// Rejects the packets whose data starts with its byte.
+    #[derive(Debug)]
+    struct Firewall(u8);
+
+    impl Middleware for Firewall {
+        fn on_recv_packet(
+            &self,
+            app: &dyn Module,
+            output: &mut ModuleOutputBuilder,
+            packet: &Packet,
+            relayer: &Signer,
+        ) -> OnRecvPacketAck {
+            if packet.data.first() == Some(&self.0) {
+                OnRecvPacketAck::Failed(Box::new(GenericAcknowledgement::from(vec![0])))
+            } else {
+                app.on_recv_packet(output, packet, relayer)
+            }
+        }
+    }
+
+    fn recv_packet(module: &mut impl Module, data: Vec<u8>) -> bool {
+        let packet = Packet {
+            data,
+            ..Packet::default()
+        };
+        let relayer = "relayer".parse().unwrap();
+        let ack = module.on_recv_packet(&mut ModuleOutputBuilder::new(), &packet, &relayer);
+        !matches!(
+            ack.write(module.as_any_mut(), &mut ModuleOutputBuilder::new())
+                .unwrap(),
+            WrittenAck::Failed(_)
+        )
+    }
+
+    #[test]
+    fn layers_rewrite_versions_in_order() {
+        let mut module = ModuleStack::new(DummyApp::default())
+            .with(VersionPrefix("inner-"))
+            .with(VersionPrefix("outer-"))
+            .build();
+
+        let (_, version) = module
+            .on_chan_open_init(
+                Order::Unordered,
+                &[ConnectionId::default()],
+                &PortId::transfer(),
+                &ChannelId::default(),
+                &Counterparty::new(PortId::transfer(), None),
+                &Version::new("outer-inner-ics20-1".to_string()),
+            )
+            .unwrap();
+        assert_eq!(version.as_str(), "outer-inner-ics20-1");
+
+        // The layers are unwrapped outermost first.
+        assert!(module
+            .on_chan_open_init(
+                Order::Unordered,
+                &[ConnectionId::default()],
+                &PortId::transfer(),
+                &ChannelId::default(),
+                &Counterparty::new(PortId::transfer(), None),
+                &Version::new("inner-outer-ics20-1".to_string()),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn write_fns_reach_the_base_module() {
+        let mut module = ModuleStack::new(DummyApp::default())
+            .with(Firewall(0xff))
+            .with(VersionPrefix("fee-"))
+            .build();
+
+        assert!(recv_packet(&mut module, vec![1, 2]));
+        assert!(!recv_packet(&mut module, vec![0xff]));
+        assert!(recv_packet(&mut module, vec![0]));
+
+        // Callbacks that a middleware does not intercept are passed through.
+        let (_, version) = module
+            .on_chan_open_try(
+                Order::Unordered,
+                &[ConnectionId::default()],
+                &PortId::transfer(),
+                &ChannelId::default(),
+                &Counterparty::new(PortId::transfer(), Some(ChannelId::default())),
+                &Version::ics20(),
+            )
+            .unwrap();
+        assert_eq!(version, Version::ics20());
+
+        assert_eq!(module.app().app().received, vec![vec![1, 2], vec![0]]);
+    }
+}
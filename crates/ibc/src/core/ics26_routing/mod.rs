@@ -1,7 +1,16 @@
 //! ICS 26: Routing module keeps a lookup table of modules for looking
 //! the appropriate module to relay to when a packet is received.
 
+pub mod codec;
+#[cfg(test)]
+mod conformance;
 pub mod context;
 pub mod error;
 pub mod handler;
+#[cfg(any(test, feature = "invariant-checks"))]
+pub mod invariants;
+#[cfg(any(test, feature = "unstable"))]
+pub mod middleware;
+pub mod msg_service;
 pub mod msgs;
+pub mod reentrancy;
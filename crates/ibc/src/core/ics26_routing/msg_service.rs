@@ -0,0 +1,486 @@
+//! Adapter for hosts that implement the Cosmos SDK `Msg` gRPC services (`ibc.core.*.v1.Msg`)
+//! directly on top of this crate.
+//!
+//! The [`MsgService`] accepts the raw `Any` messages found in a transaction body, dispatches them
+//! through ICS26 (or to a registered application handler) and returns the corresponding
+//! protobuf-encoded `Msg*Response`, alongside the events and logs that were produced.
+
+use crate::prelude::*;
+
+use alloc::collections::BTreeMap;
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::applications::transfer::v1::MsgTransferResponse;
+use ibc_proto::ibc::core::channel::v1::{
+    MsgAcknowledgementResponse, MsgChannelCloseConfirmResponse, MsgChannelCloseInitResponse,
+    MsgChannelOpenAckResponse, MsgChannelOpenConfirmResponse, MsgChannelOpenInitResponse,
+    MsgChannelOpenTryResponse, MsgRecvPacketResponse, MsgTimeoutOnCloseResponse,
+    MsgTimeoutResponse, ResponseResultType,
+};
+use ibc_proto::ibc::core::client::v1::{
+    MsgCreateClientResponse, MsgSubmitMisbehaviourResponse, MsgUpdateClientResponse,
+    MsgUpgradeClientResponse,
+};
+use ibc_proto::ibc::core::connection::v1::{
+    MsgConnectionOpenAckResponse, MsgConnectionOpenConfirmResponse, MsgConnectionOpenInitResponse,
+    MsgConnectionOpenTryResponse,
+};
+use prost::Message;
+
+use crate::applications::transfer::context::Ics20Context;
+use crate::applications::transfer::msgs::transfer::MsgTransfer;
+use crate::applications::transfer::relay::send_transfer::send_transfer;
+use crate::core::context::ExecutionContext;
+use crate::core::ics02_client::msgs::ClientMsg;
+use crate::core::ics03_connection::msgs::ConnectionMsg;
+use crate::core::ics04_channel::msgs::{ChannelMsg, PacketMsg};
+use crate::core::ics26_routing::error::Error;
+use crate::core::ics26_routing::handler::{
+    decode_with_router, dispatch_with_outcome, DispatchOutcome, MsgReceipt,
+};
+use crate::core::ics26_routing::msgs::Ics26Envelope;
+use crate::events::IbcEvent;
+use crate::handler::{HandlerOutput, HandlerOutputBuilder};
+
+pub const CREATE_CLIENT_RESPONSE_TYPE_URL: &str = "/ibc.core.client.v1.MsgCreateClientResponse";
+pub const UPDATE_CLIENT_RESPONSE_TYPE_URL: &str = "/ibc.core.client.v1.MsgUpdateClientResponse";
+pub const UPGRADE_CLIENT_RESPONSE_TYPE_URL: &str = "/ibc.core.client.v1.MsgUpgradeClientResponse";
+pub const SUBMIT_MISBEHAVIOUR_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.client.v1.MsgSubmitMisbehaviourResponse";
+
+pub const CONN_OPEN_INIT_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.connection.v1.MsgConnectionOpenInitResponse";
+pub const CONN_OPEN_TRY_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.connection.v1.MsgConnectionOpenTryResponse";
+pub const CONN_OPEN_ACK_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.connection.v1.MsgConnectionOpenAckResponse";
+pub const CONN_OPEN_CONFIRM_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.connection.v1.MsgConnectionOpenConfirmResponse";
+
+pub const CHAN_OPEN_INIT_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.channel.v1.MsgChannelOpenInitResponse";
+pub const CHAN_OPEN_TRY_RESPONSE_TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelOpenTryResponse";
+pub const CHAN_OPEN_ACK_RESPONSE_TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelOpenAckResponse";
+pub const CHAN_OPEN_CONFIRM_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.channel.v1.MsgChannelOpenConfirmResponse";
+pub const CHAN_CLOSE_INIT_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.channel.v1.MsgChannelCloseInitResponse";
+pub const CHAN_CLOSE_CONFIRM_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.channel.v1.MsgChannelCloseConfirmResponse";
+
+pub const RECV_PACKET_RESPONSE_TYPE_URL: &str = "/ibc.core.channel.v1.MsgRecvPacketResponse";
+pub const ACKNOWLEDGEMENT_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.channel.v1.MsgAcknowledgementResponse";
+pub const TIMEOUT_RESPONSE_TYPE_URL: &str = "/ibc.core.channel.v1.MsgTimeoutResponse";
+pub const TIMEOUT_ON_CLOSE_RESPONSE_TYPE_URL: &str =
+    "/ibc.core.channel.v1.MsgTimeoutOnCloseResponse";
+
+pub const TRANSFER_RESPONSE_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransferResponse";
+
+/// Result of processing a single `Msg` through the [`MsgService`].
+pub struct MsgServiceResponse {
+    /// The protobuf-encoded `Msg*Response` matching the processed message.
+    pub response: Any,
+    /// The events and logs produced while processing the message.
+    pub receipt: MsgReceipt,
+}
+
+/// Handler for an application message (e.g. `MsgTransfer`) that is not routed by ICS26.
+pub type AppMsgHandler<Ctx> = fn(&mut Ctx, Any) -> Result<MsgServiceResponse, Error>;
+
+/// Processes raw transaction messages and produces their `Msg*Response`s.
+///
+/// Core IBC messages are always handled. Application messages are handled by the
+/// [`AppMsgHandler`]s registered for their type URL.
+pub struct MsgService<Ctx> {
+    app_handlers: BTreeMap<String, AppMsgHandler<Ctx>>,
+}
+
+impl<Ctx> Default for MsgService<Ctx> {
+    fn default() -> Self {
+        Self {
+            app_handlers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Ctx> MsgService<Ctx>
+where
+    Ctx: ExecutionContext,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the handler for application messages of type `type_url`.
+    pub fn with_app_handler(
+        mut self,
+        type_url: impl Into<String>,
+        handler: AppMsgHandler<Ctx>,
+    ) -> Self {
+        self.app_handlers.insert(type_url.into(), handler);
+        self
+    }
+
+    /// Processes a single transaction message.
+    /// If this method returns an error, the runtime is expected to rollback all state
+    /// modifications to the `Ctx` caused by all messages from the transaction that this `message`
+    /// is a part of.
+    pub fn deliver(&self, ctx: &mut Ctx, message: Any) -> Result<MsgServiceResponse, Error> {
+        if let Some(handler) = self.app_handlers.get(&message.type_url) {
+            return handler(ctx, message);
+        }
+
+        let envelope = decode_with_router(ctx, message)?;
+        let response_kind = ResponseKind::from(&envelope);
+        let HandlerOutput {
+            result,
+            log,
+            events,
+        } = dispatch_with_outcome(ctx, envelope)?;
+
+        Ok(MsgServiceResponse {
+            response: response_kind.into_response(result, &events),
+            receipt: MsgReceipt {
+                events,
+                log,
+                index: None,
+            },
+        })
+    }
+}
+
+/// [`AppMsgHandler`] for ICS20 `MsgTransfer` messages.
+pub fn deliver_transfer<Ctx>(ctx: &mut Ctx, message: Any) -> Result<MsgServiceResponse, Error>
+where
+    Ctx: Ics20Context,
+{
+    let msg = MsgTransfer::try_from(message).map_err(Error::ics20_fungible_token_transfer)?;
+
+    let mut output = HandlerOutputBuilder::new();
+    send_transfer(ctx, &mut output, msg).map_err(Error::ics20_fungible_token_transfer)?;
+    let HandlerOutput { log, events, .. } = output.with_result(());
+
+    Ok(MsgServiceResponse {
+        response: to_any(TRANSFER_RESPONSE_TYPE_URL, MsgTransferResponse {}),
+        receipt: MsgReceipt {
+            events,
+            log,
+            index: None,
+        },
+    })
+}
+
+fn to_any<M: Message>(type_url: &str, msg: M) -> Any {
+    Any {
+        type_url: type_url.to_string(),
+        value: msg.encode_to_vec(),
+    }
+}
+
+/// The kind of `Msg*Response` to produce for a given [`Ics26Envelope`].
+enum ResponseKind {
+    CreateClient,
+    UpdateClient,
+    SubmitMisbehaviour,
+    UpgradeClient,
+    ConnOpenInit,
+    ConnOpenTry,
+    ConnOpenAck,
+    ConnOpenConfirm,
+    ChanOpenInit,
+    ChanOpenTry,
+    ChanOpenAck,
+    ChanOpenConfirm,
+    ChanCloseInit,
+    ChanCloseConfirm,
+    RecvPacket,
+    Acknowledgement,
+    Timeout,
+    TimeoutOnClose,
+    Custom(String),
+}
+
+impl From<&Ics26Envelope> for ResponseKind {
+    fn from(envelope: &Ics26Envelope) -> Self {
+        match envelope {
+            Ics26Envelope::Ics2Msg(msg) => match msg {
+                ClientMsg::CreateClient(_) => Self::CreateClient,
+                ClientMsg::UpdateClient(_) => Self::UpdateClient,
+                ClientMsg::Misbehaviour(_) => Self::SubmitMisbehaviour,
+                ClientMsg::UpgradeClient(_) => Self::UpgradeClient,
+            },
+            Ics26Envelope::Ics3Msg(msg) => match msg {
+                ConnectionMsg::ConnectionOpenInit(_) => Self::ConnOpenInit,
+                ConnectionMsg::ConnectionOpenTry(_) => Self::ConnOpenTry,
+                ConnectionMsg::ConnectionOpenAck(_) => Self::ConnOpenAck,
+                ConnectionMsg::ConnectionOpenConfirm(_) => Self::ConnOpenConfirm,
+            },
+            Ics26Envelope::Ics4ChannelMsg(msg) => match msg {
+                ChannelMsg::ChannelOpenInit(_) => Self::ChanOpenInit,
+                ChannelMsg::ChannelOpenTry(_) => Self::ChanOpenTry,
+                ChannelMsg::ChannelOpenAck(_) => Self::ChanOpenAck,
+                ChannelMsg::ChannelOpenConfirm(_) => Self::ChanOpenConfirm,
+                ChannelMsg::ChannelCloseInit(_) => Self::ChanCloseInit,
+                ChannelMsg::ChannelCloseConfirm(_) => Self::ChanCloseConfirm,
+            },
+            // The responses of ibc-go to the upgrade messages are not part of `ibc-proto` yet, so
+            // only their type is reported.
+            #[cfg(any(test, feature = "unstable"))]
+            Ics26Envelope::Ics4ChannelUpgradeMsg(msg) => Self::Custom(msg.type_url().to_string()),
+            Ics26Envelope::Ics4PacketMsg(msg) => match msg {
+                PacketMsg::RecvPacket(_) => Self::RecvPacket,
+                PacketMsg::AckPacket(_) => Self::Acknowledgement,
+                PacketMsg::TimeoutPacket(_) => Self::Timeout,
+                PacketMsg::TimeoutOnClosePacket(_) => Self::TimeoutOnClose,
+            },
+            Ics26Envelope::Custom(_, msg) => Self::Custom(msg.type_url.clone()),
+        }
+    }
+}
+
+impl ResponseKind {
+    fn into_response(self, outcome: DispatchOutcome, events: &[IbcEvent]) -> Any {
+        let result = match outcome {
+            DispatchOutcome::Success => ResponseResultType::Success,
+            DispatchOutcome::NoOp => ResponseResultType::Noop,
+        } as i32;
+
+        match self {
+            Self::CreateClient => {
+                to_any(CREATE_CLIENT_RESPONSE_TYPE_URL, MsgCreateClientResponse {})
+            }
+            Self::UpdateClient => {
+                to_any(UPDATE_CLIENT_RESPONSE_TYPE_URL, MsgUpdateClientResponse {})
+            }
+            Self::SubmitMisbehaviour => to_any(
+                SUBMIT_MISBEHAVIOUR_RESPONSE_TYPE_URL,
+                MsgSubmitMisbehaviourResponse {},
+            ),
+            Self::UpgradeClient => to_any(
+                UPGRADE_CLIENT_RESPONSE_TYPE_URL,
+                MsgUpgradeClientResponse {},
+            ),
+            Self::ConnOpenInit => to_any(
+                CONN_OPEN_INIT_RESPONSE_TYPE_URL,
+                MsgConnectionOpenInitResponse {},
+            ),
+            Self::ConnOpenTry => to_any(
+                CONN_OPEN_TRY_RESPONSE_TYPE_URL,
+                MsgConnectionOpenTryResponse {},
+            ),
+            Self::ConnOpenAck => to_any(
+                CONN_OPEN_ACK_RESPONSE_TYPE_URL,
+                MsgConnectionOpenAckResponse {},
+            ),
+            Self::ConnOpenConfirm => to_any(
+                CONN_OPEN_CONFIRM_RESPONSE_TYPE_URL,
+                MsgConnectionOpenConfirmResponse {},
+            ),
+            Self::ChanOpenInit => {
+                // The channel identifier and the negotiated version are only known once the
+                // message was processed, so we recover them from the emitted event.
+                let response = events
+                    .iter()
+                    .find_map(|event| match event {
+                        IbcEvent::OpenInitChannel(e) => Some(MsgChannelOpenInitResponse {
+                            channel_id: e.channel_id().to_string(),
+                            version: e.version().to_string(),
+                        }),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                to_any(CHAN_OPEN_INIT_RESPONSE_TYPE_URL, response)
+            }
+            Self::ChanOpenTry => {
+                let response = events
+                    .iter()
+                    .find_map(|event| match event {
+                        IbcEvent::OpenTryChannel(e) => Some(MsgChannelOpenTryResponse {
+                            version: e.version().to_string(),
+                        }),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                to_any(CHAN_OPEN_TRY_RESPONSE_TYPE_URL, response)
+            }
+            Self::ChanOpenAck => to_any(
+                CHAN_OPEN_ACK_RESPONSE_TYPE_URL,
+                MsgChannelOpenAckResponse {},
+            ),
+            Self::ChanOpenConfirm => to_any(
+                CHAN_OPEN_CONFIRM_RESPONSE_TYPE_URL,
+                MsgChannelOpenConfirmResponse {},
+            ),
+            Self::ChanCloseInit => to_any(
+                CHAN_CLOSE_INIT_RESPONSE_TYPE_URL,
+                MsgChannelCloseInitResponse {},
+            ),
+            Self::ChanCloseConfirm => to_any(
+                CHAN_CLOSE_CONFIRM_RESPONSE_TYPE_URL,
+                MsgChannelCloseConfirmResponse {},
+            ),
+            Self::RecvPacket => to_any(
+                RECV_PACKET_RESPONSE_TYPE_URL,
+                MsgRecvPacketResponse { result },
+            ),
+            Self::Acknowledgement => to_any(
+                ACKNOWLEDGEMENT_RESPONSE_TYPE_URL,
+                MsgAcknowledgementResponse { result },
+            ),
+            Self::Timeout => to_any(TIMEOUT_RESPONSE_TYPE_URL, MsgTimeoutResponse { result }),
+            Self::TimeoutOnClose => to_any(
+                TIMEOUT_ON_CLOSE_RESPONSE_TYPE_URL,
+                MsgTimeoutOnCloseResponse { result },
+            ),
+            // By convention, the response to `Msg{Name}` is an `Msg{Name}Response`.
+            Self::Custom(type_url) => Any {
+                type_url: format!("{}Response", type_url),
+                value: Vec::new(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::applications::transfer::msgs::transfer::TYPE_URL as TRANSFER_TYPE_URL;
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnCounterparty, State as ConnState,
+    };
+    use crate::core::ics03_connection::version::Version as ConnVersion;
+    use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+    use crate::core::ics04_channel::msgs::acknowledgement::test_util::get_dummy_raw_msg_acknowledgement;
+    use crate::core::ics04_channel::msgs::acknowledgement::{
+        MsgAcknowledgement, TYPE_URL as ACKNOWLEDGEMENT_TYPE_URL,
+    };
+    use crate::core::ics04_channel::msgs::chan_open_init::test_util::get_dummy_raw_msg_chan_open_init;
+    use crate::core::ics04_channel::msgs::chan_open_init::TYPE_URL as CHAN_OPEN_INIT_TYPE_URL;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+    use crate::core::ics24_host::identifier::{ClientId, ConnectionId, PortId};
+    use crate::core::ics26_routing::context::{ModuleId, RouterBuilder};
+    use crate::mock::context::{MockContext, MockRouterBuilder};
+    use crate::test_utils::DummyTransferModule;
+    use crate::Height;
+    use core::time::Duration;
+
+    fn get_ctx() -> MockContext {
+        let module_id: ModuleId = "transfer".parse().unwrap();
+        let mut ctx = MockContext::default()
+            .with_client(&ClientId::default(), Height::new(0, 1).unwrap())
+            .with_connection(
+                ConnectionId::new(0),
+                ConnectionEnd::new(
+                    ConnState::Open,
+                    ClientId::default(),
+                    ConnCounterparty::new(
+                        ClientId::default(),
+                        Some(ConnectionId::new(0)),
+                        CommitmentPrefix::default(),
+                    ),
+                    vec![ConnVersion::default()],
+                    Duration::MAX,
+                ),
+            );
+        let module = DummyTransferModule::new(ctx.ibc_store_share());
+        let router = MockRouterBuilder::default()
+            .add_route(module_id.clone(), module)
+            .unwrap()
+            .build();
+        ctx.scope_port_to_module(PortId::default(), module_id);
+        ctx.with_router(router)
+    }
+
+    #[test]
+    fn chan_open_init_response() {
+        let mut ctx = get_ctx();
+        let raw_msg = get_dummy_raw_msg_chan_open_init();
+        let message = Any {
+            type_url: CHAN_OPEN_INIT_TYPE_URL.to_string(),
+            value: raw_msg.encode_to_vec(),
+        };
+
+        let res = MsgService::new().deliver(&mut ctx, message).unwrap();
+
+        assert_eq!(res.response.type_url, CHAN_OPEN_INIT_RESPONSE_TYPE_URL);
+        let response = MsgChannelOpenInitResponse::decode(res.response.value.as_slice()).unwrap();
+        assert_eq!(response.channel_id, "channel-0");
+        assert_eq!(response.version, raw_msg.channel.unwrap().version);
+        assert!(matches!(
+            res.receipt.events.first(),
+            Some(IbcEvent::OpenInitChannel(_))
+        ));
+    }
+
+    #[test]
+    fn settled_packet_ack_response() {
+        let raw_msg = get_dummy_raw_msg_acknowledgement(1);
+        let packet = MsgAcknowledgement::try_from(raw_msg.clone())
+            .unwrap()
+            .packet;
+        let channel_end = ChannelEnd::new(
+            State::Open,
+            Order::Unordered,
+            Counterparty::new(
+                packet.destination_port.clone(),
+                Some(packet.destination_channel.clone()),
+            ),
+            vec![ConnectionId::new(0)],
+            Version::ics20(),
+        );
+        // The packet commitment is already gone, as after an earlier acknowledgement.
+        let mut ctx = get_ctx().with_channel(
+            packet.source_port.clone(),
+            packet.source_channel.clone(),
+            channel_end,
+        );
+        let message = Any {
+            type_url: ACKNOWLEDGEMENT_TYPE_URL.to_string(),
+            value: raw_msg.encode_to_vec(),
+        };
+
+        let res = MsgService::new().deliver(&mut ctx, message).unwrap();
+
+        assert_eq!(res.response.type_url, ACKNOWLEDGEMENT_RESPONSE_TYPE_URL);
+        let response = MsgAcknowledgementResponse::decode(res.response.value.as_slice()).unwrap();
+        assert_eq!(response.result, ResponseResultType::Noop as i32);
+        assert!(matches!(
+            res.receipt.events.as_slice(),
+            [IbcEvent::AcknowledgePacket(_)]
+        ));
+    }
+
+    #[test]
+    fn app_handler_dispatch() {
+        fn dummy_transfer_handler(
+            _ctx: &mut MockContext,
+            _message: Any,
+        ) -> Result<MsgServiceResponse, Error> {
+            Ok(MsgServiceResponse {
+                response: to_any(TRANSFER_RESPONSE_TYPE_URL, MsgTransferResponse {}),
+                receipt: MsgReceipt {
+                    events: Vec::new(),
+                    log: vec!["transfer handled".to_string()],
+                    index: None,
+                },
+            })
+        }
+
+        let mut ctx = get_ctx();
+        let message = Any {
+            type_url: TRANSFER_TYPE_URL.to_string(),
+            value: Vec::new(),
+        };
+
+        // Without a registered handler the message is unknown to ICS26.
+        let service = MsgService::new();
+        assert!(service.deliver(&mut ctx, message.clone()).is_err());
+
+        let service = service.with_app_handler(TRANSFER_TYPE_URL, dummy_transfer_handler);
+        let res = service.deliver(&mut ctx, message).unwrap();
+        assert_eq!(res.response.type_url, TRANSFER_RESPONSE_TYPE_URL);
+        assert_eq!(res.receipt.log, vec!["transfer handled".to_string()]);
+    }
+}
@@ -2,7 +2,9 @@ use crate::prelude::*;
 
 use ibc_proto::google::protobuf::Any;
 
-use crate::core::ics02_client::msgs::{create_client, update_client, upgrade_client, ClientMsg};
+use crate::core::ics02_client::msgs::{
+    create_client, misbehaviour, update_client, upgrade_client, ClientMsg,
+};
 use crate::core::ics03_connection::msgs::{
     conn_open_ack, conn_open_confirm, conn_open_init, conn_open_try, ConnectionMsg,
 };
@@ -10,22 +12,50 @@ use crate::core::ics04_channel::msgs::{
     acknowledgement, chan_close_confirm, chan_close_init, chan_open_ack, chan_open_confirm,
     chan_open_init, chan_open_try, recv_packet, timeout, timeout_on_close, ChannelMsg, PacketMsg,
 };
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::msgs::{
+    chan_upgrade_ack, chan_upgrade_cancel, chan_upgrade_confirm, chan_upgrade_init,
+    chan_upgrade_open, chan_upgrade_timeout, chan_upgrade_try, ChannelUpgradeMsg,
+};
+use crate::core::ics26_routing::context::ModuleId;
 use crate::core::ics26_routing::error::Error;
+use crate::tx_msg::Msg;
 use ibc_proto::protobuf::Protobuf;
 
 /// Enumeration of all messages that the local ICS26 module is capable of routing.
+///
+/// New message categories may be added in the future, so matches on this enum outside of this
+/// crate must include a wildcard arm.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum Ics26Envelope {
     Ics2Msg(ClientMsg),
     Ics3Msg(ConnectionMsg),
     Ics4ChannelMsg(ChannelMsg),
+    #[cfg(any(test, feature = "unstable"))]
+    Ics4ChannelUpgradeMsg(ChannelUpgradeMsg),
     Ics4PacketMsg(PacketMsg),
+    /// A message that is not a core IBC message, routed verbatim to the module registered under
+    /// the given `ModuleId` (see [`Module::on_custom_msg`](super::context::Module::on_custom_msg)).
+    Custom(ModuleId, Any),
 }
 
 impl TryFrom<Any> for Ics26Envelope {
     type Error = Error;
 
     fn try_from(any_msg: Any) -> Result<Self, Self::Error> {
+        Self::decode(any_msg, |_| None)
+    }
+}
+
+impl Ics26Envelope {
+    /// Decodes a core IBC message, or wraps a message of any other type in a
+    /// [`Ics26Envelope::Custom`] envelope for the module returned by `resolve_module` for its
+    /// type URL.
+    pub(crate) fn decode(
+        any_msg: Any,
+        resolve_module: impl FnOnce(&str) -> Option<ModuleId>,
+    ) -> Result<Self, Error> {
         match any_msg.type_url.as_str() {
             // ICS2 messages
             create_client::TYPE_URL => {
@@ -39,6 +69,11 @@ impl TryFrom<Any> for Ics26Envelope {
                     .map_err(Error::malformed_message_bytes)?;
                 Ok(Ics26Envelope::Ics2Msg(ClientMsg::UpdateClient(domain_msg)))
             }
+            misbehaviour::TYPE_URL => {
+                let domain_msg = misbehaviour::MsgSubmitMisbehaviour::decode_vec(&any_msg.value)
+                    .map_err(Error::malformed_message_bytes)?;
+                Ok(Ics26Envelope::Ics2Msg(ClientMsg::Misbehaviour(domain_msg)))
+            }
             upgrade_client::TYPE_URL => {
                 let domain_msg = upgrade_client::MsgUpgradeClient::decode_vec(&any_msg.value)
                     .map_err(Error::malformed_message_bytes)?;
@@ -121,6 +156,68 @@ impl TryFrom<Any> for Ics26Envelope {
                     ChannelMsg::ChannelCloseConfirm(domain_msg),
                 ))
             }
+            // ICS04 channel upgrade messages
+            #[cfg(any(test, feature = "unstable"))]
+            chan_upgrade_init::TYPE_URL => {
+                let domain_msg =
+                    chan_upgrade_init::MsgChannelUpgradeInit::decode_vec(&any_msg.value)
+                        .map_err(Error::malformed_message_bytes)?;
+                Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(
+                    ChannelUpgradeMsg::ChannelUpgradeInit(domain_msg),
+                ))
+            }
+            #[cfg(any(test, feature = "unstable"))]
+            chan_upgrade_try::TYPE_URL => {
+                let domain_msg = chan_upgrade_try::MsgChannelUpgradeTry::decode_vec(&any_msg.value)
+                    .map_err(Error::malformed_message_bytes)?;
+                Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(
+                    ChannelUpgradeMsg::ChannelUpgradeTry(domain_msg),
+                ))
+            }
+            #[cfg(any(test, feature = "unstable"))]
+            chan_upgrade_ack::TYPE_URL => {
+                let domain_msg = chan_upgrade_ack::MsgChannelUpgradeAck::decode_vec(&any_msg.value)
+                    .map_err(Error::malformed_message_bytes)?;
+                Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(
+                    ChannelUpgradeMsg::ChannelUpgradeAck(domain_msg),
+                ))
+            }
+            #[cfg(any(test, feature = "unstable"))]
+            chan_upgrade_confirm::TYPE_URL => {
+                let domain_msg =
+                    chan_upgrade_confirm::MsgChannelUpgradeConfirm::decode_vec(&any_msg.value)
+                        .map_err(Error::malformed_message_bytes)?;
+                Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(
+                    ChannelUpgradeMsg::ChannelUpgradeConfirm(domain_msg),
+                ))
+            }
+            #[cfg(any(test, feature = "unstable"))]
+            chan_upgrade_open::TYPE_URL => {
+                let domain_msg =
+                    chan_upgrade_open::MsgChannelUpgradeOpen::decode_vec(&any_msg.value)
+                        .map_err(Error::malformed_message_bytes)?;
+                Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(
+                    ChannelUpgradeMsg::ChannelUpgradeOpen(domain_msg),
+                ))
+            }
+            #[cfg(any(test, feature = "unstable"))]
+            chan_upgrade_timeout::TYPE_URL => {
+                let domain_msg =
+                    chan_upgrade_timeout::MsgChannelUpgradeTimeout::decode_vec(&any_msg.value)
+                        .map_err(Error::malformed_message_bytes)?;
+                Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(
+                    ChannelUpgradeMsg::ChannelUpgradeTimeout(domain_msg),
+                ))
+            }
+            #[cfg(any(test, feature = "unstable"))]
+            chan_upgrade_cancel::TYPE_URL => {
+                let domain_msg =
+                    chan_upgrade_cancel::MsgChannelUpgradeCancel::decode_vec(&any_msg.value)
+                        .map_err(Error::malformed_message_bytes)?;
+                Ok(Ics26Envelope::Ics4ChannelUpgradeMsg(
+                    ChannelUpgradeMsg::ChannelUpgradeCancel(domain_msg),
+                ))
+            }
             // ICS04 packet messages
             recv_packet::TYPE_URL => {
                 let domain_msg = recv_packet::MsgRecvPacket::decode_vec(&any_msg.value)
@@ -150,7 +247,55 @@ impl TryFrom<Any> for Ics26Envelope {
                     PacketMsg::TimeoutOnClosePacket(domain_msg),
                 ))
             }
-            _ => Err(Error::unknown_message_type_url(any_msg.type_url)),
+            type_url => match resolve_module(type_url) {
+                Some(module_id) => Ok(Ics26Envelope::Custom(module_id, any_msg)),
+                None => Err(Error::unknown_message_type_url(any_msg.type_url)),
+            },
+        }
+    }
+}
+
+/// Encodes the message of the envelope back into the `Any` it was decoded from.
+impl From<Ics26Envelope> for Any {
+    fn from(envelope: Ics26Envelope) -> Self {
+        match envelope {
+            Ics26Envelope::Ics2Msg(msg) => match msg {
+                ClientMsg::CreateClient(msg) => msg.to_any(),
+                ClientMsg::UpdateClient(msg) => msg.to_any(),
+                ClientMsg::Misbehaviour(msg) => msg.to_any(),
+                ClientMsg::UpgradeClient(msg) => msg.to_any(),
+            },
+            Ics26Envelope::Ics3Msg(msg) => match msg {
+                ConnectionMsg::ConnectionOpenInit(msg) => msg.to_any(),
+                ConnectionMsg::ConnectionOpenTry(msg) => msg.to_any(),
+                ConnectionMsg::ConnectionOpenAck(msg) => msg.to_any(),
+                ConnectionMsg::ConnectionOpenConfirm(msg) => msg.to_any(),
+            },
+            Ics26Envelope::Ics4ChannelMsg(msg) => match msg {
+                ChannelMsg::ChannelOpenInit(msg) => msg.to_any(),
+                ChannelMsg::ChannelOpenTry(msg) => msg.to_any(),
+                ChannelMsg::ChannelOpenAck(msg) => msg.to_any(),
+                ChannelMsg::ChannelOpenConfirm(msg) => msg.to_any(),
+                ChannelMsg::ChannelCloseInit(msg) => msg.to_any(),
+                ChannelMsg::ChannelCloseConfirm(msg) => msg.to_any(),
+            },
+            #[cfg(any(test, feature = "unstable"))]
+            Ics26Envelope::Ics4ChannelUpgradeMsg(msg) => match msg {
+                ChannelUpgradeMsg::ChannelUpgradeInit(msg) => msg.to_any(),
+                ChannelUpgradeMsg::ChannelUpgradeTry(msg) => msg.to_any(),
+                ChannelUpgradeMsg::ChannelUpgradeAck(msg) => msg.to_any(),
+                ChannelUpgradeMsg::ChannelUpgradeConfirm(msg) => msg.to_any(),
+                ChannelUpgradeMsg::ChannelUpgradeOpen(msg) => msg.to_any(),
+                ChannelUpgradeMsg::ChannelUpgradeTimeout(msg) => msg.to_any(),
+                ChannelUpgradeMsg::ChannelUpgradeCancel(msg) => msg.to_any(),
+            },
+            Ics26Envelope::Ics4PacketMsg(msg) => match msg {
+                PacketMsg::RecvPacket(msg) => msg.to_any(),
+                PacketMsg::AckPacket(msg) => msg.to_any(),
+                PacketMsg::TimeoutPacket(msg) => msg.to_any(),
+                PacketMsg::TimeoutOnClosePacket(msg) => msg.to_any(),
+            },
+            Ics26Envelope::Custom(_, any_msg) => any_msg,
         }
     }
 }
@@ -0,0 +1,55 @@
+//! Re-entrancy rules for the callbacks of modules.
+//!
+//! A callback runs in the middle of the message that invoked it: e.g. `on_recv_packet` runs before
+//! the receipt of the packet is stored. A callback that dispatched a message of its own there and
+//! then, e.g. to send a packet, would interleave with the partially-applied state of the current
+//! one. Callbacks thus never dispatch messages themselves: they queue them in the
+//! [`NestedDispatchGuard`] of their module, and the core dispatches them once the current message
+//! completed, as if they followed it in the transaction. Likewise, callbacks queue the packets
+//! that they send with
+//! [`ChannelKeeper::queue_send_packet`](crate::core::ics04_channel::context::ChannelKeeper::queue_send_packet).
+
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+
+/// The maximum number of nested messages dispatched after a message, including the ones queued
+/// by the callbacks of nested messages themselves. Beyond it, the message fails, so that modules
+/// that keep queueing messages cannot stall the host.
+pub const MAX_NESTED_DISPATCHES: usize = 64;
+
+/// Queues the messages that the callbacks of a module dispatch, which the core dispatches in
+/// order, through the router, after the message during which they were queued completed. A nested
+/// message goes through the same checks as any other: if it fails, the message that queued it
+/// fails as well, and the host reverts both.
+///
+/// Modules expose their guard through
+/// [`Module::nested_dispatch_guard`](crate::core::ics26_routing::context::Module::nested_dispatch_guard).
+/// As `on_recv_packet` only has a shared reference to the module, messages that depend on the
+/// received packet are queued by the `write_fn` of its acknowledgement.
+#[derive(Clone, Debug, Default)]
+pub struct NestedDispatchGuard {
+    queue: Vec<Any>,
+}
+
+impl NestedDispatchGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `msg`, to be dispatched once the current message completed.
+    pub fn dispatch(&mut self, msg: Any) {
+        self.queue.push(msg);
+    }
+
+    /// The messages queued since the current message started.
+    pub fn queued(&self) -> &[Any] {
+        &self.queue
+    }
+
+    /// Removes the queued messages, e.g. to dispatch them, or to drop them along with the failed
+    /// message that queued them.
+    pub fn take(&mut self) -> Vec<Any> {
+        core::mem::take(&mut self.queue)
+    }
+}
@@ -16,9 +16,11 @@
 //! + In variable names,
 //!     + `on_a` implies "stored on chain A"
 //!     + `of_a` implies "of light client for chain A" So
-//! `client_state_of_a_on_b` means "the client state of light client for chain A
-//! stored on chain B"
+//!       `client_state_of_a_on_b` means "the client state of light client for chain A
+//!       stored on chain B"
 
+pub mod context;
+pub mod events;
 pub mod ics02_client;
 pub mod ics03_connection;
 pub mod ics04_channel;
@@ -26,3 +28,4 @@ pub mod ics05_port;
 pub mod ics23_commitment;
 pub mod ics24_host;
 pub mod ics26_routing;
+pub mod registry;
@@ -0,0 +1,150 @@
+//! Canonical module and port identifiers of the IBC applications.
+//!
+//! Hosts that run several applications side by side should bind each application to the port
+//! listed here, so that counterparty chains and relayers find them where they expect. The
+//! [`check_port_binding`] function is used by the router builder to reject bindings that would
+//! collide with the canonical port of another application.
+use crate::prelude::*;
+
+use core::borrow::Borrow;
+
+use crate::applications::{fee, interchain_accounts, nft_transfer, transfer};
+use crate::core::ics05_port::error::Error;
+use crate::core::ics24_host::identifier::PortId;
+use crate::core::ics26_routing::context::ModuleId;
+
+/// The module identifier of an application together with the port identifier it binds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AppIdentifiers {
+    module_id: &'static str,
+    port_id: &'static str,
+    port_id_is_prefix: bool,
+}
+
+impl AppIdentifiers {
+    const fn new(module_id: &'static str, port_id: &'static str) -> Self {
+        Self {
+            module_id,
+            port_id,
+            port_id_is_prefix: false,
+        }
+    }
+
+    const fn with_port_prefix(module_id: &'static str, port_prefix: &'static str) -> Self {
+        Self {
+            module_id,
+            port_id: port_prefix,
+            port_id_is_prefix: true,
+        }
+    }
+
+    pub fn module_id_str(&self) -> &'static str {
+        self.module_id
+    }
+
+    /// The canonical port identifier, or the port identifier prefix if the application binds
+    /// one port per owner (e.g. the ICA controller).
+    pub fn port_id_str(&self) -> &'static str {
+        self.port_id
+    }
+
+    pub fn module_id(&self) -> ModuleId {
+        self.module_id
+            .parse()
+            .expect("registered module ids are valid")
+    }
+
+    /// Returns the canonical port of this application, or `None` if the application binds one
+    /// port per owner.
+    pub fn port_id(&self) -> Option<PortId> {
+        if self.port_id_is_prefix {
+            None
+        } else {
+            Some(self.port_id.parse().expect("registered port ids are valid"))
+        }
+    }
+
+    /// Returns true iff `port_id` is reserved for this application.
+    pub fn owns_port(&self, port_id: &PortId) -> bool {
+        if self.port_id_is_prefix {
+            port_id.as_str().starts_with(self.port_id)
+        } else {
+            port_id.as_str() == self.port_id
+        }
+    }
+}
+
+/// ICS20 fungible token transfer.
+pub const TRANSFER: AppIdentifiers =
+    AppIdentifiers::new(transfer::MODULE_ID_STR, transfer::PORT_ID_STR);
+
+/// ICS29 relayer incentivisation (fee middleware).
+pub const FEE: AppIdentifiers = AppIdentifiers::new(fee::MODULE_ID_STR, fee::PORT_ID_STR);
+
+/// ICS27 interchain accounts controller. Binds one `icacontroller-{owner}` port per owner.
+pub const ICA_CONTROLLER: AppIdentifiers = AppIdentifiers::with_port_prefix(
+    interchain_accounts::CONTROLLER_MODULE_ID_STR,
+    interchain_accounts::CONTROLLER_PORT_PREFIX,
+);
+
+/// ICS27 interchain accounts host.
+pub const ICA_HOST: AppIdentifiers = AppIdentifiers::new(
+    interchain_accounts::HOST_MODULE_ID_STR,
+    interchain_accounts::HOST_PORT_ID_STR,
+);
+
+/// Interchain queries host.
+pub const ICQ: AppIdentifiers = AppIdentifiers::new("interchainquery", "icqhost");
+
+/// ICS721 non-fungible token transfer.
+pub const NFT_TRANSFER: AppIdentifiers =
+    AppIdentifiers::new(nft_transfer::MODULE_ID_STR, nft_transfer::PORT_ID_STR);
+
+/// All the applications in the registry.
+pub const ALL_APPS: [AppIdentifiers; 6] =
+    [TRANSFER, FEE, ICA_CONTROLLER, ICA_HOST, ICQ, NFT_TRANSFER];
+
+/// Returns the application for which `port_id` is reserved, if any.
+pub fn lookup_by_port(port_id: &PortId) -> Option<AppIdentifiers> {
+    ALL_APPS.into_iter().find(|app| app.owns_port(port_id))
+}
+
+/// Checks that `module_id` may bind `port_id`, i.e. that `port_id` is not reserved for another
+/// application in the registry.
+pub fn check_port_binding(module_id: &ModuleId, port_id: &PortId) -> Result<(), Error> {
+    match lookup_by_port(port_id) {
+        Some(app) if app.module_id_str() != Borrow::<str>::borrow(module_id) => Err(
+            Error::reserved_port(port_id.clone(), module_id.clone(), app.module_id()),
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_identifiers_are_valid() {
+        for app in ALL_APPS {
+            let _ = app.module_id();
+            let _ = app.port_id();
+        }
+    }
+
+    #[test]
+    fn test_check_port_binding() {
+        let transfer_port = PortId::transfer();
+        assert!(check_port_binding(&TRANSFER.module_id(), &transfer_port).is_ok());
+        assert!(check_port_binding(&ICA_HOST.module_id(), &transfer_port).is_err());
+
+        let custom_module: ModuleId = "custom".parse().unwrap();
+        let custom_port: PortId = "custom".parse().unwrap();
+        assert!(check_port_binding(&custom_module, &custom_port).is_ok());
+        assert!(check_port_binding(&custom_module, &transfer_port).is_err());
+
+        let controller_port: PortId = "icacontroller-owner".parse().unwrap();
+        assert!(check_port_binding(&ICA_CONTROLLER.module_id(), &controller_port).is_ok());
+        assert!(check_port_binding(&custom_module, &controller_port).is_err());
+    }
+}
@@ -0,0 +1,976 @@
+use core::fmt::Display;
+use core::time::Duration;
+
+use ibc_proto::google::protobuf::Any;
+use ibc_proto::ibc::core::connection::v1::ClientPaths;
+use ibc_proto::protobuf::Protobuf;
+use prost::Message;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::Digest;
+
+use super::Storage;
+use crate::clients::ics06_solomachine::client_state::ClientState as SmClientState;
+use crate::clients::ics06_solomachine::consensus_state::ConsensusState as SmConsensusState;
+use crate::clients::ics07_tendermint::client_state::ClientState as TmClientState;
+use crate::clients::ics07_tendermint::consensus_state::ConsensusState as TmConsensusState;
+use crate::clients::ics07_tendermint::host_helpers::SelfClientParams;
+use crate::core::context::{ExecutionContext, ValidationContext};
+use crate::core::ics02_client::client_state::ClientState;
+use crate::core::ics02_client::client_type::ClientType;
+use crate::core::ics02_client::consensus_state::ConsensusState;
+use crate::core::ics02_client::context::{ClientKeeper, ClientReader};
+use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::core::ics03_connection::connection::ConnectionEnd;
+use crate::core::ics03_connection::context::{ConnectionKeeper, ConnectionReader};
+use crate::core::ics03_connection::error::Error as Ics03Error;
+use crate::core::ics04_channel::channel::ChannelEnd;
+use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCommitment};
+use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+use crate::core::ics04_channel::error::Error as Ics04Error;
+use crate::core::ics04_channel::packet::{Receipt, Sequence};
+use crate::core::ics05_port::context::PortReader;
+use crate::core::ics05_port::error::Error as Ics05Error;
+use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+use crate::core::ics24_host::path::{
+    AcksPath, ChannelEndsPath, ClientConnectionsPath, ClientConsensusStatePath, ClientStatePath,
+    ClientTypePath, ClientUpdateHeightPath, ClientUpdateTimePath, CommitmentsPath, ConnectionsPath,
+    NextChannelSequencePath, NextClientSequencePath, NextConnectionSequencePath, PortsPath,
+    ReceiptsPath, SeqAcksPath, SeqRecvsPath, SeqSendsPath,
+};
+use crate::core::ics24_host::store_prefix::DEFAULT_STORE_PREFIX;
+use crate::core::ics24_host::StorePrefix;
+use crate::core::ics26_routing::context::{ModuleId, Router};
+#[cfg(any(test, feature = "mocks"))]
+use crate::mock::client_state::MockClientState;
+#[cfg(any(test, feature = "mocks"))]
+use crate::mock::consensus_state::MockConsensusState;
+use crate::prelude::*;
+use crate::timestamp::Timestamp;
+use crate::Height;
+
+/// The ports bound with [`CosmwasmContext::bind_port`], for `PortReader::port_bindings`.
+const BOUND_PORTS_KEY: &str = "boundPorts";
+
+/// The default of [`CosmwasmContext::with_max_expected_time_per_block`], as in ibc-go.
+const DEFAULT_MAX_EXPECTED_TIME_PER_BLOCK: Duration = Duration::from_secs(30);
+
+/// The context of the IBC handlers of a CosmWasm contract, over the contract's storage `S` and
+/// the router `R` of its applications.
+///
+/// The state is keyed by ICS-24 path and encoded as by ibc-go: client and consensus states as
+/// protobuf `Any`s, connection and channel ends as protobuf messages, sequences and counters as
+/// big-endian `u64`s. The indices that ibc-go derives by iterating over its store (the consensus
+/// heights of a client, the channels of a connection and the bound ports) are kept under keys of
+/// their own, as not all storages can be iterated.
+///
+/// A contract builds the context at every entry point, from the block it executes in: the host
+/// height and timestamp are those of `cosmwasm_std::Env::block`.
+pub struct CosmwasmContext<S, R> {
+    storage: S,
+    router: R,
+    host_height: Height,
+    host_timestamp: Timestamp,
+    store_prefix: Option<StorePrefix>,
+    max_expected_time_per_block: Duration,
+    self_client_params: Option<SelfClientParams>,
+}
+
+impl<S: Storage, R: Router> CosmwasmContext<S, R> {
+    pub fn new(storage: S, router: R, host_height: Height, host_timestamp: Timestamp) -> Self {
+        Self {
+            storage,
+            router,
+            host_height,
+            host_timestamp,
+            store_prefix: None,
+            max_expected_time_per_block: DEFAULT_MAX_EXPECTED_TIME_PER_BLOCK,
+            self_client_params: None,
+        }
+    }
+
+    /// Keeps the state under `{prefix}/`, rather than at the root of the storage, and advertises
+    /// the prefix to counterparties as the commitment prefix of the host.
+    pub fn with_store_prefix(self, store_prefix: StorePrefix) -> Self {
+        Self {
+            store_prefix: Some(store_prefix),
+            ..self
+        }
+    }
+
+    pub fn with_max_expected_time_per_block(self, max_expected_time_per_block: Duration) -> Self {
+        Self {
+            max_expected_time_per_block,
+            ..self
+        }
+    }
+
+    /// The parameters that the clients of the host on counterparty chains must match, for the
+    /// connection handshakes to be accepted. Without them, `ConnOpenTry` and `ConnOpenAck` fail.
+    pub fn with_self_client_params(self, params: SelfClientParams) -> Self {
+        Self {
+            self_client_params: Some(params),
+            ..self
+        }
+    }
+
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+
+    /// Binds `port_id` to the application `module_id`, typically when the contract is
+    /// instantiated.
+    pub fn bind_port(&mut self, port_id: PortId, module_id: ModuleId) -> Result<(), Ics05Error> {
+        let mut port_ids = self.bound_ports()?;
+        if !port_ids.contains(&port_id) {
+            port_ids.push(port_id.clone());
+            self.set_json(BOUND_PORTS_KEY, &port_ids);
+        }
+        self.set(PortsPath(port_id), module_id.to_string().as_bytes());
+        Ok(())
+    }
+
+    /// Records the consensus state of the host at `height`, which contracts learn of out of
+    /// band, for the counterparty to prove it stored the same in the connection handshakes.
+    pub fn store_host_consensus_state(
+        &mut self,
+        height: Height,
+        consensus_state: Box<dyn ConsensusState>,
+    ) {
+        let value = consensus_state
+            .encode_vec()
+            .expect("encoding consensus state to `Any`");
+        self.set(host_consensus_state_key(height), &value);
+    }
+
+    fn key(&self, path: impl Display) -> Vec<u8> {
+        match &self.store_prefix {
+            Some(store_prefix) => format!("{}/{}", store_prefix.as_str(), path).into_bytes(),
+            None => path.to_string().into_bytes(),
+        }
+    }
+
+    fn get(&self, path: impl Display) -> Option<Vec<u8>> {
+        self.storage.get(&self.key(path))
+    }
+
+    fn set(&mut self, path: impl Display, value: &[u8]) {
+        let key = self.key(path);
+        self.storage.set(&key, value)
+    }
+
+    fn remove(&mut self, path: impl Display) {
+        let key = self.key(path);
+        self.storage.remove(&key)
+    }
+
+    fn get_u64(&self, path: impl Display) -> Result<Option<u64>, ()> {
+        self.get(path)
+            .map(|value| <[u8; 8]>::try_from(value.as_slice()).map(u64::from_be_bytes))
+            .transpose()
+            .map_err(|_| ())
+    }
+
+    fn set_u64(&mut self, path: impl Display, value: u64) {
+        self.set(path, &value.to_be_bytes())
+    }
+
+    fn increase_counter(&mut self, path: impl Display + Copy) {
+        // A corrupted counter is reported by the reader, before any identifier is generated.
+        let counter = self.get_u64(path).ok().flatten().unwrap_or_default();
+        self.set_u64(path, counter + 1)
+    }
+
+    fn get_sequence(&self, path: impl Display) -> Result<Option<Sequence>, Ics04Error> {
+        self.get_u64(path)
+            .map(|sequence| sequence.map(Sequence::from))
+            .map_err(|_| Ics04Error::implementation_specific())
+    }
+
+    fn get_json<T: DeserializeOwned>(&self, path: impl Display) -> Result<Option<T>, ()> {
+        self.get(path)
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()
+            .map_err(|_| ())
+    }
+
+    fn set_json<T: Serialize>(&mut self, path: impl Display, value: &T) {
+        let value = serde_json::to_vec(value).expect("encoding to JSON");
+        self.set(path, &value)
+    }
+
+    fn bound_ports(&self) -> Result<Vec<PortId>, Ics05Error> {
+        self.get_json(BOUND_PORTS_KEY)
+            .map(Option::unwrap_or_default)
+            .map_err(|_| Ics05Error::implementation_specific())
+    }
+
+    /// The heights of the consensus states of the client, in ascending order.
+    fn consensus_heights(&self, client_id: &ClientId) -> Result<Vec<Height>, Ics02Error> {
+        self.get_json(consensus_heights_key(client_id))
+            .map(Option::unwrap_or_default)
+            .map_err(|_| Ics02Error::implementation_specific())
+    }
+
+    fn decode_consensus_state(&self, value: &[u8]) -> Result<Box<dyn ConsensusState>, Ics02Error> {
+        let consensus_state = Any::decode(value).map_err(Ics02Error::decode)?;
+        if let Ok(consensus_state) = TmConsensusState::try_from(consensus_state.clone()) {
+            return Ok(consensus_state.into_box());
+        }
+        if let Ok(consensus_state) = SmConsensusState::try_from(consensus_state.clone()) {
+            return Ok(consensus_state.into_box());
+        }
+        #[cfg(any(test, feature = "mocks"))]
+        if let Ok(consensus_state) = MockConsensusState::try_from(consensus_state.clone()) {
+            return Ok(consensus_state.into_box());
+        }
+        Err(Ics02Error::unknown_consensus_state_type(
+            consensus_state.type_url,
+        ))
+    }
+}
+
+fn consensus_heights_key(client_id: &ClientId) -> String {
+    format!("clients/{}/consensusHeights", client_id)
+}
+
+fn connection_channels_key(connection_id: &ConnectionId) -> String {
+    format!("connections/{}/channels", connection_id)
+}
+
+fn channel_paused_key(port_id: &PortId, channel_id: &ChannelId) -> String {
+    format!(
+        "{}/paused",
+        ChannelEndsPath(port_id.clone(), channel_id.clone())
+    )
+}
+
+fn host_consensus_state_key(height: Height) -> String {
+    format!("hostConsensusStates/{}", height)
+}
+
+impl<S: Storage, R: Router> ValidationContext for CosmwasmContext<S, R> {
+    type Router = R;
+
+    fn router(&self) -> &Self::Router {
+        &self.router
+    }
+}
+
+impl<S: Storage, R: Router> ExecutionContext for CosmwasmContext<S, R> {
+    fn router_mut(&mut self) -> &mut Self::Router {
+        &mut self.router
+    }
+}
+
+impl<S: Storage, R: Router> PortReader for CosmwasmContext<S, R> {
+    fn lookup_module_by_port(&self, port_id: &PortId) -> Result<ModuleId, Ics05Error> {
+        let module_id = self
+            .get(PortsPath(port_id.clone()))
+            .ok_or_else(|| Ics05Error::unknown_port(port_id.clone()))?;
+        String::from_utf8(module_id)
+            .ok()
+            .and_then(|module_id| module_id.parse().ok())
+            .ok_or_else(Ics05Error::implementation_specific)
+    }
+
+    fn port_bindings(&self) -> Vec<(PortId, ModuleId)> {
+        self.bound_ports()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|port_id| {
+                let module_id = self.lookup_module_by_port(&port_id).ok()?;
+                Some((port_id, module_id))
+            })
+            .collect()
+    }
+}
+
+impl<S: Storage, R: Router> ClientReader for CosmwasmContext<S, R> {
+    fn client_type(&self, client_id: &ClientId) -> Result<ClientType, Ics02Error> {
+        let client_type = self
+            .get(ClientTypePath(client_id.clone()))
+            .ok_or_else(|| Ics02Error::client_not_found(client_id.clone()))?;
+        String::from_utf8(client_type)
+            .map(ClientType::new)
+            .map_err(|_| Ics02Error::implementation_specific())
+    }
+
+    fn client_state(&self, client_id: &ClientId) -> Result<Box<dyn ClientState>, Ics02Error> {
+        let client_state = self
+            .get(ClientStatePath(client_id.clone()))
+            .ok_or_else(|| Ics02Error::client_not_found(client_id.clone()))?;
+        let client_state = Any::decode(client_state.as_slice()).map_err(Ics02Error::decode)?;
+        self.decode_stored_client_state(client_id, client_state)
+    }
+
+    fn decode_client_state(&self, client_state: Any) -> Result<Box<dyn ClientState>, Ics02Error> {
+        if let Ok(client_state) = TmClientState::try_from(client_state.clone()) {
+            return Ok(client_state.into_box());
+        }
+        if let Ok(client_state) = SmClientState::try_from(client_state.clone()) {
+            return Ok(client_state.into_box());
+        }
+        #[cfg(any(test, feature = "mocks"))]
+        if let Ok(client_state) = MockClientState::try_from(client_state.clone()) {
+            return Ok(client_state.into_box());
+        }
+        Err(Ics02Error::unknown_client_state_type(client_state.type_url))
+    }
+
+    fn consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Box<dyn ConsensusState>, Ics02Error> {
+        let consensus_state = self
+            .get(ClientConsensusStatePath::new(client_id.clone(), height))
+            .ok_or_else(|| Ics02Error::consensus_state_not_found(client_id.clone(), height))?;
+        self.decode_consensus_state(&consensus_state)
+    }
+
+    fn next_consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Option<Box<dyn ConsensusState>>, Ics02Error> {
+        self.consensus_heights(client_id)?
+            .into_iter()
+            .find(|h| *h > height)
+            .map(|h| self.consensus_state(client_id, h))
+            .transpose()
+    }
+
+    fn prev_consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Option<Box<dyn ConsensusState>>, Ics02Error> {
+        self.consensus_heights(client_id)?
+            .into_iter()
+            .rev()
+            .find(|h| *h < height)
+            .map(|h| self.consensus_state(client_id, h))
+            .transpose()
+    }
+
+    fn consensus_state_heights(&self, client_id: &ClientId) -> Result<Vec<Height>, Ics02Error> {
+        self.consensus_heights(client_id)
+    }
+
+    fn host_height(&self) -> Height {
+        self.host_height
+    }
+
+    fn host_timestamp(&self) -> Timestamp {
+        self.host_timestamp
+    }
+
+    fn host_consensus_state(&self, height: Height) -> Result<Box<dyn ConsensusState>, Ics02Error> {
+        let consensus_state = self
+            .get(host_consensus_state_key(height))
+            .ok_or_else(|| Ics02Error::missing_local_consensus_state(height))?;
+        self.decode_consensus_state(&consensus_state)
+    }
+
+    fn pending_host_consensus_state(&self) -> Result<Box<dyn ConsensusState>, Ics02Error> {
+        Err(Ics02Error::implementation_specific())
+    }
+
+    fn client_counter(&self) -> Result<u64, Ics02Error> {
+        self.get_u64(NextClientSequencePath)
+            .map(Option::unwrap_or_default)
+            .map_err(|_| Ics02Error::implementation_specific())
+    }
+}
+
+impl<S: Storage, R: Router> ClientKeeper for CosmwasmContext<S, R> {
+    fn store_client_type(
+        &mut self,
+        client_id: ClientId,
+        client_type: ClientType,
+    ) -> Result<(), Ics02Error> {
+        self.set(ClientTypePath(client_id), client_type.as_str().as_bytes());
+        Ok(())
+    }
+
+    fn store_client_state(
+        &mut self,
+        client_id: ClientId,
+        client_state: Box<dyn ClientState>,
+    ) -> Result<(), Ics02Error> {
+        let value = client_state
+            .encode_vec()
+            .expect("encoding client state to `Any`");
+        self.set(ClientStatePath(client_id), &value);
+        Ok(())
+    }
+
+    fn store_consensus_state(
+        &mut self,
+        client_id: ClientId,
+        height: Height,
+        consensus_state: Box<dyn ConsensusState>,
+    ) -> Result<(), Ics02Error> {
+        let mut heights = self.consensus_heights(&client_id)?;
+        if let Err(pos) = heights.binary_search(&height) {
+            heights.insert(pos, height);
+            self.set_json(consensus_heights_key(&client_id), &heights);
+        }
+
+        let value = consensus_state
+            .encode_vec()
+            .expect("encoding consensus state to `Any`");
+        self.set(
+            ClientConsensusStatePath::new(client_id.clone(), height),
+            &value,
+        );
+        Ok(())
+    }
+
+    fn increase_client_counter(&mut self) {
+        self.increase_counter(NextClientSequencePath)
+    }
+
+    fn store_update_time(
+        &mut self,
+        client_id: ClientId,
+        height: Height,
+        timestamp: Timestamp,
+    ) -> Result<(), Ics02Error> {
+        self.set_u64(
+            ClientUpdateTimePath::new(client_id.clone(), height),
+            timestamp.nanoseconds(),
+        );
+        Ok(())
+    }
+
+    fn store_update_height(
+        &mut self,
+        client_id: ClientId,
+        height: Height,
+        host_height: Height,
+    ) -> Result<(), Ics02Error> {
+        self.set(
+            ClientUpdateHeightPath::new(client_id.clone(), height),
+            host_height.to_string().as_bytes(),
+        );
+        Ok(())
+    }
+}
+
+impl<S: Storage, R: Router> ConnectionReader for CosmwasmContext<S, R> {
+    fn connection_end(&self, conn_id: &ConnectionId) -> Result<ConnectionEnd, Ics03Error> {
+        let connection_end = self
+            .get(ConnectionsPath(conn_id.clone()))
+            .ok_or_else(|| Ics03Error::connection_not_found(conn_id.clone()))?;
+        ConnectionEnd::decode_vec(&connection_end)
+            .map_err(|_| Ics03Error::implementation_specific())
+    }
+
+    fn client_state(&self, client_id: &ClientId) -> Result<Box<dyn ClientState>, Ics03Error> {
+        ClientReader::client_state(self, client_id).map_err(Ics03Error::ics02_client)
+    }
+
+    fn decode_client_state(&self, client_state: Any) -> Result<Box<dyn ClientState>, Ics03Error> {
+        ClientReader::decode_client_state(self, client_state).map_err(Ics03Error::ics02_client)
+    }
+
+    fn host_current_height(&self) -> Height {
+        self.host_height
+    }
+
+    fn host_oldest_height(&self) -> Height {
+        // The consensus states recorded by the contract are never pruned.
+        Height::new(self.host_height.revision_number(), 1).expect("height 1 is valid")
+    }
+
+    fn max_expected_time_per_block(&self) -> Duration {
+        self.max_expected_time_per_block
+    }
+
+    fn commitment_prefix(&self) -> CommitmentPrefix {
+        match &self.store_prefix {
+            Some(store_prefix) => store_prefix.commitment_prefix(),
+            None => CommitmentPrefix::try_from(DEFAULT_STORE_PREFIX.as_bytes().to_vec())
+                .expect("the default store prefix is not empty"),
+        }
+    }
+
+    fn client_consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Box<dyn ConsensusState>, Ics03Error> {
+        self.consensus_state(client_id, height)
+            .map_err(Ics03Error::ics02_client)
+    }
+
+    fn host_consensus_state(&self, height: Height) -> Result<Box<dyn ConsensusState>, Ics03Error> {
+        ClientReader::host_consensus_state(self, height).map_err(Ics03Error::ics02_client)
+    }
+
+    fn connection_counter(&self) -> Result<u64, Ics03Error> {
+        self.get_u64(NextConnectionSequencePath)
+            .map(Option::unwrap_or_default)
+            .map_err(|_| Ics03Error::implementation_specific())
+    }
+
+    fn self_client_params(&self) -> Option<SelfClientParams> {
+        self.self_client_params.clone()
+    }
+}
+
+impl<S: Storage, R: Router> ConnectionKeeper for CosmwasmContext<S, R> {
+    fn store_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        connection_end: &ConnectionEnd,
+    ) -> Result<(), Ics03Error> {
+        let value = connection_end
+            .encode_vec()
+            .expect("encoding connection end");
+        self.set(ConnectionsPath(connection_id), &value);
+        Ok(())
+    }
+
+    fn store_connection_to_client(
+        &mut self,
+        connection_id: ConnectionId,
+        client_id: &ClientId,
+    ) -> Result<(), Ics03Error> {
+        let path = ClientConnectionsPath(client_id.clone());
+        let mut client_paths = match self.get(path.clone()) {
+            Some(value) => ClientPaths::decode(value.as_slice())
+                .map_err(|_| Ics03Error::implementation_specific())?,
+            None => ClientPaths::default(),
+        };
+        client_paths.paths.push(connection_id.to_string());
+        self.set(path, &client_paths.encode_to_vec());
+        Ok(())
+    }
+
+    fn increase_connection_counter(&mut self) {
+        self.increase_counter(NextConnectionSequencePath)
+    }
+}
+
+impl<S: Storage, R: Router> ChannelReader for CosmwasmContext<S, R> {
+    fn channel_end(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ChannelEnd, Ics04Error> {
+        let channel_end = self
+            .get(ChannelEndsPath(port_id.clone(), channel_id.clone()))
+            .ok_or_else(|| Ics04Error::channel_not_found(port_id.clone(), channel_id.clone()))?;
+        ChannelEnd::decode_vec(&channel_end).map_err(|_| Ics04Error::implementation_specific())
+    }
+
+    fn connection_end(&self, cid: &ConnectionId) -> Result<ConnectionEnd, Ics04Error> {
+        ConnectionReader::connection_end(self, cid).map_err(Ics04Error::ics03_connection)
+    }
+
+    fn connection_channels(
+        &self,
+        cid: &ConnectionId,
+    ) -> Result<Vec<(PortId, ChannelId)>, Ics04Error> {
+        self.get_json(connection_channels_key(cid))
+            .map_err(|_| Ics04Error::implementation_specific())?
+            .ok_or_else(Ics04Error::missing_channel)
+    }
+
+    fn client_state(&self, client_id: &ClientId) -> Result<Box<dyn ClientState>, Ics04Error> {
+        ClientReader::client_state(self, client_id)
+            .map_err(|e| Ics04Error::ics03_connection(Ics03Error::ics02_client(e)))
+    }
+
+    fn client_consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Box<dyn ConsensusState>, Ics04Error> {
+        ClientReader::consensus_state(self, client_id, height)
+            .map_err(|e| Ics04Error::ics03_connection(Ics03Error::ics02_client(e)))
+    }
+
+    fn get_next_sequence_send(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Sequence, Ics04Error> {
+        self.get_sequence(SeqSendsPath(port_id.clone(), channel_id.clone()))?
+            .ok_or_else(|| Ics04Error::missing_next_send_seq(port_id.clone(), channel_id.clone()))
+    }
+
+    fn get_next_sequence_recv(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Sequence, Ics04Error> {
+        self.get_sequence(SeqRecvsPath(port_id.clone(), channel_id.clone()))?
+            .ok_or_else(|| Ics04Error::missing_next_recv_seq(port_id.clone(), channel_id.clone()))
+    }
+
+    fn get_next_sequence_ack(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Sequence, Ics04Error> {
+        self.get_sequence(SeqAcksPath(port_id.clone(), channel_id.clone()))?
+            .ok_or_else(|| Ics04Error::missing_next_ack_seq(port_id.clone(), channel_id.clone()))
+    }
+
+    fn get_packet_commitment(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        seq: Sequence,
+    ) -> Result<PacketCommitment, Ics04Error> {
+        let path = CommitmentsPath::new(port_id.clone(), channel_id.clone(), seq);
+        self.get(path)
+            .map(PacketCommitment::from)
+            .ok_or_else(|| Ics04Error::packet_commitment_not_found(seq))
+    }
+
+    fn get_packet_receipt(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        seq: Sequence,
+    ) -> Result<Receipt, Ics04Error> {
+        let path = ReceiptsPath::new(port_id.clone(), channel_id.clone(), seq);
+        self.get(path)
+            .and_then(|receipt| Receipt::from_bytes(&receipt))
+            .ok_or_else(|| Ics04Error::packet_receipt_not_found(seq))
+    }
+
+    fn get_packet_acknowledgement(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        seq: Sequence,
+    ) -> Result<AcknowledgementCommitment, Ics04Error> {
+        let path = AcksPath::new(port_id.clone(), channel_id.clone(), seq);
+        self.get(path)
+            .map(AcknowledgementCommitment::from)
+            .ok_or_else(|| Ics04Error::packet_acknowledgement_not_found(seq))
+    }
+
+    fn hash(&self, value: Vec<u8>) -> Vec<u8> {
+        sha2::Sha256::digest(value).to_vec()
+    }
+
+    fn host_height(&self) -> Height {
+        self.host_height
+    }
+
+    fn host_timestamp(&self) -> Timestamp {
+        self.host_timestamp
+    }
+
+    fn host_consensus_state(&self, height: Height) -> Result<Box<dyn ConsensusState>, Ics04Error> {
+        ConnectionReader::host_consensus_state(self, height).map_err(Ics04Error::ics03_connection)
+    }
+
+    fn pending_host_consensus_state(&self) -> Result<Box<dyn ConsensusState>, Ics04Error> {
+        ClientReader::pending_host_consensus_state(self)
+            .map_err(|e| Ics04Error::ics03_connection(Ics03Error::ics02_client(e)))
+    }
+
+    fn client_update_time(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Timestamp, Ics04Error> {
+        self.get_u64(ClientUpdateTimePath::new(client_id.clone(), height))
+            .ok()
+            .flatten()
+            .and_then(|nanos| Timestamp::from_nanoseconds(nanos).ok())
+            .ok_or_else(|| Ics04Error::processed_time_not_found(client_id.clone(), height))
+    }
+
+    fn client_update_height(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Height, Ics04Error> {
+        self.get(ClientUpdateHeightPath::new(client_id.clone(), height))
+            .and_then(|value| String::from_utf8(value).ok())
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| Ics04Error::processed_height_not_found(client_id.clone(), height))
+    }
+
+    fn channel_counter(&self) -> Result<u64, Ics04Error> {
+        self.get_u64(NextChannelSequencePath)
+            .map(Option::unwrap_or_default)
+            .map_err(|_| Ics04Error::implementation_specific())
+    }
+
+    fn max_expected_time_per_block(&self) -> Duration {
+        ConnectionReader::max_expected_time_per_block(self)
+    }
+
+    fn is_channel_paused(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<bool, Ics04Error> {
+        Ok(self.get(channel_paused_key(port_id, channel_id)).is_some())
+    }
+}
+
+impl<S: Storage, R: Router> ChannelKeeper for CosmwasmContext<S, R> {
+    fn store_packet_commitment(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        sequence: Sequence,
+        commitment: PacketCommitment,
+    ) -> Result<(), Ics04Error> {
+        let path = CommitmentsPath::new(port_id, channel_id, sequence);
+        self.set(path, &commitment.into_vec());
+        Ok(())
+    }
+
+    fn delete_packet_commitment(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        seq: Sequence,
+    ) -> Result<(), Ics04Error> {
+        let path = CommitmentsPath::new(port_id.clone(), channel_id.clone(), seq);
+        self.remove(path);
+        Ok(())
+    }
+
+    fn store_packet_receipt(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        sequence: Sequence,
+        receipt: Receipt,
+    ) -> Result<(), Ics04Error> {
+        let path = ReceiptsPath::new(port_id, channel_id, sequence);
+        self.set(path, receipt.as_bytes());
+        Ok(())
+    }
+
+    fn store_packet_acknowledgement(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        sequence: Sequence,
+        ack_commitment: AcknowledgementCommitment,
+    ) -> Result<(), Ics04Error> {
+        let path = AcksPath::new(port_id, channel_id, sequence);
+        self.set(path, &ack_commitment.into_vec());
+        Ok(())
+    }
+
+    fn delete_packet_acknowledgement(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) -> Result<(), Ics04Error> {
+        let path = AcksPath::new(port_id.clone(), channel_id.clone(), sequence);
+        self.remove(path);
+        Ok(())
+    }
+
+    fn store_connection_channels(
+        &mut self,
+        conn_id: ConnectionId,
+        port_id: PortId,
+        channel_id: ChannelId,
+    ) -> Result<(), Ics04Error> {
+        let key = connection_channels_key(&conn_id);
+        let mut channels: Vec<(PortId, ChannelId)> = self
+            .get_json(&key)
+            .map_err(|_| Ics04Error::implementation_specific())?
+            .unwrap_or_default();
+        channels.push((port_id, channel_id));
+        self.set_json(key, &channels);
+        Ok(())
+    }
+
+    fn store_channel(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        channel_end: ChannelEnd,
+    ) -> Result<(), Ics04Error> {
+        let value = channel_end.encode_vec().expect("encoding channel end");
+        self.set(ChannelEndsPath(port_id, channel_id), &value);
+        Ok(())
+    }
+
+    fn store_next_sequence_send(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        seq: Sequence,
+    ) -> Result<(), Ics04Error> {
+        self.set_u64(SeqSendsPath(port_id, channel_id), seq.into());
+        Ok(())
+    }
+
+    fn store_next_sequence_recv(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        seq: Sequence,
+    ) -> Result<(), Ics04Error> {
+        self.set_u64(SeqRecvsPath(port_id, channel_id), seq.into());
+        Ok(())
+    }
+
+    fn store_next_sequence_ack(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        seq: Sequence,
+    ) -> Result<(), Ics04Error> {
+        self.set_u64(SeqAcksPath(port_id, channel_id), seq.into());
+        Ok(())
+    }
+
+    fn increase_channel_counter(&mut self) {
+        self.increase_counter(NextChannelSequencePath)
+    }
+
+    fn store_channel_paused(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        paused: bool,
+    ) -> Result<(), Ics04Error> {
+        let key = channel_paused_key(&port_id, &channel_id);
+        if paused {
+            self.set(key, &[1]);
+        } else {
+            self.remove(key);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::collections::BTreeMap;
+
+    use test_log::test;
+
+    use crate::core::ics02_client::msgs::create_client::MsgCreateClient;
+    use crate::core::ics03_connection::msgs::conn_open_init::test_util::get_dummy_raw_msg_conn_open_init;
+    use crate::core::ics03_connection::msgs::conn_open_init::MsgConnectionOpenInit;
+    use crate::core::ics26_routing::handler::deliver;
+    use crate::mock::client_state::client_type as mock_client_type;
+    use crate::mock::context::MockRouter;
+    use crate::mock::header::MockHeader;
+    use crate::test_utils::get_dummy_account_id;
+    use crate::tx_msg::Msg;
+
+    type Context = CosmwasmContext<BTreeMap<Vec<u8>, Vec<u8>>, MockRouter>;
+
+    fn context() -> Context {
+        CosmwasmContext::new(
+            BTreeMap::new(),
+            MockRouter::default(),
+            Height::new(0, 10).unwrap(),
+            Timestamp::from_nanoseconds(1_665_000_000_000_000_000).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_client_and_connection_handshake() {
+        let mut ctx = context();
+        let height = Height::new(0, 5).unwrap();
+
+        let msg = MsgCreateClient::new(
+            MockClientState::new(MockHeader::new(height)).into(),
+            MockConsensusState::new(MockHeader::new(height)).into(),
+            get_dummy_account_id(),
+        )
+        .unwrap();
+        deliver(&mut ctx, msg.to_any()).unwrap();
+
+        let client_id = ClientId::new(mock_client_type(), 0).unwrap();
+        assert_eq!(ctx.client_counter().unwrap(), 1);
+        assert_eq!(ctx.client_type(&client_id).unwrap(), mock_client_type());
+        assert_eq!(
+            ClientReader::client_latest_height(&ctx, &client_id).unwrap(),
+            height
+        );
+        assert!(ctx.consensus_state(&client_id, height).is_ok());
+        assert_eq!(ctx.consensus_state_heights(&client_id).unwrap(), [height]);
+        assert_eq!(
+            ctx.client_update_height(&client_id, height).unwrap(),
+            ctx.host_height
+        );
+        assert!(ctx
+            .storage()
+            .contains_key(b"clients/9999-mock-0/clientState".as_slice()));
+
+        let msg = MsgConnectionOpenInit {
+            client_id_on_a: client_id.clone(),
+            ..MsgConnectionOpenInit::try_from(get_dummy_raw_msg_conn_open_init()).unwrap()
+        };
+        deliver(&mut ctx, msg.to_any()).unwrap();
+
+        let connection_id = ConnectionId::new(0);
+        assert_eq!(ctx.connection_counter().unwrap(), 1);
+        let connection_end = ConnectionReader::connection_end(&ctx, &connection_id).unwrap();
+        assert_eq!(connection_end.client_id(), &client_id);
+        let client_paths = ClientPaths::decode(
+            ctx.storage()
+                .get(b"clients/9999-mock-0/connections".as_slice())
+                .unwrap()
+                .as_slice(),
+        )
+        .unwrap();
+        assert_eq!(client_paths.paths, ["connection-0"]);
+    }
+
+    #[test]
+    fn test_packet_state() {
+        let mut ctx = context().with_store_prefix(StorePrefix::new("ibc".to_string()).unwrap());
+        let port_id = PortId::transfer();
+        let channel_id = ChannelId::new(0);
+        let seq = Sequence::from(7);
+
+        assert!(ctx.get_next_sequence_send(&port_id, &channel_id).is_err());
+        ctx.store_next_sequence_send(port_id.clone(), channel_id.clone(), seq)
+            .unwrap();
+        assert_eq!(
+            ctx.get_next_sequence_send(&port_id, &channel_id).unwrap(),
+            seq
+        );
+        assert_eq!(
+            ctx.storage()
+                .get(b"ibc/nextSequenceSend/ports/transfer/channels/channel-0".as_slice()),
+            Some(&7u64.to_be_bytes().to_vec())
+        );
+
+        ctx.store_packet_receipt(port_id.clone(), channel_id.clone(), seq, Receipt::Ok)
+            .unwrap();
+        assert!(ctx.get_packet_receipt(&port_id, &channel_id, seq).is_ok());
+
+        ctx.bind_port(port_id.clone(), "transfer".parse().unwrap())
+            .unwrap();
+        ctx.bind_port(port_id.clone(), "transfer".parse().unwrap())
+            .unwrap();
+        assert_eq!(
+            ctx.port_bindings(),
+            [(port_id, "transfer".parse().unwrap())]
+        );
+    }
+}
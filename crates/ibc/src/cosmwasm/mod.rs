@@ -0,0 +1,78 @@
+//! Adapter running the IBC handlers inside a CosmWasm contract.
+//!
+//! [`CosmwasmContext`] implements the readers and keepers of the core modules, and thus
+//! [`ExecutionContext`](crate::core::context::ExecutionContext), over a key-value [`Storage`]. The
+//! state is kept under the standard ICS-24 paths (e.g. `clients/{client-id}/clientState`), encoded
+//! as by ibc-go, so that relayers query and prove it as on any other chain.
+//!
+//! [`Storage`] mirrors `cosmwasm_std::Storage`, which contracts wire in with a newtype:
+//!
+//! ```ignore
+//! struct CwStorage<'a>(&'a mut dyn cosmwasm_std::Storage);
+//!
+//! impl ibc::cosmwasm::Storage for CwStorage<'_> {
+//!     fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+//!         self.0.get(key)
+//!     }
+//!
+//!     fn set(&mut self, key: &[u8], value: &[u8]) {
+//!         self.0.set(key, value)
+//!     }
+//!
+//!     fn remove(&mut self, key: &[u8]) {
+//!         self.0.remove(key)
+//!     }
+//! }
+//!
+//! let mut ctx = CosmwasmContext::new(CwStorage(deps.storage), router, host_height, host_timestamp);
+//! let receipt = ibc::core::ics26_routing::handler::deliver(&mut ctx, msg)?;
+//! ```
+
+pub use context::CosmwasmContext;
+
+use alloc::collections::BTreeMap;
+
+use crate::prelude::*;
+
+pub mod context;
+
+/// Byte-oriented key-value store, with the interface of `cosmwasm_std::Storage`.
+pub trait Storage {
+    /// Returns the value stored under `key`, if any.
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
+
+    /// Stores `value` under `key`, replacing the previous value if any.
+    fn set(&mut self, key: &[u8], value: &[u8]);
+
+    /// Removes the value stored under `key`, if any.
+    fn remove(&mut self, key: &[u8]);
+}
+
+impl<S: Storage + ?Sized> Storage for &mut S {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        (**self).get(key)
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        (**self).set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        (**self).remove(key)
+    }
+}
+
+/// In-memory storage, e.g. for tests.
+impl Storage for BTreeMap<Vec<u8>, Vec<u8>> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        BTreeMap::get(self, key).cloned()
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        BTreeMap::remove(self, key);
+    }
+}
@@ -1,9 +1,17 @@
 use core::any::Any;
 
-pub trait AsAny: Any {
+/// Implemented for all types, to upcast trait objects to `&dyn Any`. Sealed, so that it can be
+/// extended without breaking hosts.
+pub trait AsAny: Any + sealed::Sealed {
     fn as_any(&self) -> &dyn Any;
 }
 
+mod sealed {
+    pub trait Sealed {}
+
+    impl<M: core::any::Any> Sealed for M {}
+}
+
 impl<M: Any> AsAny for M {
     fn as_any(&self) -> &dyn Any {
         self
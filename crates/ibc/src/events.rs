@@ -1,6 +1,9 @@
 use crate::prelude::*;
 
-use core::convert::{TryFrom, TryInto};
+pub mod bus;
+
+use core::convert::{Infallible, TryFrom, TryInto};
+use core::fmt::{Display, Error as FmtError, Formatter};
 use core::str::FromStr;
 use flex_error::{define_error, TraceError};
 use serde_derive::{Deserialize, Serialize};
@@ -46,6 +49,10 @@ define_error! {
             { key: String }
             | e | { format_args!("missing event key {}", e.key) },
 
+        MalformedAttribute
+            { key: String, value: String }
+            | e | { format_args!("malformed value '{}' of event key {}", e.value, e.key) },
+
         Decode
             [ TraceError<prost::DecodeError> ]
             | _ | { "error decoding protobuf" },
@@ -110,6 +117,15 @@ const CHANNEL_OPEN_ACK_EVENT: &str = "channel_open_ack";
 const CHANNEL_OPEN_CONFIRM_EVENT: &str = "channel_open_confirm";
 const CHANNEL_CLOSE_INIT_EVENT: &str = "channel_close_init";
 const CHANNEL_CLOSE_CONFIRM_EVENT: &str = "channel_close_confirm";
+const CHANNEL_UPGRADE_INIT_EVENT: &str = "channel_upgrade_init";
+const CHANNEL_UPGRADE_TRY_EVENT: &str = "channel_upgrade_try";
+const CHANNEL_UPGRADE_ACK_EVENT: &str = "channel_upgrade_ack";
+const CHANNEL_UPGRADE_CONFIRM_EVENT: &str = "channel_upgrade_confirm";
+const CHANNEL_UPGRADE_OPEN_EVENT: &str = "channel_upgrade_open";
+const CHANNEL_FLUSH_COMPLETE_EVENT: &str = "channel_flush_complete";
+const CHANNEL_UPGRADE_CANCEL_EVENT: &str = "channel_upgrade_cancelled";
+const CHANNEL_UPGRADE_TIMEOUT_EVENT: &str = "channel_upgrade_timeout";
+const CHANNEL_UPGRADE_ERROR_EVENT: &str = "channel_upgrade_error";
 /// Packet event types
 const SEND_PACKET_EVENT: &str = "send_packet";
 const RECEIVE_PACKET_EVENT: &str = "receive_packet";
@@ -117,6 +133,8 @@ const WRITE_ACK_EVENT: &str = "write_acknowledgement";
 const ACK_PACKET_EVENT: &str = "acknowledge_packet";
 const TIMEOUT_EVENT: &str = "timeout_packet";
 const CHANNEL_CLOSED_EVENT: &str = "channel_close";
+const CHANNEL_PAUSED_EVENT: &str = "channel_paused";
+const CHANNEL_RESUMED_EVENT: &str = "channel_resumed";
 
 /// Events types
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -135,7 +153,18 @@ pub enum IbcEventType {
     OpenConfirmChannel,
     CloseInitChannel,
     CloseConfirmChannel,
+    UpgradeInitChannel,
+    UpgradeTryChannel,
+    UpgradeAckChannel,
+    UpgradeConfirmChannel,
+    UpgradeOpenChannel,
+    FlushCompleteChannel,
+    UpgradeCancelChannel,
+    UpgradeTimeoutChannel,
+    UpgradeErrorChannel,
     ChannelClosed,
+    ChannelPaused,
+    ChannelResumed,
     SendPacket,
     ReceivePacket,
     WriteAck,
@@ -161,7 +190,18 @@ impl IbcEventType {
             IbcEventType::OpenConfirmChannel => CHANNEL_OPEN_CONFIRM_EVENT,
             IbcEventType::CloseInitChannel => CHANNEL_CLOSE_INIT_EVENT,
             IbcEventType::CloseConfirmChannel => CHANNEL_CLOSE_CONFIRM_EVENT,
+            IbcEventType::UpgradeInitChannel => CHANNEL_UPGRADE_INIT_EVENT,
+            IbcEventType::UpgradeTryChannel => CHANNEL_UPGRADE_TRY_EVENT,
+            IbcEventType::UpgradeAckChannel => CHANNEL_UPGRADE_ACK_EVENT,
+            IbcEventType::UpgradeConfirmChannel => CHANNEL_UPGRADE_CONFIRM_EVENT,
+            IbcEventType::UpgradeOpenChannel => CHANNEL_UPGRADE_OPEN_EVENT,
+            IbcEventType::FlushCompleteChannel => CHANNEL_FLUSH_COMPLETE_EVENT,
+            IbcEventType::UpgradeCancelChannel => CHANNEL_UPGRADE_CANCEL_EVENT,
+            IbcEventType::UpgradeTimeoutChannel => CHANNEL_UPGRADE_TIMEOUT_EVENT,
+            IbcEventType::UpgradeErrorChannel => CHANNEL_UPGRADE_ERROR_EVENT,
             IbcEventType::ChannelClosed => CHANNEL_CLOSED_EVENT,
+            IbcEventType::ChannelPaused => CHANNEL_PAUSED_EVENT,
+            IbcEventType::ChannelResumed => CHANNEL_RESUMED_EVENT,
             IbcEventType::SendPacket => SEND_PACKET_EVENT,
             IbcEventType::ReceivePacket => RECEIVE_PACKET_EVENT,
             IbcEventType::WriteAck => WRITE_ACK_EVENT,
@@ -191,12 +231,23 @@ impl FromStr for IbcEventType {
             CHANNEL_OPEN_CONFIRM_EVENT => Ok(IbcEventType::OpenConfirmChannel),
             CHANNEL_CLOSE_INIT_EVENT => Ok(IbcEventType::CloseInitChannel),
             CHANNEL_CLOSE_CONFIRM_EVENT => Ok(IbcEventType::CloseConfirmChannel),
+            CHANNEL_UPGRADE_INIT_EVENT => Ok(IbcEventType::UpgradeInitChannel),
+            CHANNEL_UPGRADE_TRY_EVENT => Ok(IbcEventType::UpgradeTryChannel),
+            CHANNEL_UPGRADE_ACK_EVENT => Ok(IbcEventType::UpgradeAckChannel),
+            CHANNEL_UPGRADE_CONFIRM_EVENT => Ok(IbcEventType::UpgradeConfirmChannel),
+            CHANNEL_UPGRADE_OPEN_EVENT => Ok(IbcEventType::UpgradeOpenChannel),
+            CHANNEL_FLUSH_COMPLETE_EVENT => Ok(IbcEventType::FlushCompleteChannel),
+            CHANNEL_UPGRADE_CANCEL_EVENT => Ok(IbcEventType::UpgradeCancelChannel),
+            CHANNEL_UPGRADE_TIMEOUT_EVENT => Ok(IbcEventType::UpgradeTimeoutChannel),
+            CHANNEL_UPGRADE_ERROR_EVENT => Ok(IbcEventType::UpgradeErrorChannel),
             SEND_PACKET_EVENT => Ok(IbcEventType::SendPacket),
             RECEIVE_PACKET_EVENT => Ok(IbcEventType::ReceivePacket),
             WRITE_ACK_EVENT => Ok(IbcEventType::WriteAck),
             ACK_PACKET_EVENT => Ok(IbcEventType::AckPacket),
             TIMEOUT_EVENT => Ok(IbcEventType::Timeout),
             CHANNEL_CLOSED_EVENT => Ok(IbcEventType::ChannelClosed),
+            CHANNEL_PAUSED_EVENT => Ok(IbcEventType::ChannelPaused),
+            CHANNEL_RESUMED_EVENT => Ok(IbcEventType::ChannelResumed),
             // from_str() for `APP_MODULE_EVENT` MUST fail because a `ModuleEvent`'s type isn't constant
             _ => Err(Error::incorrect_event_type(s.to_string())),
         }
@@ -204,7 +255,7 @@ impl FromStr for IbcEventType {
 }
 
 /// Events created by the IBC component of a chain, destined for a relayer.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum IbcEvent {
     CreateClient(ClientEvents::CreateClient),
     UpdateClient(ClientEvents::UpdateClient),
@@ -222,6 +273,7 @@ pub enum IbcEvent {
     OpenConfirmChannel(ChannelEvents::OpenConfirm),
     CloseInitChannel(ChannelEvents::CloseInit),
     CloseConfirmChannel(ChannelEvents::CloseConfirm),
+    ChannelUpgrade(ChannelEvents::ChannelUpgrade),
 
     SendPacket(ChannelEvents::SendPacket),
     ReceivePacket(ChannelEvents::ReceivePacket),
@@ -229,6 +281,8 @@ pub enum IbcEvent {
     AcknowledgePacket(ChannelEvents::AcknowledgePacket),
     TimeoutPacket(ChannelEvents::TimeoutPacket),
     ChannelClosed(ChannelEvents::ChannelClosed),
+    ChannelPaused(ChannelEvents::ChannelPaused),
+    ChannelResumed(ChannelEvents::ChannelResumed),
 
     AppModule(ModuleEvent),
 }
@@ -252,12 +306,15 @@ impl TryFrom<IbcEvent> for AbciEvent {
             IbcEvent::OpenConfirmChannel(event) => event.into(),
             IbcEvent::CloseInitChannel(event) => event.into(),
             IbcEvent::CloseConfirmChannel(event) => event.into(),
+            IbcEvent::ChannelUpgrade(event) => event.into(),
             IbcEvent::SendPacket(event) => event.try_into().map_err(Error::channel)?,
             IbcEvent::ReceivePacket(event) => event.try_into().map_err(Error::channel)?,
             IbcEvent::WriteAcknowledgement(event) => event.try_into().map_err(Error::channel)?,
             IbcEvent::AcknowledgePacket(event) => event.try_into().map_err(Error::channel)?,
             IbcEvent::TimeoutPacket(event) => event.try_into().map_err(Error::channel)?,
             IbcEvent::ChannelClosed(event) => event.into(),
+            IbcEvent::ChannelPaused(event) => event.into(),
+            IbcEvent::ChannelResumed(event) => event.into(),
             IbcEvent::AppModule(event) => event.try_into()?,
         })
     }
@@ -280,12 +337,15 @@ impl IbcEvent {
             IbcEvent::OpenConfirmChannel(_) => IbcEventType::OpenConfirmChannel,
             IbcEvent::CloseInitChannel(_) => IbcEventType::CloseInitChannel,
             IbcEvent::CloseConfirmChannel(_) => IbcEventType::CloseConfirmChannel,
+            IbcEvent::ChannelUpgrade(event) => event.step().event_type(),
             IbcEvent::SendPacket(_) => IbcEventType::SendPacket,
             IbcEvent::ReceivePacket(_) => IbcEventType::ReceivePacket,
             IbcEvent::WriteAcknowledgement(_) => IbcEventType::WriteAck,
             IbcEvent::AcknowledgePacket(_) => IbcEventType::AckPacket,
             IbcEvent::TimeoutPacket(_) => IbcEventType::Timeout,
             IbcEvent::ChannelClosed(_) => IbcEventType::ChannelClosed,
+            IbcEvent::ChannelPaused(_) => IbcEventType::ChannelPaused,
+            IbcEvent::ChannelResumed(_) => IbcEventType::ChannelResumed,
             IbcEvent::AppModule(_) => IbcEventType::AppModule,
         }
     }
@@ -343,13 +403,72 @@ impl From<ModuleEventAttribute> for Tag {
                 .parse()
                 .expect("Key::from_str() impl is infallible"),
             value: attr
-                .key
+                .value
                 .parse()
                 .expect("Value::from_str() impl is infallible"),
         }
     }
 }
 
+pub const REASON_ATTRIBUTE_KEY: &str = "reason";
+
+/// Why a channel was closed or a client frozen, reported in the optional `reason` attribute of
+/// the corresponding events.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub enum EventReason {
+    /// A packet timed out on an ordered channel.
+    Timeout,
+    /// A governance proposal of the host.
+    Governance,
+    /// The application bound to the channel requested it.
+    AppRequested,
+    /// Evidence of misbehaviour of the counterparty chain was submitted.
+    Misbehaviour,
+    /// Any other reason, specific to the host.
+    Other(String),
+}
+
+impl EventReason {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Timeout => "timeout",
+            Self::Governance => "governance",
+            Self::AppRequested => "app_requested",
+            Self::Misbehaviour => "misbehaviour",
+            Self::Other(reason) => reason,
+        }
+    }
+}
+
+impl FromStr for EventReason {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "timeout" => Self::Timeout,
+            "governance" => Self::Governance,
+            "app_requested" => Self::AppRequested,
+            "misbehaviour" => Self::Misbehaviour,
+            reason => Self::Other(reason.to_string()),
+        })
+    }
+}
+
+impl Display for EventReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<EventReason> for Tag {
+    fn from(reason: EventReason) -> Self {
+        Self {
+            key: REASON_ATTRIBUTE_KEY.parse().unwrap(),
+            value: reason.as_str().parse().unwrap(),
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -378,4 +497,260 @@ pub mod tests {
         ));
         let _ = AbciEvent::try_from(ibc_event);
     }
+
+    /// One event of each kind, with fixed attribute values.
+    pub fn golden_events() -> Vec<IbcEvent> {
+        use core::time::Duration;
+
+        use crate::applications::transfer::acknowledgement::Acknowledgement as TransferAck;
+        use crate::applications::transfer::events as TransferEvents;
+        use crate::core::ics02_client::client_type::ClientType;
+        use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement;
+        use crate::core::ics04_channel::timeout::TimeoutHeight;
+        use crate::core::ics04_channel::Version;
+        use crate::core::ics24_host::identifier::{ChannelId, ClientId, PortId};
+        use crate::timestamp::Timestamp;
+        use crate::Height;
+        use ibc_proto::google::protobuf::Any;
+
+        let client_type = ClientType::new("07-tendermint".to_string());
+        let client_id = ClientId::new(client_type.clone(), 0).unwrap();
+        let cp_client_id = ClientId::new(client_type.clone(), 1).unwrap();
+        let height = Height::new(1, 10).unwrap();
+        let conn_id = ConnectionId::new(0);
+        let cp_conn_id = ConnectionId::new(1);
+        let port_id = PortId::transfer();
+        let chan_id = ChannelId::new(0);
+        let cp_chan_id = ChannelId::new(1);
+        let delay_period = Duration::from_secs(30);
+        let packet = Packet::builder()
+            .sequence(1.into())
+            .source(port_id.clone(), chan_id.clone())
+            .destination(port_id.clone(), cp_chan_id.clone())
+            .data(br#"{"amount":"100","denom":"uatom"}"#.to_vec())
+            .timeout_height(TimeoutHeight::At(height))
+            .timeout_timestamp(Timestamp::from_nanoseconds(1_700_000_000_000_000_000).unwrap())
+            .build()
+            .unwrap();
+        let signer: crate::signer::Signer = "cosmos1sender".parse().unwrap();
+        let denom: crate::applications::transfer::PrefixedDenom =
+            "transfer/channel-1/uatom".parse().unwrap();
+
+        vec![
+            IbcEvent::CreateClient(ClientEvents::CreateClient::new(
+                client_id.clone(),
+                client_type.clone(),
+                height,
+            )),
+            IbcEvent::UpdateClient(ClientEvents::UpdateClient::new(
+                client_id.clone(),
+                client_type.clone(),
+                height,
+                vec![height],
+                Any {
+                    type_url: "/ibc.lightclients.tendermint.v1.Header".to_string(),
+                    value: vec![0xde, 0xad, 0xbe, 0xef],
+                },
+            )),
+            IbcEvent::UpgradeClient(ClientEvents::UpgradeClient::new(
+                client_id.clone(),
+                client_type.clone(),
+                height,
+            )),
+            IbcEvent::ClientMisbehaviour(
+                ClientEvents::ClientMisbehaviour::new(client_id.clone(), client_type)
+                    .with_reason(EventReason::Misbehaviour),
+            ),
+            IbcEvent::OpenInitConnection(ConnectionEvents::OpenInit::new(
+                conn_id.clone(),
+                client_id.clone(),
+                cp_client_id.clone(),
+                delay_period,
+            )),
+            IbcEvent::OpenTryConnection(ConnectionEvents::OpenTry::new(
+                conn_id.clone(),
+                client_id.clone(),
+                cp_conn_id.clone(),
+                cp_client_id.clone(),
+                delay_period,
+            )),
+            IbcEvent::OpenAckConnection(ConnectionEvents::OpenAck::new(
+                conn_id.clone(),
+                client_id.clone(),
+                cp_conn_id.clone(),
+                cp_client_id.clone(),
+                delay_period,
+            )),
+            IbcEvent::OpenConfirmConnection(ConnectionEvents::OpenConfirm::new(
+                conn_id.clone(),
+                client_id,
+                cp_conn_id,
+                cp_client_id,
+                delay_period,
+            )),
+            IbcEvent::OpenInitChannel(ChannelEvents::OpenInit::new(
+                port_id.clone(),
+                chan_id.clone(),
+                port_id.clone(),
+                conn_id.clone(),
+                Version::ics20(),
+            )),
+            IbcEvent::OpenTryChannel(ChannelEvents::OpenTry::new(
+                port_id.clone(),
+                chan_id.clone(),
+                port_id.clone(),
+                cp_chan_id.clone(),
+                conn_id.clone(),
+                Version::ics20(),
+            )),
+            IbcEvent::OpenAckChannel(ChannelEvents::OpenAck::new(
+                port_id.clone(),
+                chan_id.clone(),
+                port_id.clone(),
+                cp_chan_id.clone(),
+                conn_id.clone(),
+            )),
+            IbcEvent::OpenConfirmChannel(ChannelEvents::OpenConfirm::new(
+                port_id.clone(),
+                chan_id.clone(),
+                port_id.clone(),
+                cp_chan_id.clone(),
+                conn_id.clone(),
+            )),
+            IbcEvent::CloseInitChannel(
+                ChannelEvents::CloseInit::new(
+                    port_id.clone(),
+                    chan_id.clone(),
+                    port_id.clone(),
+                    cp_chan_id.clone(),
+                    conn_id.clone(),
+                )
+                .with_reason(EventReason::AppRequested),
+            ),
+            IbcEvent::CloseConfirmChannel(ChannelEvents::CloseConfirm::new(
+                port_id.clone(),
+                chan_id.clone(),
+                port_id.clone(),
+                cp_chan_id.clone(),
+                conn_id.clone(),
+            )),
+            IbcEvent::ChannelClosed(
+                ChannelEvents::ChannelClosed::new(
+                    port_id.clone(),
+                    chan_id,
+                    port_id,
+                    Some(cp_chan_id),
+                    conn_id.clone(),
+                    Order::Unordered,
+                )
+                .with_reason(EventReason::Timeout),
+            ),
+            IbcEvent::SendPacket(SendPacket::new(
+                packet.clone(),
+                Order::Unordered,
+                conn_id.clone(),
+            )),
+            IbcEvent::ReceivePacket(ChannelEvents::ReceivePacket::new(
+                packet.clone(),
+                Order::Unordered,
+                conn_id.clone(),
+            )),
+            IbcEvent::WriteAcknowledgement(ChannelEvents::WriteAcknowledgement::new(
+                packet.clone(),
+                Acknowledgement::from(br#"{"result":"AQ=="}"#.to_vec()),
+                conn_id.clone(),
+            )),
+            IbcEvent::AcknowledgePacket(ChannelEvents::AcknowledgePacket::new(
+                packet.clone(),
+                Order::Unordered,
+                conn_id,
+            )),
+            IbcEvent::TimeoutPacket(ChannelEvents::TimeoutPacket::new(packet, Order::Unordered)),
+            ModuleEvent::from(TransferEvents::TransferEvent {
+                sender: signer.clone(),
+                receiver: signer.clone(),
+                amount: 100u64.into(),
+                denom: denom.clone(),
+                memo: "memo".to_string(),
+            })
+            .into(),
+            ModuleEvent::from(TransferEvents::RecvEvent {
+                sender: signer.clone(),
+                receiver: signer.clone(),
+                denom: denom.clone(),
+                amount: 100u64.into(),
+                memo: "memo".to_string(),
+                success: false,
+                error: Some("insufficient funds".to_string()),
+            })
+            .into(),
+            ModuleEvent::from(TransferEvents::AckEvent {
+                sender: signer.clone(),
+                receiver: signer.clone(),
+                denom: denom.clone(),
+                amount: 100u64.into(),
+                memo: "memo".to_string(),
+                acknowledgement: TransferAck::success(),
+            })
+            .into(),
+            ModuleEvent::from(TransferEvents::AckStatusEvent {
+                acknowledgement: TransferAck::success(),
+            })
+            .into(),
+            ModuleEvent::from(TransferEvents::TimeoutEvent {
+                refund_receiver: signer,
+                refund_denom: denom.clone(),
+                refund_amount: 100u64.into(),
+                memo: "memo".to_string(),
+            })
+            .into(),
+            ModuleEvent::from(TransferEvents::DenomTraceEvent {
+                trace_hash: Some(
+                    "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string(),
+                ),
+                denom,
+            })
+            .into(),
+        ]
+    }
+
+    fn render(events: Vec<IbcEvent>) -> String {
+        let mut rendered = String::new();
+        for event in events {
+            let event = AbciEvent::try_from(event).unwrap();
+            rendered.push_str(&event.type_str);
+            rendered.push('\n');
+            for tag in event.attributes {
+                rendered.push_str(&format!("  {}={}\n", tag.key, tag.value));
+            }
+        }
+        rendered
+    }
+
+    /// Pins the exact ABCI encoding of all events, since relayers such as Hermes index them by
+    /// type and attribute keys.
+    ///
+    /// If a change to the encoding is intentional, regenerate the golden file with
+    /// `IBC_UPDATE_GOLDEN=1 cargo test -p ibc golden_abci_events`, review the diff and record
+    /// the change in the changelog.
+    #[test]
+    fn golden_abci_events() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/support/events/abci_events.golden"
+        );
+        let rendered = render(golden_events());
+
+        if std::env::var_os("IBC_UPDATE_GOLDEN").is_some() {
+            std::fs::write(path, &rendered).unwrap();
+        }
+
+        let golden = std::fs::read_to_string(path).unwrap();
+        assert!(
+            rendered == golden,
+            "ABCI events differ from {}; if intentional, rerun with IBC_UPDATE_GOLDEN=1:\n{}",
+            path,
+            rendered
+        );
+    }
 }
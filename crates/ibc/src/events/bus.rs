@@ -0,0 +1,307 @@
+//! An in-process bus of the events emitted by
+//! [`dispatch`](crate::core::ics26_routing::handler::dispatch), for hosts embedding the IBC
+//! handlers and test harnesses to react to IBC activity as it happens, instead of polling the
+//! events of the receipts.
+//!
+//! Hosts feed a bus by returning it from
+//! [`ExecutionContext::event_bus`](crate::core::context::ExecutionContext::event_bus). With the
+//! `std` feature, a [`BroadcastEventBus`] fans the events out to any number of
+//! [`EventSubscription`]s, each receiving the events matching its [`EventFilter`], e.g. all the
+//! `SendPacket` events on a port:
+//!
+//! ```
+//! # use ibc::core::ics24_host::identifier::PortId;
+//! # use ibc::events::bus::{BroadcastEventBus, EventFilter};
+//! # use ibc::events::IbcEventType;
+//! let bus = BroadcastEventBus::new(64);
+//! let mut sent_packets = bus.subscribe(
+//!     EventFilter::of_type(IbcEventType::SendPacket).with_port_id(PortId::transfer()),
+//! );
+//! assert!(sent_packets.try_recv().is_none());
+//! ```
+
+use crate::prelude::*;
+
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::events::{IbcEvent, IbcEventType};
+
+#[cfg(feature = "std")]
+use tokio::sync::broadcast;
+
+/// A sink for the events emitted by `dispatch`.
+///
+/// Events are published as the messages of a transaction are dispatched, and thus before the
+/// transaction is committed, or rolled back if one of its later messages fails.
+pub trait EventBus: Send + Sync {
+    /// Publishes an event. Must neither fail nor block.
+    fn publish(&self, event: &IbcEvent);
+}
+
+/// Selects events by type, and by the port and channel of the host that they are about.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    event_type: Option<IbcEventType>,
+    port_id: Option<PortId>,
+    channel_id: Option<ChannelId>,
+}
+
+impl EventFilter {
+    /// Matches all events.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Matches the events of the given type.
+    pub fn of_type(event_type: IbcEventType) -> Self {
+        Self {
+            event_type: Some(event_type),
+            ..Self::default()
+        }
+    }
+
+    /// Restricts the filter to the channel and packet events of the host's channels bound to
+    /// `port_id`.
+    pub fn with_port_id(self, port_id: PortId) -> Self {
+        Self {
+            port_id: Some(port_id),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to the channel and packet events of the host's channel `channel_id`.
+    pub fn with_channel_id(self, channel_id: ChannelId) -> Self {
+        Self {
+            channel_id: Some(channel_id),
+            ..self
+        }
+    }
+
+    pub fn matches(&self, event: &IbcEvent) -> bool {
+        if let Some(event_type) = &self.event_type {
+            if event_type != &event.event_type() {
+                return false;
+            }
+        }
+        if self.port_id.is_none() && self.channel_id.is_none() {
+            return true;
+        }
+
+        match host_channel(event) {
+            Some((port_id, channel_id)) => {
+                self.port_id.iter().all(|p| p == port_id)
+                    && self.channel_id.iter().all(|c| c == channel_id)
+            }
+            None => false,
+        }
+    }
+}
+
+/// The port and channel of the host that a channel or packet event is about: the source of the
+/// packets the host sends, and the destination of those it receives.
+fn host_channel(event: &IbcEvent) -> Option<(&PortId, &ChannelId)> {
+    match event {
+        IbcEvent::OpenInitChannel(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::OpenTryChannel(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::OpenAckChannel(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::OpenConfirmChannel(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::CloseInitChannel(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::CloseConfirmChannel(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::ChannelUpgrade(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::ChannelClosed(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::ChannelPaused(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::ChannelResumed(e) => Some((e.port_id(), e.channel_id())),
+        IbcEvent::SendPacket(e) => Some((e.src_port_id(), e.src_channel_id())),
+        IbcEvent::ReceivePacket(e) => Some((e.dst_port_id(), e.dst_channel_id())),
+        IbcEvent::WriteAcknowledgement(e) => Some((e.dst_port_id(), e.dst_channel_id())),
+        IbcEvent::AcknowledgePacket(e) => Some((e.src_port_id(), e.src_channel_id())),
+        IbcEvent::TimeoutPacket(e) => Some((e.src_port_id(), e.src_channel_id())),
+        _ => None,
+    }
+}
+
+/// An [`EventBus`] broadcasting the events to its subscriptions, over a tokio broadcast channel.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct BroadcastEventBus {
+    sender: broadcast::Sender<IbcEvent>,
+}
+
+#[cfg(feature = "std")]
+impl BroadcastEventBus {
+    /// Creates a bus holding up to `capacity` events for its slowest subscription, which skips
+    /// the oldest events when it falls further behind.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Subscribes to the events matching `filter`, published from now on.
+    pub fn subscribe(&self, filter: EventFilter) -> EventSubscription {
+        EventSubscription {
+            receiver: self.sender.subscribe(),
+            filter,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl EventBus for BroadcastEventBus {
+    fn publish(&self, event: &IbcEvent) {
+        if self.sender.receiver_count() > 0 {
+            // Only fails if all subscriptions were dropped in the meantime.
+            let _ = self.sender.send(event.clone());
+        }
+    }
+}
+
+/// The events of a [`BroadcastEventBus`] matching a filter.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct EventSubscription {
+    receiver: broadcast::Receiver<IbcEvent>,
+    filter: EventFilter,
+}
+
+#[cfg(feature = "std")]
+impl EventSubscription {
+    pub fn filter(&self) -> &EventFilter {
+        &self.filter
+    }
+
+    /// Waits for the next matching event, or returns `None` once the bus is dropped.
+    pub async fn recv(&mut self) -> Option<IbcEvent> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if self.filter.matches(&event) => return Some(event),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        "event subscription lagged behind, skipped {} events",
+                        skipped
+                    )
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Returns the next matching event published so far, if any, without waiting.
+    pub fn try_recv(&mut self) -> Option<IbcEvent> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) if self.filter.matches(&event) => return Some(event),
+                Ok(_) => {}
+                Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        "event subscription lagged behind, skipped {} events",
+                        skipped
+                    )
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::str::FromStr;
+
+    use crate::core::ics02_client::msgs::create_client::MsgCreateClient;
+    use crate::core::ics04_channel::channel::Order;
+    use crate::core::ics04_channel::events::{OpenInit, ReceivePacket, SendPacket};
+    use crate::core::ics04_channel::packet::Packet;
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics24_host::identifier::ConnectionId;
+    use crate::core::ics26_routing::handler::deliver;
+    use crate::mock::client_state::MockClientState;
+    use crate::mock::consensus_state::MockConsensusState;
+    use crate::mock::context::MockContext;
+    use crate::mock::header::MockHeader;
+    use crate::test_utils::get_dummy_account_id;
+    use crate::tx_msg::Msg;
+    use crate::Height;
+
+    fn packet(src_channel: u64, dst_channel: u64) -> Packet {
+        Packet {
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(src_channel),
+            destination_port: PortId::from_str("counterparty").unwrap(),
+            destination_channel: ChannelId::new(dst_channel),
+            ..Packet::default()
+        }
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let sent = IbcEvent::SendPacket(SendPacket::new(
+            packet(0, 1),
+            Order::Unordered,
+            ConnectionId::default(),
+        ));
+        // Received by the host on its channel-1 of the `counterparty` port.
+        let received = IbcEvent::ReceivePacket(ReceivePacket::new(
+            packet(0, 1),
+            Order::Unordered,
+            ConnectionId::default(),
+        ));
+        let opened = IbcEvent::OpenInitChannel(OpenInit::new(
+            PortId::transfer(),
+            ChannelId::new(2),
+            PortId::transfer(),
+            ConnectionId::default(),
+            Version::empty(),
+        ));
+
+        let filter =
+            EventFilter::of_type(IbcEventType::SendPacket).with_port_id(PortId::transfer());
+        assert!(filter.matches(&sent));
+        assert!(!filter.matches(&received));
+        assert!(!filter.matches(&opened));
+
+        let filter = EventFilter::all().with_port_id(PortId::transfer());
+        assert!(filter.matches(&sent));
+        assert!(!filter.matches(&received));
+        assert!(filter.matches(&opened));
+
+        let filter = EventFilter::all().with_channel_id(ChannelId::new(1));
+        assert!(!filter.matches(&sent));
+        assert!(filter.matches(&received));
+
+        assert!(EventFilter::all().matches(&sent));
+    }
+
+    #[test]
+    fn test_subscriptions_to_dispatched_events() {
+        let bus = BroadcastEventBus::new(16);
+        let mut all = bus.subscribe(EventFilter::all());
+        let mut created = bus.subscribe(EventFilter::of_type(IbcEventType::CreateClient));
+        let mut sent = bus.subscribe(
+            EventFilter::of_type(IbcEventType::SendPacket).with_port_id(PortId::transfer()),
+        );
+
+        let mut ctx = MockContext::default().with_event_bus(bus);
+        let height = Height::new(0, 42).unwrap();
+        let msg = MsgCreateClient::new(
+            MockClientState::new(MockHeader::new(height)).into(),
+            MockConsensusState::new(MockHeader::new(height)).into(),
+            get_dummy_account_id(),
+        )
+        .unwrap();
+        deliver(&mut ctx, msg.to_any()).unwrap();
+
+        assert!(matches!(all.try_recv(), Some(IbcEvent::CreateClient(_))));
+        assert!(all.try_recv().is_none());
+        assert!(matches!(
+            created.try_recv(),
+            Some(IbcEvent::CreateClient(_))
+        ));
+        assert!(sent.try_recv().is_none());
+
+        // The subscriptions end with the bus, which is dropped with the context.
+        drop(ctx);
+        assert!(all.try_recv().is_none());
+    }
+}
@@ -41,6 +41,17 @@
 //! `Relayer` contains utilities for testing the `ibc` crate against the [Hermes IBC relayer][relayer-repo]. It acts
 //! as scaffolding for gluing the `ibc` crate with Hermes for testing purposes.
 //!
+//! ## Stability
+//!
+//! The context traits (e.g. `ClientReader`, `ChannelKeeper`, `ExecutionContext`), the message and
+//! domain types, and the entry points of the handlers (e.g. `ics26_routing::handler::deliver`)
+//! follow semantic versioning: new trait methods come with a default implementation, and
+//! helper traits that hosts are not meant to implement are sealed.
+//!
+//! Experimental APIs require the `unstable` feature and may change in any release: the ICS29
+//! fee and transfer hooks middlewares, and the channel upgrade handshake along with its hooks in
+//! the context traits and `Module`.
+//!
 //! [core]: https://github.com/cosmos/ibc-rs/tree/main/crates/ibc/src/core
 //! [clients]: https://github.com/cosmos/ibc-rs/tree/main/crates/ibc/src/clients
 //! [applications]: https://github.com/cosmos/ibc-rs/tree/main/crates/ibc/src/applications
@@ -69,12 +80,14 @@ pub mod relayer;
 pub mod signer;
 pub mod timestamp;
 pub mod tx_msg;
+// Formatting helpers of the crate's own `Debug` impls, not part of its API.
+#[doc(hidden)]
 pub mod utils;
 
 mod serializers;
 
 /// Re-export of ICS 002 Height domain type
-pub type Height = crate::core::ics02_client::height::Height;
+pub type Height = core::ics02_client::height::Height;
 
 #[cfg(test)]
 mod test;
@@ -84,3 +97,6 @@ pub mod test_utils;
 
 #[cfg(any(test, feature = "mocks"))]
 pub mod mock; // Context mock, the underlying host chain, and client types: for testing all handlers.
+
+#[cfg(any(test, feature = "cosmwasm"))]
+pub mod cosmwasm;
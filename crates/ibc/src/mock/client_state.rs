@@ -3,7 +3,7 @@ use crate::core::ics03_connection::connection::ConnectionEnd;
 use crate::core::ics04_channel::channel::ChannelEnd;
 use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCommitment};
 use crate::core::ics04_channel::context::ChannelReader;
-use crate::core::ics04_channel::packet::Sequence;
+use crate::core::ics04_channel::packet::{Receipt, Sequence};
 use crate::core::ics23_commitment::commitment::{
     CommitmentPrefix, CommitmentProofBytes, CommitmentRoot,
 };
@@ -26,10 +26,12 @@ use crate::core::ics02_client::client_state::{ClientState, UpdatedState, Upgrade
 use crate::core::ics02_client::client_type::ClientType;
 use crate::core::ics02_client::consensus_state::ConsensusState;
 use crate::core::ics02_client::error::Error;
+use crate::core::ics02_client::header::Header;
 use crate::core::ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId};
 use crate::mock::client_state::client_type as mock_client_type;
 use crate::mock::consensus_state::MockConsensusState;
 use crate::mock::header::MockHeader;
+use crate::mock::misbehaviour::Misbehaviour as MockMisbehaviour;
 use crate::Height;
 
 pub const MOCK_CLIENT_STATE_TYPE_URL: &str = "/ibc.mock.ClientState";
@@ -50,6 +52,10 @@ pub struct MockClientRecord {
     /// The client state (representing only the latest height at the moment).
     pub client_state: Option<Box<dyn ClientState>>,
 
+    /// The client state as encoded in the store, for clients whose state is only decoded when it
+    /// is read (see `MockContext::with_encoded_client`).
+    pub encoded_client_state: Option<Any>,
+
     /// Mapping of heights to consensus states for this client.
     pub consensus_states: HashMap<Height, Box<dyn ConsensusState>>,
 }
@@ -117,9 +123,7 @@ impl TryFrom<Any> for MockClientState {
         }
 
         match raw.type_url.as_str() {
-            MOCK_CLIENT_STATE_TYPE_URL => {
-                decode_client_state(raw.value.deref()).map_err(Into::into)
-            }
+            MOCK_CLIENT_STATE_TYPE_URL => decode_client_state(raw.value.deref()),
             _ => Err(Error::unknown_client_state_type(raw.type_url)),
         }
     }
@@ -136,8 +140,13 @@ impl From<MockClientState> for Any {
 }
 
 impl ClientState for MockClientState {
+    /// Mock clients do not record the chain they track, so they all report the chain id of the
+    /// default [`MockContext`](crate::mock::context::MockContext) at their revision.
     fn chain_id(&self) -> ChainId {
-        unimplemented!()
+        ChainId::new(
+            "mockgaia".to_string(),
+            self.latest_height().revision_number(),
+        )
     }
 
     fn client_type(&self) -> ClientType {
@@ -169,6 +178,10 @@ impl ClientState for MockClientState {
         MockConsensusState::try_from(consensus_state).map(MockConsensusState::into_box)
     }
 
+    fn decode_header(&self, header: Any) -> Result<Box<dyn Header>, Error> {
+        MockHeader::try_from(header).map(MockHeader::into_box)
+    }
+
     fn check_header_and_update_state(
         &self,
         _ctx: &dyn ClientReader,
@@ -190,6 +203,30 @@ impl ClientState for MockClientState {
         })
     }
 
+    fn check_misbehaviour_and_update_state(
+        &self,
+        _ctx: &dyn ClientReader,
+        _client_id: ClientId,
+        misbehaviour: Any,
+    ) -> Result<Box<dyn ClientState>, Error> {
+        let misbehaviour = MockMisbehaviour::try_from(misbehaviour)?;
+        let (header1, header2) = (misbehaviour.header1, misbehaviour.header2);
+
+        // Two distinct headers at the same height are evidence of misbehaviour.
+        if header1.height() != header2.height() || header1 == header2 {
+            return Err(Error::misbehaviour_handling_failure(format!(
+                "headers {} and {} are not conflicting",
+                header1, header2
+            )));
+        }
+
+        Ok(MockClientState {
+            frozen_height: Some(header1.height()),
+            ..*self
+        }
+        .into_box())
+    }
+
     fn verify_upgrade_and_update_state(
         &self,
         consensus_state: Any,
@@ -213,11 +250,10 @@ impl ClientState for MockClientState {
         consensus_height: Height,
         _expected_consensus_state: &dyn ConsensusState,
     ) -> Result<(), Error> {
-        let client_prefixed_path = Path::ClientConsensusState(ClientConsensusStatePath {
-            client_id: client_id.clone(),
-            epoch: consensus_height.revision_number(),
-            height: consensus_height.revision_height(),
-        })
+        let client_prefixed_path = Path::ClientConsensusState(ClientConsensusStatePath::new(
+            client_id.clone(),
+            consensus_height,
+        ))
         .to_string();
 
         let _path = apply_prefix(prefix, vec![client_prefixed_path]);
@@ -250,6 +286,34 @@ impl ClientState for MockClientState {
         Ok(())
     }
 
+    #[cfg(any(test, feature = "unstable"))]
+    fn verify_channel_upgrade(
+        &self,
+        _height: Height,
+        _prefix: &CommitmentPrefix,
+        _proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _expected_upgrade: &crate::core::ics04_channel::upgrade::Upgrade,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn verify_channel_upgrade_error(
+        &self,
+        _height: Height,
+        _prefix: &CommitmentPrefix,
+        _proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _expected_error_receipt: &crate::core::ics04_channel::upgrade::ErrorReceipt,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     fn verify_client_full_state(
         &self,
         _height: Height,
@@ -319,6 +383,21 @@ impl ClientState for MockClientState {
     ) -> Result<(), Error> {
         Ok(())
     }
+
+    fn verify_packet_receipt(
+        &self,
+        _ctx: &dyn ChannelReader,
+        _height: Height,
+        _connection_end: &ConnectionEnd,
+        _proof: &CommitmentProofBytes,
+        _root: &CommitmentRoot,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _sequence: Sequence,
+        _receipt: &Receipt,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 impl From<MockConsensusState> for MockClientState {
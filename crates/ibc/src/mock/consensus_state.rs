@@ -77,9 +77,7 @@ impl TryFrom<Any> for MockConsensusState {
         }
 
         match raw.type_url.as_str() {
-            MOCK_CONSENSUS_STATE_TYPE_URL => {
-                decode_consensus_state(raw.value.deref()).map_err(Into::into)
-            }
+            MOCK_CONSENSUS_STATE_TYPE_URL => decode_consensus_state(raw.value.deref()),
             _ => Err(Error::unknown_consensus_state_type(raw.type_url)),
         }
     }
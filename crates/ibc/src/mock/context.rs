@@ -16,13 +16,37 @@ use ibc_proto::google::protobuf::Any;
 use sha2::Digest;
 use tracing::debug;
 
+#[cfg(any(test, feature = "unstable"))]
+use crate::applications::fee::context::{FeeContext, FeeEscrowKeeper, FeeKeeper, FeeReader};
+#[cfg(any(test, feature = "unstable"))]
+use crate::applications::fee::error::Error as FeeError;
+#[cfg(any(test, feature = "unstable"))]
+use crate::applications::fee::packet_fee::PacketFee;
+use crate::applications::interchain_accounts::context::{
+    cosmos_adr028_interchain_account_address, InterchainAccountContext, InterchainAccountKeeper,
+    InterchainAccountReader,
+};
+use crate::applications::interchain_accounts::error::Error as IcaError;
+use crate::applications::nft_transfer::context::{
+    cosmos_adr028_escrow_address as nft_escrow_address, NftTransferContext, NftTransferKeeper,
+    NftTransferReader,
+};
+use crate::applications::nft_transfer::error::Error as NftTransferError;
+use crate::applications::nft_transfer::{Nft, NftClass, PrefixedClassId, TokenId};
+#[cfg(any(test, feature = "unstable"))]
+use crate::applications::transfer::PrefixedCoin;
 use crate::clients::ics07_tendermint::client_state::test_util::get_dummy_tendermint_client_state;
 use crate::clients::ics07_tendermint::client_state::ClientState as TmClientState;
+use crate::clients::ics07_tendermint::host_helpers::{
+    validate_self_tendermint_client, SelfClientParams,
+};
+use crate::core::context::{ExecutionContext, ValidationContext};
 use crate::core::ics02_client::client_state::ClientState;
 use crate::core::ics02_client::client_type::ClientType;
 use crate::core::ics02_client::consensus_state::ConsensusState;
 use crate::core::ics02_client::context::{ClientKeeper, ClientReader};
 use crate::core::ics02_client::error::Error as Ics02Error;
+use crate::core::ics02_client::handler::update_client::UpdateGapThresholds;
 use crate::core::ics02_client::header::Header;
 use crate::core::ics03_connection::connection::ConnectionEnd;
 use crate::core::ics03_connection::context::{ConnectionKeeper, ConnectionReader};
@@ -31,27 +55,42 @@ use crate::core::ics04_channel::channel::ChannelEnd;
 use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCommitment};
 use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
 use crate::core::ics04_channel::error::Error as Ics04Error;
-use crate::core::ics04_channel::packet::{Receipt, Sequence};
+use crate::core::ics04_channel::handshake::HandshakeStart;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::packet::PacketId;
+use crate::core::ics04_channel::packet::{Packet, Receipt, Sequence};
+use crate::core::ics04_channel::pruning::PacketRecord;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics04_channel::upgrade::{ErrorReceipt, Upgrade};
 use crate::core::ics05_port::context::PortReader;
 use crate::core::ics05_port::error::Error as Ics05Error;
 use crate::core::ics05_port::error::Error;
-use crate::core::ics23_commitment::commitment::CommitmentPrefix;
+use crate::core::ics23_commitment::commitment::{CommitmentPrefix, CommitmentRoot};
+use crate::core::ics23_commitment::merkle::MerkleProof;
+use crate::core::ics23_commitment::specs::ProofSpecs;
 use crate::core::ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId};
-use crate::core::ics26_routing::context::{Ics26Context, Module, ModuleId, Router, RouterBuilder};
-use crate::core::ics26_routing::handler::{deliver, dispatch, MsgReceipt};
+use crate::core::ics24_host::path::Path;
+use crate::core::ics24_host::StorePrefix;
+use crate::core::ics26_routing::context::{Module, ModuleId, Router, RouterBuilder};
+use crate::core::ics26_routing::handler::{deliver_batch, dispatch};
+#[cfg(any(test, feature = "invariant-checks"))]
+use crate::core::ics26_routing::invariants::{check_invariants, InvariantReader, SequenceSnapshot};
 use crate::core::ics26_routing::msgs::Ics26Envelope;
+use crate::events::bus::{BroadcastEventBus, EventBus};
 use crate::events::IbcEvent;
 use crate::mock::client_state::{
     client_type as mock_client_type, MockClientRecord, MockClientState,
 };
 use crate::mock::consensus_state::MockConsensusState;
 use crate::mock::header::MockHeader;
-use crate::mock::host::{HostBlock, HostType};
+use crate::mock::host::{HostBlock, HostType, ValidatorRotation};
+use crate::mock::state_root::{self, StateRoot};
 use crate::relayer::ics18_relayer::context::Ics18Context;
 use crate::relayer::ics18_relayer::error::Error as Ics18Error;
 use crate::signer::Signer;
-use crate::timestamp::Timestamp;
+use crate::timestamp::{Timestamp, ZERO_DURATION};
 use crate::Height;
+use alloc::collections::BTreeSet;
 
 use super::client_state::MOCK_CLIENT_TYPE;
 
@@ -76,11 +115,41 @@ pub struct MockContext {
     /// Average time duration between blocks
     block_time: Duration,
 
+    /// The rotation of the validators of a `SyntheticTendermint` host chain, if any.
+    validator_rotation: Option<ValidatorRotation>,
+
+    /// The proof specs that the clients of this host on counterparty chains must have.
+    host_proof_specs: ProofSpecs,
+
     /// An object that stores all IBC related data.
     pub ibc_store: Arc<Mutex<MockIbcStore>>,
 
     /// ICS26 router impl
     router: MockRouter,
+
+    /// Whether packet events are attributed to the relayer that submitted the message.
+    packet_events_with_relayer: bool,
+
+    /// Whether packets are handled on channels flushing for an upgrade.
+    channel_upgrades_enabled: bool,
+
+    /// Whether acknowledgements are written once per block rather than as they are produced.
+    ack_batching: bool,
+
+    /// The number of consecutive timeouts after which channels are closed, if any.
+    max_consecutive_timeouts: Option<u64>,
+
+    /// The bus to which the dispatched events are published, if any.
+    event_bus: Option<BroadcastEventBus>,
+
+    /// The thresholds beyond which the gaps bridged by client updates are reported.
+    update_gap_thresholds: UpdateGapThresholds,
+
+    /// Namespace of this IBC instance, if the host runs several of them.
+    store_prefix: Option<StorePrefix>,
+
+    /// The state roots of past blocks in `history`, recorded as the chain advances past them.
+    host_state_roots: BTreeMap<Height, CommitmentRoot>,
 }
 
 /// Returns a MockContext with bare minimum initialization: no clients, no connections and no channels are
@@ -111,8 +180,18 @@ impl Clone for MockContext {
             max_history_size: self.max_history_size,
             history: self.history.clone(),
             block_time: self.block_time,
+            validator_rotation: self.validator_rotation,
+            host_proof_specs: self.host_proof_specs.clone(),
             ibc_store,
             router: self.router.clone(),
+            packet_events_with_relayer: self.packet_events_with_relayer,
+            channel_upgrades_enabled: self.channel_upgrades_enabled,
+            ack_batching: self.ack_batching,
+            max_consecutive_timeouts: self.max_consecutive_timeouts,
+            event_bus: self.event_bus.clone(),
+            update_gap_thresholds: self.update_gap_thresholds,
+            store_prefix: self.store_prefix.clone(),
+            host_state_roots: self.host_state_roots.clone(),
         }
     }
 }
@@ -172,8 +251,18 @@ impl MockContext {
                 })
                 .collect(),
             block_time,
+            validator_rotation: None,
+            host_proof_specs: ProofSpecs::default(),
             ibc_store: Arc::new(Mutex::new(MockIbcStore::default())),
             router: Default::default(),
+            packet_events_with_relayer: false,
+            channel_upgrades_enabled: false,
+            ack_batching: false,
+            max_consecutive_timeouts: None,
+            event_bus: None,
+            update_gap_thresholds: UpdateGapThresholds::default(),
+            store_prefix: None,
+            host_state_roots: BTreeMap::new(),
         }
     }
 
@@ -228,6 +317,7 @@ impl MockContext {
         let client_record = MockClientRecord {
             client_type,
             client_state,
+            encoded_client_state: None,
             consensus_states,
         };
         self.ibc_store
@@ -299,6 +389,7 @@ impl MockContext {
         let client_record = MockClientRecord {
             client_type,
             client_state,
+            encoded_client_state: None,
             consensus_states,
         };
 
@@ -310,6 +401,29 @@ impl MockContext {
         self
     }
 
+    /// Associates a client record to this context whose state is stored encoded as
+    /// `client_state`, and only decoded when it is read, as a client created by another build of
+    /// the host would be. Lets tests store clients of types that this build does not understand.
+    pub fn with_encoded_client(
+        self,
+        client_id: &ClientId,
+        client_type: ClientType,
+        client_state: Any,
+    ) -> Self {
+        let client_record = MockClientRecord {
+            client_type,
+            client_state: None,
+            encoded_client_state: Some(client_state),
+            consensus_states: Default::default(),
+        };
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .clients
+            .insert(client_id.clone(), client_record);
+        self
+    }
+
     /// Associates a connection to this context.
     pub fn with_connection(
         self,
@@ -394,11 +508,11 @@ impl MockContext {
         } else if target_height.revision_height() < latest_height.revision_height() {
             panic!("Cannot rewind history of the chain to a smaller revision height!")
         } else if target_height.revision_height() > latest_height.revision_height() {
-            // Repeatedly advance the host chain height till we hit the desired height
             let mut ctx = MockContext { ..self };
-            while ctx.latest_height().revision_height() < target_height.revision_height() {
-                ctx.advance_host_chain_height()
-            }
+            ctx.advance_host_chain(
+                target_height.revision_height() - latest_height.revision_height(),
+                ZERO_DURATION,
+            );
             ctx
         } else {
             // Both the revision number and height match
@@ -428,6 +542,179 @@ impl MockContext {
         Self { router, ..self }
     }
 
+    /// Enables the `packet_relayer` attribute on packet events.
+    pub fn with_packet_events_relayer(self) -> Self {
+        Self {
+            packet_events_with_relayer: true,
+            ..self
+        }
+    }
+
+    /// Enables the channel upgrade handshake, and the handling of packets on channels flushing
+    /// for an upgrade.
+    pub fn with_channel_upgrades(self) -> Self {
+        Self {
+            channel_upgrades_enabled: true,
+            ..self
+        }
+    }
+
+    /// Queues acknowledgements until the next block is produced, instead of writing them as they
+    /// are produced.
+    pub fn with_ack_batching(self) -> Self {
+        Self {
+            ack_batching: true,
+            ..self
+        }
+    }
+
+    /// Closes channels once `max_timeouts` packets sent on them timed out in a row.
+    pub fn with_max_consecutive_timeouts(self, max_timeouts: u64) -> Self {
+        Self {
+            max_consecutive_timeouts: Some(max_timeouts),
+            ..self
+        }
+    }
+
+    /// Reports the client updates bridging gaps beyond `thresholds`.
+    pub fn with_update_gap_thresholds(self, thresholds: UpdateGapThresholds) -> Self {
+        Self {
+            update_gap_thresholds: thresholds,
+            ..self
+        }
+    }
+
+    /// Publishes the dispatched events to `event_bus`.
+    pub fn with_event_bus(self, event_bus: BroadcastEventBus) -> Self {
+        Self {
+            event_bus: Some(event_bus),
+            ..self
+        }
+    }
+
+    /// Records that the counterparty of the given channel started flushing it for an upgrade
+    /// when its next send sequence was `seq_number`.
+    pub fn with_counterparty_upgrade_next_sequence_send(
+        self,
+        port_id: PortId,
+        chan_id: ChannelId,
+        seq_number: Sequence,
+    ) -> Self {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .counterparty_upgrade_next_sequence_send
+            .entry(port_id)
+            .or_default()
+            .insert(chan_id, seq_number);
+        self
+    }
+
+    /// Runs this context as the IBC instance namespaced under `store_prefix`, which becomes its
+    /// commitment prefix.
+    pub fn with_store_prefix(self, store_prefix: StorePrefix) -> Self {
+        Self {
+            store_prefix: Some(store_prefix),
+            ..self
+        }
+    }
+
+    /// Produces the blocks of the host chain `block_time` apart, instead of
+    /// `DEFAULT_BLOCK_TIME_SECS`. The blocks of the history are re-timed back from the latest one.
+    pub fn with_block_time(self, block_time: Duration) -> Self {
+        Self { block_time, ..self }.regenerate_history()
+    }
+
+    /// Keeps (up to) `max_history_size` blocks in the history of the host chain, pruning the older
+    /// ones.
+    pub fn with_max_history_size(self, max_history_size: usize) -> Self {
+        assert_ne!(
+            max_history_size, 0,
+            "The chain must have a non-zero max_history_size"
+        );
+        Self {
+            max_history_size,
+            ..self
+        }
+        .regenerate_history()
+    }
+
+    /// Rotates the validators of the `SyntheticTendermint` host chain as per `rotation`, from the
+    /// blocks of the history on. Lets tests exercise the verification of client updates across
+    /// validator set changes, see [`Self::host_update_header`].
+    pub fn with_validator_rotation(self, rotation: ValidatorRotation) -> Self {
+        assert!(
+            matches!(self.host_chain_type, HostType::SyntheticTendermint),
+            "Only synthetic Tendermint chains have validators"
+        );
+        Self {
+            validator_rotation: Some(rotation),
+            ..self
+        }
+        .regenerate_history()
+    }
+
+    /// Sets the proof specs that the clients of this host on counterparty chains must have, as
+    /// checked in the connection handshake. Defaults to those of the Cosmos SDK.
+    pub fn with_proof_specs(self, proof_specs: ProofSpecs) -> Self {
+        proof_specs.validate().expect("invalid host proof specs");
+        Self {
+            host_proof_specs: proof_specs,
+            ..self
+        }
+    }
+
+    /// Regenerates the history of the host chain, ending with a block at the latest height and
+    /// time, as per the current block production parameters.
+    fn regenerate_history(self) -> Self {
+        let latest_block = self.history.last().expect("history cannot be empty");
+        let (latest_height, latest_timestamp) = (latest_block.height(), latest_block.timestamp());
+        let n = min(
+            self.max_history_size as u64,
+            latest_height.revision_height(),
+        );
+
+        let history = (0..n)
+            .rev()
+            .map(|i| {
+                self.generate_host_block(
+                    latest_height.revision_height() - i,
+                    latest_timestamp
+                        .sub(self.block_time * i as u32)
+                        .expect("block times must not precede the epoch"),
+                )
+            })
+            .collect();
+        let mut ctx = Self { history, ..self };
+
+        let oldest_height = ctx.history[0].height();
+        ctx.host_state_roots
+            .retain(|height, _| *height >= oldest_height);
+        ctx
+    }
+
+    /// Generates the block of the host chain at `height`, signed by the validators at that
+    /// height if they rotate.
+    fn generate_host_block(&self, height: u64, timestamp: Timestamp) -> HostBlock {
+        match (self.host_chain_type, self.validator_rotation) {
+            (HostType::SyntheticTendermint, Some(rotation)) => {
+                HostBlock::SyntheticTendermint(HostBlock::generate_tm_block_with_validators(
+                    self.host_chain_id.clone(),
+                    height,
+                    timestamp,
+                    &rotation.validators(height),
+                    &rotation.validators(height + 1),
+                ))
+            }
+            _ => HostBlock::generate_block(
+                self.host_chain_id.clone(),
+                self.host_chain_type,
+                height,
+                timestamp,
+            ),
+        }
+    }
+
     /// Accessor for a block of the local (host) chain from this context.
     /// Returns `None` if the block at the requested height does not exist.
     pub fn host_block(&self, target_height: Height) -> Option<&HostBlock> {
@@ -442,14 +729,64 @@ impl MockContext {
         }
     }
 
+    /// Returns the header updating a client of the host chain from `trusted_height` to
+    /// `target_height`, i.e. the block at `target_height` along with the validators trusted at
+    /// `trusted_height`. Returns `None` if the block at `target_height` is not in the history.
+    pub fn host_update_header(
+        &self,
+        target_height: Height,
+        trusted_height: Height,
+    ) -> Option<HostBlock> {
+        let mut block = self.host_block(target_height)?.clone();
+        block.set_trusted_height(trusted_height);
+        if let (HostBlock::SyntheticTendermint(light_block), Some(rotation)) =
+            (&mut block, self.validator_rotation)
+        {
+            light_block.trusted_validators =
+                Some(rotation.validator_set(trusted_height.increment().revision_height()));
+        }
+        Some(block)
+    }
+
     /// Triggers the advancing of the host chain, by extending the history of blocks (or headers).
     pub fn advance_host_chain_height(&mut self) {
+        self.push_host_block(ZERO_DURATION);
+    }
+
+    /// Advances the host chain by `by_blocks` blocks, the first of which is produced `by_time`
+    /// later than the regular block time. Lets tests move the host height and time past
+    /// timeouts or trusting periods explicitly.
+    ///
+    /// Panics if `by_time` is non-zero while `by_blocks` is zero, since the host clock only
+    /// moves with new blocks.
+    pub fn advance_host_chain(&mut self, by_blocks: u64, by_time: Duration) {
+        assert!(
+            by_blocks > 0 || by_time == ZERO_DURATION,
+            "cannot advance the host time without producing a block"
+        );
+        for i in 0..by_blocks {
+            self.push_host_block(if i == 0 { by_time } else { ZERO_DURATION });
+        }
+    }
+
+    /// Appends a block produced `extra_time` after the regular block time and prunes the
+    /// history accordingly.
+    fn push_host_block(&mut self, extra_time: Duration) {
+        self.flush_acks()
+            .expect("flushing acknowledgements never fails");
+
+        // Freeze the state at the current tip before it becomes a past block.
+        let latest_height = self.latest_height();
+        let state_root = self.state_root();
+        self.host_state_roots.insert(latest_height, state_root);
+
         let latest_block = self.history.last().expect("history cannot be empty");
-        let new_block = HostBlock::generate_block(
-            self.host_chain_id.clone(),
-            self.host_chain_type,
+        let new_block = self.generate_host_block(
             latest_block.height().increment().revision_height(),
-            latest_block.timestamp().add(self.block_time).unwrap(),
+            latest_block
+                .timestamp()
+                .add(self.block_time + extra_time)
+                .unwrap(),
         );
 
         // Append the new header at the tip of the history.
@@ -461,18 +798,53 @@ impl MockContext {
             // History is not full yet.
             self.history.push(new_block);
         }
+
+        let oldest_height = self.history[0].height();
+        self.host_state_roots
+            .retain(|height, _| *height >= oldest_height);
+    }
+
+    /// The state root of the host at `height`: the one of the current store for the latest
+    /// height, and the one recorded when the chain advanced past `height` otherwise.
+    pub fn host_state_root(&self, height: Height) -> Option<CommitmentRoot> {
+        if height == self.latest_height() {
+            Some(self.state_root())
+        } else {
+            self.host_state_roots.get(&height).cloned()
+        }
+    }
+
+    /// Generates a proof of the (non-)membership of `path` in the current IBC store, which
+    /// verifies against the root of `host_consensus_state` at the latest height using
+    /// `state_root::proof_specs()`.
+    pub fn prove(&self, path: impl Into<Path>) -> Option<MerkleProof> {
+        let prefix = self.commitment_prefix();
+        state_root::prove(&self.ibc_store.lock().unwrap(), &prefix, path.into())
     }
 
     /// A datagram passes from the relayer to the IBC module (on host chain).
     /// Alternative method to `Ics18Context::send` that does not exercise any serialization.
     /// Used in testing the Ics18 algorithms, hence this may return a Ics18Error.
     pub fn deliver(&mut self, msg: Ics26Envelope) -> Result<(), Ics18Error> {
+        #[cfg(any(test, feature = "invariant-checks"))]
+        let sequences = SequenceSnapshot::take(self);
         dispatch(self, msg).map_err(Ics18Error::transaction_failed)?;
+        #[cfg(any(test, feature = "invariant-checks"))]
+        self.assert_invariants(&sequences);
         // Create a new block.
         self.advance_host_chain_height();
         Ok(())
     }
 
+    /// Panics if the store violates an invariant, `sequences` being the sequences taken before
+    /// the last dispatch.
+    #[cfg(any(test, feature = "invariant-checks"))]
+    fn assert_invariants(&self, sequences: &SequenceSnapshot) {
+        if let Err(e) = check_invariants(self, sequences) {
+            panic!("IBC store invariant violated: {}", e);
+        }
+    }
+
     /// Validates this context. Should be called after the context is mutated by a test.
     pub fn validate(&self) -> Result<(), String> {
         // Check that the number of entries is not higher than window size.
@@ -561,6 +933,9 @@ pub struct MockIbcStore {
     /// The set of all clients, indexed by their id.
     pub clients: BTreeMap<ClientId, MockClientRecord>,
 
+    /// The latest heights of the clients, as recorded by the client handlers.
+    pub client_latest_heights: BTreeMap<ClientId, Height>,
+
     /// Tracks the processed time for clients header updates
     pub client_processed_times: BTreeMap<(ClientId, Height), Timestamp>,
 
@@ -571,6 +946,9 @@ pub struct MockIbcStore {
     /// `client_counter` methods.
     pub client_ids_counter: u64,
 
+    /// The chain tracked by each client, as recorded by the client handlers.
+    pub client_chain_ids: BTreeMap<ClientId, ChainId>,
+
     /// Association between client ids and connection ids.
     pub client_connections: BTreeMap<ClientId, ConnectionId>,
 
@@ -598,8 +976,30 @@ pub struct MockIbcStore {
     /// Tracks the sequence number for the next packet to be acknowledged.
     pub next_sequence_ack: PortChannelIdMap<Sequence>,
 
+    /// The next send sequence of the counterparty when it started flushing a channel for an
+    /// upgrade.
+    pub counterparty_upgrade_next_sequence_send: PortChannelIdMap<Sequence>,
+
+    /// The upgrades in progress of the channels.
+    #[cfg(any(test, feature = "unstable"))]
+    pub channel_upgrades: PortChannelIdMap<Upgrade>,
+
+    /// The upgrades of the counterparties of the channels, once they started flushing.
+    #[cfg(any(test, feature = "unstable"))]
+    pub counterparty_upgrades: PortChannelIdMap<Upgrade>,
+
+    /// The receipts of the last failed upgrades of the channels.
+    #[cfg(any(test, feature = "unstable"))]
+    pub upgrade_error_receipts: PortChannelIdMap<ErrorReceipt>,
+
     pub packet_acknowledgement: PortChannelIdMap<BTreeMap<Sequence, AcknowledgementCommitment>>,
 
+    /// Acknowledgements queued for the next flush, when batching acknowledgements.
+    pub pending_acks: Vec<(PortId, ChannelId, Sequence, AcknowledgementCommitment)>,
+
+    /// Packets queued by module callbacks, to be sent once the current message completed.
+    pub queued_send_packets: Vec<Packet>,
+
     /// Maps ports to the the module that owns it
     pub port_to_module: BTreeMap<PortId, ModuleId>,
 
@@ -608,10 +1008,61 @@ pub struct MockIbcStore {
 
     // Used by unordered channel
     pub packet_receipt: PortChannelIdMap<BTreeMap<Sequence, Receipt>>,
+
+    /// Tracks the start of pending channel handshakes, for handshake expiry.
+    pub channel_handshake_starts: PortChannelIdMap<HandshakeStart>,
+
+    /// Tracks the packet acknowledgements written, oldest first, for pruning.
+    pub packet_records: Vec<PacketRecord>,
+
+    /// The number of packets of each channel that timed out since the last one was acknowledged.
+    pub consecutive_timeouts: PortChannelIdMap<u64>,
+
+    /// The channels paused by the host.
+    pub paused_channels: BTreeSet<(PortId, ChannelId)>,
+
+    /// The channels of the interchain accounts, by connection and controller port.
+    pub ica_active_channels: BTreeMap<(ConnectionId, PortId), ChannelId>,
+
+    /// The addresses of the interchain accounts, by connection and controller port.
+    pub interchain_accounts: BTreeMap<(ConnectionId, PortId), String>,
+
+    /// The transactions executed by interchain accounts, in order.
+    pub ica_executed_txs: Vec<(String, Vec<Any>)>,
+
+    /// The state of the ICS29 fee middleware.
+    #[cfg(any(test, feature = "unstable"))]
+    pub fee: MockFeeStore,
+
+    /// The state of the ICS721 application.
+    pub nft: MockNftStore,
+}
+
+/// The state of the ICS29 fee middleware, with the fee escrow account reduced to a log of the
+/// coins moved in and out of it.
+#[cfg(any(test, feature = "unstable"))]
+#[derive(Clone, Debug, Default)]
+pub struct MockFeeStore {
+    pub fee_enabled_channels: BTreeSet<(PortId, ChannelId)>,
+    pub payees: BTreeMap<(Signer, ChannelId), Signer>,
+    pub counterparty_payees: BTreeMap<(Signer, ChannelId), Signer>,
+    pub packet_fees: BTreeMap<PacketId, Vec<PacketFee>>,
+    /// The coins escrowed, by payer.
+    pub escrowed: Vec<(Signer, Vec<PrefixedCoin>)>,
+    /// The coins paid out or refunded, by receiver.
+    pub paid: Vec<(Signer, Vec<PrefixedCoin>)>,
+}
+
+/// The state of the ICS721 application, with the NFTs of the host and their owners.
+#[derive(Clone, Debug, Default)]
+pub struct MockNftStore {
+    pub class_traces: BTreeMap<String, PrefixedClassId>,
+    pub classes: BTreeMap<PrefixedClassId, NftClass>,
+    pub nfts: BTreeMap<(PrefixedClassId, TokenId), (Signer, Nft)>,
 }
 
 #[derive(Default)]
-pub struct MockRouterBuilder(MockRouter);
+pub struct MockRouterBuilder(MockRouter, BTreeMap<PortId, ModuleId>);
 
 impl RouterBuilder for MockRouterBuilder {
     type Router = MockRouter;
@@ -623,6 +1074,14 @@ impl RouterBuilder for MockRouterBuilder {
         }
     }
 
+    fn port_module(&self, port_id: &PortId) -> Option<&ModuleId> {
+        self.1.get(port_id)
+    }
+
+    fn record_port_binding(&mut self, port_id: PortId, module_id: ModuleId) {
+        self.1.insert(port_id, module_id);
+    }
+
     fn build(self) -> Self::Router {
         self.0
     }
@@ -638,25 +1097,36 @@ impl Debug for MockRouter {
 }
 
 impl Router for MockRouter {
-    fn get_route_mut(&mut self, module_id: &impl Borrow<ModuleId>) -> Option<&mut dyn Module> {
-        self.0.get_mut(module_id.borrow()).and_then(Arc::get_mut)
+    fn routes(&self) -> Vec<ModuleId> {
+        self.0.keys().cloned().collect()
     }
 
-    fn has_route(&self, module_id: &impl Borrow<ModuleId>) -> bool {
-        self.0.get(module_id.borrow()).is_some()
+    fn get_route(&self, module_id: &impl Borrow<ModuleId>) -> Option<&dyn Module> {
+        self.0.get(module_id.borrow()).map(|module| module.as_ref())
+    }
+
+    fn get_route_mut(&mut self, module_id: &impl Borrow<ModuleId>) -> Option<&mut dyn Module> {
+        self.0.get_mut(module_id.borrow()).and_then(Arc::get_mut)
     }
 }
 
-impl Ics26Context for MockContext {
+impl ValidationContext for MockContext {
     type Router = MockRouter;
 
     fn router(&self) -> &Self::Router {
         &self.router
     }
+}
 
+impl ExecutionContext for MockContext {
     fn router_mut(&mut self) -> &mut Self::Router {
         &mut self.router
     }
+
+    fn event_bus(&self) -> Option<&dyn EventBus> {
+        let bus: &dyn EventBus = self.event_bus.as_ref()?;
+        Some(bus)
+    }
 }
 
 impl PortReader for MockContext {
@@ -666,107 +1136,525 @@ impl PortReader for MockContext {
             None => Err(Ics05Error::unknown_port(port_id.clone())),
         }
     }
-}
 
-impl ChannelReader for MockContext {
-    fn channel_end(
-        &self,
-        port_id: &PortId,
-        channel_id: &ChannelId,
-    ) -> Result<ChannelEnd, Ics04Error> {
-        match self
-            .ibc_store
+    fn port_bindings(&self) -> Vec<(PortId, ModuleId)> {
+        self.ibc_store
             .lock()
             .unwrap()
-            .channels
-            .get(port_id)
-            .and_then(|map| map.get(channel_id))
-        {
-            Some(channel_end) => Ok(channel_end.clone()),
-            None => Err(Ics04Error::channel_not_found(
-                port_id.clone(),
-                channel_id.clone(),
-            )),
-        }
-    }
-
-    fn connection_end(&self, cid: &ConnectionId) -> Result<ConnectionEnd, Ics04Error> {
-        ConnectionReader::connection_end(self, cid).map_err(Ics04Error::ics03_connection)
+            .port_to_module
+            .iter()
+            .map(|(port_id, module_id)| (port_id.clone(), module_id.clone()))
+            .collect()
     }
+}
 
-    fn connection_channels(
-        &self,
-        cid: &ConnectionId,
-    ) -> Result<Vec<(PortId, ChannelId)>, Ics04Error> {
-        match self.ibc_store.lock().unwrap().connection_channels.get(cid) {
-            Some(pcid) => Ok(pcid.clone()),
-            None => Err(Ics04Error::missing_channel()),
-        }
+impl InterchainAccountReader for MockContext {
+    fn is_controller_enabled(&self) -> bool {
+        true
     }
 
-    fn client_state(&self, client_id: &ClientId) -> Result<Box<dyn ClientState>, Ics04Error> {
-        ClientReader::client_state(self, client_id)
-            .map_err(|e| Ics04Error::ics03_connection(Ics03Error::ics02_client(e)))
+    fn is_host_enabled(&self) -> bool {
+        true
     }
 
-    fn client_consensus_state(
-        &self,
-        client_id: &ClientId,
-        height: Height,
-    ) -> Result<Box<dyn ConsensusState>, Ics04Error> {
-        ClientReader::consensus_state(self, client_id, height)
-            .map_err(|e| Ics04Error::ics03_connection(Ics03Error::ics02_client(e)))
+    fn is_message_allowed(&self, _type_url: &str) -> bool {
+        true
     }
 
-    fn get_next_sequence_send(
-        &self,
-        port_id: &PortId,
-        channel_id: &ChannelId,
-    ) -> Result<Sequence, Ics04Error> {
-        match self
-            .ibc_store
+    fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId> {
+        self.ibc_store
             .lock()
             .unwrap()
-            .next_sequence_send
-            .get(port_id)
-            .and_then(|map| map.get(channel_id))
-        {
-            Some(sequence) => Ok(*sequence),
-            None => Err(Ics04Error::missing_next_send_seq(
-                port_id.clone(),
-                channel_id.clone(),
-            )),
-        }
+            .ica_active_channels
+            .get(&(connection_id.clone(), port_id.clone()))
+            .cloned()
     }
 
-    fn get_next_sequence_recv(
+    fn interchain_account_address(
         &self,
+        connection_id: &ConnectionId,
         port_id: &PortId,
-        channel_id: &ChannelId,
-    ) -> Result<Sequence, Ics04Error> {
-        match self
-            .ibc_store
+    ) -> Option<String> {
+        self.ibc_store
             .lock()
             .unwrap()
-            .next_sequence_recv
-            .get(port_id)
-            .and_then(|map| map.get(channel_id))
-        {
-            Some(sequence) => Ok(*sequence),
-            None => Err(Ics04Error::missing_next_recv_seq(
-                port_id.clone(),
-                channel_id.clone(),
-            )),
-        }
+            .interchain_accounts
+            .get(&(connection_id.clone(), port_id.clone()))
+            .cloned()
     }
 
-    fn get_next_sequence_ack(
-        &self,
-        port_id: &PortId,
-        channel_id: &ChannelId,
-    ) -> Result<Sequence, Ics04Error> {
-        match self
-            .ibc_store
+    /// The messages of the mock carry the address of their signer as their value.
+    fn message_signers(&self, message: &Any) -> Result<Vec<String>, IcaError> {
+        String::from_utf8(message.value.clone())
+            .map(|signer| vec![signer])
+            .map_err(|e| IcaError::execute_tx(e.to_string()))
+    }
+}
+
+impl InterchainAccountKeeper for MockContext {
+    fn store_active_channel(
+        &mut self,
+        connection_id: ConnectionId,
+        port_id: PortId,
+        channel_id: ChannelId,
+    ) -> Result<(), IcaError> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .ica_active_channels
+            .insert((connection_id, port_id), channel_id);
+        Ok(())
+    }
+
+    fn store_interchain_account_address(
+        &mut self,
+        connection_id: ConnectionId,
+        port_id: PortId,
+        address: String,
+    ) -> Result<(), IcaError> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .interchain_accounts
+            .insert((connection_id, port_id), address);
+        Ok(())
+    }
+
+    fn create_interchain_account(
+        &mut self,
+        connection_id: &ConnectionId,
+        port_id: &PortId,
+    ) -> Result<String, IcaError> {
+        let address = cosmos_adr028_interchain_account_address(connection_id, port_id);
+        Ok(subtle_encoding::bech32::encode("cosmos", address))
+    }
+
+    /// The mock only executes bank sends.
+    fn execute_tx(&mut self, address: &str, messages: Vec<Any>) -> Result<(), IcaError> {
+        if let Some(message) = messages
+            .iter()
+            .find(|message| message.type_url != "/cosmos.bank.v1beta1.MsgSend")
+        {
+            return Err(IcaError::execute_tx(format!(
+                "unknown message type {}",
+                message.type_url
+            )));
+        }
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .ica_executed_txs
+            .push((address.to_string(), messages));
+        Ok(())
+    }
+}
+
+impl InterchainAccountContext for MockContext {}
+
+impl NftTransferReader for MockContext {
+    type AccountId = Signer;
+
+    fn get_port(&self) -> Result<PortId, NftTransferError> {
+        Ok(PortId::nft_transfer())
+    }
+
+    fn get_channel_escrow_address(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Signer, NftTransferError> {
+        let address = nft_escrow_address(port_id, channel_id);
+        subtle_encoding::bech32::encode("cosmos", address)
+            .parse()
+            .map_err(NftTransferError::signer)
+    }
+
+    fn is_send_enabled(&self) -> bool {
+        true
+    }
+
+    fn is_receive_enabled(&self) -> bool {
+        true
+    }
+
+    fn class_trace(&self, hash: &str) -> Option<PrefixedClassId> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .nft
+            .class_traces
+            .get(hash)
+            .cloned()
+    }
+
+    fn get_nft_class(&self, class_id: &PrefixedClassId) -> Option<NftClass> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .nft
+            .classes
+            .get(class_id)
+            .cloned()
+    }
+
+    fn get_nft(&self, class_id: &PrefixedClassId, token_id: &TokenId) -> Option<Nft> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .nft
+            .nfts
+            .get(&(class_id.clone(), token_id.clone()))
+            .map(|(_, nft)| nft.clone())
+    }
+
+    fn get_nft_owner(&self, class_id: &PrefixedClassId, token_id: &TokenId) -> Option<Signer> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .nft
+            .nfts
+            .get(&(class_id.clone(), token_id.clone()))
+            .map(|(owner, _)| owner.clone())
+    }
+}
+
+impl NftTransferKeeper for MockContext {
+    fn store_class_trace(
+        &mut self,
+        hash: String,
+        class_id: PrefixedClassId,
+    ) -> Result<(), NftTransferError> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .nft
+            .class_traces
+            .insert(hash, class_id);
+        Ok(())
+    }
+
+    fn create_or_update_class(&mut self, class: NftClass) -> Result<(), NftTransferError> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .nft
+            .classes
+            .insert(class.class_id.clone(), class);
+        Ok(())
+    }
+
+    fn transfer_nft(
+        &mut self,
+        from: &Signer,
+        to: &Signer,
+        class_id: &PrefixedClassId,
+        token_id: &TokenId,
+    ) -> Result<(), NftTransferError> {
+        let mut ibc_store = self.ibc_store.lock().unwrap();
+        match ibc_store
+            .nft
+            .nfts
+            .get_mut(&(class_id.clone(), token_id.clone()))
+        {
+            Some((owner, _)) if owner == from => {
+                *owner = to.clone();
+                Ok(())
+            }
+            _ => Err(NftTransferError::not_nft_owner(
+                class_id.clone(),
+                token_id.clone(),
+            )),
+        }
+    }
+
+    fn mint_nft(&mut self, owner: &Signer, nft: Nft) -> Result<(), NftTransferError> {
+        let key = (nft.class_id.clone(), nft.token_id.clone());
+        let mut ibc_store = self.ibc_store.lock().unwrap();
+        if ibc_store.nft.nfts.contains_key(&key) {
+            return Err(NftTransferError::nft_already_exists(key.0, key.1));
+        }
+        ibc_store.nft.nfts.insert(key, (owner.clone(), nft));
+        Ok(())
+    }
+
+    fn burn_nft(
+        &mut self,
+        owner: &Signer,
+        class_id: &PrefixedClassId,
+        token_id: &TokenId,
+    ) -> Result<(), NftTransferError> {
+        let key = (class_id.clone(), token_id.clone());
+        let mut ibc_store = self.ibc_store.lock().unwrap();
+        match ibc_store.nft.nfts.get(&key) {
+            Some((nft_owner, _)) if nft_owner == owner => {
+                ibc_store.nft.nfts.remove(&key);
+                Ok(())
+            }
+            _ => Err(NftTransferError::not_nft_owner(key.0, key.1)),
+        }
+    }
+}
+
+impl NftTransferContext for MockContext {}
+
+#[cfg(any(test, feature = "unstable"))]
+impl FeeReader for MockContext {
+    fn is_fee_enabled(&self, port_id: &PortId, channel_id: &ChannelId) -> bool {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .fee_enabled_channels
+            .contains(&(port_id.clone(), channel_id.clone()))
+    }
+
+    fn fee_enabled_channels(&self) -> Vec<(PortId, ChannelId)> {
+        let store = self.ibc_store.lock().unwrap();
+        store.fee.fee_enabled_channels.iter().cloned().collect()
+    }
+
+    fn payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .payees
+            .get(&(relayer.clone(), channel_id.clone()))
+            .cloned()
+    }
+
+    fn counterparty_payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .counterparty_payees
+            .get(&(relayer.clone(), channel_id.clone()))
+            .cloned()
+    }
+
+    fn packet_fees(&self, packet_id: &PacketId) -> Vec<PacketFee> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .packet_fees
+            .get(packet_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn channel_packet_fees(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Vec<(PacketId, Vec<PacketFee>)> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .packet_fees
+            .iter()
+            .filter(|(packet_id, _)| {
+                &packet_id.port_id == port_id && &packet_id.channel_id == channel_id
+            })
+            .map(|(packet_id, packet_fees)| (packet_id.clone(), packet_fees.clone()))
+            .collect()
+    }
+}
+
+#[cfg(any(test, feature = "unstable"))]
+impl FeeKeeper for MockContext {
+    fn set_fee_enabled(&mut self, port_id: PortId, channel_id: ChannelId) {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .fee_enabled_channels
+            .insert((port_id, channel_id));
+    }
+
+    fn delete_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId) {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .fee_enabled_channels
+            .remove(&(port_id.clone(), channel_id.clone()));
+    }
+
+    fn store_payee(&mut self, relayer: Signer, channel_id: ChannelId, payee: Signer) {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .payees
+            .insert((relayer, channel_id), payee);
+    }
+
+    fn store_counterparty_payee(
+        &mut self,
+        relayer: Signer,
+        channel_id: ChannelId,
+        counterparty_payee: Signer,
+    ) {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .counterparty_payees
+            .insert((relayer, channel_id), counterparty_payee);
+    }
+
+    fn store_packet_fees(&mut self, packet_id: PacketId, packet_fees: Vec<PacketFee>) {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .packet_fees
+            .insert(packet_id, packet_fees);
+    }
+
+    fn delete_packet_fees(&mut self, packet_id: &PacketId) {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .packet_fees
+            .remove(packet_id);
+    }
+}
+
+#[cfg(any(test, feature = "unstable"))]
+impl FeeEscrowKeeper for MockContext {
+    fn escrow_fee(&mut self, payer: &Signer, coins: &[PrefixedCoin]) -> Result<(), FeeError> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .escrowed
+            .push((payer.clone(), coins.to_vec()));
+        Ok(())
+    }
+
+    fn pay_fee(&mut self, receiver: &Signer, coins: &[PrefixedCoin]) -> Result<(), FeeError> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .fee
+            .paid
+            .push((receiver.clone(), coins.to_vec()));
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "unstable"))]
+impl FeeContext for MockContext {}
+
+impl ChannelReader for MockContext {
+    fn channel_end(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ChannelEnd, Ics04Error> {
+        match self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .channels
+            .get(port_id)
+            .and_then(|map| map.get(channel_id))
+        {
+            Some(channel_end) => Ok(channel_end.clone()),
+            None => Err(Ics04Error::channel_not_found(
+                port_id.clone(),
+                channel_id.clone(),
+            )),
+        }
+    }
+
+    fn connection_end(&self, cid: &ConnectionId) -> Result<ConnectionEnd, Ics04Error> {
+        ConnectionReader::connection_end(self, cid).map_err(Ics04Error::ics03_connection)
+    }
+
+    fn connection_channels(
+        &self,
+        cid: &ConnectionId,
+    ) -> Result<Vec<(PortId, ChannelId)>, Ics04Error> {
+        match self.ibc_store.lock().unwrap().connection_channels.get(cid) {
+            Some(pcid) => Ok(pcid.clone()),
+            None => Err(Ics04Error::missing_channel()),
+        }
+    }
+
+    fn client_state(&self, client_id: &ClientId) -> Result<Box<dyn ClientState>, Ics04Error> {
+        ClientReader::client_state(self, client_id)
+            .map_err(|e| Ics04Error::ics03_connection(Ics03Error::ics02_client(e)))
+    }
+
+    fn client_latest_height(&self, client_id: &ClientId) -> Result<Height, Ics04Error> {
+        ClientReader::client_latest_height(self, client_id)
+            .map_err(|e| Ics04Error::ics03_connection(Ics03Error::ics02_client(e)))
+    }
+
+    fn client_consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Box<dyn ConsensusState>, Ics04Error> {
+        ClientReader::consensus_state(self, client_id, height)
+            .map_err(|e| Ics04Error::ics03_connection(Ics03Error::ics02_client(e)))
+    }
+
+    fn get_next_sequence_send(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Sequence, Ics04Error> {
+        match self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_send
+            .get(port_id)
+            .and_then(|map| map.get(channel_id))
+        {
+            Some(sequence) => Ok(*sequence),
+            None => Err(Ics04Error::missing_next_send_seq(
+                port_id.clone(),
+                channel_id.clone(),
+            )),
+        }
+    }
+
+    fn get_next_sequence_recv(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Sequence, Ics04Error> {
+        match self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_recv
+            .get(port_id)
+            .and_then(|map| map.get(channel_id))
+        {
+            Some(sequence) => Ok(*sequence),
+            None => Err(Ics04Error::missing_next_recv_seq(
+                port_id.clone(),
+                channel_id.clone(),
+            )),
+        }
+    }
+
+    fn get_next_sequence_ack(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Sequence, Ics04Error> {
+        match self
+            .ibc_store
             .lock()
             .unwrap()
             .next_sequence_ack
@@ -827,15 +1715,20 @@ impl ChannelReader for MockContext {
         channel_id: &ChannelId,
         seq: Sequence,
     ) -> Result<AcknowledgementCommitment, Ics04Error> {
-        match self
-            .ibc_store
-            .lock()
-            .unwrap()
-            .packet_acknowledgement
-            .get(port_id)
-            .and_then(|map| map.get(channel_id))
-            .and_then(|map| map.get(&seq))
-        {
+        let ibc_store = self.ibc_store.lock().unwrap();
+        let pending_ack = ibc_store
+            .pending_acks
+            .iter()
+            .rev()
+            .find(|(p, c, s, _)| p == port_id && c == channel_id && *s == seq)
+            .map(|(_, _, _, ack)| ack);
+        match pending_ack.or_else(|| {
+            ibc_store
+                .packet_acknowledgement
+                .get(port_id)
+                .and_then(|map| map.get(channel_id))
+                .and_then(|map| map.get(&seq))
+        }) {
             Some(ack) => Ok(ack.clone()),
             None => Err(Ics04Error::packet_acknowledgement_not_found(seq)),
         }
@@ -907,7 +1800,144 @@ impl ChannelReader for MockContext {
     }
 
     fn max_expected_time_per_block(&self) -> Duration {
-        self.block_time
+        // Forward method call to the Ics3 Connection-specific method.
+        ConnectionReader::max_expected_time_per_block(self)
+    }
+
+    fn packet_events_with_relayer(&self) -> bool {
+        self.packet_events_with_relayer
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn channel_upgrades_enabled(&self) -> bool {
+        self.channel_upgrades_enabled
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn counterparty_upgrade_next_sequence_send(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Option<Sequence> {
+        let ibc_store = self.ibc_store.lock().unwrap();
+        ibc_store
+            .counterparty_upgrade_next_sequence_send
+            .get(port_id)
+            .and_then(|map| map.get(channel_id))
+            .copied()
+            .or_else(|| {
+                ibc_store
+                    .counterparty_upgrades
+                    .get(port_id)
+                    .and_then(|map| map.get(channel_id))
+                    .map(|upgrade| upgrade.next_sequence_send)
+            })
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn channel_upgrade(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Option<Upgrade>, Ics04Error> {
+        Ok(self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .channel_upgrades
+            .get(port_id)
+            .and_then(|map| map.get(channel_id))
+            .cloned())
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn counterparty_upgrade(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Option<Upgrade>, Ics04Error> {
+        Ok(self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .counterparty_upgrades
+            .get(port_id)
+            .and_then(|map| map.get(channel_id))
+            .cloned())
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn upgrade_error_receipt(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<Option<ErrorReceipt>, Ics04Error> {
+        Ok(self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .upgrade_error_receipts
+            .get(port_id)
+            .and_then(|map| map.get(channel_id))
+            .cloned())
+    }
+
+    fn max_consecutive_timeouts(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Option<u64> {
+        self.max_consecutive_timeouts
+    }
+
+    fn is_channel_paused(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<bool, Ics04Error> {
+        Ok(self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .paused_channels
+            .contains(&(port_id.clone(), channel_id.clone())))
+    }
+
+    fn consecutive_timeouts(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<u64, Ics04Error> {
+        Ok(self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .consecutive_timeouts
+            .get(port_id)
+            .and_then(|map| map.get(channel_id))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn pending_channel_handshakes(
+        &self,
+    ) -> Result<Vec<(PortId, ChannelId, HandshakeStart)>, Ics04Error> {
+        Ok(self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .channel_handshake_starts
+            .iter()
+            .flat_map(|(port_id, starts)| {
+                starts.iter().map(move |(channel_id, start)| {
+                    (port_id.clone(), channel_id.clone(), start.clone())
+                })
+            })
+            .collect())
+    }
+
+    fn supports_channel_deletion(&self) -> bool {
+        true
+    }
+
+    fn packet_records(&self) -> Result<Vec<PacketRecord>, Ics04Error> {
+        Ok(self.ibc_store.lock().unwrap().packet_records.clone())
     }
 }
 
@@ -941,113 +1971,335 @@ impl ChannelKeeper for MockContext {
         self.ibc_store
             .lock()
             .unwrap()
-            .packet_acknowledgement
+            .packet_acknowledgement
+            .entry(port_id)
+            .or_default()
+            .entry(channel_id)
+            .or_default()
+            .insert(seq, ack_commitment);
+        Ok(())
+    }
+
+    fn delete_packet_acknowledgement(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        seq: Sequence,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .packet_acknowledgement
+            .get_mut(port_id)
+            .and_then(|map| map.get_mut(channel_id))
+            .and_then(|map| map.remove(&seq));
+        Ok(())
+    }
+
+    fn queue_packet_acknowledgement(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        seq: Sequence,
+        ack_commitment: AcknowledgementCommitment,
+    ) -> Result<(), Ics04Error> {
+        if !self.ack_batching {
+            return self.store_packet_acknowledgement(port_id, channel_id, seq, ack_commitment);
+        }
+        self.ibc_store.lock().unwrap().pending_acks.push((
+            port_id,
+            channel_id,
+            seq,
+            ack_commitment,
+        ));
+        Ok(())
+    }
+
+    fn flush_acks(&mut self) -> Result<(), Ics04Error> {
+        let pending_acks = core::mem::take(&mut self.ibc_store.lock().unwrap().pending_acks);
+        for (port_id, channel_id, seq, ack_commitment) in pending_acks {
+            self.store_packet_acknowledgement(port_id, channel_id, seq, ack_commitment)?;
+        }
+        Ok(())
+    }
+
+    fn queue_send_packet(&mut self, packet: Packet) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .queued_send_packets
+            .push(packet);
+        Ok(())
+    }
+
+    fn take_queued_send_packets(&mut self) -> Vec<Packet> {
+        core::mem::take(&mut self.ibc_store.lock().unwrap().queued_send_packets)
+    }
+
+    fn store_connection_channels(
+        &mut self,
+        cid: ConnectionId,
+        port_id: PortId,
+        channel_id: ChannelId,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .connection_channels
+            .entry(cid)
+            .or_default()
+            .push((port_id, channel_id));
+        Ok(())
+    }
+
+    fn store_channel(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        channel_end: ChannelEnd,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .channels
+            .entry(port_id)
+            .or_default()
+            .insert(channel_id, channel_end);
+        Ok(())
+    }
+
+    fn store_next_sequence_send(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        seq: Sequence,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_send
+            .entry(port_id)
+            .or_default()
+            .insert(channel_id, seq);
+        Ok(())
+    }
+
+    fn store_next_sequence_recv(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        seq: Sequence,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_recv
+            .entry(port_id)
+            .or_default()
+            .insert(channel_id, seq);
+        Ok(())
+    }
+
+    fn store_next_sequence_ack(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        seq: Sequence,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_ack
+            .entry(port_id)
+            .or_default()
+            .insert(channel_id, seq);
+        Ok(())
+    }
+
+    fn increase_channel_counter(&mut self) {
+        self.ibc_store.lock().unwrap().channel_ids_counter += 1;
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn store_upgrade(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        upgrade: Upgrade,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .channel_upgrades
+            .entry(port_id)
+            .or_default()
+            .insert(channel_id, upgrade);
+        Ok(())
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn delete_upgrade(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .channel_upgrades
+            .get_mut(port_id)
+            .and_then(|map| map.remove(channel_id));
+        Ok(())
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn store_counterparty_upgrade(
+        &mut self,
+        port_id: PortId,
+        channel_id: ChannelId,
+        upgrade: Upgrade,
+    ) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .counterparty_upgrades
             .entry(port_id)
             .or_default()
-            .entry(channel_id)
-            .or_default()
-            .insert(seq, ack_commitment);
+            .insert(channel_id, upgrade);
         Ok(())
     }
 
-    fn delete_packet_acknowledgement(
+    #[cfg(any(test, feature = "unstable"))]
+    fn delete_counterparty_upgrade(
         &mut self,
         port_id: &PortId,
         channel_id: &ChannelId,
-        seq: Sequence,
     ) -> Result<(), Ics04Error> {
         self.ibc_store
             .lock()
             .unwrap()
-            .packet_acknowledgement
+            .counterparty_upgrades
             .get_mut(port_id)
-            .and_then(|map| map.get_mut(channel_id))
-            .and_then(|map| map.remove(&seq));
+            .and_then(|map| map.remove(channel_id));
         Ok(())
     }
 
-    fn store_connection_channels(
+    #[cfg(any(test, feature = "unstable"))]
+    fn store_upgrade_error_receipt(
         &mut self,
-        cid: ConnectionId,
         port_id: PortId,
         channel_id: ChannelId,
+        error_receipt: ErrorReceipt,
     ) -> Result<(), Ics04Error> {
         self.ibc_store
             .lock()
             .unwrap()
-            .connection_channels
-            .entry(cid)
-            .or_insert_with(Vec::new)
-            .push((port_id, channel_id));
+            .upgrade_error_receipts
+            .entry(port_id)
+            .or_default()
+            .insert(channel_id, error_receipt);
         Ok(())
     }
 
-    fn store_channel(
+    fn store_channel_handshake_start(
         &mut self,
         port_id: PortId,
         channel_id: ChannelId,
-        channel_end: ChannelEnd,
+        start: HandshakeStart,
     ) -> Result<(), Ics04Error> {
         self.ibc_store
             .lock()
             .unwrap()
-            .channels
+            .channel_handshake_starts
             .entry(port_id)
             .or_default()
-            .insert(channel_id, channel_end);
+            .insert(channel_id, start);
         Ok(())
     }
 
-    fn store_next_sequence_send(
+    fn delete_channel_handshake_start(
         &mut self,
-        port_id: PortId,
-        channel_id: ChannelId,
-        seq: Sequence,
+        port_id: &PortId,
+        channel_id: &ChannelId,
     ) -> Result<(), Ics04Error> {
         self.ibc_store
             .lock()
             .unwrap()
-            .next_sequence_send
-            .entry(port_id)
-            .or_default()
-            .insert(channel_id, seq);
+            .channel_handshake_starts
+            .get_mut(port_id)
+            .and_then(|map| map.remove(channel_id));
         Ok(())
     }
 
-    fn store_next_sequence_recv(
+    fn store_packet_record(&mut self, record: PacketRecord) -> Result<(), Ics04Error> {
+        self.ibc_store.lock().unwrap().packet_records.push(record);
+        Ok(())
+    }
+
+    fn delete_packet_record(&mut self, record: &PacketRecord) -> Result<(), Ics04Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .packet_records
+            .retain(|r| r != record);
+        Ok(())
+    }
+
+    fn store_consecutive_timeouts(
         &mut self,
         port_id: PortId,
         channel_id: ChannelId,
-        seq: Sequence,
+        count: u64,
     ) -> Result<(), Ics04Error> {
         self.ibc_store
             .lock()
             .unwrap()
-            .next_sequence_recv
+            .consecutive_timeouts
             .entry(port_id)
             .or_default()
-            .insert(channel_id, seq);
+            .insert(channel_id, count);
         Ok(())
     }
 
-    fn store_next_sequence_ack(
+    fn store_channel_paused(
         &mut self,
         port_id: PortId,
         channel_id: ChannelId,
-        seq: Sequence,
+        paused: bool,
     ) -> Result<(), Ics04Error> {
-        self.ibc_store
-            .lock()
-            .unwrap()
-            .next_sequence_ack
-            .entry(port_id)
-            .or_default()
-            .insert(channel_id, seq);
+        let paused_channels = &mut self.ibc_store.lock().unwrap().paused_channels;
+        if paused {
+            paused_channels.insert((port_id, channel_id));
+        } else {
+            paused_channels.remove(&(port_id, channel_id));
+        }
         Ok(())
     }
 
-    fn increase_channel_counter(&mut self) {
-        self.ibc_store.lock().unwrap().channel_ids_counter += 1;
+    fn delete_channel(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<(), Ics04Error> {
+        let mut guard = self.ibc_store.lock().unwrap();
+        let ibc_store = &mut *guard;
+        ibc_store
+            .channels
+            .get_mut(port_id)
+            .and_then(|map| map.remove(channel_id));
+        for channels in ibc_store.connection_channels.values_mut() {
+            channels.retain(|(p, c)| p != port_id || c != channel_id);
+        }
+        for sequences in [
+            &mut ibc_store.next_sequence_send,
+            &mut ibc_store.next_sequence_recv,
+            &mut ibc_store.next_sequence_ack,
+        ] {
+            sequences
+                .get_mut(port_id)
+                .and_then(|map| map.remove(channel_id));
+        }
+        Ok(())
     }
 
     fn delete_packet_commitment(
@@ -1112,8 +2364,15 @@ impl ConnectionReader for MockContext {
         self.history[0].height()
     }
 
+    fn max_expected_time_per_block(&self) -> Duration {
+        self.block_time
+    }
+
     fn commitment_prefix(&self) -> CommitmentPrefix {
-        CommitmentPrefix::try_from(b"mock".to_vec()).unwrap()
+        match &self.store_prefix {
+            Some(store_prefix) => store_prefix.commitment_prefix(),
+            None => CommitmentPrefix::try_from(b"mock".to_vec()).unwrap(),
+        }
     }
 
     fn client_consensus_state(
@@ -1134,8 +2393,28 @@ impl ConnectionReader for MockContext {
         Ok(self.ibc_store.lock().unwrap().connection_ids_counter)
     }
 
-    fn validate_self_client(&self, _counterparty_client_state: Any) -> Result<(), Ics03Error> {
-        Ok(())
+    fn validate_self_client(&self, counterparty_client_state: Any) -> Result<(), Ics03Error> {
+        // Mock clients of the host are not checked against its parameters.
+        if MockClientState::try_from(counterparty_client_state.clone()).is_ok() {
+            return Ok(());
+        }
+        let params = self
+            .self_client_params()
+            .expect("the mock host provides the parameters of its clients");
+        validate_self_tendermint_client(
+            counterparty_client_state,
+            self.host_current_height(),
+            &params,
+        )
+    }
+
+    fn self_client_params(&self) -> Option<SelfClientParams> {
+        Some(SelfClientParams {
+            chain_id: self.host_chain_id.clone(),
+            proof_specs: self.host_proof_specs.clone(),
+            unbonding_period: Duration::from_secs(128000),
+            upgrade_path: Vec::new(),
+        })
     }
 }
 
@@ -1180,15 +2459,36 @@ impl ClientReader for MockContext {
     }
 
     fn client_state(&self, client_id: &ClientId) -> Result<Box<dyn ClientState>, Ics02Error> {
-        match self.ibc_store.lock().unwrap().clients.get(client_id) {
-            Some(client_record) => client_record
-                .client_state
-                .clone()
-                .ok_or_else(|| Ics02Error::client_not_found(client_id.clone())),
+        let encoded_client_state = match self.ibc_store.lock().unwrap().clients.get(client_id) {
+            Some(MockClientRecord {
+                client_state: Some(client_state),
+                ..
+            }) => return Ok(client_state.clone()),
+            Some(client_record) => client_record.encoded_client_state.clone(),
+            None => None,
+        };
+        match encoded_client_state {
+            Some(client_state) => self.decode_stored_client_state(client_id, client_state),
             None => Err(Ics02Error::client_not_found(client_id.clone())),
         }
     }
 
+    fn client_latest_height(&self, client_id: &ClientId) -> Result<Height, Ics02Error> {
+        let cached = self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .client_latest_heights
+            .get(client_id)
+            .copied();
+        // Clients set up by the builders bypass the handlers, and thus the cache.
+        match cached {
+            Some(height) => Ok(height),
+            None => ClientReader::client_state(self, client_id)
+                .map(|client_state| client_state.latest_height()),
+        }
+    }
+
     fn decode_client_state(&self, client_state: Any) -> Result<Box<dyn ClientState>, Ics02Error> {
         if let Ok(client_state) = TmClientState::try_from(client_state.clone()) {
             Ok(client_state.into_box())
@@ -1275,6 +2575,16 @@ impl ClientReader for MockContext {
         Ok(None)
     }
 
+    fn consensus_state_heights(&self, client_id: &ClientId) -> Result<Vec<Height>, Ics02Error> {
+        let ibc_store = self.ibc_store.lock().unwrap();
+        let client_record = ibc_store
+            .clients
+            .get(client_id)
+            .ok_or_else(|| Ics02Error::client_not_found(client_id.clone()))?;
+
+        Ok(client_record.consensus_states.keys().cloned().collect())
+    }
+
     fn host_height(&self) -> Height {
         self.latest_height()
     }
@@ -1290,6 +2600,15 @@ impl ClientReader for MockContext {
 
     fn host_consensus_state(&self, height: Height) -> Result<Box<dyn ConsensusState>, Ics02Error> {
         match self.host_block(height) {
+            // The root of synthetic Tendermint blocks is the signed app hash of their header.
+            Some(HostBlock::Mock(header)) => match self.host_state_root(height) {
+                Some(root) => Ok(MockConsensusState {
+                    header: *header,
+                    root,
+                }
+                .into_box()),
+                None => Ok(MockConsensusState::new(*header).into_box()),
+            },
             Some(block_ref) => Ok(block_ref.clone().into()),
             None => Err(Ics02Error::missing_local_consensus_state(height)),
         }
@@ -1302,6 +2621,22 @@ impl ClientReader for MockContext {
     fn client_counter(&self) -> Result<u64, Ics02Error> {
         Ok(self.ibc_store.lock().unwrap().client_ids_counter)
     }
+
+    fn client_ids_for_chain(&self, chain_id: &ChainId) -> Result<Vec<ClientId>, Ics02Error> {
+        Ok(self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .client_chain_ids
+            .iter()
+            .filter(|(_, client_chain_id)| *client_chain_id == chain_id)
+            .map(|(client_id, _)| client_id.clone())
+            .collect())
+    }
+
+    fn update_gap_thresholds(&self) -> UpdateGapThresholds {
+        self.update_gap_thresholds
+    }
 }
 
 impl ClientKeeper for MockContext {
@@ -1318,6 +2653,7 @@ impl ClientKeeper for MockContext {
                 client_type: client_type.clone(),
                 consensus_states: Default::default(),
                 client_state: Default::default(),
+                encoded_client_state: Default::default(),
             });
 
         client_record.client_type = client_type;
@@ -1337,9 +2673,11 @@ impl ClientKeeper for MockContext {
                 client_type: client_state.client_type(),
                 consensus_states: Default::default(),
                 client_state: Default::default(),
+                encoded_client_state: Default::default(),
             });
 
         client_record.client_state = Some(client_state);
+        client_record.encoded_client_state = None;
         Ok(())
     }
 
@@ -1357,6 +2695,7 @@ impl ClientKeeper for MockContext {
                 client_type: mock_client_type(),
                 consensus_states: Default::default(),
                 client_state: Default::default(),
+                encoded_client_state: Default::default(),
             });
 
         client_record
@@ -1365,6 +2704,19 @@ impl ClientKeeper for MockContext {
         Ok(())
     }
 
+    fn store_client_latest_height(
+        &mut self,
+        client_id: ClientId,
+        height: Height,
+    ) -> Result<(), Ics02Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .client_latest_heights
+            .insert(client_id, height);
+        Ok(())
+    }
+
     fn increase_client_counter(&mut self) {
         self.ibc_store.lock().unwrap().client_ids_counter += 1
     }
@@ -1398,6 +2750,51 @@ impl ClientKeeper for MockContext {
             .insert((client_id, height), host_height);
         Ok(())
     }
+
+    fn store_client_chain_id(
+        &mut self,
+        client_id: ClientId,
+        chain_id: ChainId,
+    ) -> Result<(), Ics02Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .client_chain_ids
+            .insert(client_id, chain_id);
+        Ok(())
+    }
+}
+
+impl StateRoot for MockContext {
+    fn state_root(&self) -> CommitmentRoot {
+        state_root::app_hash(&self.ibc_store.lock().unwrap(), &self.commitment_prefix())
+    }
+}
+
+#[cfg(any(test, feature = "invariant-checks"))]
+impl InvariantReader for MockContext {
+    fn channel_ends(&self) -> Vec<(PortId, ChannelId, ChannelEnd)> {
+        let store = self.ibc_store.lock().unwrap();
+        store
+            .channels
+            .iter()
+            .flat_map(|(port_id, channels)| {
+                channels.iter().map(move |(channel_id, channel_end)| {
+                    (port_id.clone(), channel_id.clone(), channel_end.clone())
+                })
+            })
+            .collect()
+    }
+
+    fn ibc_store_keys(&self) -> Vec<String> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .provable_store()
+            .keys()
+            .map(|key| String::from_utf8_lossy(key).into_owned())
+            .collect()
+    }
 }
 
 impl Ics18Context for MockContext {
@@ -1417,14 +2814,16 @@ impl Ics18Context for MockContext {
 
     fn send(&mut self, msgs: Vec<Any>) -> Result<Vec<IbcEvent>, Ics18Error> {
         // Forward call to Ics26 delivery method.
-        let mut all_events = vec![];
-        for msg in msgs {
-            let MsgReceipt { mut events, .. } =
-                deliver(self, msg).map_err(Ics18Error::transaction_failed)?;
-            all_events.append(&mut events);
-        }
+        #[cfg(any(test, feature = "invariant-checks"))]
+        let sequences = SequenceSnapshot::take(self);
+        let receipts = deliver_batch(self, msgs).map_err(Ics18Error::transaction_failed)?;
+        #[cfg(any(test, feature = "invariant-checks"))]
+        self.assert_invariants(&sequences);
         self.advance_host_chain_height(); // Advance chain height
-        Ok(all_events)
+        Ok(receipts
+            .into_iter()
+            .flat_map(|receipt| receipt.events)
+            .collect())
     }
 
     fn signer(&self) -> Signer {
@@ -1437,24 +2836,39 @@ mod tests {
     use test_log::test;
 
     use alloc::str::FromStr;
+    use core::time::Duration;
+    use ibc_proto::google::protobuf::Any;
 
-    use crate::core::ics04_channel::channel::{Counterparty, Order};
+    use crate::clients::ics07_tendermint::client_state::{
+        AllowUpdate, ClientState as TmClientState,
+    };
+    use crate::clients::ics07_tendermint::client_type as tm_client_type;
+    use crate::core::ics02_client::client_state::ClientState;
+    use crate::core::ics02_client::context::ClientReader;
+    use crate::core::ics03_connection::context::ConnectionReader;
+    use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+    use crate::core::ics04_channel::context::ChannelReader;
     use crate::core::ics04_channel::error::Error;
     use crate::core::ics04_channel::handler::ModuleExtras;
     use crate::core::ics04_channel::packet::Packet;
     use crate::core::ics04_channel::Version;
+    use crate::core::ics23_commitment::commitment::CommitmentProofBytes;
+    use crate::core::ics23_commitment::specs::ProofSpecs;
     use crate::core::ics24_host::identifier::ChainId;
-    use crate::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+    use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+    use crate::core::ics24_host::path::ChannelEndsPath;
     use crate::core::ics26_routing::context::{
         Acknowledgement, Module, ModuleId, ModuleOutputBuilder, OnRecvPacketAck, Router,
         RouterBuilder,
     };
     use crate::mock::context::MockContext;
     use crate::mock::context::MockRouterBuilder;
-    use crate::mock::host::HostType;
+    use crate::mock::host::{HostBlock, HostType, ValidatorRotation};
+    use crate::mock::state_root;
     use crate::prelude::*;
     use crate::signer::Signer;
     use crate::test_utils::get_dummy_bech32_account;
+    use crate::timestamp::Timestamp;
     use crate::Height;
 
     #[test]
@@ -1596,6 +3010,185 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_time_travel() {
+        let client_id = ClientId::default();
+        let mut ctx = MockContext::new(
+            ChainId::new("mockgaia".to_string(), 1),
+            HostType::Mock,
+            5,
+            Height::new(1, 5).unwrap(),
+        )
+        .with_client_parametrized(
+            &client_id,
+            Height::new(1, 5).unwrap(),
+            Some(tm_client_type()),
+            None,
+        );
+
+        let client_state = ClientReader::client_state(&ctx, &client_id).unwrap();
+        let consensus_state =
+            ClientReader::consensus_state(&ctx, &client_id, Height::new(1, 5).unwrap()).unwrap();
+        let elapsed = |ctx: &MockContext| {
+            ClientReader::host_timestamp(ctx)
+                .duration_since(&consensus_state.timestamp())
+                .unwrap_or_default()
+        };
+        assert!(!client_state.expired(elapsed(&ctx)));
+
+        let timestamp_before = ClientReader::host_timestamp(&ctx);
+        let skip = Duration::from_secs(64001);
+        ctx.advance_host_chain(7, skip);
+
+        // Height and time moved together, and the history window kept its size.
+        assert_eq!(ctx.latest_height(), Height::new(1, 12).unwrap());
+        assert_eq!(
+            ClientReader::host_timestamp(&ctx)
+                .duration_since(&timestamp_before)
+                .unwrap(),
+            skip + ctx.block_time * 7
+        );
+        assert_eq!(ctx.history.len(), 5);
+        assert!(ctx.host_block(Height::new(1, 7).unwrap()).is_none());
+        assert!(ctx.host_block(Height::new(1, 8).unwrap()).is_some());
+        assert!(ctx.validate().is_ok());
+
+        // The client's consensus state is now beyond its trusting period.
+        assert!(client_state.expired(elapsed(&ctx)));
+    }
+
+    #[test]
+    fn test_block_production_params() {
+        let block_time = Duration::from_secs(10);
+        let mut ctx = MockContext::new(
+            ChainId::new("mockgaia".to_string(), 1),
+            HostType::SyntheticTendermint,
+            5,
+            Height::new(1, 20).unwrap(),
+        )
+        .with_max_history_size(3)
+        .with_block_time(block_time)
+        .with_validator_rotation(ValidatorRotation::new(4, 2));
+        assert!(ctx.validate().is_ok());
+        assert_eq!(ctx.history.len(), 3);
+        assert!(ctx.host_block(Height::new(1, 17).unwrap()).is_none());
+        assert_eq!(
+            ConnectionReader::max_expected_time_per_block(&ctx),
+            block_time
+        );
+        assert_eq!(
+            ChannelReader::block_delay(&ctx, Duration::from_secs(25)),
+            ConnectionReader::block_delay(&ctx, Duration::from_secs(25))
+        );
+
+        ctx.advance_host_chain_height();
+        assert_eq!(ctx.history.len(), 3);
+        for pair in ctx.history.windows(2) {
+            assert_eq!(
+                pair[1].timestamp().duration_since(&pair[0].timestamp()),
+                Some(block_time)
+            );
+            match (&pair[0], &pair[1]) {
+                (HostBlock::SyntheticTendermint(prev), HostBlock::SyntheticTendermint(next)) => {
+                    // Half of the validators are replaced at every block.
+                    assert_eq!(
+                        prev.header().next_validators_hash,
+                        next.header().validators_hash
+                    );
+                    assert_ne!(prev.header().validators_hash, next.header().validators_hash);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_host_proof_specs() {
+        let ctx = MockContext::default();
+        let chain_id = ctx.host_chain_id.clone();
+        let client_state = |proof_specs: ProofSpecs| -> Any {
+            TmClientState::new(
+                chain_id.clone(),
+                Default::default(),
+                Duration::from_secs(64000),
+                Duration::from_secs(128000),
+                Duration::from_millis(3000),
+                Height::new(0, 1).unwrap(),
+                proof_specs,
+                Default::default(),
+                AllowUpdate {
+                    after_expiry: false,
+                    after_misbehaviour: false,
+                },
+                None,
+            )
+            .unwrap()
+            .into()
+        };
+
+        // Clients of the host must have its proof specs, which default to those of the Cosmos SDK.
+        assert!(ctx
+            .validate_self_client(client_state(ProofSpecs::cosmos()))
+            .is_ok());
+        assert!(ctx
+            .validate_self_client(client_state(ProofSpecs::smt()))
+            .is_err());
+
+        let ctx = ctx.with_proof_specs(ProofSpecs::smt());
+        assert!(ctx
+            .validate_self_client(client_state(ProofSpecs::smt()))
+            .is_ok());
+        assert!(ctx
+            .validate_self_client(client_state(ProofSpecs::cosmos()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_client_expiry() {
+        let tm_client_id = ClientId::new(tm_client_type(), 0).unwrap();
+        let mock_client_id: ClientId = "9999-mock-0".parse().unwrap();
+        let mut ctx = MockContext::default()
+            .with_client_parametrized(
+                &tm_client_id,
+                Height::new(0, 5).unwrap(),
+                Some(tm_client_type()),
+                None,
+            )
+            .with_client(&mock_client_id, Height::new(0, 5).unwrap());
+
+        let consensus_state =
+            ClientReader::consensus_state(&ctx, &tm_client_id, Height::new(0, 5).unwrap()).unwrap();
+        let trusting_period = Duration::from_secs(64000);
+        assert_eq!(
+            ctx.client_expiry(&tm_client_id).unwrap(),
+            (consensus_state.timestamp() + trusting_period).unwrap()
+        );
+
+        let blocks = ctx
+            .blocks_until_expiry(&tm_client_id, ctx.block_time)
+            .unwrap()
+            .unwrap();
+        assert!(blocks > 0 && blocks <= trusting_period.as_secs() / ctx.block_time.as_secs());
+
+        ctx.advance_host_chain(1, trusting_period);
+        assert_eq!(
+            ctx.blocks_until_expiry(&tm_client_id, ctx.block_time)
+                .unwrap(),
+            Some(0)
+        );
+
+        // Mock clients never expire.
+        assert_eq!(
+            ctx.client_expiry(&mock_client_id).unwrap(),
+            Timestamp::none()
+        );
+        assert_eq!(
+            ctx.blocks_until_expiry(&mock_client_id, ctx.block_time)
+                .unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_router() {
         #[derive(Default)]
@@ -1688,7 +3281,7 @@ mod tests {
         let r = MockRouterBuilder::default()
             .add_route("foomodule".parse().unwrap(), FooModule::default())
             .unwrap()
-            .add_route("barmodule".parse().unwrap(), BarModule::default())
+            .add_route("barmodule".parse().unwrap(), BarModule)
             .unwrap()
             .build();
 
@@ -1727,4 +3320,236 @@ mod tests {
                 write_fn(ctx.router.get_route_mut(&mid).unwrap().as_any_mut()).unwrap()
             });
     }
+
+    #[test]
+    fn test_router_port_collision() {
+        use crate::applications::nft_transfer;
+        use crate::core::registry;
+        use crate::test_utils::get_dummy_transfer_module;
+
+        let res = MockRouterBuilder::default().add_route_with_ports(
+            registry::TRANSFER.module_id(),
+            get_dummy_transfer_module(),
+            &[PortId::transfer()],
+        );
+        assert!(res.is_ok());
+
+        let res = MockRouterBuilder::default().add_route_with_ports(
+            "customtransfer".parse().unwrap(),
+            get_dummy_transfer_module(),
+            &[PortId::transfer()],
+        );
+        assert!(res.is_err());
+
+        // Ports outside of the registry are bound by the first module only.
+        let custom_port: PortId = "custom".parse().unwrap();
+        let builder = MockRouterBuilder::default()
+            .add_route_with_ports(
+                "foo".parse().unwrap(),
+                get_dummy_transfer_module(),
+                core::slice::from_ref(&custom_port),
+            )
+            .unwrap();
+        let res = builder.add_route_with_ports(
+            "bar".parse().unwrap(),
+            get_dummy_transfer_module(),
+            &[custom_port],
+        );
+        assert!(res.is_err());
+
+        // The shipped applications may bind their canonical port.
+        let res = MockRouterBuilder::default().add_route_with_ports(
+            nft_transfer::MODULE_ID_STR.parse().unwrap(),
+            get_dummy_transfer_module(),
+            &[nft_transfer::PORT_ID_STR.parse().unwrap()],
+        );
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn test_router_introspection() {
+        use crate::core::context::ValidationContext;
+        use crate::test_utils::get_dummy_transfer_module;
+
+        let transfer_id: ModuleId = "transfer".parse().unwrap();
+        let other_id: ModuleId = "other".parse().unwrap();
+        let router = MockRouterBuilder::default()
+            .add_route(transfer_id.clone(), get_dummy_transfer_module())
+            .unwrap()
+            .add_route(other_id.clone(), get_dummy_transfer_module())
+            .unwrap()
+            .build();
+        assert_eq!(router.routes(), vec![other_id.clone(), transfer_id.clone()]);
+
+        let mut ctx = MockContext::default().with_router(router);
+        let other_port = PortId::from_str("other").unwrap();
+        let unrouted_port = PortId::from_str("unrouted").unwrap();
+        ctx.scope_port_to_module(PortId::transfer(), transfer_id.clone());
+        ctx.scope_port_to_module(other_port.clone(), transfer_id.clone());
+        ctx.scope_port_to_module(unrouted_port.clone(), "unrouted".parse().unwrap());
+
+        assert_eq!(
+            ctx.module_for_port(&PortId::transfer()),
+            Some(transfer_id.clone())
+        );
+        assert_eq!(ctx.module_for_port(&unrouted_port), None);
+        assert_eq!(
+            ctx.ports_for_module(&transfer_id),
+            vec![other_port, PortId::transfer()]
+        );
+        assert!(ctx.ports_for_module(&other_id).is_empty());
+    }
+
+    #[test]
+    fn test_isolated_instances() {
+        use crate::core::ics02_client::msgs::create_client::MsgCreateClient;
+        use crate::core::ics02_client::msgs::ClientMsg;
+        use crate::core::ics03_connection::context::ConnectionReader;
+        use crate::core::ics24_host::StorePrefix;
+        use crate::core::ics26_routing::handler::dispatch;
+        use crate::core::ics26_routing::msgs::Ics26Envelope;
+        use crate::mock::client_state::MockClientState;
+        use crate::mock::consensus_state::MockConsensusState;
+        use crate::mock::header::MockHeader;
+        use crate::test_utils::get_dummy_account_id;
+
+        let mut instances: Vec<MockContext> = ["ibc-rollup-a", "ibc-rollup-b"]
+            .into_iter()
+            .map(|prefix| {
+                MockContext::default().with_store_prefix(StorePrefix::from_str(prefix).unwrap())
+            })
+            .collect();
+
+        let height = Height::new(0, 42).unwrap();
+        for ctx in instances.iter_mut() {
+            let msg = MsgCreateClient::new(
+                MockClientState::new(MockHeader::new(height)).into(),
+                MockConsensusState::new(MockHeader::new(height)).into(),
+                get_dummy_account_id(),
+            )
+            .unwrap();
+            dispatch(ctx, Ics26Envelope::Ics2Msg(ClientMsg::CreateClient(msg))).unwrap();
+        }
+
+        // Every instance keeps its own client counter.
+        for ctx in &instances {
+            assert_eq!(ctx.ibc_store.lock().unwrap().client_ids_counter, 1);
+        }
+        assert_eq!(
+            ConnectionReader::commitment_prefix(&instances[0]).as_bytes(),
+            b"ibc-rollup-a"
+        );
+        assert_eq!(
+            ConnectionReader::commitment_prefix(&instances[1]).as_bytes(),
+            b"ibc-rollup-b"
+        );
+    }
+
+    #[test]
+    fn host_proofs_verify_against_host_consensus_state() {
+        let port_id = PortId::transfer();
+        let channel_id = ChannelId::default();
+        let channel_end = ChannelEnd::new(
+            State::Open,
+            Order::Unordered,
+            Counterparty::new(PortId::transfer(), Some(ChannelId::default())),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+
+        let mut ctx = MockContext::default().with_channel(
+            port_id.clone(),
+            channel_id.clone(),
+            channel_end.clone(),
+        );
+        let proof_height = ctx.latest_height();
+        let proof: CommitmentProofBytes = ctx
+            .prove(ChannelEndsPath(port_id.clone(), channel_id.clone()))
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        // Changing the store after the chain advanced leaves the past root untouched.
+        ctx.advance_host_chain_height();
+        let ctx = ctx.with_channel(port_id.clone(), ChannelId::new(1), channel_end.clone());
+
+        let client_state = TmClientState::new(
+            ctx.host_chain_id.clone(),
+            Default::default(),
+            Duration::from_secs(64000),
+            Duration::from_secs(128000),
+            Duration::from_millis(3000),
+            ctx.latest_height(),
+            state_root::proof_specs(),
+            Default::default(),
+            AllowUpdate {
+                after_expiry: false,
+                after_misbehaviour: false,
+            },
+            None,
+        )
+        .unwrap();
+
+        let verify_at = |height: Height| {
+            let consensus_state = ClientReader::host_consensus_state(&ctx, height).unwrap();
+            client_state.verify_channel_state(
+                height,
+                &ctx.commitment_prefix(),
+                &proof,
+                consensus_state.root(),
+                &port_id,
+                &channel_id,
+                &channel_end,
+            )
+        };
+        verify_at(proof_height).unwrap();
+        assert!(verify_at(ctx.latest_height()).is_err());
+    }
+
+    #[test]
+    fn batched_acks_are_written_with_the_block() {
+        use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
+        use crate::core::ics04_channel::handler::write_acknowledgement::WriteAckPacketResult;
+        use crate::core::ics04_channel::packet::PacketResult;
+
+        let port_id = PortId::transfer();
+        let channel_id = ChannelId::default();
+        let write_ack = |ctx: &mut MockContext| {
+            let ack_commitment = ctx.ack_commitment(vec![1, 2, 3].into());
+            ctx.store_packet_result(PacketResult::WriteAck(WriteAckPacketResult {
+                port_id: port_id.clone(),
+                channel_id: channel_id.clone(),
+                seq: 1.into(),
+                ack_commitment,
+            }))
+            .unwrap();
+        };
+        let stored_ack = |ctx: &MockContext| {
+            ctx.ibc_store
+                .lock()
+                .unwrap()
+                .packet_acknowledgement
+                .get(&port_id)
+                .and_then(|map| map.get(&channel_id))
+                .and_then(|map| map.get(&1.into()))
+                .cloned()
+        };
+
+        let mut ctx = MockContext::default();
+        write_ack(&mut ctx);
+        let expected = stored_ack(&ctx).unwrap();
+
+        let mut ctx = MockContext::default().with_ack_batching();
+        write_ack(&mut ctx);
+        assert_eq!(stored_ack(&ctx), None);
+        assert_eq!(
+            ctx.get_packet_acknowledgement(&port_id, &channel_id, 1.into())
+                .unwrap(),
+            expected
+        );
+
+        ctx.advance_host_chain_height();
+        assert_eq!(stored_ack(&ctx), Some(expected));
+        assert!(ctx.ibc_store.lock().unwrap().pending_acks.is_empty());
+    }
 }
@@ -5,8 +5,12 @@ use ibc_proto::ibc::lightclients::tendermint::v1::Header as RawHeader;
 use ibc_proto::protobuf::Protobuf as ErasedProtobuf;
 use serde::Serialize;
 use tendermint::block::Header as TmHeader;
+use tendermint::validator::Set as ValidatorSet;
 use tendermint_testgen::light_block::TmLightBlock;
-use tendermint_testgen::{Generator, LightBlock as TestgenLightBlock};
+use tendermint_testgen::{
+    Commit as TestgenCommit, Generator, Header as TestgenHeader, LightBlock as TestgenLightBlock,
+    Validator as TestgenValidator,
+};
 
 use crate::clients::ics07_tendermint::client_type as tm_client_type;
 use crate::clients::ics07_tendermint::consensus_state::ConsensusState as TMConsensusState;
@@ -27,6 +31,7 @@ use crate::Height;
 /// The variants are as follows:
 /// - `Mock` defines that the context history consists of `MockHeader` blocks.
 /// - `SyntheticTendermint`: the context has synthetically-generated Tendermint (light) blocks.
+///
 /// See also the `HostBlock` enum to get more insights into the underlying block type.
 #[derive(Clone, Debug, Copy)]
 pub enum HostType {
@@ -34,10 +39,63 @@ pub enum HostType {
     SyntheticTendermint,
 }
 
+/// The voting power of each validator of a rotating validator set.
+const ROTATING_VALIDATOR_POWER: u64 = 10;
+
+/// Rotation of the validators of a `SyntheticTendermint` host chain: the chain has `size`
+/// validators of equal voting power, `per_block` of which are replaced at every block.
+///
+/// The validators at height `h` are `validator-{h * per_block}` up to (excluding)
+/// `validator-{h * per_block + size}`, so that the sets at two heights overlap by
+/// `size - per_block` validators per block between them, if any. E.g. with 3 validators, one of
+/// which is replaced per block, the set trusted at `h` holds 2/3 of the voting power at `h + 1`,
+/// 1/3 of it at `h + 2` and none of it beyond.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatorRotation {
+    pub size: u64,
+    pub per_block: u64,
+}
+
+impl ValidatorRotation {
+    pub fn new(size: u64, per_block: u64) -> Self {
+        assert_ne!(size, 0, "The chain must have at least one validator");
+        assert!(
+            per_block <= size,
+            "Cannot replace more validators per block than there are"
+        );
+        Self { size, per_block }
+    }
+
+    /// Returns the validators of the chain at `height`.
+    pub fn validators(&self, height: u64) -> Vec<TestgenValidator> {
+        let first = height * self.per_block;
+        (first..first + self.size)
+            .map(|i| {
+                TestgenValidator::new(&format!("validator-{}", i))
+                    .voting_power(ROTATING_VALIDATOR_POWER)
+            })
+            .collect()
+    }
+
+    /// Returns the validator set of the chain at `height`.
+    pub fn validator_set(&self, height: u64) -> ValidatorSet {
+        ValidatorSet::without_proposer(
+            self.validators(height)
+                .iter()
+                .map(|validator| validator.generate().unwrap())
+                .collect(),
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct SyntheticTmBlock {
     pub trusted_height: Height,
     pub light_block: TmLightBlock,
+    /// The validators trusted at `trusted_height`, i.e. the next validators of the block at that
+    /// height. Defaults to the next validators of this block, which are the same unless the
+    /// validators of the chain changed in between.
+    pub trusted_validators: Option<ValidatorSet>,
 }
 
 impl SyntheticTmBlock {
@@ -115,6 +173,34 @@ impl HostBlock {
         SyntheticTmBlock {
             trusted_height: Height::new(chain_id.version(), 1).unwrap(),
             light_block,
+            trusted_validators: None,
+        }
+    }
+
+    /// Generates a block at `height` signed by all of `validators`, which hands over to
+    /// `next_validators` for the next block.
+    pub fn generate_tm_block_with_validators(
+        chain_id: ChainId,
+        height: u64,
+        timestamp: Timestamp,
+        validators: &[TestgenValidator],
+        next_validators: &[TestgenValidator],
+    ) -> SyntheticTmBlock {
+        let header = TestgenHeader::new(validators)
+            .next_validators(next_validators)
+            .chain_id(chain_id.as_str())
+            .height(height)
+            .time(timestamp.into_tm_time().unwrap());
+        let commit = TestgenCommit::new(header.clone(), 1);
+        let light_block = TestgenLightBlock::new(header, commit)
+            .validators(validators)
+            .next_validators(next_validators)
+            .generate()
+            .unwrap();
+        SyntheticTmBlock {
+            trusted_height: Height::new(chain_id.version(), 1).unwrap(),
+            light_block,
+            trusted_validators: None,
         }
     }
 }
@@ -155,13 +241,18 @@ impl From<HostBlock> for Any {
             let SyntheticTmBlock {
                 trusted_height,
                 light_block,
+                trusted_validators,
             } = light_block;
 
             RawHeader {
                 signed_header: Some(light_block.signed_header.into()),
                 validator_set: Some(light_block.validators.into()),
                 trusted_height: Some(trusted_height.into()),
-                trusted_validators: Some(light_block.next_validators.into()),
+                trusted_validators: Some(
+                    trusted_validators
+                        .unwrap_or(light_block.next_validators)
+                        .into(),
+                ),
             }
             .encode_to_vec()
         }
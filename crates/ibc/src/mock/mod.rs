@@ -6,3 +6,4 @@ pub mod context;
 pub mod header;
 pub mod host;
 pub mod misbehaviour;
+pub mod state_root;
@@ -0,0 +1,439 @@
+//! Commitment roots and proofs over the state of the mock context.
+//!
+//! The IBC store of a `MockContext` is committed to as a Tendermint simple merkle tree, itself
+//! nested under the commitment prefix in a second (single leaf) simple merkle tree that plays the
+//! role of the host multistore. Proofs generated here therefore have the same two-level shape as
+//! the ones produced by a Cosmos SDK chain, and verify with `proof_specs()`.
+
+use alloc::collections::BTreeMap;
+
+use ibc_proto::protobuf::Protobuf;
+use ics23::commitment_proof::Proof;
+use ics23::{CommitmentProof, ExistenceProof, HashOp, InnerOp, LeafOp, NonExistenceProof};
+use prost::Message;
+use sha2::{Digest, Sha256};
+
+use crate::core::ics04_channel::packet::Sequence;
+use crate::core::ics23_commitment::commitment::{CommitmentPrefix, CommitmentRoot};
+use crate::core::ics23_commitment::merkle::MerkleProof;
+use crate::core::ics23_commitment::specs::ProofSpecs;
+#[cfg(any(test, feature = "unstable"))]
+use crate::core::ics24_host::path::ChannelUpgradePath;
+use crate::core::ics24_host::path::{
+    AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath, CommitmentsPath,
+    ConnectionsPath, Path, ReceiptsPath, SeqAcksPath, SeqRecvsPath, SeqSendsPath,
+};
+use crate::mock::context::MockIbcStore;
+use crate::prelude::*;
+
+/// Produces the commitment root (app hash) of the provable state of a host.
+pub trait StateRoot {
+    /// Returns the root committing to the current provable state.
+    fn state_root(&self) -> CommitmentRoot;
+}
+
+/// The proof specifications that proofs over a mock store verify with, i.e. a simple merkle tree
+/// for the IBC store followed by one for the multistore.
+pub fn proof_specs() -> ProofSpecs {
+    vec![ics23::tendermint_spec(), ics23::tendermint_spec()].into()
+}
+
+/// A sorted key-value store hashed as a Tendermint simple merkle tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimpleMerkleStore(BTreeMap<Vec<u8>, Vec<u8>>);
+
+impl SimpleMerkleStore {
+    pub fn insert(&mut self, key: impl Into<Vec<u8>>, value: Vec<u8>) {
+        self.0.insert(key.into(), value);
+    }
+
+    /// The keys of the store, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.keys().map(Vec::as_slice)
+    }
+
+    /// Root hash of the tree; the hash of the empty string for an empty store.
+    pub fn root(&self) -> Vec<u8> {
+        if self.0.is_empty() {
+            return Sha256::digest([]).to_vec();
+        }
+        subtree_root(&self.leaf_hashes())
+    }
+
+    /// An ics23 proof that `key` is part of the store.
+    pub fn existence_proof(&self, key: &[u8]) -> Option<ExistenceProof> {
+        let index = self.0.keys().position(|k| k.as_slice() == key)?;
+        Some(self.existence_proof_at(index))
+    }
+
+    /// An ics23 proof that `key` is absent from the store, built from its neighbouring keys.
+    /// Returns `None` if the key is present, or if the store is empty.
+    pub fn non_existence_proof(&self, key: &[u8]) -> Option<NonExistenceProof> {
+        if self.0.is_empty() || self.0.contains_key(key) {
+            return None;
+        }
+        let right_index = self.0.keys().position(|k| k.as_slice() > key);
+        let left_index = match right_index {
+            Some(0) => None,
+            Some(index) => Some(index - 1),
+            None => Some(self.0.len() - 1),
+        };
+
+        Some(NonExistenceProof {
+            key: key.to_vec(),
+            left: left_index.map(|index| self.existence_proof_at(index)),
+            right: right_index.map(|index| self.existence_proof_at(index)),
+        })
+    }
+
+    fn leaf_hashes(&self) -> Vec<Vec<u8>> {
+        self.0
+            .iter()
+            .map(|(key, value)| leaf_hash(key, value))
+            .collect()
+    }
+
+    fn existence_proof_at(&self, index: usize) -> ExistenceProof {
+        let (key, value) = self.0.iter().nth(index).expect("index is within the store");
+        ExistenceProof {
+            key: key.clone(),
+            value: value.clone(),
+            leaf: Some(leaf_op()),
+            path: inner_path(&self.leaf_hashes(), index),
+        }
+    }
+}
+
+fn leaf_op() -> LeafOp {
+    ics23::tendermint_spec()
+        .leaf_spec
+        .expect("tendermint spec has a leaf spec")
+}
+
+fn length_prefixed(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    prost::encoding::encode_varint(bytes.len() as u64, &mut encoded);
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+fn leaf_hash(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(leaf_op().prefix);
+    hasher.update(length_prefixed(key));
+    hasher.update(length_prefixed(&Sha256::digest(value)));
+    hasher.finalize().to_vec()
+}
+
+fn inner_hash(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
+}
+
+/// The largest power of two strictly smaller than `n`, which is where Tendermint splits a tree
+/// of `n` leaves.
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k * 2 < n {
+        k *= 2;
+    }
+    k
+}
+
+fn subtree_root(hashes: &[Vec<u8>]) -> Vec<u8> {
+    match hashes.len() {
+        1 => hashes[0].clone(),
+        n => {
+            let k = split_point(n);
+            inner_hash(&subtree_root(&hashes[..k]), &subtree_root(&hashes[k..]))
+        }
+    }
+}
+
+/// The inner nodes from the leaf at `index` up to the root.
+fn inner_path(hashes: &[Vec<u8>], index: usize) -> Vec<InnerOp> {
+    if hashes.len() == 1 {
+        return vec![];
+    }
+    let k = split_point(hashes.len());
+    if index < k {
+        let mut path = inner_path(&hashes[..k], index);
+        path.push(InnerOp {
+            hash: HashOp::Sha256.into(),
+            prefix: vec![1],
+            suffix: subtree_root(&hashes[k..]),
+        });
+        path
+    } else {
+        let mut path = inner_path(&hashes[k..], index - k);
+        let mut prefix = vec![1];
+        prefix.extend(subtree_root(&hashes[..k]));
+        path.push(InnerOp {
+            hash: HashOp::Sha256.into(),
+            prefix,
+            suffix: vec![],
+        });
+        path
+    }
+}
+
+fn encode_sequence(sequence: Sequence) -> Vec<u8> {
+    let mut seq_bytes = Vec::new();
+    u64::from(sequence)
+        .encode(&mut seq_bytes)
+        .expect("buffer size too small");
+    seq_bytes
+}
+
+impl MockIbcStore {
+    /// The provable part of the store, keyed by ICS-24 path and encoded the way light clients
+    /// expect to find it on chain.
+    pub fn provable_store(&self) -> SimpleMerkleStore {
+        let mut store = SimpleMerkleStore::default();
+        let mut insert = |path: Path, value: Vec<u8>| store.insert(path.to_string(), value);
+
+        for (client_id, record) in &self.clients {
+            if let Some(client_state) = &record.client_state {
+                insert(
+                    ClientStatePath(client_id.clone()).into(),
+                    client_state
+                        .encode_vec()
+                        .expect("encoding client state to `Any`"),
+                );
+            }
+            for (height, consensus_state) in &record.consensus_states {
+                let path = ClientConsensusStatePath::new(client_id.clone(), *height);
+                insert(
+                    path.into(),
+                    consensus_state
+                        .encode_vec()
+                        .expect("encoding consensus state to `Any`"),
+                );
+            }
+        }
+
+        for (connection_id, connection_end) in &self.connections {
+            insert(
+                ConnectionsPath(connection_id.clone()).into(),
+                connection_end
+                    .encode_vec()
+                    .expect("encoding connection end"),
+            );
+        }
+
+        for (port_id, channels) in &self.channels {
+            for (channel_id, channel_end) in channels {
+                insert(
+                    ChannelEndsPath(port_id.clone(), channel_id.clone()).into(),
+                    channel_end.encode_vec().expect("encoding channel end"),
+                );
+            }
+        }
+
+        for (port_id, channels) in &self.next_sequence_send {
+            for (channel_id, seq) in channels {
+                let path = SeqSendsPath(port_id.clone(), channel_id.clone());
+                insert(path.into(), encode_sequence(*seq));
+            }
+        }
+        for (port_id, channels) in &self.next_sequence_recv {
+            for (channel_id, seq) in channels {
+                let path = SeqRecvsPath(port_id.clone(), channel_id.clone());
+                insert(path.into(), encode_sequence(*seq));
+            }
+        }
+        for (port_id, channels) in &self.next_sequence_ack {
+            for (channel_id, seq) in channels {
+                let path = SeqAcksPath(port_id.clone(), channel_id.clone());
+                insert(path.into(), encode_sequence(*seq));
+            }
+        }
+
+        for (port_id, channels) in &self.packet_commitment {
+            for (channel_id, commitments) in channels {
+                for (sequence, commitment) in commitments {
+                    let path = CommitmentsPath::new(port_id.clone(), channel_id.clone(), *sequence);
+                    insert(path.into(), commitment.clone().into_vec());
+                }
+            }
+        }
+        for (port_id, channels) in &self.packet_acknowledgement {
+            for (channel_id, acks) in channels {
+                for (sequence, ack) in acks {
+                    let path = AcksPath::new(port_id.clone(), channel_id.clone(), *sequence);
+                    insert(path.into(), ack.clone().into_vec());
+                }
+            }
+        }
+        for (port_id, channels) in &self.packet_receipt {
+            for (channel_id, receipts) in channels {
+                for sequence in receipts.keys() {
+                    let path = ReceiptsPath::new(port_id.clone(), channel_id.clone(), *sequence);
+                    insert(path.into(), vec![1]);
+                }
+            }
+        }
+
+        #[cfg(any(test, feature = "unstable"))]
+        for (port_id, channels) in &self.channel_upgrades {
+            for (channel_id, upgrade) in channels {
+                let path = ChannelUpgradePath::Upgrade(port_id.clone(), channel_id.clone());
+                insert(path.into(), upgrade.encode_vec().expect("encoding upgrade"));
+            }
+        }
+        #[cfg(any(test, feature = "unstable"))]
+        for (port_id, channels) in &self.upgrade_error_receipts {
+            for (channel_id, error_receipt) in channels {
+                let path = ChannelUpgradePath::UpgradeError(port_id.clone(), channel_id.clone());
+                insert(
+                    path.into(),
+                    error_receipt
+                        .encode_vec()
+                        .expect("encoding upgrade error receipt"),
+                );
+            }
+        }
+
+        store
+    }
+}
+
+/// The multistore holding the IBC store (committed to by `ibc_root`) under `prefix`.
+fn multistore(prefix: &CommitmentPrefix, ibc_root: Vec<u8>) -> SimpleMerkleStore {
+    let mut store = SimpleMerkleStore::default();
+    store.insert(format!("{:?}", prefix), ibc_root);
+    store
+}
+
+/// The app hash of a host whose IBC store lives under `prefix`.
+pub fn app_hash(ibc_store: &MockIbcStore, prefix: &CommitmentPrefix) -> CommitmentRoot {
+    let ibc_root = ibc_store.provable_store().root();
+    multistore(prefix, ibc_root).root().into()
+}
+
+/// A (non-)membership proof of `path` in `ibc_store`, checkable against
+/// `app_hash(ibc_store, prefix)` with `proof_specs()`. A membership proof is generated if the path
+/// is present in the store, otherwise a non-membership proof. Returns `None` for non-membership
+/// in an empty store, which cannot be proven.
+pub fn prove(
+    ibc_store: &MockIbcStore,
+    prefix: &CommitmentPrefix,
+    path: Path,
+) -> Option<MerkleProof> {
+    let store = ibc_store.provable_store();
+    let key = path.to_string();
+
+    let proof = match store.existence_proof(key.as_bytes()) {
+        Some(existence_proof) => Proof::Exist(existence_proof),
+        None => Proof::Nonexist(store.non_existence_proof(key.as_bytes())?),
+    };
+    let multistore = multistore(prefix, store.root());
+    let store_proof = multistore
+        .existence_proof(format!("{:?}", prefix).as_bytes())
+        .expect("the IBC store is part of the multistore");
+
+    Some(MerkleProof {
+        proofs: vec![
+            CommitmentProof { proof: Some(proof) },
+            CommitmentProof {
+                proof: Some(Proof::Exist(store_proof)),
+            },
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::ics23_commitment::merkle::apply_prefix;
+    use crate::core::ics24_host::identifier::ConnectionId;
+
+    #[test]
+    fn simple_merkle_store_proofs() {
+        let specs: ProofSpecs = vec![ics23::tendermint_spec()].into();
+
+        let mut store = SimpleMerkleStore::default();
+        for key in ["b", "d", "f", "h", "j"] {
+            store.insert(key, key.as_bytes().to_vec());
+        }
+        let root = store.root();
+
+        for key in ["b", "d", "f", "h", "j"] {
+            let proof = MerkleProof {
+                proofs: vec![CommitmentProof {
+                    proof: Some(Proof::Exist(store.existence_proof(key.as_bytes()).unwrap())),
+                }],
+            };
+            let keys = ibc_proto::ibc::core::commitment::v1::MerklePath {
+                key_path: vec![key.to_string()],
+            };
+            proof
+                .verify_membership(
+                    &specs,
+                    CommitmentRoot::from(root.clone()).into(),
+                    keys,
+                    key.as_bytes().to_vec(),
+                    0,
+                )
+                .unwrap();
+        }
+
+        for key in ["a", "c", "e", "i", "k"] {
+            let proof = MerkleProof {
+                proofs: vec![CommitmentProof {
+                    proof: Some(Proof::Nonexist(
+                        store.non_existence_proof(key.as_bytes()).unwrap(),
+                    )),
+                }],
+            };
+            let keys = ibc_proto::ibc::core::commitment::v1::MerklePath {
+                key_path: vec![key.to_string()],
+            };
+            proof
+                .verify_non_membership(&specs, CommitmentRoot::from(root.clone()).into(), keys)
+                .unwrap();
+        }
+
+        assert!(store.non_existence_proof(b"b").is_none());
+    }
+
+    #[test]
+    fn store_proofs_verify_against_app_hash() {
+        let prefix = CommitmentPrefix::try_from(b"mock".to_vec()).unwrap();
+        let path = ConnectionsPath(Default::default());
+
+        let mut ibc_store = MockIbcStore::default();
+        ibc_store
+            .connections
+            .insert(Default::default(), Default::default());
+        let value = ibc_store.connections[&ConnectionId::default()]
+            .encode_vec()
+            .unwrap();
+
+        let root = app_hash(&ibc_store, &prefix);
+        let proof = prove(&ibc_store, &prefix, path.clone().into()).unwrap();
+        proof
+            .verify_membership(
+                &proof_specs(),
+                root.clone().into(),
+                apply_prefix(&prefix, vec![path.to_string()]),
+                value,
+                0,
+            )
+            .unwrap();
+
+        let absent = ChannelEndsPath(Default::default(), Default::default());
+        let proof = prove(&ibc_store, &prefix, absent.clone().into()).unwrap();
+        proof
+            .verify_non_membership(
+                &proof_specs(),
+                root.into(),
+                apply_prefix(&prefix, vec![absent.to_string()]),
+            )
+            .unwrap();
+    }
+}
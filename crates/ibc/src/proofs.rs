@@ -11,6 +11,9 @@ define_error! {
             | _ | { format_args!("proof height cannot be zero") },
         EmptyProof
             | _ | { format_args!("proof cannot be empty") },
+        ProofTooLarge
+            { size: usize, max_size: usize }
+            | e | { format_args!("proof of {} bytes exceeds the maximum of {} bytes", e.size, e.max_size) },
     }
 }
 
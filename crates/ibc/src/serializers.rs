@@ -36,3 +36,94 @@ pub mod serde_string {
             .map_err(de::Error::custom)
     }
 }
+
+/// (De)serializes a `u64` as proto3 JSON does, i.e. as a decimal string, if the `proto-json`
+/// feature is enabled, and as a number otherwise. Both forms are always accepted when
+/// deserializing.
+pub mod serde_u64 {
+    use core::fmt::{Formatter, Result as FmtResult};
+
+    use serde::de::{Error, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        if cfg!(feature = "proto-json") {
+            serializer.collect_str(value)
+        } else {
+            serializer.serialize_u64(*value)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        deserializer.deserialize_any(U64Visitor)
+    }
+
+    struct U64Visitor;
+
+    impl<'de> Visitor<'de> for U64Visitor {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+            f.write_str("a u64, either as a number or as a decimal string")
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<u64, E> {
+            Ok(value)
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<u64, E> {
+            value.parse().map_err(E::custom)
+        }
+    }
+}
+
+/// (De)serializes bytes as a base64 string, as proto3 JSON does.
+pub mod serde_base64 {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use subtle_encoding::base64;
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let encoded = String::from_utf8(base64::encode(value)).expect("base64 is ASCII");
+        serializer.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        base64::decode(String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Wrapper(#[serde(with = "super::serde_u64")] u64);
+
+    #[test]
+    fn serde_u64_accepts_numbers_and_strings() {
+        let expected = Wrapper(u64::MAX);
+        assert_eq!(
+            serde_json::from_str::<Wrapper>("18446744073709551615").unwrap(),
+            expected
+        );
+        assert_eq!(
+            serde_json::from_str::<Wrapper>(r#""18446744073709551615""#).unwrap(),
+            expected
+        );
+        assert!(serde_json::from_str::<Wrapper>(r#""-1""#).is_err());
+        assert!(serde_json::from_str::<Wrapper>("-1").is_err());
+    }
+
+    #[test]
+    fn serde_u64_serialization() {
+        let json = serde_json::to_string(&Wrapper(42)).unwrap();
+        if cfg!(feature = "proto-json") {
+            assert_eq!(json, r#""42""#);
+        } else {
+            assert_eq!(json, "42");
+        }
+    }
+}
@@ -2,35 +2,133 @@ use core::str::FromStr;
 
 use crate::prelude::*;
 
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
 use derive_more::Display;
 use flex_error::define_error;
 use serde::{Deserialize, Serialize};
 
+/// The maximum length of a signer, in bytes, as for the receivers of ICS20 transfers in ibc-go.
+pub const MAX_SIGNER_LENGTH: usize = 2048;
+
 define_error! {
     #[derive(Debug, PartialEq, Eq)]
     SignerError {
         EmptySigner
             | _ | { "signer cannot be empty" },
+
+        SignerTooLong
+            { length: usize }
+            | e | {
+                format_args!("signer is {} bytes long, more than the maximum of {}",
+                    e.length, MAX_SIGNER_LENGTH)
+            },
     }
 }
 
+/// An account address signing a message, e.g. a relayer, or sending or receiving tokens.
+///
+/// Signers are shared rather than copied when cloned, as they are when passed along to the
+/// callbacks and events of the modules.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Display)]
-pub struct Signer(String);
+#[serde(try_from = "String", into = "String")]
+pub struct Signer(Arc<str>);
+
+impl Signer {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
 
 impl FromStr for Signer {
     type Err = SignerError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.to_string();
         if s.trim().is_empty() {
             return Err(SignerError::empty_signer());
         }
-        Ok(Self(s))
+        if s.len() > MAX_SIGNER_LENGTH {
+            return Err(SignerError::signer_too_long(s.len()));
+        }
+        Ok(Self(s.into()))
+    }
+}
+
+impl TryFrom<String> for Signer {
+    type Error = SignerError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Signer> for String {
+    fn from(signer: Signer) -> Self {
+        signer.0.as_ref().to_string()
     }
 }
 
 impl AsRef<str> for Signer {
     fn as_ref(&self) -> &str {
-        self.0.as_str()
+        self.as_str()
+    }
+}
+
+/// Deduplicates equal signers, e.g. the relayer signing the messages of a transaction, so that
+/// they share a single allocation.
+#[derive(Clone, Debug, Default)]
+pub struct SignerInterner(BTreeSet<Signer>);
+
+impl SignerInterner {
+    /// Replaces `signer` with the equal signer interned before, if any, or interns it otherwise.
+    pub fn intern(&mut self, signer: &mut Signer) {
+        match self.0.get(signer) {
+            Some(interned) => *signer = interned.clone(),
+            None => {
+                self.0.insert(signer.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signer_length() {
+        assert_eq!(
+            Signer::from_str(" ").unwrap_err().detail(),
+            &SignerErrorDetail::EmptySigner(EmptySignerSubdetail {})
+        );
+
+        let longest = "a".repeat(MAX_SIGNER_LENGTH);
+        assert_eq!(Signer::from_str(&longest).unwrap().as_str(), longest);
+
+        let too_long = "a".repeat(MAX_SIGNER_LENGTH + 1);
+        assert!(matches!(
+            Signer::from_str(&too_long).unwrap_err().detail(),
+            SignerErrorDetail::SignerTooLong(e) if e.length == MAX_SIGNER_LENGTH + 1
+        ));
+        assert!(serde_json::from_str::<Signer>(&format!("\"{}\"", too_long)).is_err());
+    }
+
+    #[test]
+    fn test_signer_interning() {
+        let mut interner = SignerInterner::default();
+
+        let relayer: Signer = "cosmos1relayer".parse().unwrap();
+        let mut first = relayer.clone();
+        interner.intern(&mut first);
+        assert!(Arc::ptr_eq(&first.0, &relayer.0));
+
+        let mut second: Signer = "cosmos1relayer".parse().unwrap();
+        assert!(!Arc::ptr_eq(&second.0, &relayer.0));
+        interner.intern(&mut second);
+        assert!(Arc::ptr_eq(&second.0, &relayer.0));
+
+        let mut other: Signer = "cosmos1other".parse().unwrap();
+        interner.intern(&mut other);
+        assert_eq!(other.as_str(), "cosmos1other");
     }
 }
@@ -1,3 +1,9 @@
+//! Fixtures shared by the tests of this crate, also available to hosts with the `mocks` feature.
+//!
+//! Dummy messages and domain types live next to their types, in `test_util` modules, e.g.
+//! [`get_dummy_raw_msg_recv_packet`](crate::core::ics04_channel::msgs::recv_packet::test_util::get_dummy_raw_msg_recv_packet).
+
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -5,9 +11,14 @@ use subtle_encoding::bech32;
 use tendermint::{block, consensus, evidence, public_key::Algorithm};
 
 use crate::applications::transfer::context::{
-    cosmos_adr028_escrow_address, BankKeeper, Ics20Context, Ics20Keeper, Ics20Reader,
+    BankKeeper, DenomTraceKeeper, DenomTraceReader, EscrowKeeper, EscrowReader, Ics20Context,
+    Ics20Keeper, Ics20Reader,
+};
+use crate::applications::transfer::{
+    error::Error as Ics20Error, AddressCodec, Amount, PrefixedCoin, PrefixedDenom,
+    VoucherDenomFormat,
 };
-use crate::applications::transfer::{error::Error as Ics20Error, PrefixedCoin};
+use crate::bigint::U256;
 use crate::core::ics02_client::client_state::ClientState;
 use crate::core::ics02_client::consensus_state::ConsensusState;
 use crate::core::ics02_client::error::Error as Ics02Error;
@@ -18,7 +29,7 @@ use crate::core::ics04_channel::commitment::{AcknowledgementCommitment, PacketCo
 use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
 use crate::core::ics04_channel::error::Error;
 use crate::core::ics04_channel::handler::ModuleExtras;
-use crate::core::ics04_channel::packet::{Receipt, Sequence};
+use crate::core::ics04_channel::packet::{Packet, Receipt, Sequence};
 use crate::core::ics04_channel::Version;
 use crate::core::ics05_port::context::PortReader;
 use crate::core::ics05_port::error::Error as PortError;
@@ -40,7 +51,7 @@ pub fn default_consensus_params() -> consensus::Params {
         },
         evidence: evidence::Params {
             max_age_num_blocks: 100000,
-            max_age_duration: evidence::Duration(core::time::Duration::new(48 * 3600, 0)),
+            max_age_duration: evidence::Duration(Duration::new(48 * 3600, 0)),
             max_bytes: 0,
         },
         validator: consensus::params::ValidatorParams {
@@ -66,16 +77,90 @@ pub fn get_dummy_bech32_account() -> String {
 
 pub fn get_dummy_transfer_module() -> DummyTransferModule {
     let ibc_store = Arc::new(Mutex::new(MockIbcStore::default()));
-    DummyTransferModule { ibc_store }
+    DummyTransferModule::new(ibc_store)
+}
+/// Small deterministic pseudo-random generator (xorshift64) for fuzz-style round-trip tests.
+#[derive(Clone, Debug)]
+pub struct Xorshift(u64);
+
+impl Xorshift {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a random string of `min..=max` characters that are valid in ICS24 identifiers.
+    pub fn identifier(&mut self, min: usize, max: usize) -> String {
+        const CHARSET: &[u8] =
+            b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789._+-#[]<>";
+
+        let len = min + (self.next_u64() as usize) % (max - min + 1);
+        (0..len)
+            .map(|_| CHARSET[(self.next_u64() as usize) % CHARSET.len()] as char)
+            .collect()
+    }
 }
+
 #[derive(Debug)]
 pub struct DummyTransferModule {
     ibc_store: Arc<Mutex<MockIbcStore>>,
+    voucher_denom_format: VoucherDenomFormat,
+    /// The net amounts moved in or out of accounts by the bank, per voucher denomination. Accounts
+    /// may go negative, for transfers not to require funding senders first.
+    balances: BTreeMap<(Signer, String), i128>,
+    /// The denominations of the vouchers minted, by hash.
+    denom_traces: BTreeMap<String, PrefixedDenom>,
+    /// The total amounts escrowed, per denomination.
+    total_escrow: BTreeMap<PrefixedDenom, Amount>,
+    address_codec: Option<AddressCodec>,
 }
 
 impl DummyTransferModule {
     pub fn new(ibc_store: Arc<Mutex<MockIbcStore>>) -> Self {
-        Self { ibc_store }
+        Self {
+            ibc_store,
+            voucher_denom_format: VoucherDenomFormat::default(),
+            balances: BTreeMap::new(),
+            denom_traces: BTreeMap::new(),
+            total_escrow: BTreeMap::new(),
+            address_codec: None,
+        }
+    }
+
+    /// Checks the senders and receivers of transfers against `address_codec`, in which escrow
+    /// addresses are then encoded, rather than bech32 with the `cosmos` prefix.
+    pub fn with_address_codec(self, address_codec: AddressCodec) -> Self {
+        Self {
+            address_codec: Some(address_codec),
+            ..self
+        }
+    }
+
+    pub fn with_voucher_denom_format(self, voucher_denom_format: VoucherDenomFormat) -> Self {
+        Self {
+            voucher_denom_format,
+            ..self
+        }
+    }
+
+    /// Returns the net amount of `voucher_denom` moved in or out of `account`.
+    pub fn balance(&self, account: &Signer, voucher_denom: &str) -> i128 {
+        self.balances
+            .get(&(account.clone(), voucher_denom.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn credit(&mut self, account: &Signer, coin: &PrefixedCoin, sign: i128) {
+        let amount: U256 = coin.amount.into();
+        let key = (account.clone(), self.voucher_denom(&coin.denom));
+        *self.balances.entry(key).or_default() += sign * amount.as_u128() as i128;
     }
 }
 
@@ -115,6 +200,40 @@ impl Module for DummyTransferModule {
             counterparty_version.clone(),
         ))
     }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn on_chan_upgrade_init(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _order: Order,
+        _connection_hops: &[ConnectionId],
+        version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        Ok((ModuleExtras::empty(), version.clone()))
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn on_chan_upgrade_try(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _order: Order,
+        _connection_hops: &[ConnectionId],
+        counterparty_version: &Version,
+    ) -> Result<(ModuleExtras, Version), Error> {
+        Ok((ModuleExtras::empty(), counterparty_version.clone()))
+    }
+
+    #[cfg(any(test, feature = "unstable"))]
+    fn on_chan_upgrade_ack(
+        &mut self,
+        _port_id: &PortId,
+        _channel_id: &ChannelId,
+        _counterparty_version: &Version,
+    ) -> Result<ModuleExtras, Error> {
+        Ok(ModuleExtras::empty())
+    }
 }
 
 impl Ics20Keeper for DummyTransferModule {
@@ -179,6 +298,19 @@ impl ChannelKeeper for DummyTransferModule {
         unimplemented!()
     }
 
+    fn queue_send_packet(&mut self, packet: Packet) -> Result<(), Error> {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .queued_send_packets
+            .push(packet);
+        Ok(())
+    }
+
+    fn take_queued_send_packets(&mut self) -> Vec<Packet> {
+        core::mem::take(&mut self.ibc_store.lock().unwrap().queued_send_packets)
+    }
+
     fn store_connection_channels(
         &mut self,
         _conn_id: ConnectionId,
@@ -240,6 +372,10 @@ impl PortReader for DummyTransferModule {
     fn lookup_module_by_port(&self, _port_id: &PortId) -> Result<ModuleId, PortError> {
         unimplemented!()
     }
+
+    fn port_bindings(&self) -> Vec<(PortId, ModuleId)> {
+        unimplemented!()
+    }
 }
 
 impl BankKeeper for DummyTransferModule {
@@ -247,26 +383,30 @@ impl BankKeeper for DummyTransferModule {
 
     fn send_coins(
         &mut self,
-        _from: &Self::AccountId,
-        _to: &Self::AccountId,
-        _amt: &PrefixedCoin,
+        from: &Self::AccountId,
+        to: &Self::AccountId,
+        amt: &PrefixedCoin,
     ) -> Result<(), Ics20Error> {
+        self.credit(from, amt, -1);
+        self.credit(to, amt, 1);
         Ok(())
     }
 
     fn mint_coins(
         &mut self,
-        _account: &Self::AccountId,
-        _amt: &PrefixedCoin,
+        account: &Self::AccountId,
+        amt: &PrefixedCoin,
     ) -> Result<(), Ics20Error> {
+        self.credit(account, amt, 1);
         Ok(())
     }
 
     fn burn_coins(
         &mut self,
-        _account: &Self::AccountId,
-        _amt: &PrefixedCoin,
+        account: &Self::AccountId,
+        amt: &PrefixedCoin,
     ) -> Result<(), Ics20Error> {
+        self.credit(account, amt, -1);
         Ok(())
     }
 }
@@ -283,8 +423,16 @@ impl Ics20Reader for DummyTransferModule {
         port_id: &PortId,
         channel_id: &ChannelId,
     ) -> Result<<Self as Ics20Reader>::AccountId, Ics20Error> {
-        let addr = cosmos_adr028_escrow_address(port_id, channel_id);
-        Ok(bech32::encode("cosmos", addr).parse().unwrap())
+        let addr = self.escrow_address_bytes(port_id, channel_id);
+        let address = match &self.address_codec {
+            Some(codec) => codec.encode(&addr),
+            None => bech32::encode("cosmos", addr),
+        };
+        Ok(address.parse().unwrap())
+    }
+
+    fn address_codec(&self) -> Option<AddressCodec> {
+        self.address_codec.clone()
     }
 
     fn is_send_enabled(&self) -> bool {
@@ -294,6 +442,47 @@ impl Ics20Reader for DummyTransferModule {
     fn is_receive_enabled(&self) -> bool {
         true
     }
+
+    fn voucher_denom_format(&self) -> VoucherDenomFormat {
+        self.voucher_denom_format
+    }
+}
+
+impl DenomTraceReader for DummyTransferModule {
+    fn denom_trace(&self, hash: &str) -> Option<PrefixedDenom> {
+        self.denom_traces.get(hash).cloned()
+    }
+
+    fn all_denom_traces(&self) -> Vec<PrefixedDenom> {
+        self.denom_traces.values().cloned().collect()
+    }
+}
+
+impl DenomTraceKeeper for DummyTransferModule {
+    fn store_denom_trace(&mut self, hash: String, denom: PrefixedDenom) -> Result<(), Ics20Error> {
+        self.denom_traces.insert(hash, denom);
+        Ok(())
+    }
+}
+
+impl EscrowReader for DummyTransferModule {
+    fn get_total_escrow(&self, denom: &PrefixedDenom) -> Amount {
+        self.total_escrow
+            .get(denom)
+            .copied()
+            .unwrap_or_else(|| 0.into())
+    }
+}
+
+impl EscrowKeeper for DummyTransferModule {
+    fn store_total_escrow(
+        &mut self,
+        denom: PrefixedDenom,
+        amount: Amount,
+    ) -> Result<(), Ics20Error> {
+        self.total_escrow.insert(denom, amount);
+        Ok(())
+    }
 }
 
 impl ChannelReader for DummyTransferModule {
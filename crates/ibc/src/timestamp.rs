@@ -21,14 +21,33 @@ pub const ZERO_DURATION: Duration = Duration::from_secs(0);
 /// a `u64` value and a raw timestamp. In protocol buffer, the timestamp is
 /// represented as a `u64` Unix timestamp in nanoseconds, with 0 representing the absence
 /// of timestamp.
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Deserialize, Serialize)]
+///
+/// With the `proto-json` feature, a timestamp is (de)serialized as its protocol value, i.e. the
+/// decimal string of [`Timestamp::nanoseconds`].
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+#[cfg_attr(not(feature = "proto-json"), derive(Deserialize, Serialize))]
 pub struct Timestamp {
     time: Option<Time>,
 }
 
+#[cfg(feature = "proto-json")]
+impl serde::Serialize for Timestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serializers::serde_u64::serialize(&self.nanoseconds(), serializer)
+    }
+}
+
+#[cfg(feature = "proto-json")]
+impl<'de> serde::Deserialize<'de> for Timestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let nanoseconds = crate::serializers::serde_u64::deserialize(deserializer)?;
+        Timestamp::from_nanoseconds(nanoseconds).map_err(serde::de::Error::custom)
+    }
+}
+
 // TODO: derive when tendermint::Time supports it:
 // https://github.com/informalsystems/tendermint-rs/pull/1054
-#[allow(clippy::derive_hash_xor_eq)]
+#[allow(clippy::derived_hash_with_manual_eq)]
 impl Hash for Timestamp {
     fn hash<H: Hasher>(&self, state: &mut H) {
         let odt: Option<OffsetDateTime> = self.time.map(Into::into);
@@ -1,4 +1,3 @@
-use crate::core::ics24_host::error::ValidationError;
 use crate::prelude::*;
 use ibc_proto::google::protobuf::Any;
 
@@ -33,7 +32,10 @@ pub trait Msg: Clone {
         }
     }
 
-    fn validate_basic(&self) -> Result<(), ValidationError> {
+    /// Performs the stateless checks on this message, i.e. the ones that do not require access
+    /// to the host's store, so that they can be carried out (e.g. by a mempool) before the
+    /// message reaches `deliver`. The raw-to-domain conversions delegate to this method.
+    fn validate_basic(&self) -> Result<(), Self::ValidationError> {
         Ok(())
     }
 }